@@ -0,0 +1,30 @@
+//! Records build details for `build_info`: the enabled cargo features and, when built
+//! from a git checkout, the commit hash.
+
+use std::env;
+use std::process::Command;
+
+fn main() {
+    // Cargo sets CARGO_FEATURE_<NAME> for each enabled feature, upper-cased with
+    // dashes turned into underscores
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(|name| name.to_lowercase().replace('_', "-")))
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=SOLVER_FEATURES={}", features.join(","));
+
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty());
+    if let Some(git_hash) = git_hash {
+        println!("cargo:rustc-env=SOLVER_GIT_HASH={}", git_hash);
+    }
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-changed=build.rs");
+}
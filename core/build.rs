@@ -0,0 +1,12 @@
+//! Set `alloc_only` when the `std` feature is off.
+//!
+//! The modules shared with the main crate gate their std-only pieces on `alloc_only`
+//! rather than on a `std` feature, as the main crate's `std` feature only turns on
+//! rand's OS entropy and the main crate always has std.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if std::env::var_os("CARGO_FEATURE_STD").is_none() {
+        println!("cargo:rustc-cfg=alloc_only");
+    }
+}
@@ -0,0 +1,52 @@
+//! Injectable time source for searches with a deadline.
+//!
+//! Targets without `std` have no common clock, so searches read the time through the
+//! `Clock` trait instead, e.g. from a hardware timer.
+
+/// Source of the current time.
+pub trait Clock {
+    /// Read the clock.
+    ///
+    /// # Returns
+    /// Milliseconds since an arbitrary fixed point
+    fn now_ms(&self) -> f64;
+}
+
+/// Any function returning milliseconds is a clock, e.g. a closure reading a timer.
+impl<F: Fn() -> f64> Clock for F {
+    fn now_ms(&self) -> f64 {
+        self()
+    }
+}
+
+/// Monotonic clock of the operating system.
+///
+/// # Attributes
+/// * `epoch` - Instant the clock reads 0 at
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct StdClock {
+    epoch: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    /// Create a clock reading 0 now.
+    pub fn new() -> StdClock {
+        StdClock { epoch: std::time::Instant::now() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> StdClock {
+        StdClock::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now_ms(&self) -> f64 {
+        self.epoch.elapsed().as_secs_f64() * 1000.0
+    }
+}
@@ -0,0 +1,155 @@
+//! IDA* for the `no_std` core.
+//!
+//! Makes and unmakes moves on a single board, so memory is linear in the solution
+//! length and nothing but the path is allocated per board. There is no transposition
+//! table, observer or statistics; the main crate's `solver` has the full search.
+
+use alloc::string::String;
+use core::error::Error;
+use core::fmt;
+
+use super::board::{opposite_direction, Board, Heuristic, MOVES};
+use super::clock::Clock;
+use super::conflict_cache::ConflictCache;
+use super::error_code::ErrorCode;
+
+/// Number of expansions between readings of the clock
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// Reasons IDA* can't solve a board.
+///
+/// # Error codes
+/// * 201 - `Unreachable`, as the main crate's `SolveError::Unreachable`
+/// * 203 - `TimedOut`, as the main crate's `SolveError::BudgetExceeded`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdaError {
+    /// The board can't reach the goal
+    Unreachable,
+    /// The deadline passed before the goal was found
+    TimedOut,
+}
+
+impl fmt::Display for IdaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdaError::Unreachable => write!(f, "goal is unreachable from this board"),
+            IdaError::TimedOut => write!(f, "gave up at the deadline"),
+        }
+    }
+}
+
+impl Error for IdaError {}
+
+impl ErrorCode for IdaError {
+    fn code(&self) -> u32 {
+        match self {
+            IdaError::Unreachable => 201,
+            IdaError::TimedOut => 203,
+        }
+    }
+}
+
+/// State of one IDA* search.
+///
+/// # Attributes
+/// * `goal` - Solved board
+/// * `heuristic` - Heuristic bounding each iteration
+/// * `conflict_cache` - Cached linear conflicts of rows and columns
+/// * `clock` - Clock the deadline is read from
+/// * `deadline_ms` - Reading of the clock to give up at, if any
+/// * `expanded` - Boards expanded so far
+/// * `path` - Moves of the blank from the root to the board being searched
+struct IdaSearch<'a, C: Clock> {
+    goal: Board,
+    heuristic: Heuristic,
+    conflict_cache: ConflictCache,
+    clock: &'a C,
+    deadline_ms: Option<f64>,
+    expanded: u64,
+    path: String,
+}
+
+/// Solve a board optimally with IDA*.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `heuristic` - Heuristic bounding each iteration
+/// * `clock` - Clock the deadline is read from
+/// * `max_ms` - Give up after this many milliseconds, if set
+///
+/// # Returns
+/// Moves of the blank in an optimal solution, or why the board can't be solved
+pub fn solve<C: Clock>(board: &Board, heuristic: Heuristic, clock: &C,
+                       max_ms: Option<f64>) -> Result<String, IdaError> {
+    // Without a closed set, an unsolvable board would deepen forever
+    if !board.is_solvable() {
+        return Err(IdaError::Unreachable);
+    }
+    let goal = Board::new(board.size(), None, -1, None);
+    let mut search = IdaSearch {
+        conflict_cache: ConflictCache::new(&goal),
+        goal,
+        heuristic,
+        clock,
+        deadline_ms: max_ms.map(|max_ms| clock.now_ms() + max_ms),
+        expanded: 0,
+        path: String::new(),
+    };
+    let mut root = board.clone();
+    let mut bound = search.h(&mut root);
+    loop {
+        match search.dfs(&mut root, 0, bound, '\0')? {
+            None => return Ok(search.path),
+            Some(next_bound) => bound = next_bound,
+        }
+    }
+}
+
+impl<'a, C: Clock> IdaSearch<'a, C> {
+    /// Estimate the distance of a board from the goal.
+    fn h(&mut self, board: &mut Board) -> i32 {
+        board.store_h_cached(&self.goal, self.heuristic, &mut self.conflict_cache)
+    }
+
+    /// Search below a board up to a cost bound, moving it in place and restoring it.
+    ///
+    /// # Parameters
+    /// * `board` - Board reached by `path`
+    /// * `g` - Number of moves from the root
+    /// * `bound` - Cost bound of this iteration
+    /// * `last_move` - Move that reached the board, never undone
+    ///
+    /// # Returns
+    /// `None` once the goal is found, with `path` leading to it, otherwise the smallest
+    /// cost above the bound
+    fn dfs(&mut self, board: &mut Board, g: i32, bound: i32, last_move: char) -> Result<Option<i32>, IdaError> {
+        let f = g + self.h(board);
+        if f > bound {
+            return Ok(Some(f));
+        }
+        if board.is_goal_for(&self.goal) {
+            return Ok(None);
+        }
+        self.expanded += 1;
+        if let Some(deadline_ms) = self.deadline_ms {
+            if self.expanded.is_multiple_of(CLOCK_INTERVAL) && self.clock.now_ms() >= deadline_ms {
+                return Err(IdaError::TimedOut);
+            }
+        }
+        let mut next_bound = i32::MAX;
+        for &tile_move in MOVES.iter() {
+            if !board.is_legal_move(tile_move) || opposite_direction(tile_move) == last_move {
+                continue;
+            }
+            board.move_blank_tile(tile_move);
+            self.path.push(tile_move);
+            match self.dfs(board, g + 1, bound, tile_move)? {
+                None => return Ok(None),
+                Some(child_bound) => next_bound = next_bound.min(child_bound),
+            }
+            self.path.pop();
+            board.move_blank_tile(opposite_direction(tile_move));
+        }
+        Ok(Some(next_bound))
+    }
+}
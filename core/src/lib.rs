@@ -0,0 +1,35 @@
+//! Core of the sliding tile puzzle solver for `no_std` targets with an allocator, e.g.
+//! a Cortex-M badge.
+//!
+//! Tiles, boards, their heuristics and packing are the main crate's own modules, built
+//! here without the wasm bindings and the rest of the solver. Without the default `std`
+//! feature the crate is `#![no_std]` + `alloc`:
+//! * Randomness only comes from caller-supplied generators, e.g.
+//!   `Board::shuffle_with_rng` with `rng::RngSource`; clock-seeded shuffles are left out.
+//! * Timing comes through the `Clock` trait, see `clock`.
+//! * Boards are solved with the allocation-light IDA* in `ida`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The shared modules' unit tests use the rest of the solver, so they run in the main crate
+#![cfg(not(test))]
+
+extern crate alloc;
+
+#[path = "../../src/tile_solver_rust/board.rs"]
+pub mod board;
+pub mod clock;
+#[path = "../../src/tile_solver_rust/conflict_cache.rs"]
+pub mod conflict_cache;
+#[path = "../../src/tile_solver_rust/error_code.rs"]
+pub mod error_code;
+pub mod ida;
+#[path = "../../src/tile_solver_rust/pack.rs"]
+pub mod pack;
+#[path = "../../src/tile_solver_rust/rng.rs"]
+pub mod rng;
+#[path = "../../src/tile_solver_rust/tile.rs"]
+pub mod tile;
+#[path = "../../src/tile_solver_rust/zobrist.rs"]
+pub mod zobrist;
+
+pub use clock::Clock;
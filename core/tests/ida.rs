@@ -0,0 +1,75 @@
+use std::cell::Cell;
+use std::convert::TryFrom;
+
+use sliding_tile_puzzle_core::board::{Board, Heuristic};
+use sliding_tile_puzzle_core::error_code::ErrorCode;
+use sliding_tile_puzzle_core::ida::{self, IdaError};
+use sliding_tile_puzzle_core::rng::RngSource;
+
+/// Apply moves of the blank to a board and check that they reach the goal.
+fn _solves(board: &Board, moves: &str) -> bool {
+    let goal = Board::new(board.size(), None, -1, None);
+    let mut board = board.clone();
+    for tile_move in moves.chars() {
+        if !board.is_legal_move(tile_move) {
+            return false;
+        }
+        board.move_blank_tile(tile_move);
+    }
+    board.is_goal_for(&goal)
+}
+
+/// A clock that never reaches a deadline.
+fn _stopped_clock() -> f64 {
+    0.0
+}
+
+/// Test IDA* solves boards optimally
+#[test]
+fn test_solve() {
+    let board = Board::try_from(vec![1, 2, 3, -1, 4, 6, 7, 5, 8]).unwrap();
+    assert_eq!(ida::solve(&board, Heuristic::LinearConflicts, &_stopped_clock, None), Ok("RDR".to_string()));
+
+    // One of the two hardest 3x3 boards, 31 moves from the goal
+    let board = Board::try_from(vec![8, 6, 7, 2, 5, 4, 3, -1, 1]).unwrap();
+    for &heuristic in [Heuristic::Manhattan, Heuristic::LinearConflicts].iter() {
+        let moves = ida::solve(&board, heuristic, &_stopped_clock, None).unwrap();
+        assert_eq!(moves.len(), 31);
+        assert!(_solves(&board, &moves));
+    }
+
+    let goal = Board::new(3, None, -1, None);
+    assert_eq!(ida::solve(&goal, Heuristic::LinearConflicts, &_stopped_clock, None), Ok(String::new()));
+}
+
+/// Test IDA* solutions of shuffled boards are no longer than the shuffles
+#[test]
+fn test_solve_shuffled() {
+    let mut rng = RngSource::seeded(372);
+    for _ in 0..10 {
+        let mut board = Board::new(4, None, -1, None);
+        let shuffle = board.shuffle_with_rng(20, &mut rng);
+        let moves = ida::solve(&board, Heuristic::LinearConflicts, &_stopped_clock, None).unwrap();
+        assert!(moves.len() <= shuffle.len());
+        assert!(_solves(&board, &moves));
+    }
+}
+
+/// Test IDA* gives up on unsolvable boards and at the deadline
+#[test]
+fn test_solve_errors() {
+    let board = Board::try_from(vec![2, 1, 3, 4, 5, 6, 7, 8, -1]).unwrap();
+    let error = ida::solve(&board, Heuristic::LinearConflicts, &_stopped_clock, None).unwrap_err();
+    assert_eq!((error, error.code()), (IdaError::Unreachable, 201));
+
+    // Every reading of the clock is a second later than the one before
+    let now_ms = Cell::new(0.0);
+    let clock = || {
+        now_ms.set(now_ms.get() + 1000.0);
+        now_ms.get()
+    };
+    let board = Board::try_from(vec![8, 6, 7, 2, 5, 4, 3, -1, 1]).unwrap();
+    let error = ida::solve(&board, Heuristic::Manhattan, &clock, Some(1500.0)).unwrap_err();
+    assert_eq!((error, error.code()), (IdaError::TimedOut, 203));
+    assert_eq!(now_ms.get(), 3000.0);
+}
@@ -0,0 +1,17 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Test the crate builds without std and IDA* still solves boards there
+#[test]
+fn test_no_std() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // A target directory of its own, so the build doesn't wait on the lock of the running one
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("no_std");
+    let output = Command::new(env!("CARGO"))
+        .current_dir(manifest_dir.parent().unwrap())
+        .args(["test", "-p", "sliding_tile_puzzle_core", "--no-default-features", "--test", "ida", "--target-dir"])
+        .arg(&target_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,33 @@
+/* Solve a board through the C interface. */
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "sliding_tile_puzzle_solver.h"
+
+int main(void) {
+    const int32_t tiles[] = {1, 2, 3, -1, 4, 6, 7, 5, 8};
+    const size_t len = sizeof(tiles) / sizeof(tiles[0]);
+    uint8_t *moves;
+    int32_t count;
+
+    if (sps_is_solvable(3, tiles, len) != 1) {
+        fprintf(stderr, "unsolvable board\n");
+        return 1;
+    }
+    printf("estimate: %d\n", (int)sps_estimate(3, tiles, len));
+    /* Ask for the required capacity first */
+    count = sps_solve(3, tiles, len, NULL, 0);
+    if (count < 0) {
+        fprintf(stderr, "error: %s\n", sps_last_error_message());
+        return 1;
+    }
+    moves = malloc((size_t)count);
+    if (moves == NULL) {
+        return 1;
+    }
+    count = sps_solve(3, tiles, len, moves, (size_t)count);
+    printf("%d moves: %.*s\n", (int)count, (int)count, (const char *)moves);
+    free(moves);
+    return 0;
+}
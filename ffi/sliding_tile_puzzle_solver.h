@@ -0,0 +1,74 @@
+/*
+ * C interface to the sliding tile puzzle solver.
+ *
+ * Build the crate with the `ffi` feature and link against its cdylib.
+ * Boards are passed as n and n * n tile symbols in row-major order, with -1 for the
+ * blank. Failures return a negative error code; sps_last_error_message describes the
+ * most recent one on the calling thread.
+ *
+ * Keep in sync with src/tile_solver_rust/ffi.rs.
+ */
+
+#ifndef SLIDING_TILE_PUZZLE_SOLVER_H
+#define SLIDING_TILE_PUZZLE_SOLVER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The board is invalid */
+#define SPS_ERR_INVALID_BOARD (-1)
+/* The goal is unreachable from the board */
+#define SPS_ERR_UNREACHABLE (-2)
+/* A required pointer was null */
+#define SPS_ERR_NULL_POINTER (-3)
+/* The solver panicked */
+#define SPS_ERR_PANIC (-4)
+/* The search expanded max_nodes boards without finding the goal */
+#define SPS_ERR_NODE_BUDGET (-5)
+/* The search ran for max_time_ms milliseconds without finding the goal */
+#define SPS_ERR_TIME_BUDGET (-6)
+/* The search would have grown past max_memory_bytes */
+#define SPS_ERR_MEMORY_BUDGET (-7)
+/* The board is too large for the solver, or the solver failed a check */
+#define SPS_ERR_SOLVER (-8)
+
+/*
+ * Solve a board, writing the moves as ASCII 'U', 'D', 'L' and 'R' characters (not
+ * NUL-terminated). Returns the number of moves, or a negative error code. If the
+ * result is greater than out_cap, nothing was written and the result is the capacity
+ * needed. out_moves may be null when out_cap is 0.
+ */
+int32_t sps_solve(int32_t n, const int32_t *tiles, size_t len, uint8_t *out_moves, size_t out_cap);
+
+/*
+ * Solve a board like sps_solve, giving up after max_nodes expanded boards, max_time_ms
+ * milliseconds or about max_memory_bytes of search structures. A limit of 0 is off,
+ * or the default budget for memory.
+ */
+int32_t sps_solve_with_limits(int32_t n, const int32_t *tiles, size_t len, uint64_t max_nodes,
+                              uint64_t max_time_ms, uint64_t max_memory_bytes, uint8_t *out_moves, size_t out_cap);
+
+/* Returns 1 if the board is solvable, 0 if not, or a negative error code. */
+int32_t sps_is_solvable(int32_t n, const int32_t *tiles, size_t len);
+
+/*
+ * Returns a lower bound on the number of moves needed (Manhattan distance plus linear
+ * conflicts), or a negative error code.
+ */
+int32_t sps_estimate(int32_t n, const int32_t *tiles, size_t len);
+
+/*
+ * Returns the NUL-terminated message of the last failed call on this thread, valid
+ * until the next failing call, or null if no call has failed.
+ */
+const char *sps_last_error_message(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SLIDING_TILE_PUZZLE_SOLVER_H */
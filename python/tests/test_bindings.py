@@ -0,0 +1,114 @@
+"""Tests of the `python` feature's extension module, see ../pyproject.toml."""
+
+import threading
+import time
+
+import pytest
+
+import sliding_tile_puzzle_solver as sps
+
+# Three moves of the blank from the goal: right, down, right
+BOARD = [1, 2, 3, -1, 4, 6, 7, 5, 8]
+# One of the two hardest 3x3 boards, 31 moves from the goal
+HARDEST = [8, 6, 7, 2, 5, 4, 3, -1, 1]
+
+
+def test_solve():
+    solution = sps.solve(BOARD, 3)
+    assert solution["moves"] == "RDR"
+    assert solution["length"] == 3
+    assert solution["optimal"] is True
+    assert solution["stats"]["nodes_expanded"] > 0
+    assert set(solution["stats"]) == {
+        "nodes_expanded", "nodes_generated", "max_heuristic", "effective_branching_factor", "millis",
+    }
+    assert sps.solve(board=tuple(BOARD), n=3)["moves"] == "RDR"
+
+
+def test_solve_options():
+    for algorithm in ("astar", "ida_star"):
+        solution = sps.solve(HARDEST, 3, algorithm=algorithm, heuristic="manhattan")
+        assert solution["length"] == 31
+        assert sps.verify(HARDEST, 3, solution["moves"])
+    greedy = sps.solve(HARDEST, 3, algorithm="greedy")
+    assert greedy["optimal"] is False
+    assert sps.verify(HARDEST, 3, greedy["moves"])
+    assert sps.solve(BOARD, 3, canonical=True, max_nodes=1000)["moves"] == "RDR"
+
+
+def test_solve_errors():
+    with pytest.raises(sps.SolveError) as error:
+        sps.solve([2, 1, 3, -1], 2)
+    assert error.value.code == 201
+    assert str(error.value) == "goal is unreachable from this board"
+    with pytest.raises(sps.SolveError) as error:
+        sps.solve(HARDEST, 3, max_nodes=1)
+    assert error.value.code == 203
+    with pytest.raises(sps.SolveError) as error:
+        sps.solve(HARDEST, 3, algorithm="layered", canonical=True)
+    assert error.value.code == 202
+    with pytest.raises(ValueError, match="expected 9 tiles, found 8"):
+        sps.solve(BOARD[:8], 3)
+    with pytest.raises(ValueError, match="unknown algorithm"):
+        sps.solve(BOARD, 3, algorithm="dijkstra")
+    with pytest.raises(TypeError, match="unknown option"):
+        sps.solve(BOARD, 3, colour="red")
+    with pytest.raises(TypeError, match="missing argument"):
+        sps.solve(BOARD)
+    with pytest.raises(TypeError):
+        sps.solve(["a"] * 9, 3)
+
+
+def test_solve_releases_gil():
+    # 53 moves from the goal, about half a second to solve
+    board = sps.generate(4, 3, "expert")
+    ticks = []
+    done = threading.Event()
+
+    def solve():
+        sps.solve(board, 4)
+        done.set()
+
+    thread = threading.Thread(target=solve)
+    thread.start()
+    # Holding the GIL would stop this loop for the whole search
+    while not done.is_set():
+        ticks.append(time.monotonic())
+        time.sleep(0.001)
+    thread.join()
+    assert len(ticks) > 10
+
+
+def test_solve_threads():
+    boards = [sps.generate(3, seed, "hard") for seed in range(4)]
+    results = [None] * len(boards)
+
+    def solve(i):
+        results[i] = sps.solve(boards[i], 3)["moves"]
+
+    threads = [threading.Thread(target=solve, args=(i,)) for i in range(len(boards))]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    assert results == [sps.solve(board, 3)["moves"] for board in boards]
+
+
+def test_generate():
+    board = sps.generate(3, 348, "medium")
+    assert sorted(board) == [-1, 1, 2, 3, 4, 5, 6, 7, 8]
+    assert board == sps.generate(n=3, seed=348, difficulty="medium")
+    assert 11 <= sps.solve(board, 3)["length"] <= 18
+    assert len(sps.generate(4, 348, "easy")) == 16
+    with pytest.raises(ValueError, match="unknown difficulty"):
+        sps.generate(3, 348, "impossible")
+    with pytest.raises(ValueError):
+        sps.generate(7, 348, "easy")
+
+
+def test_verify():
+    assert sps.verify(BOARD, 3, "RDR")
+    assert not sps.verify(BOARD, 3, "RD")
+    assert not sps.verify(BOARD, 3, "LLL")
+    with pytest.raises(TypeError, match="unexpected argument"):
+        sps.verify(BOARD, 3, "RDR", notation="tiles")
@@ -0,0 +1,441 @@
+//! Benchmarks for comparing solver configurations.
+//!
+//! Korf's 100 standard 15-puzzle instances are from R. E. Korf, "Depth-first
+//! iterative-deepening: An optimal admissible tree search", Artificial Intelligence 27
+//! (1985). Instances are listed in Korf's notation, where 0 is the blank and the goal
+//! has the blank in the top-left corner.
+
+use std::time::Duration;
+
+use super::board::{Board, Heuristic, HEURISTICS};
+use super::clock;
+use super::solver::{self, SolveError, SolveOptions};
+use super::tile::{Tile, BLANK_TILE};
+
+/// Korf's instances in his notation, with their published optimal solution lengths
+const KORF_INSTANCES: [([u8; 16], usize); 100] = [
+    ([14, 13, 15, 7, 11, 12, 9, 5, 6, 0, 2, 1, 4, 8, 10, 3], 57),
+    ([13, 5, 4, 10, 9, 12, 8, 14, 2, 3, 7, 1, 0, 15, 11, 6], 55),
+    ([14, 7, 8, 2, 13, 11, 10, 4, 9, 12, 5, 0, 3, 6, 1, 15], 59),
+    ([5, 12, 10, 7, 15, 11, 14, 0, 8, 2, 1, 13, 3, 4, 9, 6], 56),
+    ([4, 7, 14, 13, 10, 3, 9, 12, 11, 5, 6, 15, 1, 2, 8, 0], 56),
+    ([14, 7, 1, 9, 12, 3, 6, 15, 8, 11, 2, 5, 10, 0, 4, 13], 52),
+    ([2, 11, 15, 5, 13, 4, 6, 7, 12, 8, 10, 1, 9, 3, 14, 0], 52),
+    ([12, 11, 15, 3, 8, 0, 4, 2, 6, 13, 9, 5, 14, 1, 10, 7], 50),
+    ([3, 14, 9, 11, 5, 4, 8, 2, 13, 12, 6, 7, 10, 1, 15, 0], 46),
+    ([13, 11, 8, 9, 0, 15, 7, 10, 4, 3, 6, 14, 5, 12, 2, 1], 59),
+    ([5, 9, 13, 14, 6, 3, 7, 12, 10, 8, 4, 0, 15, 2, 11, 1], 57),
+    ([14, 1, 9, 6, 4, 8, 12, 5, 7, 2, 3, 0, 10, 11, 13, 15], 45),
+    ([3, 6, 5, 2, 10, 0, 15, 14, 1, 4, 13, 12, 9, 8, 11, 7], 46),
+    ([7, 6, 8, 1, 11, 5, 14, 10, 3, 4, 9, 13, 15, 2, 0, 12], 59),
+    ([13, 11, 4, 12, 1, 8, 9, 15, 6, 5, 14, 2, 7, 3, 10, 0], 62),
+    ([1, 3, 2, 5, 10, 9, 15, 6, 8, 14, 13, 11, 12, 4, 7, 0], 42),
+    ([15, 14, 0, 4, 11, 1, 6, 13, 7, 5, 8, 9, 3, 2, 10, 12], 66),
+    ([6, 0, 14, 12, 1, 15, 9, 10, 11, 4, 7, 2, 8, 3, 5, 13], 55),
+    ([7, 11, 8, 3, 14, 0, 6, 15, 1, 4, 13, 9, 5, 12, 2, 10], 46),
+    ([6, 12, 11, 3, 13, 7, 9, 15, 2, 14, 8, 10, 4, 1, 5, 0], 52),
+    ([12, 8, 14, 6, 11, 4, 7, 0, 5, 1, 10, 15, 3, 13, 9, 2], 54),
+    ([14, 3, 9, 1, 15, 8, 4, 5, 11, 7, 10, 13, 0, 2, 12, 6], 59),
+    ([10, 9, 3, 11, 0, 13, 2, 14, 5, 6, 4, 7, 8, 15, 1, 12], 49),
+    ([7, 3, 14, 13, 4, 1, 10, 8, 5, 12, 9, 11, 2, 15, 6, 0], 54),
+    ([11, 4, 2, 7, 1, 0, 10, 15, 6, 9, 14, 8, 3, 13, 5, 12], 52),
+    ([5, 7, 3, 12, 15, 13, 14, 8, 0, 10, 9, 6, 1, 4, 2, 11], 58),
+    ([14, 1, 8, 15, 2, 6, 0, 3, 9, 12, 10, 13, 4, 7, 5, 11], 53),
+    ([13, 14, 6, 12, 4, 5, 1, 0, 9, 3, 10, 2, 15, 11, 8, 7], 52),
+    ([9, 8, 0, 2, 15, 1, 4, 14, 3, 10, 7, 5, 11, 13, 6, 12], 54),
+    ([12, 15, 2, 6, 1, 14, 4, 8, 5, 3, 7, 0, 10, 13, 9, 11], 47),
+    ([12, 8, 15, 13, 1, 0, 5, 4, 6, 3, 2, 11, 9, 7, 14, 10], 50),
+    ([14, 10, 9, 4, 13, 6, 5, 8, 2, 12, 7, 0, 1, 3, 11, 15], 59),
+    ([14, 3, 5, 15, 11, 6, 13, 9, 0, 10, 2, 12, 4, 1, 7, 8], 60),
+    ([6, 11, 7, 8, 13, 2, 5, 4, 1, 10, 3, 9, 14, 0, 12, 15], 52),
+    ([1, 6, 12, 14, 3, 2, 15, 8, 4, 5, 13, 9, 0, 7, 11, 10], 55),
+    ([12, 6, 0, 4, 7, 3, 15, 1, 13, 9, 8, 11, 2, 14, 5, 10], 52),
+    ([8, 1, 7, 12, 11, 0, 10, 5, 9, 15, 6, 13, 14, 2, 3, 4], 58),
+    ([7, 15, 8, 2, 13, 6, 3, 12, 11, 0, 4, 10, 9, 5, 1, 14], 53),
+    ([9, 0, 4, 10, 1, 14, 15, 3, 12, 6, 5, 7, 11, 13, 8, 2], 49),
+    ([11, 5, 1, 14, 4, 12, 10, 0, 2, 7, 13, 3, 9, 15, 6, 8], 54),
+    ([8, 13, 10, 9, 11, 3, 15, 6, 0, 1, 2, 14, 12, 5, 4, 7], 54),
+    ([4, 5, 7, 2, 9, 14, 12, 13, 0, 3, 6, 11, 8, 1, 15, 10], 42),
+    ([11, 15, 14, 13, 1, 9, 10, 4, 3, 6, 2, 12, 7, 5, 8, 0], 64),
+    ([12, 9, 0, 6, 8, 3, 5, 14, 2, 4, 11, 7, 10, 1, 15, 13], 50),
+    ([3, 14, 9, 7, 12, 15, 0, 4, 1, 8, 5, 6, 11, 10, 2, 13], 51),
+    ([8, 4, 6, 1, 14, 12, 2, 15, 13, 10, 9, 5, 3, 7, 0, 11], 49),
+    ([6, 10, 1, 14, 15, 8, 3, 5, 13, 0, 2, 7, 4, 9, 11, 12], 47),
+    ([8, 11, 4, 6, 7, 3, 10, 9, 2, 12, 15, 13, 0, 1, 5, 14], 49),
+    ([10, 0, 2, 4, 5, 1, 6, 12, 11, 13, 9, 7, 15, 3, 14, 8], 59),
+    ([12, 5, 13, 11, 2, 10, 0, 9, 7, 8, 4, 3, 14, 6, 15, 1], 53),
+    ([10, 2, 8, 4, 15, 0, 1, 14, 11, 13, 3, 6, 9, 7, 5, 12], 56),
+    ([10, 8, 0, 12, 3, 7, 6, 2, 1, 14, 4, 11, 15, 13, 9, 5], 56),
+    ([14, 9, 12, 13, 15, 4, 8, 10, 0, 2, 1, 7, 3, 11, 5, 6], 64),
+    ([12, 11, 0, 8, 10, 2, 13, 15, 5, 4, 7, 3, 6, 9, 14, 1], 56),
+    ([13, 8, 14, 3, 9, 1, 0, 7, 15, 5, 4, 10, 12, 2, 6, 11], 41),
+    ([3, 15, 2, 5, 11, 6, 4, 7, 12, 9, 1, 0, 13, 14, 10, 8], 55),
+    ([5, 11, 6, 9, 4, 13, 12, 0, 8, 2, 15, 10, 1, 7, 3, 14], 50),
+    ([5, 0, 15, 8, 4, 6, 1, 14, 10, 11, 3, 9, 7, 12, 2, 13], 51),
+    ([15, 14, 6, 7, 10, 1, 0, 11, 12, 8, 4, 9, 2, 5, 13, 3], 57),
+    ([11, 14, 13, 1, 2, 3, 12, 4, 15, 7, 9, 5, 10, 6, 8, 0], 66),
+    ([6, 13, 3, 2, 11, 9, 5, 10, 1, 7, 12, 14, 8, 4, 0, 15], 45),
+    ([4, 6, 12, 0, 14, 2, 9, 13, 11, 8, 3, 15, 7, 10, 1, 5], 57),
+    ([8, 10, 9, 11, 14, 1, 7, 15, 13, 4, 0, 12, 6, 2, 5, 3], 56),
+    ([5, 2, 14, 0, 7, 8, 6, 3, 11, 12, 13, 15, 4, 10, 9, 1], 51),
+    ([7, 8, 3, 2, 10, 12, 4, 6, 11, 13, 5, 15, 0, 1, 9, 14], 47),
+    ([11, 6, 14, 12, 3, 5, 1, 15, 8, 0, 10, 13, 9, 7, 4, 2], 61),
+    ([7, 1, 2, 4, 8, 3, 6, 11, 10, 15, 0, 5, 14, 12, 13, 9], 50),
+    ([7, 3, 1, 13, 12, 10, 5, 2, 8, 0, 6, 11, 14, 15, 4, 9], 51),
+    ([6, 0, 5, 15, 1, 14, 4, 9, 2, 13, 8, 10, 11, 12, 7, 3], 53),
+    ([15, 1, 3, 12, 4, 0, 6, 5, 2, 8, 14, 9, 13, 10, 7, 11], 52),
+    ([5, 7, 0, 11, 12, 1, 9, 10, 15, 6, 2, 3, 8, 4, 13, 14], 44),
+    ([12, 15, 11, 10, 4, 5, 14, 0, 13, 7, 1, 2, 9, 8, 3, 6], 56),
+    ([6, 14, 10, 5, 15, 8, 7, 1, 3, 4, 2, 0, 12, 9, 11, 13], 49),
+    ([14, 13, 4, 11, 15, 8, 6, 9, 0, 7, 3, 1, 2, 10, 12, 5], 56),
+    ([14, 4, 0, 10, 6, 5, 1, 3, 9, 2, 13, 15, 12, 7, 8, 11], 48),
+    ([15, 10, 8, 3, 0, 6, 9, 5, 1, 14, 13, 11, 7, 2, 12, 4], 57),
+    ([0, 13, 2, 4, 12, 14, 6, 9, 15, 1, 10, 3, 11, 5, 8, 7], 54),
+    ([3, 14, 13, 6, 4, 15, 8, 9, 5, 12, 10, 0, 2, 7, 1, 11], 53),
+    ([0, 1, 9, 7, 11, 13, 5, 3, 14, 12, 4, 2, 8, 6, 10, 15], 42),
+    ([11, 0, 15, 8, 13, 12, 3, 5, 10, 1, 4, 6, 14, 9, 7, 2], 57),
+    ([13, 0, 9, 12, 11, 6, 3, 5, 15, 8, 1, 10, 4, 14, 2, 7], 53),
+    ([14, 10, 2, 1, 13, 9, 8, 11, 7, 3, 6, 12, 15, 5, 4, 0], 62),
+    ([12, 3, 9, 1, 4, 5, 10, 2, 6, 11, 15, 0, 14, 7, 13, 8], 49),
+    ([15, 8, 10, 7, 0, 12, 14, 1, 5, 9, 6, 3, 13, 11, 4, 2], 55),
+    ([4, 7, 13, 10, 1, 2, 9, 6, 12, 8, 14, 5, 3, 0, 11, 15], 44),
+    ([6, 0, 5, 10, 11, 12, 9, 2, 1, 7, 4, 3, 14, 8, 13, 15], 45),
+    ([9, 5, 11, 10, 13, 0, 2, 1, 8, 6, 14, 12, 4, 7, 3, 15], 52),
+    ([15, 2, 12, 11, 14, 13, 9, 5, 1, 3, 8, 7, 0, 10, 6, 4], 65),
+    ([11, 1, 7, 4, 10, 13, 3, 8, 9, 14, 0, 15, 6, 5, 2, 12], 54),
+    ([5, 4, 7, 1, 11, 12, 14, 15, 10, 13, 8, 6, 2, 0, 9, 3], 50),
+    ([9, 7, 5, 2, 14, 15, 12, 10, 11, 3, 6, 1, 8, 13, 0, 4], 57),
+    ([3, 2, 7, 9, 0, 15, 12, 4, 6, 11, 5, 14, 8, 13, 10, 1], 57),
+    ([13, 9, 14, 6, 12, 8, 1, 2, 3, 4, 0, 7, 5, 10, 11, 15], 46),
+    ([5, 7, 11, 8, 0, 14, 9, 13, 10, 12, 3, 15, 6, 1, 4, 2], 53),
+    ([4, 3, 6, 13, 7, 15, 9, 0, 10, 5, 8, 11, 2, 12, 1, 14], 50),
+    ([1, 7, 15, 14, 2, 6, 4, 9, 12, 11, 13, 3, 0, 8, 5, 10], 49),
+    ([9, 14, 5, 7, 8, 15, 1, 2, 10, 4, 13, 6, 12, 0, 11, 3], 44),
+    ([0, 11, 3, 12, 5, 2, 1, 9, 8, 10, 14, 15, 7, 4, 13, 6], 54),
+    ([7, 15, 4, 0, 10, 9, 2, 5, 12, 11, 13, 6, 1, 3, 14, 8], 57),
+    ([11, 4, 0, 8, 6, 10, 5, 13, 12, 7, 14, 3, 1, 2, 9, 15], 54),
+];
+
+/// The result of solving one benchmark instance.
+///
+/// # Attributes
+/// * `instance` - Instance number, starting from 1
+/// * `optimal_length` - Published optimal solution length
+/// * `solution` - Solution length found, or why the solve failed
+/// * `nodes_expanded` - Number of boards expanded (0 if the solve failed)
+/// * `elapsed` - Time taken by the solve
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub instance: usize,
+    pub optimal_length: usize,
+    pub solution: Result<usize, SolveError>,
+    pub nodes_expanded: u64,
+    pub elapsed: Duration,
+}
+
+/// Convert an instance from Korf's notation to a board.
+/// * Rotating the board 180 degrees and relabeling tile `t` as `16 - t` maps Korf's
+///   goal onto ours, so solution lengths are unchanged.
+///
+/// # Parameters
+/// * `korf_tiles` - Tiles in Korf's notation
+///
+/// # Returns
+/// Equivalent board
+fn _from_korf(korf_tiles: &[u8; 16]) -> Board {
+    let tiles: Vec<Tile> = korf_tiles.iter()
+        .rev()
+        .map(|&tile| Tile::new(if tile == 0 { BLANK_TILE } else { 16 - tile as i32 }))
+        .collect();
+    Board::try_new(4, tiles).unwrap()
+}
+
+/// Get Korf's 100 instances.
+///
+/// # Returns
+/// The instances as boards, in order
+pub fn korf_instances() -> Vec<Board> {
+    KORF_INSTANCES.iter().map(|(tiles, _)| _from_korf(tiles)).collect()
+}
+
+/// Get the published optimal solution lengths of Korf's instances.
+///
+/// # Returns
+/// Optimal lengths, in instance order
+pub fn korf_optimal_lengths() -> Vec<usize> {
+    KORF_INSTANCES.iter().map(|&(_, length)| length).collect()
+}
+
+/// Solve Korf's instances, checking every solution is optimal.
+/// * Panics if a solution differs from the published optimal length when the algorithm
+///   claims optimality. Greedy and layered solutions aren't checked.
+///
+/// # Parameters
+/// * `options` - Options controlling each solve
+/// * `limit` - Only solve this many instances, from the first
+///
+/// # Returns
+/// One result per instance solved
+pub fn run_korf(options: &SolveOptions, limit: Option<usize>) -> Vec<BenchResult> {
+    _run(limit, options.algorithm.is_optimal(), |board| {
+        solver::solve(board, options).map(|solution| (solution.moves.len(), solution.stats.nodes_expanded))
+    })
+}
+
+/// Find the optimal lengths of Korf's instances without building solutions.
+/// * Compare with `run_korf` to measure the cost of path bookkeeping.
+///
+/// # Parameters
+/// * `options` - Options controlling each solve
+/// * `limit` - Only solve this many instances, from the first
+///
+/// # Returns
+/// One result per instance solved
+pub fn run_korf_lengths(options: &SolveOptions, limit: Option<usize>) -> Vec<BenchResult> {
+    _run(limit, true, |board| {
+        solver::solve_length_with_stats(board, options)
+            .map(|(length, stats)| (length as usize, stats.nodes_expanded))
+    })
+}
+
+/// Time a solve of each of Korf's instances, checking every length is optimal.
+///
+/// # Parameters
+/// * `limit` - Only solve this many instances, from the first
+/// * `check_optimal` - Whether to check lengths against the published optimal lengths
+/// * `solve` - Solves a board, giving the solution length and boards expanded
+///
+/// # Returns
+/// One result per instance solved
+fn _run<F>(limit: Option<usize>, check_optimal: bool, mut solve: F) -> Vec<BenchResult>
+    where F: FnMut(&Board) -> Result<(usize, u64), SolveError> {
+    let lengths = korf_optimal_lengths();
+    korf_instances().iter()
+        .zip(lengths)
+        .take(limit.unwrap_or(KORF_INSTANCES.len()))
+        .enumerate()
+        .map(|(i, (board, optimal_length))| {
+            let start = clock::now_ms();
+            let solved = solve(board);
+            let elapsed = clock::elapsed_since(start);
+            let nodes_expanded = solved.as_ref().map_or(0, |&(_, nodes)| nodes);
+            let solution = solved.map(|(length, _)| length);
+            match solution {
+                Ok(length) if check_optimal => _check_optimal(i + 1, length, optimal_length),
+                _ => {}
+            }
+            BenchResult { instance: i + 1, optimal_length, solution, nodes_expanded, elapsed }
+        })
+        .collect()
+}
+
+/// Fail loudly if a solution isn't the published optimal length.
+///
+/// # Parameters
+/// * `instance` - Instance number
+/// * `length` - Solution length found
+/// * `optimal_length` - Published optimal solution length
+fn _check_optimal(instance: usize, length: usize, optimal_length: usize) {
+    assert_eq!(
+        length, optimal_length,
+        "Korf instance {} solved in {} moves, but the optimal length is {}",
+        instance, length, optimal_length
+    );
+}
+
+/// Summary of one heuristic's performance over a set of boards.
+///
+/// # Attributes
+/// * `heuristic` - The heuristic
+/// * `mean_nodes` - Mean number of boards expanded
+/// * `median_nodes` - Median number of boards expanded
+/// * `mean_time` - Mean solve time
+/// * `median_time` - Median solve time
+/// * `mean_length` - Mean solution length
+/// * `longer_than_minimum` - Boards where the solution was longer than the shortest
+///   found by any heuristic, which means the heuristic isn't admissible
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeuristicSummary {
+    pub heuristic: Heuristic,
+    pub mean_nodes: f64,
+    pub median_nodes: u64,
+    pub mean_time: Duration,
+    pub median_time: Duration,
+    pub mean_length: f64,
+    pub longer_than_minimum: usize,
+}
+
+/// Solve the same seeded boards with every heuristic and summarize each.
+///
+/// # Parameters
+/// * `n` - Size of the boards
+/// * `count` - Number of boards
+/// * `seed` - Seed of the first board; board i uses `seed + i`
+/// * `shuffles` - Number of random moves used to generate each board
+/// * `options` - Options for every solve; the heuristic is replaced by each in turn
+///
+/// # Returns
+/// One summary per heuristic, in `HEURISTICS` order, or the first failed solve
+pub fn compare_heuristics(n: i32, count: usize, seed: u64, shuffles: i32,
+                          options: &SolveOptions) -> Result<Vec<HeuristicSummary>, SolveError> {
+    let boards: Vec<Board> = (0..count as u64)
+        .map(|i| Board::generate(n, seed.wrapping_add(i), shuffles))
+        .collect::<Result<_, _>>()
+        .map_err(|e| SolveError::InvalidOptions(e.to_string()))?;
+    // Solution length, nodes expanded and time of each board, per heuristic
+    let mut runs: Vec<Vec<(usize, u64, Duration)>> = Vec::new();
+    for &heuristic in HEURISTICS.iter() {
+        let options = options.clone().heuristic(heuristic);
+        let mut results = Vec::new();
+        for board in &boards {
+            let start = clock::now_ms();
+            let solution = solver::solve(board, &options)?;
+            results.push((solution.moves.len(), solution.stats.nodes_expanded, clock::elapsed_since(start)));
+        }
+        runs.push(results);
+    }
+    let shortest: Vec<usize> = (0..boards.len())
+        .map(|i| runs.iter().map(|results| results[i].0).min().unwrap())
+        .collect();
+    Ok(HEURISTICS.iter()
+        .zip(&runs)
+        .map(|(&heuristic, results)| {
+            let count = results.len().max(1);
+            let mut nodes: Vec<u64> = results.iter().map(|result| result.1).collect();
+            let mut times: Vec<Duration> = results.iter().map(|result| result.2).collect();
+            nodes.sort_unstable();
+            times.sort_unstable();
+            HeuristicSummary {
+                heuristic,
+                mean_nodes: nodes.iter().sum::<u64>() as f64 / count as f64,
+                median_nodes: nodes.get(nodes.len() / 2).copied().unwrap_or(0),
+                mean_time: times.iter().sum::<Duration>() / count as u32,
+                median_time: times.get(times.len() / 2).copied().unwrap_or_default(),
+                mean_length: results.iter().map(|result| result.0).sum::<usize>() as f64 / count as f64,
+                longer_than_minimum: results.iter().zip(&shortest).filter(|(result, &min)| result.0 > min).count(),
+            }
+        })
+        .collect())
+}
+
+/// Format heuristic summaries as an aligned table.
+/// * Heuristics that returned longer-than-minimum solutions are flagged with `!`.
+///
+/// # Parameters
+/// * `summaries` - Summaries from `compare_heuristics`
+///
+/// # Returns
+/// Table text, one header line and one line per heuristic
+pub fn format_comparison_table(summaries: &[HeuristicSummary]) -> String {
+    let mut table = format!(
+        "{:<18}{:>12}{:>14}{:>10}{:>12}{:>13}{:>9}\n",
+        "heuristic", "mean nodes", "median nodes", "mean ms", "median ms", "mean length", "longer"
+    );
+    for summary in summaries {
+        let flag = if summary.longer_than_minimum > 0 { " !" } else { "" };
+        table.push_str(&format!(
+            "{:<18}{:>12.1}{:>14}{:>10.3}{:>12.3}{:>13.2}{:>9}{}\n",
+            summary.heuristic.to_string(),
+            summary.mean_nodes,
+            summary.median_nodes,
+            summary.mean_time.as_secs_f64() * 1000.0,
+            summary.median_time.as_secs_f64() * 1000.0,
+            summary.mean_length,
+            summary.longer_than_minimum,
+            flag,
+        ));
+    }
+    table
+}
+
+/// Format heuristic summaries as CSV.
+///
+/// # Parameters
+/// * `summaries` - Summaries from `compare_heuristics`
+///
+/// # Returns
+/// CSV text with a header row
+pub fn format_comparison_csv(summaries: &[HeuristicSummary]) -> String {
+    let mut csv = String::from(
+        "heuristic,mean_nodes,median_nodes,mean_ms,median_ms,mean_length,longer_than_minimum\n"
+    );
+    for summary in summaries {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            summary.heuristic,
+            summary.mean_nodes,
+            summary.median_nodes,
+            summary.mean_time.as_secs_f64() * 1000.0,
+            summary.median_time.as_secs_f64() * 1000.0,
+            summary.mean_length,
+            summary.longer_than_minimum,
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the instances are solvable and consistent with their optimal lengths
+    /// - The heuristic must never exceed the optimal length, and moves change the
+    ///   Manhattan distance by one, so their parities match
+    #[test]
+    fn test_korf_instances() {
+        let boards = korf_instances();
+        let lengths = korf_optimal_lengths();
+        assert_eq!(boards.len(), 100);
+        // Korf's instances average 53.05 moves
+        assert_eq!(lengths.iter().sum::<usize>(), 5305);
+        for (board, &length) in boards.iter().zip(&lengths) {
+            assert!(board.is_solvable());
+            let breakdown = board.heuristic_breakdown(None);
+            assert!(breakdown.total as usize <= length);
+            assert_eq!(breakdown.manhattan as usize % 2, length % 2);
+        }
+    }
+
+    /// Test running the benchmark reports node limits per instance
+    #[test]
+    fn test_run_korf_limited() {
+        let results = run_korf(&SolveOptions::new().max_nodes(10), Some(2));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].instance, 1);
+        assert_eq!(results[0].optimal_length, 57);
+        assert_eq!(results[1].optimal_length, 55);
+        for result in &results {
+            assert_eq!(result.solution.as_ref().unwrap_err().to_string(), "gave up after expanding 10 boards");
+        }
+        let lengths = run_korf_lengths(&SolveOptions::new().max_nodes(10), Some(2));
+        assert_eq!(lengths.len(), 2);
+        assert!(matches!(lengths[1].solution, Err(SolveError::BudgetExceeded(_))));
+    }
+
+    /// Test non-optimal solutions fail loudly
+    #[test]
+    #[should_panic(expected = "Korf instance 3 solved in 61 moves, but the optimal length is 59")]
+    fn test_check_optimal() {
+        _check_optimal(3, 61, 59);
+    }
+
+    /// Test comparing heuristics gives one row per heuristic
+    #[test]
+    fn test_compare_heuristics() {
+        let summaries = compare_heuristics(3, 3, 7, 30, &SolveOptions::default()).unwrap();
+        assert_eq!(summaries.len(), HEURISTICS.len());
+        for summary in &summaries {
+            assert_eq!(summary.longer_than_minimum, 0);
+        }
+        // Both heuristics are admissible, so agree on solution lengths
+        assert_eq!(summaries[0].mean_length, summaries[1].mean_length);
+        let table = format_comparison_table(&summaries);
+        assert_eq!(table.lines().count(), HEURISTICS.len() + 1);
+        assert!(table.lines().nth(1).unwrap().starts_with("manhattan "));
+        assert!(!table.contains('!'));
+        let csv = format_comparison_csv(&summaries);
+        assert_eq!(csv.lines().count(), HEURISTICS.len() + 1);
+        assert!(csv.lines().nth(2).unwrap().starts_with("linear_conflicts,"));
+        assert_eq!(csv.lines().nth(2).unwrap().split(',').count(), 7);
+    }
+}
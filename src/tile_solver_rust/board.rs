@@ -1,314 +1,2770 @@
-//use rand::seq::SliceRandom;
-use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
-use std::hash::{Hash, Hasher};
+use alloc::collections::btree_map::Entry;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::sync::Arc;
+#[cfg(alloc_only)]
+use alloc::{boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::error::Error;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use rand::seq::SliceRandom;
+use rand::Rng;
 
+use super::conflict_cache::ConflictCache;
+use super::error_code::ErrorCode;
+use super::pack;
+use super::rng::RngSource;
 use super::tile::*;
+use super::zobrist::ZobristTable;
 
 /// Tile move directions
-const UP: char = 'U';
-const DOWN: char = 'D';
-const LEFT: char = 'L';
-const RIGHT: char = 'R';
-
-lazy_static! {
-    /// Opposite of each move direction
-    pub static ref OPPOSITE_DIRECTIONS: HashMap<char, char> = {
-        let mut m = HashMap::new();
-        m.insert(UP, DOWN);
-        m.insert(DOWN, UP);
-        m.insert(LEFT, RIGHT);
-        m.insert(RIGHT, LEFT);
-        m
-    };
+pub const UP: char = 'U';
+pub const DOWN: char = 'D';
+pub const LEFT: char = 'L';
+pub const RIGHT: char = 'R';
+
+/// Get the opposite of a move direction.
+///
+/// # Parameters
+/// * `direction` - Move direction
+///
+/// # Returns
+/// The opposite direction, or the character unchanged if it isn't a direction
+pub const fn opposite_direction(direction: char) -> char {
+    match direction {
+        UP => DOWN,
+        DOWN => UP,
+        LEFT => RIGHT,
+        RIGHT => LEFT,
+        other => other,
+    }
 }
 
 /// All tile moves
-const MOVES: [char; 4] = [UP, DOWN, LEFT, RIGHT];
+pub const MOVES: [char; 4] = [UP, DOWN, LEFT, RIGHT];
 
+/// Largest board size accepted when validating a layout, unless raised deliberately
+pub const DEFAULT_MAX_SIZE: i32 = 16;
 
-/// The layout of the game board.
+/// Longest path text accepted by `parse_path`, in bytes; far longer than any solution
+/// of the largest board, but keeps pathological input out of the web API
+pub const MAX_PATH_LEN: usize = 1 << 20;
+
+/// Lower bound on the solution length that shuffles reach by default, so a shuffle
+/// never hands back the solved board
+pub const DEFAULT_MIN_SHUFFLE_BOUND: i32 = 1;
+
+/// Most moves a shuffle makes beyond those asked for while reaching its minimum bound
+pub const MAX_SHUFFLE_RETRIES: i32 = 1000;
+
+/// Problems with the layout of a board.
 ///
-/// * Author - Michael Galliers
+/// # Error codes
+/// * 101 - `InvalidSize`
+/// * 102 - `WrongTileCount`
+/// * 103 - `InvalidTile`
+/// * 104 - `DuplicateTile`
+/// * 105 - `Unparsable`
+/// * 106 - `NotSquare`
+/// * 107 - `UnevenRow`
+/// * 108 - `TooLarge`
+/// * 109 - `InputTooLong`
+/// * 112 - `InvalidRegion`
+/// * 113 - `BlankOutsideRegion`
+/// * 114 - `UnknownGoalPreset`
+/// * 118 - `Uncalibrated`
+/// * 119 - `UnknownDifficulty`
+/// * 120 - `WrongLabelCount`
+/// * 121 - `InvalidLabel`
+/// * 122 - `DuplicateLabel`
+/// * 123 - `UnknownLabel`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    /// Board size is not positive
+    InvalidSize(i32),
+    /// Number of tiles does not match the board size
+    WrongTileCount { expected: usize, found: usize },
+    /// Tile symbol is not valid for the board size
+    InvalidTile(i32),
+    /// Tile symbol appears more than once
+    DuplicateTile(i32),
+    /// Board info text could not be parsed
+    Unparsable(String),
+    /// Number of tiles is not a perfect square
+    NotSquare(usize),
+    /// Row of a 2D board has a different length than the number of rows
+    UnevenRow { row: usize, expected: usize, found: usize },
+    /// Board size is larger than the maximum allowed
+    TooLarge { n: i32, max: i32 },
+    /// Board info text is longer than the maximum allowed
+    InputTooLong { length: usize, max: usize },
+    /// Region can't be extracted as a sub-board
+    InvalidRegion { top: usize, left: usize, rows: usize, cols: usize },
+    /// Blank tile is not inside the region being extracted
+    BlankOutsideRegion,
+    /// Board info names a goal preset that doesn't exist
+    UnknownGoalPreset(String),
+    /// Board size has no difficulty calibration, see `difficulty::CALIBRATIONS`
+    Uncalibrated(i32),
+    /// Difficulty name doesn't exist
+    UnknownDifficulty(String),
+    /// Number of tile labels does not match the number of tiles
+    WrongLabelCount { expected: usize, found: usize },
+    /// Tile label is empty, the blank marker or can't be written in board info
+    InvalidLabel(String),
+    /// Tile label names more than one tile, or a goal uses it more than once
+    DuplicateLabel(String),
+    /// Goal uses a label no tile has
+    UnknownLabel(String),
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::InvalidSize(n) => write!(f, "invalid board size {}", n),
+            BoardError::WrongTileCount { expected, found } =>
+                write!(f, "expected {} tiles, found {}", expected, found),
+            BoardError::InvalidTile(symbol) => write!(f, "invalid tile symbol {}", symbol),
+            BoardError::DuplicateTile(symbol) => write!(f, "duplicate tile symbol {}", symbol),
+            BoardError::Unparsable(text) => write!(f, "could not parse board info {:?}", text),
+            BoardError::NotSquare(count) => write!(f, "{} tiles can't form a square board", count),
+            BoardError::UnevenRow { row, expected, found } =>
+                write!(f, "row {} has {} tiles, expected {}", row, found, expected),
+            BoardError::TooLarge { n, max } =>
+                write!(f, "board size {} is larger than the maximum of {}", n, max),
+            BoardError::InputTooLong { length, max } =>
+                write!(f, "board info of {} bytes is longer than the maximum of {}", length, max),
+            BoardError::InvalidRegion { top, left, rows, cols } =>
+                write!(f, "can't extract a {}x{} sub-board at row {}, column {}", rows, cols, top, left),
+            BoardError::BlankOutsideRegion => write!(f, "the blank is outside the region"),
+            BoardError::UnknownGoalPreset(name) => write!(f, "unknown goal preset {:?}", name),
+            BoardError::Uncalibrated(n) => write!(f, "no difficulty tiers for board size {}", n),
+            BoardError::UnknownDifficulty(name) => write!(f, "unknown difficulty {:?}", name),
+            BoardError::WrongLabelCount { expected, found } =>
+                write!(f, "expected {} tile labels, found {}", expected, found),
+            BoardError::InvalidLabel(label) => write!(f, "invalid tile label {:?}", label),
+            BoardError::DuplicateLabel(label) =>
+                write!(f, "tile label {:?} is used more than once, so tiles can't be told apart by it", label),
+            BoardError::UnknownLabel(label) => write!(f, "no tile has the label {:?}", label),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+impl ErrorCode for BoardError {
+    fn code(&self) -> u32 {
+        match self {
+            BoardError::InvalidSize(_) => 101,
+            BoardError::WrongTileCount { .. } => 102,
+            BoardError::InvalidTile(_) => 103,
+            BoardError::DuplicateTile(_) => 104,
+            BoardError::Unparsable(_) => 105,
+            BoardError::NotSquare(_) => 106,
+            BoardError::UnevenRow { .. } => 107,
+            BoardError::TooLarge { .. } => 108,
+            BoardError::InputTooLong { .. } => 109,
+            BoardError::InvalidRegion { .. } => 112,
+            BoardError::BlankOutsideRegion => 113,
+            BoardError::UnknownGoalPreset(_) => 114,
+            BoardError::Uncalibrated(_) => 118,
+            BoardError::UnknownDifficulty(_) => 119,
+            BoardError::WrongLabelCount { .. } => 120,
+            BoardError::InvalidLabel(_) => 121,
+            BoardError::DuplicateLabel(_) => 122,
+            BoardError::UnknownLabel(_) => 123,
+        }
+    }
+}
+
+/// Reasons a move can't be made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveRejection {
+    /// Character is not one of the move directions
+    UnknownDirection(char),
+    /// Blank tile is on the top row, so it can't move up
+    BlankOnTopEdge,
+    /// Blank tile is on the bottom row, so it can't move down
+    BlankOnBottomEdge,
+    /// Blank tile is in the leftmost column, so it can't move left
+    BlankOnLeftEdge,
+    /// Blank tile is in the rightmost column, so it can't move right
+    BlankOnRightEdge,
+    /// Path is longer than `MAX_PATH_LEN`, so none of it was read
+    PathTooLong,
+}
+
+impl MoveRejection {
+    /// Name of the rejection, for reporting to other languages.
+    ///
+    /// # Returns
+    /// Name of the variant
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoveRejection::UnknownDirection(_) => "UnknownDirection",
+            MoveRejection::BlankOnTopEdge => "BlankOnTopEdge",
+            MoveRejection::BlankOnBottomEdge => "BlankOnBottomEdge",
+            MoveRejection::BlankOnLeftEdge => "BlankOnLeftEdge",
+            MoveRejection::BlankOnRightEdge => "BlankOnRightEdge",
+            MoveRejection::PathTooLong => "PathTooLong",
+        }
+    }
+}
+
+impl fmt::Display for MoveRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveRejection::UnknownDirection(direction) => write!(f, "unknown move direction {:?}", direction),
+            MoveRejection::BlankOnTopEdge => write!(f, "the blank is on the top edge"),
+            MoveRejection::BlankOnBottomEdge => write!(f, "the blank is on the bottom edge"),
+            MoveRejection::BlankOnLeftEdge => write!(f, "the blank is on the left edge"),
+            MoveRejection::BlankOnRightEdge => write!(f, "the blank is on the right edge"),
+            MoveRejection::PathTooLong => write!(f, "the path is longer than {} bytes", MAX_PATH_LEN),
+        }
+    }
+}
+
+impl Error for MoveRejection {}
+
+/// Problems reading a path written by a user.
+///
+/// # Error codes
+/// * 115 - `InvalidToken`
+/// * 116 - `TooLong`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathParseError {
+    /// Character is neither a move in the notation nor a separator
+    InvalidToken { offset: usize, index: usize, token: char },
+    /// Path text is longer than `MAX_PATH_LEN`
+    TooLong { length: usize, max: usize },
+}
+
+impl fmt::Display for PathParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathParseError::InvalidToken { offset, token, .. } =>
+                write!(f, "invalid move {:?} at byte {}", token, offset),
+            PathParseError::TooLong { length, max } =>
+                write!(f, "path of {} bytes is longer than the maximum of {}", length, max),
+        }
+    }
+}
+
+impl Error for PathParseError {}
+
+impl ErrorCode for PathParseError {
+    fn code(&self) -> u32 {
+        match self {
+            PathParseError::InvalidToken { .. } => 115,
+            PathParseError::TooLong { .. } => 116,
+        }
+    }
+}
+
+/// Report a path that can't be read as the move it stopped at.
+impl From<PathParseError> for IllegalMove {
+    fn from(error: PathParseError) -> IllegalMove {
+        match error {
+            PathParseError::InvalidToken { index, token, .. } =>
+                IllegalMove { index, rejection: MoveRejection::UnknownDirection(token) },
+            PathParseError::TooLong { .. } => IllegalMove { index: 0, rejection: MoveRejection::PathTooLong },
+        }
+    }
+}
+
+
+/// Heuristics used to estimate the distance of a board from the goal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Heuristic {
+    /// Sum of tile Manhattan distances
+    Manhattan,
+    /// Manhattan distance plus the linear conflict penalty
+    #[default]
+    LinearConflicts,
+    /// Inversions of the tiles read in row- and column-major order, see `inversion_distance`
+    InversionDistance,
+    /// Maximum of several heuristics, evaluated on each board
+    Max(HeuristicSet),
+}
+
+/// All single heuristics, e.g. for comparing them against each other
+pub const HEURISTICS: [Heuristic; 3] = [Heuristic::Manhattan, Heuristic::LinearConflicts, Heuristic::InversionDistance];
+
+/// Set of single heuristics combined by `Heuristic::Max`.
+/// * Bit `i` stands for `HEURISTICS[i]`, so the set stays `Copy` like the heuristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HeuristicSet(u8);
+
+impl HeuristicSet {
+    /// Get the heuristics in the set.
+    ///
+    /// # Returns
+    /// The heuristics, in the order of `HEURISTICS`
+    pub fn iter(self) -> impl Iterator<Item = Heuristic> {
+        HEURISTICS.iter().enumerate().filter(move |(i, _)| self.0 >> i & 1 == 1).map(|(_, &heuristic)| heuristic)
+    }
+
+    /// Get the bits of the set, e.g. for saving it.
+    ///
+    /// # Returns
+    /// Bit `i` set for each `HEURISTICS[i]` in the set
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Read a set back from its bits.
+    ///
+    /// # Parameters
+    /// * `bits` - Bits, see `bits`
+    ///
+    /// # Returns
+    /// The set, or `None` if a bit is past the end of `HEURISTICS`
+    pub fn from_bits(bits: u8) -> Option<HeuristicSet> {
+        if bits >> HEURISTICS.len() == 0 { Some(HeuristicSet(bits)) } else { None }
+    }
+}
+
+impl Heuristic {
+    /// Get the maximum of several heuristics, which is admissible like each of them.
+    /// * Parts of other maximums are merged in and duplicates dropped.
+    /// * Manhattan is dropped next to linear conflicts, which only add a non-negative
+    ///   term to it and so are at least as large on every board.
+    ///
+    /// # Parameters
+    /// * `heuristics` - Heuristics to combine
+    ///
+    /// # Returns
+    /// The heuristic itself if only one is left, a `Max` of the rest, or `None` if none
+    /// are given
+    pub fn max_of(heuristics: &[Heuristic]) -> Option<Heuristic> {
+        let mut bits = 0u8;
+        for &heuristic in heuristics {
+            bits |= match heuristic {
+                Heuristic::Max(parts) => parts.0,
+                single => 1 << HEURISTICS.iter().position(|&h| h == single).unwrap_or(0),
+            };
+        }
+        if bits & 0b11 == 0b11 {
+            bits &= !0b1;
+        }
+        match bits.count_ones() {
+            0 => None,
+            1 => Some(HEURISTICS[bits.trailing_zeros() as usize]),
+            _ => Some(Heuristic::Max(HeuristicSet(bits))),
+        }
+    }
+
+    /// Read a heuristic by name, or the maximum of several names joined with `+`, e.g.
+    /// "linear_conflicts+inversion_distance".
+    ///
+    /// # Parameters
+    /// * `name` - Name or names, see `Display`
+    ///
+    /// # Returns
+    /// The heuristic, or `None` if any name is unknown
+    pub fn parse(name: &str) -> Option<Heuristic> {
+        let heuristics: Option<Vec<Heuristic>> = name.split('+')
+            .map(|part| HEURISTICS.iter().find(|heuristic| heuristic.to_string() == part.trim()).copied())
+            .collect();
+        Heuristic::max_of(&heuristics?)
+    }
+
+    /// Check if the heuristic is consistent, changing by at most 1 across every move.
+    /// * Linear conflicts count each tile in at most one conflict across its row and
+    ///   column, so a tile sliding into a line can change which conflicts are counted
+    ///   and drop the estimate by more than 1.
+    /// * A maximum of consistent heuristics is consistent too.
+    ///
+    /// # Returns
+    /// Whether the heuristic is consistent on every board
+    pub fn is_consistent(self) -> bool {
+        match self {
+            Heuristic::Manhattan | Heuristic::InversionDistance => true,
+            Heuristic::LinearConflicts => false,
+            Heuristic::Max(parts) => parts.iter().all(Heuristic::is_consistent),
+        }
+    }
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Heuristic::Manhattan => write!(f, "manhattan"),
+            Heuristic::LinearConflicts => write!(f, "linear_conflicts"),
+            Heuristic::InversionDistance => write!(f, "inversion_distance"),
+            Heuristic::Max(parts) => {
+                let names: Vec<String> = parts.iter().map(|part| part.to_string()).collect();
+                write!(f, "{}", names.join("+"))
+            }
+        }
+    }
+}
+
+/// Ways of writing solution moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation {
+    /// Direction the blank moves, e.g. `U` when the tile above slides down
+    #[default]
+    BlankMoves,
+    /// Direction the tile moves, the opposite of the blank's
+    TileMoves,
+    /// Direction the blank moves, in lowercase
+    Lowercase,
+}
+
+/// All notations
+pub const NOTATIONS: [Notation; 3] = [Notation::BlankMoves, Notation::TileMoves, Notation::Lowercase];
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Notation::BlankMoves => write!(f, "blank_moves"),
+            Notation::TileMoves => write!(f, "tile_moves"),
+            Notation::Lowercase => write!(f, "lowercase"),
+        }
+    }
+}
+
+/// Layouts of the solved board used by different puzzle apps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GoalPreset {
+    /// Tiles in reading order, then the blank
+    #[default]
+    Standard,
+    /// The blank, then tiles in reading order
+    BlankFirst,
+    /// Tiles along rows alternating left to right and right to left, then the blank
+    Snake,
+    /// Tiles down each column in turn, then the blank
+    ColumnMajor,
+}
+
+/// All goal presets
+pub const GOAL_PRESETS: [GoalPreset; 4] =
+    [GoalPreset::Standard, GoalPreset::BlankFirst, GoalPreset::Snake, GoalPreset::ColumnMajor];
+
+impl fmt::Display for GoalPreset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoalPreset::Standard => write!(f, "standard"),
+            GoalPreset::BlankFirst => write!(f, "blank_first"),
+            GoalPreset::Snake => write!(f, "snake"),
+            GoalPreset::ColumnMajor => write!(f, "column_major"),
+        }
+    }
+}
+
+impl GoalPreset {
+    /// Get the cells of a board in the order the preset numbers them.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    ///
+    /// # Returns
+    /// Index of each cell, in numbering order
+    fn _cell_order(self, n: usize) -> Vec<usize> {
+        (0..n * n).map(|k| match self {
+            GoalPreset::Standard | GoalPreset::BlankFirst => k,
+            GoalPreset::Snake if (k / n).is_multiple_of(2) => k,
+            GoalPreset::Snake => k / n * n + (n - 1 - k % n),
+            GoalPreset::ColumnMajor => k % n * n + k / n,
+        }).collect()
+    }
+}
+
+impl Notation {
+    /// Write a move of the blank in this notation.
+    ///
+    /// # Parameters
+    /// * `blank_move` - Direction the blank moves
+    ///
+    /// # Returns
+    /// The written move
+    pub fn format_move(self, blank_move: char) -> char {
+        match self {
+            Notation::BlankMoves => blank_move,
+            Notation::TileMoves => opposite_direction(blank_move),
+            Notation::Lowercase => blank_move.to_ascii_lowercase(),
+        }
+    }
+
+    /// Read a move written in this notation.
+    ///
+    /// # Parameters
+    /// * `written` - The written move
+    ///
+    /// # Returns
+    /// Direction the blank moves, or `None` if the move isn't valid in this notation
+    pub fn parse_move(self, written: char) -> Option<char> {
+        MOVES.iter().copied().find(|&blank_move| self.format_move(blank_move) == written)
+    }
+
+    /// Write moves of the blank in this notation.
+    ///
+    /// # Parameters
+    /// * `blank_moves` - Directions the blank moves
+    ///
+    /// # Returns
+    /// The written moves
+    pub fn format_moves(self, blank_moves: &str) -> String {
+        blank_moves.chars().map(|blank_move| self.format_move(blank_move)).collect()
+    }
+}
+
+/// A move that couldn't be made while applying a sequence of moves.
+///
+/// # Error codes
+/// * 110 - Every illegal move; `rejection` says why
 ///
 /// # Attributes
-/// * `boardSize` - Size of board
-/// * `n2` - Number of tiles in board
-/// * `last_direction` - Direction of last move made
-/// * `solved_board` - Solved version of board
-/// * `tiles` - Board tiles
-/// * `blank_index` - Index of blank tile on board
-#[derive(Debug, Clone)]
-pub struct Board {
-    n: i32,
-    n2: i32,
-    pub last_direction: char,
-    tiles: Box<[Tile]>,
-    blank_index: i32,
-    pub depth: i32,
-    pub cost: i32,
-    pub path: String,
-    pub manhattan_cost_cache: i32,
-    pub linear_conflicts_cache: i32,
+/// * `index` - Position of the move in the sequence
+/// * `rejection` - Why the move couldn't be made
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllegalMove {
+    pub index: usize,
+    pub rejection: MoveRejection,
 }
 
-impl Board {
-    /// Create a new board with optional tiles.
+impl fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "move {} can't be made: {}", self.index, self.rejection)
+    }
+}
+
+impl Error for IllegalMove {}
+
+impl ErrorCode for IllegalMove {
+    fn code(&self) -> u32 {
+        110
+    }
+}
+
+/// A position where two boards differ.
+///
+/// # Attributes
+/// * `row` - Row of the position
+/// * `col` - Column of the position
+/// * `tile_in_self` - Symbol at the position in the first board (-1 for the blank)
+/// * `tile_in_other` - Symbol at the position in the second board (-1 for the blank)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileDiff {
+    pub row: i32,
+    pub col: i32,
+    pub tile_in_self: i32,
+    pub tile_in_other: i32,
+}
+
+/// One of the eight symmetries of a square board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transform {
+    Identity,
+    RotateCw,
+    Rotate180,
+    RotateCcw,
+    /// Mirror left to right
+    FlipHorizontal,
+    /// Mirror top to bottom
+    FlipVertical,
+    /// Mirror across the main diagonal
+    Transpose,
+    /// Mirror across the anti-diagonal
+    AntiTranspose,
+}
+
+/// All symmetries of a square board
+pub const TRANSFORMS: [Transform; 8] = [
+    Transform::Identity,
+    Transform::RotateCw,
+    Transform::Rotate180,
+    Transform::RotateCcw,
+    Transform::FlipHorizontal,
+    Transform::FlipVertical,
+    Transform::Transpose,
+    Transform::AntiTranspose,
+];
+
+impl Transform {
+    /// Map a position through the transform.
     ///
     /// # Parameters
-    /// * `boardSize` - Size of the board
-    /// * `solved_board` - Solved version of the board
-    /// * `depth` - The depth of the board in the state-space tree
-    /// * `parent_node` - The parent board from which this board was derived
-    /// * `tiles` - Board tiles
-    pub fn new(n: i32, solved_board: Option<&Board>, depth: i32,
-               mut tiles: Option<Box<[Tile]>>) -> Board {
-        if tiles == None {
-            tiles = Some(Tile::generate_tiles(n));
+    /// * `n` - Size of the board
+    /// * `row` - Row of the position
+    /// * `col` - Column of the position
+    ///
+    /// # Returns
+    /// Row and column of the transformed position
+    pub fn map_position(self, n: i32, row: i32, col: i32) -> (i32, i32) {
+        match self {
+            Transform::Identity => (row, col),
+            Transform::RotateCw => (col, n - 1 - row),
+            Transform::Rotate180 => (n - 1 - row, n - 1 - col),
+            Transform::RotateCcw => (n - 1 - col, row),
+            Transform::FlipHorizontal => (row, n - 1 - col),
+            Transform::FlipVertical => (n - 1 - row, col),
+            Transform::Transpose => (col, row),
+            Transform::AntiTranspose => (n - 1 - col, n - 1 - row),
         }
-        let mut board = Board {
-            n,
-            n2: n * n,
-            last_direction: '\0',
-            tiles: tiles.unwrap(),
-            blank_index: -1,
-            depth,
-            cost: -1,
-            path: String::new(),
-            manhattan_cost_cache: -1,
-            linear_conflicts_cache: -1,
+    }
+
+    /// Map a move direction through the transform.
+    ///
+    /// # Parameters
+    /// * `tile_move` - Move direction
+    ///
+    /// # Returns
+    /// The transformed direction, or the move unchanged if it isn't a direction
+    pub fn map_move(self, tile_move: char) -> char {
+        let (d_row, d_col) = match tile_move {
+            UP => (-1, 0),
+            DOWN => (1, 0),
+            LEFT => (0, -1),
+            RIGHT => (0, 1),
+            _ => return tile_move,
         };
-        board.blank_index = board.get_blank_index();
-        if solved_board != None {
-            board.cost = board.get_cost(solved_board.unwrap());
+        // Map a step away from the centre of a 3x3 board
+        let (row, col) = self.map_position(3, 1, 1);
+        let (moved_row, moved_col) = self.map_position(3, 1 + d_row, 1 + d_col);
+        match (moved_row - row, moved_col - col) {
+            (-1, 0) => UP,
+            (1, 0) => DOWN,
+            (0, -1) => LEFT,
+            _ => RIGHT,
+        }
+    }
+}
+
+/// Map the moves of a path through a transform.
+/// * Replaying the transformed path on the transformed board reaches the transformed
+///   goal.
+///
+/// # Parameters
+/// * `path` - Moves to transform
+/// * `transform` - Symmetry to apply
+///
+/// # Returns
+/// The transformed path
+pub fn transform_path(path: &str, transform: Transform) -> String {
+    path.chars().map(|tile_move| transform.map_move(tile_move)).collect()
+}
+
+/// Invert a path, so it undoes the moves it was made from.
+/// * Moves are undone last first, each by the opposite move: the inverse of `RRD` is
+///   `ULL`, not `LLU`.
+/// * Characters that aren't moves are kept as they are.
+///
+/// # Parameters
+/// * `path` - Moves of the blank
+///
+/// # Returns
+/// Moves of the blank that return a board reached by `path` to where it started
+pub fn invert_path(path: &str) -> String {
+    path.chars().rev().map(opposite_direction).collect()
+}
+
+/// Read a path written by a user, e.g. from a URL or a database.
+/// * Whitespace and commas between moves are ignored, and moves may be in either case,
+///   so `"u, l ,R"` reads as `ULR`.
+///
+/// # Parameters
+/// * `s` - Path text, at most `MAX_PATH_LEN` bytes
+/// * `notation` - Notation the moves are written in
+///
+/// # Returns
+/// Directions the blank moves, or the first character that isn't a move
+pub fn parse_path(s: &str, notation: Notation) -> Result<Vec<char>, PathParseError> {
+    if s.len() > MAX_PATH_LEN {
+        return Err(PathParseError::TooLong { length: s.len(), max: MAX_PATH_LEN });
+    }
+    let mut moves = Vec::with_capacity(s.len());
+    for (offset, token) in s.char_indices() {
+        if token.is_whitespace() || token == ',' {
+            continue;
+        }
+        let blank_move = MOVES.iter().copied()
+            .find(|&blank_move| notation.format_move(blank_move).eq_ignore_ascii_case(&token))
+            .ok_or(PathParseError::InvalidToken { offset, index: moves.len(), token })?;
+        moves.push(blank_move);
+    }
+    Ok(moves)
+}
+
+/// Drop near-duplicate boards from a pool, e.g. of generated puzzles.
+/// * Boards are kept in order unless they are at least `min_similarity` similar (see
+///   `Board::similarity`) to a board already kept.
+/// * Compares each board with every kept board, so it is quadratic in the pool size.
+///
+/// # Parameters
+/// * `boards` - Pool of boards
+/// * `min_similarity` - Similarity from which boards count as duplicates
+///
+/// # Returns
+/// The boards that aren't near-duplicates of an earlier board
+pub fn dedupe_pool(boards: Vec<Board>, min_similarity: f32) -> Vec<Board> {
+    let mut kept: Vec<Board> = Vec::with_capacity(boards.len());
+    for board in boards {
+        if kept.iter().all(|other| board.similarity(other) < min_similarity) {
+            kept.push(board);
+        }
+    }
+    kept
+}
+
+/// Build a lookup from each symbol to its cell in a goal.
+///
+/// # Parameters
+/// * `goal` - Goal board
+///
+/// # Returns
+/// Goal index of every non-blank symbol, indexed by symbol
+fn _goal_indices(goal: &Board) -> Vec<i32> {
+    let mut goal_indices = vec![0; goal.n2 as usize + 1];
+    for (i, tile) in goal.tiles.iter().enumerate() {
+        if !tile.is_blank() {
+            goal_indices[tile.symbol() as usize] = i as i32;
+        }
+    }
+    goal_indices
+}
+
+/// Components of a board's cost.
+///
+/// # Attributes
+/// * `manhattan` - Manhattan distance of the tiles from the goal
+/// * `linear_conflicts` - Linear conflict penalty
+/// * `depth` - Path cost of the board, see `Board::g`
+/// * `total` - Sum of the components
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeuristicBreakdown {
+    pub manhattan: i32,
+    pub linear_conflicts: i32,
+    pub depth: i32,
+    pub total: i32,
+}
+
+/// Scramble quality metrics of a board, for filtering generated puzzles.
+///
+/// # Attributes
+/// * `misplaced` - Tiles not at their goal position (the blank isn't counted)
+/// * `out_of_row` - Tiles not in their goal row
+/// * `out_of_column` - Tiles not in their goal column
+/// * `correct_prefix` - Number of positions, in row-major order, matching the goal before
+///   the first that doesn't
+/// * `final_moves_forced` - Whether every misplaced tile belongs in the 2x2 block around
+///   the blank's goal position, so solving only cycles that block and the final moves
+///   leave no choice (false for the goal itself)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardMetrics {
+    pub misplaced: i32,
+    pub out_of_row: i32,
+    pub out_of_column: i32,
+    pub correct_prefix: i32,
+    pub final_moves_forced: bool,
+}
+
+impl BoardMetrics {
+    /// Format the metrics as a JSON object.
+    ///
+    /// # Returns
+    /// JSON representation of the metrics
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"misplaced\":{},\"out_of_row\":{},\"out_of_column\":{},\"correct_prefix\":{},\
+             \"final_moves_forced\":{}}}",
+            self.misplaced, self.out_of_row, self.out_of_column, self.correct_prefix, self.final_moves_forced
+        )
+    }
+}
+
+/// A reason a board can't reach a goal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolvabilityProblem {
+    /// Board and goal have different sizes
+    SizeMismatch { size: i32, goal_size: i32 },
+    /// Board and goal have different numbers of blank tiles
+    BlankCount { blanks: usize, goal_blanks: usize },
+    /// Tile symbol appears more than once on the board
+    DuplicateTile(i32),
+    /// Tile is on the board but not in the goal
+    NotInGoal(i32),
+    /// Tile is in the goal but not on the board
+    NotOnBoard(i32),
+    /// Board has the goal's tiles, but an odd permutation away; swapping the two tiles
+    /// makes it solvable
+    ParityMismatch { swap: (i32, i32) },
+}
+
+impl SolvabilityProblem {
+    /// Name of the problem, for reporting to other languages.
+    ///
+    /// # Returns
+    /// Name of the variant
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolvabilityProblem::SizeMismatch { .. } => "SizeMismatch",
+            SolvabilityProblem::BlankCount { .. } => "BlankCount",
+            SolvabilityProblem::DuplicateTile(_) => "DuplicateTile",
+            SolvabilityProblem::NotInGoal(_) => "NotInGoal",
+            SolvabilityProblem::NotOnBoard(_) => "NotOnBoard",
+            SolvabilityProblem::ParityMismatch { .. } => "ParityMismatch",
+        }
+    }
+
+    /// Format the problem as a JSON object.
+    ///
+    /// # Returns
+    /// JSON object with the problem `name` and its fields
+    pub fn to_json(&self) -> String {
+        let fields = match self {
+            SolvabilityProblem::SizeMismatch { size, goal_size } =>
+                format!("\"size\":{},\"goal_size\":{}", size, goal_size),
+            SolvabilityProblem::BlankCount { blanks, goal_blanks } =>
+                format!("\"blanks\":{},\"goal_blanks\":{}", blanks, goal_blanks),
+            SolvabilityProblem::DuplicateTile(symbol) | SolvabilityProblem::NotInGoal(symbol) |
+            SolvabilityProblem::NotOnBoard(symbol) => format!("\"tile\":{}", symbol),
+            SolvabilityProblem::ParityMismatch { swap: (a, b) } => format!("\"swap\":[{},{}]", a, b),
+        };
+        format!("{{\"name\":\"{}\",{}}}", self.name(), fields)
+    }
+}
+
+impl fmt::Display for SolvabilityProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolvabilityProblem::SizeMismatch { size, goal_size } =>
+                write!(f, "the board is {0}x{0} but the goal is {1}x{1}", size, goal_size),
+            SolvabilityProblem::BlankCount { blanks, goal_blanks } =>
+                write!(f, "the board has {} blanks but the goal has {}", blanks, goal_blanks),
+            SolvabilityProblem::DuplicateTile(symbol) => write!(f, "tile {} appears more than once", symbol),
+            SolvabilityProblem::NotInGoal(symbol) => write!(f, "tile {} is not in the goal", symbol),
+            SolvabilityProblem::NotOnBoard(symbol) => write!(f, "tile {} is missing from the board", symbol),
+            SolvabilityProblem::ParityMismatch { swap: (a, b) } =>
+                write!(f, "the tiles are an odd permutation from the goal; swap tiles {} and {}", a, b),
+        }
+    }
+}
+
+/// Why a board can or can't reach a goal, e.g. for explaining a pasted board.
+///
+/// # Attributes
+/// * `problems` - Every problem found; empty if the board is solvable. Parity is only
+///   checked once the board has exactly the goal's tiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolvabilityReport {
+    pub problems: Vec<SolvabilityProblem>,
+}
+
+impl SolvabilityReport {
+    /// Check if the board can reach the goal.
+    ///
+    /// # Returns
+    /// Whether no problems were found
+    pub fn is_solvable(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Suggest a fix when parity is the only problem.
+    ///
+    /// # Returns
+    /// Symbols of two tiles whose swap makes the board solvable, if parity is the only
+    /// problem
+    pub fn suggest_fix(&self) -> Option<(i32, i32)> {
+        match self.problems.as_slice() {
+            [SolvabilityProblem::ParityMismatch { swap }] => Some(*swap),
+            _ => None,
+        }
+    }
+
+    /// Format the report as a JSON object.
+    ///
+    /// # Returns
+    /// JSON object with `solvable`, the `problems` and the suggested `fix` (or null)
+    pub fn to_json(&self) -> String {
+        let problems: Vec<String> = self.problems.iter().map(|problem| problem.to_json()).collect();
+        let fix = match self.suggest_fix() {
+            Some((a, b)) => format!("[{},{}]", a, b),
+            None => "null".to_string(),
+        };
+        format!("{{\"solvable\":{},\"problems\":[{}],\"fix\":{}}}", self.is_solvable(), problems.join(","), fix)
+    }
+}
+
+/// A board can't reach a goal, see `Board::reachability`.
+///
+/// # Attributes
+/// * `problems` - Every problem found, at least one
+///
+/// # Error codes
+/// * 117
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unreachable {
+    pub problems: Vec<SolvabilityProblem>,
+}
+
+impl fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let problems: Vec<String> = self.problems.iter().map(|problem| problem.to_string()).collect();
+        write!(f, "board can't reach the goal: {}", problems.join("; "))
+    }
+}
+
+impl Error for Unreachable {}
+
+impl ErrorCode for Unreachable {
+    fn code(&self) -> u32 {
+        117
+    }
+}
+
+/// Options for writing a board as a text grid, one row per line.
+/// * Build with `BoardFormatter::new()` and the builder methods, e.g.
+///   `BoardFormatter::new().blank(".").separator(" | ")`.
+/// * Cells are right-aligned, so columns line up however many digits the symbols have.
+/// * Trailing whitespace is trimmed from every line, e.g. when the blank ends a row.
+///
+/// # Attributes
+/// * `blank` - Marker written for the blank tile
+/// * `cell_width` - Minimum width of each cell; `None` fits the widest tile or marker
+/// * `separator` - Written between the cells of a row
+/// * `tile_prefix` - Write tiles as `Tile 7` instead of `7`
+/// * `labels` - Written instead of the symbols, label i for tile i + 1; empty to write
+///   symbols
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardFormatter {
+    pub blank: String,
+    pub cell_width: Option<usize>,
+    pub separator: String,
+    pub tile_prefix: bool,
+    pub labels: Vec<String>,
+}
+
+impl Default for BoardFormatter {
+    fn default() -> BoardFormatter {
+        BoardFormatter {
+            blank: "_".to_string(),
+            cell_width: None,
+            separator: " ".to_string(),
+            tile_prefix: false,
+            labels: Vec::new(),
+        }
+    }
+}
+
+impl BoardFormatter {
+    /// Create the default formatter, as used by `Display for Board`.
+    pub fn new() -> BoardFormatter {
+        BoardFormatter::default()
+    }
+
+    /// Set the marker written for the blank tile.
+    pub fn blank(mut self, blank: &str) -> BoardFormatter {
+        self.blank = blank.to_string();
+        self
+    }
+
+    /// Set the minimum width of each cell, or `None` to fit the widest cell.
+    pub fn cell_width(mut self, cell_width: Option<usize>) -> BoardFormatter {
+        self.cell_width = cell_width;
+        self
+    }
+
+    /// Set what is written between the cells of a row.
+    pub fn separator(mut self, separator: &str) -> BoardFormatter {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Set whether tiles are written with a `Tile ` prefix.
+    pub fn tile_prefix(mut self, tile_prefix: bool) -> BoardFormatter {
+        self.tile_prefix = tile_prefix;
+        self
+    }
+
+    /// Set the labels written instead of the symbols, label i for tile i + 1.
+    pub fn labels(mut self, labels: &[String]) -> BoardFormatter {
+        self.labels = labels.to_vec();
+        self
+    }
+
+    /// Write a board as a grid.
+    ///
+    /// # Parameters
+    /// * `board` - The board to write
+    ///
+    /// # Returns
+    /// One line per row, each ending in a newline
+    pub fn format(&self, board: &Board) -> String {
+        let prefix = if self.tile_prefix { "Tile " } else { "" };
+        let tile_text = |tile: &Tile| match self.labels.get((tile.symbol() - 1) as usize) {
+            Some(label) => label.clone(),
+            None => tile.symbol().to_string(),
+        };
+        let symbol_width = board.tiles.iter()
+            .filter(|tile| !tile.is_blank())
+            .map(|tile| tile_text(tile).chars().count())
+            .max()
+            .unwrap_or(0);
+        let fitted = (prefix.len() + symbol_width).max(self.blank.chars().count());
+        let width = self.cell_width.unwrap_or(fitted);
+        let mut text = String::new();
+        for row in board.tiles.chunks(board.n as usize) {
+            let cells: Vec<String> = row.iter()
+                .map(|tile| {
+                    let cell = if tile.is_blank() {
+                        self.blank.clone()
+                    } else {
+                        format!("{}{:>w$}", prefix, tile_text(tile), w = symbol_width)
+                    };
+                    format!("{:>w$}", cell, w = width)
+                })
+                .collect();
+            text.push_str(cells.join(&self.separator).trim_end());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// The layout of the game board.
+///
+/// * Author - Michael Galliers
+///
+/// # Attributes
+/// * `n` - Size of board
+/// * `n2` - Number of tiles in board
+/// * `tiles` - Board tiles
+/// * `blank_index` - Index of blank tile on board
+/// * `depth` - Moves made to reach the board in a search, see `g`; -1 for boards that
+///   aren't search nodes, e.g. goals
+/// * `cost` - Priority of the board in a search (f), set by the solver from `g` and the
+///   heuristic; -1 until costed
+/// * `path` - Moves of the blank that reached the board
+/// * `manhattan_cost_cache` - Manhattan part of the heuristic last stored, see `store_h`
+/// * `linear_conflicts_cache` - Rest of the heuristic last stored above the Manhattan part,
+///   e.g. the linear conflict penalty
+/// * `zobrist` - Table the board's Zobrist hash is kept with, see `with_zobrist`
+/// * `zobrist_hash` - Zobrist hash of the tiles, kept up to date while `zobrist` is set
+#[derive(Debug, Clone)]
+pub struct Board {
+    n: i32,
+    n2: i32,
+    tiles: Box<[Tile]>,
+    blank_index: i32,
+    pub depth: i32,
+    pub cost: i32,
+    pub path: String,
+    pub manhattan_cost_cache: i32,
+    pub linear_conflicts_cache: i32,
+    zobrist: Option<Arc<ZobristTable>>,
+    zobrist_hash: u64,
+}
+
+impl Board {
+    /// Create a new board with optional tiles.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `solved_board` - Solved board to cost the new board against, if any
+    /// * `depth` - The depth of the board in the state-space tree
+    /// * `tiles` - Board tiles (solved layout when `None`)
+    pub fn new(n: i32, solved_board: Option<&Board>, depth: i32,
+               mut tiles: Option<Box<[Tile]>>) -> Board {
+        if tiles.is_none() {
+            tiles = Some(Tile::generate_tiles(n));
+        }
+        let mut board = Board {
+            n,
+            n2: n * n,
+            tiles: tiles.unwrap(),
+            blank_index: -1,
+            depth,
+            cost: -1,
+            path: String::new(),
+            manhattan_cost_cache: -1,
+            linear_conflicts_cache: -1,
+            zobrist: None,
+            zobrist_hash: 0,
+        };
+        board.blank_index = board.get_blank_index();
+        if let Some(solved_board) = solved_board {
+            board.cost = board.g() + board.store_h(solved_board, Heuristic::LinearConflicts);
+        }
+        board
+    }
+
+    /// Create a new board from tiles, validating the layout.
+    /// * The board is costed against the solved board of the same size.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `tiles` - Board tiles
+    ///
+    /// # Returns
+    /// The new board, or the problem with the layout
+    pub fn try_new(n: i32, tiles: Vec<Tile>) -> Result<Board, BoardError> {
+        Board::try_new_with_max_size(n, tiles, DEFAULT_MAX_SIZE)
+    }
+
+    /// Create a new board from tiles, validating the layout against a maximum size.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `tiles` - Board tiles
+    /// * `max_size` - Largest size accepted
+    ///
+    /// # Returns
+    /// The new board, or the problem with the layout
+    pub fn try_new_with_max_size(n: i32, tiles: Vec<Tile>, max_size: i32) -> Result<Board, BoardError> {
+        Board::validate_with_max_size(n, &tiles, max_size)?;
+        let solved_board = Board::new(n, None, -1, None);
+        Ok(Board::new(n, Some(&solved_board), 0, Some(tiles.into_boxed_slice())))
+    }
+
+    /// Check that tiles form a valid board.
+    /// * Every symbol from 1 to n^2 - 1 and a single blank tile must be present.
+    /// * Boards larger than `DEFAULT_MAX_SIZE` are rejected.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `tiles` - Board tiles
+    ///
+    /// # Returns
+    /// The problem with the layout, if any
+    pub fn validate(n: i32, tiles: &[Tile]) -> Result<(), BoardError> {
+        Board::validate_with_max_size(n, tiles, DEFAULT_MAX_SIZE)
+    }
+
+    /// Check the size of a board and its number of tiles, but not the tiles themselves.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `tiles` - Board tiles
+    /// * `max_size` - Largest size accepted
+    ///
+    /// # Returns
+    /// The problem with the dimensions, if any
+    pub fn validate_dimensions(n: i32, tiles: &[Tile], max_size: i32) -> Result<(), BoardError> {
+        if n < 1 {
+            return Err(BoardError::InvalidSize(n));
+        }
+        // Checked before squaring, which could overflow
+        if n > max_size {
+            return Err(BoardError::TooLarge { n, max: max_size });
+        }
+        let n2 = (n * n) as usize;
+        if tiles.len() != n2 {
+            return Err(BoardError::WrongTileCount { expected: n2, found: tiles.len() });
+        }
+        Ok(())
+    }
+
+    /// Check that tiles form a valid board no larger than a maximum size.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `tiles` - Board tiles
+    /// * `max_size` - Largest size accepted
+    ///
+    /// # Returns
+    /// The problem with the layout, if any
+    pub fn validate_with_max_size(n: i32, tiles: &[Tile], max_size: i32) -> Result<(), BoardError> {
+        Board::validate_dimensions(n, tiles, max_size)?;
+        let n2 = (n * n) as usize;
+        let mut seen: BTreeSet<Tile> = BTreeSet::new();
+        for tile in tiles {
+            if !tile.is_blank() && (tile.symbol() < 1 || tile.symbol() >= n2 as i32) {
+                return Err(BoardError::InvalidTile(tile.symbol()));
+            }
+            if !seen.insert(*tile) {
+                return Err(BoardError::DuplicateTile(tile.symbol()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the size (width) of the board.
+    ///
+    /// # Returns
+    /// Size of the board
+    pub fn size(&self) -> i32 {
+        self.n
+    }
+
+    /// Get the board tiles.
+    ///
+    /// # Returns
+    /// Board tiles in row-major order
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    /// Get the tiles of a row.
+    ///
+    /// # Parameters
+    /// * `row` - Index of the row
+    ///
+    /// # Returns
+    /// Tiles of the row, left to right
+    ///
+    /// # Panics
+    /// If the row is outside the board, see `get_row`
+    pub fn row(&self, row: usize) -> &[Tile] {
+        self.get_row(row).unwrap_or_else(|| panic!("row {} is outside a {}x{} board", row, self.n, self.n))
+    }
+
+    /// Get the tiles of a row, if it is on the board.
+    ///
+    /// # Parameters
+    /// * `row` - Index of the row
+    ///
+    /// # Returns
+    /// Tiles of the row, left to right, or `None` if the row is outside the board
+    pub fn get_row(&self, row: usize) -> Option<&[Tile]> {
+        let n = self.n as usize;
+        self.tiles.get(row * n..(row + 1) * n)
+    }
+
+    /// Iterate over the tiles of a column.
+    ///
+    /// # Parameters
+    /// * `col` - Index of the column
+    ///
+    /// # Returns
+    /// Tiles of the column, top to bottom
+    ///
+    /// # Panics
+    /// If the column is outside the board, see `get_column`
+    pub fn column(&self, col: usize) -> impl Iterator<Item = Tile> + '_ {
+        self.get_column(col).unwrap_or_else(|| panic!("column {} is outside a {}x{} board", col, self.n, self.n))
+    }
+
+    /// Iterate over the tiles of a column, if it is on the board.
+    ///
+    /// # Parameters
+    /// * `col` - Index of the column
+    ///
+    /// # Returns
+    /// Tiles of the column, top to bottom, or `None` if the column is outside the board
+    pub fn get_column(&self, col: usize) -> Option<impl Iterator<Item = Tile> + '_> {
+        let n = self.n as usize;
+        if col >= n {
+            return None;
+        }
+        Some(self.tiles.iter().skip(col).step_by(n).copied())
+    }
+
+    /// Get index of the blank tile.
+    ///
+    /// # Returns
+    /// Index of blank tile
+    pub fn get_blank_index(&self) -> i32 {
+        for i in 0..self.n2 as usize {
+            if self.tiles[i].is_blank() {
+                return i as i32;
+            }
+        }
+        -1
+    }
+
+    /// Format the board as a Markdown pipe table.
+    /// * The header row is left empty, since boards have no column headings, and the
+    ///   blank is an empty cell.
+    ///
+    /// # Returns
+    /// Markdown table, one line per row
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        markdown.push_str(&"|   ".repeat(self.n as usize));
+        markdown.push_str("|\n");
+        markdown.push_str(&"|---".repeat(self.n as usize));
+        markdown.push_str("|\n");
+        for row in 0..self.n {
+            for col in 0..self.n {
+                let tile = self.index(row, col, None);
+                let text = if tile.is_blank() { String::new() } else { tile.symbol().to_string() };
+                markdown.push_str(&format!("| {} ", Board::_escape_markdown(&text)));
+            }
+            markdown.push_str("|\n");
+        }
+        markdown
+    }
+
+    /// Format the board as an HTML table, e.g. for setting `innerHTML`.
+    /// * Tiles carry their symbol in a `data-tile` attribute; the blank is an empty
+    ///   cell with the `blank` class.
+    ///
+    /// # Returns
+    /// HTML table, one line per row
+    pub fn to_html(&self) -> String {
+        self.to_html_labeled(&[])
+    }
+
+    /// Format the board as an HTML table, showing labels instead of symbols.
+    /// * Tiles still carry their symbol in the `data-tile` attribute.
+    ///
+    /// # Parameters
+    /// * `labels` - Label i for tile i + 1; tiles without one show their symbol
+    ///
+    /// # Returns
+    /// HTML table, one line per row
+    pub fn to_html_labeled(&self, labels: &[String]) -> String {
+        let mut html = String::from("<table class=\"board\">\n");
+        for row in 0..self.n {
+            html.push_str("<tr>");
+            for col in 0..self.n {
+                let tile = self.index(row, col, None);
+                if tile.is_blank() {
+                    html.push_str("<td class=\"blank\"></td>");
+                } else {
+                    let symbol = tile.symbol().to_string();
+                    let label = labels.get((tile.symbol() - 1) as usize).unwrap_or(&symbol);
+                    html.push_str(&format!(
+                        "<td data-tile=\"{}\">{}</td>",
+                        Board::_escape_html(&symbol),
+                        Board::_escape_html(label)
+                    ));
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        html
+    }
+
+    /// Escape text for a Markdown table cell.
+    ///
+    /// # Parameters
+    /// * `text` - Text of the cell
+    ///
+    /// # Returns
+    /// Text with backslashes and pipes escaped
+    fn _escape_markdown(text: &str) -> String {
+        text.replace('\\', "\\\\").replace('|', "\\|")
+    }
+
+    /// Escape text for HTML content and quoted attribute values.
+    ///
+    /// # Parameters
+    /// * `text` - Text to escape
+    ///
+    /// # Returns
+    /// Text with HTML special characters replaced by entities
+    fn _escape_html(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&#39;")
+    }
+
+    /// Format the board as a compact string, e.g. `3:1,2,3,4,5,6,7,_,8`.
+    /// * The size, a colon, then the tile symbols in row-major order separated by
+    ///   commas, with `_` for the blank and no whitespace.
+    ///
+    /// # Returns
+    /// Compact string that `from_compact_string` parses back to an equal board
+    pub fn to_compact_string(&self) -> String {
+        let symbols: Vec<String> = self.tiles.iter()
+            .map(|tile| if tile.is_blank() { "_".to_string() } else { tile.symbol().to_string() })
+            .collect();
+        format!("{}:{}", self.n, symbols.join(","))
+    }
+
+    /// Parse a board from its compact string.
+    /// * Whitespace around the size, the colon and each tile is ignored.
+    /// * The blank must be written `_`; every other tile is a decimal symbol from 1 to
+    ///   n^2 - 1.
+    ///
+    /// # Parameters
+    /// * `compact` - Compact string in the format of `to_compact_string`
+    ///
+    /// # Returns
+    /// The board, costed against the solved board, or the problem with the string
+    pub fn from_compact_string(compact: &str) -> Result<Board, BoardError> {
+        let unparsable = || BoardError::Unparsable(compact.to_string());
+        let mut parts = compact.splitn(2, ':');
+        let n = parts.next().unwrap().trim().parse::<i32>().map_err(|_| unparsable())?;
+        let tiles_text = parts.next().ok_or_else(unparsable)?;
+        let mut tiles = Vec::new();
+        for symbol in tiles_text.split(',').map(str::trim) {
+            if symbol == "_" {
+                tiles.push(Tile::new(BLANK_TILE));
+                continue;
+            }
+            let symbol = symbol.parse::<i32>().map_err(|_| unparsable())?;
+            if symbol < 1 {
+                return Err(BoardError::InvalidTile(symbol));
+            }
+            tiles.push(Tile::new(symbol));
+        }
+        Board::try_new(n, tiles)
+    }
+
+    /// Format the board as an 11-character base64url token of its packed tiles.
+    /// * The size isn't stored; only one size decodes to a valid board.
+    ///
+    /// # Returns
+    /// Token for use in URLs, or `None` if the board is too large to pack
+    pub fn to_url_safe(&self) -> Option<String> {
+        if self.n > pack::MAX_PACKED_SIZE {
+            return None;
+        }
+        Some(pack::encode_base64url(pack::encode_tiles(&self.tiles)))
+    }
+
+    /// Parse a board from a token made by `to_url_safe`.
+    /// * Packed tiles beyond the board are zero, which would repeat tile 1 on any
+    ///   larger board, so the smallest size that decodes to a valid board is the size.
+    ///
+    /// # Parameters
+    /// * `token` - Base64url token
+    ///
+    /// # Returns
+    /// The board, costed against the solved board, or the problem with the token
+    pub fn from_url_safe(token: &str) -> Result<Board, BoardError> {
+        let unparsable = || BoardError::Unparsable(token.to_string());
+        let packed = pack::decode_base64url(token.trim()).ok_or_else(unparsable)?;
+        for n in 1..=pack::MAX_PACKED_SIZE {
+            let bits = 4 * n * n;
+            if bits < 64 && packed >> bits != 0 {
+                continue;
+            }
+            let tiles = pack::decode_tiles(n, packed);
+            if Board::validate(n, &tiles).is_ok() {
+                return Board::try_new(n, tiles);
+            }
+        }
+        Err(unparsable())
+    }
+
+    /// Rank the board among all permutations of its tiles, see `pack` for the ordering.
+    ///
+    /// # Returns
+    /// Dense index from 0 (the solved board) to (n^2)! - 1
+    ///
+    /// # Panics
+    /// If the board is larger than `pack::MAX_PACKED_SIZE`
+    pub fn lehmer_rank(&self) -> u64 {
+        assert!(self.n <= pack::MAX_PACKED_SIZE, "a {}x{} board is too large to rank", self.n, self.n);
+        pack::rank_packed(pack::encode_tiles(&self.tiles), self.tiles.len())
+    }
+
+    /// Build the board of a Lehmer rank, the inverse of `lehmer_rank`.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board, from 1 to `pack::MAX_PACKED_SIZE`
+    /// * `rank` - Rank from 0 to (n^2)! - 1
+    /// * `solvable_only` - Whether to reject ranks of unsolvable boards
+    ///
+    /// # Returns
+    /// The board, costed against the solved board, or the problem with the rank
+    pub fn from_lehmer_rank(n: i32, rank: u64, solvable_only: bool) -> Result<Board, pack::RankError> {
+        if !(1..=pack::MAX_PACKED_SIZE).contains(&n) {
+            return Err(pack::RankError::InvalidSize(n));
+        }
+        let cells = (n * n) as usize;
+        let count = pack::permutation_count(cells);
+        if rank >= count {
+            return Err(pack::RankError::OutOfRange { rank, count });
+        }
+        let board = Board::try_new(n, pack::decode_tiles(n, pack::unrank_packed(rank, cells)))
+            .expect("every rank is a valid layout");
+        if solvable_only && !board.is_solvable() {
+            return Err(pack::RankError::Unsolvable(rank));
+        }
+        Ok(board)
+    }
+
+    /// Generate a reproducible shuffled board.
+    /// * Shared by the bindings that generate boards, so the same seed gives the same
+    ///   board everywhere.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `seed` - Seed of the random number generator
+    /// * `shuffles` - Number of random moves to make from the solved board
+    ///
+    /// * Never returns the solved board for a positive number of shuffles, see
+    ///   `shuffle_to_bound`.
+    ///
+    /// # Returns
+    /// The shuffled board, costed against the solved board, or the problem with the size
+    pub fn generate(n: i32, seed: u64, shuffles: i32) -> Result<Board, BoardError> {
+        Board::generate_with_bound(n, seed, shuffles, DEFAULT_MIN_SHUFFLE_BOUND).map(|(board, _bound)| board)
+    }
+
+    /// Generate a reproducible shuffled board at least some distance from the goal.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `seed` - Seed of the random number generator
+    /// * `shuffles` - Number of random moves to make from the solved board
+    /// * `min_bound` - Smallest heuristic lower bound to walk on until, see
+    ///   `shuffle_to_bound`
+    ///
+    /// # Returns
+    /// The shuffled board, costed against the solved board, and its lower bound, or the
+    /// problem with the size
+    pub fn generate_with_bound(n: i32, seed: u64, shuffles: i32,
+                               min_bound: i32) -> Result<(Board, i32), BoardError> {
+        if n < 2 {
+            return Err(BoardError::InvalidSize(n));
+        }
+        let mut board = Board::new(n, None, 0, None);
+        let (_moves, bound) = board.shuffle_to_bound(shuffles, min_bound, &mut RngSource::seeded(seed));
+        Ok((Board::try_new(n, board.tiles.to_vec())?, bound))
+    }
+
+    /// Generate many reproducible shuffled boards across threads.
+    /// * Board i is generated like `generate` with a seed derived from `seed` and i, so
+    ///   the boards and their order don't depend on the number of threads.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the boards
+    /// * `count` - Number of boards to generate
+    /// * `shuffles` - Number of random moves to make from the solved board
+    /// * `seed` - Master seed
+    /// * `threads` - Number of threads to split the work across (at least 1 is used)
+    ///
+    /// # Returns
+    /// The boards in index order, or the problem with the size
+    #[cfg(all(not(alloc_only), not(target_arch = "wasm32")))]
+    pub fn generate_many(n: i32, count: usize, shuffles: i32, seed: u64,
+                         threads: usize) -> Result<Vec<Board>, BoardError> {
+        if n < 2 {
+            return Err(BoardError::InvalidSize(n));
+        }
+        let chunk_size = count.div_ceil(threads.max(1)).max(1);
+        let indices: Vec<usize> = (0..count).collect();
+        let boards = std::thread::scope(|scope| {
+            let workers: Vec<_> = indices.chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || {
+                    chunk.iter()
+                        .map(|&i| Board::generate(n, Board::_derive_seed(seed, i as u64), shuffles).unwrap())
+                        .collect::<Vec<Board>>()
+                }))
+                .collect();
+            // Join in spawn order, so boards stay in index order
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        });
+        Ok(boards)
+    }
+
+    /// Derive the seed of one board from a master seed (SplitMix64).
+    ///
+    /// # Parameters
+    /// * `seed` - Master seed
+    /// * `index` - Index of the board
+    ///
+    /// # Returns
+    /// Seed of the board
+    #[cfg(all(not(alloc_only), not(target_arch = "wasm32")))]
+    fn _derive_seed(seed: u64, index: u64) -> u64 {
+        let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Create the goal layout of a preset.
+    /// * The board isn't costed; the solver always costs against `GoalPreset::Standard`.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `preset` - Goal layout
+    ///
+    /// # Returns
+    /// The goal board
+    pub fn solved_with_preset(n: i32, preset: GoalPreset) -> Board {
+        let order = preset._cell_order(n as usize);
+        let mut tiles = vec![Tile::new(BLANK_TILE); order.len()];
+        // Blank-first numbers the cells after the blank from 1, the others from the start
+        let numbered = match preset {
+            GoalPreset::BlankFirst => &order[1..],
+            _ => &order[..order.len() - 1],
+        };
+        for (k, &cell) in numbered.iter().enumerate() {
+            tiles[cell] = Tile::new(k as i32 + 1);
+        }
+        Board::new(n, None, -1, Some(tiles.into_boxed_slice()))
+    }
+
+    /// Create the root game board.
+    ///
+    /// # Parameters
+    /// * `n` - Size of board
+    /// * `shuffle_n` - Number of times to shuffle the board
+    ///
+    /// # Returns
+    /// New game board, costed against the solved board
+    #[cfg(not(alloc_only))]
+    pub fn create_game_board(n: i32, shuffle_n: i32) -> Board {
+        // Create solved and unsolved boards
+        let solved_board = Board::new(n, None, -1, None);
+        let mut unsolved_board = Board::new(n, Some(&solved_board), 0, None);
+        // Shuffle tiles, which re-costs the board
+        unsolved_board.shuffle(shuffle_n);
+        unsolved_board
+    }
+
+    /// Count the inversions in the board.
+    /// * An inversion is a pair of tiles where the larger symbol comes first.
+    /// * The blank tile is ignored.
+    ///
+    /// # Returns
+    /// Number of inversions in the tile sequence
+    pub fn inversions(&self) -> i32 {
+        let symbols: Vec<i32> = self.tiles.iter()
+            .filter(|tile| !tile.is_blank())
+            .map(|tile| tile.symbol())
+            .collect();
+        let mut total = 0;
+        for i in 0..symbols.len() {
+            for j in i + 1..symbols.len() {
+                if symbols[i] > symbols[j] {
+                    total += 1;
+                }
+            }
+        }
+        total
+    }
+
+    /// Get the parity of the board.
+    /// * On even-width boards, the row distance of the blank from its goal
+    ///   row (the bottom row) is added to the inversion count.
+    ///
+    /// # Returns
+    /// 0 if the parity is even, 1 if odd
+    pub fn parity(&self) -> i32 {
+        let mut parity = self.inversions();
+        if self.n % 2 == 0 {
+            parity += (self.n - 1) - self.blank_index / self.n;
+        }
+        parity % 2
+    }
+
+    /// Check if the board can be solved.
+    /// * A board is solvable exactly when its parity matches that of the solved board (even).
+    ///
+    /// # Returns
+    /// Whether the board is solvable
+    pub fn is_solvable(&self) -> bool {
+        self.parity() == 0
+    }
+
+    /// Check if the board can reach the goal of a preset.
+    /// * The parity rule depends on where the goal puts the blank, see `solvability`.
+    ///
+    /// # Parameters
+    /// * `preset` - Goal layout
+    ///
+    /// # Returns
+    /// Whether the board is solvable towards the preset
+    pub fn is_solvable_with_preset(&self, preset: GoalPreset) -> bool {
+        self.solvability(&Board::solved_with_preset(self.n, preset)).is_solvable()
+    }
+
+    /// Explain whether the board can reach a goal.
+    /// * Works on unvalidated boards, e.g. with duplicate tiles or several blanks. The
+    ///   goal may use any symbols, but should hold each once plus a single blank.
+    /// * The board can reach the goal exactly when it has the goal's tiles and the
+    ///   parity of their permutation matches the parity of the blank's distance from
+    ///   its goal position.
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board
+    ///
+    /// # Returns
+    /// Report of every problem found
+    pub fn solvability(&self, goal: &Board) -> SolvabilityReport {
+        let mut problems = Vec::new();
+        if self.n != goal.n || self.tiles.len() != goal.tiles.len() {
+            problems.push(SolvabilityProblem::SizeMismatch { size: self.n, goal_size: goal.n });
+            return SolvabilityReport { problems };
+        }
+        let blanks = self.tiles.iter().filter(|tile| tile.is_blank()).count();
+        let goal_blanks = goal.tiles.iter().filter(|tile| tile.is_blank()).count();
+        if blanks != goal_blanks {
+            problems.push(SolvabilityProblem::BlankCount { blanks, goal_blanks });
+        }
+        let mut counts: BTreeMap<i32, usize> = BTreeMap::new();
+        for tile in self.tiles.iter().filter(|tile| !tile.is_blank()) {
+            *counts.entry(tile.symbol()).or_insert(0) += 1;
+        }
+        let goal_symbols: BTreeSet<i32> = goal.tiles.iter().map(|tile| tile.symbol()).collect();
+        let mut symbols: Vec<i32> = counts.keys().copied().collect();
+        symbols.sort_unstable();
+        for &symbol in &symbols {
+            if counts[&symbol] > 1 {
+                problems.push(SolvabilityProblem::DuplicateTile(symbol));
+            }
+        }
+        for &symbol in &symbols {
+            if !goal_symbols.contains(&symbol) {
+                problems.push(SolvabilityProblem::NotInGoal(symbol));
+            }
+        }
+        let mut missing: Vec<i32> = goal.tiles.iter()
+            .filter(|tile| !tile.is_blank() && !counts.contains_key(&tile.symbol()))
+            .map(|tile| tile.symbol())
+            .collect();
+        missing.sort_unstable();
+        problems.extend(missing.into_iter().map(SolvabilityProblem::NotOnBoard));
+        if problems.is_empty() && self._permutation_parity(goal) != self.move_count_parity(goal) {
+            problems.push(SolvabilityProblem::ParityMismatch { swap: self._parity_swap(goal) });
+        }
+        SolvabilityReport { problems }
+    }
+
+    /// Check if the board can reach a goal.
+    /// * The board must have the goal's tiles, and the parity of the permutation taking
+    ///   the goal's layout to the board's (counting the blank as a tile) must match
+    ///   `move_count_parity`: each move swaps the blank with a tile, flipping both.
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board
+    ///
+    /// # Returns
+    /// Nothing if the goal is reachable, otherwise every problem found, see
+    /// `solvability`
+    pub fn reachability(&self, goal: &Board) -> Result<(), Unreachable> {
+        let report = self.solvability(goal);
+        if report.is_solvable() {
+            Ok(())
+        } else {
+            Err(Unreachable { problems: report.problems })
+        }
+    }
+
+    /// Get the parity of the permutation taking a goal's layout to this board's,
+    /// counting the blank as a tile.
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board with the same tiles
+    ///
+    /// # Returns
+    /// 0 if the permutation is even, 1 if odd
+    fn _permutation_parity(&self, goal: &Board) -> usize {
+        let goal_index: BTreeMap<i32, usize> = goal.tiles.iter()
+            .enumerate()
+            .map(|(i, tile)| (tile.symbol(), i))
+            .collect();
+        let targets: Vec<usize> = self.tiles.iter().map(|tile| goal_index[&tile.symbol()]).collect();
+        let mut visited = vec![false; targets.len()];
+        let mut cycles = 0;
+        for start in 0..targets.len() {
+            if visited[start] {
+                continue;
+            }
+            cycles += 1;
+            let mut i = start;
+            while !visited[i] {
+                visited[i] = true;
+                i = targets[i];
+            }
+        }
+        (targets.len() - cycles) % 2
+    }
+
+    /// Get the parity of the number of moves in every path to a goal.
+    /// * Each move shifts the blank one cell, so a path's length has the parity of the
+    ///   blank's Manhattan distance from its goal position, whatever the tiles.
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board of the same size
+    ///
+    /// # Returns
+    /// 0 if the distance is even, 1 if odd
+    pub fn move_count_parity(&self, goal: &Board) -> usize {
+        let (blank, goal_blank) = (self.blank_index, goal.get_blank_index());
+        let distance = (blank / self.n - goal_blank / self.n).abs() + (blank % self.n - goal_blank % self.n).abs();
+        distance as usize % 2
+    }
+
+    /// Pick two tiles whose swap flips the parity of the board, putting one of them home.
+    /// * The first misplaced tile, in reading order, whose goal cell holds another tile
+    ///   is swapped with that tile.
+    /// * Otherwise the only misplaced tile is on the blank's goal cell, with the blank an
+    ///   even distance from it, so no swap puts a tile home; it is swapped with the
+    ///   first other tile.
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board with the same tiles, which this board can't reach
+    ///
+    /// # Returns
+    /// Symbols of the two tiles
+    fn _parity_swap(&self, goal: &Board) -> (i32, i32) {
+        let misplaced: Vec<Tile> = self.tiles.iter().zip(goal.tiles.iter())
+            .filter(|(tile, goal_tile)| !tile.is_blank() && tile != goal_tile)
+            .map(|(tile, _)| *tile)
+            .collect();
+        let first = *misplaced.first().expect("an unsolvable board has a misplaced tile");
+        misplaced.iter()
+            .find_map(|&tile| {
+                let home = goal.tiles.iter().position(|other| *other == tile)?;
+                let displaced = self.tiles[home];
+                (!displaced.is_blank()).then(|| (tile.symbol(), displaced.symbol()))
+            })
+            .unwrap_or_else(|| {
+                let other = self.tiles.iter()
+                    .find(|tile| !tile.is_blank() && **tile != first)
+                    .expect("an unsolvable board has at least two tiles");
+                (first.symbol(), other.symbol())
+            })
+    }
+
+    /// Check if two tiles are in linear conflict.
+    /// * Indices are 0-indexed and relative to the row/column they are in.
+    ///
+    /// # Parameters
+    /// * `tile_1` - Index of first tile
+    /// * `tile_2` - Index of first tile
+    /// * `tile_1_goal` - Goal index of first tile
+    /// * `tile_2_goal` - Goal index of second tile
+    ///
+    /// # Returns
+    /// Whether they are in linear conflict
+    fn _in_conflict(tile_1: i32, tile_2: i32, tile_1_goal: i32, tile_2_goal: i32) -> bool {
+        (tile_1 < tile_2 && tile_1_goal > tile_2_goal) ||
+            (tile_1 > tile_2 && tile_1_goal < tile_2_goal)
+    }
+
+    /// Shortcut for indexing the board tiles like a 2-dim array.
+    /// TODO: Consider changing tile to array of references to improve performance
+    ///
+    /// # Parameters
+    /// * `row` - Row index
+    /// * `col` - Column index
+    /// * `board` - The board to index (default: self)
+    ///
+    /// # Returns
+    /// Tile at index specified
+    pub fn index(&self, row: i32, col: i32, board: Option<&Board>) -> Tile {
+        let board = board.unwrap_or(self);
+        board.tiles[(row * self.n + col) as usize]
+    }
+
+    /// Shuffle the tiles using valid moves to ensure the puzzle is solvable.
+    /// * Seeded from the clock, see `RngSource::from_clock`; use `shuffle_with_rng` to
+    ///   choose the generator.
+    ///
+    /// # Parameters
+    /// * `shuffle_n` - Number of random moves to make
+    ///
+    /// # Returns
+    /// The moves made
+    #[cfg(not(alloc_only))]
+    pub fn shuffle(&mut self, shuffle_n: i32) -> String {
+        self.shuffle_with_rng(shuffle_n, &mut RngSource::from_clock())
+    }
+
+    /// Shuffle the tiles using a specific random number generator.
+    /// * Seeded generators give reproducible shuffles.
+    /// * Moves never undo the previous move, but the walk may still return to an
+    ///   earlier board; see `shuffle_without_revisits` to prevent that.
+    /// * Walks on past `shuffle_n` moves rather than end on the solved board, see
+    ///   `shuffle_to_bound`.
+    /// * A board that has been costed is re-costed against the solved board.
+    ///
+    /// # Parameters
+    /// * `shuffle_n` - Number of random moves to make
+    /// * `rng` - Random number generator used to pick moves
+    ///
+    /// # Returns
+    /// The moves made
+    pub fn shuffle_with_rng<R: Rng>(&mut self, shuffle_n: i32, rng: &mut R) -> String {
+        self._shuffle(shuffle_n, rng, false, DEFAULT_MIN_SHUFFLE_BOUND).0
+    }
+
+    /// Shuffle the tiles, walking on until the board is at least some distance from
+    /// the goal.
+    /// * After `shuffle_n` moves, keeps making moves one at a time while the heuristic
+    ///   lower bound (Manhattan distance plus linear conflicts) is below `min_bound`,
+    ///   for at most `MAX_SHUFFLE_RETRIES` more moves.
+    /// * Asking for no moves leaves the board as it is.
+    ///
+    /// # Parameters
+    /// * `shuffle_n` - Number of random moves to make
+    /// * `min_bound` - Smallest lower bound to accept
+    /// * `rng` - Random number generator used to pick moves
+    ///
+    /// # Returns
+    /// The moves made and the lower bound reached, which is below `min_bound` only if
+    /// the extra moves ran out
+    pub fn shuffle_to_bound<R: Rng>(&mut self, shuffle_n: i32, min_bound: i32, rng: &mut R) -> (String, i32) {
+        self._shuffle(shuffle_n, rng, false, min_bound)
+    }
+
+    /// Shuffle the tiles without ever returning to a board seen during the walk.
+    /// * The walk stops early if every neighbouring board has already been seen.
+    /// * Seen boards are tracked packed, so on boards larger than 4x4 this only
+    ///   prevents undoing the previous move.
+    ///
+    /// # Parameters
+    /// * `shuffle_n` - Number of random moves to make
+    /// * `rng` - Random number generator used to pick moves
+    ///
+    /// # Returns
+    /// The moves made
+    pub fn shuffle_without_revisits<R: Rng>(&mut self, shuffle_n: i32, rng: &mut R) -> String {
+        self._shuffle(shuffle_n, rng, true, DEFAULT_MIN_SHUFFLE_BOUND).0
+    }
+
+    /// Shuffle the tiles with random legal moves.
+    ///
+    /// # Parameters
+    /// * `shuffle_n` - Number of random moves to make
+    /// * `rng` - Random number generator used to pick moves
+    /// * `avoid_revisits` - Whether to skip moves leading to boards seen during the walk
+    /// * `min_bound` - Smallest lower bound to walk on until, see `shuffle_to_bound`
+    ///
+    /// # Returns
+    /// The moves made and the lower bound of the board
+    fn _shuffle<R: Rng>(&mut self, shuffle_n: i32, rng: &mut R, avoid_revisits: bool,
+                        min_bound: i32) -> (String, i32) {
+        // Update the blank index
+        self.blank_index = self.get_blank_index();
+        let track_seen = avoid_revisits && self.n <= pack::MAX_PACKED_SIZE;
+        let mut seen = BTreeSet::new();
+        if track_seen {
+            seen.insert(pack::encode_tiles(&self.tiles));
+        }
+        let mut shuffle_moves = String::new();
+        let mut last_move = '\0';
+        let mut made = 0;
+        loop {
+            if made >= shuffle_n && (shuffle_n == 0 || made >= shuffle_n.saturating_add(MAX_SHUFFLE_RETRIES)
+                || self.heuristic_breakdown(None).total >= min_bound) {
+                break;
+            }
+            // Get all legal moves that don't undo the last one or revisit a board
+            let moves: Vec<(char, Board)> = self.neighbors_unordered_raw()
+                .filter(|(tile_move, _)| opposite_direction(*tile_move) != last_move)
+                .filter(|(_, next)| !track_seen || !seen.contains(&pack::encode_tiles(&next.tiles)))
+                .collect();
+            // Get a random move
+            let (board_move, next) = match moves.choose(rng) {
+                Some(choice) => choice.clone(),
+                None => break,
+            };
+            // Perform that move
+            *self = next;
+            if track_seen {
+                seen.insert(pack::encode_tiles(&self.tiles));
+            }
+            shuffle_moves.push(board_move);
+            last_move = board_move;
+            made += 1;
+        }
+        if self.cost != -1 {
+            let solved_board = Board::new(self.n, None, -1, None);
+            self.cost = self.g() + self.store_h(&solved_board, Heuristic::LinearConflicts);
+        }
+        (shuffle_moves, self.heuristic_breakdown(None).total)
+    }
+
+    /// Generate a board at an exact optimal distance from the solved board.
+    /// * Searches breadth-first out from the solved board, so every board in the final
+    ///   layer is exactly `target_depth` moves away, then picks one at random.
+    /// * Visited boards are tracked packed, so only boards that can be packed
+    ///   (n <= 4) are supported; 3x3 boards are feasible out to their diameter of 31.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `target_depth` - Optimal distance of the board from the solved board
+    /// * `rng` - Random number generator used to pick the board
+    ///
+    /// # Returns
+    /// The board and an optimal solution for it (not necessarily the only one), or
+    /// `None` if the depth exceeds the diameter of the puzzle or the board can't be packed
+    pub fn random_walk_exact<R: Rng>(n: i32, target_depth: usize, rng: &mut R) -> Option<(Board, String)> {
+        if n > pack::MAX_PACKED_SIZE {
+            return None;
+        }
+        let goal = Board::new(n, None, 0, None);
+        let goal_key = pack::encode_tiles(goal.tiles());
+        // Packed board -> (packed parent, move from the parent)
+        let mut parents: BTreeMap<u64, (u64, char)> = BTreeMap::new();
+        parents.insert(goal_key, (goal_key, '\0'));
+        let mut layer = vec![goal];
+        for _depth in 0..target_depth {
+            let mut next_layer = Vec::new();
+            for board in &layer {
+                let key = pack::encode_tiles(board.tiles());
+                for (tile_move, child) in board.neighbors_unordered_raw() {
+                    let child_key = pack::encode_tiles(child.tiles());
+                    if let Entry::Vacant(entry) = parents.entry(child_key) {
+                        entry.insert((key, tile_move));
+                        next_layer.push(child);
+                    }
+                }
+            }
+            // The puzzle has no boards this far from the solved board
+            if next_layer.is_empty() {
+                return None;
+            }
+            layer = next_layer;
+        }
+        let board = layer.choose(rng)?;
+        // Undo the generating moves back to the solved board
+        let mut solution = String::new();
+        let mut key = pack::encode_tiles(board.tiles());
+        while key != goal_key {
+            let (parent, tile_move) = parents[&key];
+            solution.push(opposite_direction(tile_move));
+            key = parent;
+        }
+        let board = Board::try_new(n, board.tiles().to_vec()).ok()?;
+        Some((board, solution))
+    }
+
+    /// Find the positions where two boards differ.
+    ///
+    /// # Parameters
+    /// * `other` - Board of the same size to compare with
+    ///
+    /// # Returns
+    /// Differing positions in row-major order
+    pub fn diff(&self, other: &Board) -> Vec<TileDiff> {
+        assert_eq!(self.n, other.n, "can't diff boards of different sizes");
+        (0..self.n2)
+            .filter(|&i| self.tiles[i as usize] != other.tiles[i as usize])
+            .map(|i| TileDiff {
+                row: i / self.n,
+                col: i % self.n,
+                tile_in_self: self.tiles[i as usize].symbol(),
+                tile_in_other: other.tiles[i as usize].symbol(),
+            })
+            .collect()
+    }
+
+    /// Identify the tile that slid to turn this board into the next.
+    ///
+    /// # Parameters
+    /// * `next` - Board after the move
+    ///
+    /// # Returns
+    /// Symbol of the tile that slid and the direction it slid in, or `None` unless the
+    /// boards differ by exactly one legal move
+    pub fn moved_tile(&self, next: &Board) -> Option<(i32, char)> {
+        if self.n != next.n {
+            return None;
+        }
+        let blank_index = self.get_blank_index();
+        let next_blank_index = next.get_blank_index();
+        let blank_move = *MOVES.iter().find(|&&tile_move| {
+            self.is_legal_move(tile_move) && self.translate_index(blank_index, tile_move) == next_blank_index
+        })?;
+        let mut moved = self.clone();
+        moved.move_blank_tile(blank_move);
+        if moved.tiles != next.tiles {
+            return None;
+        }
+        // The tile slides the opposite way to the blank
+        Some((next.tiles[blank_index as usize].symbol(), opposite_direction(blank_move)))
+    }
+
+    /// Make a sequence of moves written in a notation.
+    /// * Moves are read with `parse_path`, so they may be separated and in either case.
+    ///
+    /// # Parameters
+    /// * `moves` - Moves to make
+    /// * `notation` - Notation the moves are written in
+    ///
+    /// # Returns
+    /// The board after the moves, or the first move that couldn't be made
+    pub fn apply_moves(&self, moves: &str, notation: Notation) -> Result<Board, IllegalMove> {
+        let mut board = self.clone();
+        for (index, blank_move) in parse_path(moves, notation)?.into_iter().enumerate() {
+            board.check_move(blank_move).map_err(|rejection| IllegalMove { index, rejection })?;
+            board.move_blank_tile(blank_move);
+        }
+        Ok(board)
+    }
+
+    /// Apply a symmetry to the board.
+    /// * The new board is costed against the solved board, and keeps this board's depth.
+    ///
+    /// # Parameters
+    /// * `transform` - Symmetry to apply
+    ///
+    /// # Returns
+    /// The transformed board
+    pub fn transform(&self, transform: Transform) -> Board {
+        let mut tiles = self.tiles.clone();
+        for (i, tile) in self.tiles.iter().enumerate() {
+            let (row, col) = transform.map_position(self.n, i as i32 / self.n, i as i32 % self.n);
+            tiles[(row * self.n + col) as usize] = *tile;
+        }
+        let solved_board = Board::new(self.n, None, -1, None);
+        Board::new(self.n, Some(&solved_board), self.depth, Some(tiles))
+    }
+
+    /// Rotate the board a quarter turn clockwise.
+    ///
+    /// # Returns
+    /// The rotated board
+    pub fn rotate_cw(&self) -> Board {
+        self.transform(Transform::RotateCw)
+    }
+
+    /// Rotate the board a quarter turn counter-clockwise.
+    ///
+    /// # Returns
+    /// The rotated board
+    pub fn rotate_ccw(&self) -> Board {
+        self.transform(Transform::RotateCcw)
+    }
+
+    /// Mirror the board left to right.
+    ///
+    /// # Returns
+    /// The mirrored board
+    pub fn flip_horizontal(&self) -> Board {
+        self.transform(Transform::FlipHorizontal)
+    }
+
+    /// Mirror the board top to bottom.
+    ///
+    /// # Returns
+    /// The mirrored board
+    pub fn flip_vertical(&self) -> Board {
+        self.transform(Transform::FlipVertical)
+    }
+
+    /// Extract a region of the board as a board of its own, e.g. for solving the last
+    /// layers once the outer rows and columns are in place.
+    /// * The region must be square and reach the bottom-right corner, so its goal has the
+    ///   blank in the same place as the full board's goal.
+    /// * Tiles are renumbered to their goal positions within the region. Tiles whose goal
+    ///   is outside the region are wildcards: they take the symbols of the region's tiles
+    ///   that are outside it, in reading order, swapping the last two if needed so the
+    ///   sub-board is solvable.
+    ///
+    /// # Parameters
+    /// * `top` - Row of the region's top edge
+    /// * `left` - Column of the region's left edge
+    /// * `rows` - Number of rows in the region
+    /// * `cols` - Number of columns in the region
+    ///
+    /// # Returns
+    /// The sub-board, or why the region can't be extracted
+    pub fn sub_board(&self, top: usize, left: usize, rows: usize, cols: usize) -> Result<Board, BoardError> {
+        let n = self.n as usize;
+        if rows == 0 || rows != cols || top + rows != n || left + cols != n {
+            return Err(BoardError::InvalidRegion { top, left, rows, cols });
+        }
+        let blank_index = self.blank_index as usize;
+        if blank_index / n < top || blank_index % n < left {
+            return Err(BoardError::BlankOutsideRegion);
+        }
+        let mut tiles = Vec::with_capacity(rows * cols);
+        let mut placed = vec![false; rows * cols];
+        let mut wildcards = Vec::new();
+        for row in top..n {
+            for col in left..n {
+                let tile = self.tiles[row * n + col];
+                let goal = (tile.symbol() - 1) as usize;
+                if tile.is_blank() {
+                    tiles.push(tile);
+                } else if goal / n >= top && goal % n >= left {
+                    let symbol = (goal / n - top) * cols + goal % n - left + 1;
+                    placed[symbol - 1] = true;
+                    tiles.push(Tile::new(symbol as i32));
+                } else {
+                    wildcards.push(tiles.len());
+                    tiles.push(tile);
+                }
+            }
+        }
+        let missing = (1..rows * cols).filter(|symbol| !placed[symbol - 1]);
+        for (&i, symbol) in wildcards.iter().zip(missing) {
+            tiles[i] = Tile::new(symbol as i32);
+        }
+        let solved_board = Board::new(rows as i32, None, -1, None);
+        let board = Board::new(rows as i32, Some(&solved_board), 0, Some(tiles.clone().into_boxed_slice()));
+        if board.is_solvable() || wildcards.len() < 2 {
+            return Ok(board);
+        }
+        tiles.swap(wildcards[wildcards.len() - 2], wildcards[wildcards.len() - 1]);
+        Ok(Board::new(rows as i32, Some(&solved_board), 0, Some(tiles.into_boxed_slice())))
+    }
+
+    /// Translate a solution of this sub-board into moves on the board it was extracted from.
+    /// * Paths are blank moves, which are relative to the blank, so a path that stays in
+    ///   the region is the same path on the full board. In debug builds the path is
+    ///   checked to stay in the region.
+    ///
+    /// # Parameters
+    /// * `path` - Blank moves solving this sub-board
+    /// * `offset` - Row and column of the region's top-left corner in the full board
+    ///
+    /// # Returns
+    /// Blank moves for the full board
+    pub fn embed_path(&self, path: &str, offset: (usize, usize)) -> String {
+        debug_assert!(
+            self.apply_moves(path, Notation::BlankMoves).is_ok(),
+            "path {:?} leaves the region at {:?}", path, offset
+        );
+        path.to_string()
+    }
+
+    /// Check if a move keeps the blank tile on the board.
+    /// * Boards keep no move history, so reversing the previous move is legal; search
+    ///   pruning of back-steps is up to the solver.
+    ///
+    /// # Params
+    /// * `move_direction` - Direction to move
+    ///
+    /// # Returns
+    /// Whether or not the move is legal
+    pub fn is_legal_move(&self, move_direction: char) -> bool {
+        self.check_move(move_direction).is_ok()
+    }
+
+    /// Check if a move keeps the blank tile on the board, explaining why not.
+    ///
+    /// # Params
+    /// * `move_direction` - Direction to move
+    ///
+    /// # Returns
+    /// Nothing if the move is legal, otherwise the reason it isn't
+    pub fn check_move(&self, move_direction: char) -> Result<(), MoveRejection> {
+        match move_direction {
+            // Check if up move would be out of bounds
+            UP if self.blank_index - self.n < 0 => Err(MoveRejection::BlankOnTopEdge),
+            // Check if down move would be out of bounds
+            DOWN if self.blank_index + self.n >= self.n2 => Err(MoveRejection::BlankOnBottomEdge),
+            // Check if left move would be out of bounds
+            LEFT if self.blank_index % self.n == 0 => Err(MoveRejection::BlankOnLeftEdge),
+            // Check if right move would be out of bounds
+            RIGHT if (self.blank_index + 1) % self.n == 0 => Err(MoveRejection::BlankOnRightEdge),
+            UP | DOWN | LEFT | RIGHT => Ok(()),
+            _ => Err(MoveRejection::UnknownDirection(move_direction)),
+        }
+    }
+
+    /// Get the legal moves that can be made.
+    ///
+    /// # Returns
+    /// The legal moves that can be made
+    pub fn legal_moves(&self) -> Vec<char> {
+        let mut moves: Vec<char> = Vec::new();
+        for tile_move in &MOVES {
+            if self.is_legal_move(*tile_move) {
+                moves.push(*tile_move);
+            }
+        }
+        moves
+    }
+
+    /// Translate tile indices based on position and move direction.
+    ///
+    /// # Parameters
+    /// * `position` - Index of tile
+    /// * `move_direction` - Direction to move
+    ///
+    /// # Returns
+    /// Resulting index from translation
+    pub fn translate_index(&self, position: i32, move_direction: char) -> i32 {
+        if move_direction == UP {
+            return position - self.n;
+        }
+        if move_direction == DOWN {
+            return position + self.n;
+        }
+        if move_direction == LEFT {
+            return position - 1;
+        }
+        position + 1
+    }
+
+    /// Move the empty space in the specified direction.
+    ///
+    /// # Parameters
+    /// * `move_direction` - Direction to move the blank tile
+    pub fn move_blank_tile(&mut self, move_direction: char) {
+        // Get index to swap with
+        let swap_i = self.translate_index(self.blank_index, move_direction);
+        if let Some(zobrist) = &self.zobrist {
+            let (blank, tile) = (self.blank_index as usize, swap_i as usize);
+            self.zobrist_hash ^= zobrist.value(blank, self.tiles[blank]) ^ zobrist.value(tile, self.tiles[tile])
+                ^ zobrist.value(blank, self.tiles[tile]) ^ zobrist.value(tile, self.tiles[blank]);
+        }
+        self.tiles.swap(swap_i as usize, self.blank_index as usize);
+        // Update blank index
+        self.blank_index = swap_i;
+    }
+
+    /// Keep the board's Zobrist hash from now on, e.g. to key a visited set on it.
+    /// * The hash is computed once here, then updated by every move, including on the
+    ///   boards made by `neighbor`.
+    ///
+    /// # Parameters
+    /// * `zobrist` - Table for the board's size
+    ///
+    /// # Returns
+    /// The board
+    ///
+    /// # Panics
+    /// If the table is for another size
+    pub fn with_zobrist(mut self, zobrist: Arc<ZobristTable>) -> Board {
+        assert_eq!(zobrist.size(), self.n, "Zobrist table is for another board size");
+        self.zobrist_hash = zobrist.hash(&self.tiles);
+        self.zobrist = Some(zobrist);
+        self
+    }
+
+    /// Get the Zobrist hash of the board, see `with_zobrist`.
+    ///
+    /// # Returns
+    /// The hash, or `None` if the board keeps none
+    pub fn zobrist_hash(&self) -> Option<u64> {
+        self.zobrist.as_ref().map(|_| self.zobrist_hash)
+    }
+
+    /// Get the board reached by moving the blank tile.
+    /// * The move is appended to the path and the depth incremented.
+    /// * A cached Manhattan cost is updated with `manhattan_delta`, so it must have been
+    ///   taken against the solved board. Linear conflicts and the total cost depend on the
+    ///   heuristic, so they are reset to -1 for the caller to cost.
+    ///
+    /// # Parameters
+    /// * `move_direction` - Legal direction to move the blank tile
+    ///
+    /// # Returns
+    /// The neighbouring board
+    pub fn neighbor(&self, move_direction: char) -> Board {
+        let mut neighbor = self.clone();
+        if self.manhattan_cost_cache >= 0 {
+            neighbor.manhattan_cost_cache += self.manhattan_delta(move_direction);
+        }
+        neighbor.linear_conflicts_cache = -1;
+        neighbor.cost = -1;
+        neighbor.path.push(move_direction);
+        neighbor.move_blank_tile(move_direction);
+        neighbor.depth = self.depth + 1;
+        neighbor
+    }
+
+    /// Iterate over the boards reachable in one move, see `neighbor`.
+    /// * Boards are made lazily, in the order of `legal_moves`.
+    ///
+    /// # Returns
+    /// Each legal move with the board it reaches
+    pub fn neighbors(&self) -> impl Iterator<Item = (char, Board)> + '_ {
+        self.legal_moves().into_iter().map(move |tile_move| (tile_move, self.neighbor(tile_move)))
+    }
+
+    /// Iterate over the boards reachable in one move, only moving the tiles.
+    /// * For searches that only need the layouts, e.g. breadth-first: depth, path and
+    ///   cached costs are copied from this board unchanged.
+    /// * Boards come in the order of `legal_moves`, which seeded shuffles rely on.
+    ///
+    /// # Returns
+    /// Each legal move with the board it reaches
+    pub fn neighbors_unordered_raw(&self) -> impl Iterator<Item = (char, Board)> + '_ {
+        self.legal_moves().into_iter().map(move |tile_move| {
+            let mut neighbor = self.clone();
+            neighbor.move_blank_tile(tile_move);
+            (tile_move, neighbor)
+        })
+    }
+
+    /// Get the change in Manhattan cost against the solved board caused by a move.
+    /// * Only the tile swapped with the blank moves, so the cost can be updated
+    ///   without rescanning the board.
+    ///
+    /// # Parameters
+    /// * `move_direction` - Legal direction to move the blank tile
+    ///
+    /// # Returns
+    /// Manhattan cost after the move minus the cost before it
+    pub fn manhattan_delta(&self, move_direction: char) -> i32 {
+        let from = self.translate_index(self.blank_index, move_direction);
+        let solved_i = self.tiles[from as usize].symbol() - 1;
+        let distance = |i: i32| ((i % self.n) - (solved_i % self.n)).abs()
+            + ((i / self.n) - (solved_i / self.n)).abs();
+        distance(self.blank_index) - distance(from)
+    }
+
+    /// Get the Manhattan cost of the current board compared with the solved board.
+    ///
+    /// # Returns
+    /// Manhattan cost of board
+    fn _manhattan_cost(&self, solved_board: &Board) -> i32 {
+        let mut cost = 0;
+        // Check each tile
+        for i in 0..self.n2 {
+            // Do not use blank tile
+            if i == self.blank_index {
+                continue;
+            }
+            // Get index of tile in solved board
+            let mut solved_i = -1;
+            for j in 0..self.n2 {
+                if solved_board.tiles[j as usize] == self.tiles[i as usize] {
+                    solved_i = j;
+                    break;
+                }
+            }
+            // Get distance for x-axis
+            cost += ((i % self.n) - (solved_i % self.n)).abs();
+            // Get distance for y-axis
+            cost += ((i / self.n) - (solved_i / self.n)).abs();
+        }
+        cost
+    }
+
+    /// Get each tile's Manhattan distance from its cell in a goal, in one pass.
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board of the same size
+    ///
+    /// # Returns
+    /// Symbol and distance of every non-blank tile, in board order
+    pub fn per_tile_distances(&self, goal: &Board) -> Vec<(i32, u32)> {
+        let goal_indices = _goal_indices(goal);
+        self.tiles.iter().enumerate()
+            .filter(|(_, tile)| !tile.is_blank())
+            .map(|(i, tile)| {
+                let (i, goal_i) = (i as i32, goal_indices[tile.symbol() as usize]);
+                let distance = ((i % self.n) - (goal_i % self.n)).abs() + ((i / self.n) - (goal_i / self.n)).abs();
+                (tile.symbol(), distance as u32)
+            })
+            .collect()
+    }
+
+    /// Count the tiles that aren't in their solved cell.
+    /// * The blank isn't counted.
+    ///
+    /// # Returns
+    /// Number of misplaced tiles
+    pub fn misplaced_count(&self) -> usize {
+        self.tiles.iter().enumerate()
+            .filter(|(i, tile)| !tile.is_blank() && tile.symbol() != *i as i32 + 1)
+            .count()
+    }
+
+    /// Get the path cost of the board, g in A*.
+    /// * Boards that aren't search nodes (depth -1, e.g. goals) count as 0.
+    ///
+    /// # Returns
+    /// Moves made to reach the board
+    pub fn g(&self) -> i32 {
+        self.depth.max(0)
+    }
+
+    /// Estimate the moves left to reach a goal, h in A*.
+    /// * Manhattan distance plus linear conflicts; never more than the true distance.
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board of the same size
+    ///
+    /// # Returns
+    /// Heuristic of the board, leaving out the moves made so far
+    pub fn h(&self, goal: &Board) -> i32 {
+        self._manhattan_cost(goal) + self.linear_conflicts(goal)
+    }
+
+    /// Get the heuristic last stored on the board by `store_h` or `store_h_cached`.
+    ///
+    /// # Returns
+    /// Stored Manhattan cost plus the rest of the heuristic
+    pub fn stored_h(&self) -> i32 {
+        self.manhattan_cost_cache + self.linear_conflicts_cache
+    }
+
+    /// Calculate the heuristic of the board and store its parts on the board.
+    /// - Caches the manhattan cost and the rest of the heuristic above it: the number of
+    ///   linear conflicts, 0 for Manhattan alone, or the difference for other heuristics
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board to estimate the distance to
+    /// * `heuristic` - Heuristic to use
+    ///
+    /// # Returns
+    /// Heuristic of the board, see `h`
+    pub fn store_h(&mut self, goal: &Board, heuristic: Heuristic) -> i32 {
+        self.manhattan_cost_cache = self._manhattan_cost(goal);
+        self.linear_conflicts_cache =
+            self._h_above_manhattan(goal, heuristic, &mut |board: &Board| board.linear_conflicts(goal));
+        self.stored_h()
+    }
+
+    /// Calculate the heuristic of the board and store its parts on the board, counting
+    /// linear conflicts through a cache.
+    /// - Caches the manhattan cost and the rest of the heuristic above it, see `store_h`
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board to estimate the distance to
+    /// * `heuristic` - Heuristic to use
+    /// * `conflict_cache` - Cache of row and column conflicts against `goal`
+    ///
+    /// # Returns
+    /// Heuristic of the board, see `h`
+    pub fn store_h_cached(&mut self, goal: &Board, heuristic: Heuristic, conflict_cache: &mut ConflictCache) -> i32 {
+        self.manhattan_cost_cache = self._manhattan_cost(goal);
+        self.linear_conflicts_cache =
+            self._h_above_manhattan(goal, heuristic, &mut |board: &Board| conflict_cache.linear_conflicts(board));
+        self.stored_h()
+    }
+
+    /// Calculate how far a heuristic of the board is above its cached Manhattan cost.
+    /// * The parts of a `Max` are each evaluated on the board, cheapest first.
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board to estimate the distance to
+    /// * `heuristic` - Heuristic to use
+    /// * `linear_conflicts` - Counts the linear conflicts of the board against `goal`
+    ///
+    /// # Returns
+    /// Heuristic of the board minus `manhattan_cost_cache`, which can be negative
+    fn _h_above_manhattan(&self, goal: &Board, heuristic: Heuristic,
+                          linear_conflicts: &mut dyn FnMut(&Board) -> i32) -> i32 {
+        match heuristic {
+            Heuristic::Manhattan => 0,
+            Heuristic::LinearConflicts => linear_conflicts(self),
+            Heuristic::InversionDistance => self.inversion_distance(goal) - self.manhattan_cost_cache,
+            Heuristic::Max(parts) => {
+                // Linear conflicts are the most expensive to count, so they go last
+                let mut parts: Vec<Heuristic> = parts.iter().collect();
+                parts.sort_by_key(|&part| part == Heuristic::LinearConflicts);
+                parts.into_iter().map(|part| self._h_above_manhattan(goal, part, linear_conflicts)).max().unwrap_or(0)
+            }
         }
-        board
     }
 
-    /// Get index of the blank tile.
+    /// Calculate the inversion distance of the board.
+    /// * A vertical move slides one tile past the n - 1 tiles between its cells in
+    ///   row-major order, changing the inversions of that order by at most n - 1, and
+    ///   horizontal moves don't change it. At least inversions / (n - 1) vertical moves,
+    ///   rounded up, are needed then, and the horizontal moves are bounded the same way
+    ///   in column-major order.
+    ///
+    /// # Parameters
+    /// * `solved_board` - Solved board to calculate the distance against
     ///
     /// # Returns
-    /// Index of blank tile
-    pub fn get_blank_index(&self) -> i32 {
-        for i in 0..self.n2 as usize {
-            if self.tiles[i].is_blank() {
-                return i as i32;
+    /// Lower bounds on the vertical and horizontal moves, added together
+    fn inversion_distance(&self, solved_board: &Board) -> i32 {
+        let n = self.n as usize;
+        let row_major: Vec<usize> = (0..n * n).collect();
+        let column_major: Vec<usize> = (0..n * n).map(|i| i % n * n + i / n).collect();
+        let mut distance = 0;
+        for order in [row_major, column_major].iter() {
+            // Rank of each tile in the order on the solved board
+            let ranks: BTreeMap<&Tile, usize> = order.iter()
+                .map(|&i| &solved_board.tiles[i])
+                .filter(|tile| !tile.is_blank())
+                .enumerate()
+                .map(|(rank, tile)| (tile, rank))
+                .collect();
+            let sequence: Vec<usize> = order.iter()
+                .map(|&i| &self.tiles[i])
+                .filter(|tile| !tile.is_blank())
+                .map(|tile| ranks[tile])
+                .collect();
+            let mut inversions = 0;
+            for (i, rank) in sequence.iter().enumerate() {
+                inversions += sequence[i + 1..].iter().filter(|&later| later < rank).count();
+            }
+            if inversions > 0 {
+                distance += (inversions - 1) / (n - 1) + 1;
             }
         }
-        return -1;
-    }
-
-//    /// Create the root game board.
-//    ///
-//    /// # Parameters
-//    /// * `boardSize` - Size of board
-//    /// * `shuffle_n` - Number of times to shuffle the board
-//    ///
-//    /// # Returns
-//    /// New game board
-//    pub fn create_game_board<'a>(boardSize: i32, shuffle_n: i32) -> Board<'a> {
-//        // Create solved and unsolved boards
-//        let solved_board = Board::new(boardSize, None, -1, None, []);
-//        let mut unsolved_board = Board::new(boardSize, Some(&solved_board), 0, None, []);
-//        // Shuffle tiles
-//        unsolved_board.shuffle(shuffle_n);
-//        unsolved_board
-//    }
-
-//    /// Reset previous boards that have been encountered
-//    pub fn reset_previous_boards(previous_boards: &mut HashSet<Board>) {
-//        previous_boards.clear();
-//    }
-
-//    /// Find if board has already been encountered.
-//    ///
-//    /// # Returns
-//    /// Whether this board has been seen before
-//    pub fn previously_encountered(&self, previous_boards: &mut HashSet<Board>) -> bool {
-//        previous_boards.contains(self)
-//    }
-//
-//    /// Add board to previously encountered.
-//    ///
-//    /// Consider not doing this hear to prevent the copy from taking place (move directly in)
-//    pub fn add_to_previously_encountered(&'static self, previous_boards: &'static mut HashSet<Board>) {
-//        previous_boards.insert(self.clone());
-//    }
+        distance as i32
+    }
 
-    /// Check if two tiles are in linear conflict.
-    /// * Indices are 0-indexed and relative to the row/column they are in.
+    /// Calculate cost/heuristic for board.
+    /// - Caches the manhattan cost and number of linear conflicts
     ///
     /// # Parameters
-    /// * `tile_1` - Index of first tile
-    /// * `tile_2` - Index of first tile
-    /// * `tile_1_goal` - Goal index of first tile
-    /// * `tile_2_goal` - Goal index of second tile
+    /// * `solved_board` - Solved board to calculate cost against
     ///
     /// # Returns
-    /// Whether they are in linear conflict
-    fn _in_conflict(tile_1: i32, tile_2: i32, tile_1_goal: i32, tile_2_goal: i32) -> bool {
-        (tile_1 < tile_2 && tile_1_goal > tile_2_goal) ||
-            (tile_1 > tile_2 && tile_1_goal < tile_2_goal)
+    /// Cost for board
+    #[deprecated(note = "use g() + store_h(), which keep the path cost and the heuristic apart")]
+    pub fn get_cost(&mut self, solved_board: &Board) -> i32 {
+        self.g() + self.store_h(solved_board, Heuristic::LinearConflicts)
     }
 
-    /// Shortcut for indexing the board tiles like a 2-dim array.
-    /// TODO: Consider changing tile to array of references to improve performance
+    /// Calculate cost for board using a specific heuristic.
+    /// - Caches the manhattan cost and number of linear conflicts (0 when unused)
     ///
     /// # Parameters
-    /// * `row` - Row index
-    /// * `col` - Column index
-    /// * `board` - The board to index (default: self)
+    /// * `solved_board` - Solved board to calculate cost against
+    /// * `heuristic` - Heuristic to use
     ///
     /// # Returns
-    /// Tile at index specified
-    pub fn index(&self, row: i32, col: i32, board: Option<&Board>) -> Tile {
-        let board = if board == None { self } else { board.unwrap() };
-        board.tiles[(row * self.n + col) as usize]
+    /// Cost for board
+    #[deprecated(note = "use g() + store_h(), which keep the path cost and the heuristic apart")]
+    pub fn get_cost_with(&mut self, solved_board: &Board, heuristic: Heuristic) -> i32 {
+        self.g() + self.store_h(solved_board, heuristic)
     }
 
-//    /// Shuffle the tiles using valid moves to ensure the puzzle is solvable.
-//    ///
-//    /// # Parameters
-//    /// * `boardSize` - Number of random moves to make
-//    pub fn shuffle(&mut self, boardSize: i32) {
-//        for _i in 0..boardSize {
-//            // Update the blank index
-//            self.blank_index = self.get_blank_index();
-//            // Get all valid moves
-//            let moves: Vec<char> = self.get_moves();
-//            // Get a random move
-//            let board_move = *moves.choose(&mut rand::thread_rng()).unwrap();
-//            // Perform that move
-//            self.move_blank_tile(board_move);
-//        }
-//        // Update the blank index
-//        self.blank_index = self.get_blank_index();
-//    }
-
-    /// Check if a move is valid.
+    /// Calculate cost for board using a specific heuristic, counting linear conflicts
+    /// through a cache.
+    /// - Caches the manhattan cost and number of linear conflicts (0 when unused)
     ///
-    /// # Params
-    /// * `move_direction` - Direction to move
+    /// # Parameters
+    /// * `solved_board` - Solved board to calculate cost against
+    /// * `heuristic` - Heuristic to use
+    /// * `conflict_cache` - Cache of row and column conflicts against `solved_board`
     ///
     /// # Returns
-    /// Whether or not the move is valid
-    pub fn is_valid_move(&self, move_direction: char) -> bool {
-        // Check if move would be back-stepping
-        if OPPOSITE_DIRECTIONS.get(&move_direction).unwrap() == &self.last_direction {
-            return false;
-        }
-        // Check if up move would be out of bounds
-        if move_direction == UP && self.blank_index - self.n < 0 {
-            return false;
-        }
-        // Check if down move would be out of bounds
-        if move_direction == DOWN && self.blank_index + self.n >= self.n2 {
-            return false;
-        }
-        // Check if left move would be out of bounds
-        if move_direction == LEFT && self.blank_index % self.n == 0 {
-            return false;
-        }
-        // Check if right move would be out of bounds
-        !(move_direction == RIGHT && (self.blank_index + 1) % self.n == 0)
+    /// Cost for board
+    #[deprecated(note = "use g() + store_h_cached(), which keep the path cost and the heuristic apart")]
+    pub fn get_cost_cached(&mut self, solved_board: &Board, heuristic: Heuristic,
+                           conflict_cache: &mut ConflictCache) -> i32 {
+        self.g() + self.store_h_cached(solved_board, heuristic, conflict_cache)
     }
 
-    /// Get the available moves that can be made.
+    /// Calculate each component of the board's cost.
+    ///
+    /// # Parameters
+    /// * `goal` - Board to calculate cost against (the solved board when `None`)
     ///
     /// # Returns
-    /// The available moves that can be made
-    pub fn get_moves(&self) -> Vec<char> {
-        let mut moves: Vec<char> = Vec::new();
-        for tile_move in &MOVES {
-            if self.is_valid_move(*tile_move) {
-                moves.push(*tile_move);
+    /// The cost components and their total
+    pub fn heuristic_breakdown(&self, goal: Option<&Board>) -> HeuristicBreakdown {
+        let solved_board;
+        let goal = match goal {
+            Some(goal) => goal,
+            None => {
+                solved_board = Board::new(self.n, None, -1, None);
+                &solved_board
             }
+        };
+        let manhattan = self._manhattan_cost(goal);
+        let linear_conflicts = self.linear_conflicts(goal);
+        HeuristicBreakdown {
+            manhattan,
+            linear_conflicts,
+            depth: self.g(),
+            total: manhattan + linear_conflicts + self.g(),
         }
-        return moves;
     }
 
-    /// Translate tile indices based on position and move direction.
+    /// Calculate scramble quality metrics against a goal in one pass over the tiles.
     ///
     /// # Parameters
-    /// * `position` - Index of tile
-    /// * `move_direction` - Direction to move
+    /// * `goal` - Goal board of the same size
     ///
     /// # Returns
-    /// Resulting index from translation
-    pub fn translate_index(&self, position: i32, move_direction: char) -> i32 {
-        if move_direction == UP {
-            return position - self.n;
-        }
-        if move_direction == DOWN {
-            return position + self.n;
-        }
-        if move_direction == LEFT {
-            return position - 1;
+    /// The board's metrics
+    pub fn metrics(&self, goal: &Board) -> BoardMetrics {
+        let goal_indices = _goal_indices(goal);
+        // Rows and columns of the 2x2 block around the blank's goal position
+        let (blank_row, blank_col) = (goal.blank_index / self.n, goal.blank_index % self.n);
+        let block_row = if blank_row + 1 < self.n { blank_row + 1 } else { blank_row - 1 };
+        let block_col = if blank_col + 1 < self.n { blank_col + 1 } else { blank_col - 1 };
+        let in_block = |row: i32, col: i32| {
+            (row == blank_row || row == block_row) && (col == blank_col || col == block_col)
+        };
+
+        let mut metrics = BoardMetrics {
+            misplaced: 0,
+            out_of_row: 0,
+            out_of_column: 0,
+            correct_prefix: 0,
+            final_moves_forced: self.n > 1,
+        };
+        let mut prefix_ended = false;
+        for (i, tile) in self.tiles.iter().enumerate() {
+            prefix_ended |= *tile != goal.tiles[i];
+            if !prefix_ended {
+                metrics.correct_prefix += 1;
+            }
+            if tile.is_blank() {
+                continue;
+            }
+            let goal_index = goal_indices[tile.symbol() as usize];
+            if goal_index == i as i32 {
+                continue;
+            }
+            let (goal_row, goal_col) = (goal_index / self.n, goal_index % self.n);
+            metrics.misplaced += 1;
+            metrics.out_of_row += (goal_row != i as i32 / self.n) as i32;
+            metrics.out_of_column += (goal_col != i as i32 % self.n) as i32;
+            metrics.final_moves_forced &= in_block(goal_row, goal_col);
         }
-        return position + 1;
+        metrics.final_moves_forced &= metrics.misplaced > 0;
+        metrics
     }
 
-    /// Move the empty space in the specified direction.
+    /// Get the fraction of cells holding the same tile in both boards.
     ///
     /// # Parameters
-    /// * `move_direction` - Direction to move the blank tile
-    pub fn move_blank_tile(&mut self, move_direction: char) {
-        // Get index to swap with
-        let swap_i = self.translate_index(self.blank_index, move_direction);
-        self.tiles.swap(swap_i as usize, self.blank_index as usize);
-        // Update last move direction and blank index
-        self.last_direction = move_direction;
-        self.blank_index = swap_i;
+    /// * `other` - Board to compare against
+    ///
+    /// # Returns
+    /// Similarity from 0.0 to 1.0, or 0.0 if the boards differ in size
+    pub fn similarity(&self, other: &Board) -> f32 {
+        if self.n != other.n {
+            return 0.0;
+        }
+        let equal = self.tiles.iter().zip(other.tiles.iter()).filter(|(a, b)| a == b).count();
+        equal as f32 / self.n2 as f32
     }
 
-    /// Get the Manhattan cost of the current board compared with the solved board.
+    /// Get a structural signature of the board: how many tiles are each distance from
+    /// their goal.
+    /// * Byte k counts the tiles k moves (Manhattan distance) from their goal, the last
+    ///   byte counting tiles 7 or more away; counts saturate at 255.
+    /// * Tile order is ignored, so near-duplicates, e.g. boards a blank move apart, have
+    ///   equal or byte-wise close signatures, unlike an exact hash.
     ///
     /// # Returns
-    /// Manhattan cost of board
-    fn _manhattan_cost(&self, solved_board: &Board) -> i32 {
-        let mut cost = 0;
-        // Check each tile
-        for i in 0..self.n2 {
-            // Do not use blank tile
-            if i == self.blank_index {
+    /// Signature of the board
+    pub fn signature(&self) -> u64 {
+        let mut counts = [0u8; 8];
+        for (i, tile) in self.tiles.iter().enumerate() {
+            if tile.is_blank() {
                 continue;
             }
-            // Get index of tile in solved board
-            let mut solved_i = -1;
-            for j in 0..self.n2 {
-                if solved_board.tiles[j as usize] == self.tiles[i as usize] {
-                    solved_i = j;
-                    break;
-                }
-            }
-            // Get distance for x-axis
-            cost += ((i % self.n) - (solved_i % self.n)).abs();
-            // Get distance for y-axis
-            cost += ((i / self.n) - (solved_i / self.n)).abs();
+            let goal = tile.symbol() - 1;
+            let (i, n) = (i as i32, self.n);
+            let distance = (i / n - goal / n).abs() + (i % n - goal % n).abs();
+            let bucket = &mut counts[distance.min(7) as usize];
+            *bucket = bucket.saturating_add(1);
         }
-        return cost;
+        u64::from_le_bytes(counts)
     }
 
-    /// Calculate cost/heuristic for board.
-    /// - Caches the manhattan cost and number of linear conflicts
+    /// Check if the board is in the goal layout of a preset.
     ///
     /// # Parameters
-    /// * `solved_board` - Solved board to calculate cost against
+    /// * `preset` - Goal layout
     ///
     /// # Returns
-    /// Cost for board
-    pub fn get_cost(&mut self, solved_board: &Board) -> i32 {
-        self.manhattan_cost_cache = self._manhattan_cost(solved_board);
-        self.linear_conflicts_cache = self.linear_conflicts(solved_board);
-        self.manhattan_cost_cache + self.depth + self.linear_conflicts_cache
+    /// Whether the board is solved towards the preset
+    pub fn is_solved_with_preset(&self, preset: GoalPreset) -> bool {
+        self.is_goal_for(&Board::solved_with_preset(self.n, preset))
     }
 
-    /// Check if the board is solved.
+    /// Check if the board is in the layout of an explicit goal.
+    /// * Tiles are compared directly, so neither board needs to be costed.
+    ///
+    /// # Parameters
+    /// * `goal` - Goal board
+    ///
+    /// # Returns
+    /// Whether the board has the goal's size and tiles
+    pub fn is_goal_for(&self, goal: &Board) -> bool {
+        self.n == goal.n && self.tiles == goal.tiles
+    }
+
+    /// Check if the board is in the solved layout.
+    /// * Tiles are checked directly, so boards that were never costed work too.
     ///
     /// # Returns
     /// Whether the board is solved
     pub fn is_solved(&self) -> bool {
-        self.manhattan_cost_cache == 0
+        let last = self.tiles.len() - 1;
+        self.tiles.iter().enumerate().all(|(i, tile)| {
+            if i == last { tile.is_blank() } else { tile.symbol() == i as i32 + 1 }
+        })
     }
 
     /// Calculate the number of linear conflicts in the board.
@@ -330,267 +2786,723 @@ impl Board {
             self
         );
         // Already conflicting tiles
-        let conflicting_tiles: &mut HashSet<Tile> = &mut HashSet::new();
+        let conflicting_tiles: &mut BTreeSet<Tile> = &mut BTreeSet::new();
         let mut total = 0;
         // Get row conflicts
-        for i in 0..self.n {
-            total += self._find_row_conflicts(
-                i,
+        for row in 0..self.n as usize {
+            total += Board::_find_line_conflicts(
+                self.row(row),
                 conflicting_tiles,
-                &solved_row_map,
-                &solved_col_map,
-                &unsolved_row_map,
-                &unsolved_col_map,
+                (&solved_row_map, &solved_col_map),
+                (&unsolved_row_map, &unsolved_col_map),
             );
         }
         // Get column conflicts
-        for i in 0..self.n {
-            total += self._find_column_conflicts(
-                i,
+        for col in 0..self.n as usize {
+            let column: Vec<Tile> = self.column(col).collect();
+            total += Board::_find_line_conflicts(
+                &column,
                 conflicting_tiles,
-                &solved_row_map,
-                &solved_col_map,
-                &unsolved_row_map,
-                &unsolved_col_map,
+                (&solved_col_map, &solved_row_map),
+                (&unsolved_col_map, &unsolved_row_map),
             );
         }
         total
     }
 
-    /// Find the number of linear conflicts in a row.
+    /// Find the number of linear conflicts in a row or column.
     ///
     /// # Parameters
-    /// * `row` - Index of the row
+    /// * `line` - Tiles of the row or column
     /// * `conflicting_tiles` - Already conflicting tiles
-    /// * `solved_row_map` - Solved board map from tile to relative row index
-    /// * `solved_col_map` - Solved board map from tile to relative column index
-    /// * `unsolved_row_map` - Unsolved board map from tile to relative row index
-    /// * `unsolved_col_map` - Unsolved board map from tile to relative column index
-    ///
-    /// # Returns
-    /// Conflicts in column
-    fn _find_row_conflicts(&self, row: i32, conflicting_tiles: &mut HashSet<Tile>,
-                           solved_row_map: &HashMap<Tile, i32>,
-                           solved_col_map: &HashMap<Tile, i32>,
-                           unsolved_row_map: &HashMap<Tile, i32>,
-                           unsolved_col_map: &HashMap<Tile, i32>) -> i32 {
+    /// * `solved_maps` - Solved board maps from tile to index along the line and to the
+    ///   index of the line
+    /// * `unsolved_maps` - Unsolved board maps from tile to index along the line and to
+    ///   the index of the line
+    ///
+    /// # Returns
+    /// Conflicts in the line
+    fn _find_line_conflicts(line: &[Tile], conflicting_tiles: &mut BTreeSet<Tile>,
+                            solved_maps: (&BTreeMap<Tile, i32>, &BTreeMap<Tile, i32>),
+                            unsolved_maps: (&BTreeMap<Tile, i32>, &BTreeMap<Tile, i32>)) -> i32 {
+        let ((solved_along, solved_line), (unsolved_along, unsolved_line)) = (solved_maps, unsolved_maps);
         let mut total = 0;
-        // Go through each pair of tiles in the row
-        for i in 0..self.n - 1 {
+        // Go through each pair of tiles in the line
+        for (i, tile_i) in line.iter().enumerate() {
             // Skip blank tile
-            if self.index(row, i, None).is_blank() {
+            if tile_i.is_blank() {
                 continue;
             }
-            for j in i + 1..self.n {
-                // Ensure present and goal positions are in the same row
-                if solved_col_map[&self.index(row, i, None)]
-                    != unsolved_col_map[&self.index(row, i, None)]
-                    || solved_col_map[&self.index(row, j, None)]
-                    != unsolved_col_map[&self.index(row, j, None)] {
+            for tile_j in &line[i + 1..] {
+                // Ensure present and goal positions are in the same line
+                if solved_line[tile_i] != unsolved_line[tile_i] || solved_line[tile_j] != unsolved_line[tile_j] {
                     continue;
                 }
                 // Skip if already conflicting
-                if conflicting_tiles.contains(&self.index(row, i, None))
-                    || conflicting_tiles.contains(&self.index(row, j, None)) {
+                if conflicting_tiles.contains(tile_i) || conflicting_tiles.contains(tile_j) {
                     continue;
                 }
                 // Skip blank jth tile
-                if self.index(row, j, None).is_blank() {
+                if tile_j.is_blank() {
                     continue;
                 }
                 // Check if conflicting
                 if Board::_in_conflict(
-                    unsolved_row_map[&self.index(row, i, None)],
-                    unsolved_row_map[&self.index(row, j, None)],
-                    solved_row_map[&self.index(row, i, None)],
-                    solved_row_map[&self.index(row, j, None)],
+                    unsolved_along[tile_i],
+                    unsolved_along[tile_j],
+                    solved_along[tile_i],
+                    solved_along[tile_j],
                 ) {
                     total += 2;
-                    conflicting_tiles.insert(self.index(row, i, None));
-                    conflicting_tiles.insert(self.index(row, j, None));
+                    conflicting_tiles.insert(*tile_i);
+                    conflicting_tiles.insert(*tile_j);
                 }
             }
         }
         total
     }
 
-    /// Find the number of linear conflicts in a column.
+    /// Create a map of tiles to their relative row indices.
     ///
     /// # Parameters
-    /// * `col` - Index of the column
-    /// * `conflicting_tiles` - Already conflicting tiles
-    /// * `solved_row_map` - Solved board map from tile to relative row index
-    /// * `solved_col_map` - Solved board map from tile to relative column index
-    /// * `unsolved_row_map` - Unsolved board map from tile to relative row index
-    /// * `unsolved_col_map` - Unsolved board map from tile to relative column index
-    ///
-    /// # Returns
-    /// Conflicts in column
-    fn _find_column_conflicts(&self, col: i32, conflicting_tiles: &mut HashSet<Tile>,
-                              solved_row_map: &HashMap<Tile, i32>,
-                              solved_col_map: &HashMap<Tile, i32>,
-                              unsolved_row_map: &HashMap<Tile, i32>,
-                              unsolved_col_map: &HashMap<Tile, i32>) -> i32 {
-        let mut total = 0;
-        // Go through each pair of tiles in the column
-        for i in 0..self.n - 1 {
-            // Skip blank tile
-            if self.index(i, col, None).is_blank() {
-                continue;
+    /// * `board` - The board to use
+    ///
+    /// # Returns
+    /// Map from tile to relative row index
+    fn _create_tile_row_indices_map(&self, board: &Board) -> BTreeMap<Tile, i32> {
+        board.into_iter().map(|(_row, col, tile)| (tile, col as i32)).collect()
+    }
+
+    /// Create a map of tiles to their relative column indices.
+    ///
+    /// # Parameters
+    /// * `board` - The board to use
+    ///
+    /// # Returns
+    /// Map from tile to relative column index
+    fn _create_tile_column_indices_map(&self, board: &Board) -> BTreeMap<Tile, i32> {
+        board.into_iter().map(|(row, _col, tile)| (tile, row as i32)).collect()
+    }
+}
+
+/// Iterator over the cells of a board in row-major order, see `IntoIterator for &Board`.
+///
+/// # Attributes
+/// * `board` - The board
+/// * `index` - Index of the next cell
+#[derive(Debug, Clone)]
+pub struct Cells<'a> {
+    board: &'a Board,
+    index: usize,
+}
+
+impl<'a> Iterator for Cells<'a> {
+    type Item = (usize, usize, Tile);
+
+    fn next(&mut self) -> Option<(usize, usize, Tile)> {
+        let n = self.board.n as usize;
+        let tile = *self.board.tiles.get(self.index)?;
+        let cell = (self.index / n, self.index % n, tile);
+        self.index += 1;
+        Some(cell)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.board.tiles.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Cells<'_> {}
+
+/// Iterate over (row, column, tile) in row-major order.
+impl<'a> IntoIterator for &'a Board {
+    type Item = (usize, usize, Tile);
+    type IntoIter = Cells<'a>;
+
+    fn into_iter(self) -> Cells<'a> {
+        Cells { board: self, index: 0 }
+    }
+}
+
+impl PartialEq for Board {
+    /// Custom equivalence function based only on tiles
+    fn eq(&self, other: &Self) -> bool {
+        self.tiles == other.tiles
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    /// Custom hash function based only on tiles.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tiles.hash(state)
+    }
+}
+
+impl fmt::Display for Board {
+    /// Write the board as a grid with the default `BoardFormatter`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", BoardFormatter::default().format(self))
+    }
+}
+
+impl TryFrom<Vec<i32>> for Board {
+    type Error = BoardError;
+
+    /// Create a board from tile symbols in row-major order, with -1 for the blank.
+    /// * The size is inferred from the number of tiles, which must be a perfect square.
+    fn try_from(symbols: Vec<i32>) -> Result<Board, BoardError> {
+        let n = symbols.len().isqrt();
+        if n * n != symbols.len() {
+            return Err(BoardError::NotSquare(symbols.len()));
+        }
+        Board::try_new(n as i32, symbols.into_iter().map(Tile::new).collect())
+    }
+}
+
+impl TryFrom<&[&[i32]]> for Board {
+    type Error = BoardError;
+
+    /// Create a board from rows of tile symbols, with -1 for the blank.
+    /// * Every row must be as long as there are rows.
+    fn try_from(rows: &[&[i32]]) -> Result<Board, BoardError> {
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != rows.len() {
+                return Err(BoardError::UnevenRow { row: i, expected: rows.len(), found: row.len() });
             }
-            for j in i + 1..self.n {
-                // Ensure present and goal positions are in the same column
-                if solved_row_map[&self.index(i, col, None)]
-                    != unsolved_row_map[&self.index(i, col, None)]
-                    || solved_row_map[&self.index(j, col, None)]
-                    != unsolved_row_map[&self.index(j, col, None)] {
-                    continue;
-                }
-                // Skip if already conflicting
-                if conflicting_tiles.contains(&self.index(i, col, None))
-                    || conflicting_tiles.contains(&self.index(j, col, None)) {
-                    continue;
-                }
-                // Skip blank jth tile
-                if self.index(j, col, None).is_blank() {
-                    continue;
+        }
+        let tiles = rows.iter().flat_map(|row| row.iter()).map(|&symbol| Tile::new(symbol)).collect();
+        Board::try_new(rows.len() as i32, tiles)
+    }
+}
+
+impl From<&Board> for Vec<i32> {
+    /// Flatten a board into tile symbols in row-major order, with -1 for the blank.
+    fn from(board: &Board) -> Vec<i32> {
+        board.tiles.iter().map(|tile| tile.symbol()).collect()
+    }
+}
+
+impl Ord for Board {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost)
+            .then_with(|| other.stored_h().cmp(&self.stored_h()))
+//        self.partial_cmp(other).unwrap()
+    }
+}
+
+impl PartialOrd for Board {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+//        other.cost.partial_cmp(&self.cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::{Replay, ReplayFrame};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::{BinaryHeap, HashMap, HashSet};
+
+    /// Test boards generated across threads don't depend on the thread count
+    #[test]
+    fn test_board_generate_many() {
+        let two = Board::generate_many(4, 50, 40, 366, 2).unwrap();
+        let four = Board::generate_many(4, 50, 40, 366, 4).unwrap();
+        assert_eq!(two.len(), 50);
+        assert_eq!(two, four);
+        assert_eq!(two, Board::generate_many(4, 50, 40, 366, 1).unwrap());
+        assert_eq!(two, Board::generate_many(4, 50, 40, 366, 64).unwrap());
+        assert!(two.iter().all(|board| board.is_solvable()));
+        // Boards aren't all the same
+        assert!(two.iter().any(|board| board != &two[0]));
+        assert_ne!(two, Board::generate_many(4, 50, 40, 367, 2).unwrap());
+        assert_eq!(Board::generate_many(4, 0, 40, 366, 4).unwrap(), vec![]);
+        assert_eq!(Board::generate_many(1, 5, 40, 366, 4), Err(BoardError::InvalidSize(1)));
+    }
+
+    /// Test Markdown and HTML tables of a 3x3 board
+    #[test]
+    fn test_board_markdown_and_html() {
+        let board = Board::try_from(vec![1, 2, 3, 4, 5, 6, 7, -1, 8]).unwrap();
+        assert_eq!(board.to_markdown(), "\
+|   |   |   |
+|---|---|---|
+| 1 | 2 | 3 |
+| 4 | 5 | 6 |
+| 7 |  | 8 |
+");
+        assert_eq!(board.to_html(), "\
+<table class=\"board\">
+<tr><td data-tile=\"1\">1</td><td data-tile=\"2\">2</td><td data-tile=\"3\">3</td></tr>
+<tr><td data-tile=\"4\">4</td><td data-tile=\"5\">5</td><td data-tile=\"6\">6</td></tr>
+<tr><td data-tile=\"7\">7</td><td class=\"blank\"></td><td data-tile=\"8\">8</td></tr>
+</table>
+");
+        // Every row has one cell per column
+        for n in 1..=5 {
+            let board = Board::new(n, None, 0, None);
+            for line in board.to_markdown().lines() {
+                assert_eq!(line.matches('|').count(), n as usize + 1);
+            }
+            for line in board.to_html().lines().filter(|line| line.starts_with("<tr>")) {
+                assert_eq!(line.matches("<td").count(), n as usize);
+            }
+        }
+        assert_eq!(Board::_escape_markdown("a|b\\"), "a\\|b\\\\");
+        assert_eq!(Board::_escape_html("<a href=\"x\">&'"), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;");
+    }
+
+    /// Test boards round trip through compact strings
+    #[test]
+    fn test_board_compact_string() {
+        let mut rng = StdRng::seed_from_u64(346);
+        assert_eq!(Board::from_compact_string("1:_").unwrap().to_compact_string(), "1:_");
+        for n in 2..=5 {
+            let mut board = Board::new(n, None, 0, None);
+            board.shuffle_with_rng(50, &mut rng);
+            let compact = board.to_compact_string();
+            assert_eq!(Board::from_compact_string(&compact).unwrap(), board);
+        }
+        let board = Board::from_compact_string(" 3 : 1, 2,3 ,4,5,6,7,_,8 ").unwrap();
+        assert_eq!(board.to_compact_string(), "3:1,2,3,4,5,6,7,_,8");
+        assert_eq!(board.cost, 1);
+    }
+
+    /// Test malformed compact strings are rejected
+    #[test]
+    fn test_board_compact_string_malformed() {
+        let unparsable = |text: &str| Err(BoardError::Unparsable(text.to_string()));
+        for text in &["", "3", "3;1,2,3,4,5,6,7,_,8", "x:1", "3:1,2,3,4,5,6,7,,8", "3:1,2,3,4,5 6,7,_,8"] {
+            assert_eq!(Board::from_compact_string(text), unparsable(text));
+        }
+        assert_eq!(Board::from_compact_string("3:1,2,3,4,5,6,7,-1,8"), Err(BoardError::InvalidTile(-1)));
+        assert_eq!(Board::from_compact_string("3:1,2,3,4,5,6,7,9,8"), Err(BoardError::InvalidTile(9)));
+        assert_eq!(Board::from_compact_string("3:1,2,3,4,5,6,7,_,_"), Err(BoardError::DuplicateTile(BLANK_TILE)));
+        assert_eq!(
+            Board::from_compact_string("3:1,2,3,4,5,6,7,_"),
+            Err(BoardError::WrongTileCount { expected: 9, found: 8 })
+        );
+    }
+
+    /// Test boards round trip through URL-safe tokens
+    #[test]
+    fn test_board_url_safe() {
+        let mut rng = StdRng::seed_from_u64(346);
+        for n in 1..=4 {
+            let mut board = Board::new(n, None, 0, None);
+            if n > 1 {
+                board.shuffle_with_rng(50, &mut rng);
+            }
+            let token = board.to_url_safe().unwrap();
+            assert_eq!(token.len(), 11);
+            let parsed = Board::from_url_safe(&token).unwrap();
+            assert_eq!(parsed.size(), n);
+            assert_eq!(parsed, board);
+        }
+        assert_eq!(Board::new(4, None, 0, None).to_url_safe().unwrap(), "_ty6mHZUMhA");
+        assert_eq!(Board::new(5, None, 0, None).to_url_safe(), None);
+        for token in &["", "_ty6mHZUMh", "_ty6mHZUMhA=", "_ty6mHZ+MhA", "AAAAAAAAAAA"] {
+            assert_eq!(Board::from_url_safe(token), Err(BoardError::Unparsable(token.to_string())));
+        }
+    }
+
+    /// Test converting boards to and from symbol vectors
+    #[test]
+    fn test_board_vec_conversions() {
+        let symbols = vec![1, 2, 3, -1, 4, 6, 7, 5, 8];
+        let board = Board::try_from(symbols.clone()).unwrap();
+        assert_eq!(board.size(), 3);
+        assert_eq!(board.cost, 3);
+        assert_eq!(Vec::from(&board), symbols);
+        let rows: [&[i32]; 3] = [&[1, 2, 3], &[-1, 4, 6], &[7, 5, 8]];
+        assert_eq!(Board::try_from(&rows[..]).unwrap(), board);
+        assert_eq!(Board::try_from(vec![1, 2, 3, 4, 5, -1]), Err(BoardError::NotSquare(6)));
+        assert_eq!(Board::try_from(vec![1, 2, 2, -1]), Err(BoardError::DuplicateTile(2)));
+        let uneven: [&[i32]; 3] = [&[1, 2, 3], &[-1, 4], &[6, 7, 5, 8]];
+        assert_eq!(
+            Board::try_from(&uneven[..]),
+            Err(BoardError::UnevenRow { row: 1, expected: 3, found: 2 })
+        );
+        assert_eq!(
+            BoardError::UnevenRow { row: 1, expected: 3, found: 2 }.to_string(),
+            "row 1 has 2 tiles, expected 3"
+        );
+    }
+
+    /// Test generated boards are reproducible from their seed
+    #[test]
+    fn test_board_generate() {
+        let board = Board::generate(4, 348, 40).unwrap();
+        assert_eq!(board, Board::generate(4, 348, 40).unwrap());
+        assert_ne!(board, Board::generate(4, 349, 40).unwrap());
+        assert!(board.is_solvable());
+        assert_eq!(board.depth, 0);
+        assert_eq!(Board::generate(1, 348, 40), Err(BoardError::InvalidSize(1)));
+    }
+
+    /// Test rotations and flips against known layouts
+    #[test]
+    fn test_board_rotate_and_flip() {
+        let board = Board::try_from(vec![1, 2, 3, 4, 5, 6, 7, 8, -1]).unwrap();
+        let rows = |board: &Board| Vec::<i32>::from(board);
+        assert_eq!(rows(&board.rotate_cw()), vec![7, 4, 1, 8, 5, 2, -1, 6, 3]);
+        assert_eq!(rows(&board.rotate_ccw()), vec![3, 6, -1, 2, 5, 8, 1, 4, 7]);
+        assert_eq!(rows(&board.flip_horizontal()), vec![3, 2, 1, 6, 5, 4, -1, 8, 7]);
+        assert_eq!(rows(&board.flip_vertical()), vec![7, 8, -1, 4, 5, 6, 1, 2, 3]);
+        assert_eq!(board.rotate_cw().get_blank_index(), 6);
+        assert_eq!(board.rotate_cw().rotate_ccw(), board);
+        assert_eq!(board.rotate_cw().rotate_cw(), board.transform(Transform::Rotate180));
+        assert_eq!(board.flip_horizontal().flip_horizontal(), board);
+        assert_eq!(transform_path("URDL", Transform::RotateCw), "RDLU");
+        assert_eq!(transform_path("URDL", Transform::FlipHorizontal), "ULDR");
+    }
+
+    /// Test replaying a transformed path on a transformed board reaches the transformed goal
+    #[test]
+    fn test_transform_path_property() {
+        let mut rng = StdRng::seed_from_u64(355);
+        for n in 2..=5 {
+            let goal = Board::new(n, None, 0, None);
+            for _ in 0..10 {
+                // Random walk from the goal, recording the moves back to it
+                let mut board = goal.clone();
+                let mut walk = String::new();
+                for _ in 0..30 {
+                    let tile_move = *board.legal_moves().choose(&mut rng).unwrap();
+                    board.move_blank_tile(tile_move);
+                    walk.push(tile_move);
                 }
-                // Check if conflicting
-                if Board::_in_conflict(
-                    unsolved_col_map[&self.index(i, col, None)],
-                    unsolved_col_map[&self.index(j, col, None)],
-                    solved_col_map[&self.index(i, col, None)],
-                    solved_col_map[&self.index(j, col, None)],
-                ) {
-                    total += 2;
-                    conflicting_tiles.insert(self.index(i, col, None));
-                    conflicting_tiles.insert(self.index(j, col, None));
+                let path = invert_path(&walk);
+                for &transform in &TRANSFORMS {
+                    let mut transformed = board.transform(transform);
+                    for tile_move in transform_path(&path, transform).chars() {
+                        assert!(transformed.is_legal_move(tile_move));
+                        transformed.move_blank_tile(tile_move);
+                    }
+                    assert_eq!(transformed, goal.transform(transform));
                 }
             }
         }
-        total
     }
 
-    /// Create a map of tiles to their relative row indices.
-    ///
-    /// # Parameters
-    /// * `board` - The board to use
-    ///
-    /// # Returns
-    /// Map from tile to relative row index
-    fn _create_tile_row_indices_map(&self, board: &Board) -> HashMap<Tile, i32> {
-        let mut row_map: HashMap<Tile, i32> = HashMap::new();
-        for row in 0..self.n {
-            for col in 0..self.n {
-                row_map.insert(self.index(row, col, Some(board)), col);
+    /// Test inverted paths undo the moves last first, and inverting twice gives the path
+    #[test]
+    fn test_invert_path() {
+        assert_eq!(invert_path("RRD"), "ULL");
+        assert_eq!(invert_path("ULDR"), "LURD");
+        assert_eq!(invert_path(""), "");
+        let mut rng = StdRng::seed_from_u64(412);
+        let start = Board::new(4, None, 0, None);
+        for _ in 0..20 {
+            let mut board = start.clone();
+            let mut walk = String::new();
+            for _ in 0..rng.gen_range(1, 40) {
+                let tile_move = *board.legal_moves().choose(&mut rng).unwrap();
+                board.move_blank_tile(tile_move);
+                walk.push(tile_move);
             }
+            assert_eq!(invert_path(&invert_path(&walk)), walk);
+            assert_eq!(board.apply_moves(&invert_path(&walk), Notation::BlankMoves).unwrap(), start);
         }
-        row_map
     }
 
-    /// Create a map of tiles to their relative column indices.
-    ///
-    /// # Parameters
-    /// * `board` - The board to use
-    ///
-    /// # Returns
-    /// Map from tile to relative column index
-    fn _create_tile_column_indices_map(&self, board: &Board) -> HashMap<Tile, i32> {
-        let mut col_map: HashMap<Tile, i32> = HashMap::new();
-        for col in 0..self.n {
-            for row in 0..self.n {
-                col_map.insert(self.index(row, col, Some(board)), row);
-            }
-        }
-        col_map
-    }
-
-//    /// Get new board leaves from making every possible move.
-//    ///
-//    /// # Returns
-//    /// The new board leaves
-//    pub fn get_move_leaves(self, previous_boards: &mut HashSet<Board>) {
-//        let mut new_boards: Vec<Board> = [];
-//        for tile_move in self.get_moves() {
-//            // New board for this move
-//            let mut new_board = self.clone();
-//            // Make move
-//            new_board.move_blank_tile(tile_move);
-//            // If resulting board has been seen before, skip it
-//            if new_board.previously_encountered(previous_boards) {
-//                continue;
-//            }
-//            // Setup board
-//            new_board.parent_node = Some(self);
-//            new_board.depth = self.depth + 1;
-//            new_board.cost = new_board.get_cost();
-//            // Add to resulting boards
-//            new_boards.push(new_board);
-//            // Add to previously seen boards
-//            new_board.add_to_previously_encountered(previous_boards);
-//        }
-//    }
-}
+    /// Test moves are applied in each notation and illegal ones are reported
+    #[test]
+    fn test_apply_moves() {
+        let board = Board::new(3, None, 0, None);
+        assert_eq!(Notation::TileMoves.format_moves("ULDR"), "DRUL");
+        assert_eq!(Notation::Lowercase.format_moves("ULDR"), "uldr");
+        assert_eq!(Notation::TileMoves.parse_move('D'), Some(UP));
+        assert_eq!(Notation::Lowercase.parse_move('U'), None);
+        let moved = board.apply_moves("UL", Notation::BlankMoves).unwrap();
+        assert_eq!(board.apply_moves("DR", Notation::TileMoves).unwrap(), moved);
+        assert_eq!(board.apply_moves("ul", Notation::Lowercase).unwrap(), moved);
+        assert_eq!(board.apply_moves("UD", Notation::TileMoves),
+                   Err(IllegalMove { index: 0, rejection: MoveRejection::BlankOnBottomEdge }));
+        let error = board.apply_moves("Ux", Notation::BlankMoves).unwrap_err();
+        assert_eq!(error.rejection, MoveRejection::UnknownDirection('x'));
+        assert_eq!(error.to_string(), "move 1 can't be made: unknown move direction 'x'");
+    }
 
-impl PartialEq for Board {
-    /// Custom equivalence function based only on tiles
-    fn eq(&self, other: &Self) -> bool {
-        self.tiles == other.tiles
+    /// Test paths with separators, mixed case and invalid or excessive input
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(parse_path("u, l ,R", Notation::BlankMoves), Ok(vec![UP, LEFT, RIGHT]));
+        assert_eq!(parse_path("DR", Notation::Lowercase), Ok(vec![DOWN, RIGHT]));
+        assert_eq!(parse_path("d,r", Notation::TileMoves), Ok(vec![UP, LEFT]));
+        assert_eq!(parse_path(" \t\n", Notation::BlankMoves), Ok(vec![]));
+        // The emoji takes 4 bytes, so the offset counts bytes rather than characters
+        let error = parse_path("U, L\u{1F600}R", Notation::BlankMoves).unwrap_err();
+        assert_eq!(error, PathParseError::InvalidToken { offset: 4, index: 2, token: '\u{1F600}' });
+        assert_eq!(parse_path("\u{1F600}x", Notation::BlankMoves).unwrap_err().to_string(),
+                   "invalid move '\u{1F600}' at byte 0");
+        assert_eq!(parse_path("L\u{e9}x", Notation::BlankMoves),
+                   Err(PathParseError::InvalidToken { offset: 1, index: 1, token: '\u{e9}' }));
+        let long = "U".repeat(10_000_000);
+        assert_eq!(parse_path(&long, Notation::BlankMoves),
+                   Err(PathParseError::TooLong { length: 10_000_000, max: MAX_PATH_LEN }));
+        assert_eq!(PathParseError::TooLong { length: 1, max: 0 }.code(), 116);
+        // Moving consumers read paths the same way
+        let board = Board::new(3, None, 0, None);
+        assert_eq!(board.apply_moves("u, l", Notation::BlankMoves), board.apply_moves("UL", Notation::BlankMoves));
+        assert_eq!(board.apply_moves(&long, Notation::BlankMoves),
+                   Err(IllegalMove { index: 0, rejection: MoveRejection::PathTooLong }));
     }
-}
 
-impl Eq for Board {}
+    /// Test each reason a move can be rejected
+    #[test]
+    fn test_check_move() {
+        // Blank in the top left corner
+        let board = Board::try_from(vec![-1, 1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        assert_eq!(board.check_move(UP), Err(MoveRejection::BlankOnTopEdge));
+        assert_eq!(board.check_move(LEFT), Err(MoveRejection::BlankOnLeftEdge));
+        assert_eq!(board.check_move(DOWN), Ok(()));
+        assert_eq!(board.check_move(RIGHT), Ok(()));
+        // Blank in the bottom right corner
+        let board = Board::new(3, None, 0, None);
+        assert_eq!(board.check_move(DOWN), Err(MoveRejection::BlankOnBottomEdge));
+        assert_eq!(board.check_move(RIGHT), Err(MoveRejection::BlankOnRightEdge));
+        assert_eq!(board.check_move(UP), Ok(()));
+        assert_eq!(board.check_move('X'), Err(MoveRejection::UnknownDirection('X')));
+        assert!(!board.is_legal_move('X'));
+        assert_eq!(MoveRejection::BlankOnTopEdge.name(), "BlankOnTopEdge");
+        assert_eq!(MoveRejection::BlankOnLeftEdge.to_string(), "the blank is on the left edge");
+        // Every move off a 1x1 board is rejected
+        let board = Board::new(1, None, 0, None);
+        assert!(MOVES.iter().all(|&tile_move| board.check_move(tile_move).is_err()));
+    }
 
-impl Hash for Board {
-    /// Custom hash function based only on tiles.
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.tiles.hash(state)
+    /// Test diffs and moved tiles between boards
+    #[test]
+    fn test_board_diff_and_moved_tile() {
+        let board = Board::generate(3, 354, 20).unwrap();
+        assert!(board.diff(&board).is_empty());
+        assert_eq!(board.moved_tile(&board), None);
+        // Adjacent boards along a replay differ by exactly one moved tile
+        let moves = crate::solver::solve(&board, &Default::default()).unwrap().moves;
+        let frames: Vec<ReplayFrame> = Replay::new(&board, &moves).collect();
+        assert!(frames.len() > 2);
+        for pair in frames.windows(2) {
+            let (tile, direction) = pair[0].board.moved_tile(&pair[1].board).unwrap();
+            assert_eq!(Some(tile), pair[1].moved_tile);
+            assert_eq!(Some(opposite_direction(direction)), pair[1].blank_move);
+            assert_eq!(pair[0].board.diff(&pair[1].board).len(), 2);
+        }
+        assert_eq!(frames[0].board.moved_tile(&frames[2].board), None);
+        let solved = Board::new(3, None, 0, None);
+        let mut moved = solved.clone();
+        moved.move_blank_tile(LEFT);
+        assert_eq!(solved.moved_tile(&moved), Some((8, RIGHT)));
+        assert_eq!(moved.moved_tile(&solved), Some((8, LEFT)));
+        assert_eq!(solved.diff(&moved), vec![
+            TileDiff { row: 2, col: 1, tile_in_self: 8, tile_in_other: BLANK_TILE },
+            TileDiff { row: 2, col: 2, tile_in_self: BLANK_TILE, tile_in_other: 8 },
+        ]);
+        // Swapping two tiles leaves the blank in place, which isn't a move
+        let swapped = Board::try_from(vec![2, 1, 3, 4, 5, 6, 7, 8, BLANK_TILE]).unwrap();
+        assert_eq!(solved.moved_tile(&swapped), None);
     }
-}
 
-impl ToString for Board {
-    fn to_string(&self) -> String {
-        let mut result: String = "".to_owned();
-        for row in 0..self.n {
-            for col in 0..self.n - 1 {
-                result.push_str(&self.index(row, col, None).to_string());
-                result.push_str(", ");
+    /// Test Manhattan deltas match recalculating the cost
+    #[test]
+    fn test_board_manhattan_delta() {
+        let solved_board = Board::new(3, None, -1, None);
+        let mut board = Board::new(3, None, 0, None);
+        let mut rng = StdRng::seed_from_u64(345);
+        board.shuffle_with_rng(30, &mut rng);
+        for tile_move in board.legal_moves() {
+            let mut moved = board.clone();
+            moved.move_blank_tile(tile_move);
+            assert_eq!(
+                board.manhattan_delta(tile_move),
+                moved._manhattan_cost(&solved_board) - board._manhattan_cost(&solved_board)
+            );
+        }
+    }
+
+    /// Test neighbours are one legal move away and keep their Manhattan cost up to date
+    #[test]
+    fn test_neighbors() {
+        let solved_board = Board::new(4, None, -1, None);
+        for seed in 0..20 {
+            let mut board = Board::generate(4, 389 + seed, 25).unwrap();
+            board.path = "L".to_string();
+            board.depth = 1;
+            let neighbors: Vec<(char, Board)> = board.neighbors().collect();
+            assert_eq!(neighbors.len(), board.legal_moves().len());
+            for ((tile_move, neighbor), legal_move) in neighbors.iter().zip(board.legal_moves()) {
+                assert_eq!(*tile_move, legal_move);
+                assert_eq!(board.apply_moves(&tile_move.to_string(), Notation::BlankMoves).unwrap(), *neighbor);
+                let changed = board.tiles().iter().zip(neighbor.tiles()).filter(|(a, b)| a != b).count();
+                assert_eq!(changed, 2);
+                assert_eq!(neighbor.get_blank_index(), neighbor.blank_index);
+                assert_eq!((neighbor.depth, neighbor.path.as_str()), (2, format!("L{}", tile_move).as_str()));
+                assert_eq!(neighbor.manhattan_cost_cache, neighbor._manhattan_cost(&solved_board));
+                assert_eq!((neighbor.linear_conflicts_cache, neighbor.cost), (-1, -1));
+            }
+            let raw: Vec<(char, Board)> = board.neighbors_unordered_raw().collect();
+            assert_eq!(raw.len(), neighbors.len());
+            for ((tile_move, raw), (_, neighbor)) in raw.iter().zip(&neighbors) {
+                assert_eq!(raw.tiles(), neighbor.tiles());
+                assert_eq!((raw.depth, raw.path.as_str(), raw.cost), (1, "L", board.cost));
+                assert_eq!(raw.get_blank_index(), board.translate_index(board.blank_index, *tile_move));
             }
-            result.push_str(&self.index(row, self.n - 1, None).to_string());
-            result.push_str("\n");
         }
-        result
     }
-}
 
-impl Ord for Board {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
-            .then_with(|| (other.manhattan_cost_cache + other.linear_conflicts_cache).cmp(
-                &(&self.manhattan_cost_cache + &self.linear_conflicts_cache)))
-//        self.partial_cmp(other).unwrap()
+    /// Test heuristic lists are read as their maximum, evaluated on each board
+    #[test]
+    fn test_heuristic_max() {
+        let max = Heuristic::parse("linear_conflicts+inversion_distance").unwrap();
+        assert!(matches!(max, Heuristic::Max(_)));
+        assert_eq!(max.to_string(), "linear_conflicts+inversion_distance");
+        assert_eq!(Heuristic::parse(" inversion_distance + linear_conflicts"), Some(max));
+        assert_eq!(Heuristic::max_of(&HEURISTICS), Some(max));
+        assert_eq!(Heuristic::max_of(&[max, Heuristic::Manhattan]), Some(max));
+        assert_eq!(Heuristic::parse("manhattan"), Some(Heuristic::Manhattan));
+        assert_eq!(Heuristic::parse("manhattan+linear_conflicts"), Some(Heuristic::LinearConflicts));
+        assert_eq!(Heuristic::parse("manhattan+manhattan"), Some(Heuristic::Manhattan));
+        let manhattan_id = Heuristic::parse("manhattan+inversion_distance").unwrap();
+        assert_eq!(manhattan_id.to_string(), "manhattan+inversion_distance");
+        assert!(manhattan_id.is_consistent());
+        assert!(!max.is_consistent());
+        for name in &["", "wd+linear_conflicts", "manhattan+", "Manhattan"] {
+            assert_eq!(Heuristic::parse(name), None, "{:?}", name);
+        }
+        assert_eq!(Heuristic::max_of(&[]), None);
+
+        let solved_board = Board::new(4, None, -1, None);
+        let mut rng = RngSource::seeded(404);
+        let mut above_each = [false; 3];
+        for _i in 0..100 {
+            let mut board = Board::new(4, None, 0, None);
+            board.shuffle_with_rng(60, &mut rng);
+            let estimate = |heuristic| board.clone().store_h(&solved_board, heuristic);
+            let parts: Vec<i32> = HEURISTICS.iter().map(|&heuristic| estimate(heuristic)).collect();
+            assert_eq!(estimate(max), *parts.iter().max().unwrap(), "{}", board);
+            assert_eq!(estimate(manhattan_id), parts[0].max(parts[2]), "{}", board);
+            for (above, part) in above_each.iter_mut().zip(&parts) {
+                *above |= estimate(max) > *part;
+            }
+        }
+        // Neither part bounds the other, so the maximum beats each of them on some board
+        assert_eq!(above_each, [true; 3]);
     }
-}
 
-impl PartialOrd for Board {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-//        other.cost.partial_cmp(&self.cost)
+    /// Test the inversion distance of a few boards
+    #[test]
+    fn test_inversion_distance() {
+        let solved_board = Board::new(3, None, -1, None);
+        assert_eq!(solved_board.inversion_distance(&solved_board), 0);
+        // One vertical move: tile 6 jumps 7 and 8 in row-major order
+        let board = Board::try_from(vec![1, 2, 3, 4, 5, -1, 7, 8, 6]).unwrap();
+        assert_eq!(board.inversion_distance(&solved_board), 1);
+        // One horizontal move: tile 8 jumps 3 and 6 in column-major order
+        let board = Board::try_from(vec![1, 2, 3, 4, 5, 6, 7, -1, 8]).unwrap();
+        assert_eq!(board.inversion_distance(&solved_board), 1);
+        // One of the two hardest boards, 31 moves from the goal
+        let board = Board::try_from(vec![8, 6, 7, 2, 5, 4, 3, -1, 1]).unwrap();
+        assert!(board.inversion_distance(&solved_board) <= 31);
+        let goal = Board::try_from(vec![-1, 1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        assert_eq!(goal.inversion_distance(&goal), 0);
+        assert_eq!(Board::new(1, None, -1, None).inversion_distance(&Board::new(1, None, -1, None)), 0);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::BinaryHeap;
+    /// Test shuffles walk on rather than end near the goal
+    #[test]
+    fn test_shuffle_min_bound() {
+        // Without undoing moves, the blank of a 2x2 board can only circle it, back to
+        // the goal after 12 moves
+        let (board, bound) = Board::generate_with_bound(2, 402, 12, DEFAULT_MIN_SHUFFLE_BOUND).unwrap();
+        assert!(!board.is_solved());
+        assert!(bound >= 1);
+        assert_eq!(Board::generate(2, 402, 12).unwrap(), board);
+        assert!(!Board::generate(2, 402, 24).unwrap().is_solved());
+        let mut board = Board::new(3, None, 0, None);
+        let (moves, bound) = board.shuffle_to_bound(2, 10, &mut RngSource::seeded(402));
+        assert!(bound >= 10 && moves.len() > 2, "{} {}", moves, bound);
+        assert_eq!(bound, board.heuristic_breakdown(None).total);
+        assert_eq!(Board::new(3, None, 0, None).apply_moves(&moves, Notation::BlankMoves), Ok(board));
+        // Unreachable bounds give up after the extra moves
+        let mut board = Board::new(2, None, 0, None);
+        let (moves, bound) = board.shuffle_to_bound(1, 100, &mut RngSource::seeded(402));
+        assert_eq!((moves.len() as i32, bound), (1 + MAX_SHUFFLE_RETRIES, board.heuristic_breakdown(None).total));
+        // No moves leaves the board alone
+        let mut board = Board::new(3, None, 0, None);
+        assert_eq!(board.shuffle_to_bound(0, 5, &mut RngSource::seeded(402)), (String::new(), 0));
+    }
+
+    /// Test shuffle moves replay from the solved board and costs are kept up to date
+    #[test]
+    fn test_shuffle_reports_moves() {
+        let mut rng = StdRng::seed_from_u64(357);
+        let solved_board = Board::new(4, None, -1, None);
+        let mut board = Board::new(4, Some(&solved_board), 0, None);
+        let moves = board.shuffle_with_rng(50, &mut rng);
+        assert_eq!(moves.len(), 50);
+        let mut replay = solved_board.clone();
+        for tile_move in moves.chars() {
+            replay.move_blank_tile(tile_move);
+        }
+        assert_eq!(replay, board);
+        assert_eq!(board.get_blank_index(), replay.get_blank_index());
+        assert_eq!(board.cost, board.g() + board.h(&solved_board));
+        // Uncosted boards stay uncosted
+        let mut uncosted = Board::new(3, None, 0, None);
+        uncosted.shuffle_with_rng(10, &mut rng);
+        assert_eq!(uncosted.cost, -1);
+        // Walks without revisits pass through distinct boards
+        for n in 2..=4 {
+            let mut board = Board::new(n, None, 0, None);
+            let mut replay = board.clone();
+            let moves = board.shuffle_without_revisits(50, &mut rng);
+            let mut seen = HashSet::new();
+            seen.insert(replay.clone());
+            for tile_move in moves.chars() {
+                replay.move_blank_tile(tile_move);
+                assert!(seen.insert(replay.clone()));
+            }
+            assert_eq!(replay, board);
+            if n > 2 {
+                assert_eq!(moves.len(), 50);
+            }
+        }
+    }
+
+    /// Test boards generated at an exact depth come with a solution of that length
+    #[test]
+    fn test_random_walk_exact() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for depth in 0..=12 {
+            let (board, solution) = Board::random_walk_exact(3, depth, &mut rng).unwrap();
+            assert_eq!(solution.len(), depth);
+            assert_eq!(board.depth, 0);
+            let mut replay = board.clone();
+            for tile_move in solution.chars() {
+                assert!(replay.is_legal_move(tile_move));
+                replay.move_blank_tile(tile_move);
+            }
+            assert!(replay.is_solved());
+        }
+        // The 2x2 puzzle has a diameter of 6
+        assert!(Board::random_walk_exact(2, 6, &mut rng).is_some());
+        assert!(Board::random_walk_exact(2, 7, &mut rng).is_none());
+        assert!(Board::random_walk_exact(5, 1, &mut rng).is_none());
+    }
 
-    /// Test opposite directions hashmap
+    /// Test opposite directions
     #[test]
-    fn test_opposite_directions_hashmap() {
-        assert_eq!(OPPOSITE_DIRECTIONS.get(&UP).unwrap(), &DOWN);
-        assert_eq!(OPPOSITE_DIRECTIONS.get(&DOWN).unwrap(), &UP);
-        assert_eq!(OPPOSITE_DIRECTIONS.get(&RIGHT).unwrap(), &LEFT);
-        assert_eq!(OPPOSITE_DIRECTIONS.get(&LEFT).unwrap(), &RIGHT);
+    fn test_opposite_direction() {
+        assert_eq!(opposite_direction(UP), DOWN);
+        assert_eq!(opposite_direction(DOWN), UP);
+        assert_eq!(opposite_direction(RIGHT), LEFT);
+        assert_eq!(opposite_direction(LEFT), RIGHT);
+        assert_eq!(opposite_direction('x'), 'x');
     }
 
     /// Test board construction
@@ -606,23 +3518,23 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            Some(tiles.clone()));
+            Some(tiles.to_vec().into_boxed_slice()));
         assert_eq!(board.n, 3);
         assert_eq!(board.n2, 9);
-        assert_eq!(board.last_direction, '\0');
         assert_eq!(board.blank_index, 8);
-        assert_eq!(board.tiles, [
+        assert_eq!(*board.tiles, [
             Tile::new(8), Tile::new(4), Tile::new(6),
             Tile::new(3), Tile::new(7), Tile::new(1),
             Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
         ]);
-        assert_eq!(board.solved_board.unwrap().tiles, [
+        assert_eq!(*solved_board.tiles, [
             Tile::new(1), Tile::new(2), Tile::new(3),
             Tile::new(4), Tile::new(5), Tile::new(6),
             Tile::new(7), Tile::new(8), Tile::new(BLANK_TILE)
         ]);
         assert_eq!(board.depth, -1);
-        assert_eq!(board.cost, 17);
+        // Depth -1 isn't a path cost, so the cost is the heuristic alone
+        assert_eq!((board.g(), board.cost), (0, 18));
         assert_eq!(board.path, "");
     }
 
@@ -639,12 +3551,11 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         let board_2 = board.clone();
         assert_eq!(board.tiles, board_2.tiles);
-        assert_eq!(board.solved_board.unwrap(), board_2.solved_board.unwrap());
+        assert_eq!(board.cost, board_2.cost);
         assert_eq!(board.blank_index, board_2.blank_index);
-        assert_eq!(board.last_direction, board_2.last_direction);
         assert_eq!(board.n, board_2.n);
         assert_eq!(board.n2, board_2.n2);
     }
@@ -671,7 +3582,7 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         assert_eq!(board.index(0, 0, None), Tile::new(8));
         assert_eq!(board.index(0, 1, None), Tile::new(4));
         assert_eq!(board.index(0, 2, None), Tile::new(6));
@@ -692,11 +3603,36 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
+        assert_eq!(board.to_string(), "8 4 6\n3 7 1\n5 2 _\n");
+        // The original layout, without the blank's trailing spaces
+        let original = BoardFormatter::new().blank("").separator(", ").tile_prefix(true);
+        assert_eq!(original.format(&board), "Tile 8, Tile 4, Tile 6\nTile 3, Tile 7, Tile 1\nTile 5, Tile 2,\n");
+    }
+
+    /// Test formatted boards align mixed-width tiles without trailing whitespace
+    #[test]
+    fn test_board_formatter() {
+        let board = Board::try_from(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, -1, 13, 14, 15, 12]).unwrap();
+        assert_eq!(board.to_string(), " 1  2  3  4\n 5  6  7  8\n 9 10 11  _\n13 14 15 12\n");
+        let formatters = [
+            BoardFormatter::new(),
+            BoardFormatter::new().blank("").separator(", ").tile_prefix(true),
+            BoardFormatter::new().blank("   ").cell_width(Some(4)),
+            BoardFormatter::new().blank("empty").separator(" | "),
+        ];
+        for formatter in &formatters {
+            let text = formatter.format(&board);
+            assert_eq!(text.lines().count(), 4);
+            assert!(text.lines().all(|line| !line.ends_with(' ')), "{:?}", text);
+            // Rows without the blank line up
+            assert_eq!(text.lines().next().unwrap().len(), text.lines().last().unwrap().len());
+        }
         assert_eq!(
-            board.to_string(),
-            "Tile 8, Tile 4, Tile 6\nTile 3, Tile 7, Tile 1\nTile 5, Tile 2,       \n".to_owned()
+            formatters[1].format(&board).lines().nth(2),
+            Some("Tile  9, Tile 10, Tile 11,")
         );
+        assert_eq!(formatters[3].format(&board).lines().nth(2), Some("    9 |    10 |    11 | empty"));
     }
 
     /// Test board get blank index
@@ -712,13 +3648,13 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         assert_eq!(board.get_blank_index(), 4);
     }
 
-    /// Test board is valid move
+    /// Test board is legal move
     #[test]
-    fn test_board_is_valid_move() {
+    fn test_board_is_legal_move() {
         let solved_board = Board::new(3, None, -1, None);
         let tiles = [
             Tile::new(8), Tile::new(4), Tile::new(6),
@@ -729,11 +3665,11 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
-        assert!(board.is_valid_move(UP));
-        assert!(!board.is_valid_move(DOWN));
-        assert!(board.is_valid_move(LEFT));
-        assert!(!board.is_valid_move(RIGHT));
+            Some(tiles.to_vec().into_boxed_slice()));
+        assert!(board.is_legal_move(UP));
+        assert!(!board.is_legal_move(DOWN));
+        assert!(board.is_legal_move(LEFT));
+        assert!(!board.is_legal_move(RIGHT));
         let tiles_2 = [
             Tile::new(8), Tile::new(4), Tile::new(6),
             Tile::new(3), Tile::new(BLANK_TILE), Tile::new(1),
@@ -743,11 +3679,11 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles_2.clone());
-        assert!(board_2.is_valid_move(UP));
-        assert!(board_2.is_valid_move(DOWN));
-        assert!(board_2.is_valid_move(LEFT));
-        assert!(board_2.is_valid_move(RIGHT));
+            Some(tiles_2.to_vec().into_boxed_slice()));
+        assert!(board_2.is_legal_move(UP));
+        assert!(board_2.is_legal_move(DOWN));
+        assert!(board_2.is_legal_move(LEFT));
+        assert!(board_2.is_legal_move(RIGHT));
         let tiles_3 = [
             Tile::new(BLANK_TILE), Tile::new(4), Tile::new(6),
             Tile::new(3), Tile::new(8), Tile::new(1),
@@ -757,16 +3693,17 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles_3.clone());
-        assert!(!board_3.is_valid_move(UP));
-        assert!(board_3.is_valid_move(DOWN));
-        assert!(!board_3.is_valid_move(LEFT));
-        assert!(board_3.is_valid_move(RIGHT));
+            Some(tiles_3.to_vec().into_boxed_slice()));
+        assert!(!board_3.is_legal_move(UP));
+        assert!(board_3.is_legal_move(DOWN));
+        assert!(!board_3.is_legal_move(LEFT));
+        assert!(board_3.is_legal_move(RIGHT));
+        assert!(!board_3.is_legal_move('X'));
     }
 
-    /// Test board get moves
+    /// Test board legal moves
     #[test]
-    fn test_board_get_moves() {
+    fn test_board_legal_moves() {
         let solved_board = Board::new(3, None, -1, None);
         let tiles = [
             Tile::new(8), Tile::new(4), Tile::new(6),
@@ -777,8 +3714,8 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
-        assert_eq!(board.get_moves(), [UP, LEFT]);
+            Some(tiles.to_vec().into_boxed_slice()));
+        assert_eq!(board.legal_moves(), [UP, LEFT]);
         let tiles_2 = [
             Tile::new(8), Tile::new(4), Tile::new(6),
             Tile::new(3), Tile::new(BLANK_TILE), Tile::new(1),
@@ -788,8 +3725,8 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles_2.clone());
-        assert_eq!(board_2.get_moves(), [UP, DOWN, LEFT, RIGHT]);
+            Some(tiles_2.to_vec().into_boxed_slice()));
+        assert_eq!(board_2.legal_moves(), [UP, DOWN, LEFT, RIGHT]);
         let tiles_3 = [
             Tile::new(BLANK_TILE), Tile::new(4), Tile::new(6),
             Tile::new(3), Tile::new(8), Tile::new(1),
@@ -799,8 +3736,18 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles_3.clone());
-        assert_eq!(board_3.get_moves(), [DOWN, RIGHT]);
+            Some(tiles_3.to_vec().into_boxed_slice()));
+        assert_eq!(board_3.legal_moves(), [DOWN, RIGHT]);
+    }
+
+    /// Test legal moves after a move
+    /// - Boards keep no move history, so undoing the last move is still listed
+    #[test]
+    fn test_board_legal_moves_after_move() {
+        let mut board = Board::new(3, None, 0, None);
+        board.move_blank_tile(UP);
+        assert_eq!(board.legal_moves(), [UP, DOWN, LEFT]);
+        assert_eq!(board.clone().legal_moves(), [UP, DOWN, LEFT]);
     }
 
     /// Test board index translation
@@ -816,7 +3763,7 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         assert_eq!(board.translate_index(0, DOWN), 3);
         assert_eq!(board.translate_index(0, RIGHT), 1);
         assert_eq!(board.translate_index(8, UP), 5);
@@ -836,9 +3783,9 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         board.move_blank_tile(UP);
-        assert_eq!(board.tiles, [
+        assert_eq!(*board.tiles, [
             Tile::new(8), Tile::new(4), Tile::new(6),
             Tile::new(3), Tile::new(7), Tile::new(BLANK_TILE),
             Tile::new(5), Tile::new(2), Tile::new(1)
@@ -858,9 +3805,9 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         board.move_blank_tile(LEFT);
-        assert_eq!(board.tiles, [
+        assert_eq!(*board.tiles, [
             Tile::new(8), Tile::new(4), Tile::new(6),
             Tile::new(3), Tile::new(7), Tile::new(1),
             Tile::new(5), Tile::new(BLANK_TILE), Tile::new(2)
@@ -880,9 +3827,9 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         board.move_blank_tile(DOWN);
-        assert_eq!(board.tiles, [
+        assert_eq!(*board.tiles, [
             Tile::new(3), Tile::new(4), Tile::new(6),
             Tile::new(BLANK_TILE), Tile::new(8), Tile::new(1),
             Tile::new(5), Tile::new(2), Tile::new(7)
@@ -902,9 +3849,9 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         board.move_blank_tile(RIGHT);
-        assert_eq!(board.tiles, [
+        assert_eq!(*board.tiles, [
             Tile::new(4), Tile::new(BLANK_TILE), Tile::new(6),
             Tile::new(3), Tile::new(8), Tile::new(1),
             Tile::new(5), Tile::new(2), Tile::new(7)
@@ -924,7 +3871,7 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         assert_eq!(board._manhattan_cost(&solved_board), 18);
     }
 
@@ -941,10 +3888,10 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         board.shuffle(1000);
-        println!("{}", board.to_string());
-        assert_ne!(board.tiles, [
+        println!("{}", board);
+        assert_ne!(*board.tiles, [
             Tile::new(8), Tile::new(4), Tile::new(6),
             Tile::new(3), Tile::new(7), Tile::new(1),
             Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
@@ -960,23 +3907,70 @@ mod tests {
             Tile::new(3), Tile::new(7), Tile::new(1),
             Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
         ];
-        let mut board = Board::new(
+        let board = Board::new(
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         assert!(!board.is_solved());
         let tiles_2 = [
             Tile::new(1), Tile::new(2), Tile::new(3),
             Tile::new(4), Tile::new(5), Tile::new(6),
             Tile::new(7), Tile::new(8), Tile::new(BLANK_TILE)
         ];
-        let mut board_2 = Board::new(
+        let board_2 = Board::new(
             3,
             Some(&solved_board),
             -1,
-            tiles_2.clone());
+            Some(tiles_2.to_vec().into_boxed_slice()));
         assert!(board_2.is_solved());
+        // Boards built without a solved board can still be checked
+        let mut board_3 = Board::new(3, None, 0, None);
+        assert!(board_3.is_solved());
+        board_3.move_blank_tile(UP);
+        assert!(!board_3.is_solved());
+    }
+
+    /// Test the goal checks agree with the Manhattan cost being zero, the check they
+    /// replaced, over random boards
+    #[test]
+    fn test_is_goal_for_parity() {
+        for n in 2..=5 {
+            let solved_board = Board::new(n, None, -1, None);
+            for seed in 0..200 {
+                // Short walks often end back on the goal
+                let board = Board::generate(n, 415 + seed, (seed % 5) as i32).unwrap();
+                let manhattan_solved = board._manhattan_cost(&solved_board) == 0;
+                assert_eq!(board.is_solved(), manhattan_solved, "{}", board);
+                assert_eq!(board.is_goal_for(&solved_board), manhattan_solved, "{}", board);
+            }
+            assert!(!solved_board.is_goal_for(&Board::new(n + 1, None, -1, None)));
+        }
+        let snake = Board::solved_with_preset(3, GoalPreset::Snake);
+        assert!(snake.is_goal_for(&snake.clone()) && !snake.is_solved());
+    }
+
+    /// Test boards can be stored, returned and sent across threads
+    /// - Boards own their tiles and keep no reference to a goal board
+    #[test]
+    fn test_board_is_owned() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<Board>();
+        struct Batch {
+            boards: Vec<Board>,
+        }
+        let batch = Batch { boards: (0..4).map(|_| Board::create_game_board(3, 10)).collect() };
+        let handle = std::thread::spawn(move || batch.boards.iter().all(|board| board.is_solvable()));
+        assert!(handle.join().unwrap());
+    }
+
+    /// Test creating a shuffled game board
+    #[test]
+    fn test_board_create_game_board() {
+        let board = Board::create_game_board(3, 20);
+        assert!(board.is_solvable());
+        assert_eq!(board.depth, 0);
+        assert_eq!(board.cost, board.heuristic_breakdown(None).total);
     }
 
     /// Test board create tile row indices map
@@ -988,12 +3982,12 @@ mod tests {
             Tile::new(3), Tile::new(7), Tile::new(1),
             Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
         ];
-        let mut board = Board::new(
+        let board = Board::new(
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
-        let mut row_map: HashMap<Tile, i32> = HashMap::new();
+            Some(tiles.to_vec().into_boxed_slice()));
+        let mut row_map: BTreeMap<Tile, i32> = BTreeMap::new();
         row_map.insert(Tile::new(8), 0);
         row_map.insert(Tile::new(4), 1);
         row_map.insert(Tile::new(6), 2);
@@ -1015,12 +4009,12 @@ mod tests {
             Tile::new(3), Tile::new(7), Tile::new(1),
             Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
         ];
-        let mut board = Board::new(
+        let board = Board::new(
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
-        let mut col_map: HashMap<Tile, i32> = HashMap::new();
+            Some(tiles.to_vec().into_boxed_slice()));
+        let mut col_map: BTreeMap<Tile, i32> = BTreeMap::new();
         col_map.insert(Tile::new(8), 0);
         col_map.insert(Tile::new(3), 1);
         col_map.insert(Tile::new(5), 2);
@@ -1042,17 +4036,61 @@ mod tests {
             Tile::new(1), Tile::new(7), Tile::new(3),
             Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
         ];
-        let mut board = Board::new(
+        let board = Board::new(
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
         assert_eq!(board.linear_conflicts(&solved_board), 2);
     }
 
-    /// Test board get cost
+    /// Test iterating over cells and viewing rows and columns
     #[test]
-    fn test_board_get_cost() {
+    fn test_board_cells() {
+        let board = Board::try_from(vec![8, 4, 6, 1, 7, 3, 5, 2, -1]).unwrap();
+        let cells: Vec<(usize, usize, i32)> = board.into_iter()
+            .map(|(row, col, tile)| (row, col, tile.symbol()))
+            .collect();
+        assert_eq!(cells, vec![
+            (0, 0, 8), (0, 1, 4), (0, 2, 6),
+            (1, 0, 1), (1, 1, 7), (1, 2, 3),
+            (2, 0, 5), (2, 1, 2), (2, 2, -1),
+        ]);
+        assert_eq!(board.into_iter().len(), 9);
+        let symbols = |tiles: &[Tile]| tiles.iter().map(|tile| tile.symbol()).collect::<Vec<i32>>();
+        assert_eq!(symbols(board.row(1)), vec![1, 7, 3]);
+        assert_eq!(board.column(2).map(|tile| tile.symbol()).collect::<Vec<i32>>(), vec![6, 3, -1]);
+        assert_eq!(board.get_row(2).map(symbols), Some(vec![5, 2, -1]));
+        assert!(board.get_row(3).is_none());
+        assert!(board.get_column(3).is_none());
+        assert!(std::panic::catch_unwind(|| board.row(3).len()).is_err());
+    }
+
+    /// Test per-tile goal distances and misplaced tiles
+    #[test]
+    fn test_per_tile_distances() {
+        let goal = Board::new(3, None, -1, None);
+        let board = Board::try_from(vec![8, 4, 6, 1, 7, 3, 5, 2, -1]).unwrap();
+        let distances = board.per_tile_distances(&goal);
+        assert_eq!(distances, vec![(8, 3), (4, 2), (6, 1), (1, 1), (7, 2), (3, 1), (5, 2), (2, 2)]);
+        let total: u32 = distances.iter().map(|&(_, distance)| distance).sum();
+        assert_eq!(total as i32, board._manhattan_cost(&goal));
+        assert_eq!(board.misplaced_count(), 8);
+        for seed in 393..398 {
+            let board = Board::generate(4, seed, 60).unwrap();
+            let goal = Board::new(4, None, -1, None);
+            let total: u32 = board.per_tile_distances(&goal).iter().map(|&(_, distance)| distance).sum();
+            assert_eq!(total as i32, board._manhattan_cost(&goal));
+            assert_eq!(board.misplaced_count() as i32, board.metrics(&goal).misplaced);
+        }
+        assert!(goal.per_tile_distances(&goal).iter().all(|&(_, distance)| distance == 0));
+        assert_eq!(goal.per_tile_distances(&goal).len(), 8);
+        assert_eq!(goal.misplaced_count(), 0);
+    }
+
+    /// Test the path cost and heuristic of a board are kept apart
+    #[test]
+    fn test_board_g_and_h() {
         let solved_board = Board::new(3, None, -1, None);
         let tiles = [
             Tile::new(8), Tile::new(4), Tile::new(6),
@@ -1063,9 +4101,149 @@ mod tests {
             3,
             Some(&solved_board),
             -1,
-            tiles.clone());
+            Some(tiles.to_vec().into_boxed_slice()));
+        assert_eq!((board.g(), board.h(&solved_board)), (0, 18));
+        board.depth = 5;
+        assert_eq!((board.g(), board.h(&solved_board)), (5, 18));
+        assert_eq!(board.store_h(&solved_board, Heuristic::Manhattan), 18);
+        assert_eq!(board.stored_h(), 18);
+        #[allow(deprecated)]
+        let cost = board.get_cost(&solved_board);
+        assert_eq!(cost, 23);
+        // The solved board has f == 0 at depth 0, and at depth -1 too
+        let mut start = Board::new(3, Some(&solved_board), 0, None);
+        assert_eq!((start.g(), start.h(&solved_board), start.cost), (0, 0, 0));
+        start.depth = -1;
+        assert_eq!(start.g() + start.store_h(&solved_board, Heuristic::LinearConflicts), 0);
+    }
+
+    /// Test heuristic breakdown
+    /// - Components match the cost tests above
+    #[test]
+    fn test_board_heuristic_breakdown() {
+        let tiles = [
+            Tile::new(8), Tile::new(4), Tile::new(6),
+            Tile::new(3), Tile::new(7), Tile::new(1),
+            Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
+        ];
+        let mut board = Board::new(3, None, -1, Some(tiles.to_vec().into_boxed_slice()));
         board.depth = 5;
-        assert_eq!(board.get_cost(&solved_board), 23);
+        let breakdown = board.heuristic_breakdown(None);
+        assert_eq!(breakdown, HeuristicBreakdown {
+            manhattan: 18, linear_conflicts: 0, depth: 5, total: 23,
+        });
+        let solved_board = Board::new(3, None, -1, None);
+        assert_eq!(board.heuristic_breakdown(Some(&solved_board)), breakdown);
+        assert_eq!(board.g() + board.h(&solved_board), breakdown.total);
+        assert_eq!(solved_board.heuristic_breakdown(None).total, 0);
+        let tiles_2 = [
+            Tile::new(8), Tile::new(4), Tile::new(6),
+            Tile::new(1), Tile::new(7), Tile::new(3),
+            Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
+        ];
+        let board_2 = Board::new(3, None, -1, Some(tiles_2.to_vec().into_boxed_slice()));
+        assert_eq!(board_2.heuristic_breakdown(None).linear_conflicts, 2);
+    }
+
+    /// Test board validation
+    #[test]
+    fn test_board_try_new() {
+        let board = Board::try_new(2, vec![
+            Tile::new(1), Tile::new(2), Tile::new(BLANK_TILE), Tile::new(3)
+        ]).unwrap();
+        assert_eq!(board.blank_index, 2);
+        assert_eq!(board.depth, 0);
+        assert_eq!(board.cost, 1);
+        assert_eq!(Board::try_new(0, vec![]), Err(BoardError::InvalidSize(0)));
+        assert_eq!(
+            Board::try_new(2, vec![Tile::new(1), Tile::new(BLANK_TILE)]),
+            Err(BoardError::WrongTileCount { expected: 4, found: 2 })
+        );
+        assert_eq!(
+            Board::try_new(2, vec![Tile::new(1), Tile::new(2), Tile::new(4), Tile::new(BLANK_TILE)]),
+            Err(BoardError::InvalidTile(4))
+        );
+        assert_eq!(
+            Board::try_new(2, vec![Tile::new(1), Tile::new(1), Tile::new(3), Tile::new(BLANK_TILE)]),
+            Err(BoardError::DuplicateTile(1))
+        );
+        assert_eq!(
+            Board::try_new(2, vec![Tile::new(1), Tile::new(2), Tile::new(3), Tile::new(0)]),
+            Err(BoardError::InvalidTile(0))
+        );
+    }
+
+    /// Test board inversions
+    #[test]
+    fn test_board_inversions() {
+        let solved_board = Board::new(3, None, -1, None);
+        assert_eq!(solved_board.inversions(), 0);
+        let tiles = [
+            Tile::new(8), Tile::new(4), Tile::new(6),
+            Tile::new(3), Tile::new(7), Tile::new(1),
+            Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
+        ];
+        let board = Board::new(
+            3,
+            Some(&solved_board),
+            -1,
+            Some(tiles.to_vec().into_boxed_slice()));
+        assert_eq!(board.inversions(), 20);
+        // Blank in the middle must not count
+        let tiles_2 = [
+            Tile::new(1), Tile::new(2), Tile::new(3),
+            Tile::new(4), Tile::new(BLANK_TILE), Tile::new(5),
+            Tile::new(6), Tile::new(7), Tile::new(8)
+        ];
+        let board_2 = Board::new(
+            3,
+            Some(&solved_board),
+            -1,
+            Some(tiles_2.to_vec().into_boxed_slice()));
+        assert_eq!(board_2.inversions(), 0);
+    }
+
+    /// Test board parity and solvability
+    /// - Uses the canonical "swap 14 and 15" unsolvable 4x4
+    #[test]
+    fn test_board_is_solvable() {
+        let solved_board = Board::new(4, None, -1, None);
+        assert_eq!(solved_board.parity(), 0);
+        assert!(solved_board.is_solvable());
+        let tiles = [
+            Tile::new(1), Tile::new(2), Tile::new(3), Tile::new(4),
+            Tile::new(5), Tile::new(6), Tile::new(7), Tile::new(8),
+            Tile::new(9), Tile::new(10), Tile::new(11), Tile::new(12),
+            Tile::new(13), Tile::new(15), Tile::new(14), Tile::new(BLANK_TILE)
+        ];
+        let board = Board::new(
+            4,
+            Some(&solved_board),
+            -1,
+            Some(tiles.to_vec().into_boxed_slice()));
+        assert_eq!(board.inversions(), 1);
+        assert_eq!(board.parity(), 1);
+        assert!(!board.is_solvable());
+        // Moving the blank up a row flips the inversion parity, but not the solvability
+        let tiles_2 = [
+            Tile::new(1), Tile::new(2), Tile::new(3), Tile::new(4),
+            Tile::new(5), Tile::new(6), Tile::new(7), Tile::new(8),
+            Tile::new(9), Tile::new(10), Tile::new(11), Tile::new(BLANK_TILE),
+            Tile::new(13), Tile::new(14), Tile::new(15), Tile::new(12)
+        ];
+        let board_2 = Board::new(
+            4,
+            Some(&solved_board),
+            -1,
+            Some(tiles_2.to_vec().into_boxed_slice()));
+        assert_eq!(board_2.inversions(), 3);
+        assert_eq!(board_2.parity(), 0);
+        assert!(board_2.is_solvable());
+        // Odd width boards only depend on inversions
+        let solved_board_3 = Board::new(3, None, -1, None);
+        let mut board_3 = solved_board_3.clone();
+        board_3.shuffle(100);
+        assert!(board_3.is_solvable());
     }
 
     /// Test board priority queue
@@ -1078,27 +4256,27 @@ mod tests {
             Tile::new(3), Tile::new(7), Tile::new(1),
             Tile::new(5), Tile::new(2), Tile::new(BLANK_TILE)
         ];
-        // Create some boards with manually entered costs and shuffle to make them different
+        // Create some boards, shuffle to make them different and enter costs manually
         let mut board = Board::new(
-            3, Some(&solved_board), -1, tiles.clone());
-        board.cost = 17;
+            3, Some(&solved_board), -1, Some(tiles.to_vec().into_boxed_slice()));
         board.shuffle(1000);
+        board.cost = 17;
         let mut board_2 = Board::new(
-            3, Some(&solved_board), -1, tiles.clone());
-        board_2.cost = 5;
+            3, Some(&solved_board), -1, Some(tiles.to_vec().into_boxed_slice()));
         board_2.shuffle(1000);
+        board_2.cost = 5;
         let mut board_3 = Board::new(
-            3, Some(&solved_board), -1, tiles.clone());
-        board_3.cost = 1;
+            3, Some(&solved_board), -1, Some(tiles.to_vec().into_boxed_slice()));
         board_3.shuffle(1000);
+        board_3.cost = 1;
         let mut board_4 = Board::new(
-            3, Some(&solved_board), -1, tiles.clone());
-        board_4.cost = 5;
+            3, Some(&solved_board), -1, Some(tiles.to_vec().into_boxed_slice()));
         board_4.shuffle(1000);
+        board_4.cost = 5;
         let mut board_5 = Board::new(
-            3, Some(&solved_board), -1, tiles.clone());
-        board_5.cost = 7;
+            3, Some(&solved_board), -1, Some(tiles.to_vec().into_boxed_slice()));
         board_5.shuffle(1000);
+        board_5.cost = 7;
         // Assert all the costs for verification purposes
         assert_eq!(board.cost, 17);
         assert_eq!(board_2.cost, 5);
@@ -1128,4 +4306,266 @@ mod tests {
         assert_eq!(pq.pop().unwrap(), &board_5);
         assert_eq!(pq.pop().unwrap(), &board);
     }
+
+    /// Test scramble metrics of constructed boards
+    #[test]
+    fn test_board_metrics() {
+        let goal = Board::new(3, None, -1, None);
+        let metrics = |symbols: Vec<i32>| Board::try_from(symbols).unwrap().metrics(&goal);
+        let solved = goal.metrics(&goal);
+        assert_eq!(solved, BoardMetrics {
+            misplaced: 0, out_of_row: 0, out_of_column: 0, correct_prefix: 9, final_moves_forced: false,
+        });
+        // Two moves from the goal
+        assert_eq!(metrics(vec![1, 2, 3, 4, -1, 6, 7, 5, 8]), BoardMetrics {
+            misplaced: 2, out_of_row: 1, out_of_column: 1, correct_prefix: 4, final_moves_forced: true,
+        });
+        // Tile 4 has left the block around the blank's goal position
+        assert_eq!(metrics(vec![1, 2, 3, -1, 4, 6, 7, 5, 8]), BoardMetrics {
+            misplaced: 3, out_of_row: 1, out_of_column: 2, correct_prefix: 3, final_moves_forced: false,
+        });
+        assert_eq!(metrics(vec![8, 7, 6, 5, 4, 3, 2, 1, -1]), BoardMetrics {
+            misplaced: 8, out_of_row: 6, out_of_column: 6, correct_prefix: 0, final_moves_forced: false,
+        });
+        let small = Board::try_from(vec![2, 1, 3, -1]).unwrap();
+        assert!(small.metrics(&Board::new(2, None, -1, None)).final_moves_forced);
+        assert_eq!(
+            solved.to_json(),
+            "{\"misplaced\":0,\"out_of_row\":0,\"out_of_column\":0,\"correct_prefix\":9,\"final_moves_forced\":false}"
+        );
+    }
+
+    /// Test solving the bottom-right of a board as a sub-board solves the full board
+    #[test]
+    fn test_sub_board() {
+        let mut rng = StdRng::seed_from_u64(381);
+        let mut board = Board::new(4, None, -1, None);
+        for _ in 0..40 {
+            let moves: Vec<char> = board.legal_moves().into_iter()
+                .filter(|&m| board.translate_index(board.blank_index, m) % 4 != 0)
+                .filter(|&m| board.translate_index(board.blank_index, m) >= 4)
+                .collect();
+            board.move_blank_tile(*moves.choose(&mut rng).unwrap());
+        }
+        assert!(!board.is_solved());
+        let sub = board.sub_board(1, 1, 3, 3).unwrap();
+        let solution = crate::solver::solve(&sub, &crate::solver::SolveOptions::new()).unwrap();
+        let path = sub.embed_path(&solution.moves, (1, 1));
+        assert!(board.apply_moves(&path, Notation::BlankMoves).unwrap().is_solved());
+        // Tile 1 belongs outside the region, so it stands in for tile 6
+        let mut symbols: Vec<i32> = (1..16).chain(std::iter::once(-1)).collect();
+        symbols.swap(0, 5);
+        let swapped = Board::try_from(symbols).unwrap();
+        assert!(swapped.sub_board(1, 1, 3, 3).unwrap().is_solved());
+        assert_eq!(swapped.sub_board(1, 1, 2, 2), Err(BoardError::InvalidRegion { top: 1, left: 1, rows: 2, cols: 2 }));
+        assert_eq!(swapped.sub_board(1, 0, 3, 4).unwrap_err().code(), 112);
+        let blank_on_top = Board::new(4, None, -1, None).apply_moves("UUU", Notation::BlankMoves).unwrap();
+        assert_eq!(blank_on_top.sub_board(1, 1, 3, 3), Err(BoardError::BlankOutsideRegion));
+    }
+
+    /// Test similarity and signatures tell near-duplicates from unrelated boards
+    #[test]
+    fn test_similarity() {
+        let mut rng = StdRng::seed_from_u64(384);
+        let board = Board::generate(4, 384, 200).unwrap();
+        assert_eq!(board.similarity(&board.clone()), 1.0);
+        assert_eq!(board.similarity(&Board::new(3, None, -1, None)), 0.0);
+        for tile_move in board.legal_moves() {
+            let neighbor = board.apply_moves(&tile_move.to_string(), Notation::BlankMoves).unwrap();
+            assert_eq!(neighbor.similarity(&board), 14.0 / 16.0);
+            // Only the moved tile's distance changes, by one
+            let (a, b) = (board.signature().to_le_bytes(), neighbor.signature().to_le_bytes());
+            let changed: i32 = a.iter().zip(b.iter()).map(|(&x, &y)| (x as i32 - y as i32).abs()).sum();
+            assert_eq!(changed, 2);
+        }
+        let solved = Board::new(4, None, -1, None);
+        assert_eq!(solved.signature(), 15);
+        for _ in 0..20 {
+            let a = Board::generate(4, rng.gen(), 500).unwrap();
+            let b = Board::generate(4, rng.gen(), 500).unwrap();
+            assert!(a.similarity(&b) < 0.5);
+        }
+        let swapped = Board::try_from(vec![2, 1, 3, 4, 5, 6, 7, 8, -1]).unwrap();
+        let pool = vec![Board::new(3, None, -1, None), board.clone(), swapped, board.rotate_cw()];
+        let deduped = dedupe_pool(pool, 0.75);
+        assert_eq!(deduped.len(), 3);
+        assert_eq!(deduped[1], board);
+        assert_eq!(dedupe_pool(vec![board.clone(), board.clone()], 1.0), vec![board]);
+    }
+
+    /// Test goal presets lay out valid, solved goals and flip parity where they should
+    #[test]
+    fn test_goal_presets() {
+        let layouts = [
+            (GoalPreset::Standard, [1, 2, 3, 4, 5, 6, 7, 8, -1]),
+            (GoalPreset::BlankFirst, [-1, 1, 2, 3, 4, 5, 6, 7, 8]),
+            (GoalPreset::Snake, [1, 2, 3, 6, 5, 4, 7, 8, -1]),
+            (GoalPreset::ColumnMajor, [1, 4, 7, 2, 5, 8, 3, 6, -1]),
+        ];
+        for (preset, symbols) in &layouts {
+            let goal = Board::solved_with_preset(3, *preset);
+            assert_eq!(goal.tiles().iter().map(|tile| tile.symbol()).collect::<Vec<i32>>(), symbols.to_vec());
+        }
+        assert_eq!(Board::solved_with_preset(4, GoalPreset::Snake).get_blank_index(), 12);
+        for n in 2..=5 {
+            for &preset in &GOAL_PRESETS {
+                let goal = Board::solved_with_preset(n, preset);
+                assert!(Board::try_new(n, goal.tiles().to_vec()).is_ok());
+                assert!(goal.is_solved_with_preset(preset));
+                assert!(goal.is_solvable_with_preset(preset));
+                assert_eq!(goal.is_solved(), preset == GoalPreset::Standard);
+                let mut swapped = goal.tiles().to_vec();
+                let first = swapped.iter().position(|tile| !tile.is_blank()).unwrap();
+                let second = first + 1 + usize::from(swapped[first + 1].is_blank());
+                swapped.swap(first, second);
+                let swapped = Board::new(n, None, 0, Some(swapped.into_boxed_slice()));
+                assert!(!swapped.is_solvable_with_preset(preset));
+            }
+        }
+        // Blank-first 4x4 moves the blank an odd number of rows, flipping the usual rule
+        let standard = Board::new(4, None, 0, None);
+        assert!(standard.is_solvable());
+        assert!(!standard.is_solvable_with_preset(GoalPreset::BlankFirst));
+    }
+
+    /// Find every board reachable from a goal by breadth-first search.
+    fn _reachable(goal: &Board) -> HashMap<Vec<Tile>, usize> {
+        let mut distances = HashMap::new();
+        distances.insert(goal.tiles().to_vec(), 0);
+        let mut queue = std::collections::VecDeque::from(vec![goal.clone()]);
+        while let Some(board) = queue.pop_front() {
+            let distance = distances[board.tiles()];
+            for tile_move in board.legal_moves() {
+                let mut neighbour = board.clone();
+                neighbour.move_blank_tile(tile_move);
+                if let std::collections::hash_map::Entry::Vacant(entry) = distances.entry(neighbour.tiles().to_vec()) {
+                    entry.insert(distance + 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Test reachability matches breadth-first search for every ordered pair of 2x2 boards
+    #[test]
+    fn test_reachability_2x2_pairs() {
+        let symbols = [1, 2, 3, BLANK_TILE];
+        let boards: Vec<Board> = (0..256)
+            .map(|i: usize| (0..4).map(|cell| symbols[i >> (2 * cell) & 3]).collect::<Vec<i32>>())
+            .filter(|layout| (0..4).all(|a| (0..a).all(|b| layout[a] != layout[b])))
+            .map(|layout| Board::new(2, None, 0, Some(layout.into_iter().map(Tile::new).collect())))
+            .collect();
+        assert_eq!(boards.len(), 24);
+        for goal in &boards {
+            let reachable = _reachable(goal);
+            assert_eq!(reachable.len(), 12);
+            for board in &boards {
+                let distance = reachable.get(board.tiles());
+                assert_eq!(board.reachability(goal).is_ok(), distance.is_some(), "{:?} {:?}", board, goal);
+                if let Some(distance) = distance {
+                    assert_eq!(board.move_count_parity(goal), distance % 2);
+                }
+            }
+        }
+    }
+
+    /// Test reachability matches breadth-first search for every 3x3 board, towards a
+    /// goal with the blank in the centre
+    #[test]
+    fn test_reachability_3x3_exhaustive() {
+        let centre = [1, 2, 3, 4, BLANK_TILE, 5, 6, 7, 8];
+        let goal = Board::new(3, None, 0, Some(centre.iter().map(|&symbol| Tile::new(symbol)).collect()));
+        let reachable = _reachable(&goal);
+        assert_eq!(reachable.len(), 181_440);
+        for (tiles, &distance) in &reachable {
+            let board = Board::new(3, None, 0, Some(tiles.clone().into_boxed_slice()));
+            assert_eq!(board.reachability(&goal), Ok(()));
+            assert_eq!(board.move_count_parity(&goal), distance % 2);
+            // Swapping two tiles gives every other layout exactly once
+            let mut swapped = tiles.clone();
+            let (a, b) = if board.get_blank_index() < 2 { (2, 3) } else { (0, 1) };
+            swapped.swap(a, b);
+            let swapped = Board::new(3, None, 0, Some(swapped.into_boxed_slice()));
+            let (a, b) = match swapped.reachability(&goal).unwrap_err().problems[..] {
+                [SolvabilityProblem::ParityMismatch { swap }] => swap,
+                ref problems => panic!("expected a parity mismatch, got {:?}", problems),
+            };
+            // The suggested fix always works
+            let fixed: Vec<Tile> = swapped.tiles().iter()
+                .map(|tile| match tile.symbol() {
+                    symbol if symbol == a => Tile::new(b),
+                    symbol if symbol == b => Tile::new(a),
+                    _ => *tile,
+                })
+                .collect();
+            assert_eq!(Board::new(3, None, 0, Some(fixed.into_boxed_slice())).reachability(&goal), Ok(()));
+        }
+        let unreachable = Board::new(3, None, 0, None).reachability(&Board::new(4, None, 0, None)).unwrap_err();
+        assert_eq!(unreachable.to_string(), "board can't reach the goal: the board is 3x3 but the goal is 4x4");
+        assert_eq!(unreachable.code(), 117);
+    }
+
+    /// Test solvability reports for each kind of problem, and that the suggested fix works
+    #[test]
+    fn test_solvability() {
+        let goal = Board::new(4, None, -1, None);
+        let unvalidated = |n: i32, symbols: Vec<i32>| {
+            Board::new(n, None, 0, Some(symbols.into_iter().map(Tile::new).collect()))
+        };
+        let swap = |board: &Board, (a, b): (i32, i32)| {
+            let symbols: Vec<i32> = board.tiles().iter()
+                .map(|tile| match tile.symbol() {
+                    symbol if symbol == a => b,
+                    symbol if symbol == b => a,
+                    symbol => symbol,
+                })
+                .collect();
+            unvalidated(board.size(), symbols)
+        };
+        let mut rng = StdRng::seed_from_u64(386);
+        for _ in 0..20 {
+            let board = Board::generate(4, rng.gen(), 100).unwrap();
+            assert_eq!(board.solvability(&goal), SolvabilityReport { problems: vec![] });
+            // Swapping any two tiles flips the parity
+            let mut symbols: Vec<i32> = board.tiles().iter().map(|tile| tile.symbol()).collect();
+            let blank = board.get_blank_index() as usize;
+            let (a, b) = if blank < 2 { (2, 3) } else { (0, 1) };
+            symbols.swap(a, b);
+            let unsolvable = unvalidated(4, symbols);
+            assert!(!unsolvable.is_solvable());
+            let report = unsolvable.solvability(&goal);
+            let fix = report.suggest_fix().unwrap();
+            let fixed = swap(&unsolvable, fix);
+            assert!(fixed.solvability(&goal).is_solvable());
+            assert!(fixed.is_solvable());
+        }
+        let classic = unvalidated(4, (1..14).chain(vec![15, 14, -1]).collect());
+        assert_eq!(classic.solvability(&goal).suggest_fix(), Some((15, 14)));
+        // Custom goals with other symbols and the blank elsewhere
+        let custom_goal = unvalidated(2, vec![-1, 10, 20, 30]);
+        let custom = unvalidated(2, vec![10, -1, 20, 30]);
+        assert!(custom.solvability(&custom_goal).is_solvable());
+        assert_eq!(
+            unvalidated(2, vec![10, -1, 30, 20]).solvability(&custom_goal).suggest_fix(),
+            Some((30, 20))
+        );
+        assert_eq!(unvalidated(2, vec![1, 2, 3, -1]).solvability(&custom_goal).problems, vec![
+            SolvabilityProblem::NotInGoal(1), SolvabilityProblem::NotInGoal(2), SolvabilityProblem::NotInGoal(3),
+            SolvabilityProblem::NotOnBoard(10), SolvabilityProblem::NotOnBoard(20), SolvabilityProblem::NotOnBoard(30),
+        ]);
+        let duplicates = unvalidated(2, vec![1, 1, -1, -1]).solvability(&Board::new(2, None, -1, None));
+        assert_eq!(duplicates.problems[..2], [
+            SolvabilityProblem::BlankCount { blanks: 2, goal_blanks: 1 }, SolvabilityProblem::DuplicateTile(1),
+        ]);
+        assert_eq!(duplicates.suggest_fix(), None);
+        assert_eq!(
+            goal.solvability(&Board::new(3, None, -1, None)).problems,
+            vec![SolvabilityProblem::SizeMismatch { size: 4, goal_size: 3 }]
+        );
+        assert_eq!(
+            SolvabilityProblem::ParityMismatch { swap: (14, 15) }.to_string(),
+            "the tiles are an odd permutation from the goal; swap tiles 14 and 15"
+        );
+    }
 }
@@ -0,0 +1,80 @@
+//! Version and build details of the solver, e.g. for telemetry and bug reports from
+//! a deployed wasm bundle.
+//!
+//! Features and the git hash are recorded by the build script, `build.rs`.
+
+/// How the solver was built.
+///
+/// # Attributes
+/// * `version` - Crate version
+/// * `git_hash` - Short hash of the commit built, if built from a git checkout
+/// * `features` - Enabled cargo features, sorted
+/// * `profile` - "debug" or "release"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    pub version: String,
+    pub git_hash: Option<String>,
+    pub features: Vec<String>,
+    pub profile: String,
+}
+
+impl BuildInfo {
+    /// Format the build details as a JSON object.
+    /// * None of the details contain characters that need escaping.
+    ///
+    /// # Returns
+    /// JSON object string with the `version`, `git_hash` (null if unknown), `features`
+    /// and `profile`
+    pub fn to_json(&self) -> String {
+        let features: Vec<String> = self.features.iter().map(|feature| format!("\"{}\"", feature)).collect();
+        format!(
+            "{{\"version\":\"{}\",\"git_hash\":{},\"features\":[{}],\"profile\":\"{}\"}}",
+            self.version,
+            self.git_hash.as_ref().map_or("null".to_string(), |hash| format!("\"{}\"", hash)),
+            features.join(","),
+            self.profile,
+        )
+    }
+}
+
+/// Get the details of this build.
+///
+/// # Returns
+/// Version, git hash, enabled features and profile
+pub fn build_info() -> BuildInfo {
+    let features = env!("SOLVER_FEATURES").split(',').filter(|feature| !feature.is_empty());
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_hash: option_env!("SOLVER_GIT_HASH").map(str::to_string),
+        features: features.map(str::to_string).collect(),
+        profile: if cfg!(debug_assertions) { "debug" } else { "release" }.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the build details match the crate and its enabled features
+    #[test]
+    fn test_build_info() {
+        let info = build_info();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        for &(feature, enabled) in &[("std", cfg!(feature = "std")), ("ffi", cfg!(feature = "ffi")),
+                                    ("chaos", cfg!(feature = "chaos"))] {
+            assert_eq!(info.features.contains(&feature.to_string()), enabled, "{}", feature);
+        }
+        assert!(info.features.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(info.profile, if cfg!(debug_assertions) { "debug" } else { "release" });
+        let info = BuildInfo {
+            version: "1.2.3".to_string(),
+            git_hash: None,
+            features: vec!["chaos".to_string(), "std".to_string()],
+            profile: "release".to_string(),
+        };
+        let json = "{\"version\":\"1.2.3\",\"git_hash\":null,\"features\":[\"chaos\",\"std\"],\"profile\":\"release\"}";
+        assert_eq!(info.to_json(), json);
+        let hashed = BuildInfo { git_hash: Some("0123abcd".to_string()), features: Vec::new(), ..info };
+        assert!(hashed.to_json().contains("\"git_hash\":\"0123abcd\",\"features\":[],"));
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use super::board::Board;
+use super::pack;
+use super::solver::{SolveStats, Solution};
+
+/// Cache key: board size and packed tiles
+type CacheKey = (i32, u64);
+
+/// A cached solution.
+///
+/// # Attributes
+/// * `moves` - Solution moves from the cached board
+/// * `states` - Keys of the boards along the solution, one per move plus the start
+/// * `last_used` - Tick of the most recent use, for LRU eviction
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    moves: String,
+    states: Vec<CacheKey>,
+    last_used: u64,
+}
+
+/// Least-recently-used cache of solutions keyed on packed board state.
+/// * Every board along a cached solution can be answered with the rest of the path.
+/// * Only boards that can be packed (n <= 4) are cached.
+///
+/// # Attributes
+/// * `capacity` - Maximum number of cached solutions
+/// * `tick` - Counter used to order uses
+/// * `entries` - Cached solutions keyed on their starting board
+/// * `positions` - Board along a cached solution -> (starting board, moves already made)
+#[derive(Debug, Clone)]
+pub struct SolveCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<CacheKey, CacheEntry>,
+    positions: HashMap<CacheKey, (CacheKey, usize)>,
+}
+
+impl SolveCache {
+    /// Create an empty cache.
+    ///
+    /// # Parameters
+    /// * `capacity` - Maximum number of cached solutions
+    pub fn new(capacity: usize) -> SolveCache {
+        SolveCache {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Get the key of a board.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    ///
+    /// # Returns
+    /// Key of the board, if it can be packed
+    fn key(board: &Board) -> Option<CacheKey> {
+        if board.size() > pack::MAX_PACKED_SIZE {
+            return None;
+        }
+        Some((board.size(), pack::encode_tiles(board.tiles())))
+    }
+
+    /// Number of cached solutions.
+    ///
+    /// # Returns
+    /// Cached solution count
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache is empty.
+    ///
+    /// # Returns
+    /// Whether no solutions are cached
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all cached solutions.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.positions.clear();
+    }
+
+    /// Look up the solution of a board.
+    /// * Hits report empty stats, since no search was done.
+    ///
+    /// # Parameters
+    /// * `board` - The board to solve
+    ///
+    /// # Returns
+    /// The cached solution, if any
+    pub fn get(&mut self, board: &Board) -> Option<Solution> {
+        let (root, offset) = *self.positions.get(&SolveCache::key(board)?)?;
+        self.tick += 1;
+        let entry = self.entries.get_mut(&root)?;
+        entry.last_used = self.tick;
+        Some(Solution {
+            moves: entry.moves[offset..].to_string(),
+            stats: SolveStats::default(),
+            optimal: true,
+        })
+    }
+
+    /// Cache the solution of a board.
+    ///
+    /// # Parameters
+    /// * `board` - The solved board
+    /// * `moves` - Solution moves
+    pub fn insert(&mut self, board: &Board, moves: &str) {
+        let root = match SolveCache::key(board) {
+            Some(root) => root,
+            None => return,
+        };
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(root);
+        while self.entries.len() >= self.capacity {
+            let oldest = *self.entries.iter().min_by_key(|(_, entry)| entry.last_used).unwrap().0;
+            self.remove(oldest);
+        }
+        // Record each board along the solution
+        let mut replay = board.clone();
+        let mut states = vec![root];
+        for tile_move in moves.chars() {
+            replay.move_blank_tile(tile_move);
+            states.push(SolveCache::key(&replay).unwrap());
+        }
+        for (offset, state) in states.iter().enumerate() {
+            self.positions.insert(*state, (root, offset));
+        }
+        self.tick += 1;
+        self.entries.insert(root, CacheEntry { moves: moves.to_string(), states, last_used: self.tick });
+    }
+
+    /// Remove a cached solution and the positions that point at it.
+    ///
+    /// # Parameters
+    /// * `root` - Key of the solution's starting board
+    fn remove(&mut self, root: CacheKey) {
+        if let Some(entry) = self.entries.remove(&root) {
+            for state in entry.states {
+                if self.positions.get(&state).map(|position| position.0) == Some(root) {
+                    self.positions.remove(&state);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{self, SolveOptions};
+
+    /// Create a 3x3 board by moving the blank from the solved position.
+    fn scrambled(moves: &str) -> Board {
+        let mut board = Board::try_new(3, Board::new(3, None, 0, None).tiles().to_vec()).unwrap();
+        for tile_move in moves.chars() {
+            board.move_blank_tile(tile_move);
+        }
+        board
+    }
+
+    /// Test a board along a cached solution returns the suffix
+    #[test]
+    fn test_solve_cache_suffix_hit() {
+        let mut cache = SolveCache::new(4);
+        let board = scrambled("LURDLULDRU");
+        assert_eq!(cache.get(&board), None);
+        let solution = solver::solve(&board, &SolveOptions::default()).unwrap();
+        cache.insert(&board, &solution.moves);
+        assert_eq!(cache.get(&board).unwrap().moves, solution.moves);
+        // Replay two moves of the solution
+        let mut replay = board.clone();
+        for tile_move in solution.moves[..2].chars() {
+            replay.move_blank_tile(tile_move);
+        }
+        let hit = cache.get(&replay).unwrap();
+        assert_eq!(hit.moves, solution.moves[2..]);
+        assert_eq!(hit.moves, solver::solve(&replay, &SolveOptions::default()).unwrap().moves);
+        assert_eq!(hit.stats, SolveStats::default());
+    }
+
+    /// Test least recently used solutions are evicted
+    #[test]
+    fn test_solve_cache_eviction() {
+        let mut cache = SolveCache::new(2);
+        let boards = [scrambled("LU"), scrambled("UL"), scrambled("LLU")];
+        cache.insert(&boards[0], "DR");
+        cache.insert(&boards[1], "RD");
+        // Use the first so the second is the oldest
+        assert!(cache.get(&boards[0]).is_some());
+        cache.insert(&boards[2], "DRR");
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&boards[0]).is_some());
+        assert!(cache.get(&boards[1]).is_none());
+        assert_eq!(cache.get(&boards[2]).unwrap().moves, "DRR");
+        // Positions of the evicted solution are gone, shared ones still answer
+        let mut after_r = boards[1].clone();
+        after_r.move_blank_tile('R');
+        assert!(cache.get(&after_r).is_none());
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(cache.get(&boards[0]).is_none());
+    }
+}
@@ -0,0 +1,169 @@
+//! Wall clock for timing solves, usable both natively and in the browser.
+//!
+//! `std::time::Instant` panics on wasm32-unknown-unknown, so every timing in the crate
+//! (time budgets, solve statistics, progress events and report files) reads the clock
+//! through `now_ms` instead, which uses `Date.now()` under wasm.
+
+use std::time::Duration;
+
+/// Time a `TimeBudget` aims to leave between reads of the clock, in milliseconds
+pub const TARGET_SAMPLE_MS: f64 = 10.0;
+
+/// Most checks a `TimeBudget` lets pass between reads of the clock
+pub const MAX_SAMPLE_INTERVAL: u64 = 1 << 16;
+
+#[cfg(not(target_arch = "wasm32"))]
+lazy_static! {
+    /// Time of the first read of the clock, which native readings are relative to
+    static ref EPOCH: std::time::Instant = std::time::Instant::now();
+}
+
+/// Read the clock.
+/// * Only differences between readings are meaningful: natively the clock starts at the
+///   first reading, and under wasm it is milliseconds since the Unix epoch.
+///
+/// # Returns
+/// Current time in milliseconds
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    EPOCH.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Read the clock.
+/// * Only differences between readings are meaningful: natively the clock starts at the
+///   first reading, and under wasm it is milliseconds since the Unix epoch.
+///
+/// # Returns
+/// Current time in milliseconds
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Get the time passed since an earlier reading of the clock.
+///
+/// # Parameters
+/// * `start_ms` - Earlier reading of `now_ms`
+///
+/// # Returns
+/// Time passed, zero if the clock went backwards
+pub fn elapsed_since(start_ms: f64) -> Duration {
+    Duration::from_secs_f64((now_ms() - start_ms).max(0.0) / 1000.0)
+}
+
+/// Deadline of a search, cheap enough to check on every expansion.
+/// * The clock is only read every `interval` checks. The interval doubles while reads
+///   come less than half of `TARGET_SAMPLE_MS` apart and halves while they come more
+///   than twice that apart, so however fast the search expands boards, the deadline is
+///   noticed within a few tens of milliseconds.
+///
+/// # Attributes
+/// * `deadline_ms` - Reading of the clock at which the budget runs out; infinite for
+///   no budget
+/// * `interval` - Checks between reads of the clock
+/// * `countdown` - Checks left until the next read
+/// * `last_sample_ms` - Last reading of the clock
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeBudget {
+    deadline_ms: f64,
+    interval: u64,
+    countdown: u64,
+    last_sample_ms: f64,
+}
+
+impl TimeBudget {
+    /// Start a budget now.
+    ///
+    /// # Parameters
+    /// * `budget_ms` - Milliseconds until the budget runs out, or `None` for no budget
+    ///
+    /// # Returns
+    /// The budget
+    pub fn new(budget_ms: Option<u64>) -> TimeBudget {
+        let now = now_ms();
+        match budget_ms {
+            Some(budget_ms) => TimeBudget {
+                deadline_ms: now + budget_ms as f64,
+                interval: 1,
+                countdown: 1,
+                last_sample_ms: now,
+            },
+            None => TimeBudget {
+                deadline_ms: f64::INFINITY,
+                interval: u64::MAX,
+                countdown: u64::MAX,
+                last_sample_ms: now,
+            },
+        }
+    }
+
+    /// Get the time left before the budget runs out, e.g. to carry it over to a new
+    /// budget.
+    ///
+    /// # Returns
+    /// Whole milliseconds left, 0 once the deadline has passed, or `None` for no budget
+    pub fn remaining_ms(&self) -> Option<u64> {
+        if self.deadline_ms.is_infinite() {
+            return None;
+        }
+        Some((self.deadline_ms - now_ms()).max(0.0) as u64)
+    }
+
+    /// Check if the budget has run out, e.g. once per expansion.
+    ///
+    /// # Returns
+    /// Whether the deadline has passed, as of the last read of the clock
+    pub fn expired(&mut self) -> bool {
+        if self.countdown > 1 {
+            self.countdown -= 1;
+            return false;
+        }
+        if self.deadline_ms.is_infinite() {
+            self.countdown = u64::MAX;
+            return false;
+        }
+        let now = now_ms();
+        let since = now - self.last_sample_ms;
+        if since < TARGET_SAMPLE_MS / 2.0 {
+            self.interval = (self.interval * 2).min(MAX_SAMPLE_INTERVAL);
+        } else if since > TARGET_SAMPLE_MS * 2.0 {
+            self.interval = (self.interval / 2).max(1);
+        }
+        self.countdown = self.interval;
+        self.last_sample_ms = now;
+        now >= self.deadline_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the clock moves forward with real time
+    #[test]
+    fn test_now_ms() {
+        let start = now_ms();
+        std::thread::sleep(Duration::from_millis(20));
+        let elapsed = elapsed_since(start);
+        assert!(elapsed >= Duration::from_millis(20), "{:?}", elapsed);
+        assert!(now_ms() >= start + 20.0);
+    }
+
+    /// Test budgets expire on time however cheap the work between checks is
+    #[test]
+    fn test_time_budget() {
+        let mut unlimited = TimeBudget::new(None);
+        assert!((0..1_000_000).all(|_| !unlimited.expired()));
+        let start = now_ms();
+        let mut budget = TimeBudget::new(Some(30));
+        let mut checks: u64 = 0;
+        while !budget.expired() {
+            checks += 1;
+        }
+        let elapsed = now_ms() - start;
+        assert!((30.0..80.0).contains(&elapsed), "{} ms over {} checks", elapsed, checks);
+        // Clock reads are spread out when checks are cheap
+        assert!(budget.interval > 1);
+        assert!(TimeBudget::new(Some(0)).expired());
+    }
+}
@@ -0,0 +1,248 @@
+#[cfg(alloc_only)]
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use super::board::Board;
+use super::pack;
+
+/// Number of entries in the cache, a power of two
+const CONFLICT_CACHE_SIZE: usize = 1 << 12;
+
+/// Key bit marking an entry as filled, so an empty entry never matches
+const VALID_KEY: u32 = 1 << 31;
+
+/// Direct-mapped cache of linear conflicts per row and column.
+/// * Lines are keyed on their packed contents (a u16 for n <= 4), so a move only
+///   misses on the row and column it changed; nothing needs invalidating.
+/// * Rows are counted before columns and a tile only counts towards one conflict, so a
+///   column's key also holds which of its tiles already conflict in their row. Totals
+///   match `Board::heuristic_breakdown` exactly.
+/// * Boards larger than 4x4 are counted without the cache.
+///
+/// # Attributes
+/// * `n` - Size of the goal board
+/// * `solved_board` - Goal the conflicts are counted against
+/// * `goal_rows` - Goal row of each tile symbol
+/// * `goal_cols` - Goal column of each tile symbol
+/// * `entries` - Cached line keys and values (conflicts in the low byte, conflicting
+///   positions in the high byte)
+/// * `hits` - Number of lines answered from the cache
+/// * `misses` - Number of lines counted and added to the cache
+#[derive(Debug, Clone)]
+pub struct ConflictCache {
+    n: i32,
+    solved_board: Board,
+    goal_rows: Vec<i32>,
+    goal_cols: Vec<i32>,
+    entries: Box<[(u32, u16)]>,
+    hits: u64,
+    misses: u64,
+}
+
+impl ConflictCache {
+    /// Create an empty cache for a goal.
+    ///
+    /// # Parameters
+    /// * `solved_board` - Goal the conflicts are counted against
+    pub fn new(solved_board: &Board) -> ConflictCache {
+        let n = solved_board.size();
+        let mut goal_rows = vec![-1; (n * n) as usize + 1];
+        let mut goal_cols = goal_rows.clone();
+        for (i, tile) in solved_board.tiles().iter().enumerate() {
+            if !tile.is_blank() {
+                goal_rows[tile.symbol() as usize] = i as i32 / n;
+                goal_cols[tile.symbol() as usize] = i as i32 % n;
+            }
+        }
+        ConflictCache {
+            n,
+            solved_board: solved_board.clone(),
+            goal_rows,
+            goal_cols,
+            entries: vec![(0, 0); CONFLICT_CACHE_SIZE].into_boxed_slice(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Number of lines answered from the cache.
+    ///
+    /// # Returns
+    /// Cache hits
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of lines that had to be counted.
+    ///
+    /// # Returns
+    /// Cache misses
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Count the linear conflicts of a board against the cache's goal.
+    ///
+    /// # Parameters
+    /// * `board` - Board of the goal's size
+    ///
+    /// # Returns
+    /// Linear conflict penalty
+    pub fn linear_conflicts(&mut self, board: &Board) -> i32 {
+        if self.n > pack::MAX_PACKED_SIZE {
+            return board.heuristic_breakdown(Some(&self.solved_board)).linear_conflicts;
+        }
+        let mut total = 0;
+        // Positions within each row of tiles already conflicting
+        let mut row_masks = [0u8; 4];
+        for row in 0..self.n {
+            let (conflicts, mask) = self._line(board, row, false, 0);
+            total += conflicts;
+            row_masks[row as usize] = mask;
+        }
+        for col in 0..self.n {
+            // Rows of the column's tiles that already conflict in their row
+            let mask = row_masks[..self.n as usize].iter().enumerate()
+                .filter(|(_, &row_mask)| row_mask >> col & 1 == 1)
+                .fold(0u8, |mask, (row, _)| mask | 1 << row);
+            total += self._line(board, col, true, mask).0;
+        }
+        total
+    }
+
+    /// Count the conflicts in a row or column, going through the cache.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    /// * `line` - Index of the row or column
+    /// * `is_column` - Whether the line is a column
+    /// * `conflicting` - Positions in the line of tiles that already conflict
+    ///
+    /// # Returns
+    /// Conflicts in the line and the positions of the tiles conflicting in it
+    fn _line(&mut self, board: &Board, line: i32, is_column: bool, conflicting: u8) -> (i32, u8) {
+        let mut contents = 0u32;
+        for position in 0..self.n {
+            let tile = board.tiles()[self._index(line, position, is_column)];
+            contents |= (pack::encode_tiles(&[tile]) as u32) << (4 * position);
+        }
+        let key = VALID_KEY | (conflicting as u32) << 19 | (is_column as u32) << 18 | (line as u32) << 16 | contents;
+        let slot = (key.wrapping_mul(0x9E37_79B1) >> 20) as usize & (CONFLICT_CACHE_SIZE - 1);
+        let (cached_key, value) = self.entries[slot];
+        if cached_key == key {
+            self.hits += 1;
+            return ((value & 0xFF) as i32, (value >> 8) as u8);
+        }
+        self.misses += 1;
+        let (conflicts, mask) = self._count_line(board, line, is_column, conflicting);
+        self.entries[slot] = (key, conflicts as u16 | (mask as u16) << 8);
+        (conflicts, mask)
+    }
+
+    /// Count the conflicts in a row or column.
+    /// * Pairs are checked in order and each tile counts towards at most one conflict,
+    ///   as in `Board::heuristic_breakdown`.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    /// * `line` - Index of the row or column
+    /// * `is_column` - Whether the line is a column
+    /// * `conflicting` - Positions in the line of tiles that already conflict
+    ///
+    /// # Returns
+    /// Conflicts in the line and the positions of the tiles conflicting in it
+    fn _count_line(&self, board: &Board, line: i32, is_column: bool, mut conflicting: u8) -> (i32, u8) {
+        // Goal position along the line of each tile whose goal is in this line
+        let goals: Vec<Option<i32>> = (0..self.n)
+            .map(|position| {
+                let tile = board.tiles()[self._index(line, position, is_column)];
+                if tile.is_blank() {
+                    return None;
+                }
+                let (goal_line, goal_position) = if is_column {
+                    (self.goal_cols[tile.symbol() as usize], self.goal_rows[tile.symbol() as usize])
+                } else {
+                    (self.goal_rows[tile.symbol() as usize], self.goal_cols[tile.symbol() as usize])
+                };
+                if goal_line == line { Some(goal_position) } else { None }
+            })
+            .collect();
+        let mut found = 0u8;
+        let mut conflicts = 0;
+        for i in 0..self.n as usize {
+            for j in i + 1..self.n as usize {
+                if let (Some(goal_i), Some(goal_j)) = (goals[i], goals[j]) {
+                    if conflicting >> i & 1 == 1 || conflicting >> j & 1 == 1 {
+                        continue;
+                    }
+                    if goal_i > goal_j {
+                        conflicts += 2;
+                        conflicting |= 1 << i | 1 << j;
+                        found |= 1 << i | 1 << j;
+                    }
+                }
+            }
+        }
+        (conflicts, found)
+    }
+
+    /// Get the tile index of a position along a row or column.
+    ///
+    /// # Parameters
+    /// * `line` - Index of the row or column
+    /// * `position` - Position along the line
+    /// * `is_column` - Whether the line is a column
+    ///
+    /// # Returns
+    /// Index into the board's tiles
+    fn _index(&self, line: i32, position: i32, is_column: bool) -> usize {
+        if is_column { (position * self.n + line) as usize } else { (line * self.n + position) as usize }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::convert::TryFrom;
+
+    /// Test cached conflicts match the uncached count over random boards
+    #[test]
+    fn test_conflict_cache_matches_uncached() {
+        let mut rng = StdRng::seed_from_u64(363);
+        for n in 2..=5 {
+            let solved_board = Board::new(n, None, -1, None);
+            let mut cache = ConflictCache::new(&solved_board);
+            for _ in 0..200 {
+                let mut board = Board::new(n, None, 0, None);
+                board.shuffle_with_rng(40, &mut rng);
+                assert_eq!(
+                    cache.linear_conflicts(&board),
+                    board.heuristic_breakdown(Some(&solved_board)).linear_conflicts,
+                    "{}", board
+                );
+            }
+            if n <= 4 {
+                assert!(cache.hits() > 0);
+            } else {
+                assert_eq!(cache.hits() + cache.misses(), 0);
+            }
+        }
+    }
+
+    /// Test a tile conflicting in both its row and column only counts once
+    #[test]
+    fn test_conflict_cache_row_then_column() {
+        // Tile 5 is home and conflicts with 4 in row 1, so its column conflict with 2
+        // isn't counted
+        let board = Board::try_from(vec![1, 6, 3, 8, 5, 4, 7, 2, -1]).unwrap();
+        let solved_board = Board::new(3, None, -1, None);
+        let mut cache = ConflictCache::new(&solved_board);
+        let expected = board.heuristic_breakdown(None).linear_conflicts;
+        assert_eq!(expected, 2);
+        assert_eq!(cache.linear_conflicts(&board), expected);
+        // Again from the cache
+        assert_eq!(cache.linear_conflicts(&board), expected);
+        assert_eq!(cache.hits(), 6);
+    }
+}
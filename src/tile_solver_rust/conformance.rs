@@ -0,0 +1,252 @@
+//! Self-test of a built library, for packagers to check a binary artifact after building.
+//!
+//! `conformance_check` runs a fixed set of embedded cases covering parsing, an optimal
+//! solve, verification and replay, unsolvable boards and seeded generation. Cases only
+//! use randomness through fixed seeds, so every build on every platform must pass all
+//! of them; a failure means the artifact is broken, not that a case was unlucky.
+
+use std::error::Error;
+use std::fmt;
+
+use super::board::{Board, Notation};
+use super::clock;
+use super::error_code::{self, ErrorCode};
+use super::replay::Replay;
+use super::solver::{self, SolveError, SolveOptions};
+use super::{format_board_info, try_parse_board_info};
+
+/// Board with a known optimal solution length, the hardest 3x3 board
+const OPTIMAL_BOARD: &str = "3,8,6,7,2,5,4,3,-1,1";
+
+/// Optimal solution length of `OPTIMAL_BOARD`
+const OPTIMAL_LENGTH: usize = 31;
+
+/// Board and a solution of it, as moves of the blank
+const REPLAY_BOARD: (&str, &str) = ("3,1,2,3,-1,4,6,7,5,8", "RDR");
+
+/// Board info that must be rejected, with the code of the error
+const INVALID_BOARDS: [(&str, u32); 4] = [
+    ("3,1,2", 102),
+    ("3,1,2,3,4,5,6,7,8,9", 103),
+    ("3,1,1,3,-1,4,6,7,5,8", 104),
+    ("x,1", 105),
+];
+
+/// Board generated from `GENERATED_SEED`, in board info format
+const GENERATED_BOARD: &str = "4,9,1,4,3,5,2,8,12,-1,11,6,15,10,13,7,14";
+
+/// Seed, size and shuffles of the generated board
+const GENERATED_SEED: (u64, i32, i32) = (420, 4, 60);
+
+/// Check of a conformance case, returning what went wrong if it fails
+type Check = fn() -> Result<(), String>;
+
+/// Name and check of each conformance case, in the order they run
+const CASES: [(&str, Check); 5] = [
+    ("parse_errors", _check_parse_errors),
+    ("optimal_solve", _check_optimal_solve),
+    ("verify_replay", _check_verify_replay),
+    ("unsolvable", _check_unsolvable),
+    ("seeded_generation", _check_seeded_generation),
+];
+
+/// Outcome of one conformance case.
+///
+/// # Attributes
+/// * `name` - Name of the case
+/// * `failure` - What went wrong, if the case failed
+/// * `millis` - Wall time the case took in milliseconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConformanceCase {
+    pub name: &'static str,
+    pub failure: Option<String>,
+    pub millis: f64,
+}
+
+impl ConformanceCase {
+    /// Whether the case passed.
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Outcomes of every conformance case, in the order they ran.
+///
+/// # Attributes
+/// * `cases` - Outcome of each case
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConformanceReport {
+    pub cases: Vec<ConformanceCase>,
+}
+
+impl ConformanceReport {
+    /// Whether every case passed.
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(ConformanceCase::passed)
+    }
+
+    /// Get the cases that failed.
+    ///
+    /// # Returns
+    /// Failed cases, in the order they ran
+    pub fn failures(&self) -> Vec<&ConformanceCase> {
+        self.cases.iter().filter(|case| !case.passed()).collect()
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    /// Write a line per case with its outcome and time.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for case in &self.cases {
+            match &case.failure {
+                None => writeln!(f, "pass  {:<20} {:>8.1} ms", case.name, case.millis)?,
+                Some(failure) => writeln!(f, "FAIL  {:<20} {:>8.1} ms  {}", case.name, case.millis, failure)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A conformance check with at least one failed case.
+/// * Code 405, see `error_code::ERR_CONFORMANCE`.
+///
+/// # Attributes
+/// * `report` - Outcomes of every case, passed ones included
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConformanceFailure {
+    pub report: ConformanceReport,
+}
+
+impl fmt::Display for ConformanceFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let failures: Vec<String> = self.report.failures().iter()
+            .map(|case| format!("{} ({})", case.name, case.failure.as_deref().unwrap_or_default()))
+            .collect();
+        write!(f, "{} of {} conformance cases failed: {}", failures.len(), self.report.cases.len(), failures.join("; "))
+    }
+}
+
+impl Error for ConformanceFailure {}
+
+impl ErrorCode for ConformanceFailure {
+    fn code(&self) -> u32 {
+        error_code::ERR_CONFORMANCE
+    }
+}
+
+/// Run every conformance case.
+/// * A failing case doesn't stop the run, so the report covers every case.
+///
+/// # Returns
+/// The report if every case passed, otherwise the failure with the full report
+pub fn conformance_check() -> Result<ConformanceReport, ConformanceFailure> {
+    let report = ConformanceReport {
+        cases: CASES.iter()
+            .map(|&(name, check)| {
+                let started_ms = clock::now_ms();
+                let failure = check().err();
+                ConformanceCase { name, failure, millis: clock::now_ms() - started_ms }
+            })
+            .collect(),
+    };
+    if report.passed() {
+        Ok(report)
+    } else {
+        Err(ConformanceFailure { report })
+    }
+}
+
+/// Check invalid board info is rejected with the published codes, and valid board info
+/// parses back to itself.
+fn _check_parse_errors() -> Result<(), String> {
+    for &(board_info, code) in INVALID_BOARDS.iter() {
+        match try_parse_board_info(board_info) {
+            Ok(_) => return Err(format!("{:?} was accepted", board_info)),
+            Err(e) if e.code() != code => {
+                return Err(format!("{:?} failed with code {}, expected {}", board_info, e.code(), code));
+            }
+            Err(_) => {}
+        }
+    }
+    let board = try_parse_board_info(OPTIMAL_BOARD).map_err(|e| e.to_string())?;
+    if format_board_info(&board) != OPTIMAL_BOARD {
+        return Err(format!("{:?} formatted as {:?}", OPTIMAL_BOARD, format_board_info(&board)));
+    }
+    Ok(())
+}
+
+/// Check a 3x3 board is solved optimally.
+fn _check_optimal_solve() -> Result<(), String> {
+    let board = try_parse_board_info(OPTIMAL_BOARD).map_err(|e| e.to_string())?;
+    let solution = solver::solve(&board, &SolveOptions::for_size(3)).map_err(|e| e.to_string())?;
+    if solution.moves.len() != OPTIMAL_LENGTH || !solution.optimal {
+        return Err(format!(
+            "solved in {} moves (optimal {}), expected {} optimal moves",
+            solution.moves.len(), solution.optimal, OPTIMAL_LENGTH
+        ));
+    }
+    if !solver::verify_solution(&board, &solution.moves, Notation::BlankMoves) {
+        return Err(format!("solution {} doesn't solve the board", solution.moves));
+    }
+    Ok(())
+}
+
+/// Check a solution replays to the solved board in both notations, and a shortened one
+/// is rejected.
+fn _check_verify_replay() -> Result<(), String> {
+    let (board_info, moves) = REPLAY_BOARD;
+    let board = try_parse_board_info(board_info).map_err(|e| e.to_string())?;
+    let frames: Vec<_> = Replay::new(&board, moves).collect();
+    if frames.len() != moves.len() + 1 || !frames.last().is_some_and(|frame| frame.board.is_solved()) {
+        return Err(format!("replay of {} took {} frames and didn't end solved", moves, frames.len()));
+    }
+    let tile_moves = Notation::TileMoves.format_moves(moves);
+    if !solver::verify_solution(&board, &tile_moves, Notation::TileMoves) {
+        return Err(format!("tile moves {} don't solve the board", tile_moves));
+    }
+    if solver::verify_solution(&board, &moves[1..], Notation::BlankMoves) {
+        return Err(format!("shortened solution {} was accepted", &moves[1..]));
+    }
+    Ok(())
+}
+
+/// Check unsolvable boards are detected, both by the parity check and by the solver.
+fn _check_unsolvable() -> Result<(), String> {
+    let swapped = try_parse_board_info("3,2,1,3,4,5,6,7,8,-1").map_err(|e| e.to_string())?;
+    if swapped.is_solvable() {
+        return Err("a board with two tiles swapped is reported solvable".to_string());
+    }
+    let small = try_parse_board_info("2,2,1,3,-1").map_err(|e| e.to_string())?;
+    match solver::solve(&small, &SolveOptions::for_size(2)) {
+        Err(SolveError::Unreachable) => Ok(()),
+        Ok(solution) => Err(format!("unsolvable 2x2 board solved with {}", solution.moves)),
+        Err(e) => Err(format!("unsolvable 2x2 board failed with {}", e)),
+    }
+}
+
+/// Check seeded generation gives the same board on every platform.
+fn _check_seeded_generation() -> Result<(), String> {
+    let (seed, n, shuffles) = GENERATED_SEED;
+    let board = Board::generate(n, seed, shuffles).map_err(|e| e.to_string())?;
+    let generated = format_board_info(&board);
+    if generated != GENERATED_BOARD {
+        return Err(format!("seed {} generated {:?}, expected {:?}", seed, generated, GENERATED_BOARD));
+    }
+    if !board.is_solvable() {
+        return Err(format!("generated board {:?} is unsolvable", generated));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test every conformance case passes
+    #[test]
+    fn test_conformance_check() {
+        let report = conformance_check().unwrap_or_else(|failure| panic!("{}", failure));
+        assert_eq!(report.cases.len(), CASES.len());
+        assert!(report.to_string().lines().all(|line| line.starts_with("pass")));
+    }
+}
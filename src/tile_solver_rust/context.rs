@@ -0,0 +1,221 @@
+//! Knowledge shared across solves of related positions, e.g. every position along one
+//! game in an analysis session.
+//!
+//! The depths A* stores are only valid from the root they were found from, so instead
+//! of keeping its closed set, a `SearchContext` keeps what every optimal solution
+//! proves: each board along it is exactly as far from the goal as the rest of the
+//! solution. A position along a solved one is answered without searching, and later A*
+//! searches cost known boards by their exact distance and finish as soon as they take
+//! one off the frontier. A position moved off a solution still has to rule out shorter
+//! solutions than the way back onto it, so it only saves the rest of the search.
+
+use std::collections::HashMap;
+
+use super::board::{Board, Notation};
+use super::pack;
+use super::solver::{self, Algorithm, Solution, SolveError, SolveOptions, SolveStats, Solver};
+
+/// Exact distances to the goal of packed boards of one size, with the first move of an
+/// optimal solution (unused for the goal)
+pub(crate) type KnownDistances = HashMap<u64, (u32, char)>;
+
+/// Exact distances to the goal learned from earlier solves, reusable by later ones.
+/// * Only boards that can be packed (n <= 4) are remembered, towards the standard goal.
+///
+/// # Attributes
+/// * `known` - Known distances of each board size
+/// * `hits` - Number of solves answered or finished from known distances
+#[derive(Debug, Clone, Default)]
+pub struct SearchContext {
+    known: HashMap<i32, KnownDistances>,
+    hits: u64,
+}
+
+impl SearchContext {
+    /// Create an empty context.
+    pub fn new() -> SearchContext {
+        SearchContext::default()
+    }
+
+    /// Number of boards with a known distance.
+    ///
+    /// # Returns
+    /// Known board count, across sizes
+    pub fn len(&self) -> usize {
+        self.known.values().map(HashMap::len).sum()
+    }
+
+    /// Check if no distances are known.
+    ///
+    /// # Returns
+    /// Whether the context is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of solves that used a known distance.
+    ///
+    /// # Returns
+    /// Hit count
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Forget every known distance.
+    pub fn clear(&mut self) {
+        self.known.clear();
+    }
+
+    /// Look up the exact distance of a board from the goal.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    ///
+    /// # Returns
+    /// Optimal solution length, if known
+    pub fn distance(&self, board: &Board) -> Option<u32> {
+        if board.size() > pack::MAX_PACKED_SIZE {
+            return None;
+        }
+        let known = self.known.get(&board.size())?;
+        known.get(&pack::encode_tiles(board.tiles())).map(|&(distance, _)| distance)
+    }
+
+    /// Follow known first moves from a board to the goal.
+    ///
+    /// # Parameters
+    /// * `board` - Board with a known distance
+    ///
+    /// # Returns
+    /// Optimal solution moves, if the board's distance is known
+    fn known_moves(&self, board: &Board) -> Option<String> {
+        let known = self.known.get(&board.size())?;
+        let mut board = board.clone();
+        let mut moves = String::new();
+        loop {
+            let &(distance, next) = known.get(&pack::encode_tiles(board.tiles()))?;
+            if distance == 0 {
+                return Some(moves);
+            }
+            board.move_blank_tile(next);
+            moves.push(next);
+        }
+    }
+
+    /// Remember every board along an optimal solution.
+    ///
+    /// # Parameters
+    /// * `board` - The solved board
+    /// * `moves` - Optimal solution moves
+    pub fn record(&mut self, board: &Board, moves: &str) {
+        if board.size() > pack::MAX_PACKED_SIZE {
+            return;
+        }
+        match board.apply_moves(moves, Notation::BlankMoves) {
+            Ok(goal) if goal.is_solved() => {}
+            _ => return,
+        }
+        let known = self.known.entry(board.size()).or_default();
+        let moves: Vec<char> = moves.chars().collect();
+        let mut replay = board.clone();
+        for (made, next) in moves.iter().copied().map(Some).chain(std::iter::once(None)).enumerate() {
+            let distance = (moves.len() - made) as u32;
+            known.insert(pack::encode_tiles(replay.tiles()), (distance, next.unwrap_or('\0')));
+            if let Some(next) = next {
+                replay.move_blank_tile(next);
+            }
+        }
+    }
+}
+
+/// Solve a board using and adding to the distances known by a context.
+/// * A board with a known distance is answered without searching, with empty stats.
+/// * A* searches cost boards with a known distance by it and finish when one is taken
+///   off the frontier, so solutions stay optimal. IDA* and layered solving only use a
+///   known distance of the board itself.
+/// * Optimal solutions are recorded in the context.
+///
+/// # Parameters
+/// * `context` - Context shared across solves
+/// * `board` - The board to solve
+/// * `options` - Options controlling the solve
+///
+/// # Returns
+/// The solution moves and search statistics, or why the board can't be solved
+pub fn solve_in_context(context: &mut SearchContext, board: &Board,
+                        options: &SolveOptions) -> Result<Solution, SolveError> {
+    options.validate()?;
+    if let Some(moves) = context.known_moves(board) {
+        context.hits += 1;
+        let moves = if options.canonical {
+            solver::canonical_moves(board, moves.len(), &options.move_order)
+        } else {
+            moves
+        };
+        return Ok(Solution { moves, stats: SolveStats::default(), optimal: true });
+    }
+    let solution = if options.algorithm == Algorithm::AStar && board.size() <= pack::MAX_PACKED_SIZE {
+        let known = context.known.remove(&board.size()).unwrap_or_default();
+        let used_known = !known.is_empty();
+        let mut solver = Solver::with_options(board.size(), board.tiles().to_vec(), options).known_distances(known);
+        let solution = loop {
+            match solver.step(u64::MAX) {
+                Ok(Some(solution)) => break Ok(solution),
+                Ok(None) => continue,
+                Err(e) => break Err(e),
+            }
+        };
+        context.known.insert(board.size(), solver.take_known_distances());
+        if used_known && solution.is_ok() {
+            context.hits += 1;
+        }
+        let mut solution = solution?;
+        if options.canonical {
+            solution.moves = solver::canonical_moves(board, solution.moves.len(), &options.move_order);
+        }
+        solution
+    } else {
+        solver::solve(board, options)?
+    };
+    if solution.optimal {
+        context.record(board, &solution.moves);
+    }
+    Ok(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test positions next to a solved one are solved from the context with no or fewer
+    /// expansions, and solutions stay optimal
+    #[test]
+    fn test_solve_in_context() {
+        let board = Board::generate(3, 413, 200).unwrap();
+        let options = SolveOptions::new();
+        let mut context = SearchContext::new();
+        let first = solve_in_context(&mut context, &board, &options).unwrap();
+        assert_eq!(first, solver::solve(&board, &options).unwrap());
+        assert_eq!(context.len(), first.moves.len() + 1);
+        assert_eq!(context.distance(&board), Some(first.moves.len() as u32));
+        // Along the solution, the context answers without searching
+        let along = board.apply_moves(&first.moves[..1], Notation::BlankMoves).unwrap();
+        let solution = solve_in_context(&mut context, &along, &options).unwrap();
+        assert_eq!(solution.moves, first.moves[1..]);
+        assert_eq!(solution.stats.nodes_expanded, 0);
+        // Off the solution, the search finishes once it reaches a known board
+        for (tile_move, successor) in board.neighbors() {
+            if first.moves.starts_with(tile_move) {
+                continue;
+            }
+            let fresh = solver::solve(&successor, &options).unwrap();
+            let solution = solve_in_context(&mut context, &successor, &options).unwrap();
+            assert_eq!(solution.moves.len(), fresh.moves.len(), "{}", tile_move);
+            assert!(solver::verify_solution(&successor, &solution.moves, Notation::BlankMoves));
+            assert!(solution.stats.nodes_expanded < fresh.stats.nodes_expanded, "{:?}", solution.stats);
+        }
+        assert_eq!(context.hits(), 1 + board.neighbors().count() as u64 - 1);
+        context.clear();
+        assert!(context.is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+//! Sanity checks for heuristics during development.
+//!
+//! Heuristics are functions of a board and an explicit goal. An admissible heuristic
+//! never overestimates the optimal solution length, and a consistent one changes by
+//! at most 1 across any move.
+
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::board::{Board, Heuristic};
+use super::exact::DistanceTable;
+use super::solver::{SolveError, SolveOptions, Solver};
+
+/// A heuristic that failed a check.
+/// * Boards are boxed to keep results small.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeuristicViolation {
+    /// Estimate is larger than the optimal solution length
+    Inadmissible { board: Box<Board>, estimate: i32, distance: usize },
+    /// Estimate changes by more than 1 across a move
+    Inconsistent { board: Box<Board>, neighbour: Box<Board>, estimate: i32, neighbour_estimate: i32 },
+}
+
+impl fmt::Display for HeuristicViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeuristicViolation::Inadmissible { board, estimate, distance } => {
+                writeln!(f, "inadmissible: estimate {} exceeds optimal length {}", estimate, distance)?;
+                write!(f, "{}", board)
+            }
+            HeuristicViolation::Inconsistent { board, neighbour, estimate, neighbour_estimate } => {
+                write!(f, "inconsistent: estimate changes from {} to {}", estimate, neighbour_estimate)?;
+                if let Some((tile, direction)) = board.moved_tile(neighbour) {
+                    write!(f, " when tile {} slides {}", tile, direction)?;
+                }
+                writeln!(f)?;
+                writeln!(f, "{}", board)?;
+                write!(f, "{}", neighbour)
+            }
+        }
+    }
+}
+
+/// Get the estimate of one of the built-in heuristics.
+///
+/// # Parameters
+/// * `heuristic` - Heuristic to evaluate
+///
+/// # Returns
+/// Function of a board and its goal giving the estimated distance between them
+pub fn heuristic_fn(heuristic: Heuristic) -> impl Fn(&Board, &Board) -> i32 {
+    move |board: &Board, goal: &Board| board.clone().store_h(goal, heuristic)
+}
+
+/// Check a heuristic never overestimates the exact distance of sample boards.
+///
+/// # Parameters
+/// * `h` - Heuristic, given a board and its goal
+/// * `sample_boards` - Boards to check, of the oracle's size
+/// * `exact_oracle` - Exact distances from the goal
+///
+/// # Returns
+/// The first board whose estimate is too large, if any
+pub fn check_admissible<H: Fn(&Board, &Board) -> i32>(
+    h: H, sample_boards: &[Board], exact_oracle: &DistanceTable,
+) -> Result<(), HeuristicViolation> {
+    let goal = Board::new(exact_oracle.size(), None, 0, None);
+    for board in sample_boards {
+        let distance = match exact_oracle.distance(board) {
+            Some(distance) => distance,
+            None => continue,
+        };
+        let estimate = h(board, &goal);
+        if estimate > distance as i32 {
+            return Err(HeuristicViolation::Inadmissible { board: Box::new(board.clone()), estimate, distance });
+        }
+    }
+    Ok(())
+}
+
+/// Check a heuristic changes by at most 1 across every legal move from a board.
+///
+/// # Parameters
+/// * `h` - Heuristic, given a board and its goal
+/// * `board` - Board to check the neighbours of
+///
+/// # Returns
+/// The first move across which the estimate changes too much, if any
+pub fn check_consistent<H: Fn(&Board, &Board) -> i32>(h: H, board: &Board) -> Result<(), HeuristicViolation> {
+    let goal = Board::new(board.size(), None, 0, None);
+    let estimate = h(board, &goal);
+    for tile_move in board.legal_moves() {
+        let mut neighbour = board.clone();
+        neighbour.move_blank_tile(tile_move);
+        let neighbour_estimate = h(&neighbour, &goal);
+        if (estimate - neighbour_estimate).abs() > 1 {
+            return Err(HeuristicViolation::Inconsistent {
+                board: Box::new(board.clone()),
+                neighbour: Box::new(neighbour),
+                estimate,
+                neighbour_estimate,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check a heuristic while it guides an A* search, as `SolveOptions::paranoid` does.
+/// * Every move is checked for consistency, and the goal for a heuristic of 0.
+/// * Only the boards the search reaches are checked, but they are the ones whose
+///   estimates decide the solution.
+///
+/// # Parameters
+/// * `h` - Heuristic, given a board and its goal
+/// * `board` - Board to solve
+/// * `max_nodes` - Stop checking after expanding this many boards
+///
+/// # Returns
+/// The first violation found, if any
+pub fn check_search(h: fn(&Board, &Board) -> i32, board: &Board, max_nodes: u64) -> Result<(), HeuristicViolation> {
+    let options = SolveOptions::new().paranoid(true).max_nodes(max_nodes);
+    let mut solver = Solver::with_options(board.size(), board.tiles().to_vec(), &options).estimate(h);
+    match solver.step(max_nodes) {
+        Err(SolveError::HeuristicInconsistent(violation)) => Err(*violation),
+        _ => Ok(()),
+    }
+}
+
+/// Check a heuristic on random 3x3 boards against exact distances.
+///
+/// # Parameters
+/// * `h` - Heuristic, given a board and its goal
+/// * `samples` - Number of random boards to check
+/// * `seed` - Seed of the random number generator
+/// * `exact_oracle` - Exact 3x3 distances from the goal
+///
+/// # Returns
+/// The first violation found, if any
+pub fn check_heuristic<H: Fn(&Board, &Board) -> i32>(
+    h: H, samples: usize, seed: u64, exact_oracle: &DistanceTable,
+) -> Result<(), HeuristicViolation> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let boards: Vec<Board> = (0..samples)
+        .map(|_| {
+            let mut board = Board::new(exact_oracle.size(), None, 0, None);
+            board.shuffle_with_rng(100, &mut rng);
+            board
+        })
+        .collect();
+    check_admissible(&h, &boards, exact_oracle)?;
+    boards.iter().try_for_each(|board| check_consistent(&h, board))
+}
+
+/// Check one of the built-in heuristics by name on random 3x3 boards.
+///
+/// # Parameters
+/// * `name` - Name of the heuristic, e.g. "manhattan", or several joined with `+`, see
+///   `Heuristic::parse`
+/// * `samples` - Number of random boards to check
+/// * `seed` - Seed of the random number generator
+///
+/// # Returns
+/// A report of the first violation, or an error if the name is unknown
+pub fn check_heuristic_named(name: &str, samples: usize, seed: u64) -> Result<Option<String>, String> {
+    let heuristic = match Heuristic::parse(name) {
+        Some(heuristic) => heuristic,
+        None => return Err(format!("unknown heuristic {:?}", name)),
+    };
+    let oracle = DistanceTable::new(3);
+    Ok(check_heuristic(heuristic_fn(heuristic), samples, seed, &oracle).err().map(|violation| violation.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::HEURISTICS;
+
+    /// Test the built-in heuristics pass and a broken one is flagged
+    #[test]
+    fn test_check_heuristic() {
+        let oracle = DistanceTable::new(3);
+        for &heuristic in &HEURISTICS {
+            assert!(check_heuristic(heuristic_fn(heuristic), 200, 359, &oracle).is_ok());
+        }
+        let broken = |board: &Board, goal: &Board| heuristic_fn(Heuristic::Manhattan)(board, goal) + 5;
+        match check_heuristic(broken, 200, 359, &oracle) {
+            Err(violation @ HeuristicViolation::Inadmissible { .. }) =>
+                assert!(violation.to_string().starts_with("inadmissible: estimate")),
+            other => panic!("expected an inadmissible violation, got {:?}", other),
+        }
+        assert_eq!(check_heuristic_named("manhattan", 50, 359), Ok(None));
+        assert_eq!(check_heuristic_named("linear_conflicts+inversion_distance", 200, 359), Ok(None));
+        assert!(check_heuristic_named("nope", 50, 359).is_err());
+    }
+
+    /// Test a heuristic that jumps across a move is flagged as inconsistent
+    #[test]
+    fn test_check_consistent() {
+        // Estimate 10 unless the blank is in the bottom right corner
+        let jumpy = |board: &Board, _: &Board| if board.get_blank_index() == 8 { 0 } else { 10 };
+        let board = Board::new(3, None, 0, None);
+        let violation = check_consistent(jumpy, &board).unwrap_err();
+        let report = violation.to_string();
+        assert!(report.starts_with("inconsistent: estimate changes from 0 to 10 when tile 6 slides D\n"), "{}", report);
+        assert!(check_consistent(heuristic_fn(Heuristic::Manhattan), &board).is_ok());
+    }
+
+    /// Test a search guided by a heuristic that drops by more than 1 on some moves fails
+    /// the check, and a search guided by the Manhattan distance passes
+    #[test]
+    fn test_check_search() {
+        let board = Board::generate(3, 422, 40).unwrap();
+        let manhattan = |board: &Board, goal: &Board| heuristic_fn(Heuristic::Manhattan)(board, goal);
+        assert_eq!(check_search(manhattan, &board, 100_000), Ok(()));
+        // Admissible, but 0 whenever the blank is in the top left corner
+        let cornered = |board: &Board, goal: &Board| if board.get_blank_index() == 0 { 0 } else { board.h(goal) };
+        match check_search(cornered, &board, 100_000) {
+            Err(HeuristicViolation::Inconsistent { neighbour, neighbour_estimate: 0, .. }) =>
+                assert_eq!(neighbour.get_blank_index(), 0),
+            other => panic!("expected an inconsistent violation, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Named difficulty tiers for generated boards, e.g. for "Easy / Medium / Hard / Expert"
+//! buttons.
+//!
+//! Every tier of a calibrated board size has a range of difficulty, measured by the
+//! exact optimal solution length where solving every candidate is cheap (3x3) and by
+//! the heuristic lower bound elsewhere. Boards are generated by random walks from the
+//! solved board and kept once they fall in the range, so the ranges and walk lengths of
+//! `CALIBRATIONS` are all there is to tune.
+
+use std::fmt;
+
+use rand::RngCore;
+
+use super::board::{Board, BoardError, DEFAULT_MIN_SHUFFLE_BOUND};
+use super::rng::RngSource;
+use super::solver::{self, SolveOptions};
+
+/// Difficulty tier of a generated board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+/// Every difficulty, from easiest to hardest
+pub const DIFFICULTIES: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert];
+
+impl Difficulty {
+    /// Read a difficulty by name.
+    ///
+    /// # Parameters
+    /// * `name` - Name of the difficulty, see `Display`
+    ///
+    /// # Returns
+    /// The difficulty, or `None` if the name is unknown
+    pub fn parse(name: &str) -> Option<Difficulty> {
+        DIFFICULTIES.iter().find(|difficulty| difficulty.to_string() == name.trim()).copied()
+    }
+
+    /// Position of the difficulty in `DIFFICULTIES`.
+    ///
+    /// # Returns
+    /// Index from 0 (easy) to 3 (expert)
+    fn index(self) -> usize {
+        DIFFICULTIES.iter().position(|&difficulty| difficulty == self).unwrap()
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+            Difficulty::Expert => write!(f, "expert"),
+        }
+    }
+}
+
+/// How the difficulty of a candidate board is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    /// Length of an optimal solution, found by solving the board
+    OptimalLength,
+    /// Heuristic lower bound on the solution length
+    LowerBound,
+}
+
+/// Range of difficulty of one tier.
+///
+/// # Attributes
+/// * `min` - Smallest accepted difficulty
+/// * `max` - Largest accepted difficulty
+/// * `shuffles` - Length of the random walk generating each candidate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TierRange {
+    pub min: u32,
+    pub max: u32,
+    pub shuffles: i32,
+}
+
+impl TierRange {
+    /// Check if a difficulty falls in the range.
+    ///
+    /// # Parameters
+    /// * `difficulty` - Measured difficulty of a board
+    ///
+    /// # Returns
+    /// Whether the range includes it
+    pub fn contains(&self, difficulty: u32) -> bool {
+        self.min <= difficulty && difficulty <= self.max
+    }
+}
+
+/// Calibration of the tiers of one board size.
+///
+/// # Attributes
+/// * `n` - Size of the board
+/// * `measure` - How the ranges are measured
+/// * `tiers` - Range of each difficulty, in `DIFFICULTIES` order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Calibration {
+    pub n: i32,
+    pub measure: Measure,
+    pub tiers: [TierRange; 4],
+}
+
+/// Calibrated board sizes.
+/// * 3x3 ranges are optimal move counts, up to the puzzle's diameter of 31.
+/// * 4x4 ranges are linear conflict lower bounds, since exactly solving every candidate
+///   would be too slow; a random 4x4 board has a bound of about 40.
+pub const CALIBRATIONS: [Calibration; 2] = [
+    Calibration {
+        n: 3,
+        measure: Measure::OptimalLength,
+        tiers: [
+            TierRange { min: 5, max: 10, shuffles: 10 },
+            TierRange { min: 11, max: 18, shuffles: 24 },
+            TierRange { min: 19, max: 25, shuffles: 60 },
+            TierRange { min: 26, max: 31, shuffles: 200 },
+        ],
+    },
+    Calibration {
+        n: 4,
+        measure: Measure::LowerBound,
+        tiers: [
+            TierRange { min: 8, max: 20, shuffles: 16 },
+            TierRange { min: 21, max: 32, shuffles: 50 },
+            TierRange { min: 33, max: 42, shuffles: 150 },
+            TierRange { min: 43, max: 80, shuffles: 1000 },
+        ],
+    },
+];
+
+/// Look up the calibration of a board size.
+///
+/// # Parameters
+/// * `n` - Size of the board
+///
+/// # Returns
+/// The calibration, or `None` if the size isn't calibrated
+pub fn calibration(n: i32) -> Option<&'static Calibration> {
+    CALIBRATIONS.iter().find(|calibration| calibration.n == n)
+}
+
+/// Generate a reproducible board of a difficulty tier.
+/// * Candidates are generated with seeds drawn from `seed` until one falls in the
+///   tier's range; each range is reachable by its walk length, so only a few candidates
+///   are needed on average.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `tier` - Difficulty of the board
+/// * `seed` - Seed of the random number generator
+///
+/// # Returns
+/// The board, costed against the solved board, or `BoardError::Uncalibrated` if the
+/// size has no calibration
+pub fn generate_tier(n: i32, tier: Difficulty, seed: u64) -> Result<Board, BoardError> {
+    let calibration = calibration(n).ok_or(BoardError::Uncalibrated(n))?;
+    let range = calibration.tiers[tier.index()];
+    let mut seeds = RngSource::seeded(seed);
+    loop {
+        let candidate_seed = seeds.next_u64();
+        let candidate = match calibration.measure {
+            Measure::OptimalLength => {
+                let board = Board::generate(n, candidate_seed, range.shuffles)?;
+                solver::solve_length(&board, &SolveOptions::for_size(n)).ok().map(|length| (board, length))
+            }
+            Measure::LowerBound => {
+                // Keep walking until the bound reaches the tier
+                let min_bound = (range.min as i32).max(DEFAULT_MIN_SHUFFLE_BOUND);
+                let (board, bound) = Board::generate_with_bound(n, candidate_seed, range.shuffles, min_bound)?;
+                Some((board, bound as u32))
+            }
+        };
+        match candidate {
+            Some((board, difficulty)) if range.contains(difficulty) => return Ok(board),
+            _ => continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exact::DistanceTable;
+
+    /// Test 3x3 boards of every tier have optimal solutions in the tier's range
+    #[test]
+    fn test_generate_tier_3x3() {
+        let oracle = DistanceTable::new(3);
+        let calibration = calibration(3).unwrap();
+        for &tier in &DIFFICULTIES {
+            let range = calibration.tiers[tier.index()];
+            for seed in 0..25 {
+                let board = generate_tier(3, tier, 409 + seed).unwrap();
+                let length = oracle.distance(&board).unwrap() as u32;
+                assert!(range.contains(length), "{} board {} has length {}", tier, board, length);
+                assert_eq!(generate_tier(3, tier, 409 + seed).unwrap(), board);
+            }
+        }
+    }
+
+    /// Test 4x4 boards of every tier have lower bounds in the tier's range
+    #[test]
+    fn test_generate_tier_4x4() {
+        let calibration = calibration(4).unwrap();
+        for &tier in &DIFFICULTIES {
+            let range = calibration.tiers[tier.index()];
+            for seed in 0..10 {
+                let board = generate_tier(4, tier, 409 + seed).unwrap();
+                let bound = board.heuristic_breakdown(None).total as u32;
+                assert!(range.contains(bound), "{} board {} has bound {}", tier, board, bound);
+            }
+        }
+        assert_eq!(generate_tier(5, Difficulty::Easy, 409), Err(BoardError::Uncalibrated(5)));
+    }
+
+    /// Test the tiers of each size are ordered and don't overlap
+    #[test]
+    fn test_calibrations() {
+        for calibration in &CALIBRATIONS {
+            assert!(calibration.tiers.iter().all(|range| range.min <= range.max));
+            assert!(calibration.tiers.windows(2).all(|pair| pair[0].max < pair[1].min), "{:?}", calibration);
+        }
+        for &tier in &DIFFICULTIES {
+            assert_eq!(Difficulty::parse(&tier.to_string()), Some(tier));
+        }
+        assert_eq!(Difficulty::parse("impossible"), None);
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use super::board::{Board, Heuristic};
+use super::logging::*;
+use super::observer::SolverObserver;
+use super::solver::{self, Solution, SolveError, SolveOptions};
+
+/// Default maximum number of boards drawn in a search tree
+pub const DEFAULT_DOT_MAX_NODES: usize = 500;
+
+/// A board drawn in the search tree.
+///
+/// # Attributes
+/// * `path` - Moves from the root to the board, which identify it in the tree
+/// * `grid` - Board tiles, one row per line
+/// * `g` - Depth of the board
+/// * `h` - Heuristic value of the board
+/// * `expanded` - Position in the expansion order, if the board was expanded
+#[derive(Debug, Clone)]
+struct DotNode {
+    path: String,
+    grid: String,
+    g: i32,
+    h: i32,
+    expanded: Option<usize>,
+}
+
+/// Observer that records the explored search tree for drawing with Graphviz.
+///
+/// # Attributes
+/// * `root` - The board being solved
+/// * `max_nodes` - Maximum number of boards to record
+/// * `nodes` - Recorded boards
+/// * `index` - Path of a board -> index in `nodes`
+/// * `expansions` - Number of expansions seen
+/// * `truncated` - Whether boards were dropped because of `max_nodes`
+/// * `solution` - Solution moves, once found
+#[derive(Debug, Clone)]
+pub struct DotTracer {
+    root: Board,
+    max_nodes: usize,
+    nodes: Vec<DotNode>,
+    index: HashMap<String, usize>,
+    expansions: usize,
+    truncated: bool,
+    solution: Option<String>,
+}
+
+impl DotTracer {
+    /// Create a tracer for a board.
+    ///
+    /// # Parameters
+    /// * `root` - The board being solved
+    /// * `max_nodes` - Maximum number of boards to record
+    pub fn new(root: &Board, max_nodes: usize) -> DotTracer {
+        DotTracer {
+            root: root.clone(),
+            max_nodes,
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            expansions: 0,
+            truncated: false,
+            solution: None,
+        }
+    }
+
+    /// Check if boards were dropped because of the node limit.
+    ///
+    /// # Returns
+    /// Whether the tree was truncated
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Record a board, unless it is already recorded or the node limit has been reached.
+    ///
+    /// # Parameters
+    /// * `board` - The board to record
+    /// * `force` - Record the board even past the node limit
+    ///
+    /// # Returns
+    /// Index of the board in `nodes`, if it is recorded
+    fn record(&mut self, board: &Board, force: bool) -> Option<usize> {
+        if let Some(&i) = self.index.get(&board.path) {
+            return Some(i);
+        }
+        if self.nodes.len() >= self.max_nodes && !force {
+            self.truncated = true;
+            return None;
+        }
+        self.nodes.push(DotNode {
+            path: board.path.clone(),
+            grid: DotTracer::_grid(board),
+            g: board.depth,
+            h: board.stored_h(),
+            expanded: None,
+        });
+        self.index.insert(board.path.clone(), self.nodes.len() - 1);
+        Some(self.nodes.len() - 1)
+    }
+
+    /// Format the tiles of a board as a grid label.
+    ///
+    /// # Parameters
+    /// * `board` - The board to format
+    ///
+    /// # Returns
+    /// Rows of tile symbols separated by escaped newlines, with `_` for the blank
+    fn _grid(board: &Board) -> String {
+        let rows: Vec<String> = board.tiles()
+            .chunks(board.size() as usize)
+            .map(|row| {
+                let symbols: Vec<String> = row.iter()
+                    .map(|tile| {
+                        if tile.is_blank() { "_".to_string() } else { tile.symbol().to_string() }
+                    })
+                    .collect();
+                symbols.join(" ")
+            })
+            .collect();
+        rows.join("\\n")
+    }
+
+    /// Render the recorded search tree as a Graphviz digraph.
+    /// * Nodes are labeled with the board and f = g + h.
+    /// * Expanded nodes are filled darker the later they were expanded; unexpanded
+    ///   nodes are left white.
+    /// * The solution path is drawn in red, and is always included even when the tree
+    ///   is truncated.
+    ///
+    /// # Returns
+    /// DOT source
+    pub fn to_dot(&self) -> String {
+        let mut tracer = self.clone();
+        // Make sure every board along the solution is drawn
+        let mut on_path: Vec<String> = vec![String::new()];
+        if let Some(moves) = &self.solution {
+            let solved_board = Board::new(self.root.size(), None, -1, None);
+            let mut board = self.root.clone();
+            board.path.clear();
+            board.depth = 0;
+            board.cost = board.g() + board.store_h(&solved_board, Heuristic::LinearConflicts);
+            tracer.record(&board, true);
+            for tile_move in moves.chars() {
+                board.move_blank_tile(tile_move);
+                board.path.push(tile_move);
+                board.depth += 1;
+                board.cost = board.g() + board.store_h(&solved_board, Heuristic::LinearConflicts);
+                tracer.record(&board, true);
+                on_path.push(board.path.clone());
+            }
+        }
+        let mut dot = String::from("digraph search {\n");
+        if tracer.truncated {
+            dot.push_str(&format!(
+                "    // warning: truncated to {} boards, solution path kept\n",
+                self.max_nodes
+            ));
+        }
+        dot.push_str("    node [shape=box, style=filled, fontname=monospace];\n");
+        for (i, node) in tracer.nodes.iter().enumerate() {
+            let fill = match node.expanded {
+                Some(order) => format!(
+                    "0.6 {:.3} 1.0",
+                    0.1 + 0.8 * order as f64 / tracer.expansions.max(1) as f64
+                ),
+                None => "white".to_string(),
+            };
+            let highlight = if self.solution.is_some() && on_path.contains(&node.path) {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "    n{} [label=\"{}\\nf={} (g={}, h={})\", fillcolor=\"{}\"{}];\n",
+                i, node.grid, node.g + node.h, node.g, node.h, fill, highlight
+            ));
+        }
+        for (i, node) in tracer.nodes.iter().enumerate() {
+            let via = match node.path.chars().last() {
+                Some(via) => via,
+                None => continue,
+            };
+            let parent = match tracer.index.get(&node.path[..node.path.len() - 1]) {
+                Some(&parent) => parent,
+                None => continue,
+            };
+            let highlight = if self.solution.is_some() && on_path.contains(&node.path) {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+            dot.push_str(&format!("    n{} -> n{} [label=\"{}\"{}];\n", parent, i, via, highlight));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl SolverObserver for DotTracer {
+    fn on_expand(&mut self, board: &Board, _g: i32, _h: i32) {
+        if let Some(i) = self.record(board, false) {
+            self.nodes[i].expanded = Some(self.expansions);
+        }
+        self.expansions += 1;
+    }
+
+    fn on_generate(&mut self, board: &Board, _via: char) {
+        self.record(board, false);
+    }
+
+    fn on_goal(&mut self, solution: &Solution) {
+        self.solution = Some(solution.moves.clone());
+    }
+}
+
+/// Solve a board and draw the explored search tree as a Graphviz digraph.
+/// * Intended for small boards; trees with more than `max_nodes` boards are truncated
+///   with a warning.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `max_nodes` - Maximum number of boards to draw
+///
+/// # Returns
+/// DOT source, or why the board can't be solved
+pub fn trace_dot(board: &Board, max_nodes: usize) -> Result<String, SolveError> {
+    let mut tracer = DotTracer::new(board, max_nodes);
+    solver::solve_observed(board, &SolveOptions::default(), &mut tracer)?;
+    if tracer.is_truncated() {
+        log!(LOG_WARN, "search tree truncated to {} boards", max_nodes);
+    }
+    Ok(tracer.to_dot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a 3x3 board by moving the blank from the solved position.
+    fn scrambled(moves: &str) -> Board {
+        let mut board = Board::try_new(3, Board::new(3, None, 0, None).tiles().to_vec()).unwrap();
+        for tile_move in moves.chars() {
+            board.move_blank_tile(tile_move);
+        }
+        board
+    }
+
+    /// Check DOT source is structurally sound and return its node and edge lines.
+    fn dot_lines(dot: &str) -> (Vec<&str>, Vec<&str>) {
+        assert!(dot.starts_with("digraph search {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+        assert_eq!(dot.matches('"').count() % 2, 0);
+        let nodes = dot.lines()
+            .filter(|line| line.contains("[label=") && !line.contains("->"))
+            .collect();
+        let edges = dot.lines().filter(|line| line.contains("->")).collect();
+        (nodes, edges)
+    }
+
+    /// Test the search tree of a 5-move instance
+    #[test]
+    fn test_trace_dot() {
+        let board = scrambled("LULDR");
+        let solution = solver::solve(&board, &SolveOptions::default()).unwrap();
+        assert_eq!(solution.moves.len(), 5);
+        let dot = trace_dot(&board, DEFAULT_DOT_MAX_NODES).unwrap();
+        let (nodes, edges) = dot_lines(&dot);
+        // Every board but the root has exactly one incoming edge
+        assert_eq!(edges.len(), nodes.len() - 1);
+        assert!(!dot.contains("warning"));
+        // Root plus one highlighted node and edge per solution move
+        assert_eq!(nodes.iter().filter(|line| line.contains("color=red")).count(), 6);
+        assert_eq!(edges.iter().filter(|line| line.contains("color=red")).count(), 5);
+        assert!(nodes[0].contains("label=\"1 2 3\\n7 4 6\\n5 _ 8\\nf=5 (g=0, h=5)\""));
+        for tile_move in solution.moves.chars() {
+            let label = format!("[label=\"{}\", color=red", tile_move);
+            assert!(edges.iter().any(|line| line.contains(&label)));
+        }
+    }
+
+    /// Test large trees are truncated but keep the solution path
+    #[test]
+    fn test_trace_dot_truncated() {
+        let board = scrambled("LULDR");
+        let dot = trace_dot(&board, 3).unwrap();
+        let (nodes, edges) = dot_lines(&dot);
+        assert!(dot.contains("// warning: truncated to 3 boards"));
+        assert_eq!(nodes.iter().filter(|line| line.contains("color=red")).count(), 6);
+        assert_eq!(edges.iter().filter(|line| line.contains("color=red")).count(), 5);
+    }
+}
@@ -0,0 +1,142 @@
+//! Stable numeric error codes for callers in other languages.
+//!
+//! Every error reported through wasm, the worker protocol or the JSON server carries a
+//! code alongside its message, so callers can branch on the code instead of matching
+//! messages. A code never changes meaning once published; new errors get new codes.
+//!
+//! | Range | Errors |
+//! |-------|--------|
+//! | 1xx | Invalid boards, moves and goals: `BoardError`, `IllegalMove`, `PackError`, `RankError`, `Unreachable` |
+//! | 2xx | Failed solves: `SolveError` |
+//! | 3xx | Unusable saved data: `ResumeError`, `DecodeError`, `TableError` |
+//! | 4xx | Invalid arguments and requests, see the constants below |
+//!
+//! Each error type lists its codes in its documentation. Some errors also carry
+//! details as a JSON object, e.g. the partial result of a solve that gave up.
+
+#[cfg(alloc_only)]
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// An argument was invalid in a way no typed error describes, e.g. an unknown notation
+pub const ERR_INVALID_ARGUMENT: u32 = 400;
+
+/// A worker or server request was malformed or missing fields
+pub const ERR_INVALID_REQUEST: u32 = 401;
+
+/// The solve was cancelled
+pub const ERR_CANCELLED: u32 = 402;
+
+/// No solve is running with the request id
+pub const ERR_UNKNOWN_REQUEST: u32 = 403;
+
+/// A solve is already running with the request id
+pub const ERR_REQUEST_RUNNING: u32 = 404;
+
+/// A case of the self-test failed, see `conformance`
+pub const ERR_CONFORMANCE: u32 = 405;
+
+/// An error with a stable numeric code.
+pub trait ErrorCode: fmt::Display {
+    /// Get the code of the error.
+    ///
+    /// # Returns
+    /// Stable error code, see the module documentation
+    fn code(&self) -> u32;
+
+    /// Get structured details of the error.
+    ///
+    /// # Returns
+    /// JSON object with the details, if the error has any
+    fn details(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Free-form messages are for arguments checked without a typed error.
+impl ErrorCode for String {
+    fn code(&self) -> u32 {
+        ERR_INVALID_ARGUMENT
+    }
+}
+
+/// An error reduced to its code and message, for passing across a language boundary.
+///
+/// # Attributes
+/// * `code` - Stable error code
+/// * `message` - Description of the error
+/// * `details` - JSON object with structured details, if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodedError {
+    pub code: u32,
+    pub message: String,
+    pub details: Option<String>,
+}
+
+impl CodedError {
+    /// Create an error with a code.
+    ///
+    /// # Parameters
+    /// * `code` - Stable error code
+    /// * `message` - Description of the error
+    pub fn new<S: Into<String>>(code: u32, message: S) -> CodedError {
+        CodedError { code, message: message.into(), details: None }
+    }
+
+    /// Reduce a typed error to its code, message and details.
+    ///
+    /// # Parameters
+    /// * `error` - The error
+    pub fn from_error<E: ErrorCode>(error: &E) -> CodedError {
+        CodedError { details: error.details(), ..CodedError::new(error.code(), error.to_string()) }
+    }
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl ErrorCode for CodedError {
+    fn code(&self) -> u32 {
+        self.code
+    }
+
+    fn details(&self) -> Option<String> {
+        self.details.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Board, BoardError};
+    use crate::pack::PackError;
+    use crate::solver::{ResumeError, SolveError};
+
+    /// Test representative errors keep their published codes
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(BoardError::InvalidSize(0).code(), 101);
+        assert_eq!(BoardError::DuplicateTile(1).code(), 104);
+        assert_eq!(BoardError::InputTooLong { length: 10, max: 5 }.code(), 109);
+        assert_eq!(crate::try_parse_board_info("3,1,2").unwrap_err().code(), 102);
+        assert_eq!(crate::try_parse_board_info("x").unwrap_err().code(), 105);
+        let board = Board::new(3, None, 0, None);
+        assert_eq!(board.apply_moves("D", crate::board::Notation::BlankMoves).unwrap_err().code(), 110);
+        assert_eq!(PackError::TooLarge(5).code(), 111);
+        assert_eq!(SolveError::Unreachable.code(), 201);
+        let shuffled = Board::generate(3, 383, 30).unwrap();
+        let limited = crate::solver::solve(&shuffled, &crate::solver::SolveOptions::new().max_nodes(1)).unwrap_err();
+        assert_eq!(limited.code(), 203);
+        assert!(CodedError::from_error(&limited).details.unwrap().starts_with("{\"best_board\":"));
+        assert_eq!(SolveError::TooLarge(String::new()).code(), 204);
+        assert_eq!(SolveError::MemoryBudget(String::new()).code(), 205);
+        assert_eq!(ResumeError::Truncated.code(), 302);
+        assert_eq!("unknown notation".to_string().code(), ERR_INVALID_ARGUMENT);
+        let coded = CodedError::from_error(&SolveError::InvalidOptions("bad".to_string()));
+        assert_eq!(coded, CodedError::new(202, "invalid solve options: bad"));
+        assert_eq!((coded.code(), coded.to_string()), (202, "invalid solve options: bad".to_string()));
+    }
+}
@@ -0,0 +1,681 @@
+//! Exact analysis of the state space of small boards.
+//!
+//! Boards are searched breadth-first from the goal. States are packed 4 bits per
+//! cell, and visited states are kept in a bitset indexed by permutation rank, so a
+//! 3x3 board needs under 50KB of visited bits.
+//!
+//! A `DistanceTable` can be saved and loaded instead of rebuilt. The format is:
+//! * `TABLE_MAGIC`, then the version byte, the board size byte and a little-endian
+//!   u32 entry count
+//! * One byte per state in permutation rank order, the distance from the goal or
+//!   0xFF if unsolvable
+//! * A little-endian 64-bit FNV-1a hash of everything before it
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use super::board::{Board, Notation};
+use super::error_code::ErrorCode;
+use super::pack;
+use super::solver::{self, SolveError, SolveOptions};
+
+/// Largest number of cells that can be analysed
+pub const MAX_CELLS: usize = 10;
+
+/// Nibble used for the blank cell, as in `pack`
+const PACKED_BLANK: u64 = 0xF;
+
+/// Result of a full breadth-first search from the goal.
+///
+/// # Attributes
+/// * `rows` - Number of rows on the board
+/// * `cols` - Number of columns on the board
+/// * `states` - Number of states reachable from the goal
+/// * `diameter` - Largest optimal solution length of any reachable state
+/// * `distance_counts` - Number of states at each distance from the goal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub rows: usize,
+    pub cols: usize,
+    pub states: u64,
+    pub diameter: usize,
+    pub distance_counts: Vec<u64>,
+}
+
+impl Analysis {
+    /// Write the per-distance state counts as CSV.
+    ///
+    /// # Parameters
+    /// * `out` - Where the table is written
+    ///
+    /// # Returns
+    /// An error if writing fails
+    pub fn write_table<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "distance,states")?;
+        for (distance, count) in self.distance_counts.iter().enumerate() {
+            writeln!(out, "{},{}", distance, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}x{} board", self.rows, self.cols)?;
+        writeln!(f, "reachable states: {}", self.states)?;
+        writeln!(f, "diameter: {}", self.diameter)?;
+        for (distance, count) in self.distance_counts.iter().enumerate() {
+            writeln!(f, "{:>4} {:>10}", distance, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check a board can be analysed and count the permutations of its cells.
+///
+/// # Parameters
+/// * `rows` - Number of rows on the board
+/// * `cols` - Number of columns on the board
+///
+/// # Returns
+/// Number of cells and permutations, or why the board can't be analysed
+fn _permutations(rows: usize, cols: usize) -> Result<(usize, usize), SolveError> {
+    let cells = rows.saturating_mul(cols);
+    if cells == 0 || cells > MAX_CELLS {
+        return Err(SolveError::TooLarge(format!(
+            "can only analyse boards of 1 to {} cells, got {}x{}", MAX_CELLS, rows, cols
+        )));
+    }
+    Ok((cells, (1..=cells).product()))
+}
+
+/// Allocate a filled vector, reporting failure instead of aborting.
+///
+/// # Parameters
+/// * `len` - Number of elements
+/// * `value` - Value of every element
+///
+/// # Returns
+/// The vector, or an error if it couldn't be allocated
+fn _try_filled<T: Clone>(len: usize, value: T) -> Result<Vec<T>, SolveError> {
+    let mut filled = Vec::new();
+    filled.try_reserve_exact(len)
+        .map_err(|_| SolveError::TooLarge(format!("could not allocate {} table entries", len)))?;
+    filled.resize(len, value);
+    Ok(filled)
+}
+
+/// Search the full state space of a board breadth-first from the goal.
+///
+/// # Parameters
+/// * `rows` - Number of rows on the board
+/// * `cols` - Number of columns on the board
+/// * `visit` - Called with the rank and distance of each reachable state
+///
+/// # Returns
+/// Number of states at each distance from the goal, or why the board can't be analysed
+fn _bfs<F: FnMut(usize, usize)>(rows: usize, cols: usize, mut visit: F) -> Result<Vec<u64>, SolveError> {
+    let (cells, permutations) = _permutations(rows, cols)?;
+    let mut visited = _try_filled(permutations.div_ceil(64), 0u64)?;
+    // Goal: tile i + 1 (stored as i) at index i, blank last
+    let goal = (0..cells - 1).fold(PACKED_BLANK << (4 * (cells - 1)), |state, i| state | (i as u64) << (4 * i));
+    let goal_rank = pack::rank_packed(goal, cells) as usize;
+    visited[goal_rank / 64] |= 1 << (goal_rank % 64);
+    visit(goal_rank, 0);
+    let mut distance_counts = Vec::new();
+    let mut layer = vec![(goal, cells - 1)];
+    while !layer.is_empty() {
+        distance_counts.push(layer.len() as u64);
+        let distance = distance_counts.len();
+        let mut next_layer = Vec::new();
+        for &(state, blank) in &layer {
+            let (row, col) = (blank / cols, blank % cols);
+            let mut neighbours = Vec::with_capacity(4);
+            if row > 0 {
+                neighbours.push(blank - cols);
+            }
+            if row + 1 < rows {
+                neighbours.push(blank + cols);
+            }
+            if col > 0 {
+                neighbours.push(blank - 1);
+            }
+            if col + 1 < cols {
+                neighbours.push(blank + 1);
+            }
+            for swap in neighbours {
+                // Slide the tile into the blank
+                let tile = (state >> (4 * swap)) & 0xF;
+                let next = (state & !(0xF << (4 * blank)) & !(0xF << (4 * swap)))
+                    | tile << (4 * blank)
+                    | PACKED_BLANK << (4 * swap);
+                let rank = pack::rank_packed(next, cells) as usize;
+                if visited[rank / 64] & (1 << (rank % 64)) == 0 {
+                    visited[rank / 64] |= 1 << (rank % 64);
+                    visit(rank, distance);
+                    next_layer.push((next, swap));
+                }
+            }
+        }
+        layer = next_layer;
+    }
+    Ok(distance_counts)
+}
+
+/// Analyse the full state space of a board by breadth-first search from the goal.
+/// * Only half of all layouts are reachable, so the state count is `(rows * cols)! / 2`
+///   for boards with at least two rows and columns.
+///
+/// # Parameters
+/// * `rows` - Number of rows on the board
+/// * `cols` - Number of columns on the board
+///
+/// # Returns
+/// State count, diameter, and number of states at each distance
+///
+/// # Panics
+/// If the board is empty or has more than `MAX_CELLS` cells
+pub fn analyze(rows: usize, cols: usize) -> Analysis {
+    try_analyze(rows, cols).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Analyse the full state space of a board, reporting boards that are too large.
+///
+/// # Parameters
+/// * `rows` - Number of rows on the board
+/// * `cols` - Number of columns on the board
+///
+/// # Returns
+/// State count, diameter, and number of states at each distance, or
+/// `SolveError::TooLarge` if the board is empty, has more than `MAX_CELLS` cells or
+/// its tables can't be allocated
+pub fn try_analyze(rows: usize, cols: usize) -> Result<Analysis, SolveError> {
+    let distance_counts = _bfs(rows, cols, |_, _| {})?;
+    Ok(Analysis {
+        rows,
+        cols,
+        states: distance_counts.iter().sum(),
+        diameter: distance_counts.len() - 1,
+        distance_counts,
+    })
+}
+
+/// A board whose solution disagrees with its exact distance from the goal.
+///
+/// # Attributes
+/// * `board` - The board
+/// * `distance` - Optimal solution length found by breadth-first search
+/// * `result` - Moves the solver returned, or why it failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExhaustiveMismatch {
+    pub board: Board,
+    pub distance: usize,
+    pub result: Result<String, SolveError>,
+}
+
+impl fmt::Display for ExhaustiveMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: optimal length {}, ", self.board.to_compact_string(), self.distance)?;
+        match &self.result {
+            Ok(moves) => write!(f, "solver returned {:?} ({} moves)", moves, moves.len()),
+            Err(e) => write!(f, "solver failed: {}", e),
+        }
+    }
+}
+
+/// Result of solving every reachable state of a board, see `verify_exhaustive`.
+///
+/// # Attributes
+/// * `rows` - Number of rows on the board
+/// * `cols` - Number of columns on the board
+/// * `states` - Number of states solved
+/// * `mismatches` - States whose solution failed, didn't solve the board or wasn't
+///   optimal, in breadth-first order
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExhaustiveReport {
+    pub rows: usize,
+    pub cols: usize,
+    pub states: u64,
+    pub mismatches: Vec<ExhaustiveMismatch>,
+}
+
+impl ExhaustiveReport {
+    /// Check whether every state was solved optimally.
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for ExhaustiveReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}x{} board: {} states, {} mismatches", self.rows, self.cols, self.states, self.mismatches.len())?;
+        for mismatch in &self.mismatches {
+            writeln!(f, "  {}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+/// Solve every reachable state of a small board and check each solution against the
+/// exact distance found by breadth-first search.
+/// * A smoke test for changes to the heuristics and searches: with an optimal algorithm
+///   there should be no mismatches. Greedy and layered solutions are only expected to
+///   solve the board, so long ones are reported too.
+/// * The solver only handles square boards, so `rows` must equal `cols`.
+///
+/// # Parameters
+/// * `rows` - Number of rows on the board
+/// * `cols` - Number of columns on the board
+/// * `options` - Options every state is solved with
+///
+/// # Returns
+/// The report, or why the board can't be verified: `SolveError::InvalidOptions` for
+/// rectangular boards or bad options, `SolveError::TooLarge` as for `try_analyze`
+pub fn verify_exhaustive(rows: usize, cols: usize, options: &SolveOptions) -> Result<ExhaustiveReport, SolveError> {
+    if rows != cols {
+        return Err(SolveError::InvalidOptions(format!("the solver only handles square boards, got {}x{}", rows, cols)));
+    }
+    options.validate()?;
+    let (cells, _) = _permutations(rows, cols)?;
+    let mut states = Vec::new();
+    _bfs(rows, cols, |rank, distance| states.push((rank, distance)))?;
+    let n = rows as i32;
+    let mismatches = states.iter()
+        .filter_map(|&(rank, distance)| {
+            let board = pack::decode(n, pack::unrank_packed(rank as u64, cells));
+            let result = solver::solve(&board, options).map(|solution| solution.moves);
+            let correct = result.as_ref().is_ok_and(|moves| {
+                moves.len() == distance && solver::verify_solution(&board, moves, Notation::BlankMoves)
+            });
+            if correct { None } else { Some(ExhaustiveMismatch { board, distance, result }) }
+        })
+        .collect();
+    Ok(ExhaustiveReport { rows, cols, states: states.len() as u64, mismatches })
+}
+
+/// First bytes of a saved distance table
+pub const TABLE_MAGIC: [u8; 4] = *b"STPD";
+
+/// Version of the saved distance table format
+pub const TABLE_VERSION: u8 = 1;
+
+/// Length of the saved table header: magic, version, size and a u32 entry count
+const TABLE_HEADER_LEN: usize = 10;
+
+/// Length of the checksum at the end of a saved table
+const TABLE_CHECKSUM_LEN: usize = 8;
+
+/// Reasons a saved distance table can't be loaded.
+///
+/// # Error codes
+/// * 309 - `UnsupportedVersion`
+/// * 310 - `Truncated`
+/// * 311 - `Corrupt`
+/// * 312 - `ChecksumMismatch`
+/// * 313 - `Io`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableError {
+    /// The table was written by a different version of the format
+    UnsupportedVersion(u8),
+    /// The data ended before the checksum
+    Truncated,
+    /// The data is not a distance table
+    Corrupt(String),
+    /// The checksum doesn't match the data
+    ChecksumMismatch,
+    /// The file couldn't be read
+    Io(String),
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableError::UnsupportedVersion(version) => write!(
+                f, "unsupported distance table version {} (expected {})", version, TABLE_VERSION
+            ),
+            TableError::Truncated => write!(f, "distance table is truncated"),
+            TableError::Corrupt(reason) => write!(f, "corrupt distance table: {}", reason),
+            TableError::ChecksumMismatch => write!(f, "distance table checksum doesn't match its data"),
+            TableError::Io(reason) => write!(f, "could not read distance table: {}", reason),
+        }
+    }
+}
+
+impl Error for TableError {}
+
+impl ErrorCode for TableError {
+    fn code(&self) -> u32 {
+        match self {
+            TableError::UnsupportedVersion(_) => 309,
+            TableError::Truncated => 310,
+            TableError::Corrupt(_) => 311,
+            TableError::ChecksumMismatch => 312,
+            TableError::Io(_) => 313,
+        }
+    }
+}
+
+/// Hash bytes with 64-bit FNV-1a.
+///
+/// # Parameters
+/// * `bytes` - Data to hash
+///
+/// # Returns
+/// The checksum
+fn _checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3))
+}
+
+/// Exact distance from the goal of every state of a square board.
+///
+/// # Attributes
+/// * `n` - Size of the board
+/// * `distances` - Distance of each state by permutation rank, `UNREACHABLE` if unsolvable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceTable {
+    n: i32,
+    distances: Vec<u8>,
+}
+
+/// Distance recorded for states that can't reach the goal
+const UNREACHABLE: u8 = u8::MAX;
+
+impl DistanceTable {
+    /// Build the table by breadth-first search from the goal.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    ///
+    /// # Returns
+    /// The distance table
+    ///
+    /// # Panics
+    /// If the board has more than `MAX_CELLS` cells
+    pub fn new(n: i32) -> DistanceTable {
+        DistanceTable::try_new(n).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Build the table, reporting boards that are too large.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    ///
+    /// # Returns
+    /// The distance table, or `SolveError::TooLarge` if the board is empty, has more
+    /// than `MAX_CELLS` cells or the table can't be allocated
+    pub fn try_new(n: i32) -> Result<DistanceTable, SolveError> {
+        let size = n.max(0) as usize;
+        let (_, permutations) = _permutations(size, size)?;
+        let mut distances = _try_filled(permutations, UNREACHABLE)?;
+        _bfs(size, size, |rank, distance| distances[rank] = distance as u8)?;
+        Ok(DistanceTable { n, distances })
+    }
+
+    /// Size of the boards in the table.
+    ///
+    /// # Returns
+    /// Board size
+    pub fn size(&self) -> i32 {
+        self.n
+    }
+
+    /// Look up the optimal solution length of a board.
+    ///
+    /// # Parameters
+    /// * `board` - Board of the table's size
+    ///
+    /// # Returns
+    /// Optimal solution length, or `None` if the board is unsolvable or the wrong size
+    pub fn distance(&self, board: &Board) -> Option<usize> {
+        if board.size() != self.n {
+            return None;
+        }
+        match self.distances[board.lehmer_rank() as usize] {
+            UNREACHABLE => None,
+            distance => Some(distance as usize),
+        }
+    }
+
+    /// Get every board at a distance from the goal.
+    ///
+    /// # Parameters
+    /// * `distance` - Optimal solution length
+    ///
+    /// # Returns
+    /// Boards in permutation rank order, uncosted
+    pub fn boards_at(&self, distance: usize) -> Vec<Board> {
+        let cells = (self.n * self.n) as usize;
+        self.distances.iter()
+            .enumerate()
+            .filter(|&(_, &d)| d != UNREACHABLE && d as usize == distance)
+            .map(|(rank, _)| pack::decode(self.n, pack::unrank_packed(rank as u64, cells)))
+            .collect()
+    }
+
+    /// Serialize the table, see the module docs for the format.
+    ///
+    /// # Returns
+    /// Header, one byte per state and checksum
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(TABLE_HEADER_LEN + self.distances.len() + TABLE_CHECKSUM_LEN);
+        bytes.extend_from_slice(&TABLE_MAGIC);
+        bytes.push(TABLE_VERSION);
+        bytes.push(self.n as u8);
+        bytes.extend_from_slice(&(self.distances.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.distances);
+        let checksum = _checksum(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Deserialize a table written by `to_bytes`.
+    ///
+    /// # Parameters
+    /// * `bytes` - Serialized table
+    ///
+    /// # Returns
+    /// The distance table, or why the data isn't a valid table
+    pub fn from_bytes(bytes: &[u8]) -> Result<DistanceTable, TableError> {
+        if bytes.len() < TABLE_HEADER_LEN {
+            return Err(TableError::Truncated);
+        }
+        if bytes[..4] != TABLE_MAGIC {
+            return Err(TableError::Corrupt("not a distance table".to_string()));
+        }
+        if bytes[4] != TABLE_VERSION {
+            return Err(TableError::UnsupportedVersion(bytes[4]));
+        }
+        let n = bytes[5] as i32;
+        let (_, permutations) = _permutations(n as usize, n as usize)
+            .map_err(|_| TableError::Corrupt(format!("unsupported board size {}", n)))?;
+        let entries = u32::from_le_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]) as usize;
+        if entries != permutations {
+            return Err(TableError::Corrupt(format!(
+                "{}x{} tables have {} entries, got {}", n, n, permutations, entries
+            )));
+        }
+        let end = TABLE_HEADER_LEN + entries;
+        if bytes.len() < end + TABLE_CHECKSUM_LEN {
+            return Err(TableError::Truncated);
+        }
+        if bytes.len() > end + TABLE_CHECKSUM_LEN {
+            return Err(TableError::Corrupt(format!("{} trailing bytes", bytes.len() - end - TABLE_CHECKSUM_LEN)));
+        }
+        let mut checksum = [0; TABLE_CHECKSUM_LEN];
+        checksum.copy_from_slice(&bytes[end..]);
+        if u64::from_le_bytes(checksum) != _checksum(&bytes[..end]) {
+            return Err(TableError::ChecksumMismatch);
+        }
+        Ok(DistanceTable { n, distances: bytes[TABLE_HEADER_LEN..end].to_vec() })
+    }
+
+    /// Save the table to a file, see `to_bytes`.
+    ///
+    /// # Parameters
+    /// * `path` - File to write, replaced if it exists
+    ///
+    /// # Returns
+    /// An error if writing fails
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    /// Load a table saved with `save`.
+    ///
+    /// # Parameters
+    /// * `path` - File to read
+    ///
+    /// # Returns
+    /// The distance table, or why it couldn't be read
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<DistanceTable, TableError> {
+        let bytes = fs::read(path).map_err(|e| TableError::Io(e.to_string()))?;
+        DistanceTable::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    /// Test known state counts and diameters
+    #[test]
+    fn test_analyze() {
+        let analysis = analyze(2, 2);
+        assert_eq!(analysis.states, 12);
+        assert_eq!(analysis.diameter, 6);
+        assert_eq!(analysis.distance_counts, vec![1, 2, 2, 2, 2, 2, 1]);
+        let analysis = analyze(2, 3);
+        assert_eq!((analysis.states, analysis.diameter), (360, 21));
+        let analysis = analyze(3, 3);
+        assert_eq!(analysis.states, 181_440);
+        assert_eq!(analysis.diameter, 31);
+        assert_eq!(analysis.distance_counts[31], 2);
+        let analysis = analyze(1, 3);
+        assert_eq!((analysis.states, analysis.diameter), (3, 2));
+    }
+
+    /// Test table distances match the solver and the analysis
+    #[test]
+    fn test_distance_table() {
+        let table = DistanceTable::new(3);
+        let board = crate::try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        assert_eq!(table.distance(&board), Some(3));
+        assert_eq!(table.distance(&Board::new(3, None, 0, None)), Some(0));
+        let unsolvable = Board::try_from(vec![2, 1, 3, 4, 5, 6, 7, 8, -1]).unwrap();
+        assert_eq!(table.distance(&unsolvable), None);
+        assert_eq!(table.distance(&Board::new(2, None, 0, None)), None);
+        let farthest = table.distances.iter().filter(|&&distance| distance == 31).count();
+        assert_eq!(farthest as u64, analyze(3, 3).distance_counts[31]);
+    }
+
+    /// Test table entries are stored by Lehmer rank, and ranks and packed states convert
+    /// back and forth for non-square boards too
+    #[test]
+    fn test_rank_unrank() {
+        for &cells in &[1, 4, 6, 9] {
+            let permutations = pack::permutation_count(cells);
+            for rank in (0..permutations).step_by(permutations as usize / 50 + 1).chain(Some(permutations - 1)) {
+                assert_eq!(pack::rank_packed(pack::unrank_packed(rank, cells), cells), rank);
+            }
+        }
+        let table = DistanceTable::new(3);
+        let board = crate::try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        assert_eq!(table.distances[board.lehmer_rank() as usize], 3);
+        assert_eq!(table.distances[0], 0);
+        assert!(table.boards_at(31).iter().all(|board| table.distance(board) == Some(31)));
+        assert_eq!(table.boards_at(31).len(), 2);
+    }
+
+    /// Test a saved table loads back equal, with the same distances
+    #[test]
+    fn test_distance_table_save_load() {
+        let table = DistanceTable::new(3);
+        let path = std::env::temp_dir().join(format!("distance_table_{}.bin", std::process::id()));
+        table.save(&path).unwrap();
+        let loaded = DistanceTable::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, table);
+        assert_eq!(table.to_bytes().len(), 10 + 362_880 + 8);
+        let board = crate::try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        assert_eq!(loaded.distance(&board), Some(3));
+        assert_eq!(loaded.distance(&Board::new(3, None, 0, None)), Some(0));
+        let unsolvable = Board::try_from(vec![2, 1, 3, 4, 5, 6, 7, 8, -1]).unwrap();
+        assert_eq!(loaded.distance(&unsolvable), None);
+        let small = DistanceTable::new(2);
+        assert_eq!(DistanceTable::from_bytes(&small.to_bytes()), Ok(small));
+        assert!(matches!(DistanceTable::load(&path), Err(TableError::Io(_))));
+    }
+
+    /// Test damaged tables are rejected
+    #[test]
+    fn test_distance_table_corrupt() {
+        let bytes = DistanceTable::new(2).to_bytes();
+        let mut flipped = bytes.clone();
+        flipped[20] ^= 1;
+        assert_eq!(DistanceTable::from_bytes(&flipped), Err(TableError::ChecksumMismatch));
+        let mut checksum = bytes.clone();
+        *checksum.last_mut().unwrap() ^= 0x80;
+        assert_eq!(DistanceTable::from_bytes(&checksum), Err(TableError::ChecksumMismatch));
+        assert_eq!(DistanceTable::from_bytes(&bytes[..bytes.len() - 1]), Err(TableError::Truncated));
+        assert_eq!(DistanceTable::from_bytes(&bytes[..3]), Err(TableError::Truncated));
+        let mut version = bytes.clone();
+        version[4] = 9;
+        assert_eq!(DistanceTable::from_bytes(&version), Err(TableError::UnsupportedVersion(9)));
+        let mut size = bytes.clone();
+        size[5] = 5;
+        assert!(matches!(DistanceTable::from_bytes(&size), Err(TableError::Corrupt(_))));
+        assert!(matches!(DistanceTable::from_bytes(b"not a table at all"), Err(TableError::Corrupt(_))));
+        let mut trailing = bytes;
+        trailing.push(0);
+        let error = DistanceTable::from_bytes(&trailing).unwrap_err();
+        assert_eq!((error.code(), error.to_string().as_str()), (311, "corrupt distance table: 1 trailing bytes"));
+    }
+
+    /// Test every 2x2 state is solved optimally
+    #[test]
+    fn test_verify_exhaustive() {
+        let report = verify_exhaustive(2, 2, &SolveOptions::default()).unwrap();
+        assert_eq!((report.states, report.mismatches.len()), (12, 0));
+        assert!(report.is_ok());
+        assert_eq!(report.to_string(), "2x2 board: 12 states, 0 mismatches\n");
+        let ida_star = SolveOptions::new().algorithm(crate::solver::Algorithm::IdaStar);
+        assert!(verify_exhaustive(2, 2, &ida_star).unwrap().is_ok());
+        // Boards the solver gives up on are reported
+        let report = verify_exhaustive(2, 2, &SolveOptions::new().max_nodes(1)).unwrap();
+        assert!(!report.is_ok());
+        let mismatch = &report.mismatches[0];
+        assert!(matches!(mismatch.result, Err(SolveError::BudgetExceeded(_))));
+        assert_eq!((report.mismatches.len(), mismatch.distance), (9, 2));
+        assert!(report.to_string().contains(", solver failed: gave up after expanding 1 boards"), "{}", report);
+        assert!(matches!(verify_exhaustive(2, 3, &SolveOptions::default()), Err(SolveError::InvalidOptions(_))));
+        assert!(matches!(verify_exhaustive(4, 4, &SolveOptions::default()), Err(SolveError::TooLarge(_))));
+    }
+
+    /// Test boards too large or empty to analyse are reported instead of allocated
+    #[test]
+    fn test_analyze_too_large() {
+        for &(rows, cols) in &[(0, 3), (4, 4), (1_000_000, 1_000_000), (usize::MAX, 2)] {
+            match try_analyze(rows, cols) {
+                Err(SolveError::TooLarge(reason)) => assert!(reason.contains("1 to 10 cells"), "{}", reason),
+                other => panic!("expected TooLarge for {}x{}, got {:?}", rows, cols, other),
+            }
+        }
+        for &n in &[-3, 0, 4, i32::MAX] {
+            assert!(matches!(DistanceTable::try_new(n), Err(SolveError::TooLarge(_))));
+        }
+        assert_eq!(try_analyze(2, 2), Ok(analyze(2, 2)));
+    }
+
+    /// Test the distance table is written as CSV
+    #[test]
+    fn test_analyze_write_table() {
+        let mut out = Vec::new();
+        analyze(2, 2).write_table(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "distance,states\n0,1\n1,2\n2,2\n3,2\n4,2\n5,2\n6,1\n");
+    }
+}
@@ -0,0 +1,394 @@
+use std::fmt;
+
+use super::board::{parse_path, Board, Notation, PathParseError, DOWN, LEFT, UP};
+use super::format_board_info;
+use super::observer::SolverObserver;
+use super::solver::{self, SolveError, SolveOptions};
+
+/// A board generated while expanding another.
+///
+/// # Attributes
+/// * `via` - Move that produced the board
+/// * `board` - The board, in board info format
+/// * `f` - Cost of the board (g + h), or `None` if it was skipped as a duplicate
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChildRecord {
+    pub via: char,
+    pub board: String,
+    pub f: Option<i32>,
+}
+
+/// One expansion of the search, explaining the cost of the popped board.
+///
+/// # Attributes
+/// * `board` - The expanded board, in board info format
+/// * `g` - Depth of the board
+/// * `manhattan` - Manhattan component of the heuristic
+/// * `linear_conflicts` - Linear conflict component of the heuristic, or the rest of other
+///   heuristics above the Manhattan component
+/// * `children` - Boards generated from this one, in move order
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpansionRecord {
+    pub board: String,
+    pub g: i32,
+    pub manhattan: i32,
+    pub linear_conflicts: i32,
+    pub children: Vec<ChildRecord>,
+}
+
+impl ExpansionRecord {
+    /// Total cost of the expanded board.
+    ///
+    /// # Returns
+    /// f = g + manhattan + linear conflicts
+    pub fn f(&self) -> i32 {
+        self.g + self.manhattan + self.linear_conflicts
+    }
+}
+
+impl fmt::Display for ExpansionRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "expand {}: f = {} = g {} + manhattan {} + linear conflicts {}",
+            self.board, self.f(), self.g, self.manhattan, self.linear_conflicts
+        )?;
+        for child in &self.children {
+            match child.f {
+                Some(cost) => writeln!(f, "  {} -> {}: f = {}", child.via, child.board, cost)?,
+                None => writeln!(f, "  {} -> {}: skipped, already expanded", child.via, child.board)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Observer that records an `ExpansionRecord` for each expansion.
+///
+/// # Attributes
+/// * `max_steps` - Maximum number of expansions to record
+/// * `records` - Recorded expansions
+/// * `capped` - Whether `max_steps` expansions have been recorded
+#[derive(Debug, Clone)]
+struct ExplainRecorder {
+    max_steps: usize,
+    records: Vec<ExpansionRecord>,
+    capped: bool,
+}
+
+impl ExplainRecorder {
+    /// Add a child to the expansion being recorded.
+    ///
+    /// # Parameters
+    /// * `board` - The child board
+    /// * `via` - Move that produced the board
+    /// * `f` - Cost of the board, if it wasn't skipped
+    fn add_child(&mut self, board: &Board, via: char, f: Option<i32>) {
+        // Children of expansions past the cap are dropped along with their parent
+        if self.capped {
+            return;
+        }
+        if let Some(record) = self.records.last_mut() {
+            record.children.push(ChildRecord { via, board: format_board_info(board), f });
+        }
+    }
+}
+
+impl SolverObserver for ExplainRecorder {
+    fn on_expand(&mut self, board: &Board, g: i32, _h: i32) {
+        if self.records.len() < self.max_steps {
+            self.records.push(ExpansionRecord {
+                board: format_board_info(board),
+                g,
+                manhattan: board.manhattan_cost_cache,
+                linear_conflicts: board.linear_conflicts_cache,
+                children: Vec::new(),
+            });
+        } else {
+            self.capped = true;
+        }
+    }
+
+    fn on_generate(&mut self, board: &Board, via: char) {
+        self.add_child(board, via, Some(board.cost));
+    }
+
+    fn on_skip(&mut self, board: &Board, via: char) {
+        self.add_child(board, via, None);
+    }
+}
+
+/// Solve a board, explaining each expansion of the search.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `max_steps` - Maximum number of expansions to record
+///
+/// # Returns
+/// The recorded expansions, in order, or why the board can't be solved
+pub fn solve_explained(board: &Board, max_steps: usize) -> Result<Vec<ExpansionRecord>, SolveError> {
+    let mut recorder = ExplainRecorder { max_steps, records: Vec::new(), capped: false };
+    solver::solve_observed(board, &SolveOptions::default(), &mut recorder)?;
+    Ok(recorder.records)
+}
+
+/// Format expansions as human-readable text, one numbered step per expansion.
+///
+/// # Parameters
+/// * `records` - Expansions from `solve_explained`
+///
+/// # Returns
+/// Explanation text
+pub fn format_explanation(records: &[ExpansionRecord]) -> String {
+    records.iter()
+        .enumerate()
+        .map(|(i, record)| format!("step {}: {}", i + 1, record))
+        .collect()
+}
+
+/// One move of a solution, as the tile that slid.
+///
+/// # Attributes
+/// * `tile` - Symbol of the tile that slid
+/// * `direction` - Direction the tile slid in
+/// * `blank` - Index of the blank before the move
+#[derive(Debug, Clone, Copy)]
+struct _TileStep {
+    tile: i32,
+    direction: char,
+    blank: i32,
+}
+
+/// Describe a solution in human terms, grouping its moves into higher-level steps.
+/// * Three or more moves of the blank turning the same way around a 2x2 block rotate
+///   its tiles, e.g. "rotate tiles 2, 3 and 6 clockwise in the top-right corner (3 moves)".
+/// * Otherwise consecutive moves of the blank in one direction slide a row or column
+///   of tiles, e.g. "tiles 7 and 8 each move 1 cell right".
+/// * Anything else is described a move at a time, e.g. "tile 5 moves 1 cell left".
+///
+/// # Parameters
+/// * `start` - The board the solution starts from
+/// * `path` - Moves of the blank, read with `parse_path`
+///
+/// # Returns
+/// One description per step, up to the first move that can't be read or made; none if
+/// the path is longer than `MAX_PATH_LEN`
+pub fn describe_solution(start: &Board, path: &str) -> Vec<String> {
+    let moves = match parse_path(path, Notation::BlankMoves) {
+        Ok(moves) => moves,
+        // Everything before the bad token reads
+        Err(PathParseError::InvalidToken { offset, .. }) =>
+            parse_path(&path[..offset], Notation::BlankMoves).unwrap_or_default(),
+        Err(PathParseError::TooLong { .. }) => Vec::new(),
+    };
+    let mut board = start.clone();
+    let mut steps = Vec::new();
+    for &blank_move in &moves {
+        if !board.is_legal_move(blank_move) {
+            break;
+        }
+        let next = board.neighbor(blank_move);
+        let (tile, direction) = match board.moved_tile(&next) {
+            Some(moved) => moved,
+            None => break,
+        };
+        steps.push(_TileStep { tile, direction, blank: board.get_blank_index() });
+        board = next;
+    }
+    let mut descriptions = Vec::new();
+    let mut i = 0;
+    while i < steps.len() {
+        let rotation = _rotation_len(&steps[i..]);
+        let len = if rotation >= 3 {
+            descriptions.push(_describe_rotation(start.size(), &steps[i..i + rotation]));
+            rotation
+        } else {
+            let run = steps[i..].iter().take_while(|step| step.direction == steps[i].direction).count();
+            descriptions.push(_describe_run(&steps[i..i + run]));
+            run
+        };
+        i += len;
+    }
+    descriptions
+}
+
+/// Get the screen vector of a direction, with y growing downwards.
+fn _vector(direction: char) -> (i32, i32) {
+    match direction {
+        UP => (0, -1),
+        DOWN => (0, 1),
+        LEFT => (-1, 0),
+        _ => (1, 0),
+    }
+}
+
+/// Get the turn between two directions.
+///
+/// # Returns
+/// 1 for a clockwise quarter turn, -1 for an anticlockwise one, 0 for none or a reversal
+fn _turn(from: char, to: char) -> i32 {
+    let ((ax, ay), (bx, by)) = (_vector(from), _vector(to));
+    ax * by - ay * bx
+}
+
+/// Count the moves at the start of a sequence that circle a 2x2 block.
+/// * Each move must turn a quarter turn the same way as the one before.
+///
+/// # Parameters
+/// * `steps` - Moves to match
+///
+/// # Returns
+/// Number of moves in the rotation, at most 1 if the sequence doesn't start with one
+fn _rotation_len(steps: &[_TileStep]) -> usize {
+    if steps.len() < 2 {
+        return steps.len();
+    }
+    let turn = _turn(steps[0].direction, steps[1].direction);
+    if turn == 0 {
+        return 1;
+    }
+    1 + steps.windows(2).take_while(|pair| _turn(pair[0].direction, pair[1].direction) == turn).count()
+}
+
+/// Describe moves circling a 2x2 block.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `steps` - The rotation, at least 3 moves
+///
+/// # Returns
+/// Description naming the tiles, their sense of rotation and the block
+fn _describe_rotation(n: i32, steps: &[_TileStep]) -> String {
+    let mut tiles: Vec<i32> = Vec::new();
+    for step in steps {
+        if !tiles.contains(&step.tile) {
+            tiles.push(step.tile);
+        }
+    }
+    // Tiles sliding with clockwise turns circle the block anticlockwise, like gears
+    let sense = if _turn(steps[0].direction, steps[1].direction) > 0 { "anticlockwise" } else { "clockwise" };
+    // The blank visits three corners of the block in its first three moves
+    let row = steps[..3].iter().map(|step| step.blank / n).min().unwrap_or(0);
+    let col = steps[..3].iter().map(|step| step.blank % n).min().unwrap_or(0);
+    let vertical = match row {
+        0 => Some("top"),
+        _ if row + 2 == n => Some("bottom"),
+        _ => None,
+    };
+    let horizontal = match col {
+        0 => Some("left"),
+        _ if col + 2 == n => Some("right"),
+        _ => None,
+    };
+    let place = match (vertical, horizontal) {
+        _ if n == 2 => "on the board".to_string(),
+        (Some(vertical), Some(horizontal)) => format!("in the {}-{} corner", vertical, horizontal),
+        _ => format!("in the 2x2 block at row {}, column {}", row + 1, col + 1),
+    };
+    format!("rotate tiles {} {} {} ({} moves)", _join_tiles(&tiles), sense, place, steps.len())
+}
+
+/// Describe consecutive moves in one direction.
+///
+/// # Parameters
+/// * `steps` - The moves, at least 1
+///
+/// # Returns
+/// Description naming the tiles and the direction they slide in
+fn _describe_run(steps: &[_TileStep]) -> String {
+    let direction = match steps[0].direction {
+        UP => "up",
+        DOWN => "down",
+        LEFT => "left",
+        _ => "right",
+    };
+    if steps.len() == 1 {
+        return format!("tile {} moves 1 cell {}", steps[0].tile, direction);
+    }
+    let tiles: Vec<i32> = steps.iter().map(|step| step.tile).collect();
+    format!("tiles {} each move 1 cell {}", _join_tiles(&tiles), direction)
+}
+
+/// List tiles in prose, e.g. "1, 2 and 3".
+fn _join_tiles(tiles: &[i32]) -> String {
+    let names: Vec<String> = tiles.iter().map(|tile| tile.to_string()).collect();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        _ => names.concat(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::try_parse_board_info;
+
+    /// Test straight runs and corner rotations are grouped, and other moves described
+    /// one at a time
+    #[test]
+    fn test_describe_solution() {
+        let board = Board::new(4, None, 0, None);
+        assert_eq!(describe_solution(&board, "LLL URD R"), vec![
+            "tiles 15, 14 and 13 each move 1 cell right",
+            "rotate tiles 9, 10 and 13 anticlockwise in the bottom-left corner (3 moves)",
+            "tile 14 moves 1 cell left",
+        ]);
+        // A full turn around a block in the middle of the board
+        let centre = board.apply_moves("ULUL", Notation::BlankMoves).unwrap();
+        assert_eq!(describe_solution(&centre, "RDLU"), vec![
+            "rotate tiles 6, 7 and 10 anticlockwise in the 2x2 block at row 2, column 2 (4 moves)",
+        ]);
+        assert_eq!(describe_solution(&Board::new(3, None, 0, None), "UL"), vec![
+            "tile 6 moves 1 cell down",
+            "tile 5 moves 1 cell right",
+        ]);
+        // Descriptions stop at the first move that can't be made
+        assert_eq!(describe_solution(&board, "LD"), vec!["tile 15 moves 1 cell right"]);
+        assert!(describe_solution(&board, "x").is_empty());
+        // ...and at the first token that isn't a move
+        assert_eq!(describe_solution(&board, "LLLx"), vec!["tiles 15, 14 and 13 each move 1 cell right"]);
+        assert_eq!(describe_solution(&board, "LLLxUR"), describe_solution(&board, "LLL"));
+    }
+
+    /// Test the explanation of a 4-move instance against golden output
+    #[test]
+    fn test_solve_explained_golden() {
+        let board = try_parse_board_info("3,1,2,3,7,4,6,-1,5,8").unwrap();
+        let records = solve_explained(&board, 100).unwrap();
+        let expected = "\
+step 1: expand 3,1,2,3,7,4,6,-1,5,8: f = 4 = g 0 + manhattan 4 + linear conflicts 0
+  U -> 3,1,2,3,-1,4,6,7,5,8: f = 4
+  R -> 3,1,2,3,7,4,6,5,-1,8: f = 6
+step 2: expand 3,1,2,3,-1,4,6,7,5,8: f = 4 = g 1 + manhattan 3 + linear conflicts 0
+  U -> 3,-1,2,3,1,4,6,7,5,8: f = 6
+  R -> 3,1,2,3,4,-1,6,7,5,8: f = 4
+step 3: expand 3,1,2,3,4,-1,6,7,5,8: f = 4 = g 2 + manhattan 2 + linear conflicts 0
+  U -> 3,1,-1,3,4,2,6,7,5,8: f = 6
+  D -> 3,1,2,3,4,5,6,7,-1,8: f = 4
+  R -> 3,1,2,3,4,6,-1,7,5,8: f = 6
+step 4: expand 3,1,2,3,4,5,6,7,-1,8: f = 4 = g 3 + manhattan 1 + linear conflicts 0
+  L -> 3,1,2,3,4,5,6,-1,7,8: f = 6
+  R -> 3,1,2,3,4,5,6,7,8,-1: f = 4
+";
+        assert_eq!(format_explanation(&records), expected);
+        assert_eq!(records[0].f(), 4);
+        assert_eq!(records[0].children[0], ChildRecord {
+            via: 'U', board: "3,1,2,3,-1,4,6,7,5,8".to_string(), f: Some(4),
+        });
+    }
+
+    /// Test duplicates are reported and the step cap limits the records
+    #[test]
+    fn test_solve_explained_duplicates_and_cap() {
+        let board = try_parse_board_info("3,8,1,3,4,-1,2,7,6,5").unwrap();
+        let records = solve_explained(&board, 1000).unwrap();
+        let duplicates = records.iter()
+            .flat_map(|record| &record.children)
+            .filter(|child| child.f.is_none())
+            .count();
+        assert!(duplicates > 0);
+        assert!(format_explanation(&records).contains("skipped, already expanded"));
+        let capped = solve_explained(&board, 2).unwrap();
+        assert_eq!(capped, records[..2]);
+    }
+}
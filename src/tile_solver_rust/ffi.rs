@@ -0,0 +1,374 @@
+//! C-compatible interface for embedding the solver in other languages.
+//! * Declared for C and C++ in `ffi/sliding_tile_puzzle_solver.h`.
+//! * Boards are passed as `n` and `n * n` tile symbols in row-major order, with -1 for
+//!   the blank.
+//! * Failures return a negative error code; `sps_last_error_message` describes the
+//!   most recent one. No panic crosses the boundary.
+
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use super::board::Board;
+use super::solver::{self, SolveError, SolveOptions};
+use super::tile::Tile;
+
+/// The board is invalid
+pub const SPS_ERR_INVALID_BOARD: i32 = -1;
+/// The goal is unreachable from the board
+pub const SPS_ERR_UNREACHABLE: i32 = -2;
+/// A required pointer was null
+pub const SPS_ERR_NULL_POINTER: i32 = -3;
+/// The solver panicked
+pub const SPS_ERR_PANIC: i32 = -4;
+/// The search expanded `max_nodes` boards without finding the goal
+pub const SPS_ERR_NODE_BUDGET: i32 = -5;
+/// The search ran for `max_time_ms` milliseconds without finding the goal
+pub const SPS_ERR_TIME_BUDGET: i32 = -6;
+/// The search would have grown past `max_memory_bytes`
+pub const SPS_ERR_MEMORY_BUDGET: i32 = -7;
+/// The board is too large for the solver, or the solver failed a check
+pub const SPS_ERR_SOLVER: i32 = -8;
+
+thread_local! {
+    /// Message of the last error on this thread, for `sps_last_error_message`
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Error returned across the boundary.
+///
+/// # Attributes
+/// * `code` - Negative error code
+/// * `message` - Description of the error
+#[derive(Debug, Clone, PartialEq)]
+struct FfiError {
+    code: i32,
+    message: String,
+}
+
+impl FfiError {
+    /// Create an error.
+    ///
+    /// # Parameters
+    /// * `code` - Negative error code
+    /// * `message` - Description of the error
+    fn new<M: ToString>(code: i32, message: M) -> FfiError {
+        FfiError { code, message: message.to_string() }
+    }
+}
+
+/// Run the body of an exported function, converting errors and panics to error codes.
+///
+/// # Parameters
+/// * `body` - Body of the function
+///
+/// # Returns
+/// The result of the body, or a negative error code
+fn _guard<F: FnOnce() -> Result<i32, FfiError>>(body: F) -> i32 {
+    let error = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(result)) => return result,
+        Ok(Err(error)) => error,
+        Err(_) => FfiError::new(SPS_ERR_PANIC, "the solver panicked"),
+    };
+    // Messages come from Display impls, which never contain NUL bytes
+    let message = CString::new(error.message).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+    error.code
+}
+
+/// Read a board from C arguments.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `tiles` - Tile symbols
+/// * `len` - Number of tile symbols
+///
+/// # Returns
+/// The board, or why it couldn't be read
+///
+/// # Safety
+/// `tiles` must point to `len` readable `i32`s, or be null
+unsafe fn _read_board(n: i32, tiles: *const i32, len: usize) -> Result<Board, FfiError> {
+    if tiles.is_null() {
+        return Err(FfiError::new(SPS_ERR_NULL_POINTER, "tiles is null"));
+    }
+    let tiles: Vec<Tile> = slice::from_raw_parts(tiles, len).iter().map(|&symbol| Tile::new(symbol)).collect();
+    Board::try_new(n, tiles).map_err(|e| FfiError::new(SPS_ERR_INVALID_BOARD, e))
+}
+
+/// Solve a board, writing the moves as ASCII `U`, `D`, `L` and `R` characters.
+/// * The board is solved with `SolveOptions::for_size`, without limits beyond the
+///   default memory budget; see `sps_solve_with_limits`.
+/// * The moves are not NUL-terminated.
+/// * If the solution is longer than `out_cap`, nothing is written and the solution
+///   length is returned; callers detect this by a result greater than `out_cap`.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `tiles` - Tile symbols
+/// * `len` - Number of tile symbols
+/// * `out_moves` - Buffer receiving the moves (may be null when `out_cap` is 0)
+/// * `out_cap` - Capacity of `out_moves`
+///
+/// # Returns
+/// Number of moves, the required capacity if the buffer is too small, or a negative
+/// error code
+///
+/// # Safety
+/// `tiles` must point to `len` readable `i32`s and `out_moves` to `out_cap` writable
+/// bytes
+#[no_mangle]
+pub unsafe extern "C" fn sps_solve(n: i32, tiles: *const i32, len: usize,
+                                   out_moves: *mut u8, out_cap: usize) -> i32 {
+    sps_solve_with_limits(n, tiles, len, 0, 0, 0, out_moves, out_cap)
+}
+
+/// Solve a board within limits, writing the moves like `sps_solve`.
+/// * Each limit is off when 0; a memory limit of 0 keeps the default budget.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `tiles` - Tile symbols
+/// * `len` - Number of tile symbols
+/// * `max_nodes` - Give up after expanding this many boards
+/// * `max_time_ms` - Give up after this many milliseconds
+/// * `max_memory_bytes` - Give up before the search structures grow past about this
+///   many bytes
+/// * `out_moves` - Buffer receiving the moves (may be null when `out_cap` is 0)
+/// * `out_cap` - Capacity of `out_moves`
+///
+/// # Returns
+/// Number of moves, the required capacity if the buffer is too small, or a negative
+/// error code
+///
+/// # Safety
+/// `tiles` must point to `len` readable `i32`s and `out_moves` to `out_cap` writable
+/// bytes
+#[allow(clippy::too_many_arguments)]
+#[no_mangle]
+pub unsafe extern "C" fn sps_solve_with_limits(n: i32, tiles: *const i32, len: usize,
+                                               max_nodes: u64, max_time_ms: u64, max_memory_bytes: u64,
+                                               out_moves: *mut u8, out_cap: usize) -> i32 {
+    _guard(|| {
+        let board = _read_board(n, tiles, len)?;
+        let mut options = SolveOptions::for_size(n);
+        if max_nodes > 0 {
+            options = options.max_nodes(max_nodes);
+        }
+        if max_time_ms > 0 {
+            options = options.max_time_ms(max_time_ms);
+        }
+        if max_memory_bytes > 0 {
+            options = options.max_memory_bytes(max_memory_bytes);
+        }
+        let solution = solver::solve(&board, &options).map_err(|e| _solve_error(e, max_nodes))?;
+        let moves = solution.moves.as_bytes();
+        if moves.len() > out_cap {
+            return Ok(moves.len() as i32);
+        }
+        if !moves.is_empty() {
+            if out_moves.is_null() {
+                return Err(FfiError::new(SPS_ERR_NULL_POINTER, "out_moves is null"));
+            }
+            ptr::copy_nonoverlapping(moves.as_ptr(), out_moves, moves.len());
+        }
+        Ok(moves.len() as i32)
+    })
+}
+
+/// Convert a solve error to its error code.
+/// * Node and time budgets fail with the same `SolveError`, so the node count tells
+///   them apart.
+///
+/// # Parameters
+/// * `error` - Error of the solve
+/// * `max_nodes` - Node limit of the solve, 0 if none
+///
+/// # Returns
+/// The error with its code
+fn _solve_error(error: SolveError, max_nodes: u64) -> FfiError {
+    let code = match &error {
+        SolveError::Unreachable => SPS_ERR_UNREACHABLE,
+        SolveError::BudgetExceeded(partial) if max_nodes > 0 && partial.stats.nodes_expanded >= max_nodes =>
+            SPS_ERR_NODE_BUDGET,
+        SolveError::BudgetExceeded(_) => SPS_ERR_TIME_BUDGET,
+        SolveError::MemoryBudget(_) => SPS_ERR_MEMORY_BUDGET,
+        SolveError::InvalidOptions(_) | SolveError::TooLarge(_) | SolveError::HeuristicInconsistent(_) =>
+            SPS_ERR_SOLVER,
+    };
+    FfiError::new(code, error)
+}
+
+/// Check if a board can be solved.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `tiles` - Tile symbols
+/// * `len` - Number of tile symbols
+///
+/// # Returns
+/// 1 if the board is solvable, 0 if not, or a negative error code
+///
+/// # Safety
+/// `tiles` must point to `len` readable `i32`s
+#[no_mangle]
+pub unsafe extern "C" fn sps_is_solvable(n: i32, tiles: *const i32, len: usize) -> i32 {
+    _guard(|| Ok(_read_board(n, tiles, len)?.is_solvable() as i32))
+}
+
+/// Estimate the number of moves needed to solve a board.
+/// * The estimate never exceeds the optimal solution length.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `tiles` - Tile symbols
+/// * `len` - Number of tile symbols
+///
+/// # Returns
+/// Manhattan distance plus linear conflicts, or a negative error code
+///
+/// # Safety
+/// `tiles` must point to `len` readable `i32`s
+#[no_mangle]
+pub unsafe extern "C" fn sps_estimate(n: i32, tiles: *const i32, len: usize) -> i32 {
+    _guard(|| Ok(_read_board(n, tiles, len)?.heuristic_breakdown(None).total))
+}
+
+/// Describe the last error on the calling thread.
+/// * Successful calls leave the message in place.
+///
+/// # Returns
+/// NUL-terminated message, valid until the next failing call on this thread, or null
+/// if no call has failed
+#[no_mangle]
+pub extern "C" fn sps_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last_error| match &*last_error.borrow() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Get the last error message as a string.
+    fn last_error() -> String {
+        let message = sps_last_error_message();
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_string()
+    }
+
+    /// Test solving through the C interface
+    #[test]
+    fn test_sps_solve() {
+        let tiles = [1, 2, 3, -1, 4, 6, 7, 5, 8];
+        let mut moves = [0u8; 8];
+        let count = unsafe { sps_solve(3, tiles.as_ptr(), tiles.len(), moves.as_mut_ptr(), moves.len()) };
+        assert_eq!(count, 3);
+        assert_eq!(&moves[..3], b"RDR");
+        // Too small a buffer reports the required capacity and writes nothing
+        let mut small = [0u8; 2];
+        let count = unsafe { sps_solve(3, tiles.as_ptr(), tiles.len(), small.as_mut_ptr(), small.len()) };
+        assert_eq!(count, 3);
+        assert_eq!(small, [0, 0]);
+        assert_eq!(unsafe { sps_solve(3, tiles.as_ptr(), tiles.len(), ptr::null_mut(), 0) }, 3);
+        assert_eq!(unsafe { sps_is_solvable(3, tiles.as_ptr(), tiles.len()) }, 1);
+        assert_eq!(unsafe { sps_estimate(3, tiles.as_ptr(), tiles.len()) }, 3);
+    }
+
+    /// Test failures return error codes with messages
+    #[test]
+    fn test_sps_errors() {
+        let tiles = [1, 2, 3, -1, 4, 6, 7, 5, 8];
+        let mut moves = [0u8; 8];
+        assert_eq!(unsafe { sps_solve(3, tiles.as_ptr(), 8, moves.as_mut_ptr(), moves.len()) }, SPS_ERR_INVALID_BOARD);
+        assert_eq!(last_error(), "expected 9 tiles, found 8");
+        assert_eq!(unsafe { sps_estimate(3, ptr::null(), 9) }, SPS_ERR_NULL_POINTER);
+        assert_eq!(last_error(), "tiles is null");
+        assert_eq!(unsafe { sps_solve(3, tiles.as_ptr(), tiles.len(), ptr::null_mut(), 8) }, SPS_ERR_NULL_POINTER);
+        let unsolvable = [2, 1, 3, -1];
+        assert_eq!(unsafe { sps_is_solvable(2, unsolvable.as_ptr(), unsolvable.len()) }, 0);
+        assert_eq!(
+            unsafe { sps_solve(2, unsolvable.as_ptr(), unsolvable.len(), moves.as_mut_ptr(), moves.len()) },
+            SPS_ERR_UNREACHABLE
+        );
+        assert_eq!(last_error(), "goal is unreachable from this board");
+        assert_eq!(_guard(|| panic!("boom")), SPS_ERR_PANIC);
+        assert_eq!(last_error(), "the solver panicked");
+    }
+
+    /// Test each limit of a solve fails with its own error code
+    #[test]
+    fn test_sps_solve_limits() {
+        let solve = |board: &Board, max_nodes, max_time_ms, max_memory_bytes| {
+            let tiles: Vec<i32> = board.tiles().iter().map(|tile| tile.symbol()).collect();
+            let mut moves = [0u8; 100];
+            let (n, len, out_cap) = (board.size(), tiles.len(), moves.len());
+            unsafe {
+                sps_solve_with_limits(n, tiles.as_ptr(), len, max_nodes, max_time_ms, max_memory_bytes,
+                                      moves.as_mut_ptr(), out_cap)
+            }
+        };
+        // One of the two hardest 3x3 boards, 31 moves from the goal
+        let hardest = Board::try_new(3, [8, 6, 7, 2, 5, 4, 3, -1, 1].iter().map(|&symbol| Tile::new(symbol)).collect())
+            .unwrap();
+        assert_eq!(solve(&hardest, 0, 0, 0), 31);
+        assert_eq!(solve(&hardest, 1_000_000, 10_000, 0), 31);
+        assert_eq!(solve(&hardest, 10, 0, 0), SPS_ERR_NODE_BUDGET);
+        assert_eq!(last_error(), "gave up after expanding 10 boards");
+        assert_eq!(solve(&hardest, 0, 0, 20_000), SPS_ERR_MEMORY_BUDGET);
+        assert!(last_error().starts_with("search is out of memory"));
+        // Far beyond a millisecond of IDA*, which `for_size` picks for 4x4 boards
+        let korf = &crate::benchmarks::korf_instances()[0];
+        assert_eq!(solve(korf, 0, 1, 0), SPS_ERR_TIME_BUDGET);
+        assert_eq!(solve(korf, 1_000_000_000, 1, 0), SPS_ERR_TIME_BUDGET);
+    }
+
+    /// Test the C header declares every export and compiles with the example program
+    #[test]
+    fn test_sps_header() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("ffi");
+        let header = include_str!("../../ffi/sliding_tile_puzzle_solver.h");
+        // Declarations may be wrapped over several lines
+        let declarations = header.split_whitespace().collect::<Vec<&str>>().join(" ");
+        for declaration in &[
+            "int32_t sps_solve(int32_t n, const int32_t *tiles, size_t len, uint8_t *out_moves, size_t out_cap);",
+            "int32_t sps_solve_with_limits(int32_t n, const int32_t *tiles, size_t len, uint64_t max_nodes, \
+             uint64_t max_time_ms, uint64_t max_memory_bytes, uint8_t *out_moves, size_t out_cap);",
+            "int32_t sps_is_solvable(int32_t n, const int32_t *tiles, size_t len);",
+            "int32_t sps_estimate(int32_t n, const int32_t *tiles, size_t len);",
+            "const char *sps_last_error_message(void);",
+        ] {
+            assert!(declarations.contains(declaration), "missing {}", declaration);
+        }
+        for (name, code) in &[
+            ("SPS_ERR_INVALID_BOARD", SPS_ERR_INVALID_BOARD),
+            ("SPS_ERR_UNREACHABLE", SPS_ERR_UNREACHABLE),
+            ("SPS_ERR_NULL_POINTER", SPS_ERR_NULL_POINTER),
+            ("SPS_ERR_PANIC", SPS_ERR_PANIC),
+            ("SPS_ERR_NODE_BUDGET", SPS_ERR_NODE_BUDGET),
+            ("SPS_ERR_TIME_BUDGET", SPS_ERR_TIME_BUDGET),
+            ("SPS_ERR_MEMORY_BUDGET", SPS_ERR_MEMORY_BUDGET),
+            ("SPS_ERR_SOLVER", SPS_ERR_SOLVER),
+        ] {
+            assert!(header.contains(&format!("#define {} ({})", name, code)), "wrong {}", name);
+        }
+        let output = match Command::new("cc")
+            .args(["-fsyntax-only", "-Wall", "-Werror", "-std=c99", "-I"])
+            .arg(&dir)
+            .arg(dir.join("example.c"))
+            .output() {
+            Ok(output) => output,
+            // No C compiler to check with
+            Err(_) => return,
+        };
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+}
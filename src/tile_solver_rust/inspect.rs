@@ -0,0 +1,274 @@
+//! Diagnostic dump of a pasted board, e.g. for triaging reports of a board the solver
+//! hangs on.
+//!
+//! `inspect` gathers everything the library can say about board info in one place: the
+//! parsed grid, whether it is valid and solvable, its heuristics and encodings and,
+//! optionally, a solve with its statistics and verification. An `Inspection` prints as
+//! aligned text or formats as a JSON object whose fields are stable:
+//! * `board_info`, `grid`, `valid`, `validation_error`, `solvability`, `heuristics`,
+//!   `packed_key`, `url_token`, `lehmer_rank`, `lower_bound` and `solve`, with nulls
+//!   for what doesn't apply, e.g. the packed key of a 5x5 board.
+
+use std::fmt;
+
+use super::board::{Board, BoardError, HeuristicBreakdown, Notation, SolvabilityReport};
+use super::error_code::ErrorCode;
+use super::pack;
+use super::server::json_string;
+use super::solver::{self, Solution, SolveError, SolveOptions};
+
+/// Outcome of the optional solve of an inspected board.
+///
+/// # Attributes
+/// * `result` - The solution, or why the solve failed; unsolvable boards fail with
+///   `SolveError::Unreachable` without searching
+/// * `verified` - Whether replaying the solution reaches the goal
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectedSolve {
+    pub result: Result<Solution, SolveError>,
+    pub verified: bool,
+}
+
+/// Everything known about a board, see `inspect`.
+///
+/// # Attributes
+/// * `board_info` - Board info as given
+/// * `board` - The parsed board, if the size and tile count could be read
+/// * `validation` - Why the board is invalid, if it is
+/// * `solvability` - Whether the board can reach the standard goal, if it was parsed
+/// * `heuristics` - Cost components against the standard goal, if the board is valid
+/// * `packed_key` - Packed tiles, if the board is valid and small enough to pack
+/// * `lehmer_rank` - Permutation rank, if the board is valid and small enough to rank
+/// * `solve` - The solve, if one was asked for and the board is valid
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inspection {
+    pub board_info: String,
+    pub board: Option<Board>,
+    pub validation: Option<BoardError>,
+    pub solvability: Option<SolvabilityReport>,
+    pub heuristics: Option<HeuristicBreakdown>,
+    pub packed_key: Option<u64>,
+    pub lehmer_rank: Option<u64>,
+    pub solve: Option<InspectedSolve>,
+}
+
+impl Inspection {
+    /// Get the heuristic lower bound on the solution length.
+    ///
+    /// # Returns
+    /// Manhattan distance plus linear conflicts, if the board is valid
+    pub fn lower_bound(&self) -> Option<i32> {
+        self.heuristics.map(|heuristics| heuristics.total)
+    }
+
+    /// Format the inspection as a JSON object, see the module docs for the fields.
+    ///
+    /// # Returns
+    /// JSON object string
+    pub fn to_json(&self) -> String {
+        fn optional<T, F: Fn(&T) -> String>(value: &Option<T>, format: F) -> String {
+            value.as_ref().map_or("null".to_string(), format)
+        }
+        let grid = optional(&self.board, |board| {
+            let rows: Vec<String> = (0..board.size() as usize)
+                .map(|row| {
+                    let tiles: Vec<String> = board.row(row).iter().map(|tile| tile.symbol().to_string()).collect();
+                    format!("[{}]", tiles.join(","))
+                })
+                .collect();
+            format!("[{}]", rows.join(","))
+        });
+        let heuristics = optional(&self.heuristics, |heuristics| {
+            format!("{{\"manhattan\":{},\"linear_conflicts\":{}}}", heuristics.manhattan, heuristics.linear_conflicts)
+        });
+        let solve = optional(&self.solve, |solve| match &solve.result {
+            Ok(solution) => format!(
+                "{{\"path\":\"{}\",\"length\":{},\"optimal\":{},\"stats\":{},\"verified\":{}}}",
+                solution.moves,
+                solution.moves.len(),
+                solution.optimal,
+                solution.stats.to_json(),
+                solve.verified
+            ),
+            Err(e) => format!("{{\"error\":{},\"code\":{}}}", json_string(&e.to_string()), e.code()),
+        });
+        format!(
+            concat!(
+                "{{\"board_info\":{},\"grid\":{},\"valid\":{},\"validation_error\":{},\"solvability\":{},",
+                "\"heuristics\":{},\"packed_key\":{},\"url_token\":{},\"lehmer_rank\":{},\"lower_bound\":{},",
+                "\"solve\":{}}}"
+            ),
+            json_string(&self.board_info),
+            grid,
+            self.validation.is_none(),
+            optional(&self.validation, |e| json_string(&e.to_string())),
+            optional(&self.solvability, SolvabilityReport::to_json),
+            heuristics,
+            optional(&self.packed_key, |key| format!("\"{:#x}\"", key)),
+            optional(&self.packed_key, |&key| format!("\"{}\"", pack::encode_base64url(key))),
+            optional(&self.lehmer_rank, u64::to_string),
+            optional(&self.lower_bound(), i32::to_string),
+            solve
+        )
+    }
+}
+
+impl fmt::Display for Inspection {
+    /// Write the inspection as labelled lines, the grid first.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "board info:   {}", self.board_info)?;
+        if let Some(board) = &self.board {
+            for line in board.to_string().lines() {
+                writeln!(f, "    {}", line)?;
+            }
+        }
+        match &self.validation {
+            Some(e) => writeln!(f, "valid:        no, {}", e)?,
+            None => writeln!(f, "valid:        yes")?,
+        }
+        if let Some(report) = &self.solvability {
+            let problems: Vec<String> = report.problems.iter().map(|problem| problem.to_string()).collect();
+            if report.is_solvable() {
+                writeln!(f, "solvable:     yes")?;
+            } else {
+                writeln!(f, "solvable:     no, {}", problems.join("; "))?;
+            }
+        }
+        if let Some(heuristics) = &self.heuristics {
+            writeln!(f, "manhattan:    {}", heuristics.manhattan)?;
+            writeln!(f, "conflicts:    {}", heuristics.linear_conflicts)?;
+            writeln!(f, "lower bound:  {}", heuristics.total)?;
+        }
+        if let Some(key) = self.packed_key {
+            writeln!(f, "packed key:   {:#x} ({})", key, pack::encode_base64url(key))?;
+        }
+        if let Some(rank) = self.lehmer_rank {
+            writeln!(f, "lehmer rank:  {}", rank)?;
+        }
+        if let Some(solve) = &self.solve {
+            match &solve.result {
+                Ok(solution) => {
+                    let optimal = if solution.optimal { "optimal" } else { "not proven optimal" };
+                    writeln!(f, "solution:     {} ({} moves, {})", solution.moves, solution.moves.len(), optimal)?;
+                    writeln!(
+                        f, "stats:        {} expanded, {} generated, {:.1} ms",
+                        solution.stats.nodes_expanded, solution.stats.nodes_generated, solution.stats.millis
+                    )?;
+                    writeln!(f, "verified:     {}", if solve.verified { "yes" } else { "no" })?;
+                }
+                Err(e) => writeln!(f, "solution:     failed, {}", e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Inspect board info, e.g. pasted from a bug report.
+/// * Boards with invalid tiles are still parsed when their size and tile count can be
+///   read, so the grid and solvability problems can be shown.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `solve` - Options to solve the board with, or `None` to skip solving
+///
+/// # Returns
+/// The inspection
+pub fn inspect(board_info: &str, solve: Option<&SolveOptions>) -> Inspection {
+    let mut inspection = Inspection {
+        board_info: board_info.to_string(),
+        board: None,
+        validation: None,
+        solvability: None,
+        heuristics: None,
+        packed_key: None,
+        lehmer_rank: None,
+        solve: None,
+    };
+    let board = match super::_parse_unvalidated_board_info(board_info) {
+        Ok(board) => board,
+        Err(e) => {
+            inspection.validation = Some(e);
+            return inspection;
+        }
+    };
+    let solvability = board.solvability(&Board::new(board.size(), None, -1, None));
+    inspection.board = Some(board.clone());
+    inspection.validation = Board::validate(board.size(), board.tiles()).err();
+    if inspection.validation.is_none() {
+        inspection.heuristics = Some(board.heuristic_breakdown(None));
+        inspection.packed_key = pack::encode(&board).ok();
+        if board.size() <= pack::MAX_PACKED_SIZE {
+            inspection.lehmer_rank = Some(board.lehmer_rank());
+        }
+        inspection.solve = solve.map(|options| {
+            // Unsolvable boards would make A* exhaust half the state space
+            let result = if solvability.is_solvable() {
+                solver::solve(&board, options)
+            } else {
+                Err(SolveError::Unreachable)
+            };
+            let verified = result.as_ref()
+                .is_ok_and(|solution| solver::verify_solution(&board, &solution.moves, Notation::BlankMoves));
+            InspectedSolve { result, verified }
+        });
+    }
+    inspection.solvability = Some(solvability);
+    inspection
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{Json, JsonParser};
+
+    /// Test the JSON of a fixture board against its golden field set and values
+    #[test]
+    fn test_inspect_golden() {
+        let inspection = inspect("3,1,2,3,-1,4,6,7,5,8", Some(&SolveOptions::default()));
+        let json = inspection.to_json();
+        // Only the solve time varies between runs
+        let (head, millis) = json.split_once(",\"millis\":").unwrap();
+        assert_eq!(head, concat!(
+            "{\"board_info\":\"3,1,2,3,-1,4,6,7,5,8\",\"grid\":[[1,2,3],[-1,4,6],[7,5,8]],\"valid\":true,",
+            "\"validation_error\":null,\"solvability\":{\"solvable\":true,\"problems\":[],\"fix\":null},",
+            "\"heuristics\":{\"manhattan\":3,\"linear_conflicts\":0},\"packed_key\":\"0x74653f210\",",
+            "\"url_token\":\"AAAAB0ZT8hA\",\"lehmer_rank\":608,\"lower_bound\":3,",
+            "\"solve\":{\"path\":\"RDR\",\"length\":3,\"optimal\":true,\"stats\":{\"nodes_expanded\":3,",
+            "\"nodes_generated\":8,\"max_heuristic\":3,\"expansions_by_depth\":[1,1,1],",
+            "\"effective_branching_factor\":0.8105357137661366,\"conflict_cache_hits\":20,",
+            "\"conflict_cache_misses\":28,\"tt_hits\":0,\"tt_cutoffs\":0,\"stale_pops\":0,\"visited_key_bytes\":588",
+        ));
+        assert!(millis.ends_with("},\"verified\":true}}"));
+        let fields: Vec<String> = match JsonParser::parse(&json).unwrap() {
+            Json::Object(fields) => fields.into_iter().map(|(key, _)| key).collect(),
+            _ => panic!("{}", json),
+        };
+        assert_eq!(fields, vec![
+            "board_info", "grid", "valid", "validation_error", "solvability", "heuristics", "packed_key",
+            "url_token", "lehmer_rank", "lower_bound", "solve",
+        ]);
+        let text = inspection.to_string();
+        assert!(text.contains("\n    _ 4 6\n"), "{}", text);
+        assert!(text.contains("solution:     RDR (3 moves, optimal)\n"), "{}", text);
+        assert!(text.contains("verified:     yes\n"), "{}", text);
+    }
+
+    /// Test invalid, unsolvable and unparsable boards report what can be said about them
+    #[test]
+    fn test_inspect_problem_boards() {
+        let duplicate = inspect("3,1,1,3,-1,4,6,7,5,8", Some(&SolveOptions::default()));
+        assert_eq!(duplicate.validation, Some(BoardError::DuplicateTile(1)));
+        assert!(!duplicate.solvability.as_ref().unwrap().is_solvable());
+        assert!(duplicate.board.is_some() && duplicate.solve.is_none() && duplicate.lower_bound().is_none());
+        let unsolvable = inspect("3,2,1,3,4,5,6,7,8,-1", Some(&SolveOptions::default()));
+        assert_eq!(unsolvable.solve.as_ref().unwrap().result, Err(SolveError::Unreachable));
+        let error = "\"solve\":{\"error\":\"goal is unreachable from this board\",\"code\":201}}";
+        assert!(unsolvable.to_json().ends_with(error));
+        assert!(unsolvable.to_string().contains("solvable:     no, the tiles are an odd permutation"));
+        let large = inspect(&crate::format_board_info(&Board::new(5, None, 0, None)), None);
+        assert_eq!((large.packed_key, large.lehmer_rank, large.lower_bound()), (None, None, Some(0)));
+        let unparsable = inspect("x", None);
+        assert!(unparsable.board.is_none());
+        assert!(unparsable.to_json().contains("\"grid\":null,\"valid\":false,"));
+    }
+}
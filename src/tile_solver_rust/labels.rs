@@ -0,0 +1,251 @@
+//! Tile labels, e.g. for word puzzles where tiles show letters and the goal spells a
+//! phrase.
+//!
+//! Labels only change how a board is shown; the solver still works on the numeric
+//! symbols. In board info, labels follow the tiles after `;labels=`, label i for tile
+//! i + 1, e.g. "3,1,2,3,4,5,6,7,8,-1;labels=S,L,I,D,E,W,A,Y". Labels can't be empty, be
+//! the blank marker `_`, or hold commas, semicolons or control characters.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::board::{Board, BoardError, BoardFormatter};
+use super::format_board_info;
+use super::tile::{Tile, BLANK_TILE};
+
+/// Separates the tiles of board info from its labels
+pub const LABELS_PREFIX: &str = ";labels=";
+
+/// Marker for the blank in a goal layout of labels
+pub const BLANK_LABEL: &str = "_";
+
+/// A board whose tiles are shown with labels instead of their symbols.
+///
+/// # Attributes
+/// * `board` - The board
+/// * `labels` - Label i for tile i + 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabeledBoard {
+    board: Board,
+    labels: Vec<String>,
+}
+
+impl LabeledBoard {
+    /// Label the tiles of a board.
+    /// * Labels may repeat, e.g. the two Es of "SLIDE EASY", but then goals can't be
+    ///   built from them, see `goal_from_labels`.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    /// * `labels` - Label i for tile i + 1
+    ///
+    /// # Returns
+    /// The labeled board, or the problem with the labels
+    pub fn new(board: Board, labels: Vec<String>) -> Result<LabeledBoard, BoardError> {
+        let expected = board.tiles().len() - 1;
+        if labels.len() != expected {
+            return Err(BoardError::WrongLabelCount { expected, found: labels.len() });
+        }
+        if let Some(label) = labels.iter().find(|label| !LabeledBoard::is_valid_label(label)) {
+            return Err(BoardError::InvalidLabel(label.clone()));
+        }
+        Ok(LabeledBoard { board, labels })
+    }
+
+    /// Check if a label can be used.
+    ///
+    /// # Parameters
+    /// * `label` - The label
+    ///
+    /// # Returns
+    /// Whether the label is non-empty, isn't the blank marker and can be written in
+    /// board info
+    pub fn is_valid_label(label: &str) -> bool {
+        !label.is_empty() && label != BLANK_LABEL && !label.chars().any(|c| c == ',' || c == ';' || c.is_control())
+    }
+
+    /// The labeled board.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The labels, label i for tile i + 1.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Get the label of a tile.
+    ///
+    /// # Parameters
+    /// * `tile` - Tile of the board
+    ///
+    /// # Returns
+    /// The label, or `None` for the blank
+    pub fn label(&self, tile: &Tile) -> Option<&str> {
+        if tile.is_blank() {
+            return None;
+        }
+        self.labels.get((tile.symbol() - 1) as usize).map(String::as_str)
+    }
+
+    /// Label another board of the same size the same way, e.g. after making moves.
+    ///
+    /// # Parameters
+    /// * `board` - Board of the same size
+    ///
+    /// # Returns
+    /// The labeled board, or `BoardError::WrongLabelCount` if the size differs
+    pub fn with_board(&self, board: Board) -> Result<LabeledBoard, BoardError> {
+        LabeledBoard::new(board, self.labels.clone())
+    }
+
+    /// Build a goal board from its layout of labels.
+    ///
+    /// # Parameters
+    /// * `layout` - Label of each position in row-major order, with `BLANK_LABEL` for
+    ///   the blank
+    ///
+    /// # Returns
+    /// The goal board, or the problem with the layout: `DuplicateLabel` if a label names
+    /// several tiles (so the goal is ambiguous) or appears twice in the layout,
+    /// `UnknownLabel` if no tile has it and `WrongTileCount` if the layout is the wrong
+    /// size
+    pub fn goal_from_labels(&self, layout: &[&str]) -> Result<Board, BoardError> {
+        let mut symbols: HashMap<&str, i32> = HashMap::new();
+        for (i, label) in self.labels.iter().enumerate() {
+            if symbols.insert(label.as_str(), i as i32 + 1).is_some() {
+                return Err(BoardError::DuplicateLabel(label.clone()));
+            }
+        }
+        symbols.insert(BLANK_LABEL, BLANK_TILE);
+        let expected = self.board.tiles().len();
+        if layout.len() != expected {
+            return Err(BoardError::WrongTileCount { expected, found: layout.len() });
+        }
+        let mut tiles = Vec::with_capacity(layout.len());
+        for &label in layout {
+            let label = label.trim();
+            let symbol = *symbols.get(label).ok_or_else(|| BoardError::UnknownLabel(label.to_string()))?;
+            if tiles.iter().any(|tile: &Tile| tile.symbol() == symbol) {
+                return Err(BoardError::DuplicateLabel(label.to_string()));
+            }
+            tiles.push(Tile::new(symbol));
+        }
+        Board::try_new(self.board.size(), tiles)
+    }
+
+    /// Format the board as an HTML table of labels, see `Board::to_html_labeled`.
+    ///
+    /// # Returns
+    /// HTML table, one line per row
+    pub fn to_html(&self) -> String {
+        self.board.to_html_labeled(&self.labels)
+    }
+
+    /// Format the board and its labels as a JSON object.
+    ///
+    /// # Returns
+    /// JSON object string with the `board` info and its `labels` array
+    pub fn to_json(&self) -> String {
+        let labels: Vec<String> = self.labels.iter()
+            .map(|label| format!("\"{}\"", label.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect();
+        format!("{{\"board\":\"{}\",\"labels\":[{}]}}", format_board_info(&self.board), labels.join(","))
+    }
+
+    /// Read a board and its labels from the JSON of `to_json`.
+    ///
+    /// # Parameters
+    /// * `text` - JSON object string
+    ///
+    /// # Returns
+    /// The labeled board, or the problem with the board or labels
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_json(text: &str) -> Result<LabeledBoard, BoardError> {
+        use super::server::{Json, JsonParser};
+
+        let unparsable = || BoardError::Unparsable(text.to_string());
+        let json = JsonParser::parse(text).map_err(|_| unparsable())?;
+        let board = match json.get("board") {
+            Some(Json::String(board_info)) => super::try_parse_board_info(board_info)?,
+            _ => return Err(unparsable()),
+        };
+        let labels = match json.get("labels") {
+            Some(Json::Array(labels)) => labels.iter()
+                .map(|label| match label {
+                    Json::String(label) => Ok(label.clone()),
+                    _ => Err(unparsable()),
+                })
+                .collect::<Result<Vec<String>, BoardError>>()?,
+            _ => return Err(unparsable()),
+        };
+        LabeledBoard::new(board, labels)
+    }
+}
+
+impl fmt::Display for LabeledBoard {
+    /// Write the board as a grid of labels with the default `BoardFormatter`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", BoardFormatter::new().labels(&self.labels).format(&self.board))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{self, SolveOptions};
+    use crate::{format_labeled_board_info, try_parse_labeled_board_info};
+
+    /// Labels of the word puzzle, spelling "SLIDEWAY" when solved
+    fn _word_labels() -> Vec<String> {
+        "SLIDEWAY".chars().map(|c| c.to_string()).collect()
+    }
+
+    /// Test a word puzzle round-trips its labels and spells its phrase once solved
+    #[test]
+    fn test_word_puzzle() {
+        let board = Board::generate(3, 411, 30).unwrap();
+        let labeled = LabeledBoard::new(board.clone(), _word_labels()).unwrap();
+        assert_eq!(LabeledBoard::from_json(&labeled.to_json()), Ok(labeled.clone()));
+        let board_info = format_labeled_board_info(&labeled);
+        assert!(board_info.ends_with(";labels=S,L,I,D,E,W,A,Y"));
+        assert_eq!(try_parse_labeled_board_info(&board_info), Ok(labeled.clone()));
+        // The solver works on the symbols underneath
+        let solution = solver::solve(labeled.board(), &SolveOptions::default()).unwrap();
+        let solved = labeled.with_board(board.apply_moves(&solution.moves, crate::board::Notation::BlankMoves).unwrap())
+            .unwrap();
+        assert_eq!(solved.to_string(), "S L I\nD E W\nA Y _\n");
+        let spelled: String = solved.board().tiles().iter().filter_map(|tile| solved.label(tile)).collect();
+        assert_eq!(spelled, "SLIDEWAY");
+        assert!(solved.to_html().starts_with("<table class=\"board\">\n<tr><td data-tile=\"1\">S</td>"));
+    }
+
+    /// Test goals built from labels, and the labels that are rejected
+    #[test]
+    fn test_goal_from_labels() {
+        let labeled = LabeledBoard::new(Board::generate(3, 411, 30).unwrap(), _word_labels()).unwrap();
+        let goal = labeled.goal_from_labels(&["_", "W", "A", "Y", "S", "L", "I", "D", "E"]).unwrap();
+        let symbols: Vec<i32> = goal.tiles().iter().map(|tile| tile.symbol()).collect();
+        assert_eq!(symbols, vec![-1, 6, 7, 8, 1, 2, 3, 4, 5]);
+        assert_eq!(
+            labeled.goal_from_labels(&["_", "W", "A", "Y", "S", "L", "I", "D", "D"]),
+            Err(BoardError::DuplicateLabel("D".to_string()))
+        );
+        assert_eq!(
+            labeled.goal_from_labels(&["_", "W", "A", "Y", "S", "L", "I", "D", "Q"]),
+            Err(BoardError::UnknownLabel("Q".to_string()))
+        );
+        let mut repeated = _word_labels();
+        repeated[7] = "S".to_string();
+        let ambiguous = LabeledBoard::new(labeled.board().clone(), repeated).unwrap();
+        let error = ambiguous.goal_from_labels(&["S", "L", "I", "D", "E", "W", "A", "S", "_"]).unwrap_err();
+        assert_eq!(error, BoardError::DuplicateLabel("S".to_string()));
+        assert!(error.to_string().contains("\"S\" is used more than once"));
+        let board = labeled.board().clone();
+        let too_few = LabeledBoard::new(board.clone(), vec!["A".to_string()]);
+        assert_eq!(too_few, Err(BoardError::WrongLabelCount { expected: 8, found: 1 }));
+        let mut invalid = _word_labels();
+        invalid[0] = "a,b".to_string();
+        assert_eq!(LabeledBoard::new(board, invalid), Err(BoardError::InvalidLabel("a,b".to_string())));
+    }
+}
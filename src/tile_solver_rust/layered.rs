@@ -0,0 +1,410 @@
+//! Layered solving for boards too large to search whole.
+//!
+//! The top row and then the left column are placed tile by tile, peeling a layer off
+//! the board, until 3x3 remains, which is solved optimally with A*. Each tile, or the
+//! last two tiles of a row or column together, is moved home by a breadth-first search
+//! over the positions of those tiles and the blank, never touching placed tiles. The
+//! solutions are far from optimal, but every search is small, so even 16x16 boards
+//! solve quickly, and moves are final as soon as a row or column is placed.
+
+use std::collections::VecDeque;
+
+use super::board::{opposite_direction, Board, Heuristic, Notation, DOWN, LEFT, MOVES, RIGHT, UP};
+use super::clock::TimeBudget;
+use super::solver::{self, Algorithm, FailurePoint, PartialResult, SolveError, SolveOptions, SolveStats};
+
+/// Size of the core left to solve with A*
+const CORE_SIZE: usize = 3;
+
+/// Marks a state not yet reached in a placement search
+const UNVISITED: u8 = u8::MAX;
+
+/// Marks the starting state of a placement search
+const START: u8 = u8::MAX - 1;
+
+/// Part of the board placed by the next chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    /// Top row of the remaining board
+    Row,
+    /// Left column of the remaining board, below the row
+    Column,
+    /// The 3x3 (or smaller) core, solved with A*
+    Core,
+    /// The board is solved
+    Done,
+}
+
+/// Solver producing a solution one row or column at a time.
+/// * Pull chunks with `next_chunk`; their concatenation is the whole solution.
+///
+/// # Attributes
+/// * `board` - Board after the moves produced so far
+/// * `moves` - Moves produced so far
+/// * `layer` - Rows and columns already placed
+/// * `stage` - What the next chunk places
+/// * `heuristic` - Heuristic of the core's A* search
+/// * `max_nodes` - Give up after expanding this many states in total
+/// * `lower_bound` - Heuristic of the starting board, a bound on the optimal length
+/// * `stats` - Statistics collected so far
+/// * `time_budget` - Deadline of the row and column placements
+/// * `failure` - Failure to inject, with the `chaos` feature
+#[derive(Debug, Clone)]
+pub struct LayeredSolver {
+    board: Board,
+    moves: String,
+    layer: usize,
+    stage: Stage,
+    heuristic: Heuristic,
+    max_nodes: u64,
+    lower_bound: i32,
+    stats: SolveStats,
+    time_budget: TimeBudget,
+    failure: FailurePoint,
+}
+
+impl LayeredSolver {
+    /// Create a layered solver for a board.
+    /// * Uses the heuristic, node limit and time budget of the options.
+    ///
+    /// # Parameters
+    /// * `board` - The board to solve
+    /// * `options` - Options controlling the solve
+    ///
+    /// # Returns
+    /// The solver, or `SolveError::Unreachable` if the board can't be solved
+    pub fn new(board: &Board, options: &SolveOptions) -> Result<LayeredSolver, SolveError> {
+        if !board.is_solvable() {
+            return Err(SolveError::Unreachable);
+        }
+        let breakdown = board.heuristic_breakdown(None);
+        let n = board.size() as usize;
+        let mut board = board.clone();
+        board.path.clear();
+        Ok(LayeredSolver {
+            board,
+            moves: String::new(),
+            layer: 0,
+            stage: if n <= CORE_SIZE { Stage::Core } else { Stage::Row },
+            heuristic: options.heuristic,
+            max_nodes: options.max_nodes.unwrap_or(u64::MAX),
+            lower_bound: breakdown.manhattan + breakdown.linear_conflicts,
+            stats: SolveStats::default(),
+            time_budget: TimeBudget::new(options.max_time_ms),
+            failure: FailurePoint::new(options),
+        })
+    }
+
+    /// Get the statistics collected so far.
+    /// * Nodes count the states of every placement search plus the core's A* search.
+    pub fn stats(&self) -> &SolveStats {
+        &self.stats
+    }
+
+    /// Check if the solution is optimal once complete.
+    ///
+    /// # Returns
+    /// Whether the whole board was solved by the core's A* search
+    pub fn is_optimal(&self) -> bool {
+        self.board.size() as usize <= CORE_SIZE
+    }
+
+    /// Produce the moves placing the next row or column, or solving the core.
+    /// * Chunks are never empty and always hold whole moves; rows and columns already
+    ///   in place are skipped.
+    ///
+    /// # Returns
+    /// The next chunk of blank moves, `None` once the solution is complete, or
+    /// `SolveError::BudgetExceeded` if the node limit is reached
+    pub fn next_chunk(&mut self) -> Result<Option<String>, SolveError> {
+        loop {
+            let chunk = match self.stage {
+                Stage::Row => self.place_row()?,
+                Stage::Column => self.place_column()?,
+                Stage::Core => self.solve_core()?,
+                Stage::Done => return Ok(None),
+            };
+            if !chunk.is_empty() {
+                self.board = self.board.apply_moves(&chunk, Notation::BlankMoves).unwrap();
+                self.moves.push_str(&chunk);
+                return Ok(Some(chunk));
+            }
+        }
+    }
+
+    /// Place the top row of the remaining board.
+    ///
+    /// # Returns
+    /// Moves placing the row
+    fn place_row(&mut self) -> Result<String, SolveError> {
+        let (n, k) = (self.board.size() as usize, self.layer);
+        let cells: Vec<usize> = (k..n).map(|col| k * n + col).collect();
+        self.stage = Stage::Column;
+        self.place_line(&cells, k, k)
+    }
+
+    /// Place the left column of the remaining board, below its top row.
+    ///
+    /// # Returns
+    /// Moves placing the column
+    fn place_column(&mut self) -> Result<String, SolveError> {
+        let (n, k) = (self.board.size() as usize, self.layer);
+        let cells: Vec<usize> = (k + 1..n).map(|row| row * n + k).collect();
+        self.layer += 1;
+        self.stage = if n - self.layer <= CORE_SIZE { Stage::Core } else { Stage::Row };
+        self.place_line(&cells, k + 1, k)
+    }
+
+    /// Place the goal tiles of a row or column one at a time, then the last two together.
+    /// * The last two can't be placed one after the other without disturbing the first.
+    ///
+    /// # Parameters
+    /// * `cells` - Indices of the cells of the line, ending at the board's edge
+    /// * `rows` - Number of top rows already placed
+    /// * `cols` - Number of left columns already placed
+    ///
+    /// # Returns
+    /// Moves placing the line
+    fn place_line(&mut self, cells: &[usize], rows: usize, cols: usize) -> Result<String, SolveError> {
+        let n = self.board.size() as usize;
+        let mut locked: Vec<bool> = (0..n * n).map(|i| i / n < rows || i % n < cols).collect();
+        let mut board = self.board.clone();
+        let mut moves = String::new();
+        let (singles, pair) = cells.split_at(cells.len() - 2);
+        for &cell in singles {
+            let placed = self.place(&board, &locked, &[cell])?;
+            board = board.apply_moves(&placed, Notation::BlankMoves).unwrap();
+            moves.push_str(&placed);
+            locked[cell] = true;
+        }
+        moves.push_str(&self.place(&board, &locked, pair)?);
+        Ok(moves)
+    }
+
+    /// Solve the core of the board optimally with A*.
+    ///
+    /// # Returns
+    /// Moves solving the core
+    fn solve_core(&mut self) -> Result<String, SolveError> {
+        let n = self.board.size() as usize;
+        let k = n - CORE_SIZE.min(n);
+        let core = self.board.sub_board(k, k, n - k, n - k).unwrap();
+        let options = SolveOptions::new()
+            .algorithm(Algorithm::AStar)
+            .heuristic(self.heuristic)
+            .max_nodes(self.failure.node_limit(self.max_nodes).saturating_sub(self.stats.nodes_expanded).max(1));
+        let solution = match solver::solve(&core, &options) {
+            Ok(solution) => solution,
+            Err(SolveError::BudgetExceeded(partial)) => {
+                self.stats.nodes_expanded += partial.stats.nodes_expanded;
+                self.stats.nodes_generated += partial.stats.nodes_generated;
+                return Err(self.failure.error(self.stats.nodes_expanded, self.max_nodes, self.budget_exceeded()));
+            }
+            Err(e) => return Err(e),
+        };
+        self.stats.nodes_expanded += solution.stats.nodes_expanded;
+        self.stats.nodes_generated += solution.stats.nodes_generated;
+        self.stage = Stage::Done;
+        Ok(core.embed_path(&solution.moves, (k, k)))
+    }
+
+    /// Move goal tiles to their cells by the shortest sequence of moves that keeps the
+    /// blank off locked cells.
+    /// * Breadth-first search over the positions of the tiles and the blank, so one or
+    ///   two tiles at a time keeps it small.
+    ///
+    /// # Parameters
+    /// * `board` - Current board
+    /// * `locked` - Cells the blank must not enter
+    /// * `targets` - Cells to fill with their goal tiles
+    ///
+    /// # Returns
+    /// The moves, or `SolveError::BudgetExceeded` if the node limit is reached
+    fn place(&mut self, board: &Board, locked: &[bool], targets: &[usize]) -> Result<String, SolveError> {
+        let n = board.size() as usize;
+        let cells = n * n;
+        let encode = |blank: usize, tiles: &[usize]| {
+            tiles.iter().rev().fold(0, |index, &tile| index * cells + tile) * cells + blank
+        };
+        let decode = |mut index: usize| {
+            let blank = index % cells;
+            index /= cells;
+            let tiles: Vec<usize> = (0..targets.len()).map(|_| {
+                let tile = index % cells;
+                index /= cells;
+                tile
+            }).collect();
+            (blank, tiles)
+        };
+        let start: Vec<usize> = targets.iter()
+            .map(|&target| board.tiles().iter().position(|tile| tile.symbol() == target as i32 + 1).unwrap())
+            .collect();
+        let mut parents = vec![UNVISITED; cells.pow(targets.len() as u32 + 1)];
+        let root = encode(board.get_blank_index() as usize, &start);
+        parents[root] = START;
+        let mut queue = VecDeque::from(vec![root]);
+        while let Some(state) = queue.pop_front() {
+            let (blank, tiles) = decode(state);
+            if tiles.as_slice() == targets {
+                return Ok(_path_to(state, &parents, n, &decode, &encode));
+            }
+            if self.stats.nodes_expanded >= self.failure.node_limit(self.max_nodes) {
+                return Err(self.failure.error(self.stats.nodes_expanded, self.max_nodes, self.budget_exceeded()));
+            }
+            if self.time_budget.expired() {
+                return Err(self.budget_exceeded());
+            }
+            self.stats.nodes_expanded += 1;
+            for (direction, &tile_move) in MOVES.iter().enumerate() {
+                let next_blank = match _step(blank, tile_move, n) {
+                    Some(next_blank) if !locked[next_blank] => next_blank,
+                    _ => continue,
+                };
+                let next_tiles: Vec<usize> = tiles.iter()
+                    .map(|&tile| if tile == next_blank { blank } else { tile })
+                    .collect();
+                let next = encode(next_blank, &next_tiles);
+                if parents[next] == UNVISITED {
+                    parents[next] = direction as u8;
+                    self.stats.nodes_generated += 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        // Unreachable for solvable boards while at least 2 rows and columns are free
+        Err(SolveError::Unreachable)
+    }
+
+    /// Report the moves made before reaching the node limit.
+    ///
+    /// # Returns
+    /// `SolveError::BudgetExceeded` with the board after the chunks produced so far
+    fn budget_exceeded(&self) -> SolveError {
+        let mut best = self.board.clone();
+        best.path = self.moves.clone();
+        let partial = PartialResult::new(&best, self.lower_bound, self.stats.clone());
+        SolveError::BudgetExceeded(Box::new(partial))
+    }
+}
+
+/// Move the blank one cell, if it stays on the board.
+///
+/// # Parameters
+/// * `blank` - Index of the blank
+/// * `tile_move` - Direction to move the blank
+/// * `n` - Size of the board
+///
+/// # Returns
+/// Index of the blank after the move, if it is on the board
+fn _step(blank: usize, tile_move: char, n: usize) -> Option<usize> {
+    let (row, col) = (blank / n, blank % n);
+    match tile_move {
+        UP if row > 0 => Some(blank - n),
+        DOWN if row + 1 < n => Some(blank + n),
+        LEFT if col > 0 => Some(blank - 1),
+        RIGHT if col + 1 < n => Some(blank + 1),
+        _ => None,
+    }
+}
+
+/// Walk the parents of a placement search back to its start.
+///
+/// # Parameters
+/// * `state` - Final state
+/// * `parents` - Index into `MOVES` of the move reaching each state
+/// * `n` - Size of the board
+/// * `decode` - Split a state into the blank and tile positions
+/// * `encode` - Join the blank and tile positions into a state
+///
+/// # Returns
+/// Moves from the start to the state
+fn _path_to<D, E>(mut state: usize, parents: &[u8], n: usize, decode: &D, encode: &E) -> String
+    where D: Fn(usize) -> (usize, Vec<usize>), E: Fn(usize, &[usize]) -> usize {
+    let mut moves = Vec::new();
+    while parents[state] != START {
+        let tile_move = MOVES[parents[state] as usize];
+        moves.push(tile_move);
+        let (blank, tiles) = decode(state);
+        // The tile now where the blank came from was moved out of the blank's cell
+        let previous_blank = _step(blank, opposite_direction(tile_move), n).unwrap();
+        let previous_tiles: Vec<usize> = tiles.iter()
+            .map(|&tile| if tile == previous_blank { blank } else { tile })
+            .collect();
+        state = encode(previous_blank, &previous_tiles);
+    }
+    moves.iter().rev().collect()
+}
+
+/// Solve a board layer by layer, see the module documentation.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `options` - Options controlling the solve; only `heuristic` and `max_nodes` are used
+///
+/// # Returns
+/// The solution moves and search statistics, or why the board can't be solved
+pub fn solve_layered(board: &Board, options: &SolveOptions) -> Result<solver::Solution, SolveError> {
+    let mut layered = LayeredSolver::new(board, options)?;
+    let mut moves = String::new();
+    while let Some(chunk) = layered.next_chunk()? {
+        moves.push_str(&chunk);
+    }
+    Ok(solver::Solution { moves, stats: layered.stats.clone(), optimal: layered.is_optimal() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::verify_solution;
+    use std::convert::TryFrom;
+
+    /// Test streamed chunks of an 8x8 solution concatenate to a solution
+    #[test]
+    fn test_layered_chunks() {
+        let board = Board::generate(8, 387, 2000).unwrap();
+        let mut solver = LayeredSolver::new(&board, &SolveOptions::new()).unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = solver.next_chunk().unwrap() {
+            chunks.push(chunk);
+        }
+        // Rows and columns of 5 layers, then the core
+        assert!(chunks.len() > 5 && chunks.len() <= 11);
+        assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+        let moves = chunks.concat();
+        assert!(verify_solution(&board, &moves, Notation::BlankMoves));
+        // The first layer is in place after its row and column
+        let after_layer = board.apply_moves(&chunks[..2].concat(), Notation::BlankMoves).unwrap();
+        for cell in (0..8).chain((1..8).map(|row| row * 8)) {
+            assert_eq!(after_layer.tiles()[cell].symbol(), cell as i32 + 1);
+        }
+        assert_eq!(solver.next_chunk(), Ok(None));
+        let options = SolveOptions::new().algorithm(Algorithm::Layered);
+        let mut streamed = Vec::new();
+        let stats = solver::solve_streaming(&board, &options, |chunk| streamed.push(chunk.to_string())).unwrap();
+        assert_eq!((streamed, &stats), (chunks, solver.stats()));
+        let solution = solve_layered(&board, &SolveOptions::new()).unwrap();
+        assert_eq!(solution.moves, moves);
+        assert!(!solution.optimal);
+        assert!(solution.stats.nodes_expanded > 0);
+    }
+
+    /// Test small boards, solved boards, unsolvable boards and node limits
+    #[test]
+    fn test_layered_edge_cases() {
+        let small = Board::generate(3, 387, 40).unwrap();
+        let solution = solve_layered(&small, &SolveOptions::new()).unwrap();
+        assert!(solution.optimal);
+        assert_eq!(solution.moves.len(), solver::solve_length(&small, &SolveOptions::new()).unwrap() as usize);
+        let solved = Board::new(6, None, -1, None);
+        assert_eq!(LayeredSolver::new(&solved, &SolveOptions::new()).unwrap().next_chunk(), Ok(None));
+        let unsolvable = Board::try_from(vec![2, 1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, -1]).unwrap();
+        assert_eq!(solve_layered(&unsolvable, &SolveOptions::new()).unwrap_err(), SolveError::Unreachable);
+        let board = Board::generate(6, 387, 500).unwrap();
+        let partial = match solve_layered(&board, &SolveOptions::new().max_nodes(5000)) {
+            Err(SolveError::BudgetExceeded(partial)) => partial,
+            other => panic!("expected the budget to run out, got {:?}", other),
+        };
+        assert_eq!(partial.stats.nodes_expanded, 5000);
+        assert_eq!(board.apply_moves(&partial.best_path, Notation::BlankMoves), Ok(partial.best_board.clone()));
+    }
+}
@@ -1,95 +1,1501 @@
 #[macro_use]
 extern crate lazy_static;
 
+extern crate alloc;
+extern crate js_sys;
 extern crate rand;
 extern crate wasm_bindgen;
 extern crate web_sys;
 
-mod board;
-mod tile;
+#[macro_use]
+pub mod logging;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod benchmarks;
+pub mod board;
+pub mod build_info;
+pub mod cache;
+pub mod clock;
+pub mod conflict_cache;
+pub mod conformance;
+pub mod context;
+pub mod debug_checks;
+pub mod difficulty;
+pub mod dot;
+pub mod error_code;
+pub mod exact;
+pub mod explain;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod inspect;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod labels;
+pub mod layered;
+pub mod observer;
+pub mod pack;
+pub mod puzzle;
+#[cfg(all(feature = "python", not(target_arch = "wasm32")))]
+pub mod python;
+pub mod replay;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod report;
+pub mod rng;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+pub mod solver;
+pub mod tagged;
+pub mod tile;
+pub mod transposition;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod verify;
+pub mod worker;
+pub mod zobrist;
 
-use board::Board;
-use std::collections::{BinaryHeap, HashSet};
+use board::{Board, BoardError, GoalPreset, Heuristic, IllegalMove, Notation};
+use cache::SolveCache;
+use difficulty::Difficulty;
+use error_code::{CodedError, ErrorCode};
+use exact::DistanceTable;
+use labels::LabeledBoard;
+use layered::LayeredSolver;
+use puzzle::Puzzle;
+use solver::{Algorithm, Solution, SolveError, SolveOptions};
+use std::cell::RefCell;
+use js_sys::futures::JsFuture;
+use js_sys::{Array, Function, Object, Promise, Reflect};
 use tile::Tile;
 use wasm_bindgen::prelude::*;
 
-// A macro to provide `println!(..)`-style syntax for `console.log` logging.
-//macro_rules! log {
-//    ( $( $t:tt )* ) => {
-//        web_sys::console::log_1(&format!( $( $t )* ).into());
-//    }
-//}
 
 /// Wasm entry point for Rust tile puzzle solver.
+/// * Options are picked by board size, see `SolveOptions::for_size`; solutions of 5x5
+///   and larger boards aren't optimal.
+///
+/// * The size may name a goal preset to solve towards instead of the standard solved
+///   board, e.g. "4@snake,...", see `board::GoalPreset`.
+/// * Tile labels may follow the tiles, e.g. "3,...;labels=S,L,I,D,E,W,A,Y", see
+///   `labels`; they are checked but don't change the solution.
 ///
 /// # Parameters
 /// * `board_info` - Board info in the following format:
-///     * "<board size>(,<tile symbol>)*" (Note the regex used)
+///     * "<board size>(@<goal preset>)?(,<tile symbol>)*(;labels=<label>(,<label>)*)?" (Note
+///       the regex used)
 ///
 /// # Returns
-/// String of single characters representing solution moves
+/// String of single characters representing solution moves, or an error if the goal
+/// is unreachable
 #[wasm_bindgen]
-pub fn solve_board(board_info: &str) -> String {
-    let split_board_info: Vec<&str> = board_info.split(',').collect();
-    // Get size of board
-    let n = split_board_info[0].parse::<i32>().unwrap();
-    let tiles: Vec<Tile> = split_board_info[1..]
-        .iter()
-        .map(|&tile_id| Tile::new(tile_id.parse().unwrap()))
+pub fn solve_board(board_info: &str) -> Result<String, JsValue> {
+    solve_board_info(board_info).map_err(to_js_error)
+}
+
+/// Solve board info, towards its goal preset if it names one.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// String of single characters representing solution moves, or the problem
+fn solve_board_info(board_info: &str) -> Result<String, CodedError> {
+    let (board_info, labels) = _split_labels(board_info);
+    let (board, preset) = try_parse_goal_board_info(board_info).map_err(|e| CodedError::from_error(&e))?;
+    if let Some(labels) = labels {
+        LabeledBoard::new(board.clone(), labels).map_err(|e| CodedError::from_error(&e))?;
+    }
+    let solution = match preset {
+        GoalPreset::Standard => solve_cached(&board),
+        _ => solver::solve_to_preset(&board, preset, &SolveOptions::for_size(board.size())),
+    };
+    solution.map(|solution| solution.moves).map_err(|e| CodedError::from_error(&e))
+}
+
+/// Wasm entry point for solving a board that fails on purpose, for testing how callers
+/// handle solver failures.
+/// * Only built with the `chaos` feature. Errors are thrown exactly as `solve_board`
+///   throws them, with the partial result in `details` for budget failures.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`, without a goal preset
+/// * `fail_after_nodes` - Number of boards to expand before failing
+/// * `fail_with` - Kind of error to fail with, e.g. "budget_exceeded", see
+///   `solver::SolveErrorKind`
+///
+/// # Returns
+/// String of single characters representing solution moves, if the board is solved
+/// before the failure
+#[cfg(feature = "chaos")]
+#[wasm_bindgen]
+pub fn solve_board_failing(board_info: &str, fail_after_nodes: u64, fail_with: &str) -> Result<String, JsValue> {
+    solve_board_failing_info(board_info, fail_after_nodes, fail_with).map_err(to_js_error)
+}
+
+/// Solve board info with an injected failure.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`, without a goal preset
+/// * `fail_after_nodes` - Number of boards to expand before failing
+/// * `fail_with` - Name of the kind of error to fail with
+///
+/// # Returns
+/// String of single characters representing solution moves, or the problem
+#[cfg(feature = "chaos")]
+fn solve_board_failing_info(board_info: &str, fail_after_nodes: u64, fail_with: &str) -> Result<String, CodedError> {
+    let board = try_parse_board_info(board_info).map_err(|e| CodedError::from_error(&e))?;
+    let kind = match solver::SOLVE_ERROR_KINDS.iter().find(|kind| kind.to_string() == fail_with) {
+        Some(&kind) => kind,
+        None => return Err(CodedError::from_error(&format!("unknown solve error kind {:?}", fail_with))),
+    };
+    let options = SolveOptions::for_size(board.size()).fail_after_nodes(Some(fail_after_nodes)).fail_with(kind);
+    solver::solve(&board, &options).map(|solution| solution.moves).map_err(|e| CodedError::from_error(&e))
+}
+
+/// Convert an error into a Javascript error to be thrown.
+///
+/// # Parameters
+/// * `error` - The error
+///
+/// # Returns
+/// Javascript `Error` with the error's `message`, its numeric `code` and, if it has
+/// any, its `details` object, see `error_code`
+fn to_js_error<E: ErrorCode>(error: E) -> JsValue {
+    let js_error: JsValue = JsError::new(&error.to_string()).into();
+    Reflect::set(&js_error, &JsValue::from_str("code"), &JsValue::from(error.code())).unwrap();
+    if let Some(details) = error.details() {
+        let details = js_sys::JSON::parse(&details).unwrap();
+        Reflect::set(&js_error, &JsValue::from_str("details"), &details).unwrap();
+    }
+    js_error
+}
+
+thread_local! {
+    /// Solve cache used by `solve_board`, when enabled
+    static SOLVE_CACHE: RefCell<Option<SolveCache>> = const { RefCell::new(None) };
+}
+
+/// Enable caching of solutions for `solve_board`.
+/// * Re-solving a board, or any board along a cached solution, is answered from the cache.
+///
+/// # Parameters
+/// * `capacity` - Maximum number of cached solutions
+#[wasm_bindgen]
+pub fn enable_cache(capacity: u32) {
+    SOLVE_CACHE.with(|cache| *cache.borrow_mut() = Some(SolveCache::new(capacity as usize)));
+}
+
+/// Remove all solutions from the cache, leaving it enabled.
+#[wasm_bindgen]
+pub fn clear_cache() {
+    SOLVE_CACHE.with(|cache| {
+        if let Some(cache) = cache.borrow_mut().as_mut() {
+            cache.clear();
+        }
+    });
+}
+
+/// Solve a board, going through the solve cache when it is enabled.
+///
+/// # Parameters
+/// * `board` - The board to solve
+///
+/// # Returns
+/// The solution moves and search statistics, or why the board can't be solved
+fn solve_cached(board: &Board) -> Result<solver::Solution, SolveError> {
+    SOLVE_CACHE.with(|cache| match cache.borrow_mut().as_mut() {
+        Some(cache) => solve_with_cache(board, cache),
+        None => solver::solve(board, &SolveOptions::for_size(board.size())),
+    })
+}
+
+/// Solve a board, answering from a cache and adding the solution to it.
+/// * Only optimal solutions are cached, so every cached suffix is optimal too.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `cache` - Cache of solutions
+///
+/// # Returns
+/// The solution moves and search statistics, or why the board can't be solved
+fn solve_with_cache(board: &Board, cache: &mut SolveCache) -> Result<solver::Solution, SolveError> {
+    if let Some(solution) = cache.get(board) {
+        return Ok(solution);
+    }
+    let solution = solver::solve(board, &SolveOptions::for_size(board.size()))?;
+    if solution.optimal {
+        cache.insert(board, &solution.moves);
+    }
+    Ok(solution)
+}
+
+/// Capacity of the cache shared by the boards of a batch
+const BATCH_CACHE_CAPACITY: usize = 256;
+
+/// Wasm entry point for solving many boards in one call.
+/// * Boards are solved through the solve cache when it is enabled, otherwise through a
+///   cache shared by the batch, so boards along each other's solutions are answered
+///   without searching.
+///
+/// # Parameters
+/// * `boards` - Board info of each board, in the same format as `solve_board`,
+///   separated by `;`; a `;labels=` suffix belongs to the board before it
+///
+/// # Returns
+/// Solution moves of each board in the same order, separated by `;`, with boards that
+/// can't be solved given as `!<error message>`
+#[wasm_bindgen]
+pub fn solve_boards(boards: &str) -> String {
+    if boards.trim().is_empty() {
+        return String::new();
+    }
+    let mut batch_cache = SolveCache::new(BATCH_CACHE_CAPACITY);
+    let results: Vec<String> = _split_batch(boards).into_iter()
+        .map(|board_info| {
+            let (board_info, labels) = _split_labels(board_info);
+            let solution = try_parse_board_info(board_info)
+                .map_err(|error| error.to_string())
+                .and_then(|board| match labels {
+                    Some(labels) => LabeledBoard::new(board.clone(), labels)
+                        .map(|_| board)
+                        .map_err(|error| error.to_string()),
+                    None => Ok(board),
+                })
+                .and_then(|board| {
+                    SOLVE_CACHE.with(|cache| match cache.borrow_mut().as_mut() {
+                        Some(cache) => solve_with_cache(&board, cache),
+                        None => solve_with_cache(&board, &mut batch_cache),
+                    }).map_err(|error| error.to_string())
+                });
+            match solution {
+                Ok(solution) => solution.moves,
+                Err(message) => format!("!{}", message.replace(';', ",")),
+            }
+        })
         .collect();
-    solve_main(n, tiles)
+    results.join(";")
 }
 
+/// Split a batch of board info into the info of each board.
+///
+/// # Parameters
+/// * `boards` - Board info separated by `;`, see `solve_boards`
+///
+/// # Returns
+/// Board info of each board, with its labels if it has any
+fn _split_batch(boards: &str) -> Vec<&str> {
+    let mut board_infos = Vec::new();
+    let mut start = 0;
+    for (i, _) in boards.match_indices(';') {
+        // The `;` of a labels suffix doesn't end the board
+        if !boards[i..].starts_with(labels::LABELS_PREFIX) {
+            board_infos.push(&boards[start..i]);
+            start = i + 1;
+        }
+    }
+    board_infos.push(&boards[start..]);
+    board_infos
+}
 
-/// Main function of tile solver.
+/// Wasm entry point for solving a board without blocking the page.
+/// * Yields to the event loop after every `chunk_nodes` expansions so rendering and
+///   input stay responsive.
+/// * Uses `SolveOptions::for_size`, except that stepped searches are best-first, so
+///   15-puzzles run A* rather than IDA* and larger boards run greedy search.
 ///
 /// # Parameters
-/// * `boardSize` - Size of the board
-/// * `tiles` - Tiles that make up the board
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `chunk_nodes` - Number of boards to expand between yields
 ///
 /// # Returns
-/// String of single characters representing solution moves
-fn solve_main(n: i32, tiles: Vec<Tile>) -> String {
-    // Priority queue for storing leaf boards in state space tree
-    let mut board_leaves: BinaryHeap<Board> = BinaryHeap::new();
-    // Previously seen paths
-    let mut previous_paths: HashSet<String> = HashSet::new();
-    // Create the game board
-    let solved_board = Board::new(n, None, -1, None);
-    let unsolved_board = Board::new(
-        n,
-        Some(&solved_board),
-        0,
-        Some(tiles.into_boxed_slice()),
-    );
-    // Add path to previously seen and root board to leaves PQ
-    previous_paths.insert(unsolved_board.path.clone());
-    board_leaves.push(unsolved_board);
-
-    // Loop until solved
+/// Promise of a string of single characters representing solution moves
+#[wasm_bindgen(wasm_bindgen_futures = js_sys::futures)]
+pub async fn solve_board_async(board_info: String, chunk_nodes: u32) -> Result<String, JsValue> {
+    let board = try_parse_board_info(&board_info).map_err(to_js_error)?;
+    let options = SolveOptions::for_size(board.size());
+    let mut solver = solver::Solver::with_options(board.size(), board.tiles().to_vec(), &options);
     loop {
-        // Get the next best board leaf to expand
-        let next_best_leaf = board_leaves.pop().unwrap();
-        // Return path if solved
-        if next_best_leaf.is_solved() {
-            return next_best_leaf.path;
+        if let Some(solution) = solver.step(chunk_nodes.max(1) as u64).map_err(to_js_error)? {
+            return Ok(solution.moves);
         }
-        // Expand the next best leaf
-        for tile_move in next_best_leaf.get_moves() {
-            let mut new_board = next_best_leaf.clone();
-            // If resulting board has been seen before, skip it
-            new_board.path.push(tile_move);
-            if previous_paths.contains(&new_board.path) {
-                continue;
-            }
-            // Make move
-            new_board.move_blank_tile(tile_move);
-            // Setup new board
-            new_board.depth = next_best_leaf.depth + 1;
-            new_board.cost = new_board.get_cost(&solved_board);
-            // Add to previous paths and leaves
-            previous_paths.insert(new_board.path.clone());
-            board_leaves.push(new_board);
+        yield_to_event_loop().await?;
+    }
+}
+
+/// Wait on a zero-delay timeout, letting the browser render and handle input.
+/// * `setTimeout` is looked up on the global object so this also works in workers.
+async fn yield_to_event_loop() -> Result<(), JsValue> {
+    let set_timeout: Function = Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))?.into();
+    let mut result = Ok(JsValue::UNDEFINED);
+    let promise = Promise::new(&mut |resolve, _reject| {
+        result = set_timeout.call2(&JsValue::NULL, &resolve, &JsValue::from(0));
+    });
+    result?;
+    JsFuture::from(promise).await?;
+    Ok(())
+}
+
+/// Wasm solver producing a solution a row or column at a time, see `layered`.
+/// * Pull chunks with `next_chunk` to show moves before the whole board is solved;
+///   concatenated, the chunks are the solution.
+#[wasm_bindgen]
+pub struct StreamingSolver {
+    solver: LayeredSolver,
+}
+
+#[wasm_bindgen]
+impl StreamingSolver {
+    /// Create a streaming solver for a board of any size.
+    ///
+    /// # Parameters
+    /// * `board_info` - Board info in the same format as `solve_board`
+    #[wasm_bindgen(constructor)]
+    pub fn new(board_info: &str) -> Result<StreamingSolver, JsValue> {
+        let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+        let options = SolveOptions::new().algorithm(Algorithm::Layered).heuristic(Heuristic::LinearConflicts);
+        let solver = LayeredSolver::new(&board, &options).map_err(to_js_error)?;
+        Ok(StreamingSolver { solver })
+    }
+
+    /// Produce the next chunk of the solution.
+    ///
+    /// # Returns
+    /// String of single characters representing the next moves, or undefined once the
+    /// solution is complete
+    pub fn next_chunk(&mut self) -> Result<Option<String>, JsValue> {
+        self.solver.next_chunk().map_err(to_js_error)
+    }
+}
+
+/// Wasm table of the exact distance from the goal of every state of a small board.
+/// * Building a 3x3 table takes a couple of seconds; cache `to_bytes`, e.g. in
+///   IndexedDB, and restore it with `from_bytes` on later page loads.
+#[wasm_bindgen]
+pub struct ExactDistances {
+    table: DistanceTable,
+}
+
+#[wasm_bindgen]
+impl ExactDistances {
+    /// Build the table by breadth-first search from the goal.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board (at most 3)
+    #[wasm_bindgen(constructor)]
+    pub fn new(n: i32) -> Result<ExactDistances, JsValue> {
+        let table = DistanceTable::try_new(n).map_err(to_js_error)?;
+        Ok(ExactDistances { table })
+    }
+
+    /// Restore a table saved with `to_bytes`.
+    ///
+    /// # Parameters
+    /// * `bytes` - Serialized table
+    pub fn from_bytes(bytes: &[u8]) -> Result<ExactDistances, JsValue> {
+        let table = DistanceTable::from_bytes(bytes).map_err(to_js_error)?;
+        Ok(ExactDistances { table })
+    }
+
+    /// Serialize the table, see `exact` for the format.
+    ///
+    /// # Returns
+    /// One byte per state with a header and checksum
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.table.to_bytes()
+    }
+
+    /// Look up the optimal solution length of a board.
+    ///
+    /// # Parameters
+    /// * `board_info` - Board info in the same format as `solve_board`
+    ///
+    /// # Returns
+    /// Optimal solution length, or undefined if the board is unsolvable or the wrong size
+    pub fn distance(&self, board_info: &str) -> Result<Option<u32>, JsValue> {
+        let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+        Ok(self.table.distance(&board).map(|distance| distance as u32))
+    }
+}
+
+/// Wasm entry point for reporting how the solver was built, e.g. for telemetry.
+///
+/// # Returns
+/// JSON object string with the `version`, `git_hash`, `features` and `profile`, see
+/// `build_info::BuildInfo`
+#[wasm_bindgen]
+pub fn build_info_json() -> String {
+    build_info::build_info().to_json()
+}
+
+/// Wasm entry point for checking the built module works, see `conformance`.
+/// * Packagers can run this after building; it takes well under a second.
+///
+/// # Returns
+/// A line per case with its outcome and time, or an error (code 405) naming the failed
+/// cases
+#[wasm_bindgen]
+pub fn self_test() -> Result<String, JsValue> {
+    conformance::conformance_check().map(|report| report.to_string()).map_err(to_js_error)
+}
+
+/// Wasm entry point for setting the memory budget of every solve started from now on.
+/// * Low-memory mobile browsers can't grow the wasm heap much past 300MB. A solve that
+///   would grow past the budget throws error 205 instead of trapping, so the module
+///   stays usable.
+///
+/// # Parameters
+/// * `bytes` - Budget in bytes, 0 for no budget
+#[wasm_bindgen]
+pub fn set_default_memory_budget(bytes: u64) {
+    solver::set_default_memory_budget(if bytes == 0 { None } else { Some(bytes) });
+}
+
+/// Wasm entry point for solving a board, returning moves as bytes.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// One byte per solution move, see `solver::MOVE_ENCODING`
+#[wasm_bindgen]
+pub fn solve_board_moves(board_info: &str) -> Result<Vec<u8>, JsValue> {
+    // Solutions are only ever made of moves
+    Ok(solver::encode_moves(&solve_board(board_info)?).unwrap_or_default())
+}
+
+/// Wasm entry point for solving a board passed as a typed array.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `tiles` - Tile symbols in row-major order (-1 for the blank)
+///
+/// # Returns
+/// String of single characters representing solution moves
+#[wasm_bindgen]
+pub fn solve_board_bytes(n: i32, tiles: &[i32]) -> Result<String, JsValue> {
+    solve_symbols(n, tiles).map_err(to_js_error)
+}
+
+/// Wasm entry point for solving a board packed 4 bits per tile.
+/// * See `pack::encode_tiles` for the layout.
+///
+/// # Parameters
+/// * `n` - Size of the board (at most 4)
+/// * `packed` - Packed tiles
+///
+/// # Returns
+/// String of single characters representing solution moves
+#[wasm_bindgen]
+pub fn solve_board_packed(n: u32, packed: u64) -> Result<String, JsValue> {
+    solve_packed(n, packed).map_err(to_js_error)
+}
+
+/// Wasm entry point for solving a board and reporting search statistics.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// JSON object string with the solution `path` and solve `stats`
+#[wasm_bindgen]
+pub fn solve_board_with_stats(board_info: &str) -> Result<String, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    solver::solve(&board, &SolveOptions::for_size(board.size()))
+        .map(|solution| solution.to_json())
+        .map_err(to_js_error)
+}
+
+/// Wasm entry point for solving a board with the moves written in a notation.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `notation` - Notation name: "blank_moves", "tile_moves" or "lowercase"
+///
+/// # Returns
+/// JSON object string with the solution `path` and solve `stats`
+#[wasm_bindgen]
+pub fn solve_board_with_notation(board_info: &str, notation: &str) -> Result<String, JsValue> {
+    let notation = parse_notation(notation).map_err(to_js_error)?;
+    let puzzle = Puzzle::from_board(&try_parse_board_info(board_info).map_err(to_js_error)?);
+    let solution = puzzle.solve(&SolveOptions::for_size(puzzle.board().size())).map_err(to_js_error)?;
+    Ok(Solution { moves: solution.format(notation), ..solution }.to_json())
+}
+
+/// Wasm entry point for generating a board by reproducible random moves.
+/// * Never returns the solved board for a positive number of shuffles.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `seed` - Seed of the random number generator
+/// * `shuffles` - Number of random moves to make
+///
+/// # Returns
+/// Board info in the same format as `solve_board`
+#[wasm_bindgen]
+pub fn generate_board(n: i32, seed: u64, shuffles: i32) -> Result<String, JsValue> {
+    Board::generate(n, seed, shuffles).map(|board| format_board_info(&board)).map_err(to_js_error)
+}
+
+/// Wasm entry point for generating a board of a difficulty tier.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `tier` - Name of the difficulty: "easy", "medium", "hard" or "expert"
+/// * `seed` - Seed of the random number generator
+///
+/// # Returns
+/// Board info in the same format as `solve_board`
+#[wasm_bindgen]
+pub fn generate_board_tier(n: i32, tier: &str, seed: u64) -> Result<String, JsValue> {
+    generate_tier_info(n, tier, seed).map_err(to_js_error)
+}
+
+/// Generate a board of a difficulty tier by the tier's name.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `tier` - Name of the difficulty, see `difficulty::Difficulty`
+/// * `seed` - Seed of the random number generator
+///
+/// # Returns
+/// Board info, or the problem with the size or difficulty
+pub fn generate_tier_info(n: i32, tier: &str, seed: u64) -> Result<String, BoardError> {
+    let tier = Difficulty::parse(tier).ok_or_else(|| BoardError::UnknownDifficulty(tier.to_string()))?;
+    difficulty::generate_tier(n, tier, seed).map(|board| format_board_info(&board))
+}
+
+/// Wasm entry point for generating a board along with its scramble metrics, so
+/// candidates can be filtered.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `seed` - Seed of the random number generator
+/// * `shuffles` - Number of random moves to make
+///
+/// # Returns
+/// JSON object string with the `board` info, its heuristic `lower_bound` and its
+/// `metrics`, see `board::BoardMetrics`
+#[wasm_bindgen]
+pub fn generate_board_json(n: i32, seed: u64, shuffles: i32) -> Result<String, JsValue> {
+    generate_board_info_json(n, seed, shuffles).map_err(to_js_error)
+}
+
+/// Generate a board and describe it with its scramble metrics.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `seed` - Seed of the random number generator
+/// * `shuffles` - Number of random moves to make
+///
+/// # Returns
+/// JSON object string with the `board` info, its `lower_bound` and its `metrics`, or the
+/// problem with the size
+pub fn generate_board_info_json(n: i32, seed: u64, shuffles: i32) -> Result<String, BoardError> {
+    let (board, bound) = Board::generate_with_bound(n, seed, shuffles, board::DEFAULT_MIN_SHUFFLE_BOUND)?;
+    let metrics = board.metrics(&Board::new(n, None, -1, None));
+    Ok(format!(
+        "{{\"board\":\"{}\",\"lower_bound\":{},\"metrics\":{}}}",
+        format_board_info(&board), bound, metrics.to_json()
+    ))
+}
+
+/// Wasm entry point for solving a board with the metadata of each move, e.g. to pace
+/// an animation.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// JSON object string with the solution `path`, a `moves` array holding each move with
+/// the heuristic after it and the boards expanded below it, and solve `stats`
+#[wasm_bindgen]
+pub fn solve_board_verbose(board_info: &str) -> Result<String, JsValue> {
+    let puzzle = Puzzle::from_board(&try_parse_board_info(board_info).map_err(to_js_error)?);
+    let options = SolveOptions::for_size(puzzle.board().size()).track_path_expansions(true);
+    let solution = puzzle.solve(&options).map_err(to_js_error)?;
+    Ok(solution.to_json_verbose(puzzle.board(), options.heuristic))
+}
+
+/// Wasm entry point for checking that moves solve a board.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `moves` - Solution moves
+/// * `notation` - Notation name the moves are written in, as in `solve_board_with_notation`
+///
+/// # Returns
+/// Whether the moves solve the board
+#[wasm_bindgen]
+pub fn verify_solution(board_info: &str, moves: &str, notation: &str) -> Result<bool, JsValue> {
+    let notation = parse_notation(notation).map_err(to_js_error)?;
+    let mut puzzle = Puzzle::from_board(&try_parse_board_info(board_info).map_err(to_js_error)?);
+    Ok(puzzle.apply_moves(moves, notation).is_ok() && puzzle.is_solved())
+}
+
+/// Wasm entry point for drawing the search tree explored while solving a board.
+/// * Trees with more than `dot::DEFAULT_DOT_MAX_NODES` boards are truncated.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// Graphviz DOT source of the search tree
+#[wasm_bindgen]
+pub fn trace_dot(board_info: &str) -> Result<String, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    dot::trace_dot(&board, dot::DEFAULT_DOT_MAX_NODES).map_err(to_js_error)
+}
+
+/// Wasm entry point for inspecting the cost of a board against the solved board.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// Object with `manhattan`, `linear_conflicts`, `depth` and `total` fields
+#[wasm_bindgen]
+pub fn heuristic_breakdown(board_info: &str) -> Result<JsValue, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    let breakdown = board.heuristic_breakdown(None);
+    let object = Object::new();
+    for (key, value) in &[
+        ("manhattan", breakdown.manhattan),
+        ("linear_conflicts", breakdown.linear_conflicts),
+        ("depth", breakdown.depth),
+        ("total", breakdown.total),
+    ] {
+        Reflect::set(&object, &JsValue::from_str(key), &JsValue::from(*value))?;
+    }
+    Ok(object.into())
+}
+
+/// Wasm entry point for getting each tile's distance from its solved cell, e.g. to
+/// shade tiles by how far they are from home.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// Manhattan distance of the tile in each cell, in board order, with 0 for the blank
+#[wasm_bindgen]
+pub fn tile_distances(board_info: &str) -> Result<Vec<u32>, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    let mut distances: Vec<u32> = board.per_tile_distances(&Board::new(board.size(), None, -1, None))
+        .into_iter()
+        .map(|(_, distance)| distance)
+        .collect();
+    distances.insert(board.get_blank_index() as usize, 0);
+    Ok(distances)
+}
+
+/// Wasm entry point for finding the optimal solution length of a board.
+/// * Faster than solving when the moves themselves aren't needed.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// Number of moves in an optimal solution
+#[wasm_bindgen]
+pub fn optimal_length(board_info: &str) -> Result<i32, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    solver::solve_length(&board, &SolveOptions::default())
+        .map(|length| length as i32)
+        .map_err(to_js_error)
+}
+
+/// Wasm entry point for finding which positions differ between two boards.
+///
+/// # Parameters
+/// * `a` - First board, as board info in the same format as `solve_board`
+/// * `b` - Second board of the same size, as board info
+///
+/// # Returns
+/// Array of objects with `row`, `col`, `tile_in_self` and `tile_in_other` fields
+#[wasm_bindgen]
+pub fn board_diff(a: &str, b: &str) -> Result<JsValue, JsValue> {
+    let a = try_parse_board_info(a).map_err(to_js_error)?;
+    let b = try_parse_board_info(b).map_err(to_js_error)?;
+    if a.size() != b.size() {
+        return Err(to_js_error(format!("can't diff a {}x{} board with a {}x{} board",
+                                       a.size(), a.size(), b.size(), b.size())));
+    }
+    let diffs = Array::new();
+    for diff in a.diff(&b) {
+        let object = Object::new();
+        for (key, value) in &[
+            ("row", diff.row),
+            ("col", diff.col),
+            ("tile_in_self", diff.tile_in_self),
+            ("tile_in_other", diff.tile_in_other),
+        ] {
+            Reflect::set(&object, &JsValue::from_str(key), &JsValue::from(*value))?;
+        }
+        diffs.push(&object);
+    }
+    Ok(diffs.into())
+}
+
+/// Wasm entry point for checking if a move can be made, for explaining rejected moves.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `direction` - Move of the blank tile (U, D, L or R)
+///
+/// # Returns
+/// "ok" if the move is legal, otherwise the name of the rejection, e.g. "BlankOnTopEdge"
+#[wasm_bindgen]
+pub fn check_move(board_info: &str, direction: &str) -> Result<String, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    let mut chars = direction.chars();
+    let result = match (chars.next(), chars.next()) {
+        (Some(direction), None) => board.check_move(direction),
+        _ => return Err(to_js_error(format!("expected a single move direction, found {:?}", direction))),
+    };
+    Ok(result.map_or_else(|rejection| rejection.name().to_string(), |_| "ok".to_string()))
+}
+
+/// Wasm entry point for checking if a board can be solved.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// Whether the board is solvable, or an error if the board info is invalid
+#[wasm_bindgen]
+pub fn is_solvable(board_info: &str) -> Result<bool, JsValue> {
+    try_parse_board_info(board_info).map(|board| board.is_solvable()).map_err(to_js_error)
+}
+
+/// Wasm entry point for checking if one board can reach another, e.g. for a puzzle
+/// editor with custom goals.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `goal_info` - Board info of the goal
+///
+/// # Returns
+/// Whether the goal can be reached, see `board::Board::reachability`, or an error if
+/// either board info is invalid
+#[wasm_bindgen]
+pub fn is_reachable(board_info: &str, goal_info: &str) -> Result<bool, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    let goal = try_parse_board_info(goal_info).map_err(to_js_error)?;
+    Ok(board.reachability(&goal).is_ok())
+}
+
+/// Wasm entry point for explaining why a board can or can't be solved, e.g. for a
+/// board editor.
+/// * Boards only need to parse and have the right number of tiles; duplicate and
+///   unknown tiles are reported rather than rejected.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `goal_info` - Goal board info, or an empty string for the solved board
+///
+/// # Returns
+/// JSON object string of the `board::SolvabilityReport`
+#[wasm_bindgen]
+pub fn solvability_report(board_info: &str, goal_info: &str) -> Result<String, JsValue> {
+    solvability_json(board_info, goal_info).map_err(to_js_error)
+}
+
+/// Explain whether a board can reach a goal, see `solvability_report`.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `goal_info` - Goal board info, or an empty string for the solved board
+///
+/// # Returns
+/// JSON object string of the report, or the problem with the board info
+pub fn solvability_json(board_info: &str, goal_info: &str) -> Result<String, BoardError> {
+    let board = _parse_unvalidated_board_info(board_info)?;
+    let goal = if goal_info.is_empty() {
+        Board::new(board.size(), None, -1, None)
+    } else {
+        _parse_unvalidated_board_info(goal_info)?
+    };
+    Ok(board.solvability(&goal).to_json())
+}
+
+/// Wasm entry point for making a move in interactive play.
+/// * Reversing the previous move is allowed.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `direction` - Direction to move the blank tile (U, D, L or R)
+///
+/// # Returns
+/// Board info after the move, or an error for an illegal move
+#[wasm_bindgen]
+pub fn apply_move(board_info: &str, direction: char) -> Result<String, JsValue> {
+    apply_move_info(board_info, direction).map_err(to_js_error)
+}
+
+/// Wasm entry point for listing the legal moves in interactive play.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// Concatenated legal move characters
+#[wasm_bindgen]
+pub fn valid_moves(board_info: &str) -> Result<String, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    Ok(board::MOVES.iter().filter(|&&m| board.is_legal_move(m)).collect())
+}
+
+/// Wasm entry point for grading how obvious each move of a solution is.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `path` - Solution moves of the blank
+///
+/// # Returns
+/// JSON array string with one object per move, see `replay::MoveAnnotation`
+#[wasm_bindgen]
+pub fn annotate_solution(board_info: &str, path: &str) -> Result<String, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    let annotations = replay::annotate_solution(&board, path).map_err(to_js_error)?;
+    let objects: Vec<String> = annotations.iter().map(|annotation| annotation.to_json()).collect();
+    Ok(format!("[{}]", objects.join(",")))
+}
+
+/// Wasm entry point for finding where a player's moves strayed from an optimal line.
+/// * Boards are solved with `SolveOptions::for_size`, so boards larger than 4x4, which
+///   have no optimal algorithm there, are rejected.
+///
+/// # Parameters
+/// * `board_info` - Board info of the board the player started from, in the same
+///   format as `solve_board`
+/// * `player_path` - Moves of the blank the player made
+///
+/// # Returns
+/// JSON object string, see `replay::Divergence`, or an error for an illegal move (with
+/// its index in the message) or an unsolvable board
+#[wasm_bindgen]
+pub fn analyze_player_path(board_info: &str, player_path: &str) -> Result<String, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    replay::compare_to_optimal(&board, player_path, &SolveOptions::for_size(board.size()))
+        .map(|divergence| divergence.to_json())
+        .map_err(to_js_error)
+}
+
+/// Wasm entry point for formatting a board as an HTML table.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`, without a goal
+///   preset; tiles show their labels if it has any
+///
+/// # Returns
+/// HTML table that can be set as `innerHTML`, see `Board::to_html_labeled`
+#[wasm_bindgen]
+pub fn board_to_html(board_info: &str) -> Result<String, JsValue> {
+    let labeled = try_parse_labeled_board_info(board_info).map_err(to_js_error)?;
+    Ok(labeled.to_html())
+}
+
+/// Wasm entry point for building a goal from a layout of tile labels, e.g. the phrase
+/// a word puzzle should spell.
+///
+/// # Parameters
+/// * `board_info` - Board info with labels, e.g. "3,...;labels=S,L,I,D,E,W,A,Y"
+/// * `layout` - Comma separated label of each position in row-major order, with `_`
+///   for the blank
+///
+/// # Returns
+/// Board info of the goal with the same labels, or an error if a label is unknown,
+/// repeated or names several tiles, see `labels::LabeledBoard::goal_from_labels`
+#[wasm_bindgen]
+pub fn goal_from_labels(board_info: &str, layout: &str) -> Result<String, JsValue> {
+    let labeled = try_parse_labeled_board_info(board_info).map_err(to_js_error)?;
+    let layout: Vec<&str> = layout.split(',').collect();
+    let goal = labeled.goal_from_labels(&layout).map_err(to_js_error)?;
+    let goal = labeled.with_board(goal).map_err(to_js_error)?;
+    Ok(format_labeled_board_info(&goal))
+}
+
+/// Wasm entry point for formatting a board as a compact string for logs and URLs.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// Compact string, e.g. `3:1,2,3,4,5,6,7,_,8`
+#[wasm_bindgen]
+pub fn to_compact_string(board_info: &str) -> Result<String, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    Ok(board.to_compact_string())
+}
+
+/// Wasm entry point for parsing a board from a compact string.
+///
+/// # Parameters
+/// * `compact` - Compact string in the format of `to_compact_string`
+///
+/// # Returns
+/// Board info in the same format as `solve_board`
+#[wasm_bindgen]
+pub fn from_compact_string(compact: &str) -> Result<String, JsValue> {
+    let board = Board::from_compact_string(compact).map_err(to_js_error)?;
+    Ok(format_board_info(&board))
+}
+
+/// Wasm entry point for generating a share link token for a board.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// 11-character base64url token, or an error for boards larger than 4x4
+#[wasm_bindgen]
+pub fn to_url_safe(board_info: &str) -> Result<String, JsValue> {
+    let board = try_parse_board_info(board_info).map_err(to_js_error)?;
+    board.to_url_safe()
+        .ok_or_else(|| to_js_error(format!("{}x{} boards are too large for URL tokens", board.size(), board.size())))
+}
+
+/// Wasm entry point for reading a board from a share link token.
+///
+/// # Parameters
+/// * `token` - Token from `to_url_safe`
+///
+/// # Returns
+/// Board info in the same format as `solve_board`
+#[wasm_bindgen]
+pub fn from_url_safe(token: &str) -> Result<String, JsValue> {
+    let board = Board::from_url_safe(token).map_err(to_js_error)?;
+    Ok(format_board_info(&board))
+}
+
+/// Make a move on a board given as board info.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `direction` - Direction to move the blank tile
+///
+/// # Returns
+/// Board info after the move, or the problem
+fn apply_move_info(board_info: &str, direction: char) -> Result<String, CodedError> {
+    let mut board = try_parse_board_info(board_info).map_err(|e| CodedError::from_error(&e))?;
+    board.check_move(direction).map_err(|rejection| {
+        CodedError::new(IllegalMove { index: 0, rejection }.code(), format!("illegal move {:?}", direction))
+    })?;
+    board.move_blank_tile(direction);
+    Ok(format_board_info(&board))
+}
+
+/// Look up a notation by name.
+///
+/// # Parameters
+/// * `name` - Name of the notation, e.g. "tile_moves"
+///
+/// # Returns
+/// The notation, or a description of the problem
+fn parse_notation(name: &str) -> Result<Notation, String> {
+    board::NOTATIONS.iter()
+        .copied()
+        .find(|notation| notation.to_string() == name)
+        .ok_or_else(|| format!("unknown notation {:?}", name))
+}
+
+/// Longest board info accepted by default, enough for any board up to
+/// `board::DEFAULT_MAX_SIZE`
+pub const MAX_BOARD_INFO_LEN: usize = 1 << 12;
+
+/// Limits on board info, so pathological input is rejected before anything large is
+/// allocated.
+///
+/// # Attributes
+/// * `max_size` - Largest board size accepted
+/// * `max_input_len` - Longest board info accepted, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputLimits {
+    pub max_size: i32,
+    pub max_input_len: usize,
+}
+
+impl Default for InputLimits {
+    fn default() -> InputLimits {
+        InputLimits { max_size: board::DEFAULT_MAX_SIZE, max_input_len: MAX_BOARD_INFO_LEN }
+    }
+}
+
+impl InputLimits {
+    /// Set the largest board size accepted.
+    /// * Raises the input length limit to fit boards of that size if needed.
+    pub fn max_size(mut self, max_size: i32) -> InputLimits {
+        self.max_size = max_size;
+        // Leave 8 bytes per tile for its symbol, comma and spacing
+        let tiles = (max_size.max(0) as usize).saturating_mul(max_size.max(0) as usize);
+        self.max_input_len = self.max_input_len.max(tiles.saturating_mul(8));
+        self
+    }
+
+    /// Set the longest board info accepted, in bytes.
+    pub fn max_input_len(mut self, max_input_len: usize) -> InputLimits {
+        self.max_input_len = max_input_len;
+        self
+    }
+}
+
+/// Parse and validate board info passed in from Javascript.
+/// * Uses the default `InputLimits`.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// The parsed board, or the problem with the board info
+pub fn try_parse_board_info(board_info: &str) -> Result<Board, BoardError> {
+    try_parse_board_info_with_limits(board_info, &InputLimits::default())
+}
+
+/// Parse and validate board info within limits.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `limits` - Largest board and input accepted
+///
+/// # Returns
+/// The parsed board, or the problem with the board info
+pub fn try_parse_board_info_with_limits(board_info: &str, limits: &InputLimits) -> Result<Board, BoardError> {
+    if board_info.len() > limits.max_input_len {
+        return Err(BoardError::InputTooLong { length: board_info.len(), max: limits.max_input_len });
+    }
+    let (n, tiles) = _parse_tiles(board_info)?;
+    Board::try_new_with_max_size(n, tiles, limits.max_size)
+}
+
+/// Parse and validate board info that may have tile labels, e.g. "3,...;labels=A,B,...".
+/// * Boards without labels are labeled with their symbols.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`, without a goal
+///   preset
+///
+/// # Returns
+/// The labeled board, or the problem with the board info or labels
+pub fn try_parse_labeled_board_info(board_info: &str) -> Result<LabeledBoard, BoardError> {
+    let (board_info, labels) = _split_labels(board_info);
+    let board = try_parse_board_info(board_info)?;
+    let labels = labels.unwrap_or_else(|| (1..board.tiles().len()).map(|symbol| symbol.to_string()).collect());
+    LabeledBoard::new(board, labels)
+}
+
+/// Split the tile labels off board info.
+///
+/// # Parameters
+/// * `board_info` - Board info that may end in `labels::LABELS_PREFIX` and labels
+///
+/// # Returns
+/// The board info before the labels, and the labels if there are any
+fn _split_labels(board_info: &str) -> (&str, Option<Vec<String>>) {
+    match board_info.find(labels::LABELS_PREFIX) {
+        Some(start) => {
+            let labels = &board_info[start + labels::LABELS_PREFIX.len()..];
+            (&board_info[..start], Some(labels.split(',').map(|label| label.trim().to_string()).collect()))
+        }
+        None => (board_info, None),
+    }
+}
+
+/// Parse and validate board info that may name a goal preset, e.g. "3@snake,...".
+/// * Uses the default `InputLimits`.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// The parsed board and goal preset (`GoalPreset::Standard` if none is named), or the
+/// problem with the board info
+pub fn try_parse_goal_board_info(board_info: &str) -> Result<(Board, GoalPreset), BoardError> {
+    let (size, rest) = match board_info.find(',') {
+        Some(comma) => board_info.split_at(comma),
+        None => (board_info, ""),
+    };
+    let (size, preset) = match size.find('@') {
+        Some(at) => {
+            let name = size[at + 1..].trim();
+            let preset = board::GOAL_PRESETS.iter()
+                .copied()
+                .find(|preset| preset.to_string() == name)
+                .ok_or_else(|| BoardError::UnknownGoalPreset(name.to_string()))?;
+            (&size[..at], preset)
+        }
+        None => (size, GoalPreset::Standard),
+    };
+    let board = try_parse_board_info(&format!("{}{}", size, rest))?;
+    Ok((board, preset))
+}
+
+/// Split board info into the board size and tiles, without checking them.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// The size and tiles, or `BoardError::Unparsable`
+fn _parse_tiles(board_info: &str) -> Result<(i32, Vec<Tile>), BoardError> {
+    let unparsable = || BoardError::Unparsable(board_info.to_string());
+    let split_board_info: Vec<&str> = board_info.split(',').collect();
+    // Get size of board
+    let n = split_board_info[0].trim().parse::<i32>().map_err(|_| unparsable())?;
+    let mut tiles: Vec<Tile> = Vec::with_capacity(split_board_info.len() - 1);
+    for tile_id in &split_board_info[1..] {
+        tiles.push(Tile::new(tile_id.trim().parse().map_err(|_| unparsable())?));
+    }
+    Ok((n, tiles))
+}
+
+/// Parse board info within the default limits, checking only the size and tile count,
+/// so the tiles can be explained rather than rejected.
+///
+/// # Parameters
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// The parsed board, or the problem with the board info
+fn _parse_unvalidated_board_info(board_info: &str) -> Result<Board, BoardError> {
+    let limits = InputLimits::default();
+    if board_info.len() > limits.max_input_len {
+        return Err(BoardError::InputTooLong { length: board_info.len(), max: limits.max_input_len });
+    }
+    let (n, tiles) = _parse_tiles(board_info)?;
+    Board::validate_dimensions(n, &tiles, limits.max_size)?;
+    Ok(Board::new(n, None, 0, Some(tiles.into_boxed_slice())))
+}
+
+/// Format a board in the board info format used by `solve_board`.
+///
+/// # Parameters
+/// * `board` - The board to format
+///
+/// # Returns
+/// Board info string
+pub fn format_board_info(board: &Board) -> String {
+    let mut board_info = board.size().to_string();
+    for tile in board.tiles() {
+        board_info.push(',');
+        board_info.push_str(&tile.symbol().to_string());
+    }
+    board_info
+}
+
+/// Format a labeled board as board info, with its labels after the tiles.
+///
+/// # Parameters
+/// * `labeled` - The labeled board
+///
+/// # Returns
+/// Board info in the same format as `solve_board`
+pub fn format_labeled_board_info(labeled: &LabeledBoard) -> String {
+    format!("{}{}{}", format_board_info(labeled.board()), labels::LABELS_PREFIX, labeled.labels().join(","))
+}
+
+/// Validate and solve a board given as tile symbols.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `symbols` - Tile symbols in row-major order
+///
+/// # Returns
+/// String of single characters representing solution moves, or the problem
+fn solve_symbols(n: i32, symbols: &[i32]) -> Result<String, CodedError> {
+    let tiles: Vec<Tile> = symbols.iter().map(|&symbol| Tile::new(symbol)).collect();
+    let puzzle = Puzzle::from_tiles(n, tiles).map_err(|e| CodedError::from_error(&e))?;
+    puzzle.solve(&SolveOptions::for_size(puzzle.board().size()))
+        .map(|solution| solution.moves)
+        .map_err(|e| CodedError::from_error(&e))
+}
+
+/// Validate and solve a board given as packed tiles.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `packed` - Packed tiles
+///
+/// # Returns
+/// String of single characters representing solution moves, or the problem
+fn solve_packed(n: u32, packed: u64) -> Result<String, CodedError> {
+    if n < 1 || n > pack::MAX_PACKED_SIZE as u32 {
+        return Err(CodedError::from_error(&BoardError::InvalidSize(n as i32)));
+    }
+    let tiles = pack::decode_tiles(n as i32, packed);
+    let puzzle = Puzzle::from_tiles(n as i32, tiles).map_err(|e| CodedError::from_error(&e))?;
+    puzzle.solve(&SolveOptions::for_size(puzzle.board().size()))
+        .map(|solution| solution.moves)
+        .map_err(|e| CodedError::from_error(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test failures injected through the wasm entry point are reported like genuine ones
+    #[cfg(feature = "chaos")]
+    #[test]
+    fn test_solve_board_failing() {
+        let board_info = format_board_info(&Board::generate(3, 394, 200).unwrap());
+        let board = try_parse_board_info(&board_info).unwrap();
+        let genuine = solver::solve(&board, &SolveOptions::for_size(3).max_nodes(20)).unwrap_err();
+        // Wall times differ between runs
+        let without_millis = |error: CodedError| {
+            error.details.unwrap().split(",\"millis\"").next().unwrap().to_string()
+        };
+        let injected = solve_board_failing_info(&board_info, 20, "budget_exceeded").unwrap_err();
+        assert_eq!((injected.code, &injected.message), (genuine.code(), &genuine.to_string()));
+        assert_eq!(without_millis(injected), without_millis(CodedError::from_error(&genuine)));
+        assert_eq!(solve_board_failing_info(&board_info, 20, "unreachable").unwrap_err().code, 201);
+        assert_eq!(solve_board_failing_info(&board_info, 20, "nope").unwrap_err().code, 400);
+        assert_eq!(solve_board_failing_info(&board_info, 100_000, "too_large"), solve_board_info(&board_info));
+    }
+
+    /// Test labels are checked but ignored when solving
+    #[test]
+    fn test_solve_labeled_board_info() {
+        assert_eq!(solve_board_info("3,1,2,3,-1,4,6,7,5,8;labels=S,L,I,D,E,W,A,Y"), Ok("RDR".to_string()));
+        assert_eq!(solve_board_info("3,1,2,3,-1,4,6,7,5,8;labels=S,L").unwrap_err().code, 120);
+        let labeled = try_parse_labeled_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        assert_eq!(labeled.labels()[7], "8");
+    }
+
+    /// Test generating boards by tier name
+    #[test]
+    fn test_generate_tier_info() {
+        let board_info = generate_tier_info(3, "hard", 409).unwrap();
+        let board = try_parse_board_info(&board_info).unwrap();
+        assert_eq!(board, difficulty::generate_tier(3, Difficulty::Hard, 409).unwrap());
+        let unknown = generate_tier_info(3, "nightmare", 409).unwrap_err();
+        assert_eq!(unknown, BoardError::UnknownDifficulty("nightmare".to_string()));
+        assert_eq!(unknown.code(), 119);
+        assert_eq!(generate_tier_info(6, "easy", 409).unwrap_err().code(), 118);
+    }
+
+    /// Test the typed array and packed entry points agree with the string API
+    #[test]
+    fn test_solve_board_bytes_and_packed() {
+        let board_info = "3,1,2,3,-1,4,6,7,5,8";
+        let expected = solve_board(board_info).unwrap();
+        assert_eq!(solve_symbols(3, &[1, 2, 3, -1, 4, 6, 7, 5, 8]), Ok(expected.clone()));
+        let board = try_parse_board_info(board_info).unwrap();
+        assert_eq!(solve_packed(3, pack::encode_tiles(board.tiles())), Ok(expected));
+    }
+
+    /// Test moves are checked through the wasm entry point
+    #[test]
+    fn test_check_move() {
+        let board_info = "3,1,2,3,4,5,6,7,8,-1";
+        assert_eq!(check_move(board_info, "U").unwrap(), "ok");
+        assert_eq!(check_move(board_info, "D").unwrap(), "BlankOnBottomEdge");
+        assert_eq!(check_move(board_info, "R").unwrap(), "BlankOnRightEdge");
+    }
+
+    /// Test solutions verify when replayed in the notation they're written in
+    #[test]
+    fn test_solve_board_with_notation() {
+        let board_info = "3,1,2,3,-1,4,6,7,5,8";
+        assert!(solve_board_with_notation(board_info, "tile_moves").unwrap().starts_with("{\"path\":\"LUL\""));
+        assert!(verify_solution(board_info, "LUL", "tile_moves").unwrap());
+        assert!(verify_solution(board_info, "rdr", "lowercase").unwrap());
+        assert!(!verify_solution(board_info, "RDR", "tile_moves").unwrap());
+        assert!(parse_notation("reverse").is_err());
+    }
+
+    /// Test a batch keeps its order and reports failed boards in place
+    #[test]
+    fn test_solve_boards() {
+        let boards = "3,1,2,3,-1,4,6,7,5,8;3,2,1,3,4,5,6,7,8,-1;3,1,2,x; 3,1,2,3,4,-1,6,7,5,8;2,1,2,3,-1";
+        let results: Vec<String> = solve_boards(boards).split(';').map(String::from).collect();
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0], "RDR");
+        assert_eq!(results[1], format!("!{}", SolveError::Unreachable));
+        assert!(results[2].starts_with("!could not parse board info"));
+        // Answered from the batch cache, along the first board's solution
+        assert_eq!(results[3], "DR");
+        assert_eq!(results[4], "");
+        assert_eq!(solve_boards(""), "");
+        // Labels stay with their board
+        let boards = "3,1,2,3,-1,4,6,7,5,8;labels=S,L,I,D,E,W,A,Y;3,1,2,3,4,-1,6,7,5,8;3,1,2,3,4,5,6,-1,7,8;labels=S,L";
+        let results: Vec<String> = solve_boards(boards).split(';').map(String::from).collect();
+        assert_eq!(results.len(), 3, "{:?}", results);
+        assert_eq!(results[..2], ["RDR", "DR"]);
+        assert!(results[2].starts_with('!'), "{}", results[2]);
+    }
+
+    /// Test the byte moves decode to the same characters as the string API
+    #[test]
+    fn test_solve_board_moves() {
+        let board_info = "3,1,2,3,-1,4,6,7,5,8";
+        let moves = solve_board_moves(board_info).unwrap();
+        assert_eq!(moves, [3, 1, 3]);
+        assert_eq!(solver::decode_moves(&moves), Some(solve_board(board_info).unwrap()));
+    }
+
+    /// Test solve_board answers from the cache once enabled
+    #[test]
+    fn test_solve_board_cache() {
+        let board_info = "3,1,2,3,-1,4,6,7,5,8";
+        enable_cache(8);
+        assert_eq!(solve_board(board_info).unwrap(), "RDR");
+        // One move along the solution is a cache hit
+        let board = try_parse_board_info("3,1,2,3,4,-1,6,7,5,8").unwrap();
+        let hit = solve_cached(&board).unwrap();
+        assert_eq!(hit.moves, "DR");
+        assert_eq!(hit.stats.nodes_expanded, 0);
+        clear_cache();
+        let miss = solve_cached(&board).unwrap();
+        assert_eq!(miss.moves, "DR");
+        assert!(miss.stats.nodes_expanded > 0);
+        SOLVE_CACHE.with(|cache| *cache.borrow_mut() = None);
+    }
+
+    /// Test walking moves through the interactive play helpers
+    #[test]
+    fn test_apply_move() {
+        let board_info = "3,1,2,3,4,5,6,7,8,-1";
+        assert_eq!(valid_moves(board_info).unwrap(), "UL");
+        let moved = apply_move_info(board_info, 'U').unwrap();
+        assert_eq!(moved, "3,1,2,3,4,5,-1,7,8,6");
+        assert_eq!(valid_moves(&moved).unwrap(), "UDL");
+        // Reversing the last move is allowed in interactive play
+        assert_eq!(apply_move_info(&moved, 'D').unwrap(), board_info);
+        let moved = apply_move_info(&moved, 'L').unwrap();
+        assert_eq!(moved, "3,1,2,3,4,-1,5,7,8,6");
+        assert_eq!(valid_moves(&moved).unwrap(), "UDLR");
+        assert_eq!(apply_move_info(board_info, 'D'), Err(CodedError::new(110, "illegal move 'D'")));
+        assert_eq!(apply_move_info(board_info, 'Q'), Err(CodedError::new(110, "illegal move 'Q'")));
+        assert_eq!(apply_move_info("3,1,2", 'U').unwrap_err().code, 102);
+    }
+
+    /// Test board info can name a goal preset to solve towards
+    #[test]
+    fn test_goal_board_info() {
+        assert_eq!(solve_board_info("3@snake,1,2,3,6,5,4,7,-1,8"), Ok("R".to_string()));
+        assert_eq!(solve_board_info("3,1,2,3,4,5,6,7,-1,8"), Ok("R".to_string()));
+        assert_eq!(solve_board_info("2@blank_first,1,-1,2,3"), Ok("L".to_string()));
+        let (board, preset) = try_parse_goal_board_info("2 @ column_major,1,3,2,-1").unwrap();
+        assert_eq!(preset, GoalPreset::ColumnMajor);
+        assert!(board.is_solved_with_preset(preset));
+        assert_eq!(solve_board_info("3@spiral,1").unwrap_err().code, 114);
+        assert_eq!(try_parse_board_info("3@snake,1,2,3,6,5,4,7,-1,8").unwrap_err().code(), 105);
+    }
+
+    /// Test solvability reports explain boards that wouldn't parse as valid boards
+    #[test]
+    fn test_solvability_json() {
+        let solvable = "{\"solvable\":true,\"problems\":[],\"fix\":null}";
+        assert_eq!(solvability_json("2,1,2,3,-1", ""), Ok(solvable.to_string()));
+        assert_eq!(
+            solvability_json("2,2,1,3,-1", ""),
+            Ok(concat!(
+                "{\"solvable\":false,\"problems\":[{\"name\":\"ParityMismatch\",\"swap\":[2,1]}],",
+                "\"fix\":[2,1]}"
+            ).to_string())
+        );
+        assert_eq!(
+            solvability_json("2,1,1,-1,-1", ""),
+            Ok(concat!(
+                "{\"solvable\":false,\"problems\":[{\"name\":\"BlankCount\",\"blanks\":2,\"goal_blanks\":1},",
+                "{\"name\":\"DuplicateTile\",\"tile\":1},{\"name\":\"NotOnBoard\",\"tile\":2},",
+                "{\"name\":\"NotOnBoard\",\"tile\":3}],\"fix\":null}"
+            ).to_string())
+        );
+        assert!(solvability_json("2,1,2,3,-1", "3,1,2,3,4,5,6,7,8,-1").unwrap().contains("SizeMismatch"));
+        assert_eq!(solvability_json("2,1,2", ""), Err(BoardError::WrongTileCount { expected: 4, found: 2 }));
+    }
+
+    /// Test generated boards are reported with their metrics
+    #[test]
+    fn test_generate_board_info_json() {
+        let json = generate_board_info_json(3, 378, 30).unwrap();
+        let board = Board::generate(3, 378, 30).unwrap();
+        let metrics = board.metrics(&Board::new(3, None, -1, None));
+        let bound = board.heuristic_breakdown(None).total;
+        let expected = format!(
+            "{{\"board\":\"{}\",\"lower_bound\":{},\"metrics\":{}}}",
+            format_board_info(&board), bound, metrics.to_json()
+        );
+        assert_eq!(json, expected);
+        assert!(metrics.misplaced > 0 && bound > 0);
+        assert_eq!(generate_board_info_json(1, 378, 30), Err(BoardError::InvalidSize(1)));
+    }
+
+    /// Test parsing and formatting board info
+    #[test]
+    fn test_try_parse_board_info() {
+        let board = try_parse_board_info("2, 1,2,-1,3").unwrap();
+        assert_eq!(format_board_info(&board), "2,1,2,-1,3");
+        assert_eq!(
+            try_parse_board_info("2,1,x,-1,3"),
+            Err(BoardError::Unparsable("2,1,x,-1,3".to_string()))
+        );
+        assert_eq!(try_parse_board_info(""), Err(BoardError::Unparsable("".to_string())));
+        assert_eq!(try_parse_board_info("2,1,2,3,4"), Err(BoardError::InvalidTile(4)));
+    }
+
+    /// Test absurd sizes and input lengths give typed errors rather than allocating
+    #[test]
+    fn test_try_parse_board_info_limits() {
+        let cases = [
+            ("1000000,1,2,3", BoardError::TooLarge { n: 1_000_000, max: board::DEFAULT_MAX_SIZE }),
+            ("2147483647,1", BoardError::TooLarge { n: i32::MAX, max: board::DEFAULT_MAX_SIZE }),
+            ("17", BoardError::TooLarge { n: 17, max: board::DEFAULT_MAX_SIZE }),
+            ("-5,1,2", BoardError::InvalidSize(-5)),
+            ("0", BoardError::InvalidSize(0)),
+            ("-2147483648", BoardError::InvalidSize(i32::MIN)),
+            ("1", BoardError::WrongTileCount { expected: 1, found: 0 }),
+            ("99999999999,1", BoardError::Unparsable("99999999999,1".to_string())),
+        ];
+        for (board_info, error) in cases.iter() {
+            assert_eq!(try_parse_board_info(board_info).as_ref(), Err(error), "{}", board_info);
         }
+        assert!(try_parse_board_info("1,-1").unwrap().is_solved());
+        let long = format!("3{}", ",1".repeat(MAX_BOARD_INFO_LEN));
+        assert_eq!(
+            try_parse_board_info(&long),
+            Err(BoardError::InputTooLong { length: long.len(), max: MAX_BOARD_INFO_LEN })
+        );
+
+        // The largest default board fits within the input limit
+        let largest = format_board_info(&Board::new(board::DEFAULT_MAX_SIZE, None, 0, None));
+        assert_eq!(try_parse_board_info(&largest).unwrap().size(), board::DEFAULT_MAX_SIZE);
+        // Raising the size limit deliberately
+        let limits = InputLimits::default().max_size(40);
+        let board_info = format_board_info(&Board::new(40, None, 0, None));
+        assert!(board_info.len() > MAX_BOARD_INFO_LEN);
+        assert_eq!(try_parse_board_info(&board_info), Err(BoardError::InputTooLong {
+            length: board_info.len(),
+            max: MAX_BOARD_INFO_LEN,
+        }));
+        assert_eq!(try_parse_board_info_with_limits(&board_info, &limits).unwrap().size(), 40);
+        let limits = InputLimits::default().max_input_len(4);
+        assert!(try_parse_board_info_with_limits("2,1,2,-1,3", &limits).is_err());
+    }
+
+    /// Test the typed array and packed entry points reject invalid boards
+    #[test]
+    fn test_solve_board_bytes_and_packed_errors() {
+        assert_eq!(
+            solve_symbols(3, &[1, 2, 3]),
+            Err(CodedError::from_error(&BoardError::WrongTileCount { expected: 9, found: 3 }))
+        );
+        assert_eq!(
+            solve_symbols(2, &[1, 1, 3, -1]),
+            Err(CodedError::from_error(&BoardError::DuplicateTile(1)))
+        );
+        assert_eq!(solve_packed(5, 0), Err(CodedError::new(101, "invalid board size 5")));
+        // A nibble of 0xE decodes to tile 15, which doesn't exist on a 3x3
+        assert_eq!(solve_packed(3, 0xE).unwrap_err().code, 103);
+        // Valid but unsolvable boards report the goal as unreachable
+        assert_eq!(solve_symbols(2, &[2, 1, 3, -1]), Err(CodedError::new(201, SolveError::Unreachable.to_string())));
+    }
+
+    /// Test share link exports round trip board info
+    #[test]
+    fn test_compact_and_url_safe_exports() {
+        let board_info = "3,1,2,3,-1,4,6,7,5,8";
+        let compact = to_compact_string(board_info).unwrap();
+        assert_eq!(compact, "3:1,2,3,_,4,6,7,5,8");
+        assert_eq!(from_compact_string(&compact).unwrap(), board_info);
+        let token = to_url_safe(board_info).unwrap();
+        assert_eq!(token.len(), 11);
+        assert_eq!(from_url_safe(&token).unwrap(), board_info);
     }
 }
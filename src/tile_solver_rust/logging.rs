@@ -0,0 +1,147 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+use wasm_bindgen::prelude::*;
+
+/// Log levels, from least to most verbose
+pub const LOG_OFF: u8 = 0;
+pub const LOG_ERROR: u8 = 1;
+pub const LOG_WARN: u8 = 2;
+pub const LOG_INFO: u8 = 3;
+pub const LOG_DEBUG: u8 = 4;
+pub const LOG_TRACE: u8 = 5;
+
+/// Default number of expansions between progress messages
+pub const DEFAULT_LOG_INTERVAL: u64 = 10_000;
+
+/// Receives log messages on native builds.
+pub type LogSink = fn(level: u8, message: &str);
+
+/// Current log level
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LOG_OFF);
+
+/// Number of expansions between progress messages
+static LOG_INTERVAL: AtomicU64 = AtomicU64::new(DEFAULT_LOG_INTERVAL);
+
+lazy_static! {
+    /// Native log sink (messages go to stderr when unset)
+    static ref LOG_SINK: Mutex<Option<LogSink>> = Mutex::new(None);
+}
+
+/// A macro to provide `println!(..)`-style syntax for logging at a level.
+/// * The message is only formatted when the level is enabled.
+macro_rules! log {
+    ( $level:expr, $( $t:tt )* ) => {
+        if $crate::logging::log_enabled($level) {
+            $crate::logging::write_log($level, &format!( $( $t )* ));
+        }
+    }
+}
+
+/// Set the log level (0 = off, 1 = error, 2 = warn, 3 = info, 4 = debug, 5 = trace).
+///
+/// # Parameters
+/// * `level` - New log level
+#[wasm_bindgen]
+pub fn set_log_level(level: u8) {
+    LOG_LEVEL.store(level, Ordering::Relaxed);
+}
+
+/// Set how many expansions pass between solver progress messages.
+///
+/// # Parameters
+/// * `interval` - Expansions between messages (at least 1)
+#[wasm_bindgen]
+pub fn set_log_interval(interval: u32) {
+    LOG_INTERVAL.store(u64::from(interval.max(1)), Ordering::Relaxed);
+}
+
+/// Set where log messages go on native builds.
+///
+/// # Parameters
+/// * `sink` - Function receiving each message, or None for stderr
+pub fn set_log_sink(sink: Option<LogSink>) {
+    *LOG_SINK.lock().unwrap() = sink;
+}
+
+/// Check if messages at a level are logged.
+///
+/// # Parameters
+/// * `level` - Level of the message
+///
+/// # Returns
+/// Whether the message would be logged
+#[inline]
+pub fn log_enabled(level: u8) -> bool {
+    level != LOG_OFF && level <= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Get the number of expansions between progress messages.
+///
+/// # Returns
+/// Progress message interval
+#[inline]
+pub fn log_interval() -> u64 {
+    LOG_INTERVAL.load(Ordering::Relaxed)
+}
+
+/// Write a message to the console (wasm) or the log sink (native).
+///
+/// # Parameters
+/// * `level` - Level of the message
+/// * `message` - The message
+pub fn write_log(level: u8, message: &str) {
+    if cfg!(target_arch = "wasm32") {
+        web_sys::console::log_1(&message.into());
+        return;
+    }
+    match *LOG_SINK.lock().unwrap() {
+        Some(sink) => sink(level, message),
+        None => eprintln!("{}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::solver;
+    use crate::tile::{Tile, BLANK_TILE};
+
+    lazy_static! {
+        /// Messages captured by the test sink
+        static ref CAPTURED: Mutex<Vec<(u8, String)>> = Mutex::new(Vec::new());
+    }
+
+    fn capture(level: u8, message: &str) {
+        CAPTURED.lock().unwrap().push((level, message.to_string()));
+    }
+
+    /// Test solver progress messages reach the native sink
+    #[test]
+    fn test_solver_progress_logging() {
+        set_log_sink(Some(capture));
+        set_log_interval(1);
+        set_log_level(LOG_DEBUG);
+        assert!(log_enabled(LOG_DEBUG));
+        assert!(!log_enabled(LOG_TRACE));
+        let tiles = vec![
+            Tile::new(1), Tile::new(2), Tile::new(3),
+            Tile::new(BLANK_TILE), Tile::new(4), Tile::new(6),
+            Tile::new(7), Tile::new(5), Tile::new(8)
+        ];
+        let board = Board::new(3, None, 0, Some(tiles.into_boxed_slice()));
+        solver::solve(&board, &solver::SolveOptions::default()).unwrap();
+        set_log_level(LOG_OFF);
+        set_log_interval(DEFAULT_LOG_INTERVAL as u32);
+        set_log_sink(None);
+        assert!(!log_enabled(LOG_ERROR));
+        let captured = CAPTURED.lock().unwrap();
+        let record = captured.iter()
+            .find(|(_, message)| message.starts_with("expanded 1 boards"))
+            .unwrap();
+        assert_eq!(record.0, LOG_DEBUG);
+        assert!(record.1.contains("frontier"));
+        assert!(record.1.contains("best h"));
+    }
+}
@@ -0,0 +1,406 @@
+use std::io::{self, Write};
+
+use super::board::Board;
+use super::format_board_info;
+use super::solver::Solution;
+
+/// Header of the CSV written by `TraceRecorder::to_csv` and `CsvTraceWriter`
+pub const SAMPLE_CSV_HEADER: &str = "expansions,frontier_size,closed_size,current_f,elapsed_ms,bytes_estimate";
+
+/// Hooks called by the solver as it searches.
+/// * Every hook defaults to doing nothing, so observers only implement what they need.
+/// * The solver is generic over the observer, so `NoopObserver` compiles away entirely.
+pub trait SolverObserver {
+    /// Called when a board is taken off the frontier and expanded.
+    ///
+    /// # Parameters
+    /// * `board` - The board being expanded
+    /// * `g` - Depth of the board
+    /// * `h` - Heuristic value of the board (manhattan + linear conflicts)
+    fn on_expand(&mut self, _board: &Board, _g: i32, _h: i32) {}
+
+    /// Called when a new board is pushed onto the frontier.
+    ///
+    /// # Parameters
+    /// * `board` - The new board
+    /// * `via` - Move that produced the board
+    fn on_generate(&mut self, _board: &Board, _via: char) {}
+
+    /// Called when a new board is skipped because it was already expanded.
+    ///
+    /// # Parameters
+    /// * `board` - The skipped board (not costed)
+    /// * `via` - Move that produced the board
+    fn on_skip(&mut self, _board: &Board, _via: char) {}
+
+    /// Called once when the solution is found.
+    ///
+    /// # Parameters
+    /// * `solution` - The solution
+    fn on_goal(&mut self, _solution: &Solution) {}
+
+    /// Called when the search starts and whenever its proven lower bound rises.
+    /// * Optimal searches only; the bound never decreases over a search.
+    ///
+    /// # Parameters
+    /// * `progress` - The search so far
+    fn on_progress(&mut self, _progress: &SearchProgress) {}
+
+    /// Number of expansions between calls to `on_sample`.
+    /// * Read once per expansion; the default of `None` never samples, so observers that
+    ///   don't sample cost no extra clock reads.
+    ///
+    /// # Returns
+    /// Sampling interval, or `None` to not sample
+    fn sample_every(&self) -> Option<u64> {
+        None
+    }
+
+    /// Called every `sample_every` expansions with the size of the search structures.
+    /// * A* and greedy searches only; IDA* keeps no frontier or closed set.
+    ///
+    /// # Parameters
+    /// * `sample` - The search structures before the expansion
+    fn on_sample(&mut self, _sample: &SearchSample) {}
+}
+
+/// Progress of an optimal search, e.g. for an "estimated moves remaining" display.
+///
+/// # Attributes
+/// * `nodes_expanded` - Boards expanded so far
+/// * `proven_lower_bound` - No solution is shorter than this: the IDA* bound being
+///   searched, or the smallest cost on the A* frontier
+/// * `elapsed_ms` - Milliseconds since the search started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchProgress {
+    pub nodes_expanded: u64,
+    pub proven_lower_bound: i32,
+    pub elapsed_ms: f64,
+}
+
+/// Size of the search structures, sampled before an expansion, e.g. to plot their growth.
+///
+/// # Attributes
+/// * `expansions` - Boards expanded so far
+/// * `frontier_size` - Boards waiting on the frontier, including stale ones
+/// * `closed_size` - Boards already expanded
+/// * `current_f` - Cost of the board about to be expanded
+/// * `elapsed_ms` - Milliseconds since the search started
+/// * `bytes_estimate` - Memory of the search structures as counted against
+///   `SolveOptions::max_memory_bytes`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchSample {
+    pub expansions: u64,
+    pub frontier_size: usize,
+    pub closed_size: usize,
+    pub current_f: i32,
+    pub elapsed_ms: f64,
+    pub bytes_estimate: u64,
+}
+
+impl SearchSample {
+    /// Format the sample as a row of the sample CSV.
+    ///
+    /// # Returns
+    /// Comma separated values in `SAMPLE_CSV_HEADER` order, without a line break
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{:.3},{}",
+            self.expansions,
+            self.frontier_size,
+            self.closed_size,
+            self.current_f,
+            self.elapsed_ms,
+            self.bytes_estimate,
+        )
+    }
+}
+
+/// Observer that ignores every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl SolverObserver for NoopObserver {}
+
+/// An expansion recorded by `TraceRecorder`.
+///
+/// # Attributes
+/// * `board` - Expanded board, in board info format
+/// * `g` - Depth of the board
+/// * `h` - Heuristic value of the board
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpandEvent {
+    pub board: String,
+    pub g: i32,
+    pub h: i32,
+}
+
+/// A generated board recorded by `TraceRecorder`.
+///
+/// # Attributes
+/// * `board` - Generated board, in board info format
+/// * `via` - Move that produced the board
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateEvent {
+    pub board: String,
+    pub via: char,
+}
+
+/// Observer that records every event in order, e.g. for a search visualizer.
+///
+/// # Attributes
+/// * `expanded` - Expansions, in the order they happened
+/// * `generated` - Generated boards, in the order they were pushed
+/// * `goal` - Solution moves, once found
+/// * `samples` - Search structure sizes, every `sample_every` expansions
+/// * `sample_every` - Expansions between samples, if sampling
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TraceRecorder {
+    pub expanded: Vec<ExpandEvent>,
+    pub generated: Vec<GenerateEvent>,
+    pub goal: Option<String>,
+    pub samples: Vec<SearchSample>,
+    pub sample_every: Option<u64>,
+}
+
+impl TraceRecorder {
+    /// Create an empty recorder.
+    pub fn new() -> TraceRecorder {
+        TraceRecorder::default()
+    }
+
+    /// Also sample the size of the search structures.
+    ///
+    /// # Parameters
+    /// * `every` - Expansions between samples (at least 1 is used)
+    ///
+    /// # Returns
+    /// The recorder
+    pub fn sampling(mut self, every: u64) -> TraceRecorder {
+        self.sample_every = Some(every.max(1));
+        self
+    }
+
+    /// Format the samples as CSV, e.g. to plot frontier and closed set growth.
+    ///
+    /// # Returns
+    /// `SAMPLE_CSV_HEADER` and one row per sample, each ending in a line break
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", SAMPLE_CSV_HEADER);
+        for sample in &self.samples {
+            csv.push_str(&sample.to_csv_row());
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Format the recorded events as a JSON object.
+    ///
+    /// # Returns
+    /// JSON representation of the trace
+    pub fn to_json(&self) -> String {
+        let expanded: Vec<String> = self.expanded.iter()
+            .map(|event| format!(
+                "{{\"board\":\"{}\",\"g\":{},\"h\":{}}}",
+                event.board, event.g, event.h
+            ))
+            .collect();
+        let generated: Vec<String> = self.generated.iter()
+            .map(|event| format!("{{\"board\":\"{}\",\"via\":\"{}\"}}", event.board, event.via))
+            .collect();
+        let goal = match &self.goal {
+            Some(moves) => format!("\"{}\"", moves),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"expanded\":[{}],\"generated\":[{}],\"goal\":{}}}",
+            expanded.join(","),
+            generated.join(","),
+            goal,
+        )
+    }
+}
+
+impl SolverObserver for TraceRecorder {
+    fn on_expand(&mut self, board: &Board, g: i32, h: i32) {
+        self.expanded.push(ExpandEvent { board: format_board_info(board), g, h });
+    }
+
+    fn on_generate(&mut self, board: &Board, via: char) {
+        self.generated.push(GenerateEvent { board: format_board_info(board), via });
+    }
+
+    fn on_goal(&mut self, solution: &Solution) {
+        self.goal = Some(solution.moves.clone());
+    }
+
+    fn sample_every(&self) -> Option<u64> {
+        self.sample_every
+    }
+
+    fn on_sample(&mut self, sample: &SearchSample) {
+        self.samples.push(*sample);
+    }
+}
+
+/// Observer that writes search structure sizes as CSV while the search runs.
+/// * Each row is flushed as it is written, so an interrupted search still leaves a
+///   usable file.
+///
+/// # Attributes
+/// * `output` - Where the CSV is written
+/// * `every` - Expansions between rows
+/// * `error` - First error writing the CSV; nothing more is written after it
+#[derive(Debug)]
+pub struct CsvTraceWriter<W: Write> {
+    output: W,
+    every: u64,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> CsvTraceWriter<W> {
+    /// Start the CSV by writing its header.
+    ///
+    /// # Parameters
+    /// * `output` - Where to write the CSV
+    /// * `every` - Expansions between rows (at least 1 is used)
+    ///
+    /// # Returns
+    /// The writer, or the error writing the header
+    pub fn new(mut output: W, every: u64) -> io::Result<CsvTraceWriter<W>> {
+        writeln!(output, "{}", SAMPLE_CSV_HEADER)?;
+        output.flush()?;
+        Ok(CsvTraceWriter { output, every: every.max(1), error: None })
+    }
+
+    /// Finish writing.
+    ///
+    /// # Returns
+    /// The output, or the first error writing a row
+    pub fn finish(self) -> io::Result<W> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.output),
+        }
+    }
+}
+
+impl<W: Write> SolverObserver for CsvTraceWriter<W> {
+    fn sample_every(&self) -> Option<u64> {
+        Some(self.every)
+    }
+
+    fn on_sample(&mut self, sample: &SearchSample) {
+        if self.error.is_none() {
+            let written = writeln!(self.output, "{}", sample.to_csv_row()).and_then(|_| self.output.flush());
+            self.error = written.err();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::HEURISTICS;
+    use crate::solver::{self, Algorithm, SolveError, SolveOptions, Solver};
+    use crate::try_parse_board_info;
+
+    /// Test recorded event counts match the reported stats
+    #[test]
+    fn test_trace_recorder() {
+        let board = try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        let mut recorder = TraceRecorder::new();
+        let options = SolveOptions::default();
+        let solution = solver::solve_observed(&board, &options, &mut recorder).unwrap();
+        assert_eq!(solution, solver::solve(&board, &SolveOptions::default()).unwrap());
+        assert_eq!(recorder.expanded.len() as u64, solution.stats.nodes_expanded);
+        assert_eq!(recorder.generated.len() as u64, solution.stats.nodes_generated);
+        assert_eq!(recorder.goal.as_deref(), Some("RDR"));
+        // The root is expanded first
+        assert_eq!(
+            recorder.expanded[0],
+            ExpandEvent { board: "3,1,2,3,-1,4,6,7,5,8".to_string(), g: 0, h: 3 }
+        );
+        assert_eq!(recorder.generated[0].via, 'U');
+        let json = recorder.to_json();
+        assert!(json.starts_with("{\"expanded\":[{\"board\":\"3,1,2,3,-1,4,6,7,5,8\",\"g\":0,\"h\":3}"));
+        assert!(json.ends_with("\"goal\":\"RDR\"}"));
+    }
+
+    /// Records every bound reported through `on_progress`.
+    #[derive(Default)]
+    struct BoundRecorder {
+        bounds: Vec<i32>,
+        elapsed_ms: Vec<f64>,
+    }
+
+    impl SolverObserver for BoundRecorder {
+        fn on_progress(&mut self, progress: &SearchProgress) {
+            self.bounds.push(progress.proven_lower_bound);
+            self.elapsed_ms.push(progress.elapsed_ms);
+        }
+    }
+
+    /// Test the proven lower bound rises to the solution length for both optimal searches
+    #[test]
+    fn test_progress_lower_bound() {
+        for seed in 405..409 {
+            let board = Board::generate(3, seed, 200).unwrap();
+            for &heuristic in &HEURISTICS {
+                for &algorithm in &[Algorithm::AStar, Algorithm::IdaStar] {
+                    let options = SolveOptions::new().heuristic(heuristic).algorithm(algorithm);
+                    let root_bound = Solver::with_options(3, board.tiles().to_vec(), &options).proven_lower_bound();
+                    let mut recorder = BoundRecorder::default();
+                    let solution = solver::solve_observed(&board, &options, &mut recorder).unwrap();
+                    assert_eq!(recorder.bounds[0], root_bound);
+                    assert!(recorder.bounds.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", recorder.bounds);
+                    assert_eq!(*recorder.bounds.last().unwrap(), solution.moves.len() as i32);
+                    assert!(recorder.elapsed_ms.windows(2).all(|pair| pair[0] <= pair[1]));
+                    assert!(*recorder.elapsed_ms.last().unwrap() <= solution.stats.millis);
+                }
+            }
+        }
+    }
+
+    /// Test sampled search structure sizes are written as CSV, both recorded and
+    /// streamed
+    #[test]
+    fn test_trace_csv() {
+        let board = Board::generate(3, 410, 200).unwrap();
+        let options = SolveOptions::new();
+        let mut recorder = TraceRecorder::new().sampling(10);
+        let solution = solver::solve_observed(&board, &options, &mut recorder).unwrap();
+        let expected_rows = solution.stats.nodes_expanded.div_ceil(10) as usize;
+        assert!(expected_rows >= 2);
+        assert_eq!(recorder.samples.len(), expected_rows);
+        let csv = recorder.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "expansions,frontier_size,closed_size,current_f,elapsed_ms,bytes_estimate");
+        assert_eq!(lines.len(), expected_rows + 1);
+        let rows: Vec<Vec<f64>> = lines[1..].iter()
+            .map(|line| line.split(',').map(|value| value.parse().unwrap()).collect())
+            .collect();
+        assert!(rows.iter().all(|row| row.len() == 6));
+        assert!(rows.windows(2).all(|pair| {
+            let (before, after) = (&pair[0], &pair[1]);
+            after[0] == before[0] + 10.0 && after[2] > before[2] && after[4] >= before[4] && after[5] >= before[5]
+        }));
+        // The estimate is the one checked against the memory budget: a budget just under
+        // a sample gives up by that sample's expansion, and one equal to it after
+        let sampled = recorder.samples[1];
+        let gave_up_after = |budget: u64| match solver::solve(&board, &options.clone().max_memory_bytes(budget)) {
+            Err(SolveError::MemoryBudget(reason)) => {
+                let expanded = reason.split("after expanding ").nth(1).unwrap().split(' ').next().unwrap();
+                expanded.parse::<u64>().unwrap()
+            }
+            _ => u64::MAX,
+        };
+        assert!(gave_up_after(sampled.bytes_estimate - 1) <= sampled.expansions);
+        assert!(gave_up_after(sampled.bytes_estimate) > sampled.expansions);
+        let mut writer = CsvTraceWriter::new(Vec::new(), 10).unwrap();
+        solver::solve_observed(&board, &options, &mut writer).unwrap();
+        let streamed = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(streamed.lines().count(), lines.len());
+        assert!(streamed.starts_with(SAMPLE_CSV_HEADER));
+    }
+}
@@ -0,0 +1,463 @@
+//! Compact encodings of board states, e.g. for hashing, URLs or position databases.
+//!
+//! These formats are stable; existing encodings must keep decoding to the same boards.
+//!
+//! * Packed (n <= 4): a u64 where the tile at row-major index i occupies bits
+//!   4i..4i+4, storing its symbol - 1, and the blank is stored as 0xF. Bits above the
+//!   n^2 tiles are zero, so the solved 3x3 board packs to `0xF_7654_3210`.
+//! * Bytes (any size): one byte per tile in row-major order, storing the symbol, with
+//!   the blank stored as 0.
+//! * Lehmer rank (n <= 4): the index of the tile sequence among all permutations of
+//!   the tiles in lexicographic order, counting the blank as the highest symbol. The
+//!   solved board ranks 0 and ranks are dense, from 0 to (n^2)! - 1, so they can index
+//!   arrays; only every other rank is a solvable board.
+//!
+//! Solutions have a versioned compact form too, see `Solution::encode_compact`.
+
+#[cfg(alloc_only)]
+use alloc::{string::String, vec, vec::Vec};
+use core::error::Error;
+use core::fmt;
+
+use super::board::{Board, BoardError};
+use super::error_code::ErrorCode;
+use super::tile::*;
+
+/// Largest board size that fits in a packed u64
+pub const MAX_PACKED_SIZE: i32 = 4;
+
+/// Nibble used for the blank tile
+const PACKED_BLANK: u64 = 0xF;
+
+/// Encode tile symbols into a u64, 4 bits per tile.
+/// * The tile at index i occupies bits 4i..4i+4, storing its symbol - 1.
+/// * The blank tile is stored as 0xF.
+///
+/// # Parameters
+/// * `tiles` - Board tiles (at most 16)
+///
+/// # Returns
+/// Packed tiles
+pub fn encode_tiles(tiles: &[Tile]) -> u64 {
+    let mut packed = 0;
+    for (i, tile) in tiles.iter().enumerate() {
+        let nibble = if tile.is_blank() { PACKED_BLANK } else { (tile.symbol() - 1) as u64 & 0xF };
+        packed |= nibble << (4 * i);
+    }
+    packed
+}
+
+/// Decode tiles packed with `encode_tiles`.
+/// * Bits above the n^2 tiles of the board are ignored.
+///
+/// # Parameters
+/// * `n` - Size of the board (at most `MAX_PACKED_SIZE`)
+/// * `packed` - Packed tiles
+///
+/// # Returns
+/// Board tiles
+pub fn decode_tiles(n: i32, packed: u64) -> Vec<Tile> {
+    (0..n * n)
+        .map(|i| {
+            let nibble = (packed >> (4 * i)) & 0xF;
+            if nibble == PACKED_BLANK {
+                Tile::new(BLANK_TILE)
+            } else {
+                Tile::new(nibble as i32 + 1)
+            }
+        })
+        .collect()
+}
+
+/// Problems packing a board.
+///
+/// # Error codes
+/// * 111 - `TooLarge`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackError {
+    /// Board is larger than `MAX_PACKED_SIZE`, so its tiles don't fit in a u64
+    TooLarge(i32),
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PackError::TooLarge(n) =>
+                write!(f, "a {}x{} board is too large to pack, the maximum size is {}", n, n, MAX_PACKED_SIZE),
+        }
+    }
+}
+
+impl Error for PackError {}
+
+impl ErrorCode for PackError {
+    fn code(&self) -> u32 {
+        match self {
+            PackError::TooLarge(_) => 111,
+        }
+    }
+}
+
+/// Problems turning a Lehmer rank back into a board.
+///
+/// # Error codes
+/// * 124 - `InvalidSize`
+/// * 125 - `OutOfRange`
+/// * 126 - `Unsolvable`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RankError {
+    /// Board size is below 1 or above `MAX_PACKED_SIZE`
+    InvalidSize(i32),
+    /// Rank is not below the number of permutations of the tiles
+    OutOfRange { rank: u64, count: u64 },
+    /// Rank is of an unsolvable board, and only solvable boards were requested
+    Unsolvable(u64),
+}
+
+impl fmt::Display for RankError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RankError::InvalidSize(n) =>
+                write!(f, "can only rank boards of size 1 to {}, got {}", MAX_PACKED_SIZE, n),
+            RankError::OutOfRange { rank, count } =>
+                write!(f, "rank {} is out of range, there are {} permutations", rank, count),
+            RankError::Unsolvable(rank) => write!(f, "rank {} is an unsolvable board", rank),
+        }
+    }
+}
+
+impl Error for RankError {}
+
+impl ErrorCode for RankError {
+    fn code(&self) -> u32 {
+        match self {
+            RankError::InvalidSize(_) => 124,
+            RankError::OutOfRange { .. } => 125,
+            RankError::Unsolvable(_) => 126,
+        }
+    }
+}
+
+/// Pack a board into a u64.
+///
+/// # Parameters
+/// * `board` - Board of size at most `MAX_PACKED_SIZE`
+///
+/// # Returns
+/// Packed tiles, or an error if the board is too large
+pub fn encode(board: &Board) -> Result<u64, PackError> {
+    if board.size() > MAX_PACKED_SIZE {
+        return Err(PackError::TooLarge(board.size()));
+    }
+    Ok(encode_tiles(board.tiles()))
+}
+
+/// Unpack a board packed with `encode`.
+/// * Keys are not validated; use `Board::validate` on keys from untrusted sources.
+///
+/// # Parameters
+/// * `n` - Size of the board (at most `MAX_PACKED_SIZE`)
+/// * `key` - Packed tiles
+///
+/// # Returns
+/// The board, uncosted
+pub fn decode(n: i32, key: u64) -> Board {
+    Board::new(n, None, 0, Some(decode_tiles(n, key).into_boxed_slice()))
+}
+
+/// Encode a board of any size as one byte per tile.
+///
+/// # Parameters
+/// * `board` - Board with at most 255 tiles
+///
+/// # Returns
+/// Tile symbols in row-major order, with the blank as 0
+pub fn encode_bytes(board: &Board) -> Vec<u8> {
+    board.tiles().iter().map(|tile| if tile.is_blank() { 0 } else { tile.symbol() as u8 }).collect()
+}
+
+/// Decode a board encoded with `encode_bytes`.
+///
+/// # Parameters
+/// * `n` - Size of the board
+/// * `bytes` - One byte per tile
+///
+/// # Returns
+/// The board, costed against the solved board, or the problem with the tiles
+pub fn decode_bytes(n: i32, bytes: &[u8]) -> Result<Board, BoardError> {
+    let tiles = bytes.iter()
+        .map(|&byte| Tile::new(if byte == 0 { BLANK_TILE } else { byte as i32 }))
+        .collect();
+    Board::try_new(n, tiles)
+}
+
+/// Get the value stored in a cell of packed tiles.
+/// * The blank is returned as the last value, so values form a permutation of the cells.
+///
+/// # Parameters
+/// * `packed` - Packed tiles
+/// * `cells` - Number of cells
+/// * `index` - Index of the cell
+///
+/// # Returns
+/// Value of the cell, from 0 to `cells - 1`
+fn _cell(packed: u64, cells: usize, index: usize) -> usize {
+    let nibble = (packed >> (4 * index)) & 0xF;
+    if nibble == PACKED_BLANK { cells - 1 } else { nibble as usize }
+}
+
+/// Count the permutations of the cells of a board, the number of Lehmer ranks.
+///
+/// # Parameters
+/// * `cells` - Number of cells (at most 16)
+///
+/// # Returns
+/// `cells!`
+pub fn permutation_count(cells: usize) -> u64 {
+    (1..=cells as u64).product()
+}
+
+/// Rank packed tiles among all permutations of their cells (Lehmer code).
+/// * Works on any number of cells up to 16, so boards need not be square.
+///
+/// # Parameters
+/// * `packed` - Packed tiles, from `encode_tiles`
+/// * `cells` - Number of cells
+///
+/// # Returns
+/// Rank from 0 to `cells! - 1`
+pub fn rank_packed(packed: u64, cells: usize) -> u64 {
+    let mut rank = 0;
+    for i in 0..cells {
+        let value = _cell(packed, cells, i);
+        let smaller_after = (i + 1..cells).filter(|&j| _cell(packed, cells, j) < value).count();
+        rank = rank * (cells - i) as u64 + smaller_after as u64;
+    }
+    rank
+}
+
+/// Get the packed tiles of a Lehmer rank, the inverse of `rank_packed`.
+///
+/// # Parameters
+/// * `rank` - Rank from 0 to `cells! - 1`
+/// * `cells` - Number of cells
+///
+/// # Returns
+/// Packed tiles
+pub fn unrank_packed(mut rank: u64, cells: usize) -> u64 {
+    // Digits of the rank in the factorial number system, least significant last
+    let mut digits = vec![0; cells];
+    for i in (0..cells).rev() {
+        digits[i] = (rank % (cells - i) as u64) as usize;
+        rank /= (cells - i) as u64;
+    }
+    let mut remaining: Vec<usize> = (0..cells).collect();
+    digits.iter().enumerate().fold(0, |packed, (i, &digit)| {
+        let value = remaining.remove(digit);
+        let nibble = if value == cells - 1 { PACKED_BLANK } else { value as u64 };
+        packed | nibble << (4 * i)
+    })
+}
+
+/// Base64url alphabet (RFC 4648, section 5)
+const BASE64URL_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Length of a packed u64 encoded as unpadded base64url
+pub const BASE64URL_LEN: usize = 11;
+
+/// Encode packed tiles as unpadded base64url.
+/// * The 8 bytes of `packed` are encoded most significant first, so the final
+///   character only carries 4 bits and its 2 low bits are always zero.
+///
+/// # Parameters
+/// * `packed` - Packed tiles
+///
+/// # Returns
+/// 11-character token safe for URLs
+pub fn encode_base64url(packed: u64) -> String {
+    (0..BASE64URL_LEN)
+        .map(|i| {
+            // Shift so the 6 bits of this character are the lowest, padding the end
+            let sextet = ((packed as u128) << 2 >> (6 * (BASE64URL_LEN - 1 - i))) & 0x3F;
+            BASE64URL_ALPHABET[sextet as usize] as char
+        })
+        .collect()
+}
+
+/// Decode a token made by `encode_base64url`.
+/// * Only the exact 11-character form is accepted; padding, other alphabets and
+///   nonzero trailing bits are rejected so every value has a single token.
+///
+/// # Parameters
+/// * `token` - Base64url token
+///
+/// # Returns
+/// Packed tiles, if the token is valid
+pub fn decode_base64url(token: &str) -> Option<u64> {
+    if token.len() != BASE64URL_LEN {
+        return None;
+    }
+    let mut bits: u128 = 0;
+    for c in token.bytes() {
+        let sextet = BASE64URL_ALPHABET.iter().position(|&symbol| symbol == c)?;
+        bits = bits << 6 | sextet as u128;
+    }
+    if bits & 0x3 != 0 {
+        return None;
+    }
+    Some((bits >> 2) as u64)
+}
+
+/// Encode bytes as unpadded base64url.
+///
+/// # Parameters
+/// * `bytes` - The bytes
+///
+/// # Returns
+/// Base64url text, 4 characters per 3 bytes
+pub fn encode_base64url_bytes(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            text.push(BASE64URL_ALPHABET[(bits >> (18 - 6 * i) & 0x3F) as usize] as char);
+        }
+    }
+    text
+}
+
+/// Decode text made by `encode_base64url_bytes`.
+/// * Padding, other alphabets and nonzero trailing bits are rejected so every byte
+///   string has a single encoding.
+///
+/// # Parameters
+/// * `text` - Base64url text
+///
+/// # Returns
+/// The bytes, if the text is valid
+pub fn decode_base64url_bytes(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3 + 2);
+    for chunk in text.as_bytes().chunks(4) {
+        let mut bits = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let sextet = BASE64URL_ALPHABET.iter().position(|&symbol| symbol == c)?;
+            bits |= (sextet as u32) << (18 - 6 * i);
+        }
+        let byte_count = chunk.len() - 1;
+        if bits & (0xFF_FFFF >> (8 * byte_count)) != 0 {
+            return None;
+        }
+        bytes.extend((0..byte_count).map(|i| (bits >> (16 - 8 * i)) as u8));
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    /// Test Lehmer ranks round-trip, start from the solved board and cover every 3x3
+    /// permutation, half of them solvable
+    #[test]
+    fn test_lehmer_rank() {
+        for n in 2..=4 {
+            for seed in 0..20 {
+                let board = Board::generate(n, 414 + seed, 100).unwrap();
+                let rank = board.lehmer_rank();
+                assert!(rank < permutation_count((n * n) as usize));
+                assert_eq!(Board::from_lehmer_rank(n, rank, true), Ok(board));
+            }
+            let solved = Board::new(n, None, 0, None);
+            assert_eq!(solved.lehmer_rank(), 0);
+            assert_eq!(Board::from_lehmer_rank(n, 0, true), Ok(solved));
+        }
+        let count = permutation_count(9);
+        assert_eq!(count, 362_880);
+        let valid = (0..count)
+            .filter(|&rank| {
+                let packed = unrank_packed(rank, 9);
+                rank_packed(packed, 9) == rank && Board::validate(3, &decode_tiles(3, packed)).is_ok()
+            })
+            .count();
+        assert_eq!(valid as u64, count);
+        let sample: Vec<u64> = (0..count).step_by(97).collect();
+        let solvable = sample.iter().filter(|&&rank| Board::from_lehmer_rank(3, rank, true).is_ok()).count();
+        assert!((solvable as f64 / sample.len() as f64 - 0.5).abs() < 0.05, "{} solvable", solvable);
+        assert_eq!(Board::from_lehmer_rank(3, count, false), Err(RankError::OutOfRange { rank: count, count }));
+        let unsolvable = Board::try_from(vec![2, 1, 3, 4, 5, 6, 7, 8, -1]).unwrap();
+        let rank = unsolvable.lehmer_rank();
+        assert_eq!(Board::from_lehmer_rank(3, rank, true), Err(RankError::Unsolvable(rank)));
+        assert_eq!(Board::from_lehmer_rank(3, rank, false), Ok(unsolvable));
+        assert_eq!(Board::from_lehmer_rank(5, 0, false).unwrap_err().code(), 124);
+    }
+
+    /// Test packing round trip
+    #[test]
+    fn test_pack_round_trip() {
+        let tiles = Tile::generate_tiles(4);
+        let packed = encode_tiles(&tiles);
+        assert_eq!(packed, 0xFEDC_BA98_7654_3210);
+        assert_eq!(*decode_tiles(4, packed), *tiles);
+        let tiles_2 = Tile::generate_tiles(3);
+        assert_eq!(*decode_tiles(3, encode_tiles(&tiles_2)), *tiles_2);
+    }
+
+    /// Test known encodings, which databases depend on and must never change
+    #[test]
+    fn test_pack_pinned_encodings() {
+        let solved_3 = Board::new(3, None, 0, None);
+        assert_eq!(encode(&solved_3), Ok(0xF_7654_3210));
+        let board = crate::try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        assert_eq!(encode(&board), Ok(0x7_4653_F210));
+        assert_eq!(decode(3, 0x7_4653_F210), board);
+        let reversed = Board::try_from(vec![15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, -1]).unwrap();
+        assert_eq!(encode(&reversed), Ok(0xF012_3456_789A_BCDE));
+        assert_eq!(encode(&Board::new(5, None, 0, None)), Err(PackError::TooLarge(5)));
+        assert_eq!(encode_bytes(&board), vec![1, 2, 3, 0, 4, 6, 7, 5, 8]);
+        assert_eq!(decode_bytes(3, &[1, 2, 3, 0, 4, 6, 7, 5, 8]), Ok(board));
+        let solved_5 = Board::new(5, None, 0, None);
+        let mut expected: Vec<u8> = (1..25).collect();
+        expected.push(0);
+        assert_eq!(encode_bytes(&solved_5), expected);
+        assert_eq!(decode_bytes(5, &expected), Ok(solved_5));
+        assert_eq!(decode_bytes(3, &[1, 1, 3, 0, 4, 6, 7, 5, 8]), Err(BoardError::DuplicateTile(1)));
+    }
+
+    /// Test base64url round trips and rejects malformed tokens
+    #[test]
+    fn test_base64url() {
+        assert_eq!(encode_base64url(0), "AAAAAAAAAAA");
+        assert_eq!(encode_base64url(u64::MAX), "__________8");
+        assert_eq!(encode_base64url(0xFEDC_BA98_7654_3210), "_ty6mHZUMhA");
+        for &packed in &[0, 1, u64::MAX, 0xFEDC_BA98_7654_3210, 0x0123_4567_89AB_CDEF] {
+            assert_eq!(decode_base64url(&encode_base64url(packed)), Some(packed));
+        }
+        assert_eq!(decode_base64url("_ty6mHZUMhA="), None);
+        assert_eq!(decode_base64url("_ty6mHZUMh"), None);
+        assert_eq!(decode_base64url("_ty6mHZ+MhA"), None);
+        // Trailing bits beyond the 64 encoded must be zero
+        assert_eq!(decode_base64url("AAAAAAAAAAB"), None);
+    }
+
+    /// Test base64url byte strings match RFC 4648 and reject non-canonical text
+    #[test]
+    fn test_base64url_bytes() {
+        assert_eq!(encode_base64url_bytes(b""), "");
+        assert_eq!(encode_base64url_bytes(b"M"), "TQ");
+        assert_eq!(encode_base64url_bytes(b"Ma"), "TWE");
+        assert_eq!(encode_base64url_bytes(b"Man"), "TWFu");
+        assert_eq!(encode_base64url_bytes(&[0xFB, 0xFF]), "-_8");
+        for len in 0..10 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 37 + 200) as u8).collect();
+            assert_eq!(decode_base64url_bytes(&encode_base64url_bytes(&bytes)), Some(bytes));
+        }
+        assert_eq!(decode_base64url_bytes("TQ=="), None);
+        assert_eq!(decode_base64url_bytes("TWFuT"), None);
+        assert_eq!(decode_base64url_bytes("TR"), None);
+        assert_eq!(decode_base64url_bytes("TW+u"), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! High-level puzzle API.
+//!
+//! A `Puzzle` holds the current board and the goal it is solved towards, and keeps the
+//! board's search bookkeeping (depth, path and cost) consistent, so callers never touch
+//! it:
+//!
+//! ```
+//! use sliding_tile_puzzle_solver::puzzle::Puzzle;
+//! use sliding_tile_puzzle_solver::solver::SolveOptions;
+//!
+//! let mut puzzle = Puzzle::new(3);
+//! puzzle.scramble(7, 40);
+//! let solution = puzzle.solve(&SolveOptions::default()).unwrap();
+//! assert!(puzzle.verify(&solution));
+//! ```
+
+use super::board::{Board, BoardError, Heuristic, IllegalMove, Notation};
+use super::rng::RngSource;
+use super::solver::{self, Solution, SolveError, SolveOptions};
+use super::tile::Tile;
+
+/// A board and the goal it is solved towards.
+/// * The board is always at depth 0 with an empty path, and costed against the goal.
+///
+/// # Attributes
+/// * `board` - Current board
+/// * `goal` - Solved board of the same size
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    board: Board,
+    goal: Board,
+}
+
+impl Puzzle {
+    /// Create a solved puzzle.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    ///
+    /// # Panics
+    /// If the size is less than 1
+    pub fn new(n: i32) -> Puzzle {
+        assert!(n >= 1, "a puzzle must be at least 1x1, got {}", n);
+        Puzzle::from_board(&Board::new(n, None, 0, None))
+    }
+
+    /// Create a puzzle from tiles, validating the layout.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `tiles` - Board tiles
+    ///
+    /// # Returns
+    /// The puzzle, or the problem with the layout
+    pub fn from_tiles(n: i32, tiles: Vec<Tile>) -> Result<Puzzle, BoardError> {
+        Board::try_new(n, tiles).map(|board| Puzzle::from_board(&board))
+    }
+
+    /// Create a puzzle starting from a board.
+    /// * Any depth, path or cost the board carries is reset.
+    ///
+    /// # Parameters
+    /// * `board` - Starting board
+    pub fn from_board(board: &Board) -> Puzzle {
+        let mut puzzle = Puzzle {
+            board: board.clone(),
+            goal: Board::new(board.size(), None, -1, None),
+        };
+        puzzle._reset();
+        puzzle
+    }
+
+    /// Get the current board.
+    ///
+    /// # Returns
+    /// The board, costed against the goal
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Get the goal.
+    ///
+    /// # Returns
+    /// The solved board
+    pub fn goal(&self) -> &Board {
+        &self.goal
+    }
+
+    /// Check if the current board is the goal.
+    ///
+    /// # Returns
+    /// Whether the puzzle is solved
+    pub fn is_solved(&self) -> bool {
+        self.board.is_solved()
+    }
+
+    /// Scramble the board with reproducible random moves.
+    /// * Scrambling a solved puzzle gives the same board as `Board::generate` with the
+    ///   same seed and number of moves.
+    ///
+    /// # Parameters
+    /// * `seed` - Seed of the random number generator
+    /// * `moves` - Number of random moves to make
+    ///
+    /// # Returns
+    /// The moves made, as blank moves
+    pub fn scramble(&mut self, seed: u64, moves: i32) -> String {
+        let made = self.board.shuffle_with_rng(moves, &mut RngSource::seeded(seed));
+        self._reset();
+        made
+    }
+
+    /// Make a sequence of moves.
+    /// * The board is unchanged if any move can't be made.
+    ///
+    /// # Parameters
+    /// * `moves` - Moves to make
+    /// * `notation` - Notation the moves are written in
+    ///
+    /// # Returns
+    /// The first move that couldn't be made, if any
+    pub fn apply_moves(&mut self, moves: &str, notation: Notation) -> Result<(), IllegalMove> {
+        self.board = self.board.apply_moves(moves, notation)?;
+        self._reset();
+        Ok(())
+    }
+
+    /// Solve the puzzle from the current board.
+    ///
+    /// # Parameters
+    /// * `options` - Options controlling the solve
+    ///
+    /// # Returns
+    /// The solution moves and search statistics, or why the board can't be solved
+    pub fn solve(&self, options: &SolveOptions) -> Result<Solution, SolveError> {
+        solver::solve(&self.board, options)
+    }
+
+    /// Check that a solution solves the puzzle from the current board.
+    ///
+    /// # Parameters
+    /// * `solution` - Solution, with blank moves
+    ///
+    /// # Returns
+    /// Whether every move can be made and the board ends up solved
+    pub fn verify(&self, solution: &Solution) -> bool {
+        solver::verify_solution(&self.board, &solution.moves, Notation::BlankMoves)
+    }
+
+    /// Put the board back at the root of the search tree, costed against the goal.
+    fn _reset(&mut self) {
+        self.board.depth = 0;
+        self.board.path.clear();
+        self.board.cost = self.board.g() + self.board.store_h(&self.goal, Heuristic::LinearConflicts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test going from a scrambled puzzle to a verified solution
+    #[test]
+    fn test_puzzle_solve() {
+        let mut puzzle = Puzzle::new(3);
+        assert!(puzzle.is_solved());
+        let scramble = puzzle.scramble(371, 40);
+        assert_eq!(puzzle.board(), &Board::generate(3, 371, 40).unwrap());
+        assert_eq!((puzzle.board().depth, puzzle.board().path.as_str()), (0, ""));
+        assert_eq!(puzzle.goal(), &Board::new(3, None, -1, None));
+        let solution = puzzle.solve(&SolveOptions::default()).unwrap();
+        assert!(solution.moves.len() <= scramble.len());
+        assert!(puzzle.verify(&solution));
+        puzzle.apply_moves(&solution.moves, Notation::BlankMoves).unwrap();
+        assert!(puzzle.is_solved());
+        assert_eq!(puzzle.board().cost, 0);
+        // The moves no longer solve it from the new board
+        assert!(!puzzle.verify(&solution));
+    }
+
+    /// Test invalid layouts and moves are rejected
+    #[test]
+    fn test_puzzle_invalid() {
+        assert_eq!(
+            Puzzle::from_tiles(2, vec![Tile::new(1), Tile::new(1), Tile::new(2), Tile::new(-1)]),
+            Err(BoardError::DuplicateTile(1))
+        );
+        let mut puzzle = Puzzle::from_tiles(2, vec![Tile::new(1), Tile::new(2), Tile::new(3), Tile::new(-1)]).unwrap();
+        let error = puzzle.apply_moves("UD D", Notation::BlankMoves).unwrap_err();
+        assert_eq!(error.index, 2);
+        assert!(puzzle.is_solved());
+    }
+}
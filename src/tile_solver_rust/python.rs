@@ -0,0 +1,576 @@
+//! CPython extension module `sliding_tile_puzzle_solver`, for calling the solver from
+//! Python without a process per call.
+//! * Written against the stable parts of the CPython C API (3.10+), declared below, so
+//!   building needs no binding crate; see `python/` for packaging and tests.
+//! * `solve(board, n, **options)` returns a dict with `moves`, `length`, `optimal` and
+//!   `stats`, and releases the GIL while searching.
+//! * `generate(n, seed, difficulty)` returns a board of a difficulty tier.
+//! * `verify(board, n, moves)` checks that moves solve a board.
+//! * Solve failures raise `SolveError` with the error `code` (see `error_code`); bad
+//!   arguments raise `TypeError` or `ValueError`. No panic crosses the boundary.
+
+use std::os::raw::{c_char, c_int, c_long, c_longlong, c_ulonglong, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use super::board::{Board, Heuristic, Notation};
+use super::difficulty::{self, Difficulty};
+use super::error_code::ErrorCode;
+use super::solver::{self, Algorithm, SolveError, SolveOptions, Solution};
+use super::tile::Tile;
+
+/// A Python object, only ever handled through pointers
+#[repr(C)]
+pub struct PyObject {
+    _private: [u8; 0],
+}
+
+/// Header of a statically allocated object, `PyObject_HEAD_INIT(NULL)`
+#[repr(C)]
+struct PyObjectHead {
+    ob_refcnt: isize,
+    ob_type: *mut PyObject,
+}
+
+/// `PyModuleDef_Base`
+#[repr(C)]
+struct PyModuleDefBase {
+    ob_base: PyObjectHead,
+    m_init: Option<unsafe extern "C" fn() -> *mut PyObject>,
+    m_index: isize,
+    m_copy: *mut PyObject,
+}
+
+/// Signature of functions taking positional and keyword arguments
+type PyCFunctionWithKeywords = unsafe extern "C" fn(*mut PyObject, *mut PyObject, *mut PyObject) -> *mut PyObject;
+
+/// `PyMethodDef`
+#[repr(C)]
+struct PyMethodDef {
+    ml_name: *const c_char,
+    ml_meth: Option<PyCFunctionWithKeywords>,
+    ml_flags: c_int,
+    ml_doc: *const c_char,
+}
+
+/// `PyModuleDef`
+#[repr(C)]
+struct PyModuleDef {
+    m_base: PyModuleDefBase,
+    m_name: *const c_char,
+    m_doc: *const c_char,
+    m_size: isize,
+    m_methods: *mut PyMethodDef,
+    m_slots: *mut c_void,
+    m_traverse: *mut c_void,
+    m_clear: *mut c_void,
+    m_free: *mut c_void,
+}
+
+/// `METH_VARARGS | METH_KEYWORDS`
+const METH_VARARGS_KEYWORDS: c_int = 0x0003;
+/// API version passed to `PyModule_Create2`
+const PYTHON_API_VERSION: c_int = 1013;
+
+#[allow(non_upper_case_globals)]
+extern "C" {
+    static mut PyExc_Exception: *mut PyObject;
+    static mut PyExc_RuntimeError: *mut PyObject;
+    static mut PyExc_TypeError: *mut PyObject;
+    static mut PyExc_ValueError: *mut PyObject;
+
+    fn PyModule_Create2(def: *mut PyModuleDef, api_version: c_int) -> *mut PyObject;
+    fn PyModule_AddObjectRef(module: *mut PyObject, name: *const c_char, value: *mut PyObject) -> c_int;
+    fn PyErr_NewException(name: *const c_char, base: *mut PyObject, dict: *mut PyObject) -> *mut PyObject;
+    fn PyErr_SetString(exception: *mut PyObject, message: *const c_char);
+    fn PyErr_SetObject(exception: *mut PyObject, value: *mut PyObject);
+    fn PyErr_Occurred() -> *mut PyObject;
+    fn PyEval_SaveThread() -> *mut c_void;
+    fn PyEval_RestoreThread(state: *mut c_void);
+    fn Py_DecRef(object: *mut PyObject);
+    fn PyObject_IsTrue(object: *mut PyObject) -> c_int;
+    fn PyObject_CallOneArg(callable: *mut PyObject, arg: *mut PyObject) -> *mut PyObject;
+    fn PyObject_SetAttrString(object: *mut PyObject, name: *const c_char, value: *mut PyObject) -> c_int;
+    fn PyTuple_Size(tuple: *mut PyObject) -> isize;
+    fn PyTuple_GetItem(tuple: *mut PyObject, index: isize) -> *mut PyObject;
+    fn PyDict_New() -> *mut PyObject;
+    fn PyDict_Next(dict: *mut PyObject, position: *mut isize, key: *mut *mut PyObject,
+                   value: *mut *mut PyObject) -> c_int;
+    fn PyDict_SetItemString(dict: *mut PyObject, key: *const c_char, value: *mut PyObject) -> c_int;
+    fn PySequence_Size(sequence: *mut PyObject) -> isize;
+    fn PySequence_GetItem(sequence: *mut PyObject, index: isize) -> *mut PyObject;
+    fn PyList_New(len: isize) -> *mut PyObject;
+    fn PyList_SetItem(list: *mut PyObject, index: isize, item: *mut PyObject) -> c_int;
+    fn PyLong_AsLongLong(object: *mut PyObject) -> c_longlong;
+    fn PyLong_AsUnsignedLongLong(object: *mut PyObject) -> c_ulonglong;
+    fn PyLong_FromLongLong(value: c_longlong) -> *mut PyObject;
+    fn PyLong_FromUnsignedLongLong(value: c_ulonglong) -> *mut PyObject;
+    fn PyFloat_FromDouble(value: f64) -> *mut PyObject;
+    fn PyBool_FromLong(value: c_long) -> *mut PyObject;
+    fn PyUnicode_AsUTF8AndSize(object: *mut PyObject, size: *mut isize) -> *const c_char;
+    fn PyUnicode_FromStringAndSize(text: *const c_char, size: isize) -> *mut PyObject;
+}
+
+/// Pointer to a NUL-terminated byte string, for the C API.
+const fn _c(text: &[u8]) -> *const c_char {
+    text.as_ptr() as *const c_char
+}
+
+/// `SolveError` exception type, created when the module is initialised
+static mut SOLVE_ERROR: *mut PyObject = ptr::null_mut();
+
+static mut METHODS: [PyMethodDef; 4] = [
+    PyMethodDef {
+        ml_name: _c(b"solve\0"),
+        ml_meth: Some(_solve),
+        ml_flags: METH_VARARGS_KEYWORDS,
+        ml_doc: _c(b"solve(board, n, **options)\n--\n\nSolve a board, given as n * n tile symbols with -1 for \
+the blank.\nOptions: algorithm, heuristic, canonical, max_nodes, max_time_ms, max_memory_bytes.\0"),
+    },
+    PyMethodDef {
+        ml_name: _c(b"generate\0"),
+        ml_meth: Some(_generate),
+        ml_flags: METH_VARARGS_KEYWORDS,
+        ml_doc: _c(b"generate(n, seed, difficulty)\n--\n\nGenerate the tile symbols of a board of a difficulty \
+tier: \"easy\", \"medium\", \"hard\" or \"expert\".\0"),
+    },
+    PyMethodDef {
+        ml_name: _c(b"verify\0"),
+        ml_meth: Some(_verify),
+        ml_flags: METH_VARARGS_KEYWORDS,
+        ml_doc: _c(b"verify(board, n, moves)\n--\n\nCheck that moves of the blank solve a board.\0"),
+    },
+    // Sentinel
+    PyMethodDef { ml_name: ptr::null(), ml_meth: None, ml_flags: 0, ml_doc: ptr::null() },
+];
+
+static mut MODULE: PyModuleDef = PyModuleDef {
+    m_base: PyModuleDefBase {
+        ob_base: PyObjectHead { ob_refcnt: 1, ob_type: ptr::null_mut() },
+        m_init: None,
+        m_index: 0,
+        m_copy: ptr::null_mut(),
+    },
+    m_name: _c(b"sliding_tile_puzzle_solver\0"),
+    m_doc: _c(b"Solver for the sliding tile puzzle.\0"),
+    m_size: -1,
+    m_methods: ptr::addr_of_mut!(METHODS) as *mut PyMethodDef,
+    m_slots: ptr::null_mut(),
+    m_traverse: ptr::null_mut(),
+    m_clear: ptr::null_mut(),
+    m_free: ptr::null_mut(),
+};
+
+/// Python exception to raise.
+///
+/// # Attributes
+/// * `kind` - What went wrong
+/// * `message` - Description of the error
+#[derive(Debug, Clone, PartialEq)]
+struct PyError {
+    kind: PyErrorKind,
+    message: String,
+}
+
+/// Types of exception raised by the module.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PyErrorKind {
+    /// An exception is already set by the C API
+    Set,
+    TypeError,
+    ValueError,
+    RuntimeError,
+    /// `SolveError` with an error code
+    Solve(u32),
+}
+
+impl PyError {
+    /// Create an error.
+    ///
+    /// # Parameters
+    /// * `kind` - What went wrong
+    /// * `message` - Description of the error
+    fn new<M: ToString>(kind: PyErrorKind, message: M) -> PyError {
+        PyError { kind, message: message.to_string() }
+    }
+
+    /// Error for an exception the C API has already set.
+    fn set() -> PyError {
+        PyError::new(PyErrorKind::Set, "")
+    }
+
+    /// Raise the error in Python.
+    unsafe fn raise(self) {
+        let exception = match self.kind {
+            PyErrorKind::Set => return,
+            PyErrorKind::TypeError => PyExc_TypeError,
+            PyErrorKind::ValueError => PyExc_ValueError,
+            PyErrorKind::RuntimeError => PyExc_RuntimeError,
+            PyErrorKind::Solve(code) => return _raise_solve_error(&self.message, code),
+        };
+        match _str(&self.message) {
+            Ok(message) => {
+                PyErr_SetObject(exception, message);
+                Py_DecRef(message);
+            }
+            Err(_) => PyErr_SetString(exception, _c(b"unprintable error\0")),
+        }
+    }
+}
+
+impl From<SolveError> for PyError {
+    fn from(error: SolveError) -> PyError {
+        PyError::new(PyErrorKind::Solve(error.code()), error)
+    }
+}
+
+/// Raise `SolveError(message)` with a `code` attribute.
+///
+/// # Parameters
+/// * `message` - Description of the error
+/// * `code` - Error code
+unsafe fn _raise_solve_error(message: &str, code: u32) {
+    let exception = SOLVE_ERROR;
+    let message = match _str(message) {
+        Ok(message) => message,
+        Err(_) => return,
+    };
+    let instance = PyObject_CallOneArg(exception, message);
+    Py_DecRef(message);
+    if instance.is_null() {
+        return;
+    }
+    let code = PyLong_FromUnsignedLongLong(code as c_ulonglong);
+    if !code.is_null() {
+        if PyObject_SetAttrString(instance, _c(b"code\0"), code) == 0 {
+            PyErr_SetObject(exception, instance);
+        }
+        Py_DecRef(code);
+    }
+    Py_DecRef(instance);
+}
+
+/// Run the body of an exported function, converting errors and panics to exceptions.
+///
+/// # Parameters
+/// * `body` - Body of the function, returning a new reference
+///
+/// # Returns
+/// The result of the body, or null with an exception set
+fn _guard<F: FnOnce() -> Result<*mut PyObject, PyError>>(body: F) -> *mut PyObject {
+    let error = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(result)) => return result,
+        Ok(Err(error)) => error,
+        Err(_) => PyError::new(PyErrorKind::RuntimeError, "the solver panicked"),
+    };
+    unsafe { error.raise() };
+    ptr::null_mut()
+}
+
+/// Borrowed references to the required parameters, in order, and the remaining
+/// keyword arguments by name
+type Arguments = (Vec<*mut PyObject>, Vec<(String, *mut PyObject)>);
+
+/// Match positional and keyword arguments to parameters.
+///
+/// # Parameters
+/// * `args` - Tuple of positional arguments
+/// * `kwargs` - Dict of keyword arguments, or null
+/// * `names` - Names of the required parameters, in order
+///
+/// # Returns
+/// The parameters and the remaining keyword arguments, or the problem
+unsafe fn _arguments(args: *mut PyObject, kwargs: *mut PyObject, names: &[&str])
+                     -> Result<Arguments, PyError> {
+    let positional = PyTuple_Size(args);
+    if positional > names.len() as isize {
+        return Err(PyError::new(PyErrorKind::TypeError,
+                                format!("expected at most {} positional arguments, got {}", names.len(), positional)));
+    }
+    let mut parameters: Vec<*mut PyObject> = (0..positional).map(|i| PyTuple_GetItem(args, i)).collect();
+    parameters.resize(names.len(), ptr::null_mut());
+    let mut rest = Vec::new();
+    if !kwargs.is_null() {
+        let (mut position, mut key, mut value) = (0, ptr::null_mut(), ptr::null_mut());
+        while PyDict_Next(kwargs, &mut position, &mut key, &mut value) != 0 {
+            let name = _to_string(key)?;
+            match names.iter().position(|&parameter| parameter == name) {
+                Some(i) if !parameters[i].is_null() => {
+                    return Err(PyError::new(PyErrorKind::TypeError, format!("got multiple values for {:?}", name)))
+                }
+                Some(i) => parameters[i] = value,
+                None => rest.push((name, value)),
+            }
+        }
+    }
+    if let Some(i) = parameters.iter().position(|parameter| parameter.is_null()) {
+        return Err(PyError::new(PyErrorKind::TypeError, format!("missing argument {:?}", names[i])));
+    }
+    Ok((parameters, rest))
+}
+
+/// Read an integer.
+unsafe fn _to_i64(object: *mut PyObject) -> Result<i64, PyError> {
+    let value = PyLong_AsLongLong(object);
+    if value == -1 && !PyErr_Occurred().is_null() {
+        return Err(PyError::set());
+    }
+    Ok(value)
+}
+
+/// Read a non-negative integer.
+unsafe fn _to_u64(object: *mut PyObject) -> Result<u64, PyError> {
+    let value = PyLong_AsUnsignedLongLong(object);
+    if value == c_ulonglong::MAX && !PyErr_Occurred().is_null() {
+        return Err(PyError::set());
+    }
+    Ok(value)
+}
+
+/// Read a string.
+unsafe fn _to_string(object: *mut PyObject) -> Result<String, PyError> {
+    let mut size = 0;
+    let text = PyUnicode_AsUTF8AndSize(object, &mut size);
+    if text.is_null() {
+        return Err(PyError::set());
+    }
+    Ok(String::from_utf8_lossy(std::slice::from_raw_parts(text as *const u8, size as usize)).into_owned())
+}
+
+/// Read a board from a sequence of tile symbols and its size.
+unsafe fn _to_board(tiles: *mut PyObject, n: *mut PyObject) -> Result<Board, PyError> {
+    let n = _to_i64(n)?;
+    let len = PySequence_Size(tiles);
+    if len < 0 {
+        return Err(PyError::set());
+    }
+    let mut symbols = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        let item = PySequence_GetItem(tiles, i);
+        if item.is_null() {
+            return Err(PyError::set());
+        }
+        let symbol = _to_i64(item);
+        Py_DecRef(item);
+        symbols.push(Tile::new(symbol? as i32));
+    }
+    Board::try_new(n as i32, symbols).map_err(|e| PyError::new(PyErrorKind::ValueError, e))
+}
+
+/// Create a string.
+unsafe fn _str(text: &str) -> Result<*mut PyObject, PyError> {
+    let object = PyUnicode_FromStringAndSize(text.as_ptr() as *const c_char, text.len() as isize);
+    if object.is_null() {
+        return Err(PyError::set());
+    }
+    Ok(object)
+}
+
+/// Set an item of a dict, taking ownership of the value.
+unsafe fn _set_item(dict: *mut PyObject, key: &[u8], value: *mut PyObject) -> Result<(), PyError> {
+    if value.is_null() {
+        return Err(PyError::set());
+    }
+    let result = PyDict_SetItemString(dict, _c(key), value);
+    Py_DecRef(value);
+    if result != 0 {
+        return Err(PyError::set());
+    }
+    Ok(())
+}
+
+/// Read an algorithm by name.
+///
+/// # Parameters
+/// * `name` - "astar", "ida_star", "greedy" or "layered"
+///
+/// # Returns
+/// The algorithm, or `None` if the name is unknown
+fn _algorithm(name: &str) -> Option<Algorithm> {
+    match name {
+        "astar" => Some(Algorithm::AStar),
+        "ida_star" => Some(Algorithm::IdaStar),
+        "greedy" => Some(Algorithm::Greedy),
+        "layered" => Some(Algorithm::Layered),
+        _ => None,
+    }
+}
+
+/// Convert keyword arguments of `solve` into options.
+///
+/// # Parameters
+/// * `n` - Size of the board, choosing the defaults, see `SolveOptions::for_size`
+/// * `kwargs` - Options by name
+///
+/// # Returns
+/// The options, or the problem
+unsafe fn _options(n: i32, kwargs: &[(String, *mut PyObject)]) -> Result<SolveOptions, PyError> {
+    let invalid = |message: String| PyError::new(PyErrorKind::ValueError, message);
+    let mut options = SolveOptions::for_size(n);
+    for (key, value) in kwargs {
+        options = match key.as_str() {
+            "algorithm" => {
+                let name = _to_string(*value)?;
+                let algorithm = _algorithm(&name).ok_or_else(|| invalid(format!("unknown algorithm {:?}", name)))?;
+                options.algorithm(algorithm)
+            }
+            "heuristic" => {
+                let name = _to_string(*value)?;
+                let heuristic = Heuristic::parse(&name)
+                    .ok_or_else(|| invalid(format!("unknown heuristic {:?}", name)))?;
+                options.heuristic(heuristic)
+            }
+            "canonical" => match PyObject_IsTrue(*value) {
+                -1 => return Err(PyError::set()),
+                canonical => options.canonical(canonical == 1),
+            },
+            "max_nodes" => options.max_nodes(_to_u64(*value)?),
+            "max_time_ms" => options.max_time_ms(_to_u64(*value)?),
+            "max_memory_bytes" => options.max_memory_bytes(_to_u64(*value)?),
+            _ => return Err(PyError::new(PyErrorKind::TypeError, format!("unknown option {:?}", key))),
+        };
+    }
+    options.validate()?;
+    Ok(options)
+}
+
+/// Convert a solution into a dict.
+///
+/// # Parameters
+/// * `solution` - The solution
+///
+/// # Returns
+/// New reference to a dict of `moves`, `length`, `optimal` and `stats`
+unsafe fn _solution_dict(solution: &Solution) -> Result<*mut PyObject, PyError> {
+    let stats = PyDict_New();
+    if stats.is_null() {
+        return Err(PyError::set());
+    }
+    let filled = (|| {
+        _set_item(stats, b"nodes_expanded\0", PyLong_FromUnsignedLongLong(solution.stats.nodes_expanded))?;
+        _set_item(stats, b"nodes_generated\0", PyLong_FromUnsignedLongLong(solution.stats.nodes_generated))?;
+        _set_item(stats, b"max_heuristic\0", PyLong_FromLongLong(solution.stats.max_heuristic as c_longlong))?;
+        _set_item(stats, b"effective_branching_factor\0",
+                  PyFloat_FromDouble(solution.stats.effective_branching_factor))?;
+        _set_item(stats, b"millis\0", PyFloat_FromDouble(solution.stats.millis))
+    })();
+    if let Err(error) = filled {
+        Py_DecRef(stats);
+        return Err(error);
+    }
+    let dict = PyDict_New();
+    if dict.is_null() {
+        Py_DecRef(stats);
+        return Err(PyError::set());
+    }
+    let filled = (|| {
+        _set_item(dict, b"stats\0", stats)?;
+        _set_item(dict, b"moves\0", _str(&solution.moves).unwrap_or(ptr::null_mut()))?;
+        _set_item(dict, b"length\0", PyLong_FromLongLong(solution.moves.len() as c_longlong))?;
+        _set_item(dict, b"optimal\0", PyBool_FromLong(solution.optimal as c_long))
+    })();
+    match filled {
+        Ok(()) => Ok(dict),
+        Err(error) => {
+            Py_DecRef(dict);
+            Err(error)
+        }
+    }
+}
+
+/// `solve(board, n, **options)`
+unsafe extern "C" fn _solve(_module: *mut PyObject, args: *mut PyObject, kwargs: *mut PyObject) -> *mut PyObject {
+    _guard(|| {
+        let (parameters, rest) = _arguments(args, kwargs, &["board", "n"])?;
+        let board = _to_board(parameters[0], parameters[1])?;
+        let options = _options(board.size(), &rest)?;
+        // Other Python threads run while this one searches
+        let state = PyEval_SaveThread();
+        let result = panic::catch_unwind(|| solver::solve(&board, &options));
+        PyEval_RestoreThread(state);
+        let solution = result.map_err(|_| PyError::new(PyErrorKind::RuntimeError, "the solver panicked"))??;
+        _solution_dict(&solution)
+    })
+}
+
+/// `generate(n, seed, difficulty)`
+unsafe extern "C" fn _generate(_module: *mut PyObject, args: *mut PyObject, kwargs: *mut PyObject) -> *mut PyObject {
+    _guard(|| {
+        let (parameters, rest) = _arguments(args, kwargs, &["n", "seed", "difficulty"])?;
+        if let Some((name, _)) = rest.first() {
+            return Err(PyError::new(PyErrorKind::TypeError, format!("unexpected argument {:?}", name)));
+        }
+        let n = _to_i64(parameters[0])? as i32;
+        let seed = _to_u64(parameters[1])?;
+        let name = _to_string(parameters[2])?;
+        let tier = Difficulty::parse(&name)
+            .ok_or_else(|| PyError::new(PyErrorKind::ValueError, format!("unknown difficulty {:?}", name)))?;
+        let board = difficulty::generate_tier(n, tier, seed).map_err(|e| PyError::new(PyErrorKind::ValueError, e))?;
+        let list = PyList_New(board.tiles().len() as isize);
+        if list.is_null() {
+            return Err(PyError::set());
+        }
+        for (i, tile) in board.tiles().iter().enumerate() {
+            let symbol = PyLong_FromLongLong(tile.symbol() as c_longlong);
+            if symbol.is_null() || PyList_SetItem(list, i as isize, symbol) != 0 {
+                Py_DecRef(list);
+                return Err(PyError::set());
+            }
+        }
+        Ok(list)
+    })
+}
+
+/// `verify(board, n, moves)`
+unsafe extern "C" fn _verify(_module: *mut PyObject, args: *mut PyObject, kwargs: *mut PyObject) -> *mut PyObject {
+    _guard(|| {
+        let (parameters, rest) = _arguments(args, kwargs, &["board", "n", "moves"])?;
+        if let Some((name, _)) = rest.first() {
+            return Err(PyError::new(PyErrorKind::TypeError, format!("unexpected argument {:?}", name)));
+        }
+        let board = _to_board(parameters[0], parameters[1])?;
+        let moves = _to_string(parameters[2])?;
+        Ok(PyBool_FromLong(solver::verify_solution(&board, &moves, Notation::BlankMoves) as c_long))
+    })
+}
+
+/// Initialise the module when Python imports it.
+///
+/// # Returns
+/// New reference to the module, or null with an exception set
+///
+/// # Safety
+/// Called by the interpreter, holding the GIL
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn PyInit_sliding_tile_puzzle_solver() -> *mut PyObject {
+    let module = PyModule_Create2(ptr::addr_of_mut!(MODULE), PYTHON_API_VERSION);
+    if module.is_null() {
+        return module;
+    }
+    if SOLVE_ERROR.is_null() {
+        SOLVE_ERROR = PyErr_NewException(_c(b"sliding_tile_puzzle_solver.SolveError\0"), PyExc_Exception,
+                                         ptr::null_mut());
+    }
+    if SOLVE_ERROR.is_null() || PyModule_AddObjectRef(module, _c(b"SolveError\0"), SOLVE_ERROR) != 0 {
+        Py_DecRef(module);
+        return ptr::null_mut();
+    }
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test every algorithm can be chosen by name
+    #[test]
+    fn test_algorithm_names() {
+        let algorithms = [Algorithm::AStar, Algorithm::IdaStar, Algorithm::Greedy, Algorithm::Layered];
+        let names = ["astar", "ida_star", "greedy", "layered"];
+        for (&algorithm, name) in algorithms.iter().zip(names.iter()) {
+            assert_eq!(_algorithm(name), Some(algorithm));
+        }
+        assert_eq!(_algorithm("dijkstra"), None);
+    }
+}
@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{self, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+use super::board::*;
+use super::error_code::ErrorCode;
+use super::format_board_info;
+use super::solver::{self, SolveError, SolveOptions};
+
+/// ANSI escape sequence that clears the screen and moves the cursor to the top left
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// One board along a replayed solution.
+///
+/// # Attributes
+/// * `step` - Number of moves made so far
+/// * `total` - Number of moves in the solution
+/// * `blank_move` - Move of the blank that produced the board, `None` for the start
+/// * `moved_tile` - Symbol of the tile that slid into the blank's old position
+/// * `board` - The board after the move
+#[derive(Debug, Clone)]
+pub struct ReplayFrame {
+    pub step: usize,
+    pub total: usize,
+    pub blank_move: Option<char>,
+    pub moved_tile: Option<i32>,
+    pub board: Board,
+}
+
+impl fmt::Display for ReplayFrame {
+    /// Format the frame as a caption line followed by the board.
+    /// * Captions describe the tile's movement, which is opposite to the blank's.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.blank_move, self.moved_tile) {
+            (Some(blank_move), Some(tile)) => {
+                let (arrow, direction) = match opposite_direction(blank_move) {
+                    UP => ('↑', "up"),
+                    DOWN => ('↓', "down"),
+                    LEFT => ('←', "left"),
+                    _ => ('→', "right"),
+                };
+                writeln!(f, "step {}/{}: tile {} {} {}", self.step, self.total, tile, arrow, direction)?;
+            }
+            _ => writeln!(f, "step {}/{}: start", self.step, self.total)?,
+        }
+        write!(f, "{}", self.board)
+    }
+}
+
+/// Iterator over the boards along a solution, starting with the unsolved board.
+///
+/// # Attributes
+/// * `board` - Board after the moves made so far
+/// * `moves` - Solution moves
+/// * `step` - Number of moves made so far, or `None` before the start is yielded
+#[derive(Debug, Clone)]
+pub struct Replay {
+    board: Board,
+    moves: Vec<char>,
+    step: Option<usize>,
+}
+
+impl Replay {
+    /// Create a replay of a solution.
+    ///
+    /// # Parameters
+    /// * `board` - The unsolved board
+    /// * `moves` - Solution moves, which must all be legal
+    pub fn new(board: &Board, moves: &str) -> Replay {
+        Replay { board: board.clone(), moves: moves.chars().collect(), step: None }
+    }
+}
+
+impl Iterator for Replay {
+    type Item = ReplayFrame;
+
+    fn next(&mut self) -> Option<ReplayFrame> {
+        let total = self.moves.len();
+        let step = match self.step {
+            None => {
+                self.step = Some(0);
+                return Some(ReplayFrame {
+                    step: 0, total, blank_move: None, moved_tile: None, board: self.board.clone(),
+                });
+            }
+            Some(step) if step < total => step,
+            Some(_) => return None,
+        };
+        let blank_move = self.moves[step];
+        let blank_index = self.board.get_blank_index();
+        let moved_tile = self.board.tiles()[self.board.translate_index(blank_index, blank_move) as usize].symbol();
+        self.board.move_blank_tile(blank_move);
+        self.step = Some(step + 1);
+        Some(ReplayFrame {
+            step: step + 1,
+            total,
+            blank_move: Some(blank_move),
+            moved_tile: Some(moved_tile),
+            board: self.board.clone(),
+        })
+    }
+}
+
+/// How obvious one move of a solution is, judged by the heuristic of each child.
+///
+/// # Attributes
+/// * `step` - Number of the move, from 1
+/// * `blank_move` - Move of the blank that was made
+/// * `legal_move_count` - Number of legal moves from the board before the move
+/// * `chosen_h` - Heuristic value (Manhattan plus linear conflicts) after the move
+/// * `best_alternative_h` - Smallest heuristic value after any other legal move, if any
+/// * `obviousness` - 1 divided by the number of moves whose child looks at least as
+///   good as the chosen one, so 1 when the chosen move is the clear best
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveAnnotation {
+    pub step: usize,
+    pub blank_move: char,
+    pub legal_move_count: usize,
+    pub chosen_h: i32,
+    pub best_alternative_h: Option<i32>,
+    pub obviousness: f64,
+}
+
+impl MoveAnnotation {
+    /// Format the annotation as a JSON object.
+    ///
+    /// # Returns
+    /// JSON representation of the annotation
+    pub fn to_json(&self) -> String {
+        let best_alternative_h = match self.best_alternative_h {
+            Some(h) => h.to_string(),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"step\":{},\"move\":\"{}\",\"legal_move_count\":{},\"chosen_h\":{},\
+             \"best_alternative_h\":{},\"obviousness\":{}}}",
+            self.step, self.blank_move, self.legal_move_count, self.chosen_h, best_alternative_h, self.obviousness,
+        )
+    }
+}
+
+/// Grade how obvious each move of a solution is.
+///
+/// # Parameters
+/// * `start` - The unsolved board
+/// * `path` - Solution moves of the blank, read with `parse_path`
+///
+/// # Returns
+/// One annotation per move, or the first move that can't be made
+pub fn annotate_solution(start: &Board, path: &str) -> Result<Vec<MoveAnnotation>, IllegalMove> {
+    let path: String = parse_path(path, Notation::BlankMoves)?.into_iter().collect();
+    let path = path.as_str();
+    start.apply_moves(path, Notation::BlankMoves)?;
+    let goal = Board::new(start.size(), None, -1, None);
+    let h = |board: &Board| {
+        let breakdown = board.heuristic_breakdown(Some(&goal));
+        breakdown.manhattan + breakdown.linear_conflicts
+    };
+    let frames: Vec<ReplayFrame> = Replay::new(start, path).collect();
+    Ok(frames.windows(2)
+        .map(|pair| {
+            let (before, after) = (&pair[0].board, &pair[1]);
+            let blank_move = after.blank_move.unwrap();
+            let chosen_h = h(&after.board);
+            let alternatives: Vec<i32> = before.legal_moves().into_iter()
+                .filter(|&tile_move| tile_move != blank_move)
+                .map(|tile_move| {
+                    let mut child = before.clone();
+                    child.move_blank_tile(tile_move);
+                    h(&child)
+                })
+                .collect();
+            let rivals = alternatives.iter().filter(|&&alternative| alternative <= chosen_h).count();
+            MoveAnnotation {
+                step: after.step,
+                blank_move,
+                legal_move_count: alternatives.len() + 1,
+                chosen_h,
+                best_alternative_h: alternatives.iter().copied().min(),
+                obviousness: 1.0 / (rivals + 1) as f64,
+            }
+        })
+        .collect())
+}
+
+/// Where a player's moves first strayed from an optimal line.
+///
+/// # Attributes
+/// * `distances` - Optimal remaining distance before the first move and after each move
+/// * `index` - Index of the first move that increased the optimal remaining distance,
+///   `None` if every move brought the player closer
+/// * `board` - Board before that move, where a better move was available
+/// * `wasted_moves` - Moves beyond those an optimal player needs to make the same
+///   progress, i.e. twice the number of moves away from the goal
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub distances: Vec<usize>,
+    pub index: Option<usize>,
+    pub board: Option<Board>,
+    pub wasted_moves: usize,
+}
+
+impl Divergence {
+    /// Format the divergence as a JSON object.
+    ///
+    /// # Returns
+    /// JSON representation of the divergence, with the board in board info format
+    pub fn to_json(&self) -> String {
+        let distances: Vec<String> = self.distances.iter().map(|distance| distance.to_string()).collect();
+        let index = self.index.map_or("null".to_string(), |index| index.to_string());
+        let board = self.board.as_ref().map_or("null".to_string(), |board| format!("\"{}\"", format_board_info(board)));
+        format!(
+            "{{\"distances\":[{}],\"index\":{},\"board\":{},\"wasted_moves\":{}}}",
+            distances.join(","), index, board, self.wasted_moves
+        )
+    }
+}
+
+/// Reasons a player's moves can't be compared with an optimal line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompareError {
+    /// A player move can't be made
+    IllegalMove(IllegalMove),
+    /// A board along the path couldn't be solved
+    Solve(SolveError),
+}
+
+impl fmt::Display for CompareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompareError::IllegalMove(e) => write!(f, "{}", e),
+            CompareError::Solve(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for CompareError {}
+
+impl ErrorCode for CompareError {
+    /// Codes are those of the underlying error.
+    fn code(&self) -> u32 {
+        match self {
+            CompareError::IllegalMove(e) => e.code(),
+            CompareError::Solve(e) => e.code(),
+        }
+    }
+}
+
+/// Compare a player's moves with an optimal line, e.g. for a tutor.
+/// * Each board along the path is solved once; boards along earlier solutions get
+///   their distances from those solutions, so a player following an optimal line
+///   needs a single solve.
+///
+/// # Parameters
+/// * `start` - The board the player started from
+/// * `player_path` - Moves of the blank the player made, read with `parse_path`
+/// * `options` - Options for the solves, with an optimal algorithm
+///
+/// # Returns
+/// Where the player first went wrong and how many moves they wasted, or the first
+/// illegal move or unsolvable board, or `SolveError::InvalidOptions` if the algorithm
+/// isn't optimal
+pub fn compare_to_optimal(
+    start: &Board, player_path: &str, options: &SolveOptions,
+) -> Result<Divergence, CompareError> {
+    // Distances read off suboptimal solutions would report detours that aren't there
+    if !options.algorithm.is_optimal() {
+        let reason = format!("{:?} solutions aren't optimal distances", options.algorithm);
+        return Err(CompareError::Solve(SolveError::InvalidOptions(reason)));
+    }
+    let player_path: String = parse_path(player_path, Notation::BlankMoves)
+        .map_err(|error| CompareError::IllegalMove(error.into()))?
+        .into_iter()
+        .collect();
+    let player_path = player_path.as_str();
+    start.apply_moves(player_path, Notation::BlankMoves).map_err(CompareError::IllegalMove)?;
+    let mut oracle: HashMap<Board, usize> = HashMap::new();
+    let mut distances = Vec::with_capacity(player_path.len() + 1);
+    let frames: Vec<ReplayFrame> = Replay::new(start, player_path).collect();
+    for frame in &frames {
+        let distance = match oracle.get(&frame.board) {
+            Some(&distance) => distance,
+            None => {
+                let moves = solver::solve(&frame.board, options).map_err(CompareError::Solve)?.moves;
+                for line_frame in Replay::new(&frame.board, &moves) {
+                    oracle.insert(line_frame.board, moves.len() - line_frame.step);
+                }
+                moves.len()
+            }
+        };
+        distances.push(distance);
+    }
+    let index = distances.windows(2).position(|pair| pair[1] > pair[0]);
+    let progress = distances[0] as isize - *distances.last().unwrap() as isize;
+    Ok(Divergence {
+        board: index.map(|index| frames[index].board.clone()),
+        index,
+        wasted_moves: (player_path.len() as isize - progress) as usize,
+        distances,
+    })
+}
+
+/// Render each frame of a replayed solution.
+///
+/// # Parameters
+/// * `board` - The unsolved board
+/// * `moves` - Solution moves
+///
+/// # Returns
+/// One rendered frame per move, plus the starting board
+pub fn animation_frames(board: &Board, moves: &str) -> Vec<String> {
+    Replay::new(board, moves).map(|frame| frame.to_string()).collect()
+}
+
+/// Play a solution as a terminal animation.
+/// * With a delay, the screen is cleared before each frame. Only plain escape
+///   sequences are written and no terminal modes are changed, so interrupting the
+///   animation leaves the terminal usable.
+/// * With no delay, frames are written one after another, separated by blank lines,
+///   without sleeping, for use in scripts.
+///
+/// # Parameters
+/// * `out` - Where frames are written
+/// * `board` - The unsolved board
+/// * `moves` - Solution moves
+/// * `delay` - Time to show each frame
+///
+/// # Returns
+/// An error if writing fails
+#[cfg(not(target_arch = "wasm32"))]
+pub fn animate<W: Write>(out: &mut W, board: &Board, moves: &str, delay: Duration) -> io::Result<()> {
+    for (i, frame) in Replay::new(board, moves).enumerate() {
+        if delay.as_nanos() == 0 {
+            if i > 0 {
+                writeln!(out)?;
+            }
+        } else {
+            if i > 0 {
+                thread::sleep(delay);
+            }
+            write!(out, "{}", CLEAR_SCREEN)?;
+        }
+        write!(out, "{}", frame)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{self, Algorithm, SolveOptions};
+    use crate::try_parse_board_info;
+
+    /// Test frames are generated for each move plus the start
+    #[test]
+    fn test_animation_frames() {
+        let board = try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        let moves = solver::solve(&board, &SolveOptions::default()).unwrap().moves;
+        assert_eq!(moves, "RDR");
+        let frames = animation_frames(&board, &moves);
+        assert_eq!(frames.len(), moves.len() + 1);
+        assert!(frames[0].starts_with("step 0/3: start\n"));
+        // The blank moves right, so tile 4 slides left
+        assert!(frames[1].starts_with("step 1/3: tile 4 ← left\n"));
+        assert!(frames[2].starts_with("step 2/3: tile 5 ↑ up\n"));
+        assert!(frames[3].starts_with("step 3/3: tile 8 ← left\n"));
+        assert!(frames[3].ends_with(&Board::new(3, None, 0, None).to_string()));
+        let last = Replay::new(&board, &moves).last().unwrap();
+        assert!(last.board.is_solved());
+    }
+
+    /// Test annotations of solution moves
+    #[test]
+    fn test_annotate_solution() {
+        let board = try_parse_board_info("3,1,2,3,4,5,6,7,-1,8").unwrap();
+        let annotations = annotate_solution(&board, "R").unwrap();
+        assert_eq!(annotations.len(), 1);
+        let annotation = &annotations[0];
+        assert!(annotation.legal_move_count >= 1);
+        assert_eq!(annotation.chosen_h, 0);
+        assert_eq!(annotation.legal_move_count, 3);
+        assert_eq!(annotation.best_alternative_h, Some(2));
+        assert_eq!(annotation.obviousness, 1.0);
+        assert_eq!(
+            annotation.to_json(),
+            "{\"step\":1,\"move\":\"R\",\"legal_move_count\":3,\"chosen_h\":0,\
+             \"best_alternative_h\":2,\"obviousness\":1}"
+        );
+        let board = try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        let annotations = annotate_solution(&board, "RDR").unwrap();
+        assert_eq!(annotations.iter().map(|annotation| annotation.step).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(annotations.iter().all(|annotation| annotation.obviousness > 0.0 && annotation.obviousness <= 1.0));
+        assert_eq!(annotate_solution(&board, "").unwrap(), vec![]);
+        assert_eq!(annotate_solution(&board, "RRR").unwrap_err().index, 2);
+        assert_eq!(annotate_solution(&board, "r, d, r"), Ok(annotations));
+    }
+
+    /// Test divergence from the optimal line is found and wasted moves counted
+    #[test]
+    fn test_compare_to_optimal() {
+        let board = try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        let options = SolveOptions::default();
+        // A perfect player
+        let perfect = compare_to_optimal(&board, "RDR", &options).unwrap();
+        assert_eq!(perfect, Divergence { distances: vec![3, 2, 1, 0], index: None, board: None, wasted_moves: 0 });
+        assert_eq!(perfect.to_json(), "{\"distances\":[3,2,1,0],\"index\":null,\"board\":null,\"wasted_moves\":0}");
+        // Wanders up and back before finishing, written as a user might
+        let wandering = compare_to_optimal(&board, "r u d d r", &options).unwrap();
+        assert_eq!(wandering.distances, vec![3, 2, 3, 2, 1, 0]);
+        assert_eq!(wandering.index, Some(1));
+        assert_eq!(wandering.wasted_moves, 2);
+        assert_eq!(wandering.board.as_ref().map(format_board_info), Some("3,1,2,3,4,-1,6,7,5,8".to_string()));
+        // Gives up part way after a detour
+        let unfinished = compare_to_optimal(&board, "UD", &options).unwrap();
+        assert_eq!((unfinished.index, unfinished.wasted_moves), (Some(0), 2));
+        assert_eq!(unfinished.board, Some(board.clone()));
+        // Illegal moves report their index
+        assert_eq!(
+            compare_to_optimal(&board, "RDD", &options),
+            Err(CompareError::IllegalMove(IllegalMove { index: 2, rejection: MoveRejection::BlankOnBottomEdge }))
+        );
+        let unsolvable = try_parse_board_info("2,2,1,3,-1").unwrap();
+        assert_eq!(compare_to_optimal(&unsolvable, "", &options), Err(CompareError::Solve(SolveError::Unreachable)));
+        assert_eq!(compare_to_optimal(&unsolvable, "", &options).unwrap_err().code(), 201);
+        for &algorithm in &[Algorithm::Greedy, Algorithm::Layered] {
+            let error = compare_to_optimal(&board, "RDR", &options.clone().algorithm(algorithm)).unwrap_err();
+            assert!(matches!(error, CompareError::Solve(SolveError::InvalidOptions(_))), "{:?}", error);
+        }
+        let ida_star = compare_to_optimal(&board, "r u d d r", &SolveOptions::new().algorithm(Algorithm::IdaStar));
+        assert_eq!(ida_star, Ok(wandering));
+    }
+
+    /// Test a solved board is shown once and zero delay writes frames without clearing
+    #[test]
+    fn test_animate() {
+        let solved = Board::new(3, None, 0, None);
+        assert_eq!(animation_frames(&solved, ""), vec![format!("step 0/0: start\n{}", solved)]);
+        let board = try_parse_board_info("3,1,2,3,4,5,6,7,-1,8").unwrap();
+        let mut out = Vec::new();
+        animate(&mut out, &board, "R", Duration::from_millis(0)).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(!text.contains(CLEAR_SCREEN));
+        assert_eq!(text, animation_frames(&board, "R").join("\n"));
+        let mut out = Vec::new();
+        animate(&mut out, &board, "R", Duration::from_millis(1)).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().matches(CLEAR_SCREEN).count(), 2);
+    }
+}
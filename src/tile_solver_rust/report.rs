@@ -0,0 +1,249 @@
+//! Machine-readable reports of batch solves, one file per run.
+//!
+//! Reports are newline-delimited JSON, written as the run goes so a crash loses at most
+//! the instance being solved:
+//! * `{"type":"header","version":1,"crate_version":...,"profile":"release","options":{...},
+//!   "seed":7,"instances":3,"inputs_checksum":"..."}` - The run, written first
+//! * `{"type":"instance","index":0,"board":"3,...","solution":{...},"length":12,
+//!   "wall_time_ms":0.4}` - One per instance as it finishes, with `"error"` (code and
+//!   message) in place of the solution and length if the solve failed
+//! * `{"type":"summary","solved":3,"failed":0,"wall_time_ms":1.2}` - Written last
+//!
+//! The inputs checksum is a 64-bit FNV-1a hash of the board info of every instance,
+//! one per line, so results can be matched to the boards they came from.
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use super::board::Board;
+use super::clock;
+use super::error_code::CodedError;
+use super::format_board_info;
+use super::server::json_string;
+use super::solver::{self, Solution, SolveError, SolveOptions};
+
+/// Version of the report format
+pub const REPORT_VERSION: u32 = 1;
+
+/// Writes a report one instance at a time, flushing after every line.
+///
+/// # Attributes
+/// * `output` - Where the report is written
+/// * `solved` - Instances solved so far
+/// * `failed` - Instances that failed so far
+/// * `wall_time` - Total time of the instances written so far
+pub struct ReportWriter<W: Write> {
+    output: W,
+    solved: usize,
+    failed: usize,
+    wall_time: Duration,
+}
+
+impl<W: Write> ReportWriter<W> {
+    /// Start a report, writing its header.
+    ///
+    /// # Parameters
+    /// * `output` - Where to write the report
+    /// * `boards` - Every instance of the run, for the count and checksum
+    /// * `options` - Options every instance is solved with
+    /// * `seed` - Seed the boards were generated from, if any
+    ///
+    /// # Returns
+    /// The writer, or the error writing the header
+    pub fn new(mut output: W, boards: &[Board], options: &SolveOptions,
+               seed: Option<u64>) -> io::Result<ReportWriter<W>> {
+        let seed = seed.map_or("null".to_string(), |seed| seed.to_string());
+        writeln!(
+            output,
+            "{{\"type\":\"header\",\"version\":{},\"crate_version\":\"{}\",\"profile\":\"{}\",\
+             \"options\":{},\"seed\":{},\"instances\":{},\"inputs_checksum\":\"{:016x}\"}}",
+            REPORT_VERSION,
+            env!("CARGO_PKG_VERSION"),
+            if cfg!(debug_assertions) { "debug" } else { "release" },
+            _options_json(options),
+            seed,
+            boards.len(),
+            inputs_checksum(boards),
+        )?;
+        output.flush()?;
+        Ok(ReportWriter { output, solved: 0, failed: 0, wall_time: Duration::default() })
+    }
+
+    /// Write the result of one instance.
+    ///
+    /// # Parameters
+    /// * `index` - Index of the instance in the run
+    /// * `board` - The instance
+    /// * `result` - The solution, or why the solve failed
+    /// * `elapsed` - Wall time of the solve
+    ///
+    /// # Returns
+    /// The error writing the line, if any
+    pub fn write_instance(&mut self, index: usize, board: &Board, result: &Result<Solution, SolveError>,
+                          elapsed: Duration) -> io::Result<()> {
+        let outcome = match result {
+            Ok(solution) => {
+                self.solved += 1;
+                format!("\"solution\":{},\"length\":{}", solution.to_json(), solution.moves.len())
+            }
+            Err(e) => {
+                self.failed += 1;
+                let error = CodedError::from_error(e);
+                format!("\"error\":{{\"code\":{},\"message\":{}}}", error.code, json_string(&error.message))
+            }
+        };
+        self.wall_time += elapsed;
+        writeln!(
+            self.output,
+            "{{\"type\":\"instance\",\"index\":{},\"board\":\"{}\",{},\"wall_time_ms\":{}}}",
+            index,
+            format_board_info(board),
+            outcome,
+            elapsed.as_secs_f64() * 1000.0,
+        )?;
+        self.output.flush()
+    }
+
+    /// Finish the report with a summary of the run.
+    ///
+    /// # Returns
+    /// The output, or the error writing the summary
+    pub fn finish(mut self) -> io::Result<W> {
+        writeln!(
+            self.output,
+            "{{\"type\":\"summary\",\"solved\":{},\"failed\":{},\"wall_time_ms\":{}}}",
+            self.solved,
+            self.failed,
+            self.wall_time.as_secs_f64() * 1000.0,
+        )?;
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+/// Solve a batch of boards, reporting each as it finishes.
+///
+/// # Parameters
+/// * `boards` - The instances
+/// * `options` - Options every instance is solved with
+/// * `seed` - Seed the boards were generated from, if any, recorded in the header
+/// * `output` - Where to write the report
+///
+/// # Returns
+/// The output, or the error writing the report
+pub fn run_batch<W: Write>(boards: &[Board], options: &SolveOptions, seed: Option<u64>,
+                           output: W) -> io::Result<W> {
+    let mut writer = ReportWriter::new(output, boards, options, seed)?;
+    for (index, board) in boards.iter().enumerate() {
+        let start = clock::now_ms();
+        let result = solver::solve(board, options);
+        writer.write_instance(index, board, &result, clock::elapsed_since(start))?;
+    }
+    writer.finish()
+}
+
+/// Hash the board info of a batch of boards, one per line, with 64-bit FNV-1a.
+///
+/// # Parameters
+/// * `boards` - The boards
+///
+/// # Returns
+/// The checksum
+pub fn inputs_checksum(boards: &[Board]) -> u64 {
+    boards.iter()
+        .flat_map(|board| format_board_info(board).into_bytes().into_iter().chain(Some(b'\n')))
+        .fold(0xCBF2_9CE4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3))
+}
+
+/// Format the options that affect results as a JSON object.
+///
+/// # Parameters
+/// * `options` - The options
+///
+/// # Returns
+/// JSON object with the algorithm, heuristic, node limit and canonical flag
+fn _options_json(options: &SolveOptions) -> String {
+    format!(
+        "{{\"algorithm\":\"{:?}\",\"heuristic\":\"{}\",\"max_nodes\":{},\"canonical\":{}}}",
+        options.algorithm,
+        options.heuristic,
+        options.max_nodes.map_or("null".to_string(), |max_nodes| max_nodes.to_string()),
+        options.canonical,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Notation;
+
+    /// Get the raw text of a field of a single-line JSON object.
+    fn _field<'a>(line: &'a str, key: &str) -> &'a str {
+        let start = line.find(&format!("\"{}\":", key)).unwrap() + key.len() + 3;
+        let rest = &line[start..];
+        let end = match rest.chars().next() {
+            Some('"') => rest[1..].find('"').unwrap() + 2,
+            Some('{') => rest.find('}').unwrap() + 1,
+            _ => rest.find([',', '}']).unwrap(),
+        };
+        &rest[..end]
+    }
+
+    /// Test a batch report records every instance with a path that replays
+    #[test]
+    fn test_run_batch_report() {
+        let boards: Vec<Board> = (0..3).map(|i| Board::generate(3, 392 + i, 30).unwrap()).collect();
+        let options = SolveOptions::new().max_nodes(100_000);
+        let report = String::from_utf8(run_batch(&boards, &options, Some(392), Vec::new()).unwrap()).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(_field(lines[0], "type"), "\"header\"");
+        let options_json = "{\"algorithm\":\"AStar\",\"heuristic\":\"linear_conflicts\",\"max_nodes\":100000,";
+        assert_eq!(_field(lines[0], "options"), format!("{}\"canonical\":false}}", options_json));
+        assert_eq!((_field(lines[0], "seed"), _field(lines[0], "instances")), ("392", "3"));
+        let checksum = format!("\"{:016x}\"", inputs_checksum(&boards));
+        assert_eq!(_field(lines[0], "inputs_checksum"), checksum);
+        for (i, line) in lines[1..4].iter().enumerate() {
+            assert_eq!(_field(line, "index"), i.to_string());
+            let board = crate::try_parse_board_info(_field(line, "board").trim_matches('"')).unwrap();
+            assert_eq!(board, boards[i]);
+            let path = _field(line, "path").trim_matches('"');
+            assert_eq!(_field(line, "length"), path.len().to_string());
+            assert!(solver::verify_solution(&board, path, Notation::BlankMoves));
+            assert!(_field(line, "wall_time_ms").parse::<f64>().unwrap() >= 0.0);
+        }
+        assert_eq!((_field(lines[4], "solved"), _field(lines[4], "failed")), ("3", "0"));
+        // Any change to the inputs changes the checksum
+        assert_ne!(inputs_checksum(&boards), inputs_checksum(&boards[..2]));
+    }
+
+    /// Test failures injected into a batch are reported like genuine budget failures
+    #[cfg(feature = "chaos")]
+    #[test]
+    fn test_report_injected_failure() {
+        let boards = vec![Board::generate(3, 394, 200).unwrap()];
+        let report = |options: SolveOptions| String::from_utf8(run_batch(&boards, &options, None, Vec::new()).unwrap());
+        let genuine = report(SolveOptions::new().max_nodes(20)).unwrap();
+        let injected = report(SolveOptions::new().fail_after_nodes(Some(20))).unwrap();
+        let (genuine, injected): (Vec<&str>, Vec<&str>) = (genuine.lines().collect(), injected.lines().collect());
+        assert!(_field(genuine[1], "error").starts_with("{\"code\":203,"));
+        assert_eq!(_field(injected[1], "error"), _field(genuine[1], "error"));
+        assert_eq!(injected[1].split("\"wall_time_ms\"").next(), genuine[1].split("\"wall_time_ms\"").next());
+        assert_eq!((_field(injected[2], "solved"), _field(injected[2], "failed")), ("0", "1"));
+    }
+
+    /// Test failed instances are reported with their error
+    #[test]
+    fn test_report_failed_instance() {
+        let boards = vec![Board::generate(3, 392, 30).unwrap()];
+        let mut writer = ReportWriter::new(Vec::new(), &boards, &SolveOptions::new(), None).unwrap();
+        writer.write_instance(0, &boards[0], &Err(SolveError::Unreachable), Duration::from_millis(2)).unwrap();
+        let report = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(_field(lines[0], "seed"), "null");
+        assert_eq!(_field(lines[1], "error"), "{\"code\":201,\"message\":\"goal is unreachable from this board\"}");
+        assert_eq!(_field(lines[1], "wall_time_ms"), "2");
+        assert!(!lines[1].contains("\"solution\""));
+        assert_eq!((_field(lines[2], "solved"), _field(lines[2], "failed")), ("0", "1"));
+    }
+}
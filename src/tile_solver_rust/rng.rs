@@ -0,0 +1,127 @@
+//! Random number generation that behaves the same natively and in wasm.
+//!
+//! `RngSource` is a small SplitMix64 generator implementing rand's `RngCore`, so it
+//! works with `Board::shuffle_with_rng` and friends without the operating system entropy
+//! that `rand::thread_rng` needs, which wasm doesn't have without extra getrandom
+//! features. Explicitly seeded sources give the same numbers on every platform, so a
+//! board generated from a seed in the browser matches the one generated natively.
+//! `rand::thread_rng` stays usable natively through the default `std` feature.
+
+use rand::{Error, RngCore, SeedableRng};
+
+/// Added to the state before each output, the golden ratio in 64-bit fixed point
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// SplitMix64 random number generator.
+/// * Not cryptographically secure; meant for shuffling boards and sampling.
+///
+/// # Attributes
+/// * `state` - Value advanced by `GOLDEN_GAMMA` before each output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RngSource {
+    state: u64,
+}
+
+impl RngSource {
+    /// Create a source from an explicit seed.
+    /// * Equal seeds give equal sequences on every platform.
+    ///
+    /// # Parameters
+    /// * `seed` - Seed of the generator
+    pub fn seeded(seed: u64) -> RngSource {
+        RngSource { state: seed }
+    }
+
+    /// Create a source seeded from the clock.
+    /// * Uses `Date.now()` under wasm and the system time natively.
+    #[cfg(not(alloc_only))]
+    pub fn from_clock() -> RngSource {
+        RngSource::seeded(_clock_seed())
+    }
+}
+
+impl RngCore for RngSource {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for RngSource {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: [u8; 8]) -> RngSource {
+        RngSource::seeded(u64::from_le_bytes(seed))
+    }
+
+    /// Use the seed as the state, rather than rand's default of expanding it with PCG.
+    fn seed_from_u64(seed: u64) -> RngSource {
+        RngSource::seeded(seed)
+    }
+}
+
+/// Read the clock as a seed.
+///
+/// # Returns
+/// Milliseconds since the Unix epoch
+#[cfg(all(not(alloc_only), target_arch = "wasm32"))]
+fn _clock_seed() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// Read the clock as a seed.
+///
+/// # Returns
+/// Nanoseconds since the Unix epoch, truncated to 64 bits
+#[cfg(all(not(alloc_only), not(target_arch = "wasm32")))]
+fn _clock_seed() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_nanos() as u64).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    /// Test seeded sources give the published SplitMix64 sequence
+    #[test]
+    fn test_rng_source_seeded() {
+        // Reference outputs of SplitMix64 seeded with 1234567; wasm builds must match
+        let mut rng = RngSource::seeded(1234567);
+        let expected = [
+            6457827717110365317, 3203168211198807973, 9817491932198370423,
+            4593380528125082431, 16408922859458223821,
+        ];
+        for &value in &expected {
+            assert_eq!(rng.next_u64(), value);
+        }
+        assert_eq!(RngSource::seed_from_u64(388), RngSource::from_seed(388u64.to_le_bytes()));
+        let mut a = RngSource::seeded(388);
+        let mut b = a.clone();
+        let mut bytes = [0; 11];
+        a.fill_bytes(&mut bytes);
+        assert_eq!(&bytes[..8], &b.next_u64().to_le_bytes());
+        let rolls: Vec<u32> = (0..100).map(|_| a.gen_range(0, 6)).collect();
+        assert!(rolls.iter().all(|&roll| roll < 6) && (0..6).all(|face| rolls.contains(&face)));
+    }
+}
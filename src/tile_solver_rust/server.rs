@@ -0,0 +1,711 @@
+//! Long-running solver server speaking newline-delimited JSON.
+//!
+//! Intended for a persistent process driven by another program over stdin/stdout:
+//!
+//! ```no_run
+//! use std::io;
+//! use sliding_tile_puzzle_solver::server;
+//!
+//! server::serve(io::stdin().lock(), io::stdout()).unwrap();
+//! ```
+//!
+//! Requests, one JSON object per line:
+//! * `{"id": 1, "cmd": "solve", "board": "3,1,2,3,-1,4,6,7,5,8", "options": {...}}`,
+//!   with optional options `canonical`, `heuristic` (`"manhattan"`, `"linear_conflicts"`
+//!   or `"inversion_distance"`, or several joined with `+` for their maximum) and
+//!   `max_nodes`
+//! * `{"id": 2, "cmd": "generate", "n": 4, "seed": 7, "shuffles": 100}`
+//! * `{"id": 3, "cmd": "estimate", "board": "..."}`
+//! * `{"id": 1, "cmd": "cancel"}` - Cancel the in-flight solve with that id
+//!
+//! Responses, one JSON object per line:
+//! * `{"id": 1, "solution": {"path": ..., "stats": ..., "optimal": true}}`
+//! * `{"id": 2, "board": "...", "metrics": {...}}`, see `board::BoardMetrics`
+//! * `{"id": 3, "estimate": 5}`
+//! * `{"id": 1, "code": 201, "error": "..."}`, with a null id if the request had no usable
+//!   id and a numeric code from `error_code`; a solve that reaches `max_nodes` adds
+//!   `"details"`, see `solver::PartialResult`
+//!
+//! Solves run on their own threads in chunks, so other requests are answered while
+//! they run and a cancelled solve stops at the next chunk. They start from
+//! `SolveOptions::for_size` and give up past `SERVER_MAX_MEMORY_BYTES`. Bad requests
+//! are answered with an error and never stop the server.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::board::{Board, Heuristic};
+use super::error_code::{self, CodedError};
+use super::solver::{self, SolveError, SolveOptions, Solver};
+use super::{format_board_info, try_parse_board_info};
+use super::worker::{DEFAULT_CHUNK_NODES, DEFAULT_GENERATE_SHUFFLES};
+
+/// Memory budget of solves, in bytes, so no request can exhaust the server's memory
+pub const SERVER_MAX_MEMORY_BYTES: u64 = 1 << 30;
+
+/// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Look up a field of an object.
+    ///
+    /// # Parameters
+    /// * `key` - Name of the field
+    ///
+    /// # Returns
+    /// The field's value, if this is an object with that field
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a non-negative integer.
+    ///
+    /// # Returns
+    /// The integer, if this is a number with no fractional part that fits in a u64
+    fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Json::Number(number) if number >= 0.0 && number.fract() == 0.0 && number < u64::MAX as f64 =>
+                Some(number as u64),
+            _ => None,
+        }
+    }
+}
+
+/// Deepest nesting of arrays and objects the JSON parser accepts, so a hostile request
+/// can't overflow the stack
+const MAX_JSON_DEPTH: usize = 64;
+
+/// Recursive descent JSON parser.
+///
+/// # Attributes
+/// * `text` - Bytes being parsed
+/// * `position` - Index of the next byte
+/// * `depth` - Number of arrays and objects the next value is nested in
+pub(crate) struct JsonParser<'a> {
+    text: &'a [u8],
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    /// Parse a complete JSON document.
+    ///
+    /// # Parameters
+    /// * `text` - JSON text
+    ///
+    /// # Returns
+    /// The value, or a description of the syntax error
+    pub(crate) fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = JsonParser { text: text.as_bytes(), position: 0, depth: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position != parser.text.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(value)
+    }
+
+    /// Describe a syntax error at the current position.
+    fn error(&self, problem: &str) -> String {
+        format!("invalid JSON: {} at byte {}", problem, self.position)
+    }
+
+    /// Skip whitespace between tokens.
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.text.get(self.position) {
+            self.position += 1;
+        }
+    }
+
+    /// Consume an expected literal.
+    fn expect(&mut self, literal: &str) -> Result<(), String> {
+        if self.text[self.position..].starts_with(literal.as_bytes()) {
+            self.position += literal.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {}", literal)))
+        }
+    }
+
+    /// Parse any value.
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.text.get(self.position) {
+            Some(b'n') => self.expect("null").map(|_| Json::Null),
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'[') => self.nested(JsonParser::array),
+            Some(b'{') => self.nested(JsonParser::object),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    /// Parse an array or object one level deeper.
+    ///
+    /// # Parameters
+    /// * `parse` - Parser of the array or object
+    ///
+    /// # Returns
+    /// The value, or an error if it is nested deeper than `MAX_JSON_DEPTH`
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Json, String>) -> Result<Json, String> {
+        if self.depth == MAX_JSON_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    /// Parse a number.
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.position;
+        while let Some(b'-') | Some(b'+') | Some(b'.') | Some(b'e') | Some(b'E') | Some(b'0'..=b'9') =
+            self.text.get(self.position) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.text[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .map(Json::Number)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+
+    /// Parse a string, starting at its opening quote.
+    fn string(&mut self) -> Result<String, String> {
+        self.expect("\"")?;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = *self.text.get(self.position).ok_or_else(|| self.error("unterminated string"))?;
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = *self.text.get(self.position).ok_or_else(|| self.error("unterminated string"))?;
+                    self.position += 1;
+                    let unescaped = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    bytes.extend_from_slice(unescaped.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                _ => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8"))
+    }
+
+    /// Parse the hex digits of a `\u` escape, including a following low surrogate.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let mut code = self.hex4()?;
+        if (0xD800..0xDC00).contains(&code) {
+            self.expect("\\u")?;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("invalid surrogate pair"));
+            }
+            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+        }
+        std::char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    /// Parse 4 hex digits.
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self.text.get(self.position..self.position + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.position += 4;
+        Ok(digits)
+    }
+
+    /// Parse an array, starting at its opening bracket.
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect("[")?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.text.get(self.position) == Some(&b']') {
+            self.position += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.text.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("expected , or ]")),
+            }
+        }
+    }
+
+    /// Parse an object, starting at its opening brace.
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.text.get(self.position) == Some(&b'}') {
+            self.position += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.text.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Json::Object(fields));
+                }
+                _ => return Err(self.error("expected , or }")),
+            }
+        }
+    }
+}
+
+/// Format a string as a JSON string literal.
+///
+/// # Parameters
+/// * `text` - The string
+///
+/// # Returns
+/// Quoted and escaped string
+pub fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A request to the server.
+#[derive(Debug, Clone)]
+enum ServerRequest {
+    /// Solve a board on a worker thread
+    Solve { board: Board, options: SolveOptions },
+    /// Generate a shuffled board
+    Generate { n: i32, seed: u64, shuffles: i32 },
+    /// Estimate the solution length of a board
+    Estimate { board: Board },
+    /// Cancel the in-flight solve with the same id
+    Cancel,
+}
+
+/// Parse a request line.
+///
+/// # Parameters
+/// * `line` - JSON request
+///
+/// # Returns
+/// The request id (if usable) and either the request or the problem
+fn _parse_request(line: &str) -> (Option<u64>, Result<ServerRequest, CodedError>) {
+    let invalid = |message| CodedError::new(error_code::ERR_INVALID_REQUEST, message);
+    let json = match JsonParser::parse(line) {
+        Ok(json) => json,
+        Err(e) => return (None, Err(invalid(e))),
+    };
+    let id = json.get("id").and_then(Json::as_u64);
+    if !matches!(json, Json::Object(_)) {
+        return (None, Err(invalid("request must be a JSON object".to_string())));
+    }
+    if id.is_none() {
+        return (None, Err(invalid("missing non-negative integer field \"id\"".to_string())));
+    }
+    (id, _request_from_json(&json))
+}
+
+/// Convert a request object into a request.
+///
+/// # Parameters
+/// * `json` - Request object
+///
+/// # Returns
+/// The request, or the problem
+fn _request_from_json(json: &Json) -> Result<ServerRequest, CodedError> {
+    let invalid = |message: String| CodedError::new(error_code::ERR_INVALID_REQUEST, message);
+    let board = || match json.get("board") {
+        Some(Json::String(board)) => try_parse_board_info(board).map_err(|e| CodedError::from_error(&e)),
+        _ => Err(invalid("missing string field \"board\"".to_string())),
+    };
+    let integer = |key: &str| json.get(key).map(|value| {
+        value.as_u64().ok_or_else(|| invalid(format!("field {:?} must be a non-negative integer", key)))
+    });
+    match json.get("cmd") {
+        Some(Json::String(cmd)) => match cmd.as_str() {
+            "solve" => {
+                let board = board()?;
+                let options = _options_from_json(json.get("options"), board.size())?;
+                Ok(ServerRequest::Solve { board, options })
+            }
+            "generate" => Ok(ServerRequest::Generate {
+                n: integer("n").ok_or_else(|| invalid("missing field \"n\"".to_string()))?? as i32,
+                seed: integer("seed").unwrap_or(Ok(0))?,
+                shuffles: integer("shuffles").unwrap_or(Ok(DEFAULT_GENERATE_SHUFFLES as u64))? as i32,
+            }),
+            "estimate" => Ok(ServerRequest::Estimate { board: board()? }),
+            "cancel" => Ok(ServerRequest::Cancel),
+            other => Err(invalid(format!("unknown command {:?}", other))),
+        },
+        _ => Err(invalid("missing string field \"cmd\"".to_string())),
+    }
+}
+
+/// Convert a solve options object into options.
+/// * The object overrides `SolveOptions::for_size`, with `SERVER_MAX_MEMORY_BYTES` as
+///   the memory budget unless a default budget was set.
+///
+/// # Parameters
+/// * `json` - Options object, if given
+/// * `n` - Size of the board being solved
+///
+/// # Returns
+/// The options, or the problem
+fn _options_from_json(json: Option<&Json>, n: i32) -> Result<SolveOptions, CodedError> {
+    let invalid = |message: String| CodedError::new(error_code::ERR_INVALID_REQUEST, message);
+    let mut options = SolveOptions::for_size(n);
+    if options.max_memory_bytes.is_none() {
+        options = options.max_memory_bytes(SERVER_MAX_MEMORY_BYTES);
+    }
+    let fields = match json {
+        None | Some(Json::Null) => return Ok(options),
+        Some(Json::Object(fields)) => fields,
+        Some(_) => return Err(invalid("\"options\" must be an object".to_string())),
+    };
+    for (key, value) in fields {
+        options = match (key.as_str(), value) {
+            ("canonical", Json::Bool(canonical)) => options.canonical(*canonical),
+            ("heuristic", Json::String(name)) => match Heuristic::parse(name) {
+                Some(heuristic) => options.heuristic(heuristic),
+                None => return Err(invalid(format!("unknown heuristic {:?}", name))),
+            },
+            ("max_nodes", value) if value.as_u64().is_some() => options.max_nodes(value.as_u64().unwrap()),
+            // Undocumented, for testing how clients handle failures
+            #[cfg(feature = "chaos")]
+            ("fail_after_nodes", value) if value.as_u64().is_some() =>
+                options.fail_after_nodes(value.as_u64()),
+            #[cfg(feature = "chaos")]
+            ("fail_with", Json::String(name)) => {
+                match solver::SOLVE_ERROR_KINDS.iter().find(|kind| kind.to_string() == *name) {
+                    Some(&kind) => options.fail_with(kind),
+                    None => return Err(invalid(format!("unknown solve error kind {:?}", name))),
+                }
+            }
+            #[cfg(feature = "chaos")]
+            ("fail_after_nodes", _) | ("fail_with", _) =>
+                return Err(invalid(format!("invalid value for option {:?}", key))),
+            ("canonical", _) | ("heuristic", _) | ("max_nodes", _) =>
+                return Err(invalid(format!("invalid value for option {:?}", key))),
+            _ => return Err(invalid(format!("unknown option {:?}", key))),
+        };
+    }
+    options.validate().map_err(|e| CodedError::from_error(&e))?;
+    Ok(options)
+}
+
+/// Format a response line.
+///
+/// # Parameters
+/// * `id` - Request id, if known
+/// * `body` - Remaining fields of the response object, without braces
+///
+/// # Returns
+/// JSON response, without a trailing newline
+fn _response(id: Option<u64>, body: &str) -> String {
+    let id = id.map_or("null".to_string(), |id| id.to_string());
+    format!("{{\"id\":{},{}}}", id, body)
+}
+
+/// Format an error response line.
+///
+/// # Parameters
+/// * `id` - Request id, if known
+/// * `error` - The error, with its code
+///
+/// # Returns
+/// JSON response, without a trailing newline
+fn _error_response(id: Option<u64>, error: &CodedError) -> String {
+    _response(id, &_error_fields(error))
+}
+
+/// Format the fields of an error response.
+///
+/// # Parameters
+/// * `error` - The error, with its code
+///
+/// # Returns
+/// Error fields of the response object, without braces; `details` is only written
+/// when the error has details
+fn _error_fields(error: &CodedError) -> String {
+    let fields = format!("\"code\":{},\"error\":{}", error.code, json_string(&error.message));
+    match &error.details {
+        Some(details) => format!("{},\"details\":{}", fields, details),
+        None => fields,
+    }
+}
+
+/// Serve requests until the input ends.
+/// * Solves still running when the input ends are finished before returning.
+///
+/// # Parameters
+/// * `input` - Request lines
+/// * `output` - Where response lines are written
+///
+/// # Returns
+/// An error only if reading input or writing output fails
+pub fn serve<R: BufRead, W: Write + Send>(input: R, output: W) -> io::Result<()> {
+    let output = Mutex::new(output);
+    let write_line = |line: String| -> io::Result<()> {
+        let mut output = output.lock().unwrap();
+        writeln!(output, "{}", line)?;
+        output.flush()
+    };
+    // Cancellation flags of in-flight solves
+    let running: Mutex<HashMap<u64, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+    thread::scope(|scope| {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (id, request) = _parse_request(&line);
+            let request = match request {
+                Ok(request) => request,
+                Err(error) => {
+                    write_line(_error_response(id, &error))?;
+                    continue;
+                }
+            };
+            let id = id.unwrap();
+            match request {
+                ServerRequest::Solve { board, options } => {
+                    // Proving the goal unreachable by search could take forever on large boards
+                    if !board.is_solvable() {
+                        write_line(_error_response(Some(id), &CodedError::from_error(&SolveError::Unreachable)))?;
+                        continue;
+                    }
+                    let cancelled = Arc::new(AtomicBool::new(false));
+                    {
+                        let mut running = running.lock().unwrap();
+                        if running.contains_key(&id) {
+                            drop(running);
+                            let error = CodedError::new(
+                                error_code::ERR_REQUEST_RUNNING,
+                                format!("request id {} is already running", id),
+                            );
+                            write_line(_error_response(Some(id), &error))?;
+                            continue;
+                        }
+                        running.insert(id, cancelled.clone());
+                    }
+                    let (write_line, running) = (&write_line, &running);
+                    scope.spawn(move || {
+                        let response = _solve(&board, &options, &cancelled);
+                        running.lock().unwrap().remove(&id);
+                        // A closed output is reported by the reading loop's next write
+                        let response = response.unwrap_or_else(|error| _error_fields(&error));
+                        let _ = write_line(_response(Some(id), &response));
+                    });
+                }
+                ServerRequest::Generate { n, seed, shuffles } => {
+                    let response = match Board::generate(n, seed, shuffles) {
+                        Ok(board) => {
+                            let metrics = board.metrics(&Board::new(n, None, -1, None)).to_json();
+                            let board = json_string(&format_board_info(&board));
+                            _response(Some(id), &format!("\"board\":{},\"metrics\":{}", board, metrics))
+                        }
+                        Err(e) => _error_response(Some(id), &CodedError::from_error(&e)),
+                    };
+                    write_line(response)?;
+                }
+                ServerRequest::Estimate { board } => {
+                    let estimate = board.heuristic_breakdown(None).total;
+                    write_line(_response(Some(id), &format!("\"estimate\":{}", estimate)))?;
+                }
+                ServerRequest::Cancel => match running.lock().unwrap().get(&id) {
+                    // The solve responds once it sees the flag
+                    Some(cancelled) => cancelled.store(true, Ordering::SeqCst),
+                    None => {
+                        let message = format!("unknown request id {}", id);
+                        let error = CodedError::new(error_code::ERR_UNKNOWN_REQUEST, message);
+                        write_line(_error_response(Some(id), &error))?
+                    }
+                },
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Solve a board in chunks, stopping early if cancelled.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `options` - Options controlling the solve
+/// * `cancelled` - Set to cancel the solve
+///
+/// # Returns
+/// Fields of the response object, or the error
+fn _solve(board: &Board, options: &SolveOptions, cancelled: &AtomicBool) -> Result<String, CodedError> {
+    let mut solver = Solver::with_options(board.size(), board.tiles().to_vec(), options);
+    loop {
+        if cancelled.load(Ordering::SeqCst) {
+            return Err(CodedError::new(error_code::ERR_CANCELLED, "cancelled"));
+        }
+        if let Some(mut solution) = solver.step(DEFAULT_CHUNK_NODES).map_err(|e| CodedError::from_error(&e))? {
+            if options.canonical {
+                solution.moves = solver::canonical_moves(board, solution.moves.len(), &options.move_order);
+            }
+            return Ok(format!("\"solution\":{}", solution.to_json()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run a scripted session and return the response lines.
+    fn session(requests: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        serve(requests.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap().lines().map(str::to_string).collect()
+    }
+
+    /// Test the JSON parser on nested values and escapes
+    #[test]
+    fn test_json_parser() {
+        let json = JsonParser::parse(r#" {"a": [1, -2.5e1, true, null], "b": "x\"é😀\n"} "#).unwrap();
+        assert_eq!(json.get("a"), Some(&Json::Array(vec![
+            Json::Number(1.0), Json::Number(-25.0), Json::Bool(true), Json::Null,
+        ])));
+        assert_eq!(json.get("b"), Some(&Json::String("x\"\u{e9}\u{1f600}\n".to_string())));
+        for bad in &["", "{", "{\"a\" 1}", "[1,]", "\"abc", "{} x", "tru"] {
+            assert!(JsonParser::parse(bad).is_err(), "{:?}", bad);
+        }
+        assert_eq!(json_string("a\"b\\\n\u{1}"), "\"a\\\"b\\\\\\n\\u0001\"");
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(JsonParser::parse(&nested(MAX_JSON_DEPTH)).is_ok());
+        let too_deep = JsonParser::parse(&nested(MAX_JSON_DEPTH + 1)).unwrap_err();
+        assert_eq!(too_deep, "invalid JSON: too deeply nested at byte 64");
+    }
+
+    /// Test a session answering each command
+    #[test]
+    fn test_serve_session() {
+        let responses = session(concat!(
+            "{\"id\":1,\"cmd\":\"solve\",\"board\":\"3,1,2,3,-1,4,6,7,5,8\",\"options\":{\"canonical\":true}}\n",
+            "\n",
+            "{\"id\":2,\"cmd\":\"estimate\",\"board\":\"3,1,2,3,-1,4,6,7,5,8\"}\n",
+            "{\"id\":3,\"cmd\":\"generate\",\"n\":3,\"seed\":42,\"shuffles\":20}\n",
+        ));
+        assert_eq!(responses.len(), 3);
+        // The solve runs on its own thread, so it may answer in any position
+        let solve = responses.iter().find(|line| line.starts_with("{\"id\":1,")).unwrap();
+        assert!(solve.starts_with("{\"id\":1,\"solution\":{\"path\":\"RDR\",\"stats\":{"));
+        assert!(responses.contains(&"{\"id\":2,\"estimate\":3}".to_string()));
+        let board = Board::generate(3, 42, 20).unwrap();
+        let metrics = board.metrics(&Board::new(3, None, -1, None)).to_json();
+        let board = format_board_info(&board);
+        assert!(responses.contains(&format!("{{\"id\":3,\"board\":\"{}\",\"metrics\":{}}}", board, metrics)));
+    }
+
+    /// Test bad requests get error responses and don't stop the server
+    #[test]
+    fn test_serve_bad_requests() {
+        let responses = session(concat!(
+            "hello\n",
+            "[1, 2]\n",
+            "{\"cmd\":\"estimate\"}\n",
+            "{\"id\":4,\"cmd\":\"fly\"}\n",
+            "{\"id\":5,\"cmd\":\"estimate\",\"board\":\"3,1,2\"}\n",
+            "{\"id\":6,\"cmd\":\"solve\",\"board\":\"2,2,1,3,-1\"}\n",
+            "{\"id\":7,\"cmd\":\"solve\",\"board\":\"2,1,2,3,-1\",\"options\":{\"depth\":3}}\n",
+            "{\"id\":8,\"cmd\":\"cancel\"}\n",
+            "{\"id\":9,\"cmd\":\"estimate\",\"board\":\"2,1,2,-1,3\"}\n",
+            "{\"id\":10,\"cmd\":\"solve\",\"board\":\"3,4,1,3,7,2,6,5,8,-1\",\"options\":{\"max_nodes\":1}}\n",
+        ));
+        assert_eq!(responses.len(), 10);
+        assert_eq!(responses[0], "{\"id\":null,\"code\":401,\"error\":\"invalid JSON: unexpected character at byte 0\"}");
+        assert_eq!(responses[1], "{\"id\":null,\"code\":401,\"error\":\"request must be a JSON object\"}");
+        assert_eq!(responses[2], "{\"id\":null,\"code\":401,\"error\":\"missing non-negative integer field \\\"id\\\"\"}");
+        assert_eq!(responses[3], "{\"id\":4,\"code\":401,\"error\":\"unknown command \\\"fly\\\"\"}");
+        assert_eq!(responses[4], "{\"id\":5,\"code\":102,\"error\":\"expected 9 tiles, found 2\"}");
+        assert!(responses.contains(&"{\"id\":6,\"code\":201,\"error\":\"goal is unreachable from this board\"}".to_string()));
+        assert!(responses.contains(&"{\"id\":7,\"code\":401,\"error\":\"unknown option \\\"depth\\\"\"}".to_string()));
+        assert!(responses.contains(&"{\"id\":8,\"code\":403,\"error\":\"unknown request id 8\"}".to_string()));
+        assert!(responses.contains(&"{\"id\":9,\"estimate\":1}".to_string()));
+        assert!(responses.iter().any(|response| response.starts_with(
+            "{\"id\":10,\"code\":203,\"error\":\"gave up after expanding 1 boards\",\"details\":{\"best_board\":\"3:"
+        )));
+        // Solves are budgeted and suited to the board size
+        let options = _options_from_json(None, 4).unwrap();
+        assert_eq!(options.algorithm, SolveOptions::for_size(4).algorithm);
+        assert_eq!(options.max_memory_bytes, Some(SERVER_MAX_MEMORY_BYTES));
+        // Unsolvable boards are answered without a search, which would never end on a 4x4
+        let unsolvable = "4,2,1,3,4,5,6,7,8,9,10,11,12,13,14,15,-1";
+        let responses = session(&format!("{{\"id\":12,\"cmd\":\"solve\",\"board\":\"{}\"}}\n", unsolvable));
+        assert_eq!(responses, ["{\"id\":12,\"code\":201,\"error\":\"goal is unreachable from this board\"}"]);
+        // Nesting deep enough to overflow the stack of a recursive parser
+        let nested = session(&format!("{}\n{{\"id\":11,\"cmd\":\"fly\"}}\n", "[".repeat(100_000)));
+        assert_eq!(nested, [
+            "{\"id\":null,\"code\":401,\"error\":\"invalid JSON: too deeply nested at byte 64\"}",
+            "{\"id\":11,\"code\":401,\"error\":\"unknown command \\\"fly\\\"\"}",
+        ]);
+    }
+
+    /// Test cancelling an in-flight solve
+    #[test]
+    fn test_serve_cancel() {
+        let cancelled = AtomicBool::new(true);
+        let board = try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        assert_eq!(_solve(&board, &SolveOptions::default(), &cancelled), Err(CodedError::new(error_code::ERR_CANCELLED, "cancelled")));
+        // A hard 4x4 board keeps the search running until it is cancelled
+        let board = format_board_info(&crate::benchmarks::korf_instances()[0]);
+        let responses = session(&format!(
+            "{{\"id\":1,\"cmd\":\"solve\",\"board\":\"{}\"}}\n{{\"id\":1,\"cmd\":\"cancel\"}}\n",
+            board
+        ));
+        assert_eq!(responses, vec!["{\"id\":1,\"code\":402,\"error\":\"cancelled\"}".to_string()]);
+    }
+}
@@ -0,0 +1,4361 @@
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::mem::{size_of, size_of_val};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::board::*;
+use super::clock::{self, TimeBudget};
+use super::conflict_cache::ConflictCache;
+use super::context::KnownDistances;
+use super::debug_checks::HeuristicViolation;
+use super::error_code::ErrorCode;
+use super::layered::{self, LayeredSolver};
+use super::logging::*;
+use super::observer::{NoopObserver, SearchProgress, SearchSample, SolverObserver};
+use super::pack;
+use super::tile::{Tile, BLANK_TILE};
+use super::transposition::{TranspositionTable, TtReplacement};
+use super::zobrist::ZobristTable;
+
+/// Iterations used when numerically solving for the effective branching factor.
+const EBF_ITERATIONS: i32 = 100;
+
+/// Default move ordering used to pick a canonical solution
+pub const CANONICAL_MOVE_ORDER: [char; 4] = [DOWN, LEFT, RIGHT, UP];
+
+/// Byte encoding of solution moves: each move is stored as its index in this array
+/// (0 = U, 1 = D, 2 = L, 3 = R)
+pub const MOVE_ENCODING: [char; 4] = [UP, DOWN, LEFT, RIGHT];
+
+/// Encode moves as one byte per move.
+///
+/// # Parameters
+/// * `moves` - String of single characters representing moves
+///
+/// # Returns
+/// Encoded moves, see `MOVE_ENCODING`, or `None` if a character isn't a move
+pub fn encode_moves(moves: &str) -> Option<Vec<u8>> {
+    moves.chars()
+        .map(|tile_move| MOVE_ENCODING.iter().position(|&m| m == tile_move).map(|code| code as u8))
+        .collect()
+}
+
+/// Decode moves encoded with `encode_moves`.
+///
+/// # Parameters
+/// * `bytes` - Encoded moves
+///
+/// # Returns
+/// String of single characters representing moves, or `None` if a byte isn't a move
+pub fn decode_moves(bytes: &[u8]) -> Option<String> {
+    bytes.iter().map(|&byte| MOVE_ENCODING.get(byte as usize).copied()).collect()
+}
+
+/// Reasons a board can't be solved.
+///
+/// # Error codes
+/// * 201 - `Unreachable`
+/// * 202 - `InvalidOptions`
+/// * 203 - `BudgetExceeded`
+/// * 204 - `TooLarge`
+/// * 205 - `MemoryBudget`
+/// * 206 - `HeuristicInconsistent`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveError {
+    /// Every reachable board was expanded without finding the goal
+    Unreachable,
+    /// The options are contradictory or out of range
+    InvalidOptions(String),
+    /// The search expanded the maximum number of boards without finding the goal
+    BudgetExceeded(Box<PartialResult>),
+    /// The board is too large for the analysis, or its memory couldn't be allocated
+    TooLarge(String),
+    /// The search would have grown past `SolveOptions::max_memory_bytes`
+    MemoryBudget(String),
+    /// The heuristic failed a check of `SolveOptions::paranoid`, so the solution could
+    /// have been wrong
+    HeuristicInconsistent(Box<HeuristicViolation>),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Unreachable => write!(f, "goal is unreachable from this board"),
+            SolveError::InvalidOptions(reason) => write!(f, "invalid solve options: {}", reason),
+            SolveError::BudgetExceeded(partial) => {
+                write!(f, "gave up after expanding {} boards", partial.stats.nodes_expanded)
+            }
+            SolveError::TooLarge(reason) => write!(f, "board is too large: {}", reason),
+            SolveError::MemoryBudget(reason) => write!(f, "search is out of memory: {}", reason),
+            SolveError::HeuristicInconsistent(violation) => write!(f, "heuristic failed a check, {}", violation),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+impl ErrorCode for SolveError {
+    fn code(&self) -> u32 {
+        match self {
+            SolveError::Unreachable => 201,
+            SolveError::InvalidOptions(_) => 202,
+            SolveError::BudgetExceeded(_) => 203,
+            SolveError::TooLarge(_) => 204,
+            SolveError::MemoryBudget(_) => 205,
+            SolveError::HeuristicInconsistent(_) => 206,
+        }
+    }
+
+    fn details(&self) -> Option<String> {
+        match self {
+            SolveError::BudgetExceeded(partial) => Some(partial.to_json()),
+            _ => None,
+        }
+    }
+}
+
+impl SolveError {
+    /// Get the kind of the error, without its details.
+    pub fn kind(&self) -> SolveErrorKind {
+        match self {
+            SolveError::Unreachable => SolveErrorKind::Unreachable,
+            SolveError::InvalidOptions(_) => SolveErrorKind::InvalidOptions,
+            SolveError::BudgetExceeded(_) => SolveErrorKind::BudgetExceeded,
+            SolveError::TooLarge(_) => SolveErrorKind::TooLarge,
+            SolveError::MemoryBudget(_) => SolveErrorKind::MemoryBudget,
+            SolveError::HeuristicInconsistent(_) => SolveErrorKind::HeuristicInconsistent,
+        }
+    }
+}
+
+/// Kinds of `SolveError`, e.g. to choose which error to inject with the `chaos` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SolveErrorKind {
+    Unreachable,
+    InvalidOptions,
+    #[default]
+    BudgetExceeded,
+    TooLarge,
+    MemoryBudget,
+    HeuristicInconsistent,
+}
+
+/// Every kind of solve error, in error code order
+pub const SOLVE_ERROR_KINDS: [SolveErrorKind; 6] = [
+    SolveErrorKind::Unreachable,
+    SolveErrorKind::InvalidOptions,
+    SolveErrorKind::BudgetExceeded,
+    SolveErrorKind::TooLarge,
+    SolveErrorKind::MemoryBudget,
+    SolveErrorKind::HeuristicInconsistent,
+];
+
+impl fmt::Display for SolveErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SolveErrorKind::Unreachable => "unreachable",
+            SolveErrorKind::InvalidOptions => "invalid_options",
+            SolveErrorKind::BudgetExceeded => "budget_exceeded",
+            SolveErrorKind::TooLarge => "too_large",
+            SolveErrorKind::MemoryBudget => "memory_budget",
+            SolveErrorKind::HeuristicInconsistent => "heuristic_inconsistent",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What a search learned before giving up, e.g. to tell the player how far off the
+/// goal is.
+///
+/// # Attributes
+/// * `best_board` - Expanded board with the lowest heuristic
+/// * `best_path` - Moves from the start to `best_board`
+/// * `best_heuristic` - Heuristic of `best_board`
+/// * `proven_lower_bound` - No solution is shorter than this: the IDA* bound being
+///   searched, the lowest cost on the A* frontier, or the starting heuristic for greedy
+///   search
+/// * `stats` - Statistics collected up to the limit
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialResult {
+    pub best_board: Board,
+    pub best_path: String,
+    pub best_heuristic: i32,
+    pub proven_lower_bound: i32,
+    pub stats: SolveStats,
+}
+
+impl PartialResult {
+    /// Record the best board of a search that gave up.
+    ///
+    /// # Parameters
+    /// * `best` - Expanded board with the lowest heuristic, with its path
+    /// * `proven_lower_bound` - Smallest solution length that hasn't been ruled out
+    /// * `stats` - Statistics collected up to the limit
+    pub fn new(best: &Board, proven_lower_bound: i32, stats: SolveStats) -> PartialResult {
+        PartialResult {
+            best_board: best.clone(),
+            best_path: best.path.clone(),
+            best_heuristic: best.stored_h(),
+            proven_lower_bound,
+            stats,
+        }
+    }
+
+    /// Format the partial result as a JSON object.
+    /// * The board is written as `Board::to_compact_string`.
+    ///
+    /// # Returns
+    /// JSON representation of the partial result
+    pub fn to_json(&self) -> String {
+        format!(
+            concat!(
+                "{{\"best_board\":\"{}\",\"best_path\":\"{}\",\"best_heuristic\":{},",
+                "\"proven_lower_bound\":{},\"stats\":{}}}"
+            ),
+            self.best_board.to_compact_string(),
+            self.best_path,
+            self.best_heuristic,
+            self.proven_lower_bound,
+            self.stats.to_json()
+        )
+    }
+}
+
+/// Version of the solver state format written by `Solver::serialize_state`
+pub const STATE_VERSION: u8 = 5;
+
+/// Reasons saved solver state can't be resumed.
+///
+/// # Error codes
+/// * 301 - `UnsupportedVersion`
+/// * 302 - `Truncated`
+/// * 303 - `Corrupt`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResumeError {
+    /// The state was written by a different version of the format
+    UnsupportedVersion(u8),
+    /// The state ended before all of its data was read
+    Truncated,
+    /// The state is not valid solver state
+    Corrupt(String),
+}
+
+impl fmt::Display for ResumeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResumeError::UnsupportedVersion(version) => write!(
+                f, "unsupported solver state version {} (expected {})", version, STATE_VERSION
+            ),
+            ResumeError::Truncated => write!(f, "solver state is truncated"),
+            ResumeError::Corrupt(reason) => write!(f, "corrupt solver state: {}", reason),
+        }
+    }
+}
+
+impl Error for ResumeError {}
+
+impl ErrorCode for ResumeError {
+    fn code(&self) -> u32 {
+        match self {
+            ResumeError::UnsupportedVersion(_) => 301,
+            ResumeError::Truncated => 302,
+            ResumeError::Corrupt(_) => 303,
+        }
+    }
+}
+
+/// Version of the compact solution format written by `Solution::encode_compact`
+pub const COMPACT_VERSION: u8 = 1;
+
+/// Length of the compact solution header: the version byte and a u32 move count
+const COMPACT_HEADER_LEN: usize = 5;
+
+/// Reasons a compact solution can't be decoded.
+///
+/// # Error codes
+/// * 304 - `UnsupportedVersion`
+/// * 305 - `Truncated`
+/// * 306 - `Corrupt`
+/// * 307 - `IllegalMove`
+/// * 308 - `NotSolved`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The solution was written by a different version of the format
+    UnsupportedVersion(u8),
+    /// The data ended before all of the moves were read
+    Truncated,
+    /// The data is not a compact solution
+    Corrupt(String),
+    /// Replaying the moves from the start board made an illegal move
+    IllegalMove(IllegalMove),
+    /// Replaying the moves from the start board didn't reach the goal
+    NotSolved,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::UnsupportedVersion(version) => write!(
+                f, "unsupported compact solution version {} (expected {})", version, COMPACT_VERSION
+            ),
+            DecodeError::Truncated => write!(f, "compact solution is truncated"),
+            DecodeError::Corrupt(reason) => write!(f, "corrupt compact solution: {}", reason),
+            DecodeError::IllegalMove(e) => write!(f, "compact solution doesn't replay: {}", e),
+            DecodeError::NotSolved => write!(f, "compact solution doesn't solve the board"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+impl ErrorCode for DecodeError {
+    fn code(&self) -> u32 {
+        match self {
+            DecodeError::UnsupportedVersion(_) => 304,
+            DecodeError::Truncated => 305,
+            DecodeError::Corrupt(_) => 306,
+            DecodeError::IllegalMove(_) => 307,
+            DecodeError::NotSolved => 308,
+        }
+    }
+}
+
+/// Search algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// Best-first search over a priority queue, with a closed set
+    #[default]
+    AStar,
+    /// Iterative deepening A*, using memory linear in the solution length
+    IdaStar,
+    /// Best-first search on the heuristic alone, ignoring moves made so far; much
+    /// faster than A* but solutions aren't optimal
+    Greedy,
+    /// Place the board a row and a column at a time, then solve the 3x3 core with A*;
+    /// solves any size quickly but solutions aren't optimal, see `layered`
+    Layered,
+}
+
+impl Algorithm {
+    /// Check if the algorithm always finds an optimal solution.
+    pub fn is_optimal(self) -> bool {
+        match self {
+            Algorithm::AStar | Algorithm::IdaStar => true,
+            Algorithm::Greedy | Algorithm::Layered => false,
+        }
+    }
+}
+
+/// Options controlling how a board is solved.
+/// * Build with `SolveOptions::new()` and the builder methods, e.g.
+///   `SolveOptions::new().algorithm(Algorithm::IdaStar).max_nodes(5_000_000)`.
+///
+/// # Attributes
+/// * `canonical` - Return the lexicographically smallest optimal solution
+/// * `move_order` - Ordering of moves used for the lexicographic comparison
+/// * `algorithm` - Search algorithm
+/// * `heuristic` - Heuristic guiding the search
+/// * `max_nodes` - Give up after expanding this many boards
+/// * `max_memory_bytes` - Give up before the A* or greedy search structures grow past
+///   about this many bytes; defaults to `default_memory_budget()`
+/// * `max_time_ms` - Give up once this many milliseconds have passed since the search
+///   started
+/// * `tt_entries` - Size of the IDA* transposition table, 0 to search without one
+/// * `tt_replacement` - Which transposition table entry is kept on a collision
+/// * `order_children` - In IDA*, search the children of each board in ascending order
+///   of cost, so the last iteration reaches the goal sooner
+/// * `stop_on_generate` - In greedy search, stop as soon as the goal is generated
+///   instead of when it is expanded
+/// * `track_path_expansions` - Count the boards expanded below each prefix of the
+///   solution, see `Solution::move_metadata`
+/// * `zobrist_seed` - Key the A* and greedy visited sets on Zobrist hashes drawn with
+///   this seed instead of on the tiles
+/// * `paranoid` - Check the heuristic during A*, greedy and IDA* search, failing with
+///   `SolveError::HeuristicInconsistent` instead of returning a wrong answer
+/// * `fail_after_nodes` - With the `chaos` feature, fail on purpose after expanding
+///   this many boards
+/// * `fail_with` - With the `chaos` feature, kind of error the injected failure returns
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveOptions {
+    pub canonical: bool,
+    pub move_order: [char; 4],
+    pub algorithm: Algorithm,
+    pub heuristic: Heuristic,
+    pub max_nodes: Option<u64>,
+    pub max_memory_bytes: Option<u64>,
+    pub max_time_ms: Option<u64>,
+    pub tt_entries: usize,
+    pub tt_replacement: TtReplacement,
+    pub order_children: bool,
+    pub stop_on_generate: bool,
+    pub track_path_expansions: bool,
+    pub zobrist_seed: Option<u64>,
+    pub paranoid: bool,
+    #[cfg(feature = "chaos")]
+    pub fail_after_nodes: Option<u64>,
+    #[cfg(feature = "chaos")]
+    pub fail_with: SolveErrorKind,
+}
+
+impl Default for SolveOptions {
+    fn default() -> SolveOptions {
+        SolveOptions {
+            canonical: false,
+            move_order: CANONICAL_MOVE_ORDER,
+            algorithm: Algorithm::default(),
+            heuristic: Heuristic::default(),
+            max_nodes: None,
+            max_memory_bytes: default_memory_budget(),
+            max_time_ms: None,
+            tt_entries: 0,
+            tt_replacement: TtReplacement::default(),
+            order_children: false,
+            stop_on_generate: false,
+            track_path_expansions: false,
+            zobrist_seed: None,
+            paranoid: false,
+            #[cfg(feature = "chaos")]
+            fail_after_nodes: None,
+            #[cfg(feature = "chaos")]
+            fail_with: SolveErrorKind::default(),
+        }
+    }
+}
+
+impl SolveOptions {
+    /// Create the default options.
+    pub fn new() -> SolveOptions {
+        SolveOptions::default()
+    }
+
+    /// Create options suited to a board size, trading optimality for speed as boards grow.
+    /// * n <= 3: A* with linear conflicts, the default.
+    /// * n = 4: IDA* with linear conflicts, as A* exhausts memory on hard 15-puzzles.
+    /// * n >= 5: layered solving with linear conflicts, as no optimal search here finishes
+    ///   on a 24-puzzle; solutions aren't optimal, see `Solution::optimal`.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    pub fn for_size(n: i32) -> SolveOptions {
+        let algorithm = match n {
+            ..=3 => Algorithm::AStar,
+            4 => Algorithm::IdaStar,
+            _ => Algorithm::Layered,
+        };
+        SolveOptions::new().algorithm(algorithm).heuristic(Heuristic::LinearConflicts)
+    }
+
+    /// Set whether to return the lexicographically smallest optimal solution.
+    pub fn canonical(mut self, canonical: bool) -> SolveOptions {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Set the ordering of moves used for the lexicographic comparison.
+    pub fn move_order(mut self, move_order: [char; 4]) -> SolveOptions {
+        self.move_order = move_order;
+        self
+    }
+
+    /// Set the search algorithm.
+    pub fn algorithm(mut self, algorithm: Algorithm) -> SolveOptions {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the heuristic guiding the search.
+    pub fn heuristic(mut self, heuristic: Heuristic) -> SolveOptions {
+        self.heuristic = heuristic;
+        self
+    }
+
+    /// Set the maximum number of boards to expand.
+    pub fn max_nodes(mut self, max_nodes: u64) -> SolveOptions {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Set the approximate memory the A* and greedy search structures may grow to.
+    /// * IDA* and `solve_length` use memory linear in the solution length, besides the
+    ///   transposition table sized by `tt_entries`, so they ignore the budget.
+    pub fn max_memory_bytes(mut self, max_memory_bytes: u64) -> SolveOptions {
+        self.max_memory_bytes = Some(max_memory_bytes);
+        self
+    }
+
+    /// Set how long the search may run, in milliseconds.
+    /// * The clock is only read every few expansions, see `clock::TimeBudget`, so the
+    ///   search may overrun by a few tens of milliseconds. Layered solving only checks
+    ///   the budget while placing rows and columns.
+    pub fn max_time_ms(mut self, max_time_ms: u64) -> SolveOptions {
+        self.max_time_ms = Some(max_time_ms);
+        self
+    }
+
+    /// Set the size of the IDA* transposition table.
+    /// * The table is only used by IDA* and `solve_length`, on boards up to 4x4.
+    pub fn tt_entries(mut self, tt_entries: usize) -> SolveOptions {
+        self.tt_entries = tt_entries;
+        self
+    }
+
+    /// Set which transposition table entry is kept on a collision.
+    pub fn tt_replacement(mut self, tt_replacement: TtReplacement) -> SolveOptions {
+        self.tt_replacement = tt_replacement;
+        self
+    }
+
+    /// Set whether IDA* searches the children of each board in ascending order of cost.
+    /// * Used by IDA* and `solve_length`; ties keep the usual move order.
+    pub fn order_children(mut self, order_children: bool) -> SolveOptions {
+        self.order_children = order_children;
+        self
+    }
+
+    /// Set whether greedy search stops as soon as the goal is generated.
+    /// * Only allowed with `Algorithm::Greedy`: the optimal algorithms must wait until
+    ///   the goal is expanded.
+    pub fn stop_on_generate(mut self, stop_on_generate: bool) -> SolveOptions {
+        self.stop_on_generate = stop_on_generate;
+        self
+    }
+
+    /// Set whether to count the boards expanded below each prefix of the solution.
+    /// * Keeps the path of every expanded board until the solve finishes, so it costs
+    ///   memory proportional to the boards expanded times their depth.
+    pub fn track_path_expansions(mut self, track_path_expansions: bool) -> SolveOptions {
+        self.track_path_expansions = track_path_expansions;
+        self
+    }
+
+    /// Set the seed of the Zobrist table to key the A* and greedy visited sets on.
+    /// * Hashing 8 bytes per lookup instead of every tile speeds up large boards. Two
+    ///   boards sharing a hash would be treated as one, which could lose the optimal
+    ///   solution, but is expected once in about 2^64 pairs of boards.
+    /// * Snapshots of such a search don't hold its closed set, see
+    ///   `Solver::serialize_state`.
+    pub fn zobrist_seed(mut self, zobrist_seed: u64) -> SolveOptions {
+        self.zobrist_seed = Some(zobrist_seed);
+        self
+    }
+
+    /// Set whether A*, greedy and IDA* search check the heuristic as they go.
+    /// * A goal taken off the frontier, or reached by IDA*, must have a heuristic of 0,
+    ///   so its cost is its depth, and a heuristic claiming consistency (see `Heuristic::is_consistent`)
+    ///   must drop by at most 1 from a board to each child. Linear conflicts don't claim
+    ///   it, so only the goal is checked with them.
+    /// * The checks are a few comparisons per generated board. Debug builds always run
+    ///   them, panicking on a violation unless this is set.
+    pub fn paranoid(mut self, paranoid: bool) -> SolveOptions {
+        self.paranoid = paranoid;
+        self
+    }
+
+    /// Set after how many expanded boards the search fails on purpose, for testing how
+    /// callers handle failures.
+    /// * Checked wherever the search checks `max_nodes`, so a budget failure injected
+    ///   after as many boards as `max_nodes` is identical to the real one.
+    #[cfg(feature = "chaos")]
+    pub fn fail_after_nodes(mut self, fail_after_nodes: Option<u64>) -> SolveOptions {
+        self.fail_after_nodes = fail_after_nodes;
+        self
+    }
+
+    /// Set the kind of error the injected failure returns.
+    /// * Errors other than `BudgetExceeded` carry no partial result, as their real
+    ///   counterparts don't.
+    #[cfg(feature = "chaos")]
+    pub fn fail_with(mut self, fail_with: SolveErrorKind) -> SolveOptions {
+        self.fail_with = fail_with;
+        self
+    }
+
+    /// Check that the options make sense together.
+    ///
+    /// # Returns
+    /// `SolveError::InvalidOptions` describing the first problem found
+    pub fn validate(&self) -> Result<(), SolveError> {
+        let mut sorted_order = self.move_order;
+        sorted_order.sort_unstable();
+        let mut sorted_moves = MOVES;
+        sorted_moves.sort_unstable();
+        if sorted_order != sorted_moves {
+            return Err(SolveError::InvalidOptions(format!(
+                "move order {:?} must contain each of U, D, L and R once",
+                self.move_order.iter().collect::<String>()
+            )));
+        }
+        if self.max_nodes == Some(0) {
+            return Err(SolveError::InvalidOptions("max nodes must be at least 1".to_string()));
+        }
+        if self.max_memory_bytes == Some(0) {
+            return Err(SolveError::InvalidOptions("max memory bytes must be at least 1".to_string()));
+        }
+        if self.max_time_ms == Some(0) {
+            return Err(SolveError::InvalidOptions("max time must be at least 1 millisecond".to_string()));
+        }
+        if self.canonical {
+            match self.algorithm {
+                Algorithm::Layered =>
+                    return Err(SolveError::InvalidOptions("layered solutions can't be made canonical".to_string())),
+                Algorithm::Greedy =>
+                    return Err(SolveError::InvalidOptions("greedy solutions can't be made canonical".to_string())),
+                _ => {}
+            }
+        }
+        if self.stop_on_generate && self.algorithm != Algorithm::Greedy {
+            return Err(SolveError::InvalidOptions(format!(
+                "stopping when the goal is generated would make {:?} solutions suboptimal",
+                self.algorithm
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Memory budget given to new options, in bytes, 0 for none
+static DEFAULT_MAX_MEMORY_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Set the memory budget of every `SolveOptions` created from now on, e.g. to keep
+/// solves within what a browser lets the wasm heap grow to.
+///
+/// # Parameters
+/// * `bytes` - Budget in bytes, `None` for no budget
+pub fn set_default_memory_budget(bytes: Option<u64>) {
+    DEFAULT_MAX_MEMORY_BYTES.store(bytes.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Get the memory budget given to new options.
+///
+/// # Returns
+/// Budget in bytes, or `None` if there is no budget
+pub fn default_memory_budget() -> Option<u64> {
+    match DEFAULT_MAX_MEMORY_BYTES.load(Ordering::Relaxed) {
+        0 => None,
+        bytes => Some(bytes),
+    }
+}
+
+/// Statistics collected while solving a board.
+///
+/// # Attributes
+/// * `nodes_expanded` - Number of boards taken off the priority queue and expanded
+/// * `nodes_generated` - Number of new boards pushed onto the priority queue
+/// * `max_heuristic` - Largest heuristic value (manhattan + linear conflicts) seen
+/// * `expansions_by_depth` - Number of boards expanded at each depth of the state-space tree
+/// * `effective_branching_factor` - Effective branching factor of the search
+/// * `conflict_cache_hits` - Rows and columns whose linear conflicts were cached
+/// * `conflict_cache_misses` - Rows and columns whose linear conflicts were counted
+/// * `tt_hits` - Heuristics read from the transposition table
+/// * `tt_cutoffs` - Boards not searched again because the transposition table showed
+///   they fail the current bound
+/// * `stale_pops` - Boards taken off the priority queue and skipped because the board
+///   was already expanded, or reached in fewer moves, since it was pushed
+/// * `hint_usage` - How the hint of `solve_with_hint` was used, `None` without a hint
+/// * `expansions_by_prefix` - Number of boards expanded below each prefix of the
+///   solution: entry i counts the board reached by the first i + 1 moves and its
+///   descendants (empty unless `SolveOptions::track_path_expansions` is set)
+/// * `visited_key_bytes` - Bytes of keys hashed to look up and record boards in the A*
+///   and greedy visited sets, the tiles of each board or 8 with `zobrist_seed`
+/// * `millis` - Wall time of the solve in milliseconds (not compared by `==`, so the
+///   same search always gives equal stats)
+#[derive(Debug, Clone, Default)]
+pub struct SolveStats {
+    pub nodes_expanded: u64,
+    pub nodes_generated: u64,
+    pub max_heuristic: i32,
+    pub expansions_by_depth: Vec<u64>,
+    pub effective_branching_factor: f64,
+    pub conflict_cache_hits: u64,
+    pub conflict_cache_misses: u64,
+    pub tt_hits: u64,
+    pub tt_cutoffs: u64,
+    pub stale_pops: u64,
+    pub hint_usage: Option<HintUsage>,
+    pub expansions_by_prefix: Vec<u64>,
+    pub visited_key_bytes: u64,
+    pub millis: f64,
+}
+
+impl PartialEq for SolveStats {
+    /// Custom equivalence function ignoring the wall time
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes_expanded == other.nodes_expanded
+            && self.nodes_generated == other.nodes_generated
+            && self.max_heuristic == other.max_heuristic
+            && self.expansions_by_depth == other.expansions_by_depth
+            && self.effective_branching_factor == other.effective_branching_factor
+            && self.conflict_cache_hits == other.conflict_cache_hits
+            && self.conflict_cache_misses == other.conflict_cache_misses
+            && self.tt_hits == other.tt_hits
+            && self.tt_cutoffs == other.tt_cutoffs
+            && self.stale_pops == other.stale_pops
+            && self.hint_usage == other.hint_usage
+            && self.expansions_by_prefix == other.expansions_by_prefix
+            && self.visited_key_bytes == other.visited_key_bytes
+    }
+}
+
+impl SolveStats {
+    /// Copy the counters of a linear conflict cache.
+    ///
+    /// # Parameters
+    /// * `conflict_cache` - Cache used by the search
+    fn record_conflict_cache(&mut self, conflict_cache: &ConflictCache) {
+        self.conflict_cache_hits = conflict_cache.hits();
+        self.conflict_cache_misses = conflict_cache.misses();
+    }
+
+    /// Copy the counters of a transposition table.
+    ///
+    /// # Parameters
+    /// * `transposition` - Table used by the search
+    fn record_transposition(&mut self, transposition: &TranspositionTable) {
+        self.tt_hits = transposition.hits();
+        self.tt_cutoffs = transposition.cutoffs();
+    }
+
+    /// Fraction of rows and columns whose linear conflicts were cached.
+    ///
+    /// # Returns
+    /// Hit rate from 0 to 1, or 0 if the cache wasn't used
+    pub fn conflict_cache_hit_rate(&self) -> f64 {
+        let lookups = self.conflict_cache_hits + self.conflict_cache_misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.conflict_cache_hits as f64 / lookups as f64
+    }
+
+    /// Record the expansion of a board.
+    ///
+    /// # Parameters
+    /// * `board` - The board being expanded
+    fn record_expansion(&mut self, board: &Board) {
+        self.nodes_expanded += 1;
+        let depth = board.g() as usize;
+        if self.expansions_by_depth.len() <= depth {
+            self.expansions_by_depth.resize(depth + 1, 0);
+        }
+        self.expansions_by_depth[depth] += 1;
+        self.max_heuristic = self.max_heuristic.max(board.stored_h());
+    }
+
+    /// Solve N = 1 + b + b^2 + ... + b^d numerically for b.
+    ///
+    /// # Parameters
+    /// * `nodes` - Total number of nodes, N
+    /// * `depth` - Depth of the solution, d
+    ///
+    /// # Returns
+    /// The effective branching factor, b (0 when it is undefined)
+    pub fn effective_branching_factor(nodes: u64, depth: usize) -> f64 {
+        if depth == 0 || nodes <= 1 {
+            return 0.0;
+        }
+        let nodes = nodes as f64;
+        // Total nodes in a uniform tree with branching factor `b`
+        let tree_size = |b: f64| (0..=depth).map(|i| b.powi(i as i32)).sum::<f64>();
+        // Bisect, since the tree size grows monotonically with `b`
+        let mut low = 0.0;
+        let mut high = nodes;
+        for _ in 0..EBF_ITERATIONS {
+            let mid = (low + high) / 2.0;
+            if tree_size(mid) < nodes {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        (low + high) / 2.0
+    }
+
+    /// Format the stats as a JSON object.
+    /// * `hint_usage` is only included when the solve had a hint.
+    ///
+    /// # Returns
+    /// JSON representation of the stats
+    pub fn to_json(&self) -> String {
+        let by_depth: Vec<String> = self.expansions_by_depth.iter()
+            .map(|count| count.to_string())
+            .collect();
+        let hint_usage = self.hint_usage.map_or(String::new(), |usage| format!(",\"hint_usage\":\"{}\"", usage));
+        format!(
+            "{{\"nodes_expanded\":{},\"nodes_generated\":{},\"max_heuristic\":{},\
+             \"expansions_by_depth\":[{}],\"effective_branching_factor\":{},\
+             \"conflict_cache_hits\":{},\"conflict_cache_misses\":{},\
+             \"tt_hits\":{},\"tt_cutoffs\":{},\"stale_pops\":{},\"visited_key_bytes\":{},\"millis\":{}{}}}",
+            self.nodes_expanded,
+            self.nodes_generated,
+            self.max_heuristic,
+            by_depth.join(","),
+            self.effective_branching_factor,
+            self.conflict_cache_hits,
+            self.conflict_cache_misses,
+            self.tt_hits,
+            self.tt_cutoffs,
+            self.stale_pops,
+            self.visited_key_bytes,
+            self.millis,
+            hint_usage,
+        )
+    }
+}
+
+/// How `solve_with_hint` used its hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintUsage {
+    /// The hint solves the board, so it was returned without searching
+    Verbatim,
+    /// The boards along the hint, up to its first illegal move, seeded the search
+    Partial,
+    /// Not even the first move of the hint could be made, or the algorithm has no open
+    /// list to seed
+    Discarded,
+}
+
+impl fmt::Display for HintUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HintUsage::Verbatim => write!(f, "verbatim"),
+            HintUsage::Partial => write!(f, "partial"),
+            HintUsage::Discarded => write!(f, "discarded"),
+        }
+    }
+}
+
+/// The result of solving a board.
+///
+/// # Attributes
+/// * `moves` - String of single characters representing solution moves
+/// * `stats` - Statistics collected during the search
+/// * `optimal` - Whether the search guarantees no shorter solution exists
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub moves: String,
+    pub stats: SolveStats,
+    pub optimal: bool,
+}
+
+impl Solution {
+    /// Pack the moves into a compact, versioned binary form for storage.
+    /// * Format: a `COMPACT_VERSION` byte, the number of moves as a little-endian u32,
+    ///   then the moves 4 per byte, 2 bits each (see `MOVE_ENCODING`), the first move
+    ///   in the lowest bits. Unused bits of the last byte are zero.
+    /// * Statistics and optimality aren't stored.
+    ///
+    /// # Returns
+    /// Encoded solution
+    ///
+    /// # Panics
+    /// If the moves aren't all `U`, `D`, `L` or `R`, which solvers never return
+    pub fn encode_compact(&self) -> Vec<u8> {
+        let codes = encode_moves(&self.moves).expect("solution moves are U, D, L or R");
+        let mut bytes = vec![COMPACT_VERSION];
+        bytes.extend_from_slice(&(codes.len() as u32).to_le_bytes());
+        bytes.extend(codes.chunks(4).map(|chunk| {
+            chunk.iter().enumerate().fold(0u8, |byte, (i, &code)| byte | code << (2 * i))
+        }));
+        bytes
+    }
+
+    /// Decode a solution made by `encode_compact`, checking it solves a board.
+    ///
+    /// # Parameters
+    /// * `bytes` - Encoded solution
+    /// * `start` - Board the solution starts from
+    ///
+    /// # Returns
+    /// The solution, with empty statistics, or why it can't be decoded or doesn't
+    /// solve the board
+    pub fn decode_compact(bytes: &[u8], start: &Board) -> Result<Solution, DecodeError> {
+        let version = *bytes.first().ok_or(DecodeError::Truncated)?;
+        if version != COMPACT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let header = bytes.get(1..COMPACT_HEADER_LEN).ok_or(DecodeError::Truncated)?;
+        let length = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let packed = &bytes[COMPACT_HEADER_LEN..];
+        let expected = length.div_ceil(4);
+        if packed.len() < expected {
+            return Err(DecodeError::Truncated);
+        }
+        if packed.len() > expected {
+            return Err(DecodeError::Corrupt(format!("{} bytes after the moves", packed.len() - expected)));
+        }
+        if !length.is_multiple_of(4) && packed[expected - 1] >> (2 * (length % 4)) != 0 {
+            return Err(DecodeError::Corrupt("unused bits are set".to_string()));
+        }
+        let codes: Vec<u8> = (0..length).map(|i| packed[i / 4] >> (2 * (i % 4)) & 0x3).collect();
+        // Two bits always hold a move
+        let moves = decode_moves(&codes).unwrap_or_default();
+        let end = start.apply_moves(&moves, Notation::BlankMoves).map_err(DecodeError::IllegalMove)?;
+        if !end.is_solved() {
+            return Err(DecodeError::NotSolved);
+        }
+        Ok(Solution { moves, stats: SolveStats::default(), optimal: false })
+    }
+
+    /// Encode the solution as unpadded base64url text, e.g. for embedding in JSON.
+    ///
+    /// # Returns
+    /// `encode_compact` bytes as base64url
+    pub fn encode_compact_text(&self) -> String {
+        pack::encode_base64url_bytes(&self.encode_compact())
+    }
+
+    /// Decode a solution made by `encode_compact_text`, checking it solves a board.
+    ///
+    /// # Parameters
+    /// * `text` - Base64url text
+    /// * `start` - Board the solution starts from
+    ///
+    /// # Returns
+    /// The solution, with empty statistics, or why it can't be decoded or doesn't
+    /// solve the board
+    pub fn decode_compact_text(text: &str, start: &Board) -> Result<Solution, DecodeError> {
+        let bytes = pack::decode_base64url_bytes(text)
+            .ok_or_else(|| DecodeError::Corrupt("invalid base64url text".to_string()))?;
+        Solution::decode_compact(&bytes, start)
+    }
+
+    /// Format the solution as a JSON object.
+    ///
+    /// # Returns
+    /// JSON representation of the solution
+    pub fn to_json(&self) -> String {
+        format!("{{\"path\":\"{}\",\"stats\":{},\"optimal\":{}}}", self.moves, self.stats.to_json(), self.optimal)
+    }
+
+    /// Write the solution moves in a notation.
+    ///
+    /// # Parameters
+    /// * `notation` - Notation to write the moves in
+    ///
+    /// # Returns
+    /// The written moves
+    pub fn format(&self, notation: Notation) -> String {
+        notation.format_moves(&self.moves)
+    }
+
+    /// Describe each move of the solution, e.g. to pace an animation.
+    /// * Node counts are 0 unless the solve set `SolveOptions::track_path_expansions`.
+    ///
+    /// # Parameters
+    /// * `board` - The board the solution starts from
+    /// * `heuristic` - Heuristic to evaluate the boards along the solution with
+    ///
+    /// # Returns
+    /// One entry per move, in order
+    pub fn move_metadata(&self, board: &Board, heuristic: Heuristic) -> Vec<MoveMeta> {
+        let solved_board = Board::new(board.size(), None, -1, None);
+        let mut current = board.clone();
+        current.depth = 0;
+        self.moves.chars()
+            .enumerate()
+            .map(|(i, blank_move)| {
+                current.move_blank_tile(blank_move);
+                MoveMeta {
+                    blank_move,
+                    h: current.store_h(&solved_board, heuristic),
+                    nodes_expanded: self.stats.expansions_by_prefix.get(i).copied().unwrap_or(0),
+                }
+            })
+            .collect()
+    }
+
+    /// Format the solution as a JSON object, with the metadata of each move.
+    ///
+    /// # Parameters
+    /// * `board` - The board the solution starts from
+    /// * `heuristic` - Heuristic to evaluate the boards along the solution with
+    ///
+    /// # Returns
+    /// JSON representation of the solution, with a `moves` array
+    pub fn to_json_verbose(&self, board: &Board, heuristic: Heuristic) -> String {
+        let moves: Vec<String> = self.move_metadata(board, heuristic).iter().map(MoveMeta::to_json).collect();
+        format!(
+            "{{\"path\":\"{}\",\"moves\":[{}],\"stats\":{}}}",
+            self.moves,
+            moves.join(","),
+            self.stats.to_json()
+        )
+    }
+}
+
+/// A move of a solution, with how hard the search found it.
+///
+/// # Attributes
+/// * `blank_move` - Move of the blank
+/// * `h` - Heuristic value of the board after the move
+/// * `nodes_expanded` - Boards expanded below the board after the move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveMeta {
+    pub blank_move: char,
+    pub h: i32,
+    pub nodes_expanded: u64,
+}
+
+impl MoveMeta {
+    /// Format the metadata as a JSON object.
+    ///
+    /// # Returns
+    /// JSON representation of the metadata
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"move\":\"{}\",\"h\":{},\"nodes_expanded\":{}}}",
+            self.blank_move, self.h, self.nodes_expanded
+        )
+    }
+}
+
+/// Count the expanded boards below each prefix of a solution.
+///
+/// # Parameters
+/// * `expanded_paths` - Paths of the expanded boards
+/// * `moves` - Solution moves
+///
+/// # Returns
+/// Number of expanded paths starting with each of the solution's first 1, 2, ... moves
+fn _expansions_by_prefix(expanded_paths: &[String], moves: &str) -> Vec<u64> {
+    let mut counts = vec![0; moves.len()];
+    for path in expanded_paths {
+        let common = path.bytes().zip(moves.bytes()).take_while(|(a, b)| a == b).count();
+        for count in &mut counts[..common] {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+/// Check that moves solve a board.
+///
+/// # Parameters
+/// * `board` - The unsolved board
+/// * `moves` - Solution moves
+/// * `notation` - Notation the moves are written in
+///
+/// # Returns
+/// Whether every move can be made and the board ends up solved
+pub fn verify_solution(board: &Board, moves: &str, notation: Notation) -> bool {
+    board.apply_moves(moves, notation).is_ok_and(|solved| solved.is_solved())
+}
+
+/// Failure injected into a search, set by the `chaos` options.
+/// * Never due without the `chaos` feature.
+///
+/// # Attributes
+/// * `after_nodes` - Fail once this many boards have been expanded
+/// * `kind` - Kind of error to fail with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FailurePoint {
+    after_nodes: u64,
+    kind: SolveErrorKind,
+}
+
+impl FailurePoint {
+    /// Read the failure to inject from options.
+    ///
+    /// # Parameters
+    /// * `options` - Options controlling the solve
+    #[cfg(feature = "chaos")]
+    pub(crate) fn new(options: &SolveOptions) -> FailurePoint {
+        FailurePoint { after_nodes: options.fail_after_nodes.unwrap_or(u64::MAX), kind: options.fail_with }
+    }
+
+    /// Read the failure to inject from options.
+    ///
+    /// # Parameters
+    /// * `options` - Options controlling the solve
+    #[cfg(not(feature = "chaos"))]
+    pub(crate) fn new(_options: &SolveOptions) -> FailurePoint {
+        FailurePoint { after_nodes: u64::MAX, kind: SolveErrorKind::default() }
+    }
+
+    /// Get the number of expanded boards at which the search stops.
+    ///
+    /// # Parameters
+    /// * `max_nodes` - Node limit of the search
+    ///
+    /// # Returns
+    /// The node limit, or the injected failure if it comes first
+    pub(crate) fn node_limit(&self, max_nodes: u64) -> u64 {
+        max_nodes.min(self.after_nodes)
+    }
+
+    /// Get the error of a search stopped at its node limit.
+    ///
+    /// # Parameters
+    /// * `nodes_expanded` - Boards expanded when the search stopped
+    /// * `max_nodes` - Node limit of the search
+    /// * `budget_exceeded` - The search's genuine budget error
+    ///
+    /// # Returns
+    /// The budget error if the node limit was reached, otherwise the injected error
+    pub(crate) fn error(&self, nodes_expanded: u64, max_nodes: u64, budget_exceeded: SolveError) -> SolveError {
+        if nodes_expanded >= max_nodes {
+            return budget_exceeded;
+        }
+        match self.kind {
+            SolveErrorKind::Unreachable => SolveError::Unreachable,
+            SolveErrorKind::InvalidOptions => SolveError::InvalidOptions("injected failure".to_string()),
+            SolveErrorKind::BudgetExceeded => budget_exceeded,
+            SolveErrorKind::TooLarge => SolveError::TooLarge("injected failure".to_string()),
+            SolveErrorKind::MemoryBudget => SolveError::MemoryBudget("injected failure".to_string()),
+            SolveErrorKind::HeuristicInconsistent => match budget_exceeded {
+                SolveError::BudgetExceeded(partial) => {
+                    let (board, estimate) = (Box::new(partial.best_board), partial.best_heuristic);
+                    let violation = HeuristicViolation::Inadmissible { board, estimate, distance: 0 };
+                    SolveError::HeuristicInconsistent(Box::new(violation))
+                }
+                error => error,
+            },
+        }
+    }
+}
+
+/// Depth of each board in a visited set of A* or greedy search.
+/// * Keyed on the tiles, or on the Zobrist hash with `SolveOptions::zobrist_seed`.
+#[derive(Debug, Clone)]
+enum VisitedMap {
+    Tiles(HashMap<Vec<Tile>, i32>),
+    Zobrist(HashMap<u64, i32>),
+}
+
+impl VisitedMap {
+    /// Create an empty map.
+    ///
+    /// # Parameters
+    /// * `zobrist` - Key on Zobrist hashes
+    fn new(zobrist: bool) -> VisitedMap {
+        if zobrist { VisitedMap::Zobrist(HashMap::new()) } else { VisitedMap::Tiles(HashMap::new()) }
+    }
+
+    /// Create an empty map keyed the same way.
+    fn emptied(&self) -> VisitedMap {
+        VisitedMap::new(matches!(self, VisitedMap::Zobrist(_)))
+    }
+
+    /// Size of the key of a board, as hashed on every lookup.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    fn key_bytes(&self, board: &Board) -> u64 {
+        match self {
+            VisitedMap::Tiles(_) => size_of_val(board.tiles()) as u64,
+            VisitedMap::Zobrist(_) => size_of::<u64>() as u64,
+        }
+    }
+
+    /// Look up the depth of a board.
+    /// * Boards of a Zobrist map must keep their hash, see `Board::with_zobrist`.
+    fn get(&self, board: &Board) -> Option<i32> {
+        match self {
+            VisitedMap::Tiles(depths) => depths.get(board.tiles()).copied(),
+            VisitedMap::Zobrist(depths) => depths.get(&board.zobrist_hash().unwrap()).copied(),
+        }
+    }
+
+    /// Record the depth of a board, unless a smaller depth is recorded already.
+    ///
+    /// # Returns
+    /// Whether a larger depth was recorded before
+    fn lower(&mut self, board: &Board, depth: i32) -> bool {
+        let best = match self {
+            VisitedMap::Tiles(depths) => depths.entry(board.tiles().to_vec()).or_insert(depth),
+            VisitedMap::Zobrist(depths) => depths.entry(board.zobrist_hash().unwrap()).or_insert(depth),
+        };
+        let lowered = depth < *best;
+        *best = (*best).min(depth);
+        lowered
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            VisitedMap::Tiles(depths) => depths.len(),
+            VisitedMap::Zobrist(depths) => depths.len(),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        match self {
+            VisitedMap::Tiles(depths) => depths.capacity(),
+            VisitedMap::Zobrist(depths) => depths.capacity(),
+        }
+    }
+
+    /// Approximate size of an entry in bytes, with its key, value and a control byte.
+    ///
+    /// # Parameters
+    /// * `tiles` - Size of the tiles of a board in bytes
+    fn entry_bytes(&self, tiles: u64) -> u64 {
+        match self {
+            VisitedMap::Tiles(_) => (size_of::<Vec<Tile>>() + size_of::<i32>() + 1) as u64 + tiles,
+            VisitedMap::Zobrist(_) => (size_of::<u64>() + size_of::<i32>() + 1) as u64,
+        }
+    }
+
+    /// Iterate over the boards keyed on their tiles.
+    ///
+    /// # Returns
+    /// Tiles and depth of each board, none for a Zobrist map
+    fn tiles(&self) -> Box<dyn Iterator<Item = (&[Tile], i32)> + '_> {
+        match self {
+            VisitedMap::Tiles(depths) => Box::new(depths.iter().map(|(tiles, &depth)| (tiles.as_slice(), depth))),
+            VisitedMap::Zobrist(_) => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+/// Set of board layouts, e.g. the boards a search has expanded.
+/// * Boards are keyed on their packed tiles up to 4x4 and a hash of their tiles
+///   otherwise (see `_state_key`), or on their Zobrist hash if they carry one (see
+///   `Board::with_zobrist`). Boards of one set must all carry a table or all not.
+/// * Only the layout is keyed, so boards reached by different paths are the same
+///   entry.
+///
+/// # Attributes
+/// * `keys` - Keys of the boards in the set, hashed by `S`
+#[derive(Debug, Clone, Default)]
+pub struct ClosedSet<S = RandomState> {
+    keys: HashSet<u64, S>,
+}
+
+impl ClosedSet {
+    /// Create an empty set.
+    pub fn new() -> ClosedSet {
+        ClosedSet::default()
+    }
+}
+
+impl<S: BuildHasher> ClosedSet<S> {
+    /// Create an empty set hashing its keys with the given hasher.
+    /// * Keys are already well mixed, so a cheap hasher loses little.
+    ///
+    /// # Parameters
+    /// * `hasher` - Builds the hasher of the keys
+    pub fn with_hasher(hasher: S) -> ClosedSet<S> {
+        ClosedSet { keys: HashSet::with_hasher(hasher) }
+    }
+
+    /// Add a board to the set.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    ///
+    /// # Returns
+    /// Whether the layout wasn't in the set already
+    pub fn insert(&mut self, board: &Board) -> bool {
+        self.keys.insert(_closed_key(board))
+    }
+
+    /// Check if a board is in the set.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    ///
+    /// # Returns
+    /// Whether a board with the same layout was inserted
+    pub fn contains(&self, board: &Board) -> bool {
+        self.keys.contains(&_closed_key(board))
+    }
+
+    /// Remove a board from the set.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    ///
+    /// # Returns
+    /// Whether the layout was in the set
+    pub fn remove(&mut self, board: &Board) -> bool {
+        self.keys.remove(&_closed_key(board))
+    }
+
+    /// Number of distinct layouts in the set.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Remove every board from the set.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    fn capacity(&self) -> usize {
+        self.keys.capacity()
+    }
+}
+
+/// Key a board for a `ClosedSet`.
+///
+/// # Parameters
+/// * `board` - The board
+///
+/// # Returns
+/// The board's Zobrist hash if it carries one, otherwise `_state_key`
+fn _closed_key(board: &Board) -> u64 {
+    board.zobrist_hash().unwrap_or_else(|| _state_key(board))
+}
+
+/// Size of the data hashed to key a board for a `ClosedSet`.
+/// * Boards too large to pack have their tiles hashed into a key, which is hashed again
+///   by the set.
+///
+/// # Parameters
+/// * `board` - The board
+fn _closed_key_bytes(board: &Board) -> u64 {
+    let key = size_of::<u64>() as u64;
+    if board.zobrist_hash().is_none() && board.tiles().len() > 16 {
+        key + size_of_val(board.tiles()) as u64
+    } else {
+        key
+    }
+}
+
+/// Resumable A* search over the state-space tree.
+/// * Expands a bounded number of boards per call to `step`, so callers can interleave
+///   the search with other work.
+/// * Also runs greedy search, where boards are costed by their heuristic alone.
+///
+/// # Attributes
+/// * `root` - The board being solved
+/// * `solved_board` - Solved version of the board
+/// * `goal_key` - Packed tiles of the solved board, if it can be packed
+/// * `board_leaves` - Priority queue for storing leaf boards in state space tree
+/// * `closed` - Expanded boards; each was expanded at its depth in `best_depths` and is
+///   removed when pushed again at a smaller depth, so it is expanded again
+/// * `best_depths` - Smallest depth each board has been pushed at, so copies pushed
+///   before a shorter path was found are skipped when popped
+/// * `stats` - Statistics collected so far
+/// * `best_heuristic` - Smallest heuristic value of any expanded board
+/// * `solution` - Solution, once found
+/// * `heuristic` - Heuristic guiding the search
+/// * `max_nodes` - Give up after expanding this many boards
+/// * `max_memory_bytes` - Give up before the search structures grow past about this
+///   many bytes
+/// * `out_of_memory` - Why the search gave up on memory, once it has; its structures
+///   are freed
+/// * `conflict_cache` - Cached linear conflicts of rows and columns
+/// * `greedy` - Order boards by their heuristic alone
+/// * `stop_on_generate` - Stop as soon as the goal is generated
+/// * `expanded_paths` - Paths of the boards expanded so far, when tracked
+/// * `proven_lower_bound` - Largest cost popped off the frontier, once A* has popped a
+///   board
+/// * `started_ms` - Reading of the clock when the solver was created
+/// * `time_budget` - Deadline of the search
+/// * `known_distances` - Exact distances to the goal of packed boards, see
+///   `context::SearchContext`
+/// * `failure` - Failure to inject, with the `chaos` feature
+/// * `paranoid` - Fail with `SolveError::HeuristicInconsistent` when the heuristic
+///   fails a check, see `SolveOptions::paranoid`
+/// * `estimate` - Heuristic used instead of `heuristic`, see `estimate`
+#[derive(Debug)]
+pub struct Solver {
+    root: Board,
+    solved_board: Board,
+    goal_key: Option<u64>,
+    board_leaves: BinaryHeap<Board>,
+    closed: ClosedSet,
+    best_depths: VisitedMap,
+    stats: SolveStats,
+    best_heuristic: i32,
+    solution: Option<Solution>,
+    heuristic: Heuristic,
+    max_nodes: u64,
+    max_memory_bytes: u64,
+    out_of_memory: Option<String>,
+    conflict_cache: ConflictCache,
+    greedy: bool,
+    stop_on_generate: bool,
+    expanded_paths: Option<Vec<String>>,
+    best_board: Board,
+    proven_lower_bound: Option<i32>,
+    started_ms: f64,
+    time_budget: TimeBudget,
+    known_distances: KnownDistances,
+    failure: FailurePoint,
+    paranoid: bool,
+    estimate: Option<fn(&Board, &Board) -> i32>,
+}
+
+impl Solver {
+    /// Create a solver for a board.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `tiles` - Tiles that make up the board
+    pub fn new(n: i32, tiles: Vec<Tile>) -> Solver {
+        Solver::with_options(n, tiles, &SolveOptions::default())
+    }
+
+    /// Create a solver for a board using the algorithm, heuristic and node limit from
+    /// options.
+    /// * Optimal algorithms run A*, the others greedy search.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the board
+    /// * `tiles` - Tiles that make up the board
+    /// * `options` - Options controlling the solve
+    pub fn with_options(n: i32, tiles: Vec<Tile>, options: &SolveOptions) -> Solver {
+        let solved_board = Board::new(n, None, -1, None);
+        let mut unsolved_board = Board::new(n, None, 0, Some(tiles.into_boxed_slice()));
+        unsolved_board.cost = unsolved_board.g() + unsolved_board.store_h(&solved_board, options.heuristic);
+        if let Some(seed) = options.zobrist_seed {
+            unsolved_board = unsolved_board.with_zobrist(Arc::new(ZobristTable::new(n, seed)));
+        }
+        let mut solver = Solver {
+            root: unsolved_board.clone(),
+            conflict_cache: ConflictCache::new(&solved_board),
+            goal_key: _goal_key(&solved_board),
+            solved_board,
+            board_leaves: BinaryHeap::new(),
+            closed: ClosedSet::new(),
+            best_depths: VisitedMap::new(options.zobrist_seed.is_some()),
+            stats: SolveStats::default(),
+            best_heuristic: i32::MAX,
+            solution: None,
+            heuristic: options.heuristic,
+            max_nodes: options.max_nodes.unwrap_or(u64::MAX),
+            max_memory_bytes: options.max_memory_bytes.unwrap_or(u64::MAX),
+            out_of_memory: None,
+            greedy: !options.algorithm.is_optimal(),
+            stop_on_generate: options.stop_on_generate,
+            expanded_paths: if options.track_path_expansions { Some(Vec::new()) } else { None },
+            best_board: unsolved_board.clone(),
+            proven_lower_bound: None,
+            started_ms: clock::now_ms(),
+            time_budget: TimeBudget::new(options.max_time_ms),
+            known_distances: KnownDistances::new(),
+            failure: FailurePoint::new(options),
+            paranoid: options.paranoid,
+            estimate: None,
+        };
+        // Add root board to leaves PQ
+        solver.push(unsolved_board);
+        solver
+    }
+
+    /// Use exact distances to the goal learned by earlier searches.
+    /// * Boards with a known distance are costed by it, and the search finishes as soon
+    ///   as one is taken off the frontier. Greedy search ignores them.
+    ///
+    /// # Parameters
+    /// * `known_distances` - Distances of packed boards of this size, with the first
+    ///   move of an optimal solution
+    ///
+    /// # Returns
+    /// The solver
+    pub(crate) fn known_distances(mut self, known_distances: KnownDistances) -> Solver {
+        self.known_distances = known_distances;
+        self
+    }
+
+    /// Guide the search with a heuristic function instead of `SolveOptions::heuristic`,
+    /// e.g. to check a heuristic under development, see `debug_checks::check_search`.
+    /// * The heuristic is taken to claim consistency, so every move is checked with
+    ///   `SolveOptions::paranoid`.
+    /// * Recosts the boards already on the frontier.
+    ///
+    /// # Parameters
+    /// * `estimate` - Heuristic, given a board and its goal
+    ///
+    /// # Returns
+    /// The solver
+    pub(crate) fn estimate(mut self, estimate: fn(&Board, &Board) -> i32) -> Solver {
+        self.estimate = Some(estimate);
+        let mut root = self.root.clone();
+        self.cost(&mut root);
+        self.best_board = root.clone();
+        self.root = root;
+        let mut board_leaves = std::mem::take(&mut self.board_leaves).into_vec();
+        for board in &mut board_leaves {
+            self.cost(board);
+        }
+        self.board_leaves = BinaryHeap::from(board_leaves);
+        self
+    }
+
+    /// Give back the known distances, see `known_distances`.
+    ///
+    /// # Returns
+    /// The distances, leaving none
+    pub(crate) fn take_known_distances(&mut self) -> KnownDistances {
+        std::mem::take(&mut self.known_distances)
+    }
+
+    /// Get the board being solved.
+    ///
+    /// # Returns
+    /// The root board of the search
+    pub fn root(&self) -> &Board {
+        &self.root
+    }
+
+    /// Get the statistics collected so far.
+    ///
+    /// # Returns
+    /// Search statistics
+    pub fn stats(&self) -> &SolveStats {
+        &self.stats
+    }
+
+    /// Get the length no solution can be shorter than.
+    /// * For A*, the largest cost popped off the frontier, each the smallest on it when
+    ///   popped, so it never decreases. Greedy search proves nothing beyond the root's
+    ///   heuristic.
+    ///
+    /// # Returns
+    /// The proven lower bound on the solution length
+    pub fn proven_lower_bound(&self) -> i32 {
+        self.proven_lower_bound.unwrap_or(self.root.cost)
+    }
+
+    /// Time since the solver was created.
+    ///
+    /// # Returns
+    /// Elapsed milliseconds
+    pub fn elapsed_ms(&self) -> f64 {
+        clock::now_ms() - self.started_ms
+    }
+
+    /// Number of boards waiting on the frontier.
+    /// * Includes boards that will be skipped because they were reached more cheaply.
+    ///
+    /// # Returns
+    /// Frontier size
+    pub fn frontier_len(&self) -> usize {
+        self.board_leaves.len()
+    }
+
+    /// Sample boards from the frontier without disturbing it, e.g. for a heat map.
+    /// * Reservoir sampled while iterating the heap, so the frontier isn't cloned. The
+    ///   random number generator is seeded from the expansion count, so sampling the
+    ///   same search state gives the same sample.
+    ///
+    /// # Parameters
+    /// * `k` - Maximum number of boards to sample
+    ///
+    /// # Returns
+    /// Up to `k` (state, g, h) triples, where state is the packed tiles for boards up to
+    /// 4x4 and a hash of the tiles otherwise, g is the depth and h the heuristic value
+    pub fn frontier_sample(&self, k: usize) -> Vec<(u64, i32, i32)> {
+        let mut rng = StdRng::seed_from_u64(self.stats.nodes_expanded);
+        let mut sample: Vec<&Board> = Vec::with_capacity(k.min(self.board_leaves.len()));
+        for (i, board) in self.board_leaves.iter().enumerate() {
+            if sample.len() < k {
+                sample.push(board);
+            } else {
+                let j = rng.gen_range(0, i + 1);
+                if j < k {
+                    sample[j] = board;
+                }
+            }
+        }
+        sample.iter()
+            .map(|board| (_state_key(board), board.depth, board.stored_h()))
+            .collect()
+    }
+
+    /// Continue the search, expanding at most `max_nodes` boards.
+    /// * Once solved, further calls return the same solution.
+    ///
+    /// # Parameters
+    /// * `max_nodes` - Maximum number of boards to expand in this call
+    ///
+    /// # Returns
+    /// The solution, if it has been found, or `SolveError::Unreachable` once every
+    /// reachable board has been expanded
+    pub fn step(&mut self, max_nodes: u64) -> Result<Option<Solution>, SolveError> {
+        self.step_observed(max_nodes, &mut NoopObserver)
+    }
+
+    /// Continue the search like `step`, reporting search events to an observer.
+    ///
+    /// # Parameters
+    /// * `max_nodes` - Maximum number of boards to expand in this call
+    /// * `observer` - Observer notified of each expansion, generated board, rise in the
+    ///   proven lower bound, sample of the search structures and the goal
+    ///
+    /// # Returns
+    /// The solution, if it has been found, or `SolveError::Unreachable` once every
+    /// reachable board has been expanded (`SolveError::BudgetExceeded` if the node limit
+    /// is reached first, and `SolveError::MemoryBudget` from then on if the memory
+    /// budget is)
+    pub fn step_observed<O: SolverObserver>(&mut self, max_nodes: u64,
+                                            observer: &mut O) -> Result<Option<Solution>, SolveError> {
+        if self.solution.is_some() {
+            return Ok(self.solution.clone());
+        }
+        if let Some(reason) = &self.out_of_memory {
+            return Err(SolveError::MemoryBudget(reason.clone()));
+        }
+        let mut expanded = 0;
+        while expanded < max_nodes {
+            // Get the next best board leaf to expand
+            let next_best_leaf = match self.board_leaves.pop() {
+                Some(board) => board,
+                None => return Err(SolveError::Unreachable),
+            };
+            // Skip boards already expanded at the same or a smaller depth, or since
+            // pushed at a smaller one
+            if self.is_stale(&next_best_leaf) {
+                self.stats.stale_pops += 1;
+                continue;
+            }
+            if !self.greedy {
+                self.raise_lower_bound(next_best_leaf.cost, observer);
+                // Every admissible estimate on the frontier is at least as large, so the
+                // known rest of the way is optimal
+                if let Some(goal) = self.follow_known(&next_best_leaf) {
+                    return Ok(self.finish(goal, observer));
+                }
+            }
+            // Return path if solved
+            if _is_goal(&next_best_leaf, &self.solved_board, self.goal_key) {
+                self.check_goal(&next_best_leaf)?;
+                return Ok(self.finish(next_best_leaf, observer));
+            }
+            if self.stats.nodes_expanded >= self.failure.node_limit(self.max_nodes) {
+                // Put the board back so the search state stays intact
+                self.board_leaves.push(next_best_leaf);
+                return Err(self.failure.error(self.stats.nodes_expanded, self.max_nodes, self.budget_exceeded()));
+            }
+            if self.time_budget.expired() {
+                self.board_leaves.push(next_best_leaf);
+                return Err(self.budget_exceeded());
+            }
+            let memory = self.memory_estimate();
+            if let Some(every) = observer.sample_every() {
+                if self.stats.nodes_expanded.is_multiple_of(every) {
+                    observer.on_sample(&SearchSample {
+                        expansions: self.stats.nodes_expanded,
+                        frontier_size: self.board_leaves.len(),
+                        closed_size: self.closed.len(),
+                        current_f: next_best_leaf.cost,
+                        elapsed_ms: self.elapsed_ms(),
+                        bytes_estimate: memory,
+                    });
+                }
+            }
+            if memory > self.max_memory_bytes {
+                return Err(self.free_memory(memory));
+            }
+            self.stats.visited_key_bytes += _closed_key_bytes(&next_best_leaf);
+            self.closed.insert(&next_best_leaf);
+            if let Some(goal) = self.expand(&next_best_leaf, observer)? {
+                return Ok(self.finish(goal, observer));
+            }
+            expanded += 1;
+        }
+        Ok(None)
+    }
+
+    /// Raise the proven lower bound to the cost of a board popped off the frontier.
+    /// * The frontier is popped cheapest first and, with a consistent heuristic, no
+    ///   board costs less than the board it was generated from, so popped costs never
+    ///   decrease. Otherwise they can, and the bound keeps its largest value.
+    ///
+    /// # Parameters
+    /// * `cost` - Cost of the popped board
+    /// * `observer` - Observer notified if the bound rises
+    fn raise_lower_bound<O: SolverObserver>(&mut self, cost: i32, observer: &mut O) {
+        debug_assert!(
+            !self.heuristic.is_consistent() || self.proven_lower_bound.is_none_or(|bound| cost >= bound),
+            "popped cost {} below proven lower bound {:?}",
+            cost,
+            self.proven_lower_bound,
+        );
+        if self.proven_lower_bound.is_none_or(|bound| cost > bound) {
+            self.proven_lower_bound = Some(cost);
+            observer.on_progress(&SearchProgress {
+                nodes_expanded: self.stats.nodes_expanded,
+                proven_lower_bound: cost,
+                elapsed_ms: self.elapsed_ms(),
+            });
+        }
+    }
+
+    /// Record the solution reaching a goal board.
+    ///
+    /// # Parameters
+    /// * `goal` - The solved board
+    /// * `observer` - Observer notified of the goal
+    ///
+    /// # Returns
+    /// The solution
+    fn finish<O: SolverObserver>(&mut self, goal: Board, observer: &mut O) -> Option<Solution> {
+        self.stats.effective_branching_factor = SolveStats::effective_branching_factor(
+            self.stats.nodes_expanded,
+            goal.path.len(),
+        );
+        if let Some(expanded_paths) = self.expanded_paths.take() {
+            self.stats.expansions_by_prefix = _expansions_by_prefix(&expanded_paths, &goal.path);
+        }
+        self.stats.millis = clock::now_ms() - self.started_ms;
+        let solution = Solution {
+            moves: goal.path,
+            stats: self.stats.clone(),
+            optimal: !self.greedy,
+        };
+        observer.on_goal(&solution);
+        self.solution = Some(solution);
+        self.solution.clone()
+    }
+
+    /// Report what the search learned before reaching the node limit.
+    /// * The best board is the best expanded since the solver was created or resumed.
+    ///
+    /// # Returns
+    /// `SolveError::BudgetExceeded` with the partial result
+    fn budget_exceeded(&self) -> SolveError {
+        let mut proven_lower_bound = self.proven_lower_bound();
+        if !self.greedy {
+            // Some board on an optimal path is always on the frontier
+            if let Some(next) = self.board_leaves.peek() {
+                proven_lower_bound = proven_lower_bound.max(next.cost);
+            }
+        }
+        let stats = SolveStats { millis: clock::now_ms() - self.started_ms, ..self.stats.clone() };
+        let partial = PartialResult::new(&self.best_board, proven_lower_bound, stats);
+        SolveError::BudgetExceeded(Box::new(partial))
+    }
+
+    /// Estimate the memory of the search structures once the next board is expanded.
+    /// * Each structure is counted at the capacity it grows to if the board and its
+    ///   children don't fit, so the budget is checked before more memory is requested.
+    ///
+    /// # Returns
+    /// Approximate size of the frontier, closed set and best depths in bytes
+    fn memory_estimate(&self) -> u64 {
+        let tiles = size_of_val(self.root.tiles()) as u64;
+        // Frontier paths are at most one move longer than the deepest expanded board
+        let path = self.stats.expansions_by_depth.len() as u64 + 1;
+        let node = size_of::<Board>() as u64 + tiles + path;
+        let closed_entry = (size_of::<u64>() + 1) as u64;
+        let entry = self.best_depths.entry_bytes(tiles);
+        let grown = |len: usize, capacity: usize| {
+            let needed = len + 4;
+            if needed > capacity { needed.max(capacity * 2) as u64 } else { capacity as u64 }
+        };
+        grown(self.board_leaves.len(), self.board_leaves.capacity()) * node
+            + grown(self.closed.len(), self.closed.capacity()) * closed_entry
+            + grown(self.best_depths.len(), self.best_depths.capacity()) * entry
+    }
+
+    /// Give up on memory, freeing the search structures.
+    /// * The solver only reports the error from then on, so a caller holding it doesn't
+    ///   hold its memory.
+    ///
+    /// # Parameters
+    /// * `memory` - Estimated memory the search would have grown to
+    ///
+    /// # Returns
+    /// `SolveError::MemoryBudget`
+    fn free_memory(&mut self, memory: u64) -> SolveError {
+        let reason = format!(
+            "would grow to about {} bytes after expanding {} boards, over the budget of {}",
+            memory, self.stats.nodes_expanded, self.max_memory_bytes
+        );
+        self.board_leaves = BinaryHeap::new();
+        self.closed = ClosedSet::new();
+        self.best_depths = self.best_depths.emptied();
+        self.expanded_paths = None;
+        self.out_of_memory = Some(reason.clone());
+        SolveError::MemoryBudget(reason)
+    }
+
+    /// Cost a board for the priority queue.
+    /// * Greedy search leaves out the moves made so far.
+    ///
+    /// # Parameters
+    /// * `board` - Board at its depth
+    fn cost(&mut self, board: &mut Board) {
+        let h = match self.estimate {
+            Some(estimate) => {
+                board.manhattan_cost_cache = estimate(board, &self.solved_board);
+                board.linear_conflicts_cache = 0;
+                board.stored_h()
+            }
+            None => board.store_h_cached(&self.solved_board, self.heuristic, &mut self.conflict_cache),
+        };
+        board.cost = if self.greedy {
+            h
+        } else if let Some(&(distance, _)) = self.known_distance(board) {
+            board.g() + distance as i32
+        } else {
+            board.g() + h
+        };
+    }
+
+    /// Check a goal taken off the frontier has a heuristic of 0, so its cost is its depth.
+    /// * Checked with `SolveOptions::paranoid`, and asserted in debug builds.
+    ///
+    /// # Parameters
+    /// * `goal` - The goal popped
+    ///
+    /// # Returns
+    /// `SolveError::HeuristicInconsistent` if the heuristic of the goal isn't 0
+    fn check_goal(&self, goal: &Board) -> Result<(), SolveError> {
+        if goal.stored_h() == 0 || !(self.paranoid || cfg!(debug_assertions)) {
+            return Ok(());
+        }
+        _heuristic_violated(self.paranoid, HeuristicViolation::Inadmissible {
+            board: Box::new(goal.clone()),
+            estimate: goal.stored_h(),
+            distance: 0,
+        })
+    }
+
+    /// Check the heuristic of a board drops by at most the cost of the move to a child,
+    /// if the heuristic claims consistency.
+    /// * Checked with `SolveOptions::paranoid`, and asserted in debug builds.
+    ///
+    /// # Parameters
+    /// * `board` - The board expanded
+    /// * `child` - One of its children, costed
+    ///
+    /// # Returns
+    /// `SolveError::HeuristicInconsistent` if the heuristic drops by more
+    fn check_child(&self, board: &Board, child: &Board) -> Result<(), SolveError> {
+        if child.stored_h() >= board.stored_h() - 1 || !(self.paranoid || cfg!(debug_assertions)) {
+            return Ok(());
+        }
+        if self.estimate.is_none() && !self.heuristic.is_consistent() {
+            return Ok(());
+        }
+        _heuristic_violated(self.paranoid, HeuristicViolation::Inconsistent {
+            board: Box::new(board.clone()),
+            neighbour: Box::new(child.clone()),
+            estimate: board.stored_h(),
+            neighbour_estimate: child.stored_h(),
+        })
+    }
+
+    /// Look up the known distance of a board, see `known_distances`.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    ///
+    /// # Returns
+    /// Its distance to the goal and the first move of an optimal solution, if known
+    fn known_distance(&self, board: &Board) -> Option<&(u32, char)> {
+        if self.known_distances.is_empty() {
+            return None;
+        }
+        self.known_distances.get(&pack::encode_tiles(board.tiles()))
+    }
+
+    /// Follow the known first moves from a board with a known distance to the goal.
+    ///
+    /// # Parameters
+    /// * `board` - Board taken off the frontier
+    ///
+    /// # Returns
+    /// The goal reached through the board, if its distance is known
+    fn follow_known(&self, board: &Board) -> Option<Board> {
+        let mut goal = board.clone();
+        loop {
+            let &(distance, next) = self.known_distance(&goal)?;
+            if distance == 0 {
+                return Some(goal);
+            }
+            goal = goal.neighbor(next);
+        }
+    }
+
+    /// Check if a board has already been expanded at the same or a smaller depth.
+    ///
+    /// # Parameters
+    /// * `board` - The board to check
+    ///
+    /// # Returns
+    /// Whether the board can be skipped
+    fn is_closed(&mut self, board: &Board) -> bool {
+        self.stats.visited_key_bytes += _closed_key_bytes(board);
+        if !self.closed.contains(board) {
+            return false;
+        }
+        self.stats.visited_key_bytes += self.best_depths.key_bytes(board);
+        self.best_depths.get(board).is_some_and(|depth| depth <= board.depth)
+    }
+
+    /// Check if a board taken off the priority queue can be skipped.
+    /// * Lazy deletion: `BinaryHeap` can't lower the depth of a board already pushed, so
+    ///   a shorter path pushes a second copy and the first is skipped here.
+    ///
+    /// # Parameters
+    /// * `board` - The board popped
+    ///
+    /// # Returns
+    /// Whether the board is closed or was pushed again at a smaller depth
+    fn is_stale(&mut self, board: &Board) -> bool {
+        if self.is_closed(board) {
+            return true;
+        }
+        self.stats.visited_key_bytes += self.best_depths.key_bytes(board);
+        self.best_depths.get(board).is_some_and(|depth| depth < board.depth)
+    }
+
+    /// Push a board onto the priority queue, recording its depth if it is the smallest
+    /// it has been pushed at.
+    /// * A closed board pushed at a smaller depth is reopened.
+    ///
+    /// # Parameters
+    /// * `board` - The board, costed
+    fn push(&mut self, board: Board) {
+        self.stats.visited_key_bytes += self.best_depths.key_bytes(&board);
+        if self.best_depths.lower(&board, board.depth) {
+            self.stats.visited_key_bytes += _closed_key_bytes(&board);
+            self.closed.remove(&board);
+        }
+        self.board_leaves.push(board);
+    }
+
+    /// Push the boards along a path from the root, at their depths on the path.
+    /// * Every board pushed is reached by its path, so the search stays optimal.
+    ///
+    /// # Parameters
+    /// * `moves` - Moves of the blank from the root; stops at the first illegal move
+    ///
+    /// # Returns
+    /// Number of boards pushed
+    fn seed(&mut self, moves: &[char]) -> usize {
+        let mut board = self.root.clone();
+        for (seeded, &tile_move) in moves.iter().enumerate() {
+            if !board.is_legal_move(tile_move) {
+                return seeded;
+            }
+            board = board.neighbor(tile_move);
+            let mut seed = board.clone();
+            self.cost(&mut seed);
+            self.push(seed);
+        }
+        moves.len()
+    }
+
+    /// Expand a board, pushing each new board reachable in one move.
+    ///
+    /// # Parameters
+    /// * `board` - The board to expand
+    /// * `observer` - Observer notified of the expansion and each generated board
+    ///
+    /// # Returns
+    /// The goal, if it was generated and the search stops on generation, or
+    /// `SolveError::HeuristicInconsistent` if a child fails a check of the heuristic
+    fn expand<O: SolverObserver>(&mut self, board: &Board, observer: &mut O) -> Result<Option<Board>, SolveError> {
+        self.stats.record_expansion(board);
+        if let Some(expanded_paths) = &mut self.expanded_paths {
+            expanded_paths.push(board.path.clone());
+        }
+        observer.on_expand(board, board.depth, board.stored_h());
+        self.best_heuristic = self.best_heuristic.min(board.stored_h());
+        if board.stored_h() < self.best_board.stored_h() {
+            self.best_board = board.clone();
+        }
+        if log_enabled(LOG_DEBUG) && self.stats.nodes_expanded.is_multiple_of(log_interval()) {
+            log!(
+                LOG_DEBUG,
+                "expanded {} boards, frontier {}, best h {}",
+                self.stats.nodes_expanded,
+                self.board_leaves.len(),
+                self.best_heuristic
+            );
+        }
+        for tile_move in board.legal_moves() {
+            if _is_back_step(board, tile_move) {
+                continue;
+            }
+            let mut new_board = board.neighbor(tile_move);
+            // If resulting board has been expanded before, skip it
+            if self.is_closed(&new_board) {
+                observer.on_skip(&new_board, tile_move);
+                continue;
+            }
+            // Setup new board and add to leaves
+            self.cost(&mut new_board);
+            self.check_child(board, &new_board)?;
+            observer.on_generate(&new_board, tile_move);
+            self.stats.nodes_generated += 1;
+            if self.stop_on_generate && _is_goal(&new_board, &self.solved_board, self.goal_key) {
+                self.stats.record_conflict_cache(&self.conflict_cache);
+                return Ok(Some(new_board));
+            }
+            self.push(new_board);
+        }
+        self.stats.record_conflict_cache(&self.conflict_cache);
+        Ok(None)
+    }
+}
+
+impl Solver {
+    /// Snapshot the search so it can be resumed later, e.g. after a restart.
+    /// * Format: a `STATE_VERSION` byte, then little-endian fields: the root board,
+    ///   heuristic, search flags (greedy, stop on generate, paranoid), node limit, memory
+    ///   limit, time left, proven lower bound (-1 before one is proven), statistics,
+    ///   solution (if found), the open list in heap order and the closed set.
+    /// * Limits are `u64::MAX` when there is none. The time budget restarts on resume
+    ///   with the time that was left.
+    /// * The smallest depth each board was pushed at isn't saved; it is rebuilt from the
+    ///   open list and closed set, which hold the smallest copy of every board pushed.
+    /// * A closed set keyed on Zobrist hashes (`SolveOptions::zobrist_seed`) has no tiles
+    ///   to save, so it is saved empty and the resumed search, keyed on tiles, expands
+    ///   those boards again.
+    /// * Boards are stored as packed tiles for n <= 4 and one byte per tile otherwise;
+    ///   paths use one byte per move (see `encode_moves`).
+    /// * The linear conflict cache isn't saved, so a resumed search starts with an empty
+    ///   cache and its cache counters restart from 0. Expanded paths aren't saved either,
+    ///   so a resumed search doesn't count expansions by solution prefix.
+    ///
+    /// # Returns
+    /// Serialized solver state
+    pub fn serialize_state(&self) -> Vec<u8> {
+        let mut bytes = vec![STATE_VERSION, self.root.size() as u8];
+        _write_tiles(&mut bytes, self.root.tiles());
+        bytes.push(match self.heuristic {
+            Heuristic::Manhattan => 0,
+            Heuristic::LinearConflicts => 1,
+            Heuristic::InversionDistance => 2,
+            Heuristic::Max(parts) => 0x80 | parts.bits(),
+        });
+        bytes.push(self.greedy as u8 | (self.stop_on_generate as u8) << 1 | (self.paranoid as u8) << 2);
+        bytes.extend_from_slice(&self.max_nodes.to_le_bytes());
+        bytes.extend_from_slice(&self.max_memory_bytes.to_le_bytes());
+        bytes.extend_from_slice(&self.time_budget.remaining_ms().unwrap_or(u64::MAX).to_le_bytes());
+        bytes.extend_from_slice(&self.proven_lower_bound.unwrap_or(-1).to_le_bytes());
+        bytes.extend_from_slice(&self.best_heuristic.to_le_bytes());
+        bytes.extend_from_slice(&self.stats.nodes_expanded.to_le_bytes());
+        bytes.extend_from_slice(&self.stats.nodes_generated.to_le_bytes());
+        bytes.extend_from_slice(&self.stats.max_heuristic.to_le_bytes());
+        bytes.extend_from_slice(&(self.stats.expansions_by_depth.len() as u32).to_le_bytes());
+        for count in &self.stats.expansions_by_depth {
+            bytes.extend_from_slice(&count.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.stats.effective_branching_factor.to_le_bytes());
+        bytes.extend_from_slice(&self.stats.stale_pops.to_le_bytes());
+        bytes.extend_from_slice(&self.stats.visited_key_bytes.to_le_bytes());
+        match &self.solution {
+            Some(solution) => {
+                bytes.push(1);
+                _write_moves(&mut bytes, &solution.moves);
+            }
+            None => bytes.push(0),
+        }
+        // Heap order, so the resumed solver pops boards in the same order
+        bytes.extend_from_slice(&(self.board_leaves.len() as u32).to_le_bytes());
+        for board in self.board_leaves.iter() {
+            _write_tiles(&mut bytes, board.tiles());
+            _write_moves(&mut bytes, &board.path);
+        }
+        let closed: Vec<(&[Tile], i32)> = self.best_depths.tiles()
+            .filter(|&(tiles, _)| self.closed.keys.contains(&_tiles_key(tiles)))
+            .collect();
+        bytes.extend_from_slice(&(closed.len() as u32).to_le_bytes());
+        for (tiles, depth) in closed {
+            _write_tiles(&mut bytes, tiles);
+            bytes.extend_from_slice(&depth.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Resume a search from state written by `serialize_state`.
+    ///
+    /// # Parameters
+    /// * `bytes` - Serialized solver state
+    ///
+    /// # Returns
+    /// A solver that continues exactly where the snapshot left off, or why the state
+    /// can't be resumed
+    pub fn resume(bytes: &[u8]) -> Result<Solver, ResumeError> {
+        let mut reader = StateReader { bytes, position: 0 };
+        let version = reader.u8()?;
+        if version != STATE_VERSION {
+            return Err(ResumeError::UnsupportedVersion(version));
+        }
+        let n = reader.u8()? as i32;
+        if n < 1 {
+            return Err(ResumeError::Corrupt(format!("invalid board size {}", n)));
+        }
+        let root_tiles = reader.tiles(n)?;
+        let heuristic = match reader.u8()? {
+            0 => Heuristic::Manhattan,
+            1 => Heuristic::LinearConflicts,
+            2 => Heuristic::InversionDistance,
+            other => match HeuristicSet::from_bits(other & 0x7f) {
+                Some(parts) if other & 0x80 != 0 => Heuristic::Max(parts),
+                _ => return Err(ResumeError::Corrupt(format!("unknown heuristic {}", other))),
+            },
+        };
+        let flags = reader.u8()?;
+        if flags > 0b111 {
+            return Err(ResumeError::Corrupt(format!("unknown search flags {}", flags)));
+        }
+        let algorithm = if flags & 1 == 1 { Algorithm::Greedy } else { Algorithm::AStar };
+        let options = SolveOptions::new()
+            .heuristic(heuristic)
+            .algorithm(algorithm)
+            .stop_on_generate(flags & 2 == 2)
+            .paranoid(flags & 4 == 4);
+        let mut solver = Solver::with_options(n, root_tiles, &options);
+        solver.max_nodes = reader.u64()?;
+        solver.max_memory_bytes = reader.u64()?;
+        solver.time_budget = match reader.u64()? {
+            u64::MAX => TimeBudget::new(None),
+            remaining_ms => TimeBudget::new(Some(remaining_ms)),
+        };
+        solver.proven_lower_bound = match reader.i32()? {
+            -1 => None,
+            bound => Some(bound),
+        };
+        solver.best_heuristic = reader.i32()?;
+        solver.stats.nodes_expanded = reader.u64()?;
+        solver.stats.nodes_generated = reader.u64()?;
+        solver.stats.max_heuristic = reader.i32()?;
+        let depths = reader.u32()?;
+        solver.stats.expansions_by_depth = (0..depths).map(|_| reader.u64()).collect::<Result<_, _>>()?;
+        solver.stats.effective_branching_factor = f64::from_bits(reader.u64()?);
+        solver.stats.stale_pops = reader.u64()?;
+        solver.stats.visited_key_bytes = reader.u64()?;
+        solver.solution = match reader.u8()? {
+            0 => None,
+            1 => Some(Solution { moves: reader.moves()?, stats: solver.stats.clone(), optimal: !solver.greedy }),
+            other => return Err(ResumeError::Corrupt(format!("invalid solution flag {}", other))),
+        };
+        let leaves = reader.u32()?;
+        let mut board_leaves = Vec::new();
+        for _i in 0..leaves {
+            let tiles = reader.tiles(n)?;
+            let mut board = Board::new(n, None, 0, Some(tiles.into_boxed_slice()));
+            board.path = reader.moves()?;
+            board.depth = board.path.len() as i32;
+            solver.cost(&mut board);
+            board_leaves.push(board);
+        }
+        // Already in heap order, so this doesn't reorder anything
+        solver.board_leaves = BinaryHeap::from(board_leaves);
+        let closed = reader.u32()?;
+        let mut closed_boards = Vec::new();
+        for _i in 0..closed {
+            let tiles = reader.tiles(n)?;
+            closed_boards.push((Board::new(n, None, 0, Some(tiles.into_boxed_slice())), reader.i32()?));
+        }
+        let mut best_depths = VisitedMap::new(false);
+        for board in solver.board_leaves.iter() {
+            best_depths.lower(board, board.depth);
+        }
+        for (board, depth) in &closed_boards {
+            best_depths.lower(board, *depth);
+        }
+        // Boards pushed again at a smaller depth are open again
+        solver.closed = ClosedSet::new();
+        for (board, depth) in &closed_boards {
+            if best_depths.get(board) == Some(*depth) {
+                solver.closed.insert(board);
+            }
+        }
+        solver.best_depths = best_depths;
+        if reader.position != bytes.len() {
+            return Err(ResumeError::Corrupt("trailing bytes".to_string()));
+        }
+        Ok(solver)
+    }
+}
+
+/// Identify a board's tiles with a u64.
+///
+/// # Parameters
+/// * `board` - The board
+///
+/// # Returns
+/// Packed tiles for boards up to 4x4, otherwise a hash of the tiles
+fn _state_key(board: &Board) -> u64 {
+    _tiles_key(board.tiles())
+}
+
+/// Identify tiles with a u64, see `_state_key`.
+fn _tiles_key(tiles: &[Tile]) -> u64 {
+    if tiles.len() <= 16 {
+        return pack::encode_tiles(tiles);
+    }
+    let mut hasher = DefaultHasher::new();
+    tiles.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Append tiles to solver state.
+///
+/// # Parameters
+/// * `bytes` - Solver state being written
+/// * `tiles` - Board tiles
+fn _write_tiles(bytes: &mut Vec<u8>, tiles: &[Tile]) {
+    if tiles.len() <= 16 {
+        bytes.extend_from_slice(&super::pack::encode_tiles(tiles).to_le_bytes());
+    } else {
+        bytes.extend(tiles.iter().map(|tile| if tile.is_blank() { 0 } else { tile.symbol() as u8 }));
+    }
+}
+
+/// Append moves to solver state, prefixed with their count.
+///
+/// # Parameters
+/// * `bytes` - Solver state being written
+/// * `moves` - String of single characters representing moves
+fn _write_moves(bytes: &mut Vec<u8>, moves: &str) {
+    bytes.extend_from_slice(&(moves.len() as u32).to_le_bytes());
+    // Paths are only ever built from moves
+    bytes.extend(encode_moves(moves).unwrap_or_default());
+}
+
+/// Reader over serialized solver state.
+///
+/// # Attributes
+/// * `bytes` - Serialized solver state
+/// * `position` - Index of the next byte
+struct StateReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    /// Read the next `count` bytes.
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ResumeError> {
+        let end = self.position.checked_add(count).ok_or(ResumeError::Truncated)?;
+        let taken = self.bytes.get(self.position..end).ok_or(ResumeError::Truncated)?;
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, ResumeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ResumeError> {
+        let mut buffer = [0; 4];
+        buffer.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(buffer))
+    }
+
+    fn i32(&mut self) -> Result<i32, ResumeError> {
+        self.u32().map(|value| value as i32)
+    }
+
+    fn u64(&mut self) -> Result<u64, ResumeError> {
+        let mut buffer = [0; 8];
+        buffer.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(buffer))
+    }
+
+    /// Read and validate the tiles of a board.
+    fn tiles(&mut self, n: i32) -> Result<Vec<Tile>, ResumeError> {
+        let n2 = (n * n) as usize;
+        let tiles = if n2 <= 16 {
+            super::pack::decode_tiles(n, self.u64()?)
+        } else {
+            self.take(n2)?.iter()
+                .map(|&symbol| Tile::new(if symbol == 0 { BLANK_TILE } else { symbol as i32 }))
+                .collect()
+        };
+        Board::validate(n, &tiles).map_err(|e| ResumeError::Corrupt(e.to_string()))?;
+        Ok(tiles)
+    }
+
+    /// Read moves written by `_write_moves`.
+    fn moves(&mut self) -> Result<String, ResumeError> {
+        let count = self.u32()? as usize;
+        let encoded = self.take(count)?;
+        decode_moves(encoded).ok_or_else(|| ResumeError::Corrupt("invalid move".to_string()))
+    }
+}
+
+/// Main function of tile solver.
+/// * Every other way of solving a board funnels through here.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `options` - Options controlling the solve
+///
+/// # Returns
+/// The solution moves and search statistics, or why the board can't be solved
+pub fn solve(board: &Board, options: &SolveOptions) -> Result<Solution, SolveError> {
+    solve_observed(board, options, &mut NoopObserver)
+}
+
+/// Solve a board, reporting search events to an observer.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `options` - Options controlling the solve
+/// * `observer` - Observer notified of each expansion, generated board and the goal
+///
+/// # Returns
+/// The solution moves and search statistics, or why the board can't be solved
+pub fn solve_observed<O: SolverObserver>(board: &Board, options: &SolveOptions,
+                                         observer: &mut O) -> Result<Solution, SolveError> {
+    options.validate()?;
+    let started_ms = clock::now_ms();
+    let mut solution = match options.algorithm {
+        Algorithm::AStar | Algorithm::Greedy => {
+            // An empty frontier also proves it, but only small boards get that far
+            if !board.is_solvable() {
+                return Err(SolveError::Unreachable);
+            }
+            let mut solver = Solver::with_options(board.size(), board.tiles().to_vec(), options);
+            loop {
+                if let Some(solution) = solver.step_observed(u64::MAX, observer)? {
+                    break solution;
+                }
+            }
+        }
+        Algorithm::IdaStar => _ida_star(board, options, observer)?,
+        Algorithm::Layered => {
+            let solution = layered::solve_layered(board, options)?;
+            observer.on_goal(&solution);
+            solution
+        }
+    };
+    if options.canonical {
+        solution.moves = canonical_moves(board, solution.moves.len(), &options.move_order);
+    }
+    solution.stats.millis = clock::now_ms() - started_ms;
+    Ok(solution)
+}
+
+/// Solve a board, starting from moves that probably lead towards the goal, e.g. those a
+/// player has already made or an earlier solution.
+/// * A hint that solves the board is returned as is, without searching; it is only
+///   known to be optimal if its length matches the board's heuristic.
+/// * Otherwise A* and greedy search start with the boards along the hint, up to its
+///   first illegal move, on the open list. The hint can only speed the search up, and
+///   A* solutions stay optimal.
+/// * IDA* and layered solving have no open list, so they discard the hint.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `hint_path` - Moves of the blank, read with `parse_path`; unreadable hints are
+///   discarded
+/// * `options` - Options controlling the solve
+///
+/// # Returns
+/// The solution moves and search statistics, with `SolveStats::hint_usage` set, or why
+/// the board can't be solved
+pub fn solve_with_hint(board: &Board, hint_path: &str, options: &SolveOptions) -> Result<Solution, SolveError> {
+    options.validate()?;
+    let hint = parse_path(hint_path, Notation::BlankMoves).unwrap_or_default();
+    let moves: String = hint.iter().collect();
+    if board.apply_moves(&moves, Notation::BlankMoves).is_ok_and(|solved| solved.is_solved()) {
+        let breakdown = board.heuristic_breakdown(None);
+        let optimal = moves.len() as i32 == breakdown.manhattan + breakdown.linear_conflicts;
+        if optimal || !options.canonical {
+            let stats = SolveStats { hint_usage: Some(HintUsage::Verbatim), ..SolveStats::default() };
+            let moves = if options.canonical {
+                canonical_moves(board, moves.len(), &options.move_order)
+            } else {
+                moves
+            };
+            return Ok(Solution { moves, stats, optimal });
+        }
+    }
+    let mut solution = match options.algorithm {
+        Algorithm::AStar | Algorithm::Greedy => {
+            let mut solver = Solver::with_options(board.size(), board.tiles().to_vec(), options);
+            let seeded = solver.seed(&hint);
+            let mut solution = loop {
+                if let Some(solution) = solver.step(u64::MAX)? {
+                    break solution;
+                }
+            };
+            solution.stats.hint_usage = Some(if seeded > 0 { HintUsage::Partial } else { HintUsage::Discarded });
+            solution
+        }
+        Algorithm::IdaStar | Algorithm::Layered => {
+            let mut solution = solve(board, &options.clone().canonical(false))?;
+            solution.stats.hint_usage = Some(HintUsage::Discarded);
+            solution
+        }
+    };
+    if options.canonical {
+        solution.moves = canonical_moves(board, solution.moves.len(), &options.move_order);
+    }
+    Ok(solution)
+}
+
+/// Solve a board towards the goal of a preset rather than the standard solved board.
+/// * Every preset puts the blank in a corner, so solutions are optimal when the
+///   algorithm is, see `solve_between`.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `preset` - Goal layout
+/// * `options` - Options controlling the solve
+///
+/// # Returns
+/// The solution moves and search statistics, or why the board can't reach the goal
+pub fn solve_to_preset(board: &Board, preset: GoalPreset, options: &SolveOptions) -> Result<Solution, SolveError> {
+    if preset == GoalPreset::Standard {
+        return solve(board, options);
+    }
+    solve_between(board, &Board::solved_with_preset(board.size(), preset), options)
+}
+
+/// Solve a board towards any layout of its tiles.
+/// * Reachability is checked up front, see `Board::reachability`.
+/// * A goal with the blank in a corner is mirrored to move that corner to the bottom
+///   right, and the board renumbered so the mirrored goal reads like the standard one,
+///   solved as usual, and the moves mirrored back.
+/// * Otherwise the goal's blank is first moved right then down to the bottom right,
+///   and the solution ends by moving it back, so it isn't known to be optimal.
+///
+/// # Parameters
+/// * `start` - The board to solve
+/// * `goal` - Layout to solve towards
+/// * `options` - Options controlling the solve
+///
+/// # Returns
+/// The solution moves and search statistics, or `SolveError::Unreachable` if the
+/// board can't reach the goal
+pub fn solve_between(start: &Board, goal: &Board, options: &SolveOptions) -> Result<Solution, SolveError> {
+    if start.reachability(goal).is_err() {
+        return Err(SolveError::Unreachable);
+    }
+    let n = start.size();
+    let blank = goal.get_blank_index();
+    let (row, col) = (blank / n, blank % n);
+    if row != 0 && row != n - 1 || col != 0 && col != n - 1 {
+        let detour: String = std::iter::repeat_n(RIGHT, (n - 1 - col) as usize)
+            .chain(std::iter::repeat_n(DOWN, (n - 1 - row) as usize))
+            .collect();
+        let cornered = goal.apply_moves(&detour, Notation::BlankMoves).expect("moves stay on the board");
+        let mut solution = solve_between(start, &cornered, options)?;
+        solution.moves.push_str(&invert_path(&detour));
+        solution.optimal = false;
+        return Ok(solution);
+    }
+    // Each mirror is its own inverse, so it also maps the moves back
+    let mirror = match (row == 0, col == 0) {
+        (true, true) => Transform::Rotate180,
+        (true, false) => Transform::FlipVertical,
+        (false, true) => Transform::FlipHorizontal,
+        (false, false) => Transform::Identity,
+    };
+    let goal = goal.transform(mirror);
+    let symbols: HashMap<i32, i32> = goal.tiles().iter()
+        .enumerate()
+        .filter(|(_, tile)| !tile.is_blank())
+        .map(|(i, tile)| (tile.symbol(), i as i32 + 1))
+        .collect();
+    let tiles: Vec<Tile> = start.transform(mirror).tiles().iter()
+        .map(|tile| if tile.is_blank() { *tile } else { Tile::new(symbols[&tile.symbol()]) })
+        .collect();
+    let mut solution = solve(&Board::new(n, None, 0, Some(tiles.into_boxed_slice())), options)?;
+    solution.moves = transform_path(&solution.moves, mirror);
+    Ok(solution)
+}
+
+/// Find moves that scramble the solved board into a target board, e.g. for a demo that
+/// starts solved and ends on an interesting position.
+/// * Solves the target with the options for its size, see `SolveOptions::for_size`,
+///   and inverts the solution, see `invert_path`.
+///
+/// # Parameters
+/// * `target` - Board to scramble into
+///
+/// # Returns
+/// Moves of the blank that reach exactly `target` from the solved board, or the error
+/// solving the target, e.g. `SolveError::Unreachable`
+pub fn scramble_to(target: &Board) -> Result<String, SolveError> {
+    let solution = solve(target, &SolveOptions::for_size(target.size()))?;
+    Ok(invert_path(&solution.moves))
+}
+
+/// Solve a board, passing the solution to a sink in chunks as soon as they are final.
+/// * `Algorithm::Layered` passes the moves of each row and column as they are placed,
+///   then those of the core; other algorithms pass the whole solution once found.
+/// * Chunks are never empty and hold whole moves; concatenated, they are the solution.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `options` - Options controlling the solve
+/// * `sink` - Called with each chunk of blank moves, in order
+///
+/// # Returns
+/// Search statistics, or why the board can't be solved
+pub fn solve_streaming<F: FnMut(&str)>(board: &Board, options: &SolveOptions,
+                                       mut sink: F) -> Result<SolveStats, SolveError> {
+    if options.algorithm != Algorithm::Layered {
+        let solution = solve(board, options)?;
+        if !solution.moves.is_empty() {
+            sink(&solution.moves);
+        }
+        return Ok(solution.stats);
+    }
+    options.validate()?;
+    let mut solver = LayeredSolver::new(board, options)?;
+    while let Some(chunk) = solver.next_chunk()? {
+        sink(&chunk);
+    }
+    Ok(solver.stats().clone())
+}
+
+/// Solve a board with iterative deepening A*.
+/// * Each iteration is a depth-first search pruning boards whose cost exceeds the
+///   bound; the next bound is the smallest cost that was pruned.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `options` - Options controlling the solve
+/// * `observer` - Observer notified of each expansion, generated board and the goal
+///
+/// # Returns
+/// The solution moves and search statistics, or why the board can't be solved
+fn _ida_star<O: SolverObserver>(board: &Board, options: &SolveOptions,
+                                observer: &mut O) -> Result<Solution, SolveError> {
+    // Without a closed set, an unsolvable board would deepen forever
+    if !board.is_solvable() {
+        return Err(SolveError::Unreachable);
+    }
+    let (search, root) = IdaSearch::new(board, options, None);
+    search.solve(&root, observer)
+}
+
+/// Find the optimal solution length of a board without building the solution.
+/// * Runs IDA* on a single board, making and unmaking moves in place, so no paths or
+///   parent links are kept and nearly nothing is allocated per board.
+/// * With the Manhattan heuristic, costs are updated incrementally per move, which is
+///   several times faster than `solve`; linear conflicts are counted per row and
+///   column through a `ConflictCache`.
+/// * Both algorithms find the same length, so `algorithm` and `canonical` are
+///   ignored; `max_nodes` limits the boards expanded by the depth-first search.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `options` - Options controlling the solve
+///
+/// # Returns
+/// Number of moves in an optimal solution, or why the board can't be solved
+pub fn solve_length(board: &Board, options: &SolveOptions) -> Result<u32, SolveError> {
+    solve_length_with_stats(board, options).map(|(length, _stats)| length)
+}
+
+/// Find the optimal solution length of a board, with search statistics.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `options` - Options controlling the solve
+///
+/// # Returns
+/// Number of moves in an optimal solution and search statistics, or why the board
+/// can't be solved
+pub fn solve_length_with_stats(board: &Board, options: &SolveOptions) -> Result<(u32, SolveStats), SolveError> {
+    options.validate()?;
+    if !board.is_solvable() {
+        return Err(SolveError::Unreachable);
+    }
+    let (search, mut root) = IdaSearch::new(board, options, None);
+    search.solve_length(&mut root)
+}
+
+/// Report a heuristic that failed a check of a search.
+///
+/// # Parameters
+/// * `paranoid` - Whether the search checks its heuristic, see `SolveOptions::paranoid`
+/// * `violation` - The failed check
+///
+/// # Returns
+/// `SolveError::HeuristicInconsistent`, or panics in debug builds unless paranoid
+fn _heuristic_violated(paranoid: bool, violation: HeuristicViolation) -> Result<(), SolveError> {
+    debug_assert!(paranoid, "heuristic failed a check, {}", violation);
+    Err(SolveError::HeuristicInconsistent(Box::new(violation)))
+}
+
+/// Pack a goal for the goal test of the search loops, see `_is_goal`.
+///
+/// # Parameters
+/// * `goal` - Goal board
+///
+/// # Returns
+/// Packed tiles of the goal, or `None` if it is too large to pack
+fn _goal_key(goal: &Board) -> Option<u64> {
+    pack::encode(goal).ok()
+}
+
+/// Check if a board of the search is the goal.
+/// * Boards that can be packed are compared by their packed tiles, a single u64
+///   comparison against the precomputed key; larger boards compare tiles.
+///
+/// # Parameters
+/// * `board` - Board of the goal's size
+/// * `goal` - Goal board
+/// * `goal_key` - Packed tiles of the goal, from `_goal_key`
+///
+/// # Returns
+/// Whether the board is in the goal layout
+fn _is_goal(board: &Board, goal: &Board, goal_key: Option<u64>) -> bool {
+    match goal_key {
+        Some(key) => pack::encode_tiles(board.tiles()) == key,
+        None => board.is_goal_for(goal),
+    }
+}
+
+/// State shared across the depth-first searches of IDA*.
+///
+/// # Attributes
+/// * `solved_board` - Solved version of the board
+/// * `goal_key` - Packed tiles of the solved board, if it can be packed
+/// * `heuristic` - Heuristic guiding the search
+/// * `max_nodes` - Give up after expanding this many boards
+/// * `stats` - Statistics collected so far
+/// * `conflict_cache` - Cached linear conflicts of rows and columns
+/// * `transposition` - Heuristics and failed searches of boards seen before, if enabled
+/// * `order_children` - Search the children of each board in ascending order of cost
+/// * `expanded_paths` - Paths of the boards expanded so far, when tracked
+/// * `best_board` - Expanded board with the lowest heuristic, with its path
+/// * `started_ms` - Reading of the clock when the search started
+/// * `time_budget` - Deadline of the search
+/// * `failure` - Failure to inject, with the `chaos` feature
+/// * `paranoid` - Fail with `SolveError::HeuristicInconsistent` when the heuristic
+///   fails a check, see `SolveOptions::paranoid`
+/// * `estimate` - Heuristic used instead of `heuristic`, see `Solver::estimate`
+struct IdaSearch {
+    solved_board: Board,
+    goal_key: Option<u64>,
+    heuristic: Heuristic,
+    order_children: bool,
+    max_nodes: u64,
+    stats: SolveStats,
+    conflict_cache: ConflictCache,
+    transposition: Option<TranspositionTable>,
+    expanded_paths: Option<Vec<String>>,
+    best_board: Board,
+    started_ms: f64,
+    time_budget: TimeBudget,
+    failure: FailurePoint,
+    paranoid: bool,
+    estimate: Option<fn(&Board, &Board) -> i32>,
+}
+
+impl IdaSearch {
+    /// Create a search of a board.
+    ///
+    /// # Parameters
+    /// * `board` - The board to solve
+    /// * `options` - Options controlling the solve
+    /// * `estimate` - Heuristic used instead of `options.heuristic`, if any
+    ///
+    /// # Returns
+    /// The search and the root board, costed
+    fn new(board: &Board, options: &SolveOptions,
+           estimate: Option<fn(&Board, &Board) -> i32>) -> (IdaSearch, Board) {
+        let solved_board = Board::new(board.size(), None, -1, None);
+        let mut root = board.clone();
+        root.path.clear();
+        root.depth = 0;
+        let mut search = IdaSearch {
+            conflict_cache: ConflictCache::new(&solved_board),
+            goal_key: _goal_key(&solved_board),
+            solved_board,
+            heuristic: options.heuristic,
+            order_children: options.order_children,
+            max_nodes: options.max_nodes.unwrap_or(u64::MAX),
+            stats: SolveStats::default(),
+            transposition: IdaSearch::transposition_table(board, options),
+            expanded_paths: if options.track_path_expansions { Some(Vec::new()) } else { None },
+            best_board: root.clone(),
+            started_ms: clock::now_ms(),
+            time_budget: TimeBudget::new(options.max_time_ms),
+            failure: FailurePoint::new(options),
+            paranoid: options.paranoid,
+            estimate,
+        };
+        root.cost = root.g() + search.h(&mut root);
+        search.best_board = root.clone();
+        (search, root)
+    }
+
+    /// Solve the root board, deepening the bound until the goal is found.
+    ///
+    /// # Parameters
+    /// * `root` - Root board, from `new`
+    /// * `observer` - Observer notified of each expansion, generated board and the goal
+    ///
+    /// # Returns
+    /// The solution moves and search statistics, or why the board can't be solved
+    fn solve<O: SolverObserver>(mut self, root: &Board, observer: &mut O) -> Result<Solution, SolveError> {
+        let mut bound = root.cost;
+        loop {
+            let progress = SearchProgress {
+                nodes_expanded: self.stats.nodes_expanded,
+                proven_lower_bound: bound,
+                elapsed_ms: clock::now_ms() - self.started_ms,
+            };
+            observer.on_progress(&progress);
+            match self.dfs(root, bound, observer)? {
+                Ok(moves) => {
+                    if let Some(expanded_paths) = self.expanded_paths.take() {
+                        self.stats.expansions_by_prefix = _expansions_by_prefix(&expanded_paths, &moves);
+                    }
+                    self.stats.effective_branching_factor = SolveStats::effective_branching_factor(
+                        self.stats.nodes_expanded,
+                        moves.len(),
+                    );
+                    self.stats.record_conflict_cache(&self.conflict_cache);
+                    if let Some(transposition) = &self.transposition {
+                        self.stats.record_transposition(transposition);
+                    }
+                    self.stats.millis = clock::now_ms() - self.started_ms;
+                    let solution = Solution { moves, stats: self.stats, optimal: true };
+                    observer.on_goal(&solution);
+                    return Ok(solution);
+                }
+                Err(next_bound) => {
+                    // Everything within the bound was searched, so only larger costs remain
+                    debug_assert!(next_bound > bound, "next bound {} not above bound {}", next_bound, bound);
+                    bound = next_bound;
+                }
+            }
+        }
+    }
+
+    /// Find the optimal solution length of the root board, see `solve_length`.
+    ///
+    /// # Parameters
+    /// * `root` - Root board, from `new`
+    ///
+    /// # Returns
+    /// Number of moves in an optimal solution and search statistics, or why the board
+    /// can't be solved
+    fn solve_length(mut self, root: &mut Board) -> Result<(u32, SolveStats), SolveError> {
+        let mut bound = root.cost;
+        loop {
+            match self.dfs_length(root, bound, '\0')? {
+                Ok(length) => {
+                    self.stats.effective_branching_factor = SolveStats::effective_branching_factor(
+                        self.stats.nodes_expanded,
+                        length as usize,
+                    );
+                    self.stats.record_conflict_cache(&self.conflict_cache);
+                    if let Some(transposition) = &self.transposition {
+                        self.stats.record_transposition(transposition);
+                    }
+                    self.stats.millis = clock::now_ms() - self.started_ms;
+                    return Ok((length as u32, self.stats));
+                }
+                Err(next_bound) => bound = next_bound,
+            }
+        }
+    }
+
+    /// Estimate the distance of a board from the goal, storing it on the board.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    ///
+    /// # Returns
+    /// The heuristic of the board
+    fn h(&mut self, board: &mut Board) -> i32 {
+        match self.estimate {
+            Some(estimate) => {
+                board.manhattan_cost_cache = estimate(board, &self.solved_board);
+                board.linear_conflicts_cache = 0;
+                board.stored_h()
+            }
+            None => board.store_h_cached(&self.solved_board, self.heuristic, &mut self.conflict_cache),
+        }
+    }
+
+    /// Check a goal reached by the search has a heuristic of 0, as in `Solver::check_goal`.
+    ///
+    /// # Parameters
+    /// * `goal` - The goal reached
+    ///
+    /// # Returns
+    /// `SolveError::HeuristicInconsistent` if the heuristic of the goal isn't 0
+    fn check_goal(&self, goal: &Board) -> Result<(), SolveError> {
+        if goal.stored_h() == 0 || !(self.paranoid || cfg!(debug_assertions)) {
+            return Ok(());
+        }
+        _heuristic_violated(self.paranoid, HeuristicViolation::Inadmissible {
+            board: Box::new(goal.clone()),
+            estimate: goal.stored_h(),
+            distance: 0,
+        })
+    }
+
+    /// Check the heuristic of a board drops by at most 1 to a child, if the heuristic
+    /// claims consistency, as in `Solver::check_child`.
+    ///
+    /// # Parameters
+    /// * `board` - The board expanded
+    /// * `tile_move` - Move reaching the child
+    /// * `child_h` - Heuristic of the child
+    ///
+    /// # Returns
+    /// `SolveError::HeuristicInconsistent` if the heuristic drops by more
+    fn check_child(&self, board: &Board, tile_move: char, child_h: i32) -> Result<(), SolveError> {
+        if child_h >= board.stored_h() - 1 || !(self.paranoid || cfg!(debug_assertions)) {
+            return Ok(());
+        }
+        if self.estimate.is_none() && !self.heuristic.is_consistent() {
+            return Ok(());
+        }
+        let mut neighbour = board.clone();
+        neighbour.move_blank_tile(tile_move);
+        _heuristic_violated(self.paranoid, HeuristicViolation::Inconsistent {
+            board: Box::new(board.clone()),
+            neighbour: Box::new(neighbour),
+            estimate: board.stored_h(),
+            neighbour_estimate: child_h,
+        })
+    }
+
+    /// Create the transposition table asked for by the options.
+    ///
+    /// # Parameters
+    /// * `board` - The board being solved
+    /// * `options` - Options controlling the solve
+    ///
+    /// # Returns
+    /// The table, or `None` if it is disabled or the board is too large to store
+    fn transposition_table(board: &Board, options: &SolveOptions) -> Option<TranspositionTable> {
+        if options.tt_entries == 0 || TranspositionTable::key(board).is_none() {
+            return None;
+        }
+        Some(TranspositionTable::new(options.tt_entries, options.tt_replacement))
+    }
+
+    /// Cost a newly generated board, reading its heuristic from the transposition table
+    /// when possible.
+    ///
+    /// # Parameters
+    /// * `board` - Board at its depth
+    /// * `bound` - Cost bound of this iteration
+    fn cost(&mut self, board: &mut Board, bound: i32) {
+        let key = match &mut self.transposition {
+            Some(transposition) => {
+                let key = pack::encode_tiles(board.tiles());
+                if transposition.load_heuristic(board, key) {
+                    return;
+                }
+                Some(key)
+            }
+            None => None,
+        };
+        board.cost = board.g() + self.h(board);
+        if let (Some(transposition), Some(key)) = (&mut self.transposition, key) {
+            transposition.store_heuristic(board, key, bound);
+        }
+    }
+
+    /// Keep a board if it is the closest to the goal expanded so far.
+    ///
+    /// # Parameters
+    /// * `board` - Board being expanded
+    fn record_best(&mut self, board: &Board) {
+        if board.stored_h() < self.best_board.stored_h() {
+            self.best_board = board.clone();
+        }
+    }
+
+    /// Report what the search learned before reaching the node limit.
+    /// * Every bound below this iteration's was searched without finding the goal, so
+    ///   the bound is a lower bound on the solution length.
+    ///
+    /// # Parameters
+    /// * `bound` - Cost bound of this iteration
+    ///
+    /// # Returns
+    /// `SolveError::BudgetExceeded` with the partial result
+    fn budget_exceeded(&self, bound: i32) -> SolveError {
+        let stats = SolveStats { millis: clock::now_ms() - self.started_ms, ..self.stats.clone() };
+        SolveError::BudgetExceeded(Box::new(PartialResult::new(&self.best_board, bound, stats)))
+    }
+
+    /// Check the transposition table for a board already shown to fail this iteration.
+    ///
+    /// # Parameters
+    /// * `board` - Board about to be searched
+    /// * `bound` - Cost bound of this iteration
+    ///
+    /// # Returns
+    /// The smallest cost searching the board would prune, if it is known to fail
+    fn cutoff(&mut self, board: &Board, bound: i32) -> Option<i32> {
+        let transposition = self.transposition.as_mut()?;
+        transposition.cutoff(pack::encode_tiles(board.tiles()), board.depth, bound)
+    }
+
+    /// Record in the transposition table that a board failed this iteration.
+    ///
+    /// # Parameters
+    /// * `board` - Board that was searched
+    /// * `bound` - Cost bound of this iteration
+    /// * `next_bound` - Smallest cost pruned below the board
+    fn store_result(&mut self, board: &Board, bound: i32, next_bound: i32) {
+        if let Some(transposition) = &mut self.transposition {
+            transposition.store_result(board, pack::encode_tiles(board.tiles()), bound, next_bound);
+        }
+    }
+
+    /// Depth-first search below a board, pruning boards whose cost exceeds a bound.
+    ///
+    /// # Parameters
+    /// * `board` - Current board
+    /// * `bound` - Cost bound of this iteration
+    /// * `observer` - Observer notified of each expansion and generated board
+    ///
+    /// # Returns
+    /// The solution moves if found, otherwise the smallest pruned cost
+    fn dfs<O: SolverObserver>(&mut self, board: &Board, bound: i32,
+                              observer: &mut O) -> Result<Result<String, i32>, SolveError> {
+        if board.cost > bound {
+            return Ok(Err(board.cost));
+        }
+        if _is_goal(board, &self.solved_board, self.goal_key) {
+            self.check_goal(board)?;
+            return Ok(Ok(board.path.clone()));
+        }
+        if let Some(pruned) = self.cutoff(board, bound) {
+            return Ok(Err(pruned));
+        }
+        if self.stats.nodes_expanded >= self.failure.node_limit(self.max_nodes) {
+            return Err(self.failure.error(self.stats.nodes_expanded, self.max_nodes, self.budget_exceeded(bound)));
+        }
+        if self.time_budget.expired() {
+            return Err(self.budget_exceeded(bound));
+        }
+        self.stats.record_expansion(board);
+        self.record_best(board);
+        if let Some(expanded_paths) = &mut self.expanded_paths {
+            expanded_paths.push(board.path.clone());
+        }
+        observer.on_expand(board, board.depth, board.stored_h());
+        let mut children = Vec::with_capacity(4);
+        for tile_move in board.legal_moves() {
+            if _is_back_step(board, tile_move) {
+                continue;
+            }
+            let mut new_board = board.clone();
+            new_board.path.push(tile_move);
+            new_board.move_blank_tile(tile_move);
+            new_board.depth = board.depth + 1;
+            self.cost(&mut new_board, bound);
+            self.check_child(board, tile_move, new_board.stored_h())?;
+            observer.on_generate(&new_board, tile_move);
+            self.stats.nodes_generated += 1;
+            children.push(new_board);
+        }
+        if self.order_children {
+            // Stable, so ties keep the move order
+            children.sort_by_key(|child| child.cost);
+        }
+        let mut next_bound = i32::MAX;
+        for child in &children {
+            match self.dfs(child, bound, observer)? {
+                Ok(moves) => return Ok(Ok(moves)),
+                Err(pruned) => next_bound = next_bound.min(pruned),
+            }
+        }
+        self.store_result(board, bound, next_bound);
+        Ok(Err(next_bound))
+    }
+
+    /// Depth-first search below a board for the solution length only.
+    /// * The board is moved in place and restored before returning.
+    ///
+    /// # Parameters
+    /// * `board` - Current board, costed
+    /// * `bound` - Cost bound of this iteration
+    /// * `last_move` - Move that produced the board, so it isn't undone
+    ///
+    /// # Returns
+    /// The solution length if found, otherwise the smallest pruned cost
+    fn dfs_length(&mut self, board: &mut Board, bound: i32,
+                  last_move: char) -> Result<Result<i32, i32>, SolveError> {
+        if board.cost > bound {
+            return Ok(Err(board.cost));
+        }
+        if _is_goal(board, &self.solved_board, self.goal_key) {
+            self.check_goal(board)?;
+            return Ok(Ok(board.depth));
+        }
+        if let Some(pruned) = self.cutoff(board, bound) {
+            return Ok(Err(pruned));
+        }
+        if self.stats.nodes_expanded >= self.failure.node_limit(self.max_nodes) {
+            return Err(self.failure.error(self.stats.nodes_expanded, self.max_nodes, self.budget_exceeded(bound)));
+        }
+        if self.time_budget.expired() {
+            return Err(self.budget_exceeded(bound));
+        }
+        self.stats.record_expansion(board);
+        self.record_best(board);
+        let costs = (board.cost, board.manhattan_cost_cache, board.linear_conflicts_cache);
+        // Cost every child up front, so they can be ordered without costing them twice
+        let mut children = [('\0', 0, 0, 0); 4];
+        let mut child_count = 0;
+        for &tile_move in MOVES.iter() {
+            if !board.is_legal_move(tile_move) || opposite_direction(tile_move) == last_move {
+                continue;
+            }
+            let child = if self.heuristic == Heuristic::Manhattan && self.estimate.is_none() {
+                // Update the cost incrementally instead of rescanning the board
+                let manhattan = costs.1 + board.manhattan_delta(tile_move);
+                (tile_move, board.g() + 1 + manhattan, manhattan, 0)
+            } else {
+                board.move_blank_tile(tile_move);
+                board.depth += 1;
+                self.cost(board, bound);
+                let child = (tile_move, board.cost, board.manhattan_cost_cache, board.linear_conflicts_cache);
+                board.move_blank_tile(opposite_direction(tile_move));
+                board.depth -= 1;
+                board.cost = costs.0;
+                board.manhattan_cost_cache = costs.1;
+                board.linear_conflicts_cache = costs.2;
+                child
+            };
+            self.check_child(board, tile_move, child.2 + child.3)?;
+            children[child_count] = child;
+            child_count += 1;
+            self.stats.nodes_generated += 1;
+        }
+        if self.order_children {
+            children[..child_count].sort_by_key(|&(_, cost, _, _)| cost);
+        }
+        let mut next_bound = i32::MAX;
+        for &(tile_move, cost, manhattan, linear_conflicts) in &children[..child_count] {
+            board.move_blank_tile(tile_move);
+            board.path.push(tile_move);
+            board.depth += 1;
+            board.cost = cost;
+            board.manhattan_cost_cache = manhattan;
+            board.linear_conflicts_cache = linear_conflicts;
+            let result = self.dfs_length(board, bound, tile_move);
+            board.move_blank_tile(opposite_direction(tile_move));
+            board.path.pop();
+            board.depth -= 1;
+            board.cost = costs.0;
+            board.manhattan_cost_cache = costs.1;
+            board.linear_conflicts_cache = costs.2;
+            match result? {
+                Ok(length) => return Ok(Ok(length)),
+                Err(pruned) => next_bound = next_bound.min(pruned),
+            }
+        }
+        self.store_result(board, bound, next_bound);
+        Ok(Err(next_bound))
+    }
+}
+
+/// Boards expanded by `scramble_adversarial` when the options don't set a node limit
+pub const DEFAULT_ADVERSARIAL_NODES: u64 = 100_000;
+
+/// Scramble a board by searching for the farthest board from the goal within a budget.
+/// * Best-first search on the negated heuristic, skipping boards already expanded at
+///   the same or a smaller depth, as in A*. Boards at the budget aren't expanded.
+/// * Produces much harder boards than a random walk of the same length.
+/// * Only `heuristic` and `max_nodes` of the options are used; the search stops after
+///   `DEFAULT_ADVERSARIAL_NODES` expansions if no node limit is set.
+///
+/// # Parameters
+/// * `board` - Board to scramble
+/// * `budget` - Maximum number of moves
+/// * `options` - Options controlling the search
+///
+/// # Returns
+/// The board with the largest heuristic found, costed against the solved board, and
+/// the moves reaching it (the shortest such moves found, if several boards tie)
+pub fn scramble_adversarial(board: &Board, budget: usize, options: &SolveOptions) -> (Board, String) {
+    let solved_board = Board::new(board.size(), None, -1, None);
+    let max_nodes = options.max_nodes.unwrap_or(DEFAULT_ADVERSARIAL_NODES);
+    let heuristic = |board: &mut Board| board.store_h(&solved_board, options.heuristic);
+    let mut root = board.clone();
+    root.path.clear();
+    root.depth = 0;
+    let root_h = heuristic(&mut root);
+    // Costs are negated heuristics, so the heap pops the farthest board first
+    root.cost = -root_h;
+    let mut best = (root_h, root.clone());
+    let mut leaves = BinaryHeap::new();
+    leaves.push(root);
+    let mut closed: HashMap<Vec<Tile>, i32> = HashMap::new();
+    let mut expanded = 0;
+    while let Some(leaf) = leaves.pop() {
+        if closed.get(leaf.tiles()).is_some_and(|&depth| depth <= leaf.depth) {
+            continue;
+        }
+        let h = -leaf.cost;
+        if h > best.0 || (h == best.0 && leaf.depth < best.1.depth) {
+            best = (h, leaf.clone());
+        }
+        if leaf.depth as usize >= budget || expanded >= max_nodes {
+            continue;
+        }
+        closed.insert(leaf.tiles().to_vec(), leaf.depth);
+        expanded += 1;
+        for tile_move in leaf.legal_moves() {
+            if _is_back_step(&leaf, tile_move) {
+                continue;
+            }
+            let mut child = leaf.clone();
+            child.path.push(tile_move);
+            child.move_blank_tile(tile_move);
+            child.depth = leaf.depth + 1;
+            child.cost = -heuristic(&mut child);
+            leaves.push(child);
+        }
+    }
+    let mut farthest = best.1;
+    let path = std::mem::take(&mut farthest.path);
+    farthest.depth = 0;
+    farthest.cost = farthest.g() + farthest.store_h(&solved_board, Heuristic::LinearConflicts);
+    (farthest, path)
+}
+
+/// Find the lexicographically smallest solution of a known optimal length.
+///
+/// # Parameters
+/// * `board` - The board to solve
+/// * `optimal_length` - Length of an optimal solution
+/// * `move_order` - Ordering of moves used for the lexicographic comparison
+///
+/// # Returns
+/// The smallest optimal solution
+pub fn canonical_moves(board: &Board, optimal_length: usize, move_order: &[char]) -> String {
+    let mut moves = String::new();
+    // The first solution found in move order is the lexicographically smallest
+    _for_each_solution_of_length(board, optimal_length as i32, move_order, &mut |path: &str| {
+        moves = path.to_string();
+        false
+    });
+    moves
+}
+
+/// Count the optimal solutions of a board.
+/// * Distinct move sequences (paths) are counted, not distinct states; two solutions
+///   passing through the same intermediate board by different routes both count.
+///
+/// # Parameters
+/// * `board` - The board to analyze
+/// * `limit` - Stop counting once this many solutions have been found
+///
+/// # Returns
+/// Number of optimal solutions, capped at `limit`
+pub fn count_optimal_solutions(board: &Board, limit: u64) -> u64 {
+    let mut count = 0;
+    for_each_optimal_solution(board, &mut |_path: &str| {
+        count += 1;
+        count < limit
+    });
+    count
+}
+
+/// Enumerate the optimal solutions of a board.
+/// * Like `count_optimal_solutions`, solutions are distinct move sequences.
+///
+/// # Parameters
+/// * `board` - The board to analyze
+/// * `k` - Maximum number of solutions to return
+///
+/// # Returns
+/// Up to `k` optimal solution move strings, in depth-first move order
+pub fn enumerate_optimal_solutions(board: &Board, k: usize) -> Vec<String> {
+    let mut solutions: Vec<String> = Vec::new();
+    if k == 0 {
+        return solutions;
+    }
+    for_each_optimal_solution(board, &mut |path: &str| {
+        solutions.push(path.to_string());
+        solutions.len() < k
+    });
+    solutions
+}
+
+/// Visit each optimal solution of a board.
+/// * The optimal length is found with `solve_length`, with the options `for_size`
+///   picks for the board, then a depth-limited DFS visits every path of that length,
+///   pruning any board whose cost (g + h) exceeds it.
+///
+/// # Parameters
+/// * `board` - The board to analyze
+/// * `visit` - Called with each solution; returning false stops the search
+fn for_each_optimal_solution<F: FnMut(&str) -> bool>(board: &Board, visit: &mut F) {
+    // Unreachable boards have no solutions to visit
+    let optimal_length = match solve_length(board, &SolveOptions::for_size(board.size())) {
+        Ok(length) => length as i32,
+        Err(_) => return,
+    };
+    _for_each_solution_of_length(board, optimal_length, &MOVES, visit);
+}
+
+/// Visit each solution of a board no longer than a bound.
+///
+/// # Parameters
+/// * `board` - The board to analyze
+/// * `bound` - Maximum solution length
+/// * `move_order` - Order in which moves are tried
+/// * `visit` - Called with each solution; returning false stops the search
+fn _for_each_solution_of_length<F: FnMut(&str) -> bool>(board: &Board, bound: i32,
+                                                         move_order: &[char], visit: &mut F) {
+    let solved_board = Board::new(board.size(), None, -1, None);
+    let mut root = board.clone();
+    root.path.clear();
+    root.depth = 0;
+    root.cost = root.g() + root.store_h(&solved_board, Heuristic::LinearConflicts);
+    _optimal_solutions_dfs(&root, &solved_board, bound, move_order, visit);
+}
+
+/// Depth-limited DFS over paths no longer than the optimal length.
+///
+/// # Parameters
+/// * `board` - Current board
+/// * `solved_board` - Solved version of the board
+/// * `bound` - Optimal solution length
+/// * `move_order` - Order in which moves are tried
+/// * `visit` - Called with each solution; returning false stops the search
+///
+/// # Returns
+/// Whether the search should continue
+fn _optimal_solutions_dfs<F: FnMut(&str) -> bool>(board: &Board, solved_board: &Board,
+                                                   bound: i32, move_order: &[char],
+                                                   visit: &mut F) -> bool {
+    if board.cost > bound {
+        return true;
+    }
+    if board.is_solved() {
+        return visit(&board.path);
+    }
+    for &tile_move in move_order {
+        if !board.is_legal_move(tile_move) || _is_back_step(board, tile_move) {
+            continue;
+        }
+        let mut new_board = board.clone();
+        new_board.path.push(tile_move);
+        new_board.move_blank_tile(tile_move);
+        new_board.depth = board.depth + 1;
+        new_board.cost = new_board.g() + new_board.store_h(solved_board, Heuristic::LinearConflicts);
+        if !_optimal_solutions_dfs(&new_board, solved_board, bound, move_order, visit) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Check if a move would undo the move that led to a board during search.
+///
+/// # Parameters
+/// * `board` - Board being expanded
+/// * `tile_move` - Move to check
+///
+/// # Returns
+/// Whether the move reverses the last move on the board's path
+fn _is_back_step(board: &Board, tile_move: char) -> bool {
+    board.path.ends_with(opposite_direction(tile_move))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+    use std::convert::TryFrom;
+    use std::hash::BuildHasherDefault;
+
+    /// Count solutions of exactly `length` moves by trying every move sequence.
+    fn brute_force_count(board: &Board, solved_board: &Board, length: usize) -> u64 {
+        if length == 0 {
+            return if board == solved_board { 1 } else { 0 };
+        }
+        let mut total = 0;
+        for &tile_move in &['U', 'D', 'L', 'R'] {
+            let mut new_board = board.clone();
+            if !new_board.is_legal_move(tile_move) {
+                continue;
+            }
+            new_board.move_blank_tile(tile_move);
+            total += brute_force_count(&new_board, solved_board, length - 1);
+        }
+        total
+    }
+
+    /// Test effective branching factor solver
+    #[test]
+    fn test_effective_branching_factor() {
+        let ebf = SolveStats::effective_branching_factor(15, 3);
+        assert!((ebf - 2.0).abs() < 1e-6);
+        assert_eq!(SolveStats::effective_branching_factor(15, 0), 0.0);
+    }
+
+    /// Test solve stats
+    /// - Ensure the depth histogram sums to the total expansions
+    #[test]
+    fn test_solve_stats() {
+        let tiles = vec![
+            Tile::new(1), Tile::new(2), Tile::new(3),
+            Tile::new(BLANK_TILE), Tile::new(4), Tile::new(6),
+            Tile::new(7), Tile::new(5), Tile::new(8)
+        ];
+        let board = Board::new(3, None, 0, Some(tiles.into_boxed_slice()));
+        let solution = solve(&board, &SolveOptions::default()).unwrap();
+        assert_eq!(solution.moves, "RDR");
+        let stats = &solution.stats;
+        assert!(stats.nodes_expanded > 0);
+        assert_eq!(stats.expansions_by_depth.iter().sum::<u64>(), stats.nodes_expanded);
+        assert_eq!(stats.expansions_by_depth[0], 1);
+        assert!(stats.max_heuristic >= 3);
+        assert!(stats.effective_branching_factor > 0.0);
+    }
+
+    /// Test counting optimal solutions one move from solved
+    #[test]
+    fn test_count_optimal_solutions_one_move() {
+        let solved_board = Board::new(3, None, -1, None);
+        let mut board = solved_board.clone();
+        board.move_blank_tile('U');
+        assert_eq!(count_optimal_solutions(&board, 100), 1);
+        assert_eq!(enumerate_optimal_solutions(&board, 10), ["D"]);
+    }
+
+    /// Test a board whose only optimal first move reverses the last shuffle move
+    /// - Move history from shuffling must not prune the search at the root
+    #[test]
+    fn test_solve_first_move_reverses_shuffle() {
+        let mut board = Board::new(3, None, 0, None);
+        for tile_move in "LLUR".chars() {
+            board.move_blank_tile(tile_move);
+        }
+        assert_eq!(solve(&board, &SolveOptions::default()).unwrap().moves, "LDRR");
+        assert_eq!(canonical_moves(&board, 4, &MOVES), "LDRR");
+        assert_eq!(enumerate_optimal_solutions(&board, 10), ["LDRR"]);
+    }
+
+    /// Test counting and enumerating optimal solutions against brute force
+    /// - The bottom-right 2x2 block is rotated, so it can be solved in either direction
+    #[test]
+    fn test_count_optimal_solutions_brute_force() {
+        let solved_board = Board::new(3, None, -1, None);
+        let mut board = solved_board.clone();
+        for tile_move in "LURDLU".chars() {
+            board.move_blank_tile(tile_move);
+        }
+        let count = count_optimal_solutions(&board, 1000);
+        assert_eq!(count, brute_force_count(&board, &solved_board, 6));
+        assert_eq!(count, 2);
+        // Limit caps the count
+        assert_eq!(count_optimal_solutions(&board, 1), 1);
+        // Enumerated solutions are distinct and all solve the board
+        let solutions = enumerate_optimal_solutions(&board, 100);
+        assert_eq!(solutions, ["DRULDR", "RDLURD"]);
+        assert_eq!(enumerate_optimal_solutions(&board, 1).len(), 1);
+        for (i, path) in solutions.iter().enumerate() {
+            assert_eq!(path.len(), 6);
+            assert!(!solutions[i + 1..].contains(path));
+            let mut replay = board.clone();
+            for tile_move in path.chars() {
+                replay.move_blank_tile(tile_move);
+            }
+            assert_eq!(replay, solved_board);
+        }
+        // 4x4 boards are bounded with the IDA* length rather than an A* solve
+        let board_4x4 = Board::generate(4, 325, 40).unwrap();
+        let length = solve_length(&board_4x4, &SolveOptions::for_size(4)).unwrap() as usize;
+        let solutions_4x4 = enumerate_optimal_solutions(&board_4x4, 5);
+        assert!(!solutions_4x4.is_empty());
+        assert!(solutions_4x4.iter().all(|path| path.len() == length), "{:?}", solutions_4x4);
+    }
+
+    /// Test canonical solve picks the lexicographically smallest optimal solution
+    #[test]
+    fn test_solve_canonical() {
+        let solved_board = Board::new(3, None, -1, None);
+        let mut board = solved_board.clone();
+        for tile_move in "LURDLU".chars() {
+            board.move_blank_tile(tile_move);
+        }
+        let solutions = enumerate_optimal_solutions(&board, 100);
+        assert!(solutions.len() > 1);
+        let options = SolveOptions::new().canonical(true);
+        let solution = solve(&board, &options).unwrap();
+        // Smallest under D < L < R < U
+        let rank = |path: &String| -> Vec<usize> {
+            path.chars().map(|c| CANONICAL_MOVE_ORDER.iter().position(|&m| m == c).unwrap()).collect()
+        };
+        let smallest = solutions.iter().min_by_key(|path| rank(path)).unwrap();
+        assert_eq!(&solution.moves, smallest);
+        assert_eq!(solution.moves, "DRULDR");
+        // Configurable ordering
+        let options_2 = options.move_order([RIGHT, DOWN, LEFT, UP]);
+        let solution_2 = solve(&board, &options_2).unwrap();
+        assert_eq!(solution_2.moves, "RDLURD");
+    }
+
+    /// Test the default options match the plain A* solver
+    #[test]
+    fn test_solve_options_default() {
+        let options = SolveOptions::new();
+        assert_eq!(options, SolveOptions::default());
+        assert_eq!(options.algorithm, Algorithm::AStar);
+        assert_eq!(options.heuristic, Heuristic::LinearConflicts);
+        assert!(!options.canonical);
+        assert_eq!(options.max_nodes, None);
+        let mut board = Board::new(3, None, 0, None);
+        for tile_move in "LURDLULDRU".chars() {
+            board.move_blank_tile(tile_move);
+        }
+        let mut solver = Solver::new(3, board.tiles().to_vec());
+        let expected = solver.step(u64::MAX).unwrap().unwrap();
+        assert_eq!(solve(&board, &options), Ok(expected));
+    }
+
+    /// Test the other algorithms and heuristics find solutions of the same length
+    #[test]
+    fn test_solve_options_algorithms() {
+        let mut board = Board::new(3, None, 0, None);
+        for tile_move in "LURDLULDRU".chars() {
+            board.move_blank_tile(tile_move);
+        }
+        let optimal = solve(&board, &SolveOptions::new()).unwrap().moves;
+        let options = [
+            SolveOptions::new().heuristic(Heuristic::Manhattan),
+            SolveOptions::new().algorithm(Algorithm::IdaStar),
+            SolveOptions::new().algorithm(Algorithm::IdaStar).heuristic(Heuristic::Manhattan),
+        ];
+        for options in &options {
+            let solution = solve(&board, options).unwrap();
+            assert_eq!(solution.moves.len(), optimal.len());
+            assert!(solution.stats.nodes_expanded > 0);
+        }
+        // Canonical solutions don't depend on the algorithm
+        let canonical = SolveOptions::new().canonical(true);
+        assert_eq!(
+            solve(&board, &canonical.clone().algorithm(Algorithm::IdaStar)).unwrap().moves,
+            solve(&board, &canonical).unwrap().moves
+        );
+    }
+
+    /// Test hints that solve the board, start it off, or can't be used at all
+    #[test]
+    fn test_solve_with_hint() {
+        let options = SolveOptions::new();
+        // Verbatim, known to be optimal when as long as the heuristic
+        let board = crate::try_parse_board_info("3,1,2,3,-1,4,6,7,5,8").unwrap();
+        let hinted = solve_with_hint(&board, "r, d, r", &options).unwrap();
+        assert_eq!((hinted.moves.as_str(), hinted.optimal), ("RDR", true));
+        assert_eq!((hinted.stats.hint_usage, hinted.stats.nodes_expanded), (Some(HintUsage::Verbatim), 0));
+        assert!(hinted.stats.to_json().ends_with(",\"hint_usage\":\"verbatim\"}"));
+        let detour = solve_with_hint(&board, "UDRDR", &options).unwrap();
+        assert_eq!((detour.moves.as_str(), detour.optimal), ("UDRDR", false));
+        // Partial: the first half of an optimal solution, then a wrong turn
+        let board = Board::generate(3, 397, 60).unwrap();
+        let unhinted = solve(&board, &options).unwrap();
+        assert_eq!(unhinted.stats.hint_usage, None);
+        assert!(!unhinted.stats.to_json().contains("hint_usage"));
+        let half = &unhinted.moves[..unhinted.moves.len() / 2];
+        let wrong_turn = board.apply_moves(half, Notation::BlankMoves).unwrap().legal_moves().into_iter()
+            .find(|&tile_move| !unhinted.moves[half.len()..].starts_with(tile_move))
+            .unwrap();
+        let hint = format!("{}{}", half, wrong_turn);
+        let partial = solve_with_hint(&board, &hint, &options).unwrap();
+        assert_eq!(partial.stats.hint_usage, Some(HintUsage::Partial));
+        assert_eq!(partial.moves.len(), unhinted.moves.len());
+        assert!(partial.optimal && verify_solution(&board, &partial.moves, Notation::BlankMoves));
+        assert!(partial.stats.nodes_expanded <= unhinted.stats.nodes_expanded);
+        let greedy = solve_with_hint(&board, &hint, &SolveOptions::new().algorithm(Algorithm::Greedy)).unwrap();
+        assert_eq!(greedy.stats.hint_usage, Some(HintUsage::Partial));
+        assert!(verify_solution(&board, &greedy.moves, Notation::BlankMoves));
+        // Discarded: unreadable, illegal from the start, or nothing to seed
+        let illegal = board.legal_moves();
+        let illegal = MOVES.iter().find(|tile_move| !illegal.contains(tile_move)).unwrap().to_string();
+        for hint in &["x?", illegal.as_str()] {
+            let discarded = solve_with_hint(&board, hint, &options).unwrap();
+            assert_eq!(discarded.stats.hint_usage, Some(HintUsage::Discarded));
+            assert_eq!(discarded.moves, unhinted.moves);
+        }
+        let ida_star = solve_with_hint(&board, half, &SolveOptions::new().algorithm(Algorithm::IdaStar)).unwrap();
+        assert_eq!(ida_star.stats.hint_usage, Some(HintUsage::Discarded));
+        assert_eq!(ida_star.moves.len(), unhinted.moves.len());
+        // Canonical solutions don't depend on the hint
+        let canonical = SolveOptions::new().canonical(true);
+        assert_eq!(solve_with_hint(&board, &hint, &canonical).unwrap().moves, solve(&board, &canonical).unwrap().moves);
+    }
+
+    /// Test scrambled boards solve back to each goal preset
+    #[test]
+    fn test_solve_to_preset() {
+        for &(n, algorithm) in &[(3, Algorithm::AStar), (4, Algorithm::IdaStar)] {
+            let options = SolveOptions::new().algorithm(algorithm);
+            for &preset in &GOAL_PRESETS {
+                let mut board = Board::solved_with_preset(n, preset);
+                let scramble = board.shuffle_with_rng(30, &mut crate::rng::RngSource::seeded(390));
+                let solution = solve_to_preset(&board, preset, &options).unwrap();
+                assert!(solution.moves.len() <= scramble.len());
+                let solved = board.apply_moves(&solution.moves, Notation::BlankMoves).unwrap();
+                assert!(solved.is_solved_with_preset(preset), "{} {:?}", n, preset);
+            }
+        }
+        let snake = Board::solved_with_preset(3, GoalPreset::Snake);
+        assert!(solve_to_preset(&snake, GoalPreset::Snake, &SolveOptions::new()).unwrap().moves.is_empty());
+        // The standard goal can't reach the blank-first goal on a 4x4
+        let standard = Board::new(4, None, 0, None);
+        let unreachable = solve_to_preset(&standard, GoalPreset::BlankFirst, &SolveOptions::new());
+        assert_eq!(unreachable, Err(SolveError::Unreachable));
+    }
+
+    /// Test solving between boards towards goals with the blank in every cell
+    #[test]
+    fn test_solve_between() {
+        let mut rng = StdRng::seed_from_u64(407);
+        for blank in 0..9 {
+            let detour: String = std::iter::repeat_n(UP, 2 - blank / 3)
+                .chain(std::iter::repeat_n(LEFT, 2 - blank % 3))
+                .collect();
+            let detoured = Board::new(3, None, 0, None).apply_moves(&detour, Notation::BlankMoves).unwrap();
+            // Relabel the tiles at random, keeping the blank in place
+            let mut symbols: Vec<i32> = (1..9).collect();
+            symbols.shuffle(&mut rng);
+            let tiles: Vec<Tile> = detoured.tiles().iter()
+                .map(|tile| if tile.is_blank() { *tile } else { Tile::new(symbols[tile.symbol() as usize - 1]) })
+                .collect();
+            let goal = Board::try_new(3, tiles).unwrap();
+            assert_eq!(goal.get_blank_index() as usize, blank);
+            let mut start = goal.clone();
+            start.shuffle_with_rng(30, &mut rng);
+            let solution = solve_between(&start, &goal, &SolveOptions::default()).unwrap();
+            assert_eq!(start.apply_moves(&solution.moves, Notation::BlankMoves).unwrap().tiles(), goal.tiles());
+            assert_eq!(solution.moves.len() % 2, start.move_count_parity(&goal));
+            // Only goals with the blank in a corner are solved optimally
+            assert_eq!(solution.optimal, blank % 2 == 0 && blank != 4, "{}", blank);
+        }
+        let goal = Board::new(3, None, 0, None);
+        let swapped: Vec<Tile> = [2, 1, 3, 4, 5, 6, 7, 8, BLANK_TILE].iter().map(|&symbol| Tile::new(symbol)).collect();
+        let swapped = Board::try_new(3, swapped).unwrap();
+        assert_eq!(solve_between(&swapped, &goal, &SolveOptions::default()), Err(SolveError::Unreachable));
+        assert!(solve_between(&goal, &goal, &SolveOptions::default()).unwrap().moves.is_empty());
+    }
+
+    /// Test scrambles replayed from the solved board land exactly on their targets
+    #[test]
+    fn test_scramble_to() {
+        let mut rng = StdRng::seed_from_u64(412);
+        for n in 2..=4 {
+            let solved = Board::new(n, None, 0, None);
+            for _ in 0..10 {
+                let mut target = solved.clone();
+                target.shuffle_with_rng(if n == 4 { 30 } else { 200 }, &mut rng);
+                let scramble = scramble_to(&target).unwrap();
+                let replayed = solved.apply_moves(&scramble, Notation::BlankMoves).unwrap();
+                assert_eq!(replayed.tiles(), target.tiles(), "{}", scramble);
+            }
+        }
+        let swapped: Vec<Tile> = [2, 1, 3, 4, 5, 6, 7, 8, BLANK_TILE].iter().map(|&symbol| Tile::new(symbol)).collect();
+        assert_eq!(scramble_to(&Board::try_new(3, swapped).unwrap()), Err(SolveError::Unreachable));
+        assert_eq!(scramble_to(&Board::new(3, None, 0, None)), Ok(String::new()));
+    }
+
+    /// Test size-aware options solve a 24-puzzle and report what is guaranteed
+    #[test]
+    fn test_solve_options_for_size() {
+        assert_eq!(SolveOptions::for_size(3), SolveOptions::default());
+        assert_eq!(SolveOptions::for_size(4).algorithm, Algorithm::IdaStar);
+        assert_eq!(SolveOptions::for_size(5).algorithm, Algorithm::Layered);
+        let board = Board::generate(3, 382, 30).unwrap();
+        assert!(solve(&board, &SolveOptions::for_size(3)).unwrap().optimal);
+        let board = Board::generate(4, 382, 40).unwrap();
+        assert!(solve(&board, &SolveOptions::for_size(4)).unwrap().optimal);
+        let board = Board::generate(5, 382, 500).unwrap();
+        let solution = solve(&board, &SolveOptions::for_size(5).max_nodes(1_000_000)).unwrap();
+        assert!(!solution.optimal);
+        assert!(verify_solution(&board, &solution.moves, Notation::BlankMoves));
+        assert!(solution.to_json().ends_with(",\"optimal\":false}"));
+    }
+
+    /// Test invalid options and node limits are reported
+    #[test]
+    fn test_solve_options_invalid() {
+        let board = Board::create_game_board(3, 30);
+        let repeated = SolveOptions::new().move_order([UP, UP, LEFT, RIGHT]);
+        assert_eq!(
+            solve(&board, &repeated),
+            Err(SolveError::InvalidOptions(
+                "move order \"UULR\" must contain each of U, D, L and R once".to_string()
+            ))
+        );
+        assert!(SolveOptions::new().max_nodes(0).validate().is_err());
+        for (algorithm, name) in &[(Algorithm::Layered, "layered"), (Algorithm::Greedy, "greedy")] {
+            let canonical = SolveOptions::new().algorithm(*algorithm).canonical(true);
+            let reason = format!("{} solutions can't be made canonical", name);
+            assert_eq!(canonical.validate(), Err(SolveError::InvalidOptions(reason.clone())));
+            assert_eq!(solve(&board, &canonical), Err(SolveError::InvalidOptions(reason)));
+        }
+        assert_eq!(SolveOptions::new().algorithm(Algorithm::IdaStar).canonical(true).validate(), Ok(()));
+        let mut board = Board::new(3, None, 0, None);
+        for tile_move in "LURDLULDRU".chars() {
+            board.move_blank_tile(tile_move);
+        }
+        for algorithm in &[Algorithm::AStar, Algorithm::IdaStar, Algorithm::Greedy] {
+            let limited = SolveOptions::new().algorithm(*algorithm).max_nodes(1);
+            let error = solve(&board, &limited).unwrap_err();
+            assert_eq!((error.code(), error.to_string()), (203, "gave up after expanding 1 boards".to_string()));
+        }
+    }
+
+    /// Test searches that give up report a best board reached by its path and a sound bound
+    #[test]
+    fn test_budget_exceeded_partial() {
+        let board = Board::generate(3, 383, 200).unwrap();
+        let optimal = solve_length(&board, &SolveOptions::new().algorithm(Algorithm::IdaStar)).unwrap() as i32;
+        let heuristic = |board: &Board| {
+            let breakdown = board.heuristic_breakdown(None);
+            breakdown.manhattan + breakdown.linear_conflicts
+        };
+        let initial = heuristic(&board);
+        let options = [
+            SolveOptions::new(),
+            SolveOptions::new().algorithm(Algorithm::Greedy),
+            SolveOptions::new().algorithm(Algorithm::IdaStar),
+        ];
+        for options in &options {
+            let partial = match solve(&board, &options.clone().max_nodes(20)) {
+                Err(SolveError::BudgetExceeded(partial)) => partial,
+                other => panic!("expected the budget to run out, got {:?}", other),
+            };
+            assert_eq!(partial.stats.nodes_expanded, 20);
+            assert_eq!(board.apply_moves(&partial.best_path, Notation::BlankMoves), Ok(partial.best_board.clone()));
+            assert_eq!(partial.best_heuristic, heuristic(&partial.best_board));
+            assert!(partial.best_heuristic < initial);
+            assert!(initial <= partial.proven_lower_bound && partial.proven_lower_bound <= optimal);
+        }
+        let partial = match solve_length(&board, &SolveOptions::new().max_nodes(20)) {
+            Err(SolveError::BudgetExceeded(partial)) => partial,
+            other => panic!("expected the budget to run out, got {:?}", other),
+        };
+        assert_eq!(board.apply_moves(&partial.best_path, Notation::BlankMoves), Ok(partial.best_board.clone()));
+        assert!(partial.to_json().starts_with(&format!(
+            "{{\"best_board\":\"{}\",\"best_path\":\"{}\",",
+            partial.best_board.to_compact_string(),
+            partial.best_path
+        )));
+    }
+
+    /// Test injected failures can't be told apart from genuine ones
+    #[cfg(feature = "chaos")]
+    #[test]
+    fn test_injected_failures() {
+        let cases = [
+            (Board::generate(3, 394, 200).unwrap(), Algorithm::AStar),
+            (Board::generate(3, 394, 200).unwrap(), Algorithm::Greedy),
+            (Board::generate(4, 394, 200).unwrap(), Algorithm::IdaStar),
+            (Board::generate(6, 394, 500).unwrap(), Algorithm::Layered),
+        ];
+        for (board, algorithm) in &cases {
+            let options = SolveOptions::new().algorithm(*algorithm);
+            let genuine = solve(board, &options.clone().max_nodes(20));
+            assert!(matches!(genuine, Err(SolveError::BudgetExceeded(_))), "{:?}", algorithm);
+            let injected = solve(board, &options.clone().fail_after_nodes(Some(20)));
+            assert_eq!(injected, genuine, "{:?}", algorithm);
+            let coded = |result: Result<Solution, SolveError>| {
+                let coded = crate::error_code::CodedError::from_error(&result.unwrap_err());
+                // Wall times differ between runs
+                let details = coded.details.map(|details| details.split(",\"millis\"").next().unwrap().to_string());
+                (coded.code, coded.message, details)
+            };
+            assert_eq!(coded(injected), coded(genuine));
+            // A genuine budget reached first wins over the injected failure
+            let earlier = options.clone().max_nodes(10).fail_after_nodes(Some(20)).fail_with(SolveErrorKind::TooLarge);
+            assert_eq!(solve(board, &earlier), solve(board, &options.clone().max_nodes(10)));
+            for &kind in &SOLVE_ERROR_KINDS {
+                let error = solve(board, &options.clone().fail_after_nodes(Some(20)).fail_with(kind)).unwrap_err();
+                assert_eq!(error.kind(), kind);
+                assert_eq!(error.details().is_some(), kind == SolveErrorKind::BudgetExceeded);
+            }
+        }
+        let board = &cases[0].0;
+        let injected = solve_length(board, &SolveOptions::new().fail_after_nodes(Some(20)));
+        assert_eq!(injected, solve_length(board, &SolveOptions::new().max_nodes(20)));
+        // Boards solved before the failure is due are unaffected
+        let easy = Board::generate(3, 394, 4).unwrap();
+        let options = SolveOptions::new().fail_after_nodes(Some(1000)).fail_with(SolveErrorKind::Unreachable);
+        assert_eq!(solve(&easy, &options), solve(&easy, &SolveOptions::new()));
+    }
+
+    /// Test the maximum of heuristics is admissible and solves optimally, and on Korf's
+    /// instances expands no more boards than the better single heuristic
+    #[test]
+    fn test_solve_heuristic_max() {
+        let max = Heuristic::parse("linear_conflicts+inversion_distance").unwrap();
+        let oracle = crate::exact::DistanceTable::new(3);
+        let boards: Vec<Board> = (0..200).map(|seed| Board::generate(3, 404 + seed, 60).unwrap()).collect();
+        let estimate = crate::debug_checks::heuristic_fn(max);
+        assert_eq!(crate::debug_checks::check_admissible(estimate, &boards, &oracle), Ok(()));
+        for board in &boards[..20] {
+            for &algorithm in &[Algorithm::AStar, Algorithm::IdaStar] {
+                let solution = solve(board, &SolveOptions::new().algorithm(algorithm).heuristic(max)).unwrap();
+                assert_eq!(Some(solution.moves.len()), oracle.distance(board), "{}", board);
+            }
+        }
+
+        // Instances whose IDA* searches are short enough for a debug build. The singles
+        // are only searched up to the maximum's node count, which is all the comparison needs.
+        let instances = crate::benchmarks::korf_instances();
+        let lengths = crate::benchmarks::korf_optimal_lengths();
+        for &i in &[54, 78] {
+            let options = SolveOptions::new().algorithm(Algorithm::IdaStar);
+            let solution = solve(&instances[i], &options.clone().heuristic(max)).unwrap();
+            assert_eq!(solution.moves.len(), lengths[i]);
+            let nodes = solution.stats.nodes_expanded;
+            for &heuristic in HEURISTICS.iter() {
+                let single = solve(&instances[i], &options.clone().heuristic(heuristic).max_nodes(nodes));
+                let single_nodes = single.map_or(nodes, |solution| solution.stats.nodes_expanded);
+                assert!(single_nodes >= nodes, "{} on instance {}", heuristic, i);
+            }
+        }
+    }
+
+    /// Test searches over their memory budget fail with their structures freed, and
+    /// later solves are unaffected
+    #[test]
+    fn test_memory_budget() {
+        let board = Board::generate(3, 399, 200).unwrap();
+        for &algorithm in &[Algorithm::AStar, Algorithm::Greedy] {
+            let options = SolveOptions::new().algorithm(algorithm).max_memory_bytes(20_000);
+            let error = solve(&board, &options).unwrap_err();
+            assert!(matches!(error, SolveError::MemoryBudget(_)), "{:?}", error);
+            assert_eq!(error.code(), 205);
+            assert!(error.to_string().ends_with("over the budget of 20000"), "{}", error);
+            let mut solver = Solver::with_options(3, board.tiles().to_vec(), &options);
+            let stopped = loop {
+                match solver.step(10) {
+                    Ok(None) => continue,
+                    other => break other,
+                }
+            };
+            assert_eq!(stopped, Err(error));
+            assert_eq!(solver.frontier_len(), 0);
+            assert_eq!(solver.step(10), stopped);
+            // Easy boards fit in the same budget
+            let easy = Board::generate(3, 399, 6).unwrap();
+            assert!(solve(&easy, &options).is_ok());
+        }
+        let unlimited = solve(&board, &SolveOptions::new()).unwrap();
+        assert!(verify_solution(&board, &unlimited.moves, Notation::BlankMoves));
+        assert!(SolveOptions::new().max_memory_bytes(0).validate().is_err());
+        assert_eq!(SolveOptions::new().max_memory_bytes, default_memory_budget());
+    }
+
+    /// Test visited sets keyed on Zobrist hashes find the same solutions while hashing far
+    /// fewer key bytes on large boards, and their snapshots still resume
+    #[test]
+    fn test_zobrist_visited_set() {
+        let board = Board::generate(5, 417, 80).unwrap();
+        let greedy = SolveOptions::new().algorithm(Algorithm::Greedy);
+        let tiles = solve(&board, &greedy).unwrap();
+        let zobrist = solve(&board, &greedy.clone().zobrist_seed(417)).unwrap();
+        assert_eq!(zobrist.moves, tiles.moves);
+        assert_eq!(zobrist.stats.nodes_expanded, tiles.stats.nodes_expanded);
+        assert!(
+            zobrist.stats.visited_key_bytes * 10 < tiles.stats.visited_key_bytes,
+            "{} vs {} bytes", zobrist.stats.visited_key_bytes, tiles.stats.visited_key_bytes
+        );
+        let small = Board::generate(3, 417, 200).unwrap();
+        let options = SolveOptions::new().zobrist_seed(crate::zobrist::DEFAULT_ZOBRIST_SEED);
+        let optimal = solve_length(&small, &SolveOptions::new()).unwrap() as usize;
+        assert_eq!(solve(&small, &options).unwrap().moves.len(), optimal);
+        let mut solver = Solver::with_options(3, small.tiles().to_vec(), &options);
+        assert_eq!(solver.step(20), Ok(None));
+        let mut resumed = Solver::resume(&solver.serialize_state()).unwrap();
+        let solution = loop {
+            if let Some(solution) = resumed.step(1000).unwrap() {
+                break solution;
+            }
+        };
+        assert!(verify_solution(&small, &solution.moves, Notation::BlankMoves));
+        assert_eq!(solution.moves.len(), optimal);
+    }
+
+    /// Test the closed set keys boards on their layout alone
+    #[test]
+    fn test_closed_set() {
+        for n in [3, 5] {
+            let board = Board::generate(n, 418, 30).unwrap();
+            let mut closed = ClosedSet::with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+            assert!(closed.is_empty());
+            assert!(closed.insert(&board));
+            // The same layout reached another way
+            let mut other = board.neighbor(board.legal_moves()[0]);
+            other = other.neighbor(opposite_direction(other.path.chars().last().unwrap()));
+            other.depth = board.depth + 2;
+            other.cost = board.cost + 7;
+            assert_ne!(other.path, board.path);
+            assert!(!closed.insert(&other));
+            assert!(closed.contains(&other));
+            let neighbors: Vec<Board> = board.legal_moves().into_iter().map(|m| board.neighbor(m)).collect();
+            for neighbor in &neighbors {
+                assert!(!closed.contains(neighbor));
+                assert!(closed.insert(neighbor));
+                assert!(!closed.insert(neighbor));
+            }
+            assert_eq!(closed.len(), neighbors.len() + 1);
+            assert!(closed.remove(&other));
+            assert!(!closed.contains(&board));
+            closed.clear();
+            assert_eq!(closed.len(), 0);
+        }
+        // Boards carrying a Zobrist table are keyed on their hash
+        let table = Arc::new(ZobristTable::new(3, 418));
+        let mut closed = ClosedSet::new();
+        assert!(closed.insert(&Board::new(3, None, 0, None).with_zobrist(table)));
+        assert!(!closed.insert(&Board::new(3, None, 0, None).with_zobrist(Arc::new(ZobristTable::new(3, 418)))));
+    }
+
+    /// Test paranoid searches fail with the typed error on heuristics that break their
+    /// checks, and solve as usual with the built-in ones
+    #[test]
+    fn test_solver_paranoid() {
+        let board = Board::generate(3, 422, 40).unwrap();
+        let solve_with = |estimate: fn(&Board, &Board) -> i32| {
+            let options = SolveOptions::new().heuristic(Heuristic::Manhattan).paranoid(true);
+            Solver::with_options(3, board.tiles().to_vec(), &options).estimate(estimate).step(u64::MAX)
+        };
+        // Drops from the Manhattan distance to 0 when the blank reaches the top left
+        let error = solve_with(|board, goal| if board.get_blank_index() == 0 { 0 } else { board.h(goal) }).unwrap_err();
+        assert_eq!((error.kind(), error.code()), (SolveErrorKind::HeuristicInconsistent, 206));
+        match &error {
+            SolveError::HeuristicInconsistent(violation) => match violation.as_ref() {
+                HeuristicViolation::Inconsistent { board, neighbour, estimate, neighbour_estimate } => {
+                    assert!(*estimate > 1 && *neighbour_estimate == 0);
+                    // The two boards are a move apart
+                    assert!(board.moved_tile(neighbour).is_some());
+                }
+                other => panic!("expected an inconsistent violation, got {:?}", other),
+            },
+            other => panic!("expected a heuristic error, got {:?}", other),
+        }
+        assert!(error.to_string().starts_with("heuristic failed a check, inconsistent: estimate changes from"));
+        // Consistent, but never 0, so the goal's cost isn't its depth
+        match solve_with(|board, goal| board.h(goal) + 1).unwrap_err() {
+            SolveError::HeuristicInconsistent(violation) => match *violation {
+                HeuristicViolation::Inadmissible { board, estimate: 1, distance: 0 } => assert!(board.is_solved()),
+                other => panic!("expected an inadmissible violation, got {:?}", other),
+            },
+            other => panic!("expected a heuristic error, got {:?}", other),
+        }
+        for &heuristic in &HEURISTICS {
+            let options = SolveOptions::new().heuristic(heuristic);
+            let paranoid = solve(&board, &options.clone().paranoid(true)).unwrap();
+            assert_eq!(paranoid.moves, solve(&board, &options).unwrap().moves);
+        }
+    }
+
+    /// Test paranoid IDA* searches, full and length-only, fail with the typed error on
+    /// heuristics that break their checks
+    #[test]
+    fn test_ida_star_paranoid() {
+        let board = Board::generate(3, 422, 40).unwrap();
+        let options = SolveOptions::new().algorithm(Algorithm::IdaStar).heuristic(Heuristic::Manhattan).paranoid(true);
+        // Drops from the Manhattan distance to 0 when the blank reaches its goal position
+        let inconsistent: fn(&Board, &Board) -> i32 =
+            |board, goal| if board.get_blank_index() == 8 { 0 } else { board.h(goal) };
+        // Consistent, but never 0, so the goal's cost isn't its depth
+        let inadmissible: fn(&Board, &Board) -> i32 = |board, goal| board.h(goal) + 1;
+        for &(estimate, expect_inconsistent) in &[(inconsistent, true), (inadmissible, false)] {
+            let (search, root) = IdaSearch::new(&board, &options, Some(estimate));
+            let solved = search.solve(&root, &mut NoopObserver).map(|solution| solution.moves.len());
+            let (search, mut root) = IdaSearch::new(&board, &options, Some(estimate));
+            let length = search.solve_length(&mut root).map(|(length, _stats)| length as usize);
+            for result in [solved, length] {
+                match result.unwrap_err() {
+                    SolveError::HeuristicInconsistent(violation) => match *violation {
+                        HeuristicViolation::Inconsistent { board, neighbour, estimate, neighbour_estimate } => {
+                            assert!(expect_inconsistent);
+                            assert!(estimate > 1 && neighbour_estimate == 0);
+                            assert!(board.moved_tile(&neighbour).is_some());
+                        }
+                        HeuristicViolation::Inadmissible { board, estimate: 1, distance: 0 } => {
+                            assert!(!expect_inconsistent);
+                            assert!(board.is_solved());
+                        }
+                        other => panic!("unexpected violation {:?}", other),
+                    },
+                    other => panic!("expected a heuristic error, got {:?}", other),
+                }
+            }
+        }
+        // IDA* is the default for 4x4, and solves as usual when paranoid
+        let board = Board::generate(4, 422, 40).unwrap();
+        let options = SolveOptions::for_size(4);
+        assert_eq!(options.algorithm, Algorithm::IdaStar);
+        let paranoid = solve(&board, &options.clone().paranoid(true)).unwrap();
+        assert_eq!(paranoid.moves, solve(&board, &options).unwrap().moves);
+        assert_eq!(solve_length(&board, &options.paranoid(true)), Ok(paranoid.moves.len() as u32));
+    }
+
+    /// Test searches over their time budget give up within the clock's sampling accuracy
+    #[test]
+    fn test_time_budget() {
+        let board = &crate::benchmarks::korf_instances()[0];
+        for &algorithm in &[Algorithm::AStar, Algorithm::IdaStar] {
+            let options = SolveOptions::new().algorithm(algorithm).heuristic(Heuristic::Manhattan).max_time_ms(100);
+            let start = clock::now_ms();
+            let partial = match solve(board, &options) {
+                Err(SolveError::BudgetExceeded(partial)) => partial,
+                other => panic!("expected the time budget to run out, got {:?}", other),
+            };
+            let elapsed = clock::now_ms() - start;
+            assert!((100.0..150.0).contains(&elapsed), "{:?} gave up after {} ms", algorithm, elapsed);
+            assert!(partial.stats.millis >= 100.0 && partial.stats.millis <= elapsed);
+            assert!(partial.stats.nodes_expanded > 0);
+        }
+        let easy = Board::generate(3, 408, 20).unwrap();
+        let solution = solve(&easy, &SolveOptions::new().max_time_ms(1000)).unwrap();
+        assert!(solution.stats.millis >= 0.0);
+        assert!(SolveOptions::new().max_time_ms(0).validate().is_err());
+    }
+
+    /// Test greedy search stopping when the goal is generated, and that optimal
+    /// algorithms refuse to
+    #[test]
+    fn test_stop_on_generate() {
+        // Solved by the first move tried from the last board expanded
+        let board = Board::generate(4, 399, 80).unwrap();
+        let greedy = SolveOptions::new().algorithm(Algorithm::Greedy);
+        let expanded = solve(&board, &greedy).unwrap();
+        let generated = solve(&board, &greedy.clone().stop_on_generate(true)).unwrap();
+        assert!(verify_solution(&board, &generated.moves, Notation::BlankMoves));
+        // The goal would have been the next board expanded, so only generation is saved
+        assert_eq!(generated.stats.nodes_expanded, expanded.stats.nodes_expanded);
+        assert!(generated.stats.nodes_generated < expanded.stats.nodes_generated);
+        assert!(generated.moves.len() >= solve_length(&board, &SolveOptions::default()).unwrap() as usize);
+        for &algorithm in &[Algorithm::AStar, Algorithm::IdaStar] {
+            let options = SolveOptions::new().algorithm(algorithm).stop_on_generate(true);
+            assert_eq!(
+                solve(&board, &options),
+                Err(SolveError::InvalidOptions(format!(
+                    "stopping when the goal is generated would make {:?} solutions suboptimal",
+                    algorithm
+                )))
+            );
+        }
+        // Greedy searches resume as greedy searches
+        let mut solver = Solver::with_options(4, board.tiles().to_vec(), &greedy.stop_on_generate(true));
+        assert_eq!(solver.step(2), Ok(None));
+        let mut resumed = Solver::resume(&solver.serialize_state()).unwrap();
+        assert_eq!(resumed.step(u64::MAX).unwrap().map(|solution| solution.moves), Some(generated.moves));
+    }
+
+    /// Test move byte encoding round trip
+    #[test]
+    fn test_move_encoding() {
+        assert_eq!(encode_moves("UDLR"), Some(vec![0, 1, 2, 3]));
+        assert_eq!(encode_moves(""), Some(vec![]));
+        assert_eq!(decode_moves(&[3, 1, 3]), Some("RDR".to_string()));
+        assert_eq!(decode_moves(&encode_moves("DRULDR").unwrap()), Some("DRULDR".to_string()));
+        // Anything else isn't a move
+        assert_eq!(encode_moves("UDx"), None);
+        assert_eq!(encode_moves("u"), None);
+        assert_eq!(decode_moves(&[0, 4]), None);
+        assert_eq!(decode_moves(&[255]), None);
+    }
+
+    /// Test stepping the solver in chunks matches an uninterrupted solve
+    #[test]
+    fn test_solver_step() {
+        let solved_board = Board::new(3, None, -1, None);
+        let mut board = solved_board.clone();
+        for tile_move in "LURDLULDRU".chars() {
+            board.move_blank_tile(tile_move);
+        }
+        let expected = solve(&board, &SolveOptions::default()).unwrap();
+        let mut solver = Solver::new(3, board.tiles().to_vec());
+        let mut steps = 0;
+        let solution = loop {
+            steps += 1;
+            if let Some(solution) = solver.step(1).unwrap() {
+                break solution;
+            }
+            assert_eq!(solver.stats().nodes_expanded, steps);
+        };
+        assert_eq!(solution, expected);
+        // Solved solvers keep returning their solution
+        assert_eq!(solver.step(1), Ok(Some(expected)));
+    }
+
+    /// Test an unsolvable board ends the search once the frontier empties
+    /// - Tiles 1 and 2 are swapped, so the goal isn't among the 12 reachable 2x2 boards
+    #[test]
+    fn test_solve_unreachable() {
+        let tiles = vec![Tile::new(2), Tile::new(1), Tile::new(3), Tile::new(BLANK_TILE)];
+        let board = Board::new(2, None, 0, Some(tiles.clone().into_boxed_slice()));
+        let mut solver = Solver::new(2, tiles.clone());
+        assert_eq!(solver.step(u64::MAX), Err(SolveError::Unreachable));
+        assert_eq!(solver.stats().nodes_expanded, 12);
+        assert_eq!(solve(&board, &SolveOptions::default()), Err(SolveError::Unreachable));
+        let ida_star = SolveOptions::new().algorithm(Algorithm::IdaStar);
+        assert_eq!(solve(&board, &ida_star), Err(SolveError::Unreachable));
+        assert_eq!(SolveError::Unreachable.to_string(), "goal is unreachable from this board");
+        assert_eq!(count_optimal_solutions(&board, 10), 0);
+        // Larger boards are rejected up front instead of searched until the frontier empties
+        let swapped = Board::try_from(vec![2, 1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, -1]).unwrap();
+        for &algorithm in &[Algorithm::AStar, Algorithm::Greedy] {
+            let options = SolveOptions::new().algorithm(algorithm);
+            assert_eq!(solve(&swapped, &options), Err(SolveError::Unreachable));
+        }
+    }
+
+    /// Test solutions are optimal on boards at known distances
+    /// - Property check over random depths from 1 to 20, with a fixed seed
+    #[test]
+    fn test_solve_known_distances() {
+        let mut rng = StdRng::seed_from_u64(344);
+        for _case in 0..20 {
+            let depth = rng.gen_range(1, 21);
+            let (board, generated) = Board::random_walk_exact(3, depth, &mut rng).unwrap();
+            for algorithm in &[Algorithm::AStar, Algorithm::IdaStar] {
+                let options = SolveOptions::new().algorithm(*algorithm);
+                let solution = solve(&board, &options).unwrap();
+                assert_eq!(solution.moves.len(), depth, "{} via {:?}", board, algorithm);
+            }
+            assert_eq!(generated.len(), depth);
+        }
+    }
+
+    /// Test a solution formatted in each notation verifies in that notation
+    #[test]
+    fn test_solution_notation() {
+        let mut rng = StdRng::seed_from_u64(360);
+        for _ in 0..10 {
+            let mut board = Board::new(3, None, 0, None);
+            board.shuffle_with_rng(30, &mut rng);
+            let solution = solve(&board, &SolveOptions::default()).unwrap();
+            for &notation in &NOTATIONS {
+                let written = solution.format(notation);
+                assert!(verify_solution(&board, &written, notation));
+                assert_eq!(board.apply_moves(&written, notation), board.apply_moves(&solution.moves, Notation::BlankMoves));
+            }
+            if !solution.moves.is_empty() {
+                assert!(!verify_solution(&board, &solution.format(Notation::TileMoves), Notation::BlankMoves));
+            }
+        }
+    }
+
+    /// Test there is one metadata entry per move, with counts shrinking along the path
+    #[test]
+    fn test_move_metadata() {
+        let board = Board::generate(3, 375, 30).unwrap();
+        let solved_board = Board::new(3, None, -1, None);
+        for &algorithm in &[Algorithm::AStar, Algorithm::IdaStar] {
+            let options = SolveOptions::default().algorithm(algorithm).track_path_expansions(true);
+            let solution = solve(&board, &options).unwrap();
+            let metadata = solution.move_metadata(&board, options.heuristic);
+            assert_eq!(metadata.len(), solution.moves.len());
+            assert_eq!(metadata.last().unwrap().h, 0);
+            assert_eq!(metadata.iter().map(|meta| meta.blank_move).collect::<String>(), solution.moves);
+            assert!(metadata.windows(2).all(|pair| pair[0].nodes_expanded >= pair[1].nodes_expanded));
+            assert!(metadata[0].nodes_expanded > 0 && metadata[0].nodes_expanded <= solution.stats.nodes_expanded);
+            let json = solution.to_json_verbose(&board, options.heuristic);
+            assert!(json.contains(&metadata[0].to_json()), "{}", json);
+        }
+        // Without tracking the counts are 0
+        let solution = solve(&board, &SolveOptions::default()).unwrap();
+        assert!(solution.move_metadata(&board, Heuristic::default()).iter().all(|meta| meta.nodes_expanded == 0));
+        // A solved board has no moves
+        let solution = solve(&solved_board, &SolveOptions::default().track_path_expansions(true)).unwrap();
+        assert_eq!(solution.move_metadata(&solved_board, Heuristic::default()), vec![]);
+    }
+
+    /// Test compact solutions round trip and are validated by replaying
+    #[test]
+    fn test_solution_compact() {
+        let mut rng = StdRng::seed_from_u64(380);
+        for moves in 0..8 {
+            let mut board = Board::new(3, None, 0, None);
+            board.shuffle_with_rng(moves, &mut rng);
+            let solution = solve(&board, &SolveOptions::default()).unwrap();
+            let bytes = solution.encode_compact();
+            assert_eq!(bytes.len(), 5 + solution.moves.len().div_ceil(4));
+            assert_eq!(Solution::decode_compact(&bytes, &board).unwrap().moves, solution.moves);
+            let text = solution.encode_compact_text();
+            assert_eq!(Solution::decode_compact_text(&text, &board).unwrap().moves, solution.moves);
+        }
+        let board = Board::generate(4, 380, 60).unwrap();
+        let solution = solve(&board, &SolveOptions::default()).unwrap();
+        assert_eq!(solution.moves.len() % 4, 2);
+        let bytes = solution.encode_compact();
+        assert_eq!(&bytes[..5], &[COMPACT_VERSION, solution.moves.len() as u8, 0, 0, 0]);
+        assert_eq!(Solution::decode_compact(&bytes, &board).unwrap().moves, solution.moves);
+
+        // Flipping a move byte changes the moves, which no longer replay to the goal
+        let mut corrupt = bytes.clone();
+        corrupt[6] ^= 0xFF;
+        assert!(matches!(
+            Solution::decode_compact(&corrupt, &board),
+            Err(DecodeError::IllegalMove(_)) | Err(DecodeError::NotSolved)
+        ));
+        let mut corrupt = bytes.clone();
+        *corrupt.last_mut().unwrap() |= 0xC0;
+        assert_eq!(
+            Solution::decode_compact(&corrupt, &board),
+            Err(DecodeError::Corrupt("unused bits are set".to_string()))
+        );
+        assert_eq!(Solution::decode_compact(&bytes[..bytes.len() - 1], &board), Err(DecodeError::Truncated));
+        assert_eq!(Solution::decode_compact(&bytes[..3], &board), Err(DecodeError::Truncated));
+        assert_eq!(Solution::decode_compact(&[2], &board), Err(DecodeError::UnsupportedVersion(2)));
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(matches!(Solution::decode_compact(&longer, &board), Err(DecodeError::Corrupt(_))));
+        // The moves can't be replayed from another board
+        assert_eq!(Solution::decode_compact(&bytes, &Board::new(4, None, 0, None)).unwrap_err().code(), 307);
+        assert!(Solution::decode_compact_text("not base64!", &board).is_err());
+    }
+
+    /// Test linear conflict solves report conflict cache use and Manhattan ones don't
+    #[test]
+    fn test_conflict_cache_stats() {
+        let board = Board::generate(4, 363, 40).unwrap();
+        let stats = solve(&board, &SolveOptions::default()).unwrap().stats;
+        assert!(stats.conflict_cache_hits > 0);
+        assert!(stats.conflict_cache_hit_rate() > 0.0 && stats.conflict_cache_hit_rate() < 1.0);
+        assert!(stats.to_json().contains(&format!("\"conflict_cache_hits\":{}", stats.conflict_cache_hits)));
+        let (_, stats) = solve_length_with_stats(&board, &SolveOptions::default()).unwrap();
+        assert!(stats.conflict_cache_hits > 0);
+        let manhattan = SolveOptions::new().heuristic(Heuristic::Manhattan);
+        let stats = solve(&board, &manhattan).unwrap().stats;
+        assert_eq!((stats.conflict_cache_hits, stats.conflict_cache_misses), (0, 0));
+        assert_eq!(stats.conflict_cache_hit_rate(), 0.0);
+    }
+
+    /// Test IDA* stays optimal with a transposition table, and expands fewer boards
+    #[test]
+    fn test_transposition_table() {
+        let mut rng = StdRng::seed_from_u64(368);
+        let ida_star = SolveOptions::new().algorithm(Algorithm::IdaStar);
+        for _case in 0..10 {
+            let mut board = Board::new(3, None, 0, None);
+            board.shuffle_with_rng(60, &mut rng);
+            let board = Board::try_new(3, board.tiles().to_vec()).unwrap();
+            let optimal = solve(&board, &SolveOptions::default()).unwrap().moves.len();
+            let plain = solve(&board, &ida_star).unwrap().stats;
+            for &(entries, replacement) in &[(1 << 16, TtReplacement::Always), (64, TtReplacement::PreferShallow)] {
+                let options = ida_star.clone().tt_entries(entries).tt_replacement(replacement);
+                let solution = solve(&board, &options).unwrap();
+                assert_eq!(solution.moves.len(), optimal);
+                assert!(verify_solution(&board, &solution.moves, Notation::BlankMoves));
+                assert!(solution.stats.nodes_expanded <= plain.nodes_expanded);
+                assert_eq!(solve_length(&board, &options), Ok(optimal as u32));
+            }
+        }
+        let board = Board::generate(4, 388, 50).unwrap();
+        let plain = solve_length_with_stats(&board, &SolveOptions::default()).unwrap();
+        let cached = solve_length_with_stats(&board, &SolveOptions::new().tt_entries(1 << 16)).unwrap();
+        assert_eq!(cached.0, plain.0);
+        assert!(cached.1.nodes_expanded < plain.1.nodes_expanded);
+        assert!(cached.1.tt_hits > 0 && cached.1.tt_cutoffs > 0);
+        assert_eq!((plain.1.tt_hits, plain.1.tt_cutoffs), (0, 0));
+        assert!(cached.1.to_json().contains(&format!("\"tt_cutoffs\":{}", cached.1.tt_cutoffs)));
+    }
+
+    /// Test ordering children by cost keeps solutions optimal
+    #[test]
+    fn test_order_children() {
+        let ida_star = SolveOptions::new().algorithm(Algorithm::IdaStar);
+        for &heuristic in &HEURISTICS {
+            let board = Board::generate(4, 369, 40).unwrap();
+            let options = ida_star.clone().heuristic(heuristic);
+            let unordered = solve(&board, &options).unwrap();
+            let ordered = solve(&board, &options.clone().order_children(true)).unwrap();
+            assert_eq!(ordered.moves.len(), unordered.moves.len());
+            assert!(verify_solution(&board, &ordered.moves, Notation::BlankMoves));
+            assert_eq!(
+                solve_length(&board, &options.clone().order_children(true)),
+                Ok(unordered.moves.len() as u32)
+            );
+        }
+    }
+
+    /// Test the length-only search agrees with full solves
+    #[test]
+    fn test_solve_length() {
+        let mut rng = StdRng::seed_from_u64(345);
+        for _case in 0..20 {
+            let mut board = Board::new(3, None, 0, None);
+            board.shuffle_with_rng(rng.gen_range(0, 40), &mut rng);
+            let board = Board::try_new(3, board.tiles().to_vec()).unwrap();
+            let solution = solve(&board, &SolveOptions::default()).unwrap();
+            assert_eq!(solve_length(&board, &SolveOptions::default()), Ok(solution.moves.len() as u32));
+            let manhattan = SolveOptions::new().heuristic(Heuristic::Manhattan);
+            assert_eq!(solve_length(&board, &manhattan), Ok(solution.moves.len() as u32));
+        }
+        let tiles = vec![Tile::new(2), Tile::new(1), Tile::new(3), Tile::new(BLANK_TILE)];
+        let board = Board::new(2, None, 0, Some(tiles.into_boxed_slice()));
+        assert_eq!(solve_length(&board, &SolveOptions::default()), Err(SolveError::Unreachable));
+        let (board, _) = Board::random_walk_exact(3, 12, &mut rng).unwrap();
+        assert!(matches!(solve_length(&board, &SolveOptions::new().max_nodes(1)), Err(SolveError::BudgetExceeded(_))));
+    }
+
+    /// Test frontier samples are bounded and respect the heap order
+    #[test]
+    fn test_frontier_sample() {
+        let board = Board::generate(4, 373, 60).unwrap();
+        let mut solver = Solver::new(4, board.tiles().to_vec());
+        let mut trace = crate::observer::TraceRecorder::default();
+        assert_eq!(solver.step_observed(200, &mut trace), Ok(None));
+        let last_popped = trace.expanded.last().map(|event| event.g + event.h).unwrap();
+        for &k in &[0, 5, solver.frontier_len(), solver.frontier_len() + 10] {
+            let sample = solver.frontier_sample(k);
+            assert_eq!(sample.len(), k.min(solver.frontier_len()));
+            assert!(sample.iter().all(|&(_, g, h)| g + h >= last_popped));
+        }
+        // Sampling the same state again gives the same sample
+        assert_eq!(solver.frontier_sample(20), solver.frontier_sample(20));
+        let (state, g, _) = solver.frontier_sample(1)[0];
+        let sampled = pack::decode(4, state);
+        assert!(solver.board_leaves.iter().any(|leaf| leaf.tiles() == sampled.tiles() && leaf.depth == g));
+    }
+
+    /// Test adversarial scrambles stay within budget and beat random walks
+    #[test]
+    fn test_scramble_adversarial() {
+        let solved_board = Board::new(4, None, -1, None);
+        let options = SolveOptions::new().max_nodes(2_000);
+        let (board, path) = scramble_adversarial(&solved_board, 40, &options);
+        assert!(path.len() <= 40);
+        assert_eq!(solved_board.apply_moves(&path, Notation::BlankMoves).unwrap().tiles(), board.tiles());
+        assert!(board.cost > 0);
+        assert_eq!((board.depth, board.path.as_str()), (0, ""));
+        // Starting from a scrambled board only moves further away
+        let start = Board::generate(4, 374, 20).unwrap();
+        let (farther, path) = scramble_adversarial(&start, 10, &options);
+        assert!(path.len() <= 10);
+        assert!(farther.cost >= start.cost);
+        // Random walks of the same length end nearer the goal
+        let mut rng = StdRng::seed_from_u64(374);
+        let beaten = (0..10)
+            .filter(|_| {
+                let mut walk = Board::new(4, None, 0, None);
+                walk.shuffle_with_rng(40, &mut rng);
+                board.cost >= walk.g() + walk.h(&solved_board)
+            })
+            .count();
+        assert!(beaten >= 9);
+        // A zero budget leaves the board alone
+        assert_eq!(scramble_adversarial(&start, 0, &options), (start.clone(), String::new()));
+    }
+
+    /// Test boards pushed again along a shorter path are only expanded at that depth
+    #[test]
+    fn test_stale_pops_skipped() {
+        /// Checks each expansion is at the smallest depth the board was pushed at
+        #[derive(Default)]
+        struct DepthChecker {
+            best_depths: HashMap<Vec<Tile>, i32>,
+            shortcuts: usize,
+        }
+        impl SolverObserver for DepthChecker {
+            fn on_expand(&mut self, board: &Board, g: i32, _h: i32) {
+                let best = *self.best_depths.entry(board.tiles().to_vec()).or_insert(g);
+                assert_eq!(g, best, "expanded at depth {} after being pushed at {}", g, best);
+            }
+            fn on_generate(&mut self, board: &Board, _via: char) {
+                match self.best_depths.get_mut(board.tiles()) {
+                    Some(best) if board.depth < *best => {
+                        *best = board.depth;
+                        self.shortcuts += 1;
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.best_depths.insert(board.tiles().to_vec(), board.depth);
+                    }
+                }
+            }
+        }
+        // Greedy search ignores depth, so it often finds a shorter path to a board it
+        // has already pushed
+        let options = SolveOptions::new().algorithm(Algorithm::Greedy);
+        let (mut shortcuts, mut stale_pops) = (0, 0);
+        for seed in 395..400 {
+            let board = Board::generate(4, seed, 100).unwrap();
+            let mut checker = DepthChecker::default();
+            let solution = solve_observed(&board, &options, &mut checker).unwrap();
+            assert!(verify_solution(&board, &solution.moves, Notation::BlankMoves));
+            assert!(solution.stats.to_json().contains(&format!("\"stale_pops\":{}", solution.stats.stale_pops)));
+            shortcuts += checker.shortcuts;
+            stale_pops += solution.stats.stale_pops;
+        }
+        assert!(shortcuts > 0 && stale_pops > 0, "{} {}", shortcuts, stale_pops);
+    }
+
+    /// Test a search snapshotted halfway resumes to the same solution
+    #[test]
+    fn test_solver_resume() {
+        let board = Board::generate(3, 351, 60).unwrap();
+        let mut uninterrupted = Solver::new(3, board.tiles().to_vec());
+        let expected = uninterrupted.step(u64::MAX).unwrap().unwrap();
+        let mut solver = Solver::new(3, board.tiles().to_vec());
+        assert_eq!(solver.step(expected.stats.nodes_expanded / 2), Ok(None));
+        let state = solver.serialize_state();
+        assert_eq!(state[0], STATE_VERSION);
+        let mut resumed = Solver::resume(&state).unwrap();
+        assert_eq!(resumed.root(), &board);
+        assert_eq!(resumed.serialize_state().len(), state.len());
+        // The conflict cache starts empty when resumed, so only its counters differ
+        let without_cache = |solution: Solution| Solution {
+            stats: SolveStats { conflict_cache_hits: 0, conflict_cache_misses: 0, ..solution.stats },
+            ..solution
+        };
+        let resumed_solution = resumed.step(u64::MAX).unwrap().unwrap();
+        assert_eq!(without_cache(resumed_solution.clone()), without_cache(expected));
+        // Solved state resumes to the same solution
+        let mut solved = Solver::resume(&resumed.serialize_state()).unwrap();
+        assert_eq!(solved.step(1).unwrap().map(without_cache), Some(without_cache(resumed_solution)));
+
+        // Limits and checks carry over
+        let board = Board::generate(3, 399, 200).unwrap();
+        let options = SolveOptions::new().max_memory_bytes(20_000).max_time_ms(1_000_000).paranoid(true);
+        let mut solver = Solver::with_options(3, board.tiles().to_vec(), &options);
+        assert_eq!(solver.step(10), Ok(None));
+        let mut resumed = Solver::resume(&solver.serialize_state()).unwrap();
+        assert_eq!((resumed.max_memory_bytes, resumed.paranoid), (20_000, true));
+        assert_eq!(resumed.proven_lower_bound(), solver.proven_lower_bound());
+        assert!(resumed.time_budget.remaining_ms().unwrap() <= 1_000_000);
+        let error = loop {
+            match resumed.step(10) {
+                Ok(None) => continue,
+                other => break other,
+            }
+        };
+        assert!(matches!(error, Err(SolveError::MemoryBudget(_))), "{:?}", error);
+    }
+
+    /// Test invalid solver state is rejected
+    #[test]
+    fn test_solver_resume_invalid() {
+        let state = Solver::new(3, Board::generate(3, 351, 20).unwrap().tiles().to_vec()).serialize_state();
+        let mut old = state.clone();
+        old[0] = 0;
+        assert_eq!(Solver::resume(&old).err(), Some(ResumeError::UnsupportedVersion(0)));
+        assert_eq!(Solver::resume(&state[..state.len() - 1]).err(), Some(ResumeError::Truncated));
+        assert_eq!(Solver::resume(&[]).err(), Some(ResumeError::Truncated));
+        let mut trailing = state.clone();
+        trailing.push(0);
+        assert_eq!(Solver::resume(&trailing).err(), Some(ResumeError::Corrupt("trailing bytes".to_string())));
+        let mut duplicate = state;
+        // Overwrite the root tiles with zeros, repeating tile 1
+        duplicate[2..10].copy_from_slice(&[0; 8]);
+        assert_eq!(
+            Solver::resume(&duplicate).err(),
+            Some(ResumeError::Corrupt("duplicate tile symbol 1".to_string()))
+        );
+        assert_eq!(
+            ResumeError::UnsupportedVersion(0).to_string(),
+            "unsupported solver state version 0 (expected 5)"
+        );
+    }
+}
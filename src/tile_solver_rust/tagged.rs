@@ -0,0 +1,214 @@
+//! Solves tagged with a caller-chosen id, for pages that multiplex many solves and
+//! correlate responses by id rather than by order.
+//!
+//! ```js
+//! const results = [solve_board_tagged_async(1, a, 1000, onProgress),
+//!                  solve_board_tagged_async(2, b, 1000, onProgress)];
+//! cancel(1);
+//! // results[0] resolves to {id: 1, error: "cancelled", code: 402}
+//! // results[1] resolves to {id: 2, path: "..."}
+//! ```
+//!
+//! Results are `{id, path}` or `{id, error, code}`, and progress reports are the worker's
+//! `{type: "progress", id, ...}` objects (see `worker`). Stepped solves are kept in a
+//! thread-local registry of `WorkerState` solves, which drops each solve once it
+//! finishes, fails or is cancelled. Ids should be unique among in-flight solves; starting
+//! a solve with the id of one in flight replaces it.
+
+use std::cell::RefCell;
+
+use js_sys::{Function, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use super::error_code::CodedError;
+use super::worker::{self, WorkerRequest, WorkerResponse, WorkerState};
+use super::{solve_board_info, yield_to_event_loop};
+
+thread_local! {
+    /// In-flight stepped solves, keyed by id
+    static TAGGED: RefCell<WorkerState> = RefCell::new(WorkerState::new());
+}
+
+/// Wasm entry point for solving a board and tagging the result with an id.
+/// * The solve runs to completion in this call, so it can't be cancelled; use
+///   `solve_board_tagged_async` for solves that may be.
+///
+/// # Parameters
+/// * `id` - Id echoed in the result
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// `{id, path}`, or `{id, error, code}` if the board can't be solved
+#[wasm_bindgen]
+pub fn solve_board_tagged(id: u32, board_info: &str) -> JsValue {
+    result_to_js(&solve_tagged(id, board_info))
+}
+
+/// Wasm entry point for solving a board in chunks, tagged with an id.
+/// * Yields to the event loop after every `chunk_nodes` expansions, so `cancel` can be
+///   called while the solve is in flight.
+/// * Solves with `Solver::new`, as the worker does.
+///
+/// # Parameters
+/// * `id` - Id echoed in the progress reports and the result
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `chunk_nodes` - Number of boards to expand between yields
+/// * `on_progress` - Called with a progress report after each chunk that doesn't finish
+///
+/// # Returns
+/// Promise of `{id, path}`, or `{id, error, code}` if the board can't be solved or the
+/// solve was cancelled
+#[wasm_bindgen(wasm_bindgen_futures = js_sys::futures)]
+pub async fn solve_board_tagged_async(id: u32, board_info: String, chunk_nodes: u32,
+                                      on_progress: Option<Function>) -> Result<JsValue, JsValue> {
+    let mut response = start(id, &board_info, chunk_nodes as u64);
+    while let WorkerResponse::Progress { .. } = response {
+        if let Some(on_progress) = &on_progress {
+            on_progress.call1(&JsValue::NULL, &worker::response_to_js(&response))?;
+        }
+        yield_to_event_loop().await?;
+        response = step(id);
+    }
+    Ok(result_to_js(&response))
+}
+
+/// Wasm entry point for cancelling a tagged solve.
+/// * Its promise resolves with a cancelled error after its current chunk.
+///
+/// # Parameters
+/// * `id` - Id of the solve
+///
+/// # Returns
+/// Whether a solve with the id was in flight
+#[wasm_bindgen]
+pub fn cancel(id: u32) -> bool {
+    let response = TAGGED.with(|tagged| tagged.borrow_mut().handle(WorkerRequest::Cancel { id }));
+    response == WorkerResponse::cancelled(id)
+}
+
+/// Wasm entry point for counting the tagged solves still in flight.
+///
+/// # Returns
+/// In-flight solve count
+#[wasm_bindgen]
+pub fn tagged_pending() -> u32 {
+    TAGGED.with(|tagged| tagged.borrow().pending() as u32)
+}
+
+/// Solve board info in one call, tagged with an id.
+///
+/// # Parameters
+/// * `id` - Id of the solve
+/// * `board_info` - Board info in the same format as `solve_board`
+///
+/// # Returns
+/// The solution, or why the board can't be solved
+fn solve_tagged(id: u32, board_info: &str) -> WorkerResponse {
+    match solve_board_info(board_info) {
+        Ok(path) => WorkerResponse::Solved { id, path },
+        Err(CodedError { code, message, .. }) => WorkerResponse::Error { id, code, message },
+    }
+}
+
+/// Register a stepped solve and run its first chunk.
+///
+/// # Parameters
+/// * `id` - Id of the solve
+/// * `board_info` - Board info in the same format as `solve_board`
+/// * `chunk_nodes` - Number of boards to expand per chunk
+///
+/// # Returns
+/// Progress, or the result if the first chunk finishes
+fn start(id: u32, board_info: &str, chunk_nodes: u64) -> WorkerResponse {
+    TAGGED.with(|tagged| {
+        tagged.borrow_mut().handle(WorkerRequest::Solve {
+            id,
+            board: board_info.to_string(),
+            canonical: false,
+            chunk_nodes,
+            sample_frontier: false,
+        })
+    })
+}
+
+/// Run the next chunk of a stepped solve.
+///
+/// # Parameters
+/// * `id` - Id of the solve
+///
+/// # Returns
+/// Progress, the result, or a cancelled error if the solve is no longer in flight
+fn step(id: u32) -> WorkerResponse {
+    TAGGED.with(|tagged| {
+        let mut tagged = tagged.borrow_mut();
+        if !tagged.is_pending(id) {
+            return WorkerResponse::cancelled(id);
+        }
+        tagged.handle(WorkerRequest::Continue { id })
+    })
+}
+
+/// Convert a finished solve into a Javascript result object.
+///
+/// # Parameters
+/// * `response` - The solution or error of the solve
+///
+/// # Returns
+/// `{id, path}` or `{id, error, code}`
+fn result_to_js(response: &WorkerResponse) -> JsValue {
+    let object = Object::new();
+    let set = |key: &str, value: JsValue| {
+        Reflect::set(&object, &JsValue::from_str(key), &value).unwrap();
+    };
+    match response {
+        WorkerResponse::Solved { id, path } => {
+            set("id", (*id).into());
+            set("path", path.as_str().into());
+        }
+        WorkerResponse::Error { id, code, message } => {
+            set("id", (*id).into());
+            set("error", message.as_str().into());
+            set("code", (*code).into());
+        }
+        _ => unreachable!("unfinished solve {:?}", response),
+    }
+    object.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_code;
+
+    /// Board that takes more than one single-node chunk to solve
+    const BOARD: &str = "3,1,2,3,-1,4,6,7,5,8";
+
+    /// Test cancelling one of two tagged solves leaves the other to finish with its id
+    #[test]
+    fn test_tagged_cancel() {
+        for id in [1, 2] {
+            let response = start(id, BOARD, 1);
+            assert!(matches!(response, WorkerResponse::Progress { id: progress_id, .. } if progress_id == id));
+        }
+        assert_eq!(tagged_pending(), 2);
+        assert!(cancel(1));
+        assert!(!cancel(1));
+        assert_eq!(step(1), WorkerResponse::cancelled(1));
+        let mut response = step(2);
+        while let WorkerResponse::Progress { id, .. } = response {
+            assert_eq!(id, 2);
+            response = step(2);
+        }
+        assert_eq!(response, WorkerResponse::Solved { id: 2, path: "RDR".to_string() });
+        // Finished and cancelled solves are both dropped from the registry
+        assert_eq!(tagged_pending(), 0);
+        assert!(!cancel(2));
+        assert_eq!(solve_tagged(3, BOARD), WorkerResponse::Solved { id: 3, path: "RDR".to_string() });
+        assert!(matches!(
+            solve_tagged(4, "3,1,2"),
+            WorkerResponse::Error { id: 4, code, .. } if code != error_code::ERR_CANCELLED
+        ));
+        assert!(matches!(start(5, "3,1,2", 1), WorkerResponse::Error { id: 5, .. }));
+        assert_eq!(tagged_pending(), 0);
+    }
+}
@@ -1,9 +1,13 @@
+use core::fmt;
+#[cfg(alloc_only)]
+use alloc::{boxed::Box, vec::Vec};
+
 /// Symbol for blank tile
 pub const BLANK_TILE: i32 = -1;
 
 /// Simple representation of a tile in the board.
 /// * `symbol` - Symbol to represent tile
-#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
 pub struct Tile {
     symbol: i32
 }
@@ -17,6 +21,14 @@ impl Tile {
         Tile { symbol }
     }
 
+    /// Get the symbol of the tile.
+    ///
+    /// # Returns
+    /// Symbol representing the tile
+    pub fn symbol(&self) -> i32 {
+        self.symbol
+    }
+
     /// Check if tile is blank.
     ///
     /// # Returns
@@ -44,16 +56,14 @@ impl Tile {
     }
 }
 
-impl ToString for Tile {
+impl fmt::Display for Tile {
     /// Format the tile symbol.
-    ///
-    /// # Returns
-    /// The formatted tile symbol
-    fn to_string(&self) -> String {
+    /// * Boards are written through `board::BoardFormatter`, which lines tiles up.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.is_blank() {
-            return "      ".to_string();
+            return write!(f, "Blank");
         }
-        format!("Tile {}", self.symbol)
+        write!(f, "Tile {}", self.symbol)
     }
 }
 
@@ -74,7 +84,7 @@ mod tests {
         let tile = Tile::new(1);
         assert_eq!(tile.to_string(), "Tile 1");
         let tile_2 = Tile::new(BLANK_TILE);
-        assert_eq!(tile_2.to_string(), "      ");
+        assert_eq!(tile_2.to_string(), "Blank");
     }
 
     /// Test tile equivalence
@@ -100,7 +110,7 @@ mod tests {
     #[test]
     fn test_tile_generate_tiles() {
         let tiles = Tile::generate_tiles(2);
-        assert_eq!(tiles, [Tile::new(1), Tile::new(2),
+        assert_eq!(*tiles, [Tile::new(1), Tile::new(2),
                            Tile::new(3), Tile::new(BLANK_TILE)]);
     }
 }
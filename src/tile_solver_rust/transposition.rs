@@ -0,0 +1,274 @@
+use super::board::Board;
+use super::pack;
+
+/// Which entry is kept when two boards map to the same slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TtReplacement {
+    /// The newest board always takes the slot
+    #[default]
+    Always,
+    /// A result from the current iteration is only replaced by a board at most as deep,
+    /// since shallow results prune larger subtrees
+    PreferShallow,
+}
+
+/// One slot of the table.
+///
+/// # Attributes
+/// * `key` - Packed board, 0 when the slot is empty
+/// * `manhattan` - Manhattan distance of the board
+/// * `linear_conflicts` - Linear conflict penalty of the board
+/// * `depth` - Depth the board was searched at
+/// * `bound` - Bound of the iteration the board was searched in, -1 if it wasn't
+/// * `next_bound` - Smallest cost pruned below the board in that iteration
+#[derive(Debug, Clone, Copy, Default)]
+struct Entry {
+    key: u64,
+    manhattan: i32,
+    linear_conflicts: i32,
+    depth: i32,
+    bound: i32,
+    next_bound: i32,
+}
+
+/// Direct-mapped transposition table for IDA*.
+/// * Boards are keyed on their packed tiles; every packed board holds a blank nibble,
+///   so 0 never collides with a real key. Boards larger than 4x4 aren't stored.
+/// * Entries remember the heuristic of a board, so reaching it again doesn't recount
+///   it, and the result of searching it in the current iteration. The iteration is
+///   identified by its bound, which only grows, so nothing needs clearing between
+///   iterations.
+/// * A board that failed the bound at some depth fails it again at any larger depth,
+///   with pruned costs larger by the difference, so the search can be skipped.
+///
+/// # Attributes
+/// * `entries` - Slots of the table
+/// * `replacement` - Which entry is kept on a collision
+/// * `hits` - Number of heuristics read from the table
+/// * `cutoffs` - Number of boards not searched again
+#[derive(Debug, Clone)]
+pub struct TranspositionTable {
+    entries: Box<[Entry]>,
+    replacement: TtReplacement,
+    hits: u64,
+    cutoffs: u64,
+}
+
+impl TranspositionTable {
+    /// Create an empty table.
+    ///
+    /// # Parameters
+    /// * `entries` - Number of slots, at least 1
+    /// * `replacement` - Which entry is kept on a collision
+    pub fn new(entries: usize, replacement: TtReplacement) -> TranspositionTable {
+        TranspositionTable {
+            entries: vec![Entry::default(); entries.max(1)].into_boxed_slice(),
+            replacement,
+            hits: 0,
+            cutoffs: 0,
+        }
+    }
+
+    /// Number of heuristics read from the table.
+    ///
+    /// # Returns
+    /// Heuristic hits
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of boards whose search was skipped.
+    ///
+    /// # Returns
+    /// Cutoffs
+    pub fn cutoffs(&self) -> u64 {
+        self.cutoffs
+    }
+
+    /// Get the key of a board.
+    ///
+    /// # Parameters
+    /// * `board` - The board
+    ///
+    /// # Returns
+    /// Packed tiles, or `None` if the board is too large to store
+    pub fn key(board: &Board) -> Option<u64> {
+        if board.size() > pack::MAX_PACKED_SIZE {
+            return None;
+        }
+        Some(pack::encode_tiles(board.tiles()))
+    }
+
+    /// Fill in a board's heuristic from the table.
+    /// * Sets the board's Manhattan and linear conflict caches and its cost.
+    ///
+    /// # Parameters
+    /// * `board` - Board at its depth
+    /// * `key` - Key of the board
+    ///
+    /// # Returns
+    /// Whether the board was found
+    pub fn load_heuristic(&mut self, board: &mut Board, key: u64) -> bool {
+        let entry = self.entries[self._slot(key)];
+        if entry.key != key {
+            return false;
+        }
+        self.hits += 1;
+        board.manhattan_cost_cache = entry.manhattan;
+        board.linear_conflicts_cache = entry.linear_conflicts;
+        board.cost = board.g() + entry.manhattan + entry.linear_conflicts;
+        true
+    }
+
+    /// Remember a board's heuristic.
+    ///
+    /// # Parameters
+    /// * `board` - Costed board
+    /// * `key` - Key of the board
+    /// * `bound` - Bound of the current iteration
+    pub fn store_heuristic(&mut self, board: &Board, key: u64, bound: i32) {
+        let slot = self._slot(key);
+        if self.entries[slot].key == key || !self._replaces(slot, board.depth, bound) {
+            return;
+        }
+        self.entries[slot] = Entry {
+            key,
+            manhattan: board.manhattan_cost_cache,
+            linear_conflicts: board.linear_conflicts_cache,
+            depth: board.depth,
+            bound: -1,
+            next_bound: 0,
+        };
+    }
+
+    /// Check whether a board already failed the current iteration.
+    ///
+    /// # Parameters
+    /// * `key` - Key of the board
+    /// * `depth` - Depth the board is reached at
+    /// * `bound` - Bound of the current iteration
+    ///
+    /// # Returns
+    /// The smallest cost that searching the board would prune, if it is known to fail
+    pub fn cutoff(&mut self, key: u64, depth: i32, bound: i32) -> Option<i32> {
+        let entry = self.entries[self._slot(key)];
+        if entry.key != key || entry.bound != bound || entry.depth > depth {
+            return None;
+        }
+        self.cutoffs += 1;
+        Some(entry.next_bound.saturating_add(depth - entry.depth))
+    }
+
+    /// Remember that a board failed the current iteration.
+    ///
+    /// # Parameters
+    /// * `board` - Board that was searched
+    /// * `key` - Key of the board
+    /// * `bound` - Bound of the current iteration
+    /// * `next_bound` - Smallest cost pruned below the board
+    pub fn store_result(&mut self, board: &Board, key: u64, bound: i32, next_bound: i32) {
+        let slot = self._slot(key);
+        let entry = self.entries[slot];
+        // A shallower result for the same board prunes more
+        if entry.key == key && entry.bound == bound && entry.depth < board.depth {
+            return;
+        }
+        if entry.key != key && !self._replaces(slot, board.depth, bound) {
+            return;
+        }
+        self.entries[slot] = Entry {
+            key,
+            manhattan: board.manhattan_cost_cache,
+            linear_conflicts: board.linear_conflicts_cache,
+            depth: board.depth,
+            bound,
+            next_bound,
+        };
+    }
+
+    /// Check whether another board may take an occupied slot.
+    ///
+    /// # Parameters
+    /// * `slot` - Index of the slot
+    /// * `depth` - Depth of the new board
+    /// * `bound` - Bound of the current iteration
+    ///
+    /// # Returns
+    /// Whether the slot's entry can be replaced
+    fn _replaces(&self, slot: usize, depth: i32, bound: i32) -> bool {
+        let entry = self.entries[slot];
+        match self.replacement {
+            TtReplacement::Always => true,
+            TtReplacement::PreferShallow => entry.key == 0 || entry.bound != bound || entry.depth >= depth,
+        }
+    }
+
+    /// Get the slot of a key.
+    ///
+    /// # Parameters
+    /// * `key` - Key of a board
+    ///
+    /// # Returns
+    /// Index into the entries
+    fn _slot(&self, key: u64) -> usize {
+        (key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as usize % self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Test heuristics and results are stored and read back
+    #[test]
+    fn test_transposition_table() {
+        let mut table = TranspositionTable::new(64, TtReplacement::Always);
+        let mut rng = StdRng::seed_from_u64(368);
+        let mut board = Board::new(3, None, -1, None);
+        board.shuffle_with_rng(10, &mut rng);
+        board.depth = 4;
+        board.cost = board.g() + board.store_h(&Board::new(3, None, -1, None), Default::default());
+        let key = TranspositionTable::key(&board).unwrap();
+        let mut copy = board.clone();
+        assert!(!table.load_heuristic(&mut copy, key));
+        table.store_heuristic(&board, key, 20);
+        copy.depth = 6;
+        assert!(table.load_heuristic(&mut copy, key));
+        assert_eq!(copy.cost, board.cost + 2);
+        assert_eq!(table.hits(), 1);
+
+        assert_eq!(table.cutoff(key, 4, 20), None);
+        table.store_result(&board, key, 20, 23);
+        assert_eq!(table.cutoff(key, 4, 20), Some(23));
+        assert_eq!(table.cutoff(key, 6, 20), Some(25));
+        // Shallower than the failed search, or in another iteration
+        assert_eq!(table.cutoff(key, 2, 20), None);
+        assert_eq!(table.cutoff(key, 4, 22), None);
+        assert_eq!(table.cutoffs(), 2);
+        assert_eq!(TranspositionTable::key(&Board::new(5, None, -1, None)), None);
+    }
+
+    /// Test a shallow result survives a deeper collision when preferred
+    #[test]
+    fn test_transposition_table_prefer_shallow() {
+        // One slot, so every board collides
+        let mut table = TranspositionTable::new(1, TtReplacement::PreferShallow);
+        let mut rng = StdRng::seed_from_u64(368);
+        let mut shallow = Board::new(3, None, 0, None);
+        shallow.shuffle_with_rng(6, &mut rng);
+        let mut deep = shallow.clone();
+        deep.move_blank_tile(deep.legal_moves()[0]);
+        deep.depth = shallow.depth + 1;
+        let shallow_key = TranspositionTable::key(&shallow).unwrap();
+        let deep_key = TranspositionTable::key(&deep).unwrap();
+        table.store_result(&shallow, shallow_key, 10, 12);
+        table.store_heuristic(&deep, deep_key, 10);
+        table.store_result(&deep, deep_key, 10, 12);
+        assert_eq!(table.cutoff(shallow_key, shallow.depth, 10), Some(12));
+        // A later iteration frees the slot
+        table.store_result(&deep, deep_key, 12, 14);
+        assert_eq!(table.cutoff(deep_key, deep.depth, 12), Some(14));
+    }
+}
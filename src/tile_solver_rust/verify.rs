@@ -0,0 +1,293 @@
+//! Bulk verification of stored solutions, e.g. to re-check a database of solved boards
+//! after each solver release.
+//!
+//! Input is newline-delimited JSON, one entry per line:
+//! * `{"board": "3,1,2,3,-1,4,6,7,5,8", "path": "RDR"}` - The board in board info
+//!   format and its solution as moves of the blank
+//!
+//! Blank lines are skipped, and a line that can't be read is reported as a failure
+//! without stopping the run. Failures are written as newline-delimited JSON too:
+//! * `{"line":2,"kind":"not_solved","message":"...","board":"...","path":"..."}` - With
+//!   a null board or path if the line had no usable one
+//!
+//! Failure kinds are `malformed`, `illegal_move`, `not_solved`, `suboptimal` and
+//! `solve_failed`, the last two only when checking optimality.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::thread;
+
+use super::board::{parse_path, IllegalMove, Notation};
+use super::server::{json_string, Json, JsonParser};
+use super::solver::{self, SolveError, SolveOptions};
+use super::try_parse_board_info;
+
+/// Why a stored solution failed verification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyFailure {
+    /// The line isn't an entry: invalid JSON, a missing field or an invalid board
+    Malformed(String),
+    /// A move of the path can't be made
+    IllegalMove(IllegalMove),
+    /// The path doesn't end on the goal
+    NotSolved,
+    /// The path is longer than an optimal solution
+    Suboptimal { length: usize, optimal: u32 },
+    /// Re-solving the board to check the path's length failed
+    SolveFailed(SolveError),
+}
+
+impl VerifyFailure {
+    /// Get the name of the failure, as written to the failure report.
+    ///
+    /// # Returns
+    /// Snake case name, e.g. "not_solved"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            VerifyFailure::Malformed(_) => "malformed",
+            VerifyFailure::IllegalMove(_) => "illegal_move",
+            VerifyFailure::NotSolved => "not_solved",
+            VerifyFailure::Suboptimal { .. } => "suboptimal",
+            VerifyFailure::SolveFailed(_) => "solve_failed",
+        }
+    }
+}
+
+impl fmt::Display for VerifyFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyFailure::Malformed(problem) => write!(f, "malformed entry: {}", problem),
+            VerifyFailure::IllegalMove(e) => write!(f, "{}", e),
+            VerifyFailure::NotSolved => write!(f, "path doesn't reach the goal"),
+            VerifyFailure::Suboptimal { length, optimal } =>
+                write!(f, "path has {} moves, but the optimal solution has {}", length, optimal),
+            VerifyFailure::SolveFailed(e) => write!(f, "couldn't re-solve the board: {}", e),
+        }
+    }
+}
+
+/// An entry that failed verification.
+///
+/// # Attributes
+/// * `line` - Line number of the entry, from 1
+/// * `board` - Board info of the entry, if it had a valid board
+/// * `path` - Path of the entry, if it had one
+/// * `failure` - Why the entry failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedEntry {
+    pub line: usize,
+    pub board: Option<String>,
+    pub path: Option<String>,
+    pub failure: VerifyFailure,
+}
+
+impl FailedEntry {
+    /// Format the failure as a line of the failure report.
+    ///
+    /// # Returns
+    /// JSON object string with the `line`, `kind`, `message`, `board` and `path`
+    pub fn to_json(&self) -> String {
+        let optional = |text: &Option<String>| text.as_deref().map_or("null".to_string(), json_string);
+        format!(
+            "{{\"line\":{},\"kind\":\"{}\",\"message\":{},\"board\":{},\"path\":{}}}",
+            self.line,
+            self.failure.kind(),
+            json_string(&self.failure.to_string()),
+            optional(&self.board),
+            optional(&self.path),
+        )
+    }
+}
+
+/// Outcome of verifying a file of stored solutions.
+///
+/// # Attributes
+/// * `entries` - Number of entries checked, not counting blank lines
+/// * `passed` - Number of entries that passed
+/// * `failures` - Entries that failed, in line order
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifySummary {
+    pub entries: usize,
+    pub passed: usize,
+    pub failures: Vec<FailedEntry>,
+}
+
+impl VerifySummary {
+    /// Check if every entry passed.
+    ///
+    /// # Returns
+    /// Whether there were no failures
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Write the failures as newline-delimited JSON.
+    ///
+    /// # Parameters
+    /// * `output` - Where to write the failure report
+    ///
+    /// # Returns
+    /// The error writing the report, if any
+    pub fn write_failures<W: Write>(&self, mut output: W) -> io::Result<()> {
+        for failure in &self.failures {
+            writeln!(output, "{}", failure.to_json())?;
+        }
+        output.flush()
+    }
+}
+
+impl fmt::Display for VerifySummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} entries: {} passed, {} failed", self.entries, self.passed, self.failures.len())
+    }
+}
+
+/// Verify a file of stored solutions, replaying each path from its board.
+///
+/// # Parameters
+/// * `input` - Newline-delimited JSON entries
+/// * `check_optimal` - Options to re-solve each board with, to check its path is no
+///   longer than an optimal solution; `None` only checks paths reach the goal
+/// * `jobs` - Number of threads to split the entries across (at least 1 is used)
+///
+/// # Returns
+/// The summary of the run, or the error reading the input
+pub fn verify_file<R: BufRead>(input: R, check_optimal: Option<&SolveOptions>,
+                               jobs: usize) -> io::Result<VerifySummary> {
+    let mut lines = Vec::new();
+    for (index, line) in input.split(b'\n').enumerate() {
+        let line = line?;
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            lines.push((index + 1, line));
+        }
+    }
+    let chunk_size = lines.len().div_ceil(jobs.max(1)).max(1);
+    let failures: Vec<FailedEntry> = thread::scope(|scope| {
+        let workers: Vec<_> = lines.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                chunk.iter()
+                    .filter_map(|(line, text)| _verify_entry(*line, text, check_optimal))
+                    .collect::<Vec<FailedEntry>>()
+            }))
+            .collect();
+        // Join in spawn order, so failures stay in line order
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+    });
+    Ok(VerifySummary { entries: lines.len(), passed: lines.len() - failures.len(), failures })
+}
+
+/// Verify one entry.
+///
+/// # Parameters
+/// * `line` - Line number of the entry
+/// * `text` - Raw line
+/// * `check_optimal` - Options to re-solve the board with, if checking optimality
+///
+/// # Returns
+/// The failure, if the entry failed
+fn _verify_entry(line: usize, text: &[u8], check_optimal: Option<&SolveOptions>) -> Option<FailedEntry> {
+    let malformed = |problem: String| FailedEntry {
+        line,
+        board: None,
+        path: None,
+        failure: VerifyFailure::Malformed(problem),
+    };
+    let text = match std::str::from_utf8(text) {
+        Ok(text) => text,
+        Err(_) => return Some(malformed("invalid UTF-8".to_string())),
+    };
+    let json = match JsonParser::parse(text.trim_end_matches('\r')) {
+        Ok(json) => json,
+        Err(e) => return Some(malformed(e)),
+    };
+    let field = |key: &str| match json.get(key) {
+        Some(Json::String(value)) => Ok(value.clone()),
+        _ => Err(format!("missing string field \"{}\"", key)),
+    };
+    let (board_info, path) = match (field("board"), field("path")) {
+        (Ok(board_info), Ok(path)) => (board_info, path),
+        (Err(problem), _) | (_, Err(problem)) => return Some(malformed(problem)),
+    };
+    let board = match try_parse_board_info(&board_info) {
+        Ok(board) => board,
+        Err(e) => return Some(FailedEntry { path: Some(path), ..malformed(format!("invalid board: {}", e)) }),
+    };
+    let failure = match board.apply_moves(&path, Notation::BlankMoves) {
+        Err(e) => Some(VerifyFailure::IllegalMove(e)),
+        Ok(end) if !end.is_solved() => Some(VerifyFailure::NotSolved),
+        Ok(_) => check_optimal.and_then(|options| {
+            let length = parse_path(&path, Notation::BlankMoves).map_or(0, |moves| moves.len());
+            match solver::solve_length(&board, options) {
+                Ok(optimal) if length > optimal as usize => Some(VerifyFailure::Suboptimal { length, optimal }),
+                Ok(_) => None,
+                Err(e) => Some(VerifyFailure::SolveFailed(e)),
+            }
+        }),
+    };
+    failure.map(|failure| FailedEntry { line, board: Some(board_info), path: Some(path), failure })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::MoveRejection;
+
+    /// Test a file with good, non-solving and malformed entries is reported line by line
+    #[test]
+    fn test_verify_file() {
+        let input = concat!(
+            "{\"board\": \"3,1,2,3,-1,4,6,7,5,8\", \"path\": \"RDR\"}\n",
+            "{\"board\": \"3,1,2,3,-1,4,6,7,5,8\", \"path\": \"RD\"}\n",
+            "\n",
+            "{\"board\": \"3,1,2,3,-1,4,6,7,5,8\"\n",
+            "{\"board\": \"3,1,2,3,-1,4,6,7,5,8\", \"path\": \"RLRDR\"}\n",
+            "{\"board\": \"3,1,2,3,-1,4,6,7,5,8\", \"path\": \"L\"}\r\n",
+        );
+        for jobs in 1..4 {
+            let summary = verify_file(input.as_bytes(), None, jobs).unwrap();
+            assert_eq!((summary.entries, summary.passed, summary.failures.len()), (5, 2, 3));
+            assert_eq!(summary.to_string(), "5 entries: 2 passed, 3 failed");
+            let lines: Vec<usize> = summary.failures.iter().map(|failure| failure.line).collect();
+            assert_eq!(lines, vec![2, 4, 6]);
+            let mut report = Vec::new();
+            summary.write_failures(&mut report).unwrap();
+            let report = String::from_utf8(report).unwrap();
+            let report: Vec<&str> = report.lines().collect();
+            assert_eq!(report[0], concat!(
+                "{\"line\":2,\"kind\":\"not_solved\",\"message\":\"path doesn't reach the goal\",",
+                "\"board\":\"3,1,2,3,-1,4,6,7,5,8\",\"path\":\"RD\"}",
+            ));
+            assert!(report[1].starts_with("{\"line\":4,\"kind\":\"malformed\",\"message\":\"malformed entry: invalid"));
+            assert!(report[1].ends_with("\"board\":null,\"path\":null}"));
+            assert!(report[2].starts_with("{\"line\":6,\"kind\":\"illegal_move\","));
+            assert_eq!(
+                summary.failures[2].failure,
+                VerifyFailure::IllegalMove(IllegalMove { index: 0, rejection: MoveRejection::BlankOnLeftEdge })
+            );
+        }
+        // Only checking optimality catches the detour
+        let summary = verify_file(input.as_bytes(), Some(&SolveOptions::default()), 2).unwrap();
+        assert_eq!(summary.failures.len(), 4);
+        assert_eq!(summary.failures[2].line, 5);
+        assert_eq!(summary.failures[2].failure, VerifyFailure::Suboptimal { length: 5, optimal: 3 });
+    }
+
+    /// Test entries with missing fields or invalid boards are reported as malformed
+    #[test]
+    fn test_verify_malformed_entries() {
+        let input = concat!(
+            "[1, 2]\n",
+            "{\"board\": \"3,1,2,3\", \"path\": \"R\"}\n",
+            "{\"path\": 5}\n",
+        );
+        let summary = verify_file(input.as_bytes(), None, 1).unwrap();
+        let failures: Vec<String> = summary.failures.iter().map(|failure| failure.failure.to_string()).collect();
+        assert_eq!(failures, vec![
+            "malformed entry: missing string field \"board\"",
+            "malformed entry: invalid board: expected 9 tiles, found 3",
+            "malformed entry: missing string field \"board\"",
+        ]);
+        assert_eq!(summary.failures[1].path.as_deref(), Some("R"));
+        assert!(verify_file(&b"\xff\n"[..], None, 1).unwrap().failures[0].to_json().contains("invalid UTF-8"));
+    }
+}
@@ -0,0 +1,569 @@
+//! Message protocol for running the solver inside a Web Worker.
+//!
+//! The Javascript side only has to forward messages:
+//!
+//! ```js
+//! self.onmessage = (e) => {
+//!     const response = handle_message(e.data);
+//!     self.postMessage(response);
+//!     if (response.type === "progress") {
+//!         setTimeout(() => self.onmessage({data: {type: "continue", id: response.id}}));
+//!     }
+//! };
+//! ```
+//!
+//! Requests:
+//! * `{type: "solve", id, board, options?: {canonical?, chunk_nodes?, sample_frontier?}}`
+//! * `{type: "continue", id}` - Resume a solve that reported progress
+//! * `{type: "cancel", id}`
+//! * `{type: "generate", id, n, seed, shuffles?}`
+//! * `{type: "export", id}` - Snapshot an in-flight solve, e.g. to stash in IndexedDB
+//! * `{type: "import", id, state, options?: {canonical?, chunk_nodes?, sample_frontier?}}` -
+//!   Resume a solve from an exported `Uint8Array` snapshot
+//!
+//! Responses are `{type: "progress" | "result" | "state" | "error", id, ...}`, with error
+//! responses carrying a numeric `code` (see `error_code`) and a `message`. Solves are run in
+//! chunks so that a `cancel` posted while one is in progress is seen between chunks. With
+//! `sample_frontier` set, progress responses carry a `frontier` array of up to
+//! `FRONTIER_SAMPLE_SIZE` `[state, g, h]` triples, with the packed state as a `BigInt`.
+//! Every progress response carries the search's `proven_lower_bound`, which never
+//! decreases, so a page can show "between X and Y moves remaining", and its `elapsed_ms`
+//! since the solve was started or imported.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use js_sys::{Array, Object, Reflect, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use super::board::Board;
+use super::error_code::{self, ErrorCode};
+use super::solver::{self, Solver, CANONICAL_MOVE_ORDER};
+use super::{format_board_info, try_parse_board_info};
+
+/// Default number of boards expanded per solve chunk
+pub const DEFAULT_CHUNK_NODES: u64 = 10_000;
+
+/// Default number of random moves used to generate a board
+pub const DEFAULT_GENERATE_SHUFFLES: i32 = 100;
+
+/// Number of frontier boards sampled into each progress response, when asked for
+pub const FRONTIER_SAMPLE_SIZE: usize = 200;
+
+/// A message sent to the worker.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkerRequest {
+    /// Start solving a board
+    Solve { id: u32, board: String, canonical: bool, chunk_nodes: u64, sample_frontier: bool },
+    /// Resume a solve that reported progress
+    Continue { id: u32 },
+    /// Cancel an in-flight solve
+    Cancel { id: u32 },
+    /// Generate a shuffled board
+    Generate { id: u32, n: i32, seed: u64, shuffles: i32 },
+    /// Snapshot an in-flight solve
+    Export { id: u32 },
+    /// Resume a solve from a snapshot
+    Import { id: u32, state: Vec<u8>, canonical: bool, chunk_nodes: u64, sample_frontier: bool },
+}
+
+/// A message sent back from the worker.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkerResponse {
+    /// A solve is still running, with no solution shorter than its proven lower bound and
+    /// (state, g, h) samples of its frontier if asked for
+    Progress {
+        id: u32,
+        nodes_expanded: u64,
+        proven_lower_bound: i32,
+        elapsed_ms: f64,
+        frontier: Option<Vec<(u64, i32, i32)>>,
+    },
+    /// A solve finished with the given moves
+    Solved { id: u32, path: String },
+    /// A board was generated, in board info format
+    Generated { id: u32, board: String },
+    /// Snapshot of an in-flight solve, see `Solver::serialize_state`
+    State { id: u32, state: Vec<u8> },
+    /// The request failed or was cancelled
+    Error { id: u32, code: u32, message: String },
+}
+
+impl WorkerResponse {
+    /// Create an error response.
+    ///
+    /// # Parameters
+    /// * `id` - Request id
+    /// * `error` - Why the request failed
+    ///
+    /// # Returns
+    /// The error response
+    pub fn error<E: ErrorCode>(id: u32, error: E) -> WorkerResponse {
+        WorkerResponse::Error { id, code: error.code(), message: error.to_string() }
+    }
+
+    /// Create the error response of a cancelled solve.
+    ///
+    /// # Parameters
+    /// * `id` - Request id of the solve
+    ///
+    /// # Returns
+    /// The error response
+    pub(crate) fn cancelled(id: u32) -> WorkerResponse {
+        WorkerResponse::Error { id, code: error_code::ERR_CANCELLED, message: "cancelled".to_string() }
+    }
+
+    /// Create an error response for a request id with no solve in flight.
+    ///
+    /// # Parameters
+    /// * `id` - Request id
+    ///
+    /// # Returns
+    /// The error response
+    fn unknown_request(id: u32) -> WorkerResponse {
+        WorkerResponse::Error {
+            id,
+            code: error_code::ERR_UNKNOWN_REQUEST,
+            message: format!("unknown request id {}", id),
+        }
+    }
+}
+
+/// An in-flight solve.
+///
+/// # Attributes
+/// * `board` - The board being solved
+/// * `solver` - Search state
+/// * `canonical` - Whether to return the canonical optimal solution
+/// * `chunk_nodes` - Boards to expand per chunk
+/// * `sample_frontier` - Whether progress responses sample the frontier
+#[derive(Debug)]
+struct PendingSolve {
+    board: Board,
+    solver: Solver,
+    canonical: bool,
+    chunk_nodes: u64,
+    sample_frontier: bool,
+}
+
+/// Worker state: in-flight solves keyed by request id.
+#[derive(Debug, Default)]
+pub struct WorkerState {
+    solves: HashMap<u32, PendingSolve>,
+}
+
+impl WorkerState {
+    /// Create an empty worker state.
+    pub fn new() -> WorkerState {
+        WorkerState::default()
+    }
+
+    /// Number of solves that are still in flight.
+    ///
+    /// # Returns
+    /// In-flight solve count
+    pub fn pending(&self) -> usize {
+        self.solves.len()
+    }
+
+    /// Check if a solve is still in flight.
+    ///
+    /// # Parameters
+    /// * `id` - Request id of the solve
+    ///
+    /// # Returns
+    /// Whether the solve was started and hasn't finished, failed or been cancelled
+    pub fn is_pending(&self, id: u32) -> bool {
+        self.solves.contains_key(&id)
+    }
+
+    /// Handle a request from the main thread.
+    ///
+    /// # Parameters
+    /// * `request` - The request to handle
+    ///
+    /// # Returns
+    /// The response to post back
+    pub fn handle(&mut self, request: WorkerRequest) -> WorkerResponse {
+        match request {
+            WorkerRequest::Solve { id, board, canonical, chunk_nodes, sample_frontier } => {
+                let board = match try_parse_board_info(&board) {
+                    Ok(board) => board,
+                    Err(e) => return WorkerResponse::error(id, e),
+                };
+                let solver = Solver::new(board.size(), board.tiles().to_vec());
+                self.solves.insert(id, PendingSolve {
+                    board,
+                    solver,
+                    canonical,
+                    chunk_nodes: chunk_nodes.max(1),
+                    sample_frontier,
+                });
+                self.run_chunk(id)
+            }
+            WorkerRequest::Continue { id } => self.run_chunk(id),
+            WorkerRequest::Cancel { id } => match self.solves.remove(&id) {
+                Some(_) => WorkerResponse::cancelled(id),
+                None => WorkerResponse::unknown_request(id),
+            },
+            WorkerRequest::Generate { id, n, seed, shuffles } => {
+                match Board::generate(n, seed, shuffles) {
+                    Ok(board) => WorkerResponse::Generated { id, board: format_board_info(&board) },
+                    Err(e) => WorkerResponse::error(id, e),
+                }
+            }
+            WorkerRequest::Export { id } => match self.solves.get(&id) {
+                Some(pending) => WorkerResponse::State { id, state: pending.solver.serialize_state() },
+                None => WorkerResponse::unknown_request(id),
+            },
+            WorkerRequest::Import { id, state, canonical, chunk_nodes, sample_frontier } => {
+                let solver = match Solver::resume(&state) {
+                    Ok(solver) => solver,
+                    Err(e) => return WorkerResponse::error(id, e),
+                };
+                self.solves.insert(id, PendingSolve {
+                    board: solver.root().clone(),
+                    solver,
+                    canonical,
+                    chunk_nodes: chunk_nodes.max(1),
+                    sample_frontier,
+                });
+                self.run_chunk(id)
+            }
+        }
+    }
+
+    /// Run one chunk of an in-flight solve.
+    ///
+    /// # Parameters
+    /// * `id` - Request id of the solve
+    ///
+    /// # Returns
+    /// Progress, or the result once the solve finishes
+    fn run_chunk(&mut self, id: u32) -> WorkerResponse {
+        let pending = match self.solves.get_mut(&id) {
+            Some(pending) => pending,
+            None => return WorkerResponse::unknown_request(id),
+        };
+        match pending.solver.step(pending.chunk_nodes) {
+            Ok(None) => WorkerResponse::Progress {
+                id,
+                nodes_expanded: pending.solver.stats().nodes_expanded,
+                proven_lower_bound: pending.solver.proven_lower_bound(),
+                elapsed_ms: pending.solver.elapsed_ms(),
+                frontier: if pending.sample_frontier {
+                    Some(pending.solver.frontier_sample(FRONTIER_SAMPLE_SIZE))
+                } else {
+                    None
+                },
+            },
+            Err(e) => {
+                self.solves.remove(&id);
+                WorkerResponse::error(id, e)
+            }
+            Ok(Some(solution)) => {
+                let pending = self.solves.remove(&id).unwrap();
+                let path = if pending.canonical {
+                    solver::canonical_moves(&pending.board, solution.moves.len(), &CANONICAL_MOVE_ORDER)
+                } else {
+                    solution.moves
+                };
+                WorkerResponse::Solved { id, path }
+            }
+        }
+    }
+}
+
+thread_local! {
+    /// Worker state shared across messages
+    static WORKER: RefCell<WorkerState> = RefCell::new(WorkerState::new());
+}
+
+/// Wasm entry point for the worker message protocol.
+///
+/// # Parameters
+/// * `msg` - Request object posted to the worker
+///
+/// # Returns
+/// Response object to post back
+#[wasm_bindgen]
+pub fn handle_message(msg: JsValue) -> JsValue {
+    let response = match request_from_js(&msg) {
+        Ok(request) => WORKER.with(|worker| worker.borrow_mut().handle(request)),
+        Err(message) => WorkerResponse::Error {
+            id: get_number(&msg, "id").unwrap_or(0.0) as u32,
+            code: error_code::ERR_INVALID_REQUEST,
+            message,
+        },
+    };
+    response_to_js(&response)
+}
+
+/// Read a numeric field from a Javascript object.
+fn get_number(object: &JsValue, key: &str) -> Option<f64> {
+    Reflect::get(object, &JsValue::from_str(key)).ok()?.as_f64()
+}
+
+/// Convert a Javascript request object into a request.
+///
+/// # Parameters
+/// * `msg` - Request object
+///
+/// # Returns
+/// The request, or a description of what is wrong with it
+fn request_from_js(msg: &JsValue) -> Result<WorkerRequest, String> {
+    let field = |key: &str| Reflect::get(msg, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED);
+    let number = |key: &str| get_number(msg, key).ok_or(format!("missing numeric field {:?}", key));
+    let id = number("id")? as u32;
+    match field("type").as_string().as_deref() {
+        Some("solve") => {
+            let board = field("board").as_string().ok_or("missing field \"board\"")?;
+            let (canonical, chunk_nodes, sample_frontier) = solve_options_from_js(&field("options"));
+            Ok(WorkerRequest::Solve { id, board, canonical, chunk_nodes, sample_frontier })
+        }
+        Some("import") => {
+            let state = field("state").dyn_into::<Uint8Array>().map_err(|_| "missing Uint8Array field \"state\"")?;
+            let (canonical, chunk_nodes, sample_frontier) = solve_options_from_js(&field("options"));
+            Ok(WorkerRequest::Import { id, state: state.to_vec(), canonical, chunk_nodes, sample_frontier })
+        }
+        Some("export") => Ok(WorkerRequest::Export { id }),
+        Some("continue") => Ok(WorkerRequest::Continue { id }),
+        Some("cancel") => Ok(WorkerRequest::Cancel { id }),
+        Some("generate") => Ok(WorkerRequest::Generate {
+            id,
+            n: number("n")? as i32,
+            seed: number("seed")? as u64,
+            shuffles: get_number(msg, "shuffles").map(|n| n as i32).unwrap_or(DEFAULT_GENERATE_SHUFFLES),
+        }),
+        Some(other) => Err(format!("unknown message type {:?}", other)),
+        None => Err("missing field \"type\"".to_string()),
+    }
+}
+
+/// Read the options of a solve or import request.
+///
+/// # Parameters
+/// * `options` - Options object, or undefined
+///
+/// # Returns
+/// Whether to return the canonical solution, the boards to expand per chunk, and
+/// whether to sample the frontier
+fn solve_options_from_js(options: &JsValue) -> (bool, u64, bool) {
+    if !options.is_object() {
+        return (false, DEFAULT_CHUNK_NODES, false);
+    }
+    let flag = |key: &str| Reflect::get(options, &JsValue::from_str(key))
+        .map(|value| value.is_truthy())
+        .unwrap_or(false);
+    let chunk_nodes = get_number(options, "chunk_nodes").map(|nodes| nodes as u64).unwrap_or(DEFAULT_CHUNK_NODES);
+    (flag("canonical"), chunk_nodes, flag("sample_frontier"))
+}
+
+/// Convert a response into a Javascript object.
+///
+/// # Parameters
+/// * `response` - The response
+///
+/// # Returns
+/// Response object
+pub(crate) fn response_to_js(response: &WorkerResponse) -> JsValue {
+    let object = Object::new();
+    let set = |key: &str, value: JsValue| {
+        Reflect::set(&object, &JsValue::from_str(key), &value).unwrap();
+    };
+    match response {
+        WorkerResponse::Progress { id, nodes_expanded, proven_lower_bound, elapsed_ms, frontier } => {
+            set("type", "progress".into());
+            set("id", (*id).into());
+            set("nodes_expanded", (*nodes_expanded as f64).into());
+            set("proven_lower_bound", (*proven_lower_bound).into());
+            set("elapsed_ms", (*elapsed_ms).into());
+            if let Some(frontier) = frontier {
+                let samples: Array = frontier.iter()
+                    .map(|&(state, g, h)| -> JsValue {
+                        Array::of3(&JsValue::bigint_from_str(&state.to_string()), &g.into(), &h.into()).into()
+                    })
+                    .collect();
+                set("frontier", samples.into());
+            }
+        }
+        WorkerResponse::Solved { id, path } => {
+            set("type", "result".into());
+            set("id", (*id).into());
+            set("path", path.as_str().into());
+        }
+        WorkerResponse::Generated { id, board } => {
+            set("type", "result".into());
+            set("id", (*id).into());
+            set("board", board.as_str().into());
+        }
+        WorkerResponse::State { id, state } => {
+            set("type", "state".into());
+            set("id", (*id).into());
+            set("state", Uint8Array::from(&state[..]).into());
+        }
+        WorkerResponse::Error { id, code, message } => {
+            set("type", "error".into());
+            set("id", (*id).into());
+            set("code", (*code).into());
+            set("message", message.as_str().into());
+        }
+    }
+    object.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Board that takes more than one single-node chunk to solve
+    const BOARD: &str = "3,1,2,3,-1,4,6,7,5,8";
+
+    /// Test a solve running to completion in chunks
+    #[test]
+    fn test_worker_solve() {
+        let mut worker = WorkerState::new();
+        let mut response = worker.handle(WorkerRequest::Solve {
+            id: 1, board: BOARD.to_string(), canonical: false, chunk_nodes: 1, sample_frontier: false,
+        });
+        assert!(
+            matches!(
+                response,
+                WorkerResponse::Progress { id: 1, nodes_expanded: 1, proven_lower_bound: 3, frontier: None, .. }
+            ),
+            "unexpected response {:?}",
+            response
+        );
+        while let WorkerResponse::Progress { .. } = response {
+            response = worker.handle(WorkerRequest::Continue { id: 1 });
+        }
+        assert_eq!(response, WorkerResponse::Solved { id: 1, path: "RDR".to_string() });
+        assert_eq!(worker.pending(), 0);
+    }
+
+    /// Test progress responses sample the frontier when asked to
+    #[test]
+    fn test_worker_sample_frontier() {
+        let mut worker = WorkerState::new();
+        let response = worker.handle(WorkerRequest::Solve {
+            id: 8, board: BOARD.to_string(), canonical: false, chunk_nodes: 1, sample_frontier: true,
+        });
+        match response {
+            WorkerResponse::Progress {
+                id: 8, nodes_expanded: 1, proven_lower_bound: 3, frontier: Some(frontier), ..
+            } => {
+                // The root's three children
+                assert_eq!(frontier.len(), 3);
+                assert!(frontier.iter().all(|&(_, g, _)| g == 1));
+            }
+            other => panic!("unexpected response {:?}", other),
+        }
+    }
+
+    /// Test cancelling a solve between chunks
+    #[test]
+    fn test_worker_cancel() {
+        let mut worker = WorkerState::new();
+        let response = worker.handle(WorkerRequest::Solve {
+            id: 7, board: BOARD.to_string(), canonical: true, chunk_nodes: 1, sample_frontier: false,
+        });
+        assert!(
+            matches!(
+                response,
+                WorkerResponse::Progress { id: 7, nodes_expanded: 1, proven_lower_bound: 3, frontier: None, .. }
+            ),
+            "unexpected response {:?}",
+            response
+        );
+        assert_eq!(
+            worker.handle(WorkerRequest::Cancel { id: 7 }),
+            WorkerResponse::Error { id: 7, code: error_code::ERR_CANCELLED, message: "cancelled".to_string() }
+        );
+        assert_eq!(worker.pending(), 0);
+        assert_eq!(
+            worker.handle(WorkerRequest::Continue { id: 7 }),
+            WorkerResponse::Error {
+                id: 7,
+                code: error_code::ERR_UNKNOWN_REQUEST,
+                message: "unknown request id 7".to_string(),
+            }
+        );
+    }
+
+    /// Test invalid boards produce errors
+    #[test]
+    fn test_worker_invalid_board() {
+        let mut worker = WorkerState::new();
+        let response = worker.handle(WorkerRequest::Solve {
+            id: 2, board: "3,1,2".to_string(), canonical: false, chunk_nodes: 10, sample_frontier: false,
+        });
+        assert_eq!(
+            response,
+            WorkerResponse::Error { id: 2, code: 102, message: "expected 9 tiles, found 2".to_string() }
+        );
+    }
+
+    /// Test generating is reproducible for a seed
+    #[test]
+    fn test_worker_generate() {
+        let mut worker = WorkerState::new();
+        let request = WorkerRequest::Generate { id: 3, n: 3, seed: 42, shuffles: 50 };
+        let response = worker.handle(request.clone());
+        assert_eq!(worker.handle(request), response);
+        match response {
+            WorkerResponse::Generated { id, board } => {
+                assert_eq!(id, 3);
+                assert!(try_parse_board_info(&board).unwrap().is_solvable());
+            }
+            other => panic!("unexpected response {:?}", other),
+        }
+    }
+
+    /// Test exporting a solve and importing it into a fresh worker
+    #[test]
+    fn test_worker_export_import() {
+        let mut worker = WorkerState::new();
+        worker.handle(WorkerRequest::Solve {
+            id: 4, board: BOARD.to_string(), canonical: false, chunk_nodes: 1, sample_frontier: false,
+        });
+        let state = match worker.handle(WorkerRequest::Export { id: 4 }) {
+            WorkerResponse::State { id: 4, state } => state,
+            other => panic!("unexpected response {:?}", other),
+        };
+        let mut fresh = WorkerState::new();
+        let mut response = fresh.handle(WorkerRequest::Import {
+            id: 5, state, canonical: false, chunk_nodes: 1, sample_frontier: false,
+        });
+        assert!(
+            matches!(
+                response,
+                WorkerResponse::Progress { id: 5, nodes_expanded: 2, proven_lower_bound: 3, frontier: None, .. }
+            ),
+            "unexpected response {:?}",
+            response
+        );
+        while let WorkerResponse::Progress { .. } = response {
+            response = fresh.handle(WorkerRequest::Continue { id: 5 });
+        }
+        assert_eq!(response, WorkerResponse::Solved { id: 5, path: "RDR".to_string() });
+        assert_eq!(
+            fresh.handle(WorkerRequest::Import {
+                id: 6, state: vec![0], canonical: false, chunk_nodes: 1, sample_frontier: false,
+            }),
+            WorkerResponse::Error {
+                id: 6,
+                code: 301,
+                message: "unsupported solver state version 0 (expected 5)".to_string(),
+            }
+        );
+        assert_eq!(
+            fresh.handle(WorkerRequest::Export { id: 5 }),
+            WorkerResponse::Error {
+                id: 5,
+                code: error_code::ERR_UNKNOWN_REQUEST,
+                message: "unknown request id 5".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Zobrist hashing of boards, for keying visited sets on boards too large to pack.
+//!
+//! A board's Zobrist hash is the XOR of one random value per (cell, tile) pair on it.
+//! A move only changes two cells, so a board carrying a table updates its hash with
+//! four XORs in `Board::move_blank_tile` instead of rehashing every tile. Different
+//! boards can share a hash, with probability about 2^-64 per pair of boards.
+
+#[cfg(alloc_only)]
+use alloc::vec::Vec;
+use core::fmt;
+
+use rand::RngCore;
+
+use super::rng::RngSource;
+use super::tile::Tile;
+
+/// Seed of the table used when no seed is given
+pub const DEFAULT_ZOBRIST_SEED: u64 = 0x5A0B_8157;
+
+/// Random values for each (cell, tile) pair of a board size.
+///
+/// # Attributes
+/// * `n` - Size of the boards hashed
+/// * `seed` - Seed the values were drawn with
+/// * `values` - Value of tile slot s in cell c at index c * n^2 + s, where the blank
+///   uses slot 0 and tile k slot k
+#[derive(Clone, PartialEq, Eq)]
+pub struct ZobristTable {
+    n: i32,
+    seed: u64,
+    values: Vec<u64>,
+}
+
+impl ZobristTable {
+    /// Draw the values of a table.
+    /// * Equal seeds give equal tables on every platform.
+    ///
+    /// # Parameters
+    /// * `n` - Size of the boards to hash
+    /// * `seed` - Seed of the random number generator
+    ///
+    /// # Returns
+    /// The table
+    pub fn new(n: i32, seed: u64) -> ZobristTable {
+        let cells = (n.max(0) * n.max(0)) as usize;
+        let mut rng = RngSource::seeded(seed);
+        let values = (0..cells * cells).map(|_| rng.next_u64()).collect();
+        ZobristTable { n, seed, values }
+    }
+
+    /// Size of the boards the table hashes.
+    pub fn size(&self) -> i32 {
+        self.n
+    }
+
+    /// Seed the table was drawn with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Get the value of a tile in a cell.
+    ///
+    /// # Parameters
+    /// * `cell` - Row-major index of the cell
+    /// * `tile` - Tile of the board (the blank or 1 to n^2 - 1)
+    ///
+    /// # Returns
+    /// The random value XORed into hashes of boards with the tile in the cell
+    pub fn value(&self, cell: usize, tile: Tile) -> u64 {
+        let slot = if tile.is_blank() { 0 } else { tile.symbol() as usize };
+        self.values[cell * (self.n * self.n) as usize + slot]
+    }
+
+    /// Hash tiles from scratch.
+    ///
+    /// # Parameters
+    /// * `tiles` - Tiles of a board of the table's size
+    ///
+    /// # Returns
+    /// XOR of the values of every tile in its cell
+    pub fn hash(&self, tiles: &[Tile]) -> u64 {
+        tiles.iter().enumerate().fold(0, |hash, (cell, &tile)| hash ^ self.value(cell, tile))
+    }
+}
+
+impl fmt::Debug for ZobristTable {
+    /// Write the size and seed, leaving out the values.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ZobristTable").field("n", &self.n).field("seed", &self.seed).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use std::sync::Arc;
+
+    /// Test the hash updated by each move equals the hash recomputed from scratch
+    #[test]
+    fn test_incremental_hash() {
+        for n in 2..=6 {
+            let table = Arc::new(ZobristTable::new(n, 417));
+            let mut board = Board::new(n, None, 0, None).with_zobrist(table.clone());
+            assert_eq!(board.zobrist_hash(), Some(table.hash(board.tiles())));
+            let mut rng = RngSource::seeded(417 + n as u64);
+            for _ in 0..5000 {
+                let moves = board.legal_moves();
+                board.move_blank_tile(moves[rng.next_u64() as usize % moves.len()]);
+                assert_eq!(board.zobrist_hash(), Some(table.hash(board.tiles())));
+            }
+            let neighbor = board.neighbor(board.legal_moves()[0]);
+            assert_eq!(neighbor.zobrist_hash(), Some(table.hash(neighbor.tiles())));
+        }
+        assert_eq!(ZobristTable::new(3, 1), ZobristTable::new(3, 1));
+        assert_ne!(ZobristTable::new(3, 1).hash(Board::new(3, None, 0, None).tiles()), 0);
+        assert_eq!(Board::new(3, None, 0, None).zobrist_hash(), None);
+    }
+}